@@ -179,6 +179,15 @@ class Adafruit_RA8875_R : public Adafruit_RA8875 {
         void X11OptionsEngageNow (bool fullscreen) {
             (void)(fullscreen);
         }
+        void setAAQuality (int q) {
+            (void)(q);
+        }
+        void setX11Placement (int x, int y, bool on_top, bool borderless) {
+            (void)(x);
+            (void)(y);
+            (void)(on_top);
+            (void)(borderless);
+        }
         void getScreenSize (int *w, int *h) {
             *w = 800;
             *h = 480;
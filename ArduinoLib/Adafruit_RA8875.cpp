@@ -142,6 +142,9 @@ Adafruit_RA8875::Adafruit_RA8875(uint8_t CS, uint8_t RST)
         // init the protected region flag
         pr_draw = false;
 
+        // no drawing yet
+        n_draws = n_frames = 0;
+
         // insure earth map pointers are NULL until set
         DEARTH_BIG = NULL;
         NEARTH_BIG = NULL;
@@ -151,6 +154,52 @@ Adafruit_RA8875::Adafruit_RA8875(uint8_t CS, uint8_t RST)
 
         // reset until known
         screen_w = screen_h = 0;
+
+        // no anti-aliasing until asked
+        aa_quality = 1;
+
+        // drawing is opaque until asked
+        draw_opacity = 1;
+
+        // let window manager place window until asked
+        place_x = place_y = -1;
+        place_on_top = place_borderless = false;
+
+        // upright until asked, no staging area until begin()
+        rotation = 0;
+        fb_stage = NULL;
+}
+
+/* set rotation, 0 for upright or 2 for 180 degrees, and arrange for a complete refresh.
+ * N.B. 90 and 270 degrees (1 and 3) are not supported: the stage, window and mouse mapping all assume a
+ *      landscape FB_XRES x FB_YRES image and there is no portrait layout, so they are logged and ignored.
+ */
+void Adafruit_RA8875::setRotation (int r)
+{
+        if (r != 0 && r != 2) {
+            printf ("Display: rotation %d is not supported, only 0 or 2 (180 degrees), staying upright\n", r);
+            r = 0;
+        }
+
+        if (!fb_stage) {
+            rotation = r;
+            return;
+        }
+
+        pthread_mutex_lock (&fb_lock);
+            rotation = r;
+            memset (fb_stage, 1, fb_nbytes);    // unlikely color
+            damageAll();
+        pthread_mutex_unlock (&fb_lock);
+}
+
+/* return index into fb_stage of the given canvas pixel, allowing for rotation
+ */
+int Adafruit_RA8875::stageIndex (int x, int y)
+{
+        if (rotation == 2)
+            return ((FB_YRES-1-y)*FB_XRES + FB_XRES-1-x);
+        return (y*FB_XRES + x);
 }
 
 void Adafruit_RA8875::setEarthPix (char *day_pixels, char *night_pixels)
@@ -177,7 +226,9 @@ bool Adafruit_RA8875::begin (int not_used)
 	}
 	Screen *screen = XDefaultScreenOfDisplay (display);
         int screen_num = XScreenNumberOfScreen(screen);
-        Window root = RootWindow(display,screen_num);
+        root = RootWindow(display,screen_num);
+        root_w = DisplayWidth(display, screen_num);
+        root_h = DisplayHeight(display, screen_num);
 	unsigned long black_pixel = BlackPixelOfScreen (screen);
 
 	// require TrueColor visual so we can use fb_canvas directly in img but try various depths
@@ -229,6 +280,7 @@ bool Adafruit_RA8875::begin (int not_used)
 	    exit(1);
 	}
 	memset (fb_stage, 1, fb_nbytes);        // unlikely color
+        damageAll();
 
 	// create XImage using staging area
 	img = XCreateImage(display, visual, visdepth, ZPixmap, 0, (char*)fb_stage, FB_XRES, FB_YRES,
@@ -239,8 +291,9 @@ bool Adafruit_RA8875::begin (int not_used)
 	wa.bit_gravity = NorthWestGravity;
 	wa.background_pixel = black_pixel;
 	unsigned long value_mask = CWBitGravity | CWBackPixel;
-        win = XCreateWindow(display, root, 0, 0, fb_si.xres, fb_si.yres, 0, visdepth, InputOutput,
-                visual, value_mask, &wa);
+        bool placed = place_x >= 0 && place_y >= 0;
+        win = XCreateWindow(display, root, placed ? place_x : 0, placed ? place_y : 0,
+                fb_si.xres, fb_si.yres, 0, visdepth, InputOutput, visual, value_mask, &wa);
 
 	// create a black GC for this visual
 	XGCValues gcv;
@@ -260,9 +313,36 @@ bool Adafruit_RA8875::begin (int not_used)
         win_size_hints->base_height = FB_YRES;
         win_size_hints->min_width = FB_XRES;
         win_size_hints->min_height = FB_YRES;
+        if (placed) {
+            // user-specified position takes precedence over window manager placement policy
+            win_size_hints->flags |= USPosition;
+            win_size_hints->x = place_x;
+            win_size_hints->y = place_y;
+        }
         XSetWMNormalHints(display, win, win_size_hints);
         XFree(win_size_hints);
 
+        // ask to stay on top of other windows, must be set before mapping
+        if (place_on_top) {
+            Atom wm_state = XInternAtom(display, "_NET_WM_STATE", False);
+            Atom above = XInternAtom(display, "_NET_WM_STATE_ABOVE", False);
+            XChangeProperty (display, win, wm_state, XA_ATOM, 32, PropModeReplace, (unsigned char *)&above, 1);
+        }
+
+        // ask for no title bar or borders using the widely supported Motif hints
+        if (place_borderless) {
+            struct {
+                unsigned long flags, functions, decorations;
+                long input_mode;
+                unsigned long status;
+            } mwm_hints = {2, 0, 0, 0, 0};                      // flags 2 means just decorations, all off
+            Atom mwm = XInternAtom(display, "_MOTIF_WM_HINTS", False);
+            XChangeProperty (display, win, mwm, mwm, 32, PropModeReplace, (unsigned char *)&mwm_hints, 5);
+        }
+
+        // watch root window size to learn when monitors come and go
+        XSelectInput (display, root, StructureNotifyMask);
+
 	// set titles
         XTextProperty window_name_property;
         XTextProperty icon_name_property;
@@ -411,6 +491,7 @@ bool Adafruit_RA8875::begin (int not_used)
 	    exit(1);
 	}
 	memset (fb_stage, 1, fb_nbytes);        // unlikely color
+        damageAll();
 
 	// set up a reentrantable lock
 	pthread_mutexattr_t fb_attr;
@@ -443,6 +524,26 @@ bool Adafruit_RA8875::begin (int not_used)
 #endif // _USE_FB0
 }
 
+/* set X11 window position in root window coordinates, or -1 to let window manager choose,
+ * whether to keep the window above all others and whether to omit window manager decorations.
+ * must be called before begin() to have any effect. not used with _USE_FB0.
+ */
+void Adafruit_RA8875::setX11Placement (int x, int y, bool on_top, bool borderless)
+{
+        place_x = x;
+        place_y = y;
+        place_on_top = on_top;
+        place_borderless = borderless;
+}
+
+/* set anti-aliasing quality, silently forced to 1, 2 or 4.
+ * this affects plotLine(), thick lines and text, not circles or other shapes.
+ */
+void Adafruit_RA8875::setAAQuality (int q)
+{
+        aa_quality = q >= 4 ? 4 : (q >= 2 ? 2 : 1);
+}
+
 bool Adafruit_RA8875::displayReady()
 {
         return (ready);
@@ -583,7 +684,7 @@ uint16_t Adafruit_RA8875::readData(void)
 {
 	// return the next pixel from RA8875; app is expecting full res.
 
-	fbpix_t fbpix = fb_stage[read_y*FB_XRES + read_x];
+	fbpix_t fbpix = fb_stage[stageIndex (read_x, read_y)];
 	uint16_t p16 = FBPIXTORGB16(fbpix);
 	if (read_msb) {
 	    read_msb = false;
@@ -736,8 +837,7 @@ void Adafruit_RA8875::touchRead (uint16_t *x, uint16_t *y)
 {
 	// mouse is in fb_si coords return in app coords
 	pthread_mutex_lock(&mouse_lock);
-	    *x = (mouse_x-FB_X0)/SCALESZ;
-	    *y = (mouse_y-FB_Y0)/SCALESZ;
+	    mouseToApp (x, y);
 
             // printf ("touchRead D %d U %d -> ", mouse_downs, mouse_ups);
 
@@ -759,6 +859,21 @@ void Adafruit_RA8875::touchRead (uint16_t *x, uint16_t *y)
 	pthread_mutex_unlock(&mouse_lock);
 }
 
+/* convert mouse_x and mouse_y to app coords, allowing for rotation.
+ * N.B. caller must hold mouse_lock
+ */
+void Adafruit_RA8875::mouseToApp (uint16_t *x, uint16_t *y)
+{
+        int fx = mouse_x-FB_X0;
+        int fy = mouse_y-FB_Y0;
+        if (rotation == 2) {
+            fx = FB_XRES-1-fx;
+            fy = FB_YRES-1-fy;
+        }
+        *x = fx/SCALESZ;
+        *y = fy/SCALESZ;
+}
+
 /* get mouse location in app coords.
  * return whether currently within app and cursor visible.
  */
@@ -767,10 +882,8 @@ bool Adafruit_RA8875::getMouse (uint16_t *x, uint16_t *y)
 	pthread_mutex_lock(&mouse_lock);
 
             bool ok = mouse_idle <= MOUSE_FADE && mouse_x >= 0;
-            if (ok) {
-                *x = (mouse_x-FB_X0)/SCALESZ;
-                *y = (mouse_y-FB_Y0)/SCALESZ;
-            }
+            if (ok)
+                mouseToApp (x, y);
 
 	pthread_mutex_unlock(&mouse_lock);
 
@@ -794,6 +907,7 @@ void Adafruit_RA8875::drawPixel(int16_t x, int16_t y, uint16_t color16)
 			plotfb (x+dx, y+dy, fbpix);
 	    }
 	    fb_dirty = true;
+	    n_draws++;
 	pthread_mutex_unlock (&fb_lock);
 }
 
@@ -811,6 +925,7 @@ void Adafruit_RA8875::drawSubPixel(int16_t x, int16_t y, uint16_t color16)
 	pthread_mutex_lock(&fb_lock);
 	    plotfb (x, y, fbpix);
 	    fb_dirty = true;
+	    n_draws++;
 	pthread_mutex_unlock (&fb_lock);
 }
 
@@ -826,6 +941,7 @@ void Adafruit_RA8875::drawLine(int16_t x0, int16_t y0, int16_t x1, int16_t y1, u
 	pthread_mutex_lock(&fb_lock);
 	    plotLine (x0, y0, x1, y1, fbpix);
 	    fb_dirty = true;
+	    n_draws++;
 	pthread_mutex_unlock (&fb_lock);
 }
 
@@ -844,7 +960,16 @@ uint16_t color16)
                 plotLine (x0, y0, x1, y1, fbpix);
             else
                 drawThickLine (x0, y0, x1, y1, thickness, fbpix);
+            if (thickness > 1 && aa_quality > 1 && x0 != x1 && y0 != y1) {
+                // soften each long edge with an anti-aliased line just outside the solid line
+                float dx = x1 - x0, dy = y1 - y0;
+                float len = sqrtf (dx*dx + dy*dy);
+                float ox = -dy*thickness/(2*len), oy = dx*thickness/(2*len);
+                plotLineAA (x0+ox, y0+oy, x1+ox, y1+oy, fbpix);
+                plotLineAA (x0-ox, y0-oy, x1-ox, y1-oy, fbpix);
+            }
 	    fb_dirty = true;
+	    n_draws++;
 	pthread_mutex_unlock (&fb_lock);
 }
 
@@ -869,6 +994,7 @@ void Adafruit_RA8875::drawRect(int16_t x0, int16_t y0, int16_t w, int16_t h, uin
 	    plotLine (x0+w, y0+h, x0, y0+h, fbpix);
 	    plotLine (x0, y0+h, x0, y0, fbpix);
 	    fb_dirty = true;
+	    n_draws++;
 	pthread_mutex_unlock (&fb_lock);
 }
 
@@ -890,6 +1016,7 @@ void Adafruit_RA8875::fillRect(int16_t x0, int16_t y0, int16_t w, int16_t h, uin
 		for (uint16_t x = x0; x < x0+w; x++)
 		    plotfb (x, y, fbpix);
 	    fb_dirty = true;
+	    n_draws++;
 	pthread_mutex_unlock (&fb_lock);
 }
 
@@ -915,6 +1042,7 @@ void Adafruit_RA8875::drawCircle(int16_t x0, int16_t y0, int16_t r0, uint16_t co
                 }
             }
 	    fb_dirty = true;
+	    n_draws++;
 	pthread_mutex_unlock (&fb_lock);
 
 }
@@ -940,6 +1068,7 @@ void Adafruit_RA8875::fillCircle(int16_t x0, int16_t y0, int16_t r0, uint16_t co
                 }
             }
 	    fb_dirty = true;
+	    n_draws++;
 	pthread_mutex_unlock (&fb_lock);
 }
 
@@ -958,6 +1087,7 @@ void Adafruit_RA8875::drawTriangle(int16_t x0, int16_t y0, int16_t x1, int16_t y
 	    plotLine (x1, y1, x2, y2, fbpix);
 	    plotLine (x2, y2, x0, y0, fbpix);
 	    fb_dirty = true;
+	    n_draws++;
 	pthread_mutex_unlock (&fb_lock);
 }
 
@@ -985,6 +1115,7 @@ void Adafruit_RA8875::fillTriangle(int16_t x0, int16_t y0, int16_t x1, int16_t y
 		plotLine (xleft, y, xrite, y, fbpix);
 	    }
 	    fb_dirty = true;
+	    n_draws++;
 	pthread_mutex_unlock (&fb_lock);
 }
 
@@ -1309,22 +1440,145 @@ void Adafruit_RA8875::plotLineRaw(int16_t x0, int16_t y0, int16_t x1, int16_t y1
 }
 
 
+/* plot line using Xiaolin Wu's algorithm, blending each pixel with the canvas by its coverage.
+ * coverage is quantized to aa_quality levels, just as that many samples per pixel would.
+ * black lines are typically used to erase so they cover every pixel touched at full strength.
+ * https://en.wikipedia.org/wiki/Xiaolin_Wu%27s_line_algorithm
+ */
+void Adafruit_RA8875::plotLineAA(float x0, float y0, float x1, float y1, fbpix_t color)
+{
+        bool steep = fabsf(y1 - y0) > fabsf(x1 - x0);
+        if (steep) {
+            float t;
+            t = x0; x0 = y0; y0 = t;
+            t = x1; x1 = y1; y1 = t;
+        }
+        if (x0 > x1) {
+            float t;
+            t = x0; x0 = x1; x1 = t;
+            t = y0; y0 = y1; y1 = t;
+        }
+
+        float dx = x1 - x0;
+        float gradient = dx == 0 ? 1 : (y1 - y0)/dx;
+        int16_t xstart = roundf (x0);
+        int16_t xend = roundf (x1);
+
+        for (int16_t x = xstart; x <= xend; x++) {
+            float y = y0 + gradient*(x - x0);
+            int16_t iy = floorf (y);
+            float f = y - iy;
+            if (steep) {
+                blendfb (iy, x, color, 1 - f);
+                blendfb (iy+1, x, color, f);
+            } else {
+                blendfb (x, iy, color, 1 - f);
+                blendfb (x, iy+1, color, f);
+            }
+        }
+}
+
+/* plot line using the anti-aliased algorithm if engaged, but axis-aligned lines are already perfect
+ */
 void Adafruit_RA8875::plotLine(int16_t x0, int16_t y0, int16_t x1, int16_t y1, fbpix_t color)
 {
-        plotLineRaw (x0, y0, x1, y1, color);
+        if (aa_quality > 1 && x0 != x1 && y0 != y1)
+            plotLineAA (x0, y0, x1, y1, color);
+        else
+            plotLineRaw (x0, y0, x1, y1, color);
 }
 
 void Adafruit_RA8875::plotfb (int16_t x, int16_t y, fbpix_t color)
 {
+        if (draw_opacity < 1) {
+            // blend with existing pixel, N.B. blendfb() comes through here too so opacities combine
+            uint16_t new16 = FBPIXTORGB16(color);
+            uint16_t old16 = FBPIXTORGB16(fb_canvas[y*FB_XRES + x]);
+            float unopacity = 1 - draw_opacity;
+            uint8_t r = draw_opacity*RGB565_R(new16) + unopacity*RGB565_R(old16);
+            uint8_t g = draw_opacity*RGB565_G(new16) + unopacity*RGB565_G(old16);
+            uint8_t b = draw_opacity*RGB565_B(new16) + unopacity*RGB565_B(old16);
+            color = RGB16TOFBPIX(RGB565(r,g,b));
+        }
         fb_canvas[y*FB_XRES + x] = color;
+
+        // grow damage box
+        if (x < dmg_x0)
+            dmg_x0 = x;
+        if (x > dmg_x1)
+            dmg_x1 = x;
+        if (y < dmg_y0)
+            dmg_y0 = y;
+        if (y > dmg_y1)
+            dmg_y1 = y;
+}
+
+/* mark the entire canvas as changed so the next drawCanvas() examines every pixel.
+ * N.B. caller must hold fb_lock once the display threads are running
+ */
+void Adafruit_RA8875::damageAll()
+{
+        dmg_x0 = 0;
+        dmg_y0 = 0;
+        dmg_x1 = FB_XRES-1;
+        dmg_y1 = FB_YRES-1;
+}
+
+/* mark the canvas as unchanged since the last drawCanvas().
+ */
+void Adafruit_RA8875::damageNone()
+{
+        dmg_x0 = FB_XRES;
+        dmg_y0 = FB_YRES;
+        dmg_x1 = -1;
+        dmg_y1 = -1;
+}
+
+/* set opacity of subsequent drawing, 0 .. 1
+ */
+void Adafruit_RA8875::setDrawOpacity (float opacity)
+{
+        draw_opacity = opacity < 0 ? 0 : (opacity > 1 ? 1 : opacity);
+}
+
+/* return opacity of subsequent drawing, 0 .. 1
+ */
+float Adafruit_RA8875::getDrawOpacity ()
+{
+        return (draw_opacity);
+}
+
+/* blend color into canvas at x,y by the given coverage 0 .. 1.
+ */
+void Adafruit_RA8875::blendfb (int16_t x, int16_t y, fbpix_t color, float cover)
+{
+        if (x < 0 || x >= FB_XRES || y < 0 || y >= FB_YRES)
+            return;
+
+        cover = roundf (cover*aa_quality)/aa_quality;
+        if (cover <= 0)
+            return;
+        if (cover >= 1 || color == 0) {
+            plotfb (x, y, color);
+            return;
+        }
+
+        uint16_t new16 = FBPIXTORGB16(color);
+        uint16_t old16 = FBPIXTORGB16(fb_canvas[y*FB_XRES + x]);
+        float uncover = 1 - cover;
+        uint8_t r = cover*RGB565_R(new16) + uncover*RGB565_R(old16);
+        uint8_t g = cover*RGB565_G(new16) + uncover*RGB565_G(old16);
+        uint8_t b = cover*RGB565_B(new16) + uncover*RGB565_B(old16);
+        plotfb (x, y, RGB16TOFBPIX(RGB565(r,g,b)));
 }
 
 /* plot hi res earth lat0,lng0 at app's screen location x0,y0.
  * we interpolate this to SCALESZxSCALESZ, knowing dlat and dlng going one full step right and down.
- * frac_day is 1 for all DEARTH, 0 for all NEARTH else blend
+ * fract_day is 1 for all DEARTH, 0 for all NEARTH else blend; it too is interpolated to each subpixel
+ * knowing its change going one full step right and down so the terminator has no steps.
  */
 void Adafruit_RA8875::plotEarth (uint16_t x0, uint16_t y0, float lat0, float lng0,
-float dlatr, float dlngr, float dlatd, float dlngd, float fract_day)
+float dlatr, float dlngr, float dlatd, float dlngd, float fract_day0, float dfdayr, float dfdayd)
 {
         // beware of no map files
         if (!DEARTH_BIG || !NEARTH_BIG)
@@ -1341,6 +1595,8 @@ float dlatr, float dlngr, float dlatd, float dlngd, float fract_day)
         dlngr /= SCALESZ;
         dlatd /= SCALESZ;
         dlngd /= SCALESZ;
+        dfdayr /= SCALESZ;
+        dfdayd /= SCALESZ;
 
         // ditto starting loc
 	x0 *= SCALESZ;
@@ -1355,6 +1611,9 @@ float dlatr, float dlngr, float dlatd, float dlngd, float fract_day)
                 int ey = (int)((90-lat)*EARTH_BIG_H/180 + EARTH_BIG_H + 0.5F);
                 ex = (ex + EARTH_BIG_W) % EARTH_BIG_W;
                 ey = (ey + EARTH_BIG_H) % EARTH_BIG_H;
+                float fract_day = fract_day0 + dfdayr*c + dfdayd*r;
+                if (fract_day < 0) fract_day = 0;
+                if (fract_day > 1) fract_day = 1;
 		uint16_t c16; 
 		if (fract_day == 0) {
 		    c16 = (*NEARTH_BIG)[ey][ex];
@@ -1381,6 +1640,41 @@ float dlatr, float dlngr, float dlatd, float dlngd, float fract_day)
 	}
 }
 
+/* return whether the given glyph pixel is set, pixels outside the glyph are not.
+ */
+static bool glyphBit (const GFXglyph *gp, const uint8_t *bp, int c, int r)
+{
+	if (c < 0 || c >= gp->width || r < 0 || r >= gp->height)
+	    return (false);
+	int bitn = r*gp->width + c;
+	return ((bp[bitn/8] & (1 << (7-(bitn%8)))) != 0);
+}
+
+/* return the fraction of the given unset glyph pixel covered by the smoothed glyph outline.
+ * the outline is where the bilinear blend of neighboring pixel centers crosses one half, sampled
+ * aa_quality x aa_quality times. this fills the inside corners of stair steps along sloped and curved
+ * strokes but leaves straight edges, and the set pixels, exactly as designed.
+ */
+float Adafruit_RA8875::glyphCover (const GFXglyph *gp, const uint8_t *bp, int c, int r)
+{
+	int n_in = 0;
+	for (int sy = 0; sy < aa_quality; sy++) {
+	    float fy = (sy + 0.5F)/aa_quality - 0.5F;
+	    int r1 = fy < 0 ? r - 1 : r + 1;
+	    fy = fabsf (fy);
+	    for (int sx = 0; sx < aa_quality; sx++) {
+		float fx = (sx + 0.5F)/aa_quality - 0.5F;
+		int c1 = fx < 0 ? c - 1 : c + 1;
+		fx = fabsf (fx);
+		float v = fx*(1-fy)*glyphBit (gp, bp, c1, r) + (1-fx)*fy*glyphBit (gp, bp, c, r1)
+				+ fx*fy*glyphBit (gp, bp, c1, r1);
+		if (v >= 0.5F)
+		    n_in++;
+	    }
+	}
+	return ((float)n_in/(aa_quality*aa_quality));
+}
+
 void Adafruit_RA8875::plotChar (char ch)
 {
 	if (ch < current_font->first || ch > current_font->last)
@@ -1389,17 +1683,17 @@ void Adafruit_RA8875::plotChar (char ch)
 	uint8_t *bp = &current_font->bitmap[gp->bitmapOffset];
 	int16_t x = cursor_x + gp->xOffset;
 	int16_t y = cursor_y + gp->yOffset;
-	uint16_t bitn = 0;
 	pthread_mutex_lock (&fb_lock);
 	    for (uint16_t r = 0; r < gp->height; r++) {
 		for (uint16_t c = 0; c < gp->width; c++) {
-		    uint8_t bit = bp[bitn/8] & (1 << (7-(bitn%8)));
-		    if (bit)
+		    if (glyphBit (gp, bp, c, r))
 			plotfb (x+c, y+r, text_color);
-		    bitn++;
+		    else if (aa_quality > 1)
+			blendfb (x+c, y+r, text_color, glyphCover (gp, bp, c, r));
 		}
 	    }
 	    fb_dirty = true;
+	    n_draws++;
 	pthread_mutex_unlock (&fb_lock);
 
 	cursor_x += gp->xAdvance;
@@ -1430,6 +1724,15 @@ void Adafruit_RA8875::drawPR(void)
 }
 
 
+/* pass back the number of drawing operations and frames sent to the display so far
+ */
+void Adafruit_RA8875::getRenderStats (uint32_t *draws, uint32_t *frames)
+{
+        *draws = n_draws;
+        *frames = n_frames;
+}
+
+
 /* return a typed character, else 0
  */
 char Adafruit_RA8875::getChar()
@@ -1480,38 +1783,50 @@ void Adafruit_RA8875::drawCanvas()
         // bounding box
         int bb_x0 = 0, bb_y0 = 0, bb_x1 = 0, bb_y1 = 0;
 
-        for (int y = 0; y < FB_YRES; y++) {
+        // only pixels within the damage box can differ unless drawing the protected region too
+        int scan_x0 = 0, scan_y0 = 0, scan_x1 = FB_XRES-1, scan_y1 = FB_YRES-1;
+        if (!pr_draw) {
+            scan_x0 = dmg_x0;
+            scan_y0 = dmg_y0;
+            scan_x1 = dmg_x1;
+            scan_y1 = dmg_y1;
+        }
+
+        for (int y = scan_y0; y <= scan_y1; y++) {
 
             // we assume protected region is at lower right
             int max_x = pr_draw || pr_w == 0 || y < pr_y ? FB_XRES : pr_x;
 
-            // handy start of this row
-            fbpix_t *stage_p = &fb_stage[y*FB_XRES];
+            // handy start of this row, stage row is reversed from the far corner when rotated
+            int sy = rotation == 2 ? FB_YRES-1-y : y;
+            fbpix_t *stage_p = &fb_stage[sy*FB_XRES];
             fbpix_t *canvas_p = &fb_canvas[y*FB_XRES];
 
-            for (int x = 0; x < FB_XRES; x++) {
+            for (int x = scan_x0; x <= scan_x1; x++) {
 
                 int max_y = pr_draw || pr_h == 0 || x < pr_x ? FB_YRES : pr_y;
                 if (x >= max_x && y >= max_y)
                     continue;
 
-                if (stage_p[x] != canvas_p[x]) {
+                int sx = rotation == 2 ? FB_XRES-1-x : x;
+                if (stage_p[sx] != canvas_p[x]) {
 
                     // update pixel
-                    stage_p[x] = canvas_p[x];
+                    stage_p[sx] = canvas_p[x];
 
-                    // update bounding box
+                    // update bounding box, in stage coords
                     if (!any_change) {
-                        bb_x0 = bb_x1 = x;
-                        bb_y0 = bb_y1 = y;
+                        bb_x0 = bb_x1 = sx;
+                        bb_y0 = bb_y1 = sy;
                     }
-                    if (x < bb_x0)
-                        bb_x0 = x;
-                    if (x > bb_x1)
-                        bb_x1 = x;
-                    if (y > bb_y1)
-                        bb_y1 = y;
-                    // y can't get any smaller
+                    if (sx < bb_x0)
+                        bb_x0 = sx;
+                    if (sx > bb_x1)
+                        bb_x1 = sx;
+                    if (sy < bb_y0)
+                        bb_y0 = sy;
+                    if (sy > bb_y1)
+                        bb_y1 = sy;
 
                     // found something to do
                     any_change = true;
@@ -1533,7 +1848,33 @@ void Adafruit_RA8875::drawCanvas()
 
             // let server catch up before next loop
             XSync (display, false);
+
+            n_frames++;
+        }
+
+        damageNone();
+}
+
+/* root window changed size, typically because a monitor was added or removed, so put our window back
+ * at its requested position if that is still on screen, else the origin so it remains visible.
+ */
+// _USE_X11
+void Adafruit_RA8875::placeWindow()
+{
+        if (place_x < 0 || place_y < 0)
+            return;
+
+        int x = place_x, y = place_y;
+        if (x + FB_XRES > root_w || y + FB_YRES > root_h) {
+            printf ("X11: %dx%d+%d+%d no longer fits in %dx%d, moving to origin\n", FB_XRES, FB_YRES,
+                                        place_x, place_y, root_w, root_h);
+            x = y = 0;
         }
+        XMoveWindow (display, win, x, y);
+
+        // full screen applies to whichever monitor now holds the window
+        if (options_fullscreen)
+            options_engage = true;
 }
 
 // _USE_X11
@@ -1761,6 +2102,16 @@ void Adafruit_RA8875::fbThread ()
 		    break;
 
 		case ConfigureNotify:
+                    if (event.xconfigure.window == root) {
+                        // monitor hot-plug changes root size
+                        if (event.xconfigure.width != root_w || event.xconfigure.height != root_h) {
+                            printf ("X11: root now %dx%d\n", event.xconfigure.width, event.xconfigure.height);
+                            root_w = event.xconfigure.width;
+                            root_h = event.xconfigure.height;
+                            placeWindow();
+                        }
+                        break;
+                    }
 		    // printf ("ConfigureNotify: %dx%d+%d+%d\n", event.xconfigure.width, event.xconfigure.height, event.xconfigure.x, event.xconfigure.y);
 		    fb_si.xres = event.xconfigure.width;
 		    fb_si.yres = event.xconfigure.height;
@@ -1772,7 +2123,10 @@ void Adafruit_RA8875::fbThread ()
 		    XFillRectangle (display, win, black_gc, FB_X0 + FB_XRES, FB_Y0, FB_X0+1, FB_YRES);
 		    XFillRectangle (display, win, black_gc, 0, FB_Y0 + FB_YRES, fb_si.xres, FB_Y0+1);
                     // invalidate staging area to get a full refresh
-                    memset (fb_stage, ~0, fb_nbytes);
+                    pthread_mutex_lock (&fb_lock);
+                        memset (fb_stage, ~0, fb_nbytes);
+                        damageAll();
+                    pthread_mutex_unlock (&fb_lock);
 		    break;
 		}
 	    }
@@ -2164,25 +2518,40 @@ void Adafruit_RA8875::drawCanvas()
         // put only the unproteced region unless pr_draw is set
         if (pr_draw) {
             // draw everything
-            memcpy (fb_stage, fb_canvas, fb_nbytes);
+            for (int y = 0; y < FB_YRES; y++)
+                stageRow (y, 0, FB_XRES);
         } else {
-            // draw only around the protected area
-            uint16_t bw = FB_XRES*BYTESPFBPIX;                                  // bytes wide
+            // draw only damaged rows around the protected area
             uint16_t pr_r = pr_x + pr_w;                                        // right of PR
             uint16_t pr_b = pr_y + pr_h;                                        // bottom of PR
-            fbpix_t *s_row = fb_stage;                                          // next stage row
-            fbpix_t *c_row = fb_canvas;                                         // next canvas row
-            for (uint16_t y = 0; y < FB_YRES; y++, c_row += FB_XRES, s_row += FB_XRES) {
+            for (int y = dmg_y0; y <= dmg_y1; y++) {
                 if (y < pr_y) {
-                    memcpy (s_row, c_row, bw);                                  // above
+                    stageRow (y, 0, FB_XRES);                                   // above
                 } else if (y < pr_b) {
-                    memcpy (s_row, c_row, pr_x*BYTESPFBPIX);                    // left
-                    memcpy (s_row+pr_r, c_row+pr_r, (FB_XRES-pr_r)*BYTESPFBPIX);// right
+                    stageRow (y, 0, pr_x);                                      // left
+                    stageRow (y, pr_r, FB_XRES);                                // right
                 } else {
-                    memcpy (s_row, c_row, bw);                                  // below
+                    stageRow (y, 0, FB_XRES);                                   // below
                 }
             }
         }
+
+        damageNone();
+        n_frames++;
+}
+
+/* copy canvas row y columns [x0,x1) to fb_stage, allowing for rotation
+ */
+// _USE_FB0
+void Adafruit_RA8875::stageRow (int y, int x0, int x1)
+{
+        fbpix_t *c_row = &fb_canvas[y*FB_XRES];
+        if (rotation == 2) {
+            fbpix_t *s_end = &fb_stage[(FB_YRES-1-y)*FB_XRES + FB_XRES-1];     // stage pixel of x == 0
+            for (int x = x0; x < x1; x++)
+                *(s_end - x) = c_row[x];
+        } else
+            memcpy (&fb_stage[y*FB_XRES + x0], c_row + x0, (x1-x0)*BYTESPFBPIX);
 }
 
 /* thread that runs forever to update display buffer whenever fb_canvas changes
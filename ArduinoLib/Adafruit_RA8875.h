@@ -15,6 +15,7 @@
 #include <sys/time.h>
 #include <X11/Xlib.h>
 #include <X11/Xutil.h>
+#include <X11/Xatom.h>
 
 // simplest to just recreate the same fb structure
 struct fb_var_screeninfo {
@@ -106,10 +107,7 @@ class Adafruit_RA8875 {
 	{
 	}
 
-	void setRotation (int r)
-	{
-	    rotation = r;
-	}
+	void setRotation (int r);
 
 	void textSetCursor(uint16_t x, uint16_t y)
 	{
@@ -164,7 +162,8 @@ class Adafruit_RA8875 {
 
 	// special method to draw hi res earth pixel
 	void plotEarth (uint16_t x0, uint16_t y0, float lat0, float lng0,
-            float dlatr, float dlngr, float dlatd, float dlngd, float fract_day);
+            float dlatr, float dlngr, float dlatd, float dlngd,
+            float fract_day, float dfdayr, float dfdayd);
 
         // methods to implement a protected rectangle drawn only with drawPR()
         void setPR (uint16_t x, uint16_t y, uint16_t w, uint16_t h);
@@ -176,6 +175,10 @@ class Adafruit_RA8875 {
 	// real/app display size
 	int SCALESZ;
 
+        // set opacity 0 .. 1 of subsequent drawing over existing canvas pixels
+        void setDrawOpacity (float opacity);
+        float getDrawOpacity (void);
+
         // get next keyboard character
         char getChar(void);
 
@@ -193,10 +196,22 @@ class Adafruit_RA8875 {
         // use to learn whether display is ready
         bool displayReady(void);
 
+        // set X11 window position, stacking and decorations, must be called before begin()
+        void setX11Placement (int x, int y, bool on_top, bool borderless);
+
+        // set anti-aliasing quality: 1 is off, else 2 or 4 coverage levels per pixel.
+        // sloped lines are smoothed and glyph stair steps are filled; text still starts on whole pixels.
+        void setAAQuality (int q);
+
+        // get counts of drawing operations and frames sent to the display since startup
+        void getRenderStats (uint32_t *draws, uint32_t *frames);
+
     protected:
 
-	// 0: normal 2: 180 degs
+	// 0: normal 2: 180 degs, 90 and 270 are not supported
 	int rotation;
+        int stageIndex (int x, int y);
+        void mouseToApp (uint16_t *x, uint16_t *y);
 
         // set when display is definitely up and running
         volatile bool ready;
@@ -249,6 +264,11 @@ class Adafruit_RA8875 {
         // used by X11OptionsEngageNow
         volatile bool options_engage, options_fullscreen;
 
+        // used to position the window, and to keep it there if the root window changes
+        Window root;
+        int root_w, root_h;
+        void placeWindow(void);
+
 
 #endif // _USE_X11
 
@@ -267,6 +287,7 @@ class Adafruit_RA8875 {
         int kb_fd;
 
         void setCursorIfVis (uint16_t row, uint16_t col, fbpix_t color);
+        void stageRow (int y, int x0, int x1);
 
 	int fb_fd;                      // frame buffer mmap file descriptor
 	fbpix_t *fb_fb;                 // pointer to mmap fb
@@ -288,6 +309,10 @@ class Adafruit_RA8875 {
         // total display size
         volatile int screen_w, screen_h;
 
+        // initial window placement options, see setX11Placement()
+        int place_x, place_y;                   // position, or -1 to let window manager decide
+        bool place_on_top, place_borderless;
+
         /* for drawLineOverlap:
          * Overlap means drawing additional pixel when changing minor direction
          * Needed for drawThickLine, otherwise some pixels will be missing in the thick line
@@ -307,6 +332,12 @@ class Adafruit_RA8875 {
 	pthread_mutex_t fb_lock;
 	struct fb_var_screeninfo fb_si;
 	volatile bool fb_dirty;
+        volatile uint32_t n_draws;      // drawing operations, for getRenderStats()
+        volatile uint32_t n_frames;     // frames sent to display, for getRenderStats()
+        int dmg_x0, dmg_y0;             // canvas bounding box changed since last drawCanvas(), ..
+        int dmg_x1, dmg_y1;             // .. inclusive, empty if x1 < x0. protected by fb_lock
+        void damageAll(void);
+        void damageNone(void);
 	fbpix_t *fb_canvas;             // main drawing image buffer
 	fbpix_t *fb_stage;              // temp image during staging to fb hw
 	int fb_nbytes;                  // bytes in each in-memory image buffer
@@ -316,8 +347,13 @@ class Adafruit_RA8875 {
         void plotLineLow(int16_t x0, int16_t y0, int16_t x1, int16_t y1, fbpix_t color);
         void plotLineHigh(int16_t x0, int16_t y0, int16_t x1, int16_t y1, fbpix_t color);
         void plotLineRaw(int16_t x0, int16_t y0, int16_t x1, int16_t y1, fbpix_t color);
+        void plotLineAA(float x0, float y0, float x1, float y1, fbpix_t color);
+        void blendfb (int16_t x, int16_t y, fbpix_t color, float cover);
+        int aa_quality;
+        float draw_opacity;
 	void plotfb (int16_t x, int16_t y, fbpix_t color);
 	void plotChar (char c);
+	float glyphCover (const GFXglyph *gp, const uint8_t *bp, int c, int r);
 	fbpix_t text_color;
 	uint16_t cursor_x, cursor_y;
 	uint16_t read_x, read_y;
@@ -12,6 +12,7 @@
 #include <sys/resource.h>
 
 #include "Arduino.h"
+#include "WiFiClient.h"
 
 char **our_argv;                // our argv for restarting
 std::string our_dir;            // our storage directory, including trailing /
@@ -155,6 +156,35 @@ static void mkAppDir(const char *user_dir)
         umask(old_um);
 }
 
+// environment variables that may set options for headless installs, such as in a container.
+// an option given on the command line takes precedence over its variable.
+typedef struct {
+        char opt;                       // option letter
+        bool has_arg;                   // whether option takes an argument, else variable is a yes/no flag
+        const char *env;                // environment variable name
+} EnvOption;
+static const EnvOption env_options[] = {
+        { 'a', true,  "HAMCLOCK_AA" },
+        { 'b', true,  "HAMCLOCK_BACKEND" },
+        { 'd', true,  "HAMCLOCK_DIR" },
+        { 'f', true,  "HAMCLOCK_FULLSCREEN" },
+        { 'g', false, "HAMCLOCK_GEOLOCATE" },
+        { 'i', true,  "HAMCLOCK_GEOLOCATE_IP" },
+        { 'k', false, "HAMCLOCK_NOSETUP" },
+        { 'l', true,  "HAMCLOCK_CENTERLNG" },
+        { 'm', false, "HAMCLOCK_DEMO" },
+        { 'n', false, "HAMCLOCK_BORDERLESS" },
+        { 'o', false, "HAMCLOCK_LOG_STDOUT" },
+        { 'p', true,  "HAMCLOCK_POSITION" },
+        { 'q', true,  "HAMCLOCK_ADIF" },
+        { 't', false, "HAMCLOCK_ONTOP" },
+        { 'T', true,  "HAMCLOCK_TIMEOUTS" },
+        { 'w', true,  "HAMCLOCK_WEBPORT" },
+        { 'x', true,  "HAMCLOCK_PROXY" },
+        { 'X', true,  "HAMCLOCK_NOPROXY" },
+};
+#define N_ENV_OPTIONS (sizeof(env_options)/sizeof(env_options[0]))
+
 /* show usage and exit(1)
  */
 static void usage (const char *errfmt, ...)
@@ -175,20 +205,103 @@ static void usage (const char *errfmt, ...)
         fprintf (stderr, "Purpose: display time and other information useful to amateur radio operators\n");
         fprintf (stderr, "Usage: %s [options]\n", me);
         fprintf (stderr, "Options:\n");
+        fprintf (stderr, " -a q : set line and text anti-aliasing quality q to 1 (off), 2 or 4\n");
+        fprintf (stderr, " -A s : send a test alert to the HamClock running on this host then exit; s is kind=K&severity=S\n");
         fprintf (stderr, " -b h : set backend host to h instead of %s\n", svr_host);
         fprintf (stderr, " -d d : set working dir d instead of %s\n", defaultAppDir().c_str());
+        fprintf (stderr, " -e s : print alert history then exit; s is format=csv|json|adif&from=YYYY-MM-DD&to=YYYY-MM-DD&kind=K\n");
         fprintf (stderr, " -f o : display full screen initially \"on\" or \"off\"\n");
         fprintf (stderr, " -g   : init DE using geolocation with our IP; requires -k\n");
         fprintf (stderr, " -i i : init DE using geolocation with IP i; requires -k\n");
         fprintf (stderr, " -k   : don't offer Setup or wait for Skips\n");
         fprintf (stderr, " -l l : set mercator center lng to l degs; requires -k\n");
         fprintf (stderr, " -m   : enable demo mode\n");
+        fprintf (stderr, " -n   : X11 window without title bar or borders\n");
         fprintf (stderr, " -o   : write diagnostic log to stdout instead of in working dir\n");
+        fprintf (stderr, " -p p : X11 window position x,y in desktop pixels, eg, to pin to a second monitor\n");
+        fprintf (stderr, " -q f : follow QSOs appended to ADIF log file f for the QSO_Rate pane\n");
+        fprintf (stderr, " -s f : play the simulation scenario in file f, see simulate.cpp\n");
+        fprintf (stderr, " -S s : soak test for leaks then exit 0 if ok else 1; s is hours[,MB]; requires -k\n");
+        fprintf (stderr, " -t   : keep X11 window on top of all others\n");
+        fprintf (stderr, " -T t : network connect and read timeouts c,r in seconds instead of 5,5\n");
         fprintf (stderr, " -w p : set web server port p instead of %d\n", svr_port);
+        fprintf (stderr, " -x u : connect via HTTP proxy u, eg, http://proxy:3128; default is $http_proxy\n");
+        fprintf (stderr, " -X h : comma list of hosts or domains to reach without proxy; default is $no_proxy\n");
+        fprintf (stderr, "Environment:\n");
+        fprintf (stderr, " Options may also be set with these variables, flags with yes or no; command line wins:\n");
+        for (unsigned i = 0; i < N_ENV_OPTIONS; i++)
+            fprintf (stderr, "  -%c : %s\n", env_options[i].opt, env_options[i].env);
+        fprintf (stderr, " HAMCLOCK_API_TOKEN and HAMCLOCK_WEB_AUTH (user:password) override files api_token and web_auth\n");
+        fprintf (stderr, " Settings may also be set with these variables; they override saved values, see Setup:\n");
+        printEnvSettings (stderr);
+        fprintf (stderr, " Precedence is command line, then environment, then saved settings, then defaults\n");
 
         exit(1);
 }
 
+/* return whether the given command line args include option opt
+ */
+static bool cliHasOption (int ac, char *av[], char opt)
+{
+        while (--ac && **++av == '-') {
+            for (char *s = *av; *++s; ) {
+                if (*s == opt)
+                    return (true);
+                // skip over this option's argument, if any
+                if (strchr ("aAbdefilpqsSTwxX", *s) && ac > 1) {
+                    av++;
+                    ac--;
+                }
+            }
+        }
+        return (false);
+}
+
+/* return a new argv with options from env_options that are not on the command line ahead of av[1..].
+ * N.B. -g and -i are alternatives so either on the command line hides both variables.
+ */
+static char **addEnvArgs (int ac, char *av[], int &new_ac)
+{
+        char **new_av = (char **) malloc ((ac + 2*N_ENV_OPTIONS + 1) * sizeof(char*));
+        if (!new_av) {
+            fprintf (stderr, "No memory for args\n");
+            exit(1);
+        }
+        new_ac = 0;
+        new_av[new_ac++] = av[0];
+
+        for (unsigned i = 0; i < N_ENV_OPTIONS; i++) {
+            const EnvOption &eo = env_options[i];
+            const char *value = getenv (eo.env);
+            if (!value || !*value)
+                continue;
+            if (cliHasOption (ac, av, eo.opt)
+                        || (eo.opt == 'g' && cliHasOption (ac, av, 'i'))
+                        || (eo.opt == 'i' && cliHasOption (ac, av, 'g')))
+                continue;
+            if (!eo.has_arg && strcasecmp (value, "yes") && strcasecmp (value, "true") && strcmp (value, "1"))
+                continue;
+
+            char *flag = (char *) malloc (3);
+            if (!flag) {
+                fprintf (stderr, "No memory for args\n");
+                exit(1);
+            }
+            flag[0] = '-';
+            flag[1] = eo.opt;
+            flag[2] = '\0';
+            new_av[new_ac++] = flag;
+            if (eo.has_arg)
+                new_av[new_ac++] = (char *) value;
+        }
+
+        for (int i = 1; i < ac; i++)
+            new_av[new_ac++] = av[i];
+        new_av[new_ac] = NULL;
+
+        return (new_av);
+}
+
 /* process main's argc/argv -- never returns if any issues
  */
 static void crackArgs (int ac, char *av[])
@@ -197,12 +310,37 @@ static void crackArgs (int ac, char *av[])
         bool full_screen = false;
         bool fs_set = false;
         const char *new_appdir = NULL;
+        const char *sim_fn = NULL;
+        const char *soak_spec = NULL;
+        const char *export_spec = NULL;
+        const char *test_spec = NULL;
         bool cl_set = false;
+        int aa_q = 0;
+        int win_x = -1, win_y = -1;
+        bool on_top = false;
+        bool borderless = false;
+        int connect_to = 0, read_to = 0;
+        const char *proxy = NULL;
+        const char *no_proxy = getenv ("no_proxy");
 
          while (--ac && **++av == '-') {
             char *s = *av;
             while (*++s) {
                 switch (*s) {
+                case 'a':
+                    if (ac < 2)
+                        usage ("missing quality for -a");
+                    aa_q = atoi(*++av);
+                    if (aa_q != 1 && aa_q != 2 && aa_q != 4)
+                        usage ("-a requires 1, 2 or 4");
+                    ac--;
+                    break;
+                case 'A':
+                    if (ac < 2)
+                        usage ("missing spec for -A");
+                    test_spec = *++av;
+                    ac--;
+                    break;
                 case 'b':
                     if (ac < 2)
                         usage ("missing host name for -b");
@@ -215,6 +353,12 @@ static void crackArgs (int ac, char *av[])
                     new_appdir = *++av;
                     ac--;
                     break;
+                case 'e':
+                    if (ac < 2)
+                        usage ("missing spec for -e");
+                    export_spec = *++av;
+                    ac--;
+                    break;
                 case 'f':
                     if (ac < 2) {
                         usage ("missing arg for -f");
@@ -252,16 +396,66 @@ static void crackArgs (int ac, char *av[])
                 case 'm':
                     setDemoMode(true);
                     break;
+                case 'n':
+                    borderless = true;
+                    break;
                 case 'o':
                     diag_to_file = false;
                     break;
                     break;
+                case 'p':
+                    if (ac < 2)
+                        usage ("missing x,y for -p");
+                    if (sscanf (*++av, "%d,%d", &win_x, &win_y) != 2 || win_x < 0 || win_y < 0)
+                        usage ("-p requires x,y both >= 0");
+                    ac--;
+                    break;
+                case 'q':
+                    if (ac < 2)
+                        usage ("missing ADIF log file for -q");
+                    setQSOLogFile (*++av);
+                    ac--;
+                    break;
+                case 's':
+                    if (ac < 2)
+                        usage ("missing scenario file for -s");
+                    sim_fn = *++av;
+                    ac--;
+                    break;
+                case 'S':
+                    if (ac < 2)
+                        usage ("missing hours for -S");
+                    soak_spec = *++av;
+                    ac--;
+                    break;
+                case 't':
+                    on_top = true;
+                    break;
+                case 'T':
+                    if (ac < 2)
+                        usage ("missing timeouts for -T");
+                    if (sscanf (*++av, "%d,%d", &connect_to, &read_to) != 2 || connect_to <= 0 || read_to <= 0)
+                        usage ("-T requires c,r both > 0");
+                    ac--;
+                    break;
                 case 'w':
                     if (ac < 2)
                         usage ("missing port number for -w");
                     svr_port = atoi(*++av);
                     ac--;
                     break;
+                case 'x':
+                    if (ac < 2)
+                        usage ("missing proxy url for -x");
+                    proxy = *++av;
+                    ac--;
+                    break;
+                case 'X':
+                    if (ac < 2)
+                        usage ("missing host list for -X");
+                    no_proxy = *++av;
+                    ac--;
+                    break;
                 default:
                     usage ("unknown option: %c", *s);
                 }
@@ -277,10 +471,42 @@ static void crackArgs (int ac, char *av[])
             usage ("-i requires -k");
         if (cl_set && !skip_skip)
             usage ("-l requires -k");
+        if (soak_spec && !skip_skip)
+            usage ("-S requires -k");
+        if (proxy) {
+            if (!setNetProxy (proxy))
+                usage ("proxy must be [http://]host:port: %s", proxy);
+        } else if ((proxy = getenv ("http_proxy")) != NULL && !setNetProxy (proxy)) {
+            // a stray environment variable should not keep us from starting
+            fprintf (stderr, "Ignoring http_proxy, not [http://]host:port: %s\n", proxy);
+        }
+        setNetNoProxy (no_proxy);
+        if (connect_to > 0)
+            (void) setNetTimeouts (connect_to, read_to);
+        if (sim_fn) {
+            char ynot[200];
+            if (!loadSimulation (sim_fn, ynot, sizeof(ynot)))
+                usage ("%s", ynot);
+        }
+        if (soak_spec) {
+            char ynot[100];
+            if (!setSoakTest (soak_spec, ynot, sizeof(ynot)))
+                usage ("%s", ynot);
+        }
 
         // prepare our working directory in our_dir
         mkAppDir (new_appdir);
 
+        // just export alerts if requested
+        if (export_spec)
+            exit (printAlertHistory (export_spec) ? 0 : 1);
+
+        // just send a test alert if requested
+        if (test_spec) {
+            std::string cmd = std::string("set_testalert?") + test_spec;
+            exit (sendLocalWebCommand (cmd.c_str()) ? 0 : 1);
+        }
+
         // redirect stdout to diag file unless requested not to
         if (diag_to_file)
             stdout2File();
@@ -288,6 +514,13 @@ static void crackArgs (int ac, char *av[])
         // set desired screen option if set
         if (fs_set)
             setX11FullScreen (full_screen);
+
+        // set desired anti-aliasing quality if set
+        if (aa_q)
+            setX11AAQuality (aa_q);
+
+        // set desired window placement
+        setX11Placement (win_x, win_y, on_top, borderless);
 }
 
 /* Every normal C program requires a main().
@@ -301,13 +534,20 @@ int main (int ac, char *av[])
         // always want stdout synchronous 
         setbuf (stdout, NULL);
 
-        // check args
-        crackArgs (ac, av);
+        // check args, including any set in the environment
+        int env_ac;
+        char **env_av = addEnvArgs (ac, av, env_ac);
+        crackArgs (env_ac, env_av);
 
         // log args after cracking so they go to proper diag file
         printf ("\nNew program args:\n");
         for (int i = 0; i < ac; i++)
             printf ("  argv[%d] = %s\n", i, av[i]);
+        if (env_ac > ac) {
+            printf ("Args from environment:\n");
+            for (int i = 1; i <= env_ac - ac; i++)
+                printf ("  %s\n", env_av[i]);
+        }
 
         // log our working dir
         printf ("working directory is %s\n", our_dir.c_str());
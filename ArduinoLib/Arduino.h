@@ -87,8 +87,16 @@ extern std::string our_dir;
 
 // glue with parent program
 extern void setX11FullScreen (bool);
+extern void setX11AAQuality (int q);
+extern void printEnvSettings (FILE *fp);
+extern void setX11Placement (int x, int y, bool on_top, bool borderless);
 extern void setDemoMode(bool on);
 extern void setCenterLng (int16_t l);
+extern bool printAlertHistory (const char *spec);
+extern bool sendLocalWebCommand (const char *cmd);
+extern bool loadSimulation (const char *fn, char ynot[], size_t ynot_len);
+extern void setQSOLogFile (const char *fn);
+extern bool setSoakTest (const char *spec, char ynot[], size_t ynot_len);
 extern void fatalError (const char *fmt, ...);
 extern const char *svr_host;
 extern int svr_port;
@@ -2,6 +2,10 @@
  */
 
 #include <signal.h>
+#include <sys/ioctl.h>
+#if defined(__linux__)
+#include <linux/sockios.h>
+#endif
 
 #include "IPAddress.h"
 #include "WiFiClient.h"
@@ -9,6 +13,112 @@
 // set for core info
 static bool _trace_client = false;
 
+// optional HTTP proxy through which all connections are tunneled using CONNECT
+static char *proxy_host;                // NULL if none
+static int proxy_port;
+static char *no_proxy;                  // malloced comma separated hosts to reach directly, or NULL
+
+// timeouts, millis
+static int connect_to_ms = 5000;
+static int read_to_ms = 5000;
+
+
+/* set the proxy from url of the form [http://]host:port, or clear it if url is NULL or empty.
+ * return false if url is not understood.
+ */
+bool setNetProxy (const char *url)
+{
+        free (proxy_host);
+        proxy_host = NULL;
+        if (!url || !*url)
+            return (true);
+
+        if (strncmp (url, "http://", 7) == 0)
+            url += 7;
+        char host[200];
+        int port;
+        if (sscanf (url, "%199[^:/]:%d", host, &port) != 2 || port <= 0 || port > 65535)
+            return (false);
+
+        proxy_host = strdup (host);
+        proxy_port = port;
+        printf ("WiFiCl: using proxy %s:%d\n", proxy_host, proxy_port);
+        return (true);
+}
+
+/* set the comma separated list of hosts, or domains within which all hosts, to reach without the
+ * proxy, or clear the list if hosts is NULL. "*" means all.
+ */
+void setNetNoProxy (const char *hosts)
+{
+        free (no_proxy);
+        no_proxy = hosts ? strdup (hosts) : NULL;
+}
+
+/* set the connect and read timeouts, return false if either is not positive.
+ */
+bool setNetTimeouts (int connect_secs, int read_secs)
+{
+        if (connect_secs <= 0 || read_secs <= 0)
+            return (false);
+        connect_to_ms = 1000*connect_secs;
+        read_to_ms = 1000*read_secs;
+        return (true);
+}
+
+/* return the read timeout, millis
+ */
+int getNetReadTimeout()
+{
+        return (read_to_ms);
+}
+
+/* return the connect timeout, millis
+ */
+int getNetConnectTimeout()
+{
+        return (connect_to_ms);
+}
+
+/* return whether connections to the given host should go through the proxy
+ */
+static bool useProxy (const char *host)
+{
+        if (!proxy_host || strcmp (host, "localhost") == 0 || strcmp (host, "127.0.0.1") == 0)
+            return (false);
+        if (!no_proxy)
+            return (true);
+
+        size_t hl = strlen (host);
+        const char *np = no_proxy;
+        while (*np) {
+            np += strspn (np, ", ");
+            size_t nl = strcspn (np, ", ");
+            const char *name = np;
+            np += nl;
+            if (nl > 0 && name[0] == '.') {
+                name++;
+                nl--;
+            }
+            if (nl == 0)
+                continue;
+            if (nl == 1 && name[0] == '*')
+                return (false);
+            if (hl == nl && strncasecmp (host, name, nl) == 0)
+                return (false);
+            if (hl > nl && host[hl-nl-1] == '.' && strncasecmp (host+hl-nl, name, nl) == 0)
+                return (false);
+        }
+        return (true);
+}
+
+/* return whether connections to the given host go through the proxy, so the proxy resolves its name
+ */
+bool netUsesProxy (const char *host)
+{
+        return (useProxy (host));
+}
+
 WiFiClient::WiFiClient()
 {
 	socket = -1;
@@ -81,7 +191,7 @@ int WiFiClient::tout (int to_ms, int fd)
         FD_SET (fd, &wset);
 
         tv.tv_sec = to_ms / 1000;
-        tv.tv_usec = to_ms % 1000;
+        tv.tv_usec = 1000 * (to_ms % 1000);
 
         ret = select (fd + 1, &rset, &wset, NULL, &tv);
         if (ret > 0)
@@ -94,36 +204,120 @@ int WiFiClient::tout (int to_ms, int fd)
 
 bool WiFiClient::connect(const char *host, int port)
 {
-        struct addrinfo hints, *aip;
+        if (useProxy (host))
+            return (connectProxy (host, port));
+        return (connectDirect (host, port));
+}
+
+/* connect to host:port through the proxy using an HTTP CONNECT tunnel.
+ * once established the tunnel behaves exactly like a direct connection.
+ */
+bool WiFiClient::connectProxy (const char *host, int port)
+{
+        if (!connectDirect (proxy_host, proxy_port))
+            return (false);
+
+        // ask for tunnel, IPv6 addresses must be in []
+        char buf[300];
+        const char *lb = strchr (host, ':') ? "[" : "";
+        const char *rb = strchr (host, ':') ? "]" : "";
+        int n = snprintf (buf, sizeof(buf), "CONNECT %s%s%s:%d HTTP/1.1\r\nHost: %s%s%s:%d\r\n\r\n",
+                                lb, host, rb, port, lb, host, rb, port);
+        if (write ((const uint8_t *)buf, n) != n)
+            return (false);
+
+        // read response header up through blank line, first line is status.
+        // N.B. any tunnel data beyond the header stays in peek[] for our caller.
+        int status = 0;
+        int bl = 0;
+        bool first = true;
+        struct timeval tv0;
+        gettimeofday (&tv0, NULL);
+        while (socket >= 0) {
+            if (!available()) {
+                struct timeval tv1;
+                gettimeofday (&tv1, NULL);
+                if ((tv1.tv_sec-tv0.tv_sec)*1000 + (tv1.tv_usec-tv0.tv_usec)/1000 > read_to_ms) {
+                    printf ("WiFiCl: proxy %s:%d timed out\n", proxy_host, proxy_port);
+                    stop();
+                    return (false);
+                }
+                usleep (10000);
+                continue;
+            }
+            char c = peek[next_peek++];
+            if (c == '\r')
+                continue;
+            if (c != '\n') {
+                if (bl < (int)sizeof(buf)-1)
+                    buf[bl++] = c;
+                continue;
+            }
+            buf[bl] = '\0';
+            if (first) {
+                if (sscanf (buf, "HTTP/%*s %d", &status) != 1)
+                    status = 0;
+                first = false;
+            } else if (bl == 0) {
+                break;
+            }
+            bl = 0;
+        }
+
+        if (status != 200) {
+            printf ("WiFiCl: proxy %s:%d refused %s:%d: %d\n", proxy_host, proxy_port, host, port, status);
+            stop();
+            return (false);
+        }
+
+        printf ("WiFiCl: tunnel to %s:%d via proxy socket %d\n", host, port, socket);
+        return (true);
+}
+
+/* connect directly to host:port
+ */
+bool WiFiClient::connectDirect (const char *host, int port)
+{
+        struct addrinfo hints, *aip0, *aip;
         char port_str[16];
-        int sockfd;
+        int sockfd = -1;
 
-        /* lookup host address.
-         * N.B. must call freeaddrinfo(aip) after successful call before returning
+        /* lookup host addresses, IPv4 or IPv6.
+         * N.B. must call freeaddrinfo(aip0) after successful call before returning
          */
         memset (&hints, 0, sizeof(hints));
-        hints.ai_family = AF_INET;
+        hints.ai_family = AF_UNSPEC;
         hints.ai_socktype = SOCK_STREAM;
+        hints.ai_flags = AI_ADDRCONFIG;
         sprintf (port_str, "%d", port);
-        int error = ::getaddrinfo (host, port_str, &hints, &aip);
+        int error = ::getaddrinfo (host, port_str, &hints, &aip0);
         if (error) {
             printf ("getaddrinfo(%s:%d): %s\n", host, port, gai_strerror(error));
             return (false);
         }
 
-        /* create socket */
-        sockfd = ::socket (aip->ai_family, aip->ai_socktype, aip->ai_protocol);
-        if (sockfd < 0) {
-            freeaddrinfo (aip);
-            printf ("socket(%s:%d): %s\n", host, port, strerror(errno));
-	    return (false);
+        /* try each address in turn until one connects */
+        for (aip = aip0; aip != NULL; aip = aip->ai_next) {
+
+            /* create socket */
+            sockfd = ::socket (aip->ai_family, aip->ai_socktype, aip->ai_protocol);
+            if (sockfd < 0) {
+                printf ("socket(%s:%d): %s\n", host, port, strerror(errno));
+                continue;
+            }
+
+            /* connect */
+            if (connect_to (sockfd, aip->ai_addr, aip->ai_addrlen, connect_to_ms) < 0) {
+                printf ("connect(%s,%d): %s\n", host,port,strerror(errno));
+                close (sockfd);
+                sockfd = -1;
+                continue;
+            }
+
+            break;
         }
-
-        /* connect */
-        if (connect_to (sockfd, aip->ai_addr, aip->ai_addrlen, 5000) < 0) {
-            printf ("connect(%s,%d): %s\n", host,port,strerror(errno));
-            freeaddrinfo (aip);
-            close (sockfd);
+        if (sockfd < 0) {
+            freeaddrinfo (aip0);
             return (false);
         }
 
@@ -132,7 +326,7 @@ bool WiFiClient::connect(const char *host, int port)
 
         /* ok */
         printf ("WiFiCl: new %s:%d socket %d\n", host, port, sockfd);
-        freeaddrinfo (aip);
+        freeaddrinfo (aip0);
 	socket = sockfd;
 	n_peek = 0;
         next_peek = 0;
@@ -239,6 +433,24 @@ int WiFiClient::write (const uint8_t *buf, int n)
 	return (n);
 }
 
+/* return n bytes written but not yet acknowledged by the peer, 0 if closed or unknown
+ */
+int WiFiClient::unsent (void)
+{
+        if (socket < 0)
+            return (0);
+
+        int n = 0;
+#if defined(SIOCOUTQ)
+        if (ioctl (socket, SIOCOUTQ, &n) < 0)
+            n = 0;
+#elif defined(FIONWRITE)
+        if (ioctl (socket, FIONWRITE, &n) < 0)
+            n = 0;
+#endif
+        return (n);
+}
+
 void WiFiClient::print (void)
 {
 }
@@ -307,13 +519,15 @@ void WiFiClient::println (uint32_t i)
 
 String WiFiClient::remoteIP()
 {
-	struct sockaddr_in sa;
+	struct sockaddr_storage sa;
 	socklen_t len = sizeof(sa);
 
-	getpeername(socket, (struct sockaddr *)&sa, &len);
-	struct in_addr ipAddr = sa.sin_addr;
-
-	char str[INET_ADDRSTRLEN];
-	inet_ntop(AF_INET, &ipAddr, str, INET_ADDRSTRLEN);
+	char str[INET6_ADDRSTRLEN] = "";
+	if (getpeername(socket, (struct sockaddr *)&sa, &len) == 0) {
+	    if (sa.ss_family == AF_INET6)
+		inet_ntop(AF_INET6, &((struct sockaddr_in6 *)&sa)->sin6_addr, str, sizeof(str));
+	    else
+		inet_ntop(AF_INET, &((struct sockaddr_in *)&sa)->sin_addr, str, sizeof(str));
+	}
 	return (String(str));
 }
@@ -45,6 +45,7 @@ class WiFiClient {
 	int read();
 	operator bool();
 	int write (const uint8_t *buf, int n);
+        int unsent (void);
 	void print (void);
 	void print (String s);
 	void print (float f);
@@ -66,11 +67,21 @@ class WiFiClient {
         int next_peek;                  // next peek[] index to use
 
 
+        bool connectDirect (const char *host, int port);
+        bool connectProxy (const char *host, int port);
         int connect_to (int sockfd, struct sockaddr *serv_addr, int addrlen, int to_ms);
         int tout (int to_ms, int fd);
 
 };
 
+// network settings shared by all connections
+extern bool setNetProxy (const char *url);
+extern void setNetNoProxy (const char *hosts);
+extern bool setNetTimeouts (int connect_secs, int read_secs);
+extern int getNetReadTimeout (void);
+extern int getNetConnectTimeout (void);
+extern bool netUsesProxy (const char *host);
+
 
 
 #endif // _WIFICLIENT_H
@@ -100,8 +100,8 @@ SCoord wifi_tt_s;
 Adafruit_RA8875_R tft(RA8875_CS, RA8875_RESET);
 
 // manage the great circle path through DE and DX points
-#define MAX_GPATH               1500    // max number of points to draw in great circle path
-static SCoord *gpath;                   // malloced path points
+static SCoord *gpath;                   // malloced path points from drawGCArc()
+#define GPATH_WIDTH     2               // path line width
 static uint16_t n_gpath;                // actual number in use
 static uint32_t gpath_time;             // millis() when great path was drawn
 #define GPATH_COLOR     RA8875_WHITE    // path color
@@ -110,6 +110,7 @@ static SBox prefix_b;                   // where to show DX prefix text
 // manage antenna heading circle paths
 static SCoord *hpath1, *hpath2;
 static uint16_t n_hpath1, n_hpath2;
+#define HPATH_WIDTH     1               // heading line width
 int16_t antenna_heading, antenna_width;  // In degrees (positive or negative); will be converted to radians as needed.
 
 // manage using DX cluster prefix or one from nearestPrefix()
@@ -239,12 +240,10 @@ void setup()
     initBrightness();
 
 #if defined(_IS_UNIX)
-    // connect signal handler
+    // connect signal handler, fatal signals also write a crash report
     signal (SIGTERM, onSig);
-    signal (SIGILL, onSig);
     signal (SIGKILL, onSig);
-    signal (SIGSEGV, onSig);
-    signal (SIGBUS, onSig);
+    initCrashReport();
 #endif // _IS_UNIX
 
 
@@ -314,6 +313,9 @@ void setup()
     // prep stopwatch
     initStopwatch();
 
+    // load any translations before drawing text
+    initLanguage();
+
     // draw initial callsign
     eraseScreen();
     free (cs_info.call);
@@ -372,6 +374,11 @@ void setup()
     version_b.y = cs_info.box.y+cs_info.box.h+CSINFO_DROP;
     version_b.h = 8;
 
+#if defined(_IS_UNIX)
+    // mention any crash report from last time
+    checkPrevCrash();
+#endif // _IS_UNIX
+
     // start WiFi, maps and set de_ll.lat_d/de_ll.lng_d from geolocation or gpsd as desired -- uses tftMsg()
     initSys();
 
@@ -391,6 +398,9 @@ void setup()
     // init sensors
     initBME280();
 
+    // load any plugins so their pane is available
+    initPlugins();
+
     // read plot settings from NVnsure sane defaults 
     initPlotPanes();
 
@@ -535,6 +545,9 @@ void setup()
     // perform inital screen layout
     initScreen();
 
+    // return to a stopwatch page if one was up when last running
+    restoreSWDisplay();
+
     // now start checking repetative wd
     max_wd_dt = 0;
 }
@@ -542,11 +555,19 @@ void setup()
 // called repeatedly forever
 void loop()
 {
+    // show we are alive
+    checkHealth();
+
     // update stopwatch exclusively, if active
     if (runStopwatch()) {
         // these can run even while stopwatch is up
         followBrightness();
         readBME280();
+        #if defined(_IS_UNIX)
+            // unattended big clock is just when escalations matter most
+            checkCrashState();
+            checkAlertEscalations();
+        #endif // _IS_UNIX
         return;
     }
 
@@ -573,6 +594,26 @@ void loop()
     checkOnAir();
     readBME280();
     runNextDemoCommand();
+    runSimulation();
+    checkSoakTest();
+    checkMapCenter();
+    checkEventScripts();
+    checkWebSockets();
+    checkDoppler();
+    checkMDNS();
+    #if defined(_IS_UNIX)
+        char kbc = tft.getChar();
+        if (kbc)
+            (void) brightnessOn();
+        checkScreenshots(kbc);
+        checkMapLayerKey(kbc);
+        checkPerfHUD(kbc);
+        checkStopwatchKey(kbc);
+        if (kbc == MAPCENTER_KEY)
+            cycleMapCenterMode();
+        checkCrashState();
+        checkAlertEscalations();
+    #endif // _IS_UNIX
 
     // check for touch events
     checkTouch();
@@ -920,6 +961,7 @@ void newDX (LatLong &ll, const char *grid, const char *ovprefix)
     // show DX weather and update band conditions if showing
     showDXWX();
     newBC();
+    newEME();
 
     // persist
     NVWriteFloat (NV_DX_LAT, dx_ll.lat_d);
@@ -944,7 +986,7 @@ void newDE (LatLong &ll, const char *grid)
     // sat path will change, stop gimbal and require op to start
     stopGimbalNow();
 
-    if (azm_on) {
+    if (azm_on || getMapCenterMode() == MCM_DE) {
 
         // must start over because everything moves to keep new DE centered
 
@@ -990,6 +1032,8 @@ void newDE (LatLong &ll, const char *grid)
     if (findPaneChoiceNow(PLOT_CH_MOON) != PANE_NONE)
         updateMoonPane(true);           // only if not scheduled by showDEWX
     newBC();
+    newIonosonde();
+    newEME();
     newVOACAPMap(prop_map);
     sendDXClusterDELLGrid();
     setSatObserver (de_ll.lat_d, de_ll.lng_d);
@@ -1088,10 +1132,7 @@ static void eraseDXPath()
             drawMapCoord (prefix_b.x + dx, prefix_b.y + dy);
 
     // erase the great path
-    for (uint16_t i = 0; i < n_gpath; i++) {
-        drawMapCoord (gpath[i]);                        // draws x and x+1, y
-        drawMapCoord (gpath[i].x, gpath[i].y+1);        //        "       , y+1
-    }
+    eraseGCArc (gpath, n_gpath, GPATH_WIDTH);
 
     // mark no longer active
     setDXPathInvalid();
@@ -1116,52 +1157,6 @@ void propDEDXPath (bool long_path, LatLong &ll, float *distp, float *bearp)
     }
 }
 
-/* draw Great Circle at heading from DE.
- * Save screen coordinates in *path.  Buffer must be allocated at least MAX_GPATH long.
- * returns: actual number of points drawn.
- */
-uint16_t drawGreatCirclePath(float dist, float bear, SCoord *path, uint16_t shortColor, uint16_t longColor) {
-    // walk great circle path from DE through DX, storing each point
-    float ca, B;
-    SCoord s;
-    uint16_t num_points = 0;
-
-    // start with max nnumber of points, then reduce
-    path = (SCoord *) realloc (path, MAX_GPATH * sizeof(SCoord));
-    if (!path) {
-        Serial.println (F("Failed to malloc path"));
-        reboot();
-    }
-
-    for (float b = 0; b < 2*M_PIF; b += 2*M_PIF/MAX_GPATH) {
-        solveSphere (bear, b, sdelat, cdelat, &ca, &B);
-        ll2s (asinf(ca), fmodf(de_ll.lng+B+5*M_PIF,2*M_PIF)-M_PIF, s, 1);
-        if (overMap(s) && (num_points == 0 || memcmp (&s, &path[num_points-1], sizeof(SCoord)))) {
-            uint16_t c = b < dist ? shortColor : longColor;
-            path[num_points++] = s;
-
-            // beware drawing the fat pixel off the edge of earth because eraseDXPath won't erase it
-            LatLong ll;
-            tft.drawPixel (s.x, s.y, c);
-            if (s2ll(s.x+1, s.y, ll))
-                tft.drawPixel (s.x+1, s.y, c);
-            if (s2ll(s.x+1, s.y+1, ll))
-                tft.drawPixel (s.x+1, s.y+1, c);
-            if (s2ll(s.x, s.y+1, ll))
-                tft.drawPixel (s.x, s.y+1, c);
-        }
-    }
-    // reduce to actual number of points used
-    // Serial.printf (_FX("n_gpath %u -> %u\n"), MAX_GPATH, n_gpath);
-    path = (SCoord *) realloc (path, num_points * sizeof(SCoord));
-    if (!path) {
-        Serial.println (F("Failed to realloc gpath"));
-        reboot();
-    }
-
-    return num_points;
-}
-
 /*
  * Draw two great circles, at antenna heading + width/2, and heading - width/2
  * heading and width are in radians.
@@ -1170,11 +1165,22 @@ uint16_t drawGreatCirclePath(float dist, float bear, SCoord *path, uint16_t shor
 void drawHeadingPath() {
     resetWatchdog();
 
+    float prev_opacity;
+    if (!beginMapLayer (MAPL_PATH, &prev_opacity))
+        return;
+
     float h = fmodf(float(antenna_heading) * M_PIF*2.0 / 360, 2*M_PIF);
     float w = fmodf(float(antenna_width) * M_PIF*2.0 / 360, 2*M_PIF);
 
-    n_hpath1 = drawGreatCirclePath(M_PIF, h + w/2.0, hpath1, getAntennaHeadingColor(), getAntennaBackColor());
-    n_hpath2 = drawGreatCirclePath(M_PIF, h - w/2.0, hpath2, getAntennaHeadingColor(), getAntennaBackColor());
+    GCArc arc;
+    initGCArc (arc, de_ll, h + w/2.0, 2*M_PIF, getAntennaHeadingColor(), HPATH_WIDTH);
+    arc.split = M_PIF;
+    arc.color2 = getAntennaBackColor();
+    n_hpath1 = drawGCArc (arc, hpath1);
+    arc.bear = h - w/2.0;
+    n_hpath2 = drawGCArc (arc, hpath2);
+
+    endMapLayer (prev_opacity);
 }
 
 /* draw great circle through DE and DX.
@@ -1188,7 +1194,12 @@ void drawDXPath ()
     float dist, bear;
     propDEDXPath (false, dx_ll, &dist, &bear);
 
-    n_gpath = drawGreatCirclePath(dist, bear, gpath, getShortPathColor(), getLongPathColor());
+    // full circle in short path color as far as DX then long path color the rest of the way around
+    GCArc arc;
+    initGCArc (arc, de_ll, bear, 2*M_PIF, getShortPathColor(), GPATH_WIDTH);
+    arc.split = dist;
+    arc.color2 = getLongPathColor();
+    n_gpath = drawGCArc (arc, gpath);
 
     // printFreeHeap (F("drawDXPath"));
 }
@@ -1249,38 +1260,40 @@ void setOnAir (bool on)
     drawCallsign (true);
 }
 
-/* change call sign to ON AIR as long as GPIO21 is low
+/* change call sign to ON AIR as long as GPIO21 is low or rigctld reports PTT.
+ * also accumulate transmit time and run the on_air event script when it changes.
  */
 bool checkOnAir()
 {
+    bool on = false;
 
 #if defined(_SUPPORT_GPIO) && defined(_IS_UNIX)
 
     // ignore if not supposed to use GPIO
-    if (!GPIOOk())
-        return (false);
+    if (GPIOOk()) {
+        GPIO& gpio = GPIO::getGPIO();
+        gpio.setAsInput (ONAIR_GPIO);
+        on = gpio.isReady() && !gpio.readPin(ONAIR_GPIO);
+    }
+
+#endif // _SUPPORT_GPIO && _IS_UNIX
+
+    if (getRigPTT())
+        on = true;
+
+    noteTransmitting (on);
 
     // only draw when changes
     static bool prev_on;
 
-    GPIO& gpio = GPIO::getGPIO();
-    gpio.setAsInput (ONAIR_GPIO);
-    bool on = gpio.isReady() && !gpio.readPin(ONAIR_GPIO);
-
-    if (on && !prev_on)
-        setOnAir(true);
-    else if (!on && prev_on)
-        setOnAir(false);
+    if (on != prev_on) {
+        setOnAir(on);
+        runEventScript ("on_air", on ? "on" : "off", NULL);
+    }
 
     prev_on = on;
 
     return (on);
-
-#else
-
-    return (false);
-
-#endif // _SUPPORT_GPIO && _IS_UNIX
 }
 
 
@@ -1291,8 +1304,13 @@ void drawCallsign (bool all)
 {
     tft.graphicsMode();
 
+    // ON AIR always stands out as white on red
+    bool on_air = strcmp (cs_info.call, on_air_msg) == 0;
+
     if (all) {
-        if (cs_info.bg_rainbow)
+        if (on_air)
+            tft.fillRect (cs_info.box.x, cs_info.box.y, cs_info.box.w, cs_info.box.h, RA8875_RED);
+        else if (cs_info.bg_rainbow)
             drawRainbow (cs_info.box);
         else
             tft.fillRect (cs_info.box.x, cs_info.box.y, cs_info.box.w, cs_info.box.h, cs_info.bg_color);
@@ -1320,7 +1338,7 @@ void drawCallsign (bool all)
         }
     }
 
-    tft.setTextColor (cs_info.fg_color);
+    tft.setTextColor (on_air ? RA8875_WHITE : cs_info.fg_color);
     int cx = cs_info.box.x + (cs_info.box.w-cw)/2;
     int cy = cs_info.box.y + ch + (cs_info.box.h-ch)/2 - 3;
     tft.setCursor (cx, cy);
@@ -1385,10 +1403,18 @@ static void drawWiFiInfo()
     if (!timesUp(&prev_ms, 5000))
         return;
 
-    // toggle rssi and ip, or just ip if no wifi
-    static bool rssi_ip_toggle;
+    // cycle ip, rssi and a reminder during alert quiet hours, or just ip if no wifi, or what is wrong if
+    // the network monitor found a problem
+    static uint8_t info_phase;                  // 0 ip, 1 rssi, 2 quiet hours
     char str[30];
-    if (rssi_ip_toggle) {
+    NetState ns = getNetState();
+    if (ns != NET_OK && ns != NET_NOLINK) {
+        snprintf (str, sizeof(str), "%s", getNetStateName(ns));
+        tft.setTextColor (RA8875_RED);
+    } else if (info_phase == 2) {
+        strcpy (str, "   Quiet hours");
+        tft.setTextColor (RA8875_YELLOW);
+    } else if (info_phase == 1) {
         // show RSSI, if working
         int16_t rssi = WiFi.RSSI();
         if (rssi < 10) {
@@ -1421,8 +1447,8 @@ static void drawWiFiInfo()
         tft.print(str);
     }
 
-    // toggle
-    rssi_ip_toggle = !rssi_ip_toggle;
+    // next, quiet hours phase only while in effect
+    info_phase = (info_phase + 1) % (quietHoursNow() ? 3 : 2);
 
 }
 
@@ -1571,17 +1597,22 @@ void drawAllSymbols(bool erase_too)
 
     if (DRAPScaleIsUp())
         drawDRAPScale();
-    if (!overRSS(sun_c.s))
-        drawSun();
-    if (!overRSS(moon_c.s))
-        drawMoon();
-    updateBeacons(erase_too, true, false);
-    drawDEMarker(false);
-    drawDXMarker(false);
-    if (!overRSS(deap_c.s))
-        drawDEAPMarker();
-    drawDXClusterSpotsOnMap();
-    drawSanta ();
+
+    float prev_opacity;
+    if (beginMapLayer (MAPL_SYMBOLS, &prev_opacity)) {
+        if (!overRSS(sun_c.s))
+            drawSun();
+        if (!overRSS(moon_c.s))
+            drawMoon();
+        updateBeacons(erase_too, true, false);
+        drawDEMarker(false);
+        drawDXMarker(false);
+        if (!overRSS(deap_c.s))
+            drawDEAPMarker();
+        drawDXClusterSpotsOnMap();
+        drawSanta ();
+        endMapLayer (prev_opacity);
+    }
 
     updateClocks(false);
 }
@@ -1863,12 +1894,19 @@ void setMapTagBox (const char *tag, const SCoord &s, uint16_t r, SBox &box)
 /* draw a small string within the given box set by setMapTagBox
  */
 void drawMapTag (const char *tag, SBox &box)
+{
+    drawMapTag (tag, box, RA8875_WHITE, RA8875_BLACK);
+}
+
+/* draw tag in the given box with the given colors
+ */
+void drawMapTag (const char *tag, SBox &box, uint16_t fg_color, uint16_t bg_color)
 {
     // draw
-    tft.fillRect (box.x, box.y, box.w, box.h, RA8875_BLACK);
+    tft.fillRect (box.x, box.y, box.w, box.h, bg_color);
     selectFontStyle (LIGHT_FONT, FAST_FONT);
     tft.setCursor (box.x+2, box.y);
-    tft.setTextColor (RA8875_WHITE);
+    tft.setTextColor (fg_color);
     tft.print((char*)tag);
 }
 
@@ -1943,6 +1981,7 @@ void resetWatchdog()
 
     ESP.wdtFeed();
     yield();
+    pingSystemd();
 }
 
 /* like delay() but breaks into small chunks so we can call resetWatchdog()
@@ -59,19 +59,21 @@
   #define _SUPPORT_ENVSENSOR
 #endif
 
-// Flip screen only on ESP
-#if defined(_IS_ESP8266)
-  #define _SUPPORT_FLIP
-#endif
+// Flip screen 180 degrees, by the RA8875 on ESP else when staging the frame buffer
+#define _SUPPORT_FLIP
 
 // kx3 on any system with GPIO
 #if defined(_SUPPORT_GPIO)
   #define _SUPPORT_KX3
 #endif
 
-// phot only supported on ESP and then only if phot is detected
+// phot supported on ESP, or as an I2C light sensor on systems that can control a backlight;
+// either only if detected
 #if defined(_IS_ESP8266)
   #define _SUPPORT_PHOT
+#elif (defined(_IS_LINUX_RPI) || defined(_USE_FB0)) && defined(_IIC_LINUX)
+  #define _SUPPORT_PHOT
+  #define _SUPPORT_LUXSENSOR
 #endif
 
 
@@ -276,6 +278,23 @@ typedef enum {
     PLOT_CH_DRAP,
     PLOT_CH_COUNTDOWN,
     PLOT_CH_STEREO_A,
+    PLOT_CH_OUTLOOK,
+    PLOT_CH_IONOSONDE,
+    PLOT_CH_FLARES,
+    PLOT_CH_CME,
+    PLOT_CH_BANDREC,
+    PLOT_CH_EME,
+    PLOT_CH_SDO_5,
+    PLOT_CH_SDO_CYCLE,
+    PLOT_CH_NCDXF,
+    PLOT_CH_BANDPLAN,
+    PLOT_CH_DXPEDS,
+    PLOT_CH_PLUGINS,
+    PLOT_CH_QSORATE,
+    PLOT_CH_TXMETER,
+    PLOT_CH_SATSKY,
+    PLOT_CH_ARISS,
+    PLOT_CH_AWARDS,
 
     PLOT_CH_N
 } PlotChoice;
@@ -522,6 +541,7 @@ extern time_t getUptime (uint16_t *days, uint8_t *hrs, uint8_t *mins, uint8_t *s
 extern void eraseScreen(void);
 extern void setMapTagBox (const char *tag, const SCoord &c, uint16_t r, SBox &box);
 extern void drawMapTag (const char *tag, SBox &box);
+extern void drawMapTag (const char *tag, SBox &box, uint16_t fg_color, uint16_t bg_color);
 extern void setDXPrefixOverride (char p[MAX_PREF_LEN]);
 extern bool getDXPrefix (char p[MAX_PREF_LEN+1]);
 extern void call2Prefix (const char *call, char prefix[MAX_PREF_LEN]);
@@ -548,6 +568,118 @@ extern void doOTAupdate(const char *ver);
 
 
 
+/*********************************************************************************************
+ *
+ * alerthist.cpp
+ *
+ */
+
+extern void recordAlert (const char *kind, const char *call, float kHz, const char *mode, const char *details);
+#if defined(_IS_UNIX)
+extern bool getWiFiAlerts (WiFiClient *clientp, char line[]);
+extern bool printAlertHistory (const char *spec);
+#endif // _IS_UNIX
+
+
+
+/*********************************************************************************************
+ *
+ * alertqueue.cpp
+ *
+ */
+
+#if defined(_IS_UNIX)
+#define AQ_ITEMLEN      300             // max bytes in one AlertQItem
+#define AQ_MAXTAGS      4               // tags are 0 .. AQ_MAXTAGS-1, N.B. at least MAX_WS
+
+typedef enum {
+    AQ_WS,                              // web socket clients, tag is client index
+    AQ_SCRIPT,                          // event scripts
+    AQ_N
+} AlertQSink;
+
+// one item waiting for a sink
+typedef struct {
+    int tag;                            // sink specific, eg web socket client index
+    time_t t;                           // when queued
+    uint16_t len;                       // n used in data[]
+    char data[AQ_ITEMLEN];              // sink specific, eg web socket message body
+} AlertQItem;
+
+extern bool alertQPush (AlertQSink sink, int tag, const char *data, size_t len);
+extern bool alertQPop (AlertQSink sink, int tag, AlertQItem &item);
+extern int alertQDepth (AlertQSink sink, int tag);
+extern void alertQDiscard (AlertQSink sink, int tag);
+extern void noteAlertQSent (AlertQSink sink, const AlertQItem *item);
+extern void noteAlertQDropped (AlertQSink sink);
+extern void noteAlertQLag (AlertQSink sink);
+extern bool setAlertQPolicy (const char *sink, const char *policy, char *ynot, size_t ynot_len);
+extern void printAlertQueues (WiFiClient &client, const char *prefix);
+#endif // _IS_UNIX
+
+
+
+/*********************************************************************************************
+ *
+ * alertmsg.cpp
+ *
+ */
+
+extern void formatAlertMessage (const char *kind, const char *call, float kHz, const char *mode,
+        char *details, size_t details_len);
+#if defined(_IS_UNIX)
+extern bool setAlertTemplate (const char *kind, const char *tmpl, char *ynot, size_t ynot_len);
+extern void printAlertTemplates (WiFiClient &client, const char *prefix);
+#endif // _IS_UNIX
+
+
+
+
+/*********************************************************************************************
+ *
+ * alertnotify.cpp
+ *
+ */
+
+typedef enum {
+    ALSEV_INFO,                         // worth knowing, eg a watched call was spotted
+    ALSEV_WARNING,                      // needs attention soon, eg countdown finished
+    ALSEV_CRITICAL,                     // needs attention now, eg alarm clock or high SWR
+    ALSEV_N
+} AlertSeverity;
+
+extern bool notifyAlert (const char *kind, AlertSeverity sev, char *details, size_t details_len,
+        const char *call, float kHz, const char *mode, const char *arg1, const char *arg2);
+
+
+
+/*********************************************************************************************
+ *
+ * alertsound.cpp
+ *
+ */
+
+extern void playAlertSound (const char *kind, AlertSeverity sev);
+#if defined(_IS_UNIX)
+extern bool setAlertSound (const char *kind, const char *sound, char *ynot, size_t ynot_len);
+extern void printAlertSounds (WiFiClient &client, const char *prefix);
+#endif // _IS_UNIX
+
+
+
+/*********************************************************************************************
+ *
+ * ariss.cpp
+ *
+ */
+
+extern bool updateARISS (const SBox &box);
+extern bool retrieveARISS (const char *&ynot);
+extern bool checkARISSTouch (const SCoord &s, const SBox &box);
+extern void checkARISSAlerts(void);
+
+
+
 /*********************************************************************************************
  *
  * askNewPos.cpp
@@ -588,6 +720,111 @@ extern void getLunarRS (const time_t t0, const LatLong &ll, time_t *riset, time_
 
 
 
+/*********************************************************************************************
+ *
+ * aurora.cpp
+ *
+ */
+
+extern bool getAuroraAlert(void);
+extern void setAuroraAlert (bool on);
+extern bool getAuroraView (bool &visible, char *where, size_t where_len, char *basis, size_t basis_len);
+extern void checkAuroraAlert(void);
+#if defined(_IS_UNIX)
+extern bool getAuroraProbability (const LatLong &ll, int &pct);
+extern void drawAuroraLayer(void);
+#endif // _IS_UNIX
+
+
+
+
+/*********************************************************************************************
+ *
+ * awards.cpp
+ *
+ */
+
+extern void noteAwardLoggedQSO (const char *grid, float kHz, const char *mode);
+extern void checkAwards (void);
+extern bool awardSlotNeeded (const char *entity, float kHz, const char *mode);
+extern bool updateAwards (const SBox &box);
+extern bool checkAwardsTouch (const SCoord &s, const SBox &box);
+extern void printAwards (WiFiClient &client);
+
+
+
+
+/*********************************************************************************************
+ *
+ * bandrec.cpp
+ *
+ */
+
+typedef struct {
+    int band;                   // meters
+    int score;                  // 0 .. 100, higher is better
+    int heading;                // suggested beam heading from DE, degrees E of N
+    int confidence;             // 0 .. 100, how much to trust score
+} BandRec;
+
+extern int getBandRecommendations (BandRec recs[], int n_recs, float &muf);
+extern float modelMUF (const LatLong &ll);
+extern bool updateBandRec (const SBox &box);
+extern const char *heading2Compass (int heading);
+
+
+
+
+/*********************************************************************************************
+ *
+ * bands.cpp
+ *
+ */
+
+typedef enum {
+    HB_160M,
+    HB_80M,
+    HB_60M,
+    HB_40M,
+    HB_30M,
+    HB_20M,
+    HB_17M,
+    HB_15M,
+    HB_12M,
+    HB_10M,
+    HB_6M,
+    HB_4M,
+    HB_2M,
+    HB_125CM,
+    HB_70CM,
+    HB_33CM,
+    HB_23CM,
+    HB_N,
+    HB_NONE = HB_N
+} HamBand;
+
+extern HamBand findHamBand (float kHz);
+extern const char *getHamBandName (HamBand b);
+extern bool getHamBandEdges (HamBand b, float &lo_khz, float &hi_khz);
+
+
+
+
+/*********************************************************************************************
+ *
+ * bandplan.cpp
+ *
+ */
+
+extern bool updateBandPlan (const SBox &box);
+extern const char *findBandPlanMode (float kHz);
+extern bool checkBandPlanTouch (const SCoord &s, const SBox &box);
+extern void getBandPlan (int &region, int &band);
+extern bool setBandPlan (int region, int band);
+
+
+
+
 /*********************************************************************************************
  *
  * brightness.cpp
@@ -609,6 +846,9 @@ extern bool getDisplayInfo (uint16_t &percent, uint16_t &idle_min, uint16_t &idl
 extern void setFullBrightness(void);
 extern bool brControlOk(void);
 extern bool brOnOffOk(void);
+extern void wakeDisplayForAlert (const char *kind, AlertSeverity sev);
+extern const char *getDisplayWakeName(void);
+extern bool setDisplayWake (const char *name);
 extern bool found_phot;
 
 
@@ -622,6 +862,7 @@ extern bool found_phot;
  */
 extern void readCities(void);
 extern const char *getNearestCity (const LatLong &ll, LatLong &city_ll);
+extern bool geocodeCity (const char *name, LatLong &ll, char *found, size_t found_len);
 
 
 
@@ -641,6 +882,14 @@ enum {
     DETIME_N,
 };
 
+// time zone used to show scheduled events, N.B. must match event_tz_names[]
+typedef enum {
+    EVTZ_DE,                                    // DE time zone, as possibly adjusted by op
+    EVTZ_UTC,                                   // UTC
+    EVTZ_LOCAL,                                 // system time zone database including daylight time, UNIX only
+    EVTZ_N
+} EventTZ;
+
 extern uint8_t de_time_fmt;
 extern void initTime(void);
 extern time_t nowWO(void);
@@ -657,6 +906,11 @@ extern void showClocks(void);
 extern void drawDXSunRiseSetInfo(void);
 extern int DEWeekday(void);
 extern int32_t utcOffset(void);
+extern time_t eventTime (time_t utc);
+extern void formatEventHM (time_t t, char *buf, size_t buf_len);
+extern const char *getEventTZName(void);
+extern bool getEvent12Hr(void);
+extern bool setEventTimes (const char *zone, int hours);
 extern const char *gpsd_server, *ntp_server;
 
 
@@ -688,6 +942,74 @@ extern void rgbtohsv(uint8_t *h, uint8_t *s, uint8_t *v, uint8_t r, uint8_t g, u
 
 
 
+/*********************************************************************************************
+ *
+ * cme.cpp
+ *
+ */
+
+extern bool updateCME (const SBox &box);
+
+
+
+
+/*********************************************************************************************
+ *
+ * crash.cpp
+ *
+ */
+
+#if defined(_IS_UNIX)
+extern void initCrashReport(void);
+extern void checkCrashState(void);
+extern void checkPrevCrash(void);
+#endif // _IS_UNIX
+
+
+
+
+/*********************************************************************************************
+ *
+ * dashboard.cpp
+ *
+ */
+
+#if defined(_IS_UNIX)
+extern bool getWiFiDashboard (WiFiClient *clientp, char name[]);
+#endif // _IS_UNIX
+
+
+
+
+/*********************************************************************************************
+ *
+ * databundle.cpp
+ *
+ */
+
+extern void saveDataBundle (const char *name, const char *data, size_t len);
+extern char *loadDataBundle (const char *name, time_t &fetched);
+
+
+
+
+/*********************************************************************************************
+ *
+ * doppler.cpp
+ *
+ */
+
+extern bool setDoppler (uint32_t hz, uint16_t port, const char *push);
+extern bool getDoppler (uint32_t &hz, uint16_t &port, char push[]);   // push is NV_DOPPLERPUSH_LEN
+extern bool getDopplerHz (uint32_t &hz);
+extern void checkDoppler(void);
+#if defined(_IS_UNIX)
+extern uint32_t dopplerShiftHz (uint32_t hz, float rate, bool uplink);
+#endif // _IS_UNIX
+
+
+
+
 /*********************************************************************************************
  *
  * dxcluster.cpp
@@ -695,27 +1017,81 @@ extern void rgbtohsv(uint8_t *h, uint8_t *s, uint8_t *v, uint8_t r, uint8_t g, u
  */
 
 #define MAX_SPOTCALL_LEN                12
+#define MAX_SPOTMODE_LEN                8
+#define MAX_SPOTTERS_LEN                40
+#define SPOT_NO_SNR                     (-128)
+#define SPOT_FADE_MIN                   0.3F    // brightness of a spot about to expire, full is 1
+#define MAX_SPOTMERGE                   60      // max spot merge window that may be set, minutes
 typedef struct {
     char call[MAX_SPOTCALL_LEN];        // call
+    char spotter[MAX_SPOTCALL_LEN];     // call of spotting station, the one with best snr if merged
+    char spotters[MAX_SPOTTERS_LEN];    // all spotters merged into this spot, comma separated, + if more
+    uint8_t n_spotters;                 // n spotters merged into this spot
+    char mode[MAX_SPOTMODE_LEN];        // mode noted in spot, if any
+    char entity[MAX_PREF_LEN+1];        // prefix of nearest entity
+    bool watched;                       // call is on the watch list
+    bool tuned;                         // freq is near the rig dial frequency
+    uint8_t qsl_users;                  // QSLU_ bits of the QSL services call is known to use
+    bool needed;                        // would fill a DXCC band and mode slot not yet worked
+    uint8_t source;                     // 0 main cluster or WSJT-X, else spotsources.cpp source 1 .. n
     float freq;                         // kHz
     char grid[MAID_CHARLEN];            // used only with WSJT-X
     uint16_t uts;                       // UT spotted
+    time_t spotted;                     // now() when received, for aging
+    int8_t snr;                         // dB report in comment, else SPOT_NO_SNR
     LatLong ll;                         // lat, long
     SBox map_b;                         // map label
+    bool on_map;                        // map label is drawn now
 } DXClusterSpot;
 
+// propagation path favored to a spot now
+typedef enum {
+    SPATH_SHORT,
+    SPATH_LONG,
+    SPATH_GRAY,
+} SpotPath;
+
 extern bool updateDXCluster(const SBox &box);
 extern void closeDXCluster(void);
+extern void getDXClusterHostInUse (const char **hostp, int *portp);
 extern bool checkDXClusterTouch (const SCoord &s, const SBox &box);
 extern bool getDXClusterSpots (DXClusterSpot **spp, uint8_t *nspotsp);
+extern SpotPath getSpotPath (const DXClusterSpot &spot);
+extern const char *getSpotPathName (SpotPath p);
+extern void setSpotAge (uint16_t mins);
+extern uint16_t getSpotAge (void);
+extern void setSpotMerge (uint8_t mins);
+extern uint8_t getSpotMerge (void);
 extern bool overAnyDXClusterSpots(const SCoord &s);
 extern void drawDXClusterSpotsOnMap (void);
 extern void updateDXClusterSpotScreenLocations(void);
 extern bool isDXClusterConnected(void);
 extern bool sendDXClusterDELLGrid(void);
+extern const char *getDXClusterSortName(void);
+extern bool setDXClusterSortName (const char *name);
+extern const char *getDXClusterWatchCalls(void);
+extern bool setDXClusterWatchCalls (const char *calls);
+extern const char *getDXAlertFilter(void);
+extern bool setDXAlertFilter (const char *spec, char *ynot, size_t ynot_len);
+extern void updateDXClusterSpotFlags(void);
+extern bool isDXClusterWatchedCall (const char *call);
+extern bool injectDXClusterSpot (float kHz, const char *call, const char *grid, const char *mode);
+extern void ackDXClusterWatchAlert(void);
+
+
+
+
+
 
 
+/*********************************************************************************************
+ *
+ * dxpeds.cpp
+ *
+ */
 
+extern bool updateDXPeds (const SBox &box);
+extern bool checkDXPedsTouch (const SCoord &s, const SBox &box);
 
 
 
@@ -792,7 +1168,6 @@ extern bool waiting4DXPath(void);
 extern void eraseSCircle (const SCircle &c);
 extern void eraseRSSBox (void);
 extern void drawMapMenu(void);
-extern bool segmentSpanOk (SCoord &s0, SCoord &s1);
 extern void roundLatLong (LatLong &ll);
 extern void initScreen(void);
 extern bool checkOnAir(void);
@@ -864,6 +1239,12 @@ extern bool isSatMoon(void);
 extern const char **getAllSatNames(void);
 extern int nextSatRSEvents (time_t **rises, time_t **sets);
 extern void showNextSatEvents (void);
+extern bool isSatPassVisible (time_t rise, time_t set);
+extern bool getSatVisAlert (void);
+extern void setSatVisAlert (bool on);
+extern bool getSatPassTrack (float az[], float el[], int max_pts, int &n_pts, bool &full_pass);
+extern void satConfigChanged (const char *name);
+extern bool getSatTopoAt (time_t t, float &az, float &el, float &range, float &rate);
 
 #define SAT_NOAZ        (-999)  // error flag
 #define SAT_MIN_EL      0.0F    // rise elevation
@@ -876,99 +1257,284 @@ extern void showNextSatEvents (void);
 
 /*********************************************************************************************
  *
- * gimbal.cpp
+ * eclipse.cpp
  *
  */
 
-extern void initGimbalGUI(const SBox &box);
-extern bool haveGimbal(void);
-extern void updateGimbal (void);
-extern bool checkGimbalTouch (const SCoord &s, const SBox &box);
-extern void stopGimbalNow(void);
-extern void closeGimbal(void);
-extern bool getGimbalWrapAz (float *azp);
+extern bool setEclipseDays (int days);
+extern int getEclipseDays(void);
+extern bool getNextEclipse (const char **kind, time_t &greatest);
+extern void formatEclipseCountdown (time_t t, char *buf, size_t buf_len);
+#if defined(_IS_UNIX)
+extern void drawEclipsePath(void);
+#endif // _IS_UNIX
+
 
 
 
+/*********************************************************************************************
+ *
+ * escalate.cpp
+ *
+ */
+
+extern void escalateAlert (const char *kind, const char *details);
+extern void ackAlertEscalations (const char *kind);
+#if defined(_IS_UNIX)
+extern void checkAlertEscalations(void);
+extern bool setAlertEscalation (const char *kind, int mins, const char *chain, char *ynot, size_t ynot_len);
+extern void printAlertEscalations (WiFiClient &client, const char *prefix);
+#endif // _IS_UNIX
+
+
 
 
 /*********************************************************************************************
  *
- * gpsd.cpp
+ * netmon.cpp
  *
  */
 
-extern bool getGPSDLatLong(LatLong *llp);
-extern time_t getGPSDUTC(const char **server);
+typedef enum {
+    NET_OK,                             // backend answers normally
+    NET_NOLINK,                         // no network link
+    NET_DNS,                            // backend name does not resolve
+    NET_PORTAL,                         // web requests are intercepted by a captive portal
+    NET_NOSERVER,                       // backend does not answer
+} NetState;
+
+extern NetState checkNetState(void);
+extern NetState pollNetState(void);
+extern NetState getNetState(void);
+extern const char *getNetStateName (NetState ns);
+
 
 
 
 /*********************************************************************************************
  *
- * setup.cpp
+ * eme.cpp
  *
  */
 
+extern bool updateEME (const SBox &box);
 
-extern void clockSetup(void);
-extern const char *getWiFiSSID(void);
-extern const char *getWiFiPW(void);
-extern const char *getCallsign(void);
-extern const char *getDXClusterHost(void);
-extern int getDXClusterPort(void);
-extern bool useMetricUnits(void);
-extern bool useGeoIP(void);
-extern bool useGPSD(void);
-extern bool mapDXClusterSpots(void);
-extern bool plotSpotCallsigns(void);
-extern bool rotateScreen(void);
-extern float getBMETempCorr(int i);
-extern float getBMEPresCorr(int i);
-extern const char *getGPSDHost(void);
-extern bool useLocalNTPHost(void);
-extern bool GPIOOk(void);
-extern const char *getLocalNTPHost(void);
-extern bool useDXCluster(void);
-extern uint32_t getKX3Baud(void);
-extern void drawStringInBox (const char str[], const SBox &b, bool inverted, uint16_t color);
-extern bool logUsageOk(void);
-extern uint16_t getSatPathColor(void);
-extern uint16_t getSatFootColor(void);
-extern uint16_t getShortPathColor(void);
-extern uint16_t getLongPathColor(void);
-extern uint16_t getAntennaHeadingColor(void);
-extern uint16_t getAntennaBackColor(void);
-extern uint8_t getBrMax(void);
-extern uint8_t getBrMin(void);
-extern bool getX11FullScreen(void);
-extern bool latSpecIsValid (const char *lng_spec, float &lng);
-extern bool lngSpecIsValid (const char *lng_spec, float &lng);
-extern bool getDemoMode(void);
-extern void setDemoMode(bool on);
-extern uint16_t getGridColor(void);
-extern int16_t getCenterLng(void);
-extern void setCenterLng(int16_t);
 
 
 
+/*********************************************************************************************
+ *
+ * flares.cpp
+ *
+ */
+
+extern bool updateFlares (const SBox &box);
 
 
 
 
 /*********************************************************************************************
  *
- * magdecl.cpp
+ * gcarc.cpp
  *
  */
 
-extern int magdecl (float l, float L, float e, float y, float *mdp);
+// one great circle arc ready to draw
+typedef struct {
+    LatLong from;                       // starting location
+    float bear;                         // initial bearing from from, rads E of N
+    float dist;                         // arc length, rads, 2pi for a full circle
+    float split;                        // distance along arc where color changes to color2, rads
+    uint16_t color, color2;             // colors before and after split
+    uint8_t width;                      // line width, app pixels
+} GCArc;
+
+extern void initGCArc (GCArc &arc, const LatLong &from, float bear, float dist, uint16_t color, uint8_t width);
+extern uint16_t drawGCArc (const GCArc &arc, SCoord *&path);
+extern void eraseGCArc (const SCoord *path, uint16_t n_path, uint8_t width);
 
 
 
 
 /*********************************************************************************************
  *
- * mapmanage.cpp
+ * geoutil.cpp
+ *
+ */
+
+#define GEO_MAXPIECES   4               // max pieces from geoClipSegment()
+
+extern void geoLerpLL (const LatLong &a, const LatLong &b, float t, LatLong &m);
+extern bool geoCrossesPole (const LatLong &a, const LatLong &b);
+extern bool geoSameSheet (const LatLong &a, const LatLong &b);
+extern int geoClipSegment (const LatLong &a, const LatLong &b, uint8_t edge, SCoord s0[], SCoord s1[]);
+extern void drawGeoSegment (const LatLong &a, const LatLong &b, uint8_t width, uint16_t color);
+extern bool segmentSpanOk (SCoord &s0, SCoord &s1);
+
+
+
+
+/*********************************************************************************************
+ *
+ * gimbal.cpp
+ *
+ */
+
+extern void initGimbalGUI(const SBox &box);
+extern bool haveGimbal(void);
+extern void updateGimbal (void);
+extern bool checkGimbalTouch (const SCoord &s, const SBox &box);
+extern void stopGimbalNow(void);
+extern void closeGimbal(void);
+extern bool getGimbalWrapAz (float *azp);
+
+
+
+
+
+/*********************************************************************************************
+ *
+ * gpsd.cpp
+ *
+ */
+
+extern bool getGPSDLatLong(LatLong *llp);
+extern time_t getGPSDUTC(const char **server);
+
+
+
+
+/*********************************************************************************************
+ *
+ * health.cpp
+ *
+ */
+
+extern void checkHealth(void);
+extern void pingSystemd(void);
+extern bool getHealth (char *report, size_t report_len);
+
+
+
+/*********************************************************************************************
+ *
+ * setup.cpp
+ *
+ */
+
+// how the mercator map center longitude is chosen
+typedef enum {
+    MCM_FIXED,                          // setup center longitude
+    MCM_DE,                             // DE longitude
+    MCM_ZERO,                           // prime meridian
+    MCM_SUN,                            // follow the sun, keeping local noon centered
+    MCM_N
+} MapCenterMode;
+
+#define MAPCENTER_KEY   ('O'-'@')               // hot key to cycle MapCenterMode: Control-O
+
+
+extern void clockSetup(void);
+extern const char *getWiFiSSID(void);
+extern const char *getWiFiPW(void);
+extern const char *getCallsign(void);
+extern const char *getDXClusterHost(void);
+extern int getDXClusterPort(void);
+extern bool useMetricUnits(void);
+extern bool useGeoIP(void);
+extern bool useGPSD(void);
+extern bool mapDXClusterSpots(void);
+extern bool plotSpotCallsigns(void);
+extern bool rotateScreen(void);
+extern float getBMETempCorr(int i);
+extern float getBMEPresCorr(int i);
+extern const char *getGPSDHost(void);
+extern bool useLocalNTPHost(void);
+extern bool GPIOOk(void);
+extern const char *getLocalNTPHost(void);
+extern bool useDXCluster(void);
+extern uint32_t getKX3Baud(void);
+extern void drawStringInBox (const char str[], const SBox &b, bool inverted, uint16_t color);
+extern bool logUsageOk(void);
+extern uint16_t getSatPathColor(void);
+extern uint16_t getSatFootColor(void);
+extern uint16_t getShortPathColor(void);
+extern uint16_t getLongPathColor(void);
+extern uint16_t getAntennaHeadingColor(void);
+extern uint16_t getAntennaBackColor(void);
+extern uint8_t getBrMax(void);
+extern uint8_t getBrMin(void);
+extern bool getX11FullScreen(void);
+extern int getX11AAQuality(void);
+extern bool latSpecIsValid (const char *lng_spec, float &lng);
+extern bool lngSpecIsValid (const char *lng_spec, float &lng);
+extern bool placeSpecIsValid (const char *place_spec, LatLong &ll);
+extern bool getDemoMode(void);
+extern void setDemoMode(bool on);
+extern uint16_t getGridColor(void);
+extern int16_t getCenterLng(void);
+extern void setCenterLng(int16_t);
+extern MapCenterMode getMapCenterMode(void);
+extern const char *getMapCenterModeName(void);
+extern bool setMapCenterModeName (const char *name, bool &moved);
+extern void cycleMapCenterMode(void);
+extern void checkMapCenter(void);
+
+
+
+
+
+
+
+/*********************************************************************************************
+ *
+ * lang.cpp
+ *
+ */
+
+extern const char *xlate (const char *english);
+extern bool setLanguage (const char *code);
+extern const char *getLanguage(void);
+extern void initLanguage(void);
+
+
+
+
+
+
+
+/*********************************************************************************************
+ *
+ * localk.cpp
+ *
+ */
+
+extern void checkLocalK(void);
+extern bool setLocalKURL (const char *url);
+extern const char *getLocalKURL(void);
+extern bool setLocalK (float k, const char *station);
+extern bool getLocalK (float &k, time_t &age, const char **station);
+
+
+
+
+
+
+
+/*********************************************************************************************
+ *
+ * magdecl.cpp
+ *
+ */
+
+extern int magdecl (float l, float L, float e, float y, float *mdp);
+
+
+
+
+/*********************************************************************************************
+ *
+ * mapmanage.cpp
  *
  */
 
@@ -1023,6 +1589,18 @@ extern FS_Info *getConfigDirInfo (int *n_info, char **fs_name, uint64_t *fs_size
 
 
 
+/*********************************************************************************************
+ *
+ * mdns.cpp
+ *
+ */
+
+extern void initMDNS(void);
+extern void checkMDNS(void);
+
+
+
+
 /*********************************************************************************************
  *
  * menu.cpp
@@ -1091,6 +1669,7 @@ extern void updateBeacons (bool erase_too, bool immediate, bool force);
 extern void updateBeaconScreenLocations(void);
 extern bool overAnyBeacon (const SCoord &s);
 extern void drawBeaconBox();
+extern bool updateBeaconPane (const SBox &box);
 
 typedef uint8_t BeaconID;
 
@@ -1148,7 +1727,7 @@ typedef enum {
     NV_METRIC_ON,               // whether to use metric or imperical values
     NV_LKSCRN_ON,               // whether screen lock is on
     NV_AZIMUTHAL_ON,            // whether map is azimuthal or mercator
-    NV_ROTATE_SCRN,             // whether to flip screen
+    NV_ROTATE_SCRN,             // whether to flip screen 180 degrees
 
     NV_WIFI_SSID,               // WIFI SSID
     NV_WIFI_PASSWD_OLD,         // deprecated
@@ -1216,6 +1795,48 @@ typedef enum {
     NV_ANTENNAHEADINGCOLOR,     // Antenna heading color
     NV_ANTENNABACKCOLOR,        // Antenna backside color
 
+    NV_SCREENSHOTMINS,          // minutes between periodic screenshots, 0 for off
+    NV_ALERTSTYLE,              // AlertStyle
+    NV_EVENTTIMES,              // EventTZ and 12 hour flag for showing scheduled events
+    NV_SWXHISTDAYS,             // days to keep space weather history, 0 for none
+    NV_SATVISALERT,             // whether sat rise alarm is only for visible passes
+    NV_PANE1ROTSETHI,           // PlotChoice bitmask of pane 1 rotation choices, upper 32 bits
+    NV_PANE2ROTSETHI,           // PlotChoice bitmask of pane 2 rotation choices, upper 32 bits
+    NV_PANE3ROTSETHI,           // PlotChoice bitmask of pane 3 rotation choices, upper 32 bits
+    NV_BPREGION,                // IARU region of band plan pane, 1..3
+    NV_BPBAND,                  // index of band shown in band plan pane
+    NV_DXSPOTSORT,              // DX cluster spot table sort order
+    NV_WATCHCALLS,              // space separated calls to watch for in DX cluster spots
+    NV_MAPCENTER,               // MapCenterMode
+    NV_MAPLAYEROPAC,            // 4 bits per MapLayer opacity, 0..10 tenths
+    NV_MAPLAYERORDER,           // 3 bits per MapLayer overlay, in drawing order from the bottom
+    NV_SWDISPLAY,               // SWDisplayState showing when last changed, restored at startup
+    NV_WATCHACKCALL,            // DX cluster watched call whose alert was last acknowledged
+    NV_WATCHACKTIME,            // unix time of NV_WATCHACKCALL acknowledgement
+    NV_BR_WAKE,                 // least urgent alert kind that turns the display on, see wake_names[]
+    NV_CONTESTSTART,            // UTC start of contest period shown by stopwatch, 0 if none
+    NV_CONTESTEND,              // UTC end of contest period shown by stopwatch, 0 if none
+    NV_RIGHOST,                 // hamlib rigctld host polled for PTT, empty if none
+    NV_RIGPORT,                 // hamlib rigctld port
+    NV_SWRALERT,                // SWR alert level, tenths, 0 for none
+    NV_LANGUAGE,                // on-screen language code, see lang.cpp
+    NV_TEMPUNITS,               // show temperatures in C, else F
+    NV_SPOTTIME,                // show DX spot times in DE local time, else UTC
+    NV_FREQFMT,                 // frequency display: 0x80 for MHz else kHz, | n decimals
+    NV_DOPPLERHZ,               // nominal satellite downlink frequency for doppler, Hz, 0 if none
+    NV_DOPPLERPORT,             // doppler rigctld-style server port, 0 if off
+    NV_DOPPLERPUSH,             // host:port of SDR rigctld-style server to push doppler to, empty if none
+    NV_WEBSDR,                  // "kind url" of KiwiSDR or WebSDR receiver for spot links, empty if none
+    NV_SPOTAGE,                 // minutes for DX spots to fade and expire, 0 never
+    NV_ECLIPSEDAYS,             // days ahead to show the next solar eclipse on the map, 0 never
+    NV_OFFLINE,                 // 1 to stay offline regardless of connectivity, 0 to follow it
+    NV_QUIETHOURS,              // 7 2-byte quiet start times then 7 end times, each mins from DE midnight
+    NV_QUIETKEEP,               // AlertSeverity still notifying during quiet hours, ALSEV_N for none
+    NV_AURORAALERT,             // whether to alert when aurora may be visible from DE
+    NV_LOCALKURL,               // url of local magnetometer K index page, empty if none
+    NV_SPOTMERGE,               // minutes within which repeat DX spots are merged, 0 never
+    NV_DXALERTFILT,             // DX watch alert filter, eg cont=OC,AF&band=40m, empty for none
+
     NV_N
 } NV_Name;
 
@@ -1232,6 +1853,15 @@ typedef enum {
 #define NV_DAILYONOFF_LEN       28      // (2*DAYSPERWEEK*sizeof(uint16_t))
 #define NV_DE_GRID_LEN          MAID_CHARLEN
 #define NV_DX_GRID_LEN          MAID_CHARLEN
+#define NV_WATCHCALLS_LEN       60
+#define NV_WATCHACKCALL_LEN     MAX_SPOTCALL_LEN
+#define NV_RIGHOST_LEN          26
+#define NV_LANGUAGE_LEN         8
+#define NV_DOPPLERPUSH_LEN      32
+#define NV_WEBSDR_LEN           64
+#define NV_QUIETHOURS_LEN       28      // (2*DAYSPERWEEK*sizeof(uint16_t))
+#define NV_LOCALKURL_LEN        64
+#define NV_DXALERTFILT_LEN      48
 
 
 // accessor functions
@@ -1269,6 +1899,79 @@ extern void getNVMaidenhead (NV_Name nv, char maid[MAID_CHARLEN]);
 
 
 
+/*********************************************************************************************
+ *
+ * imgcache.cpp
+ *
+ */
+
+// what a server told us about a page so we can later ask whether it changed, see httpGET()
+typedef struct {
+    uint32_t lastmod;                           // Last-Modified as UNIX time, 0 if unknown
+    char etag[80];                              // ETag including quotes, empty if unknown
+} HTTPValidators;
+
+extern FILE *openImageCache (const char *url, int ttl, HTTPValidators &v);
+extern bool getImageCacheValidators (const char *url, HTTPValidators &v);
+extern FILE *renewImageCache (const char *url);
+extern FILE *createImageCache (const char *url);
+extern void commitImageCache (const char *url, FILE *fp, bool ok, const HTTPValidators &v);
+extern void getImageCacheStats (int &hits, int &renews, int &misses);
+
+
+
+
+/*********************************************************************************************
+ *
+ * ionosonde.cpp
+ *
+ */
+
+extern bool updateIonosonde (const SBox &box);
+extern bool refreshIonosonde (const char *&ynot);
+extern bool getIonosondeCorrection (float &ratio, LatLong &ll, time_t &age);
+
+
+
+
+/*********************************************************************************************
+ *
+ * outlook.cpp
+ *
+ */
+
+extern bool updateOutlook (const SBox &box, bool fetch);
+extern bool checkOutlookTouch (const SCoord &s, const SBox &box);
+
+
+
+
+/*********************************************************************************************
+ *
+ * passplan.cpp
+ *
+ */
+
+#if defined(_IS_UNIX)
+extern bool getWiFiPassPlan (WiFiClient *clientp, char line[]);
+#endif // _IS_UNIX
+
+
+
+
+/*********************************************************************************************
+ *
+ * perfhud.cpp
+ *
+ */
+
+#if defined(_IS_UNIX)
+extern void checkPerfHUD (char kbc);
+#endif // _IS_UNIX
+
+
+
+
 /*********************************************************************************************
  *
  * plot.cpp
@@ -1285,6 +1988,7 @@ extern bool plotXY (const SBox &box, float x[], float y[], int nxy, const char *
         const char *ylabel, uint16_t color, float y_min, float y_max, float big_value);
 extern bool plotXYstr (const SBox &box, float x[], float y[], int nxy, const char *xlabel,
         const char *ylabel, uint16_t color, float y_min, float y_max, char *label_str);
+extern void plotTrend (const SBox &box, float delta, int prec);
 extern void plotWX (const SBox &b, uint16_t color, const WXInfo &wi);
 extern void plotMessage (const SBox &b, uint16_t color, const char *message);
 extern void plotNOAASWx (const SBox &box, const NOAASpaceWx &noaaspw);
@@ -1304,7 +2008,7 @@ extern SBox plot_b[PANE_N];                     // box for each pane
 extern PlotChoice plot_ch[PANE_N];              // current choice in each pane
 extern const char *plot_names[PLOT_CH_N];       // must be in same order as PlotChoice
 extern time_t next_rotationT[PANE_N];           // time of next rotation, iff > 1 bit set in rotset[i]
-extern uint32_t plot_rotset[PANE_N];            // bitmask of all PlotChoice rotation choices
+extern uint64_t plot_rotset[PANE_N];            // bitmask of all PlotChoice rotation choices
                                                 // N.B. rotset[i] must always include plot_ch[i]
 
 #define PLOT_ROT_INTERVAL       30              // rotation interval, secs
@@ -1323,6 +2027,9 @@ extern void showRotatingBorder (bool soon, PlotPane pp);
 extern void initPlotPanes(void);
 extern void savePlotOps(void);
 extern bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color);
+extern bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color, uint32_t *lastmodp);
+extern bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color, uint32_t *lastmodp,
+    int cache_ttl);
 extern bool waitForTap (const SBox &inbox, const SBox &outbox, bool (*fp)(void), uint32_t to_ms, SCoord &tap);
 
 
@@ -1336,6 +2043,84 @@ extern bool waitForTap (const SBox &inbox, const SBox &outbox, bool (*fp)(void),
  */
 
 extern bool nearestPrefix (const LatLong &ll, char prefix[MAX_PREF_LEN+1]);
+extern const char *llContinent (const LatLong &ll);
+
+
+
+
+/*********************************************************************************************
+ *
+ * qrcode.cpp
+ *
+ */
+
+#define QR_MAXN         37                      // max modules on a side, version 5
+
+extern int makeQRCode (const char *text, uint8_t qr[QR_MAXN][QR_MAXN]);
+extern bool drawQRCode (const char *text, const SBox &box);
+
+
+
+
+/*********************************************************************************************
+ *
+ * qslusers.cpp
+ *
+ */
+
+#define QSLU_LOTW       0x01                    // uploads to LoTW
+#define QSLU_EQSL       0x02                    // eQSL Authenticity Guaranteed member
+
+extern void checkQSLUsers (void);
+extern uint8_t getQSLUserFlags (const char *call);
+#if defined(_IS_UNIX)
+extern void printQSLUsers (WiFiClient &client, const char *prefix);
+#endif // _IS_UNIX
+
+
+
+
+/*********************************************************************************************
+ *
+ * qsorate.cpp
+ *
+ */
+
+extern void noteLoggedQSO (time_t t, float kHz, const char *mode);
+extern void resetQSORate(void);
+extern bool haveQSORate(void);
+extern void getQSORates (int &last10, int &last100, uint32_t &session);
+extern bool updateQSORate (const SBox &box);
+#if defined(_IS_UNIX)
+#define MAX_ADIF_FIELD  40                      // longest ADIF field value we keep
+#define MAX_ADIF_NAMES  16                      // most fields one ADIF reader may ask for
+typedef char ADIFValue[MAX_ADIF_FIELD+1];
+typedef void (*ADIFRecordCB) (ADIFValue values[]);
+extern void setQSOLogFile (const char *fn);
+extern const char *getQSOLogFile (void);
+extern long readADIFRecords (FILE *fp, long pos, long size, const char *names[], int n_names, ADIFRecordCB cb);
+extern float crackADIFkHz (const char *freq, const char *band);
+#endif // _IS_UNIX
+
+
+
+
+/*********************************************************************************************
+ *
+ * quiethours.cpp
+ *
+ */
+
+extern AlertSeverity alertSeverity (const char *kind);
+extern const char *alertSeverityName (int sev);
+extern bool crackAlertSeverity (const char *name, int &sev);
+extern bool setQuietHours (int dow, uint16_t start_mins, uint16_t end_mins);
+extern void getQuietHours (int dow, uint16_t &start_mins, uint16_t &end_mins);
+extern void setQuietKeep (int sev);
+extern int getQuietKeep(void);
+extern bool quietHoursNow(void);
+extern bool alertIsQuiet (const char *kind);
+extern bool alertIsQuiet (const char *kind, AlertSeverity sev);
 
 
 
@@ -1347,6 +2132,13 @@ extern bool nearestPrefix (const LatLong &ll, char prefix[MAX_PREF_LEN+1]);
  */
 
 void setRadioSpot (float kHz);
+bool getRadioSpot (float &kHz);
+extern bool setRigctld (const char *host, uint16_t port);
+extern bool getRigctld (char host[NV_RIGHOST_LEN], uint16_t &port);
+extern bool getRigPTT(void);
+extern bool getRigFrequency (float &kHz);
+extern void noteTransmitting (bool on);
+extern void getTransmitTime (uint32_t &total_secs, int &duty_pct);
 
 
 
@@ -1372,6 +2164,147 @@ extern SBox santa_b;
 
 
 
+/*********************************************************************************************
+ *
+ * satconfig.cpp
+ *
+ */
+
+// preferred satellite operating mode, N.B. must match mode_names[]
+typedef enum {
+    SATMODE_ANY,
+    SATMODE_FM,
+    SATMODE_SSB,
+    SATMODE_CW,
+    SATMODE_DATA,
+    SATMODE_N
+} SatMode;
+
+#define SATCFG_DEFPRI   5               // default priority
+#define SATCFG_MAXPRI   9               // max priority, listed first in the picker
+#define SATCFG_MAXEL    60              // max pass elevation threshold, degrees
+
+// settings for one satellite
+typedef struct {
+    char name[NV_SATNAME_LEN];          // as from the sat server, blanks are underscores
+    bool enabled;                       // whether it may be tracked
+    uint8_t priority;                   // 0 .. SATCFG_MAXPRI
+    uint8_t min_el;                     // lowest elevation that counts as a pass, degrees
+    uint8_t mode;                       // SatMode
+} SatConfig;
+
+extern const char *getSatModeName (uint8_t mode);
+extern bool crackSatMode (const char *name, uint8_t &mode);
+extern void getSatConfig (const char *name, SatConfig &sc);
+extern bool setSatConfig (const SatConfig &sc);
+extern bool satIsEnabled (const char *name);
+extern float getSatMinEl (const char *name);
+extern void printSatConfigs (WiFiClient &client);
+
+
+
+
+/*********************************************************************************************
+ *
+ * satsky.cpp
+ *
+ */
+
+extern bool haveSatSky(void);
+extern bool updateSatSky (const SBox &box);
+
+
+
+/*********************************************************************************************
+ *
+ * maplayers.cpp
+ *
+ */
+
+// layers drawn on the map, night is always the lowest, the others are overlays in any order
+typedef enum {
+    MAPL_NIGHT,                         // night side shading
+    MAPL_GRID,                          // grid lines
+    MAPL_PATH,                          // DE heading path
+    MAPL_SAT,                           // satellite path, footprint and name
+    MAPL_SYMBOLS,                       // sun, moon, beacons, markers and spots
+    MAPL_AURORA,                        // NOAA OVATION aurora probability
+    MAPL_N
+} MapLayer;
+#define MAPL_NOVER      (MAPL_N-1)      // n overlays above night
+
+#if defined(_IS_UNIX)
+extern const char *getMapLayerName (MapLayer ml);
+extern float getMapLayerOpacity (MapLayer ml);
+extern MapLayer getMapOverlay (int i);
+extern bool setMapLayerOpacity (const char *name, int percent);
+extern bool setMapOverlayOrder (const char *names);
+extern void checkMapLayerKey (char kbc);
+#endif // _IS_UNIX
+extern bool beginMapLayer (MapLayer ml, float *prev);
+extern void endMapLayer (float prev);
+
+
+
+/*********************************************************************************************
+ *
+ * plugins.cpp
+ *
+ */
+
+extern void initPlugins(void);
+extern bool havePlugins(void);
+extern bool updatePlugins (const SBox &box);
+extern bool checkPluginsTouch (const SCoord &s, const SBox &box);
+
+
+
+/*********************************************************************************************
+ *
+ * scripts.cpp
+ *
+ */
+
+extern void runEventScript (const char *event, ...);
+extern void checkEventScripts(void);
+
+
+
+/*********************************************************************************************
+ *
+ * screenshot.cpp
+ *
+ */
+
+extern void beginScreenRead(void);
+extern uint16_t readScreenPixel(void);
+#if defined(_IS_UNIX)
+extern uint8_t *captureScreenPNG (size_t *n_png, int shrink);
+extern uint8_t *encodePNG (const uint8_t *rgb, uint32_t ncols, uint32_t nrows, size_t *n_png);
+extern bool saveScreenshot (char *fn, size_t fn_len);
+extern bool setScreenshotPeriod (int mins);
+extern int getScreenshotPeriod(void);
+extern void checkScreenshots(char kbc);
+#endif // _IS_UNIX
+
+
+
+
+/*********************************************************************************************
+ *
+ * share.cpp
+ *
+ */
+
+#if defined(_IS_UNIX)
+extern bool getWiFiSharePNG (WiFiClient *clientp, char line[]);
+extern bool getWiFiShareJSON (WiFiClient *clientp, char line[]);
+extern bool saveShareCard (char *fn, size_t fn_len);
+#endif // _IS_UNIX
+
+
+
+
 /*********************************************************************************************
  *
  * selectFont.cpp
@@ -1395,6 +2328,34 @@ typedef enum {
 } FontSize;
 
 extern void selectFontStyle (FontWeight w, FontSize s);
+extern void foldUTF8 (char *str);
+
+
+
+
+/*********************************************************************************************
+ *
+ * swxhistory.cpp
+ *
+ */
+
+// kinds of space weather history, N.B. must match swxh_names[]
+typedef enum {
+    SWXH_SSN,
+    SWXH_SFI,
+    SWXH_KP,
+    SWXH_SWIND,
+    SWXH_XRAY,
+    SWXH_DRAP,
+    SWXH_N
+} SWxHistKind;
+
+extern void recordSWxSample (SWxHistKind kind, float value);
+extern int getSWxHistory (SWxHistKind kind, time_t from, time_t **times, float **values);
+extern int getSWxHistoryDays(void);
+extern bool setSWxHistoryDays (int days);
+extern const char *getSWxHistName (SWxHistKind kind);
+extern bool findSWxHistKind (const char *name, SWxHistKind &kind);
 
 
 
@@ -1409,6 +2370,70 @@ extern void solveSphere (float A, float b, float cc, float sc, float *cap, float
 
 
 
+/*********************************************************************************************
+ *
+ * spotparse.cpp
+ *
+ */
+
+typedef struct {
+    char spotter[MAX_SPOTCALL_LEN];     // call of spotting station
+    char call[MAX_SPOTCALL_LEN];        // DX call
+    float kHz;                          // frequency
+    char mode[MAX_SPOTMODE_LEN];        // mode in comment, else ""
+    char grid[MAID_CHARLEN];            // DX grid in comment, else ""
+    int snr;                            // dB report in comment, else SPOT_NO_SNR
+    int ut;                             // HHMM UT, else -1
+} DXSpotLine;
+
+extern bool crackDXSpotLine (const char *line, DXSpotLine &sl);
+extern void addDXClusterSourceSpot (const SBox &box, uint8_t source, DXSpotLine &sl);   // in dxcluster.cpp
+
+
+
+
+/*********************************************************************************************
+ *
+ * soak.cpp
+ *
+ */
+
+#if defined(_IS_UNIX)
+extern bool setSoakTest (const char *spec, char ynot[], size_t ynot_len);
+#endif // _IS_UNIX
+extern void checkSoakTest(void);
+
+
+
+
+/*********************************************************************************************
+ *
+ * spotsources.cpp
+ *
+ */
+
+extern void pollSpotSources (const SBox &box);
+extern void closeSpotSources (void);
+extern const char *getSpotSourceLabel (uint8_t source);
+extern uint16_t getSpotSourceColor (uint8_t source);
+#if defined(_IS_UNIX)
+extern void printSpotSources (WiFiClient &client, const char *prefix);
+#endif // _IS_UNIX
+
+
+
+
+/*********************************************************************************************
+ *
+ * testalert.cpp
+ *
+ */
+
+extern bool sendTestAlert (const char *kind, int sev, bool &quiet, char *ynot, size_t ynot_len);
+
+
+
+
 /*********************************************************************************************
  *
  * touch.cpp
@@ -1464,9 +2489,18 @@ typedef enum {
     ALMS_RINGING
 } AlarmState;
 
+// how alerts draw attention to their pane or icon, N.B. must match alert_style_names[]
+typedef enum {
+    ALERT_FLASH,                                // blink
+    ALERT_PULSE,                                // calmly pulse pane border, no blinking
+    ALERT_STEADY,                               // highlight pane border, no motion at all
+    ALERT_N
+} AlertStyle;
+
 extern SBox stopwatch_b;                        // clock icon on main display
 
 extern void initStopwatch(void);
+extern void restoreSWDisplay(void);
 extern void checkStopwatchTouch(TouchType tt);
 extern bool runStopwatch(void);
 extern void drawMainPageStopwatch (bool force);
@@ -1476,12 +2510,34 @@ extern SWDisplayState getSWDisplayState (void);
 extern void getAlarmState (AlarmState &as, uint16_t &hr, uint16_t &mn);
 extern void setAlarmState (const AlarmState &as, uint16_t hr, uint16_t mn);
 extern SWBCBits getBigClockBits(void);
+extern const char *getAlertStyleName(void);
+extern bool setAlertStyle (const char *name);
+extern void drawAlertBorder (const SBox &b, uint16_t color, bool alerting);
+extern void setContestPeriod (time_t start, time_t end);
+extern bool getContestPeriod (time_t &start, time_t &end);
+extern void checkStopwatchKey (char kbc);
 
 
 
 
 
 
+/*********************************************************************************************
+ *
+ * txmeter.cpp
+ *
+ */
+
+extern void setSWRAlert (float level);
+extern float getSWRAlert(void);
+extern void noteTxMeter (float watts, float swr);
+extern bool getTxMeter (float &watts, float &swr);
+extern bool haveTxMeter(void);
+extern bool updateTxMeter (const SBox &box, bool all);
+
+
+
+
 /*********************************************************************************************
  *
  * tz.cpp
@@ -1491,6 +2547,44 @@ extern int32_t getTZ (const LatLong &ll);
 
 
 
+/*********************************************************************************************
+ *
+ * units.cpp
+ *
+ */
+
+extern bool showTempC(void);
+extern void setTempC (bool c);
+extern float tempFromC (float c);
+extern char tempUnits(void);
+extern void setDistKm (bool km);
+extern float distFromMiles (float miles);
+extern const char *distUnits(void);
+extern float speedFromMps (float mps);
+extern const char *speedUnits(void);
+extern bool showSpotLocalTime(void);
+extern void setSpotLocalTime (bool local);
+extern uint16_t spotTime (uint16_t uts);
+extern const char *spotTimeZone(void);
+extern bool setFreqFormat (bool mhz, int decimals);
+extern void getFreqFormat (bool &mhz, int &decimals);
+extern const char *freqUnits(void);
+extern void formatFreq (char buf[], size_t buf_len, float kHz, int width);
+
+
+
+/*********************************************************************************************
+ *
+ * websdr.cpp
+ *
+ */
+
+extern bool setWebSDR (const char *kind, const char *url);
+extern bool getWebSDR (const char **kind, const char **url);
+extern bool getSpotSDRLink (const DXClusterSpot &spot, char link[], size_t link_len);
+
+
+
 /*********************************************************************************************
  *
  * webserver.cpp
@@ -1502,6 +2596,24 @@ extern void checkWebServer(void);
 extern TouchType readCalTouchWS (SCoord &s);
 extern const char platform[];
 extern void runNextDemoCommand(void);
+extern bool runInternalWebCommand (const char *cmd);
+
+
+
+/*********************************************************************************************
+ *
+ * websocket.cpp
+ *
+ */
+
+#if defined(_IS_UNIX)
+extern bool acceptWebSocket (WiFiClient &client, const char *key);
+extern void base64Encode (const uint8_t *in, size_t len, char *out);
+#endif // _IS_UNIX
+extern void checkWebSockets(void);
+extern void sendWebSocketSpot (const DXClusterSpot &spot);
+extern void sendWebSocketAlert (const char *kind, const char *details);
+extern int nWebSockets(void);
 
 
 
@@ -1518,10 +2630,68 @@ typedef struct {
 } NTPServer;
 #define NTP_TOO_LONG 5000U                      // too long response time, millis()
 
+// why a data source last failed to refresh
+typedef enum {
+    FE_NONE,                                    // no failure yet
+    FE_CONNECT,                                 // could not connect to server
+    FE_HTTP,                                    // server answered with an HTTP error status
+    FE_HEADER,                                  // response header was incomplete
+    FE_SHORT,                                   // response body timed out or was cut short
+    FE_DATA,                                    // response could not be used
+} FetchErrorKind;
+
+typedef struct {
+    FetchErrorKind kind;                        // what went wrong
+    int http_status;                            // HTTP status, 0 if none
+    bool retryable;                             // whether trying again soon may help
+    time_t t;                                   // when, 0 if never
+    char page[60];                              // page being fetched, "" if not requested
+    char detail[40];                            // message shown in its pane, if any
+} FetchError;
+
+typedef struct {
+    const char *name;                           // name in refresh.txt and get_refresh.txt
+    int def_secs;                               // default interval, secs
+    int min_secs;                               // shortest interval allowed, secs
+    int secs;                                   // interval in use, secs
+    time_t last_ok;                             // time of last successful refresh, 0 if never
+    int n_fails;                                // consecutive failed refreshes
+    FetchError err;                             // most recent failure
+} RefreshSource;
+
+// latest space weather announced by the DX cluster in WWV or WCY lines, values < 0 are not known
+typedef struct {
+    time_t t;                                   // when received, 0 if never
+    char node[12];                              // announcing node
+    float sfi, a, k, ssn;                       // solar flux, A and K indices, sunspot number
+    char aurora[8];                             // WCY Au= value, empty if not known
+    bool differs;                               // set if disagrees with fresh backend values
+} ClusterSpaceWx;
+
+// everything we know about space weather, see getSpaceWeather(). values never read are zero and ancient.
+typedef struct {
+    SPWxValue ssn, flux, kp, swind, drap, protons;
+    SPWxValue xray;                             // X-ray flux, W/m^2
+    char xray_class[10];                        // xray as a flare class, eg M1.2
+    NOAASpaceWx noaaspw;                        // NOAA R, S and G scales
+    time_t noaaspw_age;                         // secs old
+    float pathrel[PROP_MAP_N];                  // VOACAP DE-DX path reliability, 0..1
+    time_t pathrel_age;                         // secs old
+    char flare_class[8];                        // largest flare in the last 24 hours, "" if none
+    time_t flare_peak;                          // its peak time
+    time_t flares_age;                          // secs since flares were read
+    time_t cme_arrival;                         // soonest predicted CME arrival not long past, 0 if none
+    int cme_speed;                              // its speed, km/s
+    time_t cmes_age;                            // secs since CMEs were read
+    ClusterSpaceWx cluster;                     // latest from DX cluster WWV or WCY
+} SpaceWeather;
+
 
 extern void initSys (void);
 extern void initWiFiRetry(void);
 extern void newBC(void);
+extern void newIonosonde(void);
+extern void newEME(void);
 extern void newVOACAPMap(PropMapSetting pm);
 extern void newCoreMap(CoreMaps cm);
 extern void updateWiFi(void);
@@ -1535,16 +2705,40 @@ extern bool getTCPLine (WiFiClient &client, char line[], uint16_t line_len, uint
 extern void sendUserAgent (WiFiClient &client);
 extern bool wifiOk(void);
 extern void httpGET (WiFiClient &client, const char *server, const char *page);
+extern void httpGET (WiFiClient &client, const char *server, const char *page, const HTTPValidators *vp);
 extern bool httpSkipHeader (WiFiClient &client);
 extern bool httpSkipHeader (WiFiClient &client, uint32_t *lastmodp);
+extern bool httpSkipHeader (WiFiClient &client, HTTPValidators &v, bool &not_modified);
 extern void FWIFIPR (WiFiClient &client, const __FlashStringHelper *str);
 extern void FWIFIPRLN (WiFiClient &client, const __FlashStringHelper *str);
 extern int getNTPServers (const NTPServer **listp);
+extern int getRefreshSources (const RefreshSource **listp);
+extern void setOfflineMode (bool forced);
+extern bool getOfflineMode (bool &forced, time_t &since);
+extern bool isOffline (void);
+extern void refreshSWxPanes (void);
+extern const char *fetchErrorName (FetchErrorKind kind);
+extern void noteFetchDetail (const char *detail);
+extern void noteClusterSpaceWeather (const ClusterSpaceWx &cspw);
+extern void noteFlares (const char *largest, time_t peak);
+extern void noteCMEs (time_t arrival, int speed);
 extern uint16_t bc_power;
 
-extern void getSpaceWeather (SPWxValue &ssn, SPWxValue &flux, SPWxValue &kp, SPWxValue &swind, 
-    SPWxValue &drap, NOAASpaceWx &noaaspw, time_t &noaaspw_age, char xray[], time_t &xray_age,
-    float pathrel[PROP_MAP_N], time_t &pathrel_age);
+extern void getSpaceWeather (SpaceWeather &spw);
+
+
+/*********************************************************************************************
+ *
+ * simulate.cpp
+ *
+ */
+
+extern bool loadSimulation (const char *fn, char ynot[], size_t ynot_len);
+extern void runSimulation(void);
+extern void applySimSpaceWeather (SpaceWeather &spw);
+extern bool getSimSpaceWeather (const char *name, float &value);
+
+
 
 
 /*********************************************************************************************
@@ -0,0 +1,366 @@
+/* keep a history of alerts and export it as CSV, JSON or ADIF, from the web server or command line.
+ *
+ * the history is ALERTS_FN in our_dir, one alert per line: unix_time,kind,call,kHz,mode,details
+ * where kind is watch, alarm, countdown, swr, iss or aurora; call is only set for watch alerts and kHz and
+ * mode only for watch and iss alerts. When the file grows beyond ALERTS_MAXSIZE it is moved aside to ALERTS_FN.old and a new one started.
+ *
+ * exports are selected with a spec of the same form for both, all parts optional:
+ *   format=csv|json|adif&from=YYYY-MM-DD&to=YYYY-MM-DD&kind=watch|alarm|countdown|swr|iss|aurora
+ * adif always includes only watch alerts, as a log of calls heard.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#define ALERTS_FN       "alerts.csv"            // history file in our_dir
+#define ALERTS_MAXSIZE  (1024*1024)             // move aside when larger than this, bytes
+#define ALERT_DETAILS   80                      // max details length
+
+typedef enum {
+    AF_CSV,
+    AF_JSON,
+    AF_ADIF,
+} AlertFormat;
+
+// one alert
+typedef struct {
+    long t;                                     // unix time
+    char kind[12];                              // watch, alarm, countdown, swr, iss or aurora
+    char call[MAX_SPOTCALL_LEN];                // call, watch only
+    float kHz;                                  // frequency, watch and iss only
+    char mode[MAX_SPOTMODE_LEN];                // mode, watch and iss only
+    char details[ALERT_DETAILS];                // anything else
+} AlertRec;
+
+// export choices
+typedef struct {
+    AlertFormat format;
+    time_t from, to;                            // time range, to is exclusive
+    char kind[12];                              // only this kind, all if empty
+} AlertSpec;
+
+
+/* copy from to to of size to_len, replacing each character that is not printable or would need
+ * quoting in any of our formats with blank so the history never needs escaping.
+ */
+static void cleanAlertField (const char *from, char *to, size_t to_len)
+{
+    size_t i;
+    for (i = 0; i < to_len-1 && from[i]; i++) {
+        char c = from[i];
+        to[i] = (!isprint(c) || c == ',' || c == '"' || c == '\\' || c == '<' || c == '>') ? ' ' : c;
+    }
+    to[i] = '\0';
+}
+
+/* add an alert to the history.
+ * call and mode may be empty, kHz 0, if they do not apply.
+ */
+void recordAlert (const char *kind, const char *call, float kHz, const char *mode, const char *details)
+{
+    std::string fn = our_dir + ALERTS_FN;
+
+    // move aside if too big
+    struct stat sbuf;
+    if (stat (fn.c_str(), &sbuf) == 0 && sbuf.st_size > ALERTS_MAXSIZE) {
+        std::string old_fn = fn + ".old";
+        if (rename (fn.c_str(), old_fn.c_str()) < 0)
+            Serial.printf (_FX("Alerts: rename %s: %s\n"), fn.c_str(), strerror(errno));
+    }
+
+    FILE *fp = fopen (fn.c_str(), "a");
+    if (!fp) {
+        Serial.printf (_FX("Alerts: %s: %s\n"), fn.c_str(), strerror(errno));
+        return;
+    }
+
+    AlertRec ar;
+    cleanAlertField (kind, ar.kind, sizeof(ar.kind));
+    cleanAlertField (call, ar.call, sizeof(ar.call));
+    cleanAlertField (mode, ar.mode, sizeof(ar.mode));
+    cleanAlertField (details, ar.details, sizeof(ar.details));
+    fprintf (fp, "%ld,%s,%s,%.1f,%s,%s\n", (long)now(), ar.kind, ar.call, kHz, ar.mode, ar.details);
+    fclose (fp);
+}
+
+/* crack a YYYY-MM-DD date to the unix time at its start.
+ */
+static bool crackAlertDate (const char *str, time_t &t)
+{
+    int yr, mo, dy;
+    if (sscanf (str, "%d-%d-%d", &yr, &mo, &dy) != 3 || yr < 1970 || mo < 1 || mo > 12 || dy < 1 || dy > 31)
+        return (false);
+    tmElements_t tm;
+    tm.Year = yr - 1970;
+    tm.Month = mo;
+    tm.Day = dy;
+    tm.Hour = tm.Minute = tm.Second = 0;
+    t = makeTime (tm);
+    return (true);
+}
+
+/* crack an export spec. if trouble put reason in errmsg and return false.
+ */
+static bool crackAlertSpec (const char *spec_str, AlertSpec &spec, char *errmsg, size_t errmsg_len)
+{
+    spec.format = AF_CSV;
+    spec.from = 0;
+    spec.to = 0;
+    spec.kind[0] = '\0';
+
+    char copy[strlen(spec_str)+1];
+    strcpy (copy, spec_str);
+    char *save;
+    for (char *kw = strtok_r (copy, "&", &save); kw != NULL; kw = strtok_r (NULL, "&", &save)) {
+        char *eq = strchr (kw, '=');
+        if (!eq) {
+            snprintf (errmsg, errmsg_len, "%s is not name=value", kw);
+            return (false);
+        }
+        *eq++ = '\0';
+        if (strcmp (kw, "format") == 0) {
+            if (strcmp (eq, "csv") == 0)
+                spec.format = AF_CSV;
+            else if (strcmp (eq, "json") == 0)
+                spec.format = AF_JSON;
+            else if (strcmp (eq, "adif") == 0)
+                spec.format = AF_ADIF;
+            else {
+                snprintf (errmsg, errmsg_len, "format must be csv, json or adif");
+                return (false);
+            }
+        } else if (strcmp (kw, "from") == 0 || strcmp (kw, "to") == 0) {
+            time_t t;
+            if (!crackAlertDate (eq, t)) {
+                snprintf (errmsg, errmsg_len, "%s must be YYYY-MM-DD", kw);
+                return (false);
+            }
+            if (kw[0] == 'f')
+                spec.from = t;
+            else
+                spec.to = t + SECS_PER_DAY;           // include all of that day
+        } else if (strcmp (kw, "kind") == 0) {
+            if (strcmp (eq, "watch") && strcmp (eq, "alarm") && strcmp (eq, "countdown") && strcmp (eq, "swr")
+                                        && strcmp (eq, "iss") && strcmp (eq, "aurora")) {
+                snprintf (errmsg, errmsg_len, "kind must be watch, alarm, countdown, swr, iss or aurora");
+                return (false);
+            }
+            strcpy (spec.kind, eq);
+        } else {
+            snprintf (errmsg, errmsg_len, "unknown %s", kw);
+            return (false);
+        }
+    }
+
+    if (spec.format == AF_ADIF)
+        strcpy (spec.kind, "watch");
+
+    return (true);
+}
+
+/* crack one line of the history file
+ */
+static bool crackAlertLine (const char *line, AlertRec &ar)
+{
+    memset (&ar, 0, sizeof(ar));
+    // empty fields are allowed so scan piece by piece
+    const char *p = line;
+    char *endp;
+    ar.t = strtol (p, &endp, 10);
+    if (endp == p || *endp != ',')
+        return (false);
+    p = endp + 1;
+    size_t l = strcspn (p, ",");
+    if (l == 0 || l >= sizeof(ar.kind) || p[l] != ',')
+        return (false);
+    memcpy (ar.kind, p, l);
+    p += l + 1;
+    l = strcspn (p, ",");
+    if (l >= sizeof(ar.call) || p[l] != ',')
+        return (false);
+    memcpy (ar.call, p, l);
+    p += l + 1;
+    ar.kHz = strtof (p, &endp);
+    if (*endp != ',')
+        return (false);
+    p = endp + 1;
+    l = strcspn (p, ",");
+    if (l >= sizeof(ar.mode) || p[l] != ',')
+        return (false);
+    memcpy (ar.mode, p, l);
+    p += l + 1;
+    l = strcspn (p, "\r\n");
+    if (l >= sizeof(ar.details))
+        l = sizeof(ar.details) - 1;
+    memcpy (ar.details, p, l);
+    return (true);
+}
+
+/* append one ADIF field to out
+ */
+static void addADIFField (std::string &out, const char *name, const char *value)
+{
+    if (value[0]) {
+        char buf[50];
+        snprintf (buf, sizeof(buf), "<%s:%d>", name, (int)strlen(value));
+        out += buf;
+        out += value;
+        out += ' ';
+    }
+}
+
+/* append one alert to out in the given format
+ */
+static void formatAlert (const AlertRec &ar, AlertFormat format, bool first, std::string &out)
+{
+    char buf[200];
+    time_t t = ar.t;
+
+    switch (format) {
+    case AF_CSV:
+        snprintf (buf, sizeof(buf), "%04d-%02d-%02dT%02d:%02d:%02dZ,%s,%s,%.1f,%s,%s\n", year(t), month(t),
+                    day(t), hour(t), minute(t), second(t), ar.kind, ar.call, ar.kHz, ar.mode, ar.details);
+        out += buf;
+        break;
+
+    case AF_JSON:
+        snprintf (buf, sizeof(buf), "%s\n  {\"t\":%ld,\"kind\":\"%s\",\"call\":\"%s\",\"khz\":%.1f,"
+                    "\"mode\":\"%s\",\"details\":\"%s\"}", first ? "" : ",", ar.t, ar.kind, ar.call,
+                    ar.kHz, ar.mode, ar.details);
+        out += buf;
+        break;
+
+    case AF_ADIF: {
+        char freq[20], date[10], time_on[10];
+        snprintf (freq, sizeof(freq), "%.4f", ar.kHz/1000);             // MHz
+        snprintf (date, sizeof(date), "%04d%02d%02d", year(t), month(t), day(t));
+        snprintf (time_on, sizeof(time_on), "%02d%02d%02d", hour(t), minute(t), second(t));
+        addADIFField (out, "CALL", ar.call);
+        addADIFField (out, "FREQ", freq);
+        addADIFField (out, "MODE", ar.mode);
+        addADIFField (out, "QSO_DATE", date);
+        addADIFField (out, "TIME_ON", time_on);
+        snprintf (buf, sizeof(buf), "watched call spotted%s%s", ar.details[0] ? ": " : "", ar.details);
+        addADIFField (out, "COMMENT", buf);
+        out += "<EOR>\n";
+        break;
+        }
+    }
+}
+
+/* export the alert history as described by spec_str to out and set ctype to its Content-Type.
+ * if trouble put reason in errmsg and return false.
+ */
+static bool exportAlertHistory (const char *spec_str, std::string &out, const char *&ctype, char *errmsg,
+size_t errmsg_len)
+{
+    AlertSpec spec;
+    if (!crackAlertSpec (spec_str, spec, errmsg, errmsg_len))
+        return (false);
+
+    // heading
+    switch (spec.format) {
+    case AF_CSV:
+        ctype = "text/csv";
+        out = "utc,kind,call,khz,mode,details\n";
+        break;
+    case AF_JSON:
+        ctype = "application/json";
+        out = "[";
+        break;
+    case AF_ADIF:
+        ctype = "text/plain";
+        out = "HamClock watched calls heard\n";
+        addADIFField (out, "ADIF_VER", "3.1.4");
+        addADIFField (out, "PROGRAMID", "HamClock");
+        addADIFField (out, "PROGRAMVERSION", HC_VERSION);
+        out += "<EOH>\n";
+        break;
+    }
+
+    // each matching alert, older file first
+    bool first = true;
+    const char *suffixes[] = {".old", ""};
+    for (unsigned i = 0; i < NARRAY(suffixes); i++) {
+        std::string fn = our_dir + ALERTS_FN + suffixes[i];
+        FILE *fp = fopen (fn.c_str(), "r");
+        if (!fp)
+            continue;
+        char line[200];
+        AlertRec ar;
+        while (fgets (line, sizeof(line), fp)) {
+            if (!crackAlertLine (line, ar))
+                continue;
+            if ((spec.from && ar.t < spec.from) || (spec.to && ar.t >= spec.to)
+                                || (spec.kind[0] && strcmp (spec.kind, ar.kind) != 0))
+                continue;
+            formatAlert (ar, spec.format, first, out);
+            first = false;
+        }
+        fclose (fp);
+    }
+
+    if (spec.format == AF_JSON)
+        out += "\n]\n";
+
+    return (true);
+}
+
+/* web command to export alert history.
+ */
+bool getWiFiAlerts (WiFiClient *clientp, char line[])
+{
+    std::string out;
+    const char *ctype;
+    char errmsg[100];
+    if (!exportAlertHistory (line, out, ctype, errmsg, sizeof(errmsg))) {
+        strcpy (line, errmsg);
+        return (false);
+    }
+
+    if (clientp) {
+        resetWatchdog();
+        FWIFIPRLN (*clientp, F("HTTP/1.0 200 OK"));
+        sendUserAgent (*clientp);
+        clientp->print (F("Content-Type: "));
+        clientp->print (ctype);
+        FWIFIPRLN (*clientp, F("; charset=us-ascii"));
+        FWIFIPRLN (*clientp, F("Connection: close\r\n"));
+        clientp->write ((const uint8_t *)out.c_str(), out.size());
+    }
+
+    return (true);
+}
+
+/* command line export of alert history to stdout.
+ * return whether ok, else explain on stderr.
+ */
+bool printAlertHistory (const char *spec)
+{
+    std::string out;
+    const char *ctype;
+    char errmsg[100];
+    if (!exportAlertHistory (spec, out, ctype, errmsg, sizeof(errmsg))) {
+        fprintf (stderr, "%s\n", errmsg);
+        return (false);
+    }
+    fputs (out.c_str(), stdout);
+    return (true);
+}
+
+#else // !_IS_UNIX
+
+/* dummy version for systems with no history file
+ */
+void recordAlert (const char *kind, const char *call, float kHz, const char *mode, const char *details)
+{
+    (void) kind;
+    (void) call;
+    (void) kHz;
+    (void) mode;
+    (void) details;
+}
+
+#endif // _IS_UNIX
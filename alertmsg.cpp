@@ -0,0 +1,299 @@
+/* optional message templates for each kind of alert, so web socket clients, escalations and the alert
+ * history get exactly the wording and fields the user wants instead of our default details.
+ *
+ * a template is any text with these placeholders:
+ *   {details}  our default message
+ *   {kind}     alert kind
+ *   {call}     DX call, watch only
+ *   {freq}     frequency, kHz, watch and iss only
+ *   {band}     band name from freq, eg 20m, watch and iss only
+ *   {mode}     mode, watch and iss only
+ *   {kp}       latest planetary Kp
+ *   {sat}      ISS for iss alerts, else the satellite being tracked
+ *   {utc}      time of the alert, HH:MM UTC
+ *   {de}       our call sign
+ * placeholders that do not apply to the alert expand to nothing.
+ *
+ * templates are saved in TMPL_FN in our_dir, one per line: kind template
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#define TMPL_FN         "alertmsg.txt"          // template file in our_dir
+#define TMPL_LEN        80                      // max template length, including EOS
+
+// template for each kind, same kinds as alerthist.cpp
+typedef struct {
+    const char *kind;                           // alert kind
+    char tmpl[TMPL_LEN];                        // template, empty for our default details
+} AlertTemplate;
+
+static AlertTemplate templates[] = {
+    { "watch" },
+    { "countdown" },
+    { "alarm" },
+    { "swr" },
+    { "iss" },
+    { "aurora" },
+};
+#define N_TEMPLATES NARRAY(templates)
+
+// placeholder names, N.B. must match order in expandField()
+static const char *field_names[] = {
+    "details", "kind", "call", "freq", "band", "mode", "kp", "sat", "utc", "de",
+};
+#define N_FIELDS NARRAY(field_names)
+
+static bool templates_loaded;
+
+
+/* return the AlertTemplate for the given kind, else NULL
+ */
+static AlertTemplate *findTemplate (const char *kind)
+{
+    for (unsigned i = 0; i < N_TEMPLATES; i++)
+        if (strcmp (kind, templates[i].kind) == 0)
+            return (&templates[i]);
+    return (NULL);
+}
+
+/* return field_names[] index of the placeholder starting at p just after its {, else -1.
+ * set len to the length of the name.
+ */
+static int findField (const char *p, size_t &len)
+{
+    const char *close = strchr (p, '}');
+    if (!close)
+        return (-1);
+    len = close - p;
+    for (unsigned i = 0; i < N_FIELDS; i++)
+        if (strlen (field_names[i]) == len && strncmp (p, field_names[i], len) == 0)
+            return (i);
+    return (-1);
+}
+
+/* check whether tmpl is a sensible template.
+ * if not put reason in ynot and return false.
+ */
+static bool checkTemplate (const char *tmpl, char *ynot, size_t ynot_len)
+{
+    if (strlen (tmpl) >= TMPL_LEN) {
+        snprintf (ynot, ynot_len, "template must be shorter than %d chars", TMPL_LEN);
+        return (false);
+    }
+    for (const char *p = strchr (tmpl, '{'); p; p = strchr (p+1, '{')) {
+        size_t len;
+        if (findField (p+1, len) < 0) {
+            snprintf (ynot, ynot_len, "unknown placeholder at %.20s", p);
+            return (false);
+        }
+    }
+    if (strchr (tmpl, '\n')) {
+        snprintf (ynot, ynot_len, "template must be one line");
+        return (false);
+    }
+    return (true);
+}
+
+/* read TMPL_FN once
+ */
+static void loadTemplates()
+{
+    if (templates_loaded)
+        return;
+    templates_loaded = true;
+
+    std::string fn = our_dir + TMPL_FN;
+    FILE *fp = fopen (fn.c_str(), "r");
+    if (!fp)
+        return;
+
+    char line[150];
+    while (fgets (line, sizeof(line), fp)) {
+        line[strcspn (line, "\r\n")] = '\0';
+        char kind[20];
+        int n;
+        if (line[0] == '#' || sscanf (line, "%19s %n", kind, &n) != 1)
+            continue;
+        AlertTemplate *atp = findTemplate (kind);
+        char ynot[80];
+        if (!atp || !checkTemplate (line+n, ynot, sizeof(ynot))) {
+            Serial.printf (_FX("AlertMsg: %s: ignoring %s\n"), TMPL_FN, line);
+            continue;
+        }
+        strcpy (atp->tmpl, line+n);
+    }
+    fclose (fp);
+}
+
+/* write all templates to TMPL_FN
+ */
+static void saveTemplates()
+{
+    std::string fn = our_dir + TMPL_FN;
+    FILE *fp = fopen (fn.c_str(), "w");
+    if (!fp) {
+        Serial.printf (_FX("AlertMsg: %s: %s\n"), fn.c_str(), strerror(errno));
+        return;
+    }
+
+    fprintf (fp, "# kind template, see alertmsg.cpp for placeholders\n");
+    for (unsigned i = 0; i < N_TEMPLATES; i++)
+        if (templates[i].tmpl[0])
+            fprintf (fp, "%s %s\n", templates[i].kind, templates[i].tmpl);
+    fclose (fp);
+}
+
+/* put the value of field_names[f] for the given alert into value
+ */
+static void expandField (int f, const char *kind, const char *details, const char *call, float kHz,
+const char *mode, char *value, size_t value_len)
+{
+    value[0] = '\0';
+
+    switch (f) {
+    case 0:     // details
+        snprintf (value, value_len, "%s", details);
+        break;
+    case 1:     // kind
+        snprintf (value, value_len, "%s", kind);
+        break;
+    case 2:     // call
+        snprintf (value, value_len, "%s", call ? call : "");
+        break;
+    case 3:     // freq
+        if (kHz > 0)
+            snprintf (value, value_len, "%.1f", kHz);
+        break;
+    case 4:     // band
+        if (kHz > 0) {
+            HamBand b = findHamBand (kHz);
+            if (b != HB_NONE)
+                snprintf (value, value_len, "%s", getHamBandName (b));
+        }
+        break;
+    case 5:     // mode
+        snprintf (value, value_len, "%s", mode ? mode : "");
+        break;
+    case 6: {   // kp
+        SpaceWeather spw;
+        getSpaceWeather (spw);
+        if (spw.kp.age < SECSPERDAY)
+            snprintf (value, value_len, "%.0f", spw.kp.value);
+        } break;
+    case 7:     // sat
+        if (strcmp (kind, "iss") == 0)
+            snprintf (value, value_len, "ISS");
+        else {
+            char name[NV_SATNAME_LEN];
+            if (NVReadString (NV_SATNAME, name)) {
+                strncpySubChar (name, name, ' ', '_', NV_SATNAME_LEN);
+                snprintf (value, value_len, "%s", name);
+            }
+        }
+        break;
+    case 8:     // utc
+        snprintf (value, value_len, "%02d:%02d", hour(), minute());
+        break;
+    case 9:     // de
+        snprintf (value, value_len, "%s", getCallsign());
+        break;
+    }
+}
+
+/* replace details IN PLACE with the user's template for the given kind of alert, if any, filled in with
+ * the given alert fields. call and mode may be NULL and kHz 0 if they do not apply.
+ */
+void formatAlertMessage (const char *kind, const char *call, float kHz, const char *mode,
+char *details, size_t details_len)
+{
+    loadTemplates();
+
+    AlertTemplate *atp = findTemplate (kind);
+    if (!atp || !atp->tmpl[0])
+        return;
+
+    // expand into a copy because {details} refers to the original
+    StackMalloc out_mem(details_len);
+    char *out = (char *) out_mem.getMem();
+    size_t ol = 0;
+    for (const char *p = atp->tmpl; *p && ol < details_len-1; ) {
+        size_t len;
+        int f;
+        if (*p == '{' && (f = findField (p+1, len)) >= 0) {
+            char value[100];
+            expandField (f, kind, details, call, kHz, mode, value, sizeof(value));
+            ol += snprintf (out+ol, details_len-ol, "%s", value);
+            if (ol > details_len-1)
+                ol = details_len-1;
+            p += len + 2;
+        } else
+            out[ol++] = *p++;
+    }
+    out[ol] = '\0';
+
+    strcpy (details, out);
+}
+
+/* set the template for the given alert kind, empty for our default details.
+ * if trouble put reason in ynot and return false.
+ */
+bool setAlertTemplate (const char *kind, const char *tmpl, char *ynot, size_t ynot_len)
+{
+    loadTemplates();
+
+    AlertTemplate *atp = findTemplate (kind);
+    if (!atp) {
+        size_t l = snprintf (ynot, ynot_len, "kind must be one of:");
+        for (unsigned i = 0; i < N_TEMPLATES && l < ynot_len; i++)
+            l += snprintf (ynot+l, ynot_len-l, " %s", templates[i].kind);
+        return (false);
+    }
+    if (!checkTemplate (tmpl, ynot, ynot_len))
+        return (false);
+
+    strcpy (atp->tmpl, tmpl);
+    saveTemplates();
+    return (true);
+}
+
+/* print the template of each alert kind that has one, one per line with the given prefix
+ */
+void printAlertTemplates (WiFiClient &client, const char *prefix)
+{
+    loadTemplates();
+
+    char buf[150];
+    bool any = false;
+    for (unsigned i = 0; i < N_TEMPLATES; i++) {
+        if (templates[i].tmpl[0]) {
+            snprintf (buf, sizeof(buf), "%s%s: %s", prefix, templates[i].kind, templates[i].tmpl);
+            client.println (buf);
+            any = true;
+        }
+    }
+    if (!any) {
+        snprintf (buf, sizeof(buf), "%sdefault", prefix);
+        client.println (buf);
+    }
+}
+
+#else // !_IS_UNIX
+
+/* dummy version for systems with no template file
+ */
+void formatAlertMessage (const char *kind, const char *call, float kHz, const char *mode,
+char *details, size_t details_len)
+{
+    (void) kind;
+    (void) call;
+    (void) kHz;
+    (void) mode;
+    (void) details;
+    (void) details_len;
+}
+
+#endif // _IS_UNIX
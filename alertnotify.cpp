@@ -0,0 +1,35 @@
+/* deliver each alert through all of its notifications in one place so every kind, and test alerts, behave
+ * the same way.
+ *
+ * in order: apply the user's message template to the details, then unless quiet hours hold it back run the
+ * on_alert script, tell web socket clients, wake the display and play its sound as its severity warrants
+ * and start any escalation if it is critical; finally record it in the alert history, quiet or not.
+ */
+
+#include "HamClock.h"
+
+
+/* notify an alert of the given kind and severity.
+ * details is updated in place from the kind's message template, see alertmsg.cpp.
+ * call, kHz and mode describe the spot or frequency, if any, else NULL, 0 and NULL.
+ * arg1 and arg2 follow kind as arguments to the on_alert script, NULL if not needed.
+ * return whether it notified, ie, was not held back by quiet hours.
+ */
+bool notifyAlert (const char *kind, AlertSeverity sev, char *details, size_t details_len,
+    const char *call, float kHz, const char *mode, const char *arg1, const char *arg2)
+{
+    formatAlertMessage (kind, call, kHz, mode, details, details_len);
+
+    bool quiet = alertIsQuiet (kind, sev);
+    if (!quiet) {
+        runEventScript ("on_alert", kind, arg1, arg2, NULL);
+        sendWebSocketAlert (kind, details);
+        wakeDisplayForAlert (kind, sev);
+        playAlertSound (kind, sev);
+        if (sev == ALSEV_CRITICAL)
+            escalateAlert (kind, details);
+    }
+    recordAlert (kind, call ? call : "", kHz, mode ? mode : "", details);
+
+    return (!quiet);
+}
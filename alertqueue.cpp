@@ -0,0 +1,288 @@
+/* bounded queues between alerts and the sinks that can fall behind, with counters to show when they do.
+ *
+ * sinks are:
+ *   ws         a web socket client whose unsent output has backed up, see websocket.cpp
+ *   script     event scripts when MAX_SCRIPTS are already running, see scripts.cpp
+ *
+ * while a sink is lagging its alerts wait here, oldest first, until it catches up. each tag, such as a web
+ * socket client, may have at most AQ_DEPTH alerts waiting so one slow client can not crowd out the others.
+ * when a tag is full the sink policy decides which alert is lost: drop-newest discards the new alert,
+ * drop-oldest makes room by discarding the oldest one waiting for the same tag. for each sink we count
+ * alerts delivered directly or after waiting, dropped, times it began lagging, the current depth of all
+ * tags, the peak depth of any one tag and the longest wait; these are reported by get_sys.txt. an alert
+ * only counts as sent once the sink reports it was delivered, one taken from the queue but then lost
+ * counts as dropped.
+ *
+ * policies are saved in AQ_FN in our_dir, one per line: sink drop-oldest|drop-newest
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#define AQ_FN           "alertqueue.txt"        // policy file in our_dir
+#define AQ_DEPTH        16                      // max items waiting for each tag
+
+typedef enum {
+    AQP_DROPNEWEST,
+    AQP_DROPOLDEST,
+    AQP_N
+} AlertQPolicy;
+
+// policy names, N.B. index is AlertQPolicy
+static const char *policy_names[AQP_N] = { "drop-newest", "drop-oldest" };
+
+// one sink queue and its counters
+typedef struct {
+    const char *name;                           // sink name
+    uint8_t policy;                             // AlertQPolicy
+    AlertQItem items[AQ_DEPTH*AQ_MAXTAGS];      // waiting for all tags, oldest first
+    int n_items;                                // n used in items[]
+    int peak;                                   // most seen waiting for any one tag
+    uint32_t n_sent;                            // delivered directly or after waiting
+    uint32_t n_dropped;                         // lost to a full tag, a failed send or a closed client
+    uint32_t n_lags;                            // times the sink began lagging
+    long max_wait;                              // longest time an item waited before delivery, secs
+} AlertQueue;
+
+// N.B. index is AlertQSink
+static AlertQueue queues[AQ_N] = {
+    { "ws" },
+    { "script" },
+};
+
+static bool policies_loaded;
+
+
+/* return the queue with the given name, else NULL
+ */
+static AlertQueue *findQueue (const char *name)
+{
+    for (int i = 0; i < AQ_N; i++)
+        if (strcmp (name, queues[i].name) == 0)
+            return (&queues[i]);
+    return (NULL);
+}
+
+/* read AQ_FN once
+ */
+static void loadPolicies()
+{
+    if (policies_loaded)
+        return;
+    policies_loaded = true;
+
+    std::string fn = our_dir + AQ_FN;
+    FILE *fp = fopen (fn.c_str(), "r");
+    if (!fp)
+        return;
+
+    char line[100];
+    while (fgets (line, sizeof(line), fp)) {
+        char sink[20], policy[20];
+        if (line[0] == '#' || sscanf (line, "%19s %19s", sink, policy) != 2)
+            continue;
+        AlertQueue *qp = findQueue (sink);
+        int p;
+        for (p = 0; p < AQP_N; p++)
+            if (strcmp (policy, policy_names[p]) == 0)
+                break;
+        if (!qp || p == AQP_N) {
+            Serial.printf (_FX("AlertQ: %s: ignoring %s"), AQ_FN, line);
+            continue;
+        }
+        qp->policy = p;
+    }
+    fclose (fp);
+}
+
+/* write all policies to AQ_FN
+ */
+static void savePolicies()
+{
+    std::string fn = our_dir + AQ_FN;
+    FILE *fp = fopen (fn.c_str(), "w");
+    if (!fp) {
+        Serial.printf (_FX("AlertQ: %s: %s\n"), fn.c_str(), strerror(errno));
+        return;
+    }
+
+    fprintf (fp, "# sink drop-oldest|drop-newest\n");
+    for (int i = 0; i < AQ_N; i++)
+        fprintf (fp, "%s %s\n", queues[i].name, policy_names[queues[i].policy]);
+    fclose (fp);
+}
+
+/* remove items[i] from the given queue
+ */
+static void removeItem (AlertQueue &q, int i)
+{
+    q.n_items--;
+    memmove (&q.items[i], &q.items[i+1], (q.n_items - i) * sizeof(AlertQItem));
+}
+
+/* return index of the oldest item in q for the given tag, any if tag < 0, else -1
+ */
+static int oldestItem (const AlertQueue &q, int tag)
+{
+    for (int i = 0; i < q.n_items; i++)
+        if (tag < 0 || q.items[i].tag == tag)
+            return (i);
+    return (-1);
+}
+
+/* add len bytes of data for the given sink and tag, such as a web socket client index, to wait until the
+ * sink catches up. if the tag already has AQ_DEPTH waiting apply the sink policy.
+ * return whether data was added.
+ */
+bool alertQPush (AlertQSink sink, int tag, const char *data, size_t len)
+{
+    loadPolicies();
+
+    AlertQueue &q = queues[sink];
+    if (tag < 0 || tag >= AQ_MAXTAGS) {
+        Serial.printf (_FX("AlertQ: %s bogus tag %d\n"), q.name, tag);
+        q.n_dropped++;
+        return (false);
+    }
+    if (len > sizeof(q.items[0].data)) {
+        Serial.printf (_FX("AlertQ: %s item too long\n"), q.name);
+        q.n_dropped++;
+        return (false);
+    }
+
+    int depth = alertQDepth (sink, tag);
+    if (depth == AQ_DEPTH) {
+        q.n_dropped++;
+        if (q.policy == AQP_DROPNEWEST) {
+            Serial.printf (_FX("AlertQ: %s %d full, dropping newest\n"), q.name, tag);
+            return (false);
+        }
+        Serial.printf (_FX("AlertQ: %s %d full, dropping oldest\n"), q.name, tag);
+        removeItem (q, oldestItem (q, tag));
+        depth--;
+    }
+
+    AlertQItem &item = q.items[q.n_items++];
+    item.tag = tag;
+    item.t = now();
+    item.len = len;
+    memcpy (item.data, data, len);
+    if (++depth > q.peak)
+        q.peak = depth;
+    return (true);
+}
+
+/* remove the oldest item waiting for the given sink and tag, any if tag < 0, into item.
+ * N.B. caller must report the outcome with noteAlertQSent() or noteAlertQDropped().
+ * return whether there was one.
+ */
+bool alertQPop (AlertQSink sink, int tag, AlertQItem &item)
+{
+    AlertQueue &q = queues[sink];
+    int i = oldestItem (q, tag);
+    if (i < 0)
+        return (false);
+
+    item = q.items[i];
+    removeItem (q, i);
+    return (true);
+}
+
+/* return number of items waiting for the given sink and tag, all if tag < 0
+ */
+int alertQDepth (AlertQSink sink, int tag)
+{
+    const AlertQueue &q = queues[sink];
+    int n = 0;
+    for (int i = 0; i < q.n_items; i++)
+        if (tag < 0 || q.items[i].tag == tag)
+            n++;
+    return (n);
+}
+
+/* drop all items waiting for the given sink and tag, as when a web socket client closes
+ */
+void alertQDiscard (AlertQSink sink, int tag)
+{
+    AlertQueue &q = queues[sink];
+    for (int i; (i = oldestItem (q, tag)) >= 0; ) {
+        removeItem (q, i);
+        q.n_dropped++;
+    }
+}
+
+/* note an alert was delivered to the given sink, directly if item is NULL else after waiting in item
+ */
+void noteAlertQSent (AlertQSink sink, const AlertQItem *item)
+{
+    AlertQueue &q = queues[sink];
+    q.n_sent++;
+    if (item) {
+        long wait = now() - item->t;
+        if (wait > q.max_wait)
+            q.max_wait = wait;
+    }
+}
+
+/* note an alert for the given sink was lost, as when its send failed
+ */
+void noteAlertQDropped (AlertQSink sink)
+{
+    queues[sink].n_dropped++;
+}
+
+/* note the given sink has just begun lagging
+ */
+void noteAlertQLag (AlertQSink sink)
+{
+    queues[sink].n_lags++;
+}
+
+/* set the policy for the given sink.
+ * if trouble put reason in ynot and return false.
+ */
+bool setAlertQPolicy (const char *sink, const char *policy, char *ynot, size_t ynot_len)
+{
+    loadPolicies();
+
+    AlertQueue *qp = findQueue (sink);
+    if (!qp) {
+        size_t l = snprintf (ynot, ynot_len, "sink must be one of:");
+        for (int i = 0; i < AQ_N && l < ynot_len; i++)
+            l += snprintf (ynot+l, ynot_len-l, " %s", queues[i].name);
+        return (false);
+    }
+    int p;
+    for (p = 0; p < AQP_N; p++)
+        if (strcmp (policy, policy_names[p]) == 0)
+            break;
+    if (p == AQP_N) {
+        snprintf (ynot, ynot_len, "policy must be %s or %s", policy_names[AQP_DROPOLDEST],
+                                        policy_names[AQP_DROPNEWEST]);
+        return (false);
+    }
+
+    qp->policy = p;
+    savePolicies();
+    return (true);
+}
+
+/* print the policy and counters of each sink, one per line with the given prefix
+ */
+void printAlertQueues (WiFiClient &client, const char *prefix)
+{
+    loadPolicies();
+
+    char buf[200];
+    for (int i = 0; i < AQ_N; i++) {
+        const AlertQueue &q = queues[i];
+        snprintf (buf, sizeof(buf), "%s%-6s %s sent %u dropped %u lags %u depth %d peak %d maxwait %ld s",
+                        prefix, q.name, policy_names[q.policy], q.n_sent, q.n_dropped, q.n_lags, q.n_items,
+                        q.peak, q.max_wait);
+        client.println (buf);
+    }
+}
+
+#endif // _IS_UNIX
@@ -0,0 +1,303 @@
+/* play a sound for each kind of alert so each can be told apart from across the room.
+ *
+ * each alert kind may have one sound, either:
+ *   off            no sound, the default
+ *   a pattern      of . short beep, - long beep and _ pause, eg "..-" or "-_-", played as a tone
+ *   a file name    ending in .wav in our_dir/sounds, supplied by the user
+ * sounds play through aplay so they need a working ALSA device. they obey quiet hours like other notices.
+ * the sound plays once for info alerts, twice for warnings and three times for critical alerts.
+ *
+ * settings are saved in SND_FN in our_dir, one per line: kind sound
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#include <fcntl.h>
+#include <sys/wait.h>
+
+#define SND_FN          "alertsound.txt"        // settings file in our_dir
+#define SND_DIR         "sounds"                // user sound files dir within our_dir
+#define SND_MAXLEN      24                      // max sound spec length, including EOS
+#define SND_MAXPAT      16                      // max pattern elements
+#define SND_RATE        8000                    // pattern sample rate, Hz
+#define SND_HZ          880                     // pattern tone, Hz
+#define SND_SHORT       120                     // short beep and gap duration, ms
+#define SND_LONG        360                     // long beep and pause duration, ms
+
+// sound for each kind, same kinds as alerthist.cpp
+typedef struct {
+    const char *kind;                           // alert kind
+    char sound[SND_MAXLEN];                     // off, pattern or file
+} AlertSound;
+
+static AlertSound sounds[] = {
+    { "watch",     "off" },
+    { "countdown", "off" },
+    { "alarm",     "off" },
+    { "swr",       "off" },
+    { "iss",       "off" },
+    { "aurora",    "off" },
+};
+#define N_SOUNDS NARRAY(sounds)
+
+static bool sounds_loaded;
+
+
+/* return the AlertSound for the given kind, else NULL
+ */
+static AlertSound *findSound (const char *kind)
+{
+    for (unsigned i = 0; i < N_SOUNDS; i++)
+        if (strcmp (kind, sounds[i].kind) == 0)
+            return (&sounds[i]);
+    return (NULL);
+}
+
+/* return whether the given sound is a beep pattern
+ */
+static bool isPattern (const char *sound)
+{
+    size_t l = strlen (sound);
+    return (l > 0 && l <= SND_MAXPAT && strspn (sound, ".-_") == l);
+}
+
+/* check whether sound is a sensible sound spec.
+ * if not put reason in ynot and return false.
+ */
+static bool checkSound (const char *sound, char *ynot, size_t ynot_len)
+{
+    if (strcmp (sound, "off") == 0 || isPattern (sound))
+        return (true);
+
+    size_t l = strlen (sound);
+    if (l >= SND_MAXLEN) {
+        snprintf (ynot, ynot_len, "sound must be shorter than %d chars", SND_MAXLEN);
+        return (false);
+    }
+    if (l < 5 || strcasecmp (sound+l-4, ".wav") != 0 || strchr (sound, '/') || sound[0] == '.') {
+        snprintf (ynot, ynot_len, "sound must be off, pattern of .-_ or file.wav");
+        return (false);
+    }
+    std::string path = our_dir + SND_DIR + "/" + sound;
+    if (access (path.c_str(), R_OK) < 0) {
+        snprintf (ynot, ynot_len, "%s/%s: %s", SND_DIR, sound, strerror(errno));
+        return (false);
+    }
+    return (true);
+}
+
+/* read SND_FN once
+ */
+static void loadSounds()
+{
+    if (sounds_loaded)
+        return;
+    sounds_loaded = true;
+
+    std::string fn = our_dir + SND_FN;
+    FILE *fp = fopen (fn.c_str(), "r");
+    if (!fp)
+        return;
+
+    char line[100];
+    while (fgets (line, sizeof(line), fp)) {
+        char kind[20], sound[SND_MAXLEN];
+        if (line[0] == '#' || sscanf (line, "%19s %23s", kind, sound) != 2)
+            continue;
+        AlertSound *asp = findSound (kind);
+        char ynot[80];
+        if (!asp || !checkSound (sound, ynot, sizeof(ynot))) {
+            Serial.printf (_FX("AlertSound: %s: ignoring %s"), SND_FN, line);
+            continue;
+        }
+        strcpy (asp->sound, sound);
+    }
+    fclose (fp);
+}
+
+/* write all sounds to SND_FN
+ */
+static void saveSounds()
+{
+    std::string fn = our_dir + SND_FN;
+    FILE *fp = fopen (fn.c_str(), "w");
+    if (!fp) {
+        Serial.printf (_FX("AlertSound: %s: %s\n"), fn.c_str(), strerror(errno));
+        return;
+    }
+
+    fprintf (fp, "# kind off|pattern|file.wav\n");
+    for (unsigned i = 0; i < N_SOUNDS; i++)
+        fprintf (fp, "%s %s\n", sounds[i].kind, sounds[i].sound);
+    fclose (fp);
+}
+
+/* write a little-endian value of n bytes to fp
+ */
+static void putLE (FILE *fp, uint32_t v, int n)
+{
+    for (int i = 0; i < n; i++)
+        fputc ((v >> (8*i)) & 0xff, fp);
+}
+
+/* render the given beep pattern as a wav file at path.
+ * return whether successful.
+ */
+static bool writePattern (const char *pattern, const std::string &path)
+{
+    // find total length
+    uint32_t n_ms = 0;
+    for (const char *p = pattern; *p; p++)
+        n_ms += (*p == '.' ? SND_SHORT : SND_LONG) + SND_SHORT;
+    uint32_t n_samples = n_ms * SND_RATE / 1000;
+
+    // write to tmp then rename so a sound still playing is never cut short
+    std::string tmp = path + ".tmp";
+    FILE *fp = fopen (tmp.c_str(), "w");
+    if (!fp) {
+        Serial.printf (_FX("AlertSound: %s: %s\n"), tmp.c_str(), strerror(errno));
+        return (false);
+    }
+
+    // header for 8 bit unsigned mono pcm
+    fputs ("RIFF", fp);
+    putLE (fp, 36 + n_samples, 4);
+    fputs ("WAVEfmt ", fp);
+    putLE (fp, 16, 4);
+    putLE (fp, 1, 2);
+    putLE (fp, 1, 2);
+    putLE (fp, SND_RATE, 4);
+    putLE (fp, SND_RATE, 4);
+    putLE (fp, 1, 2);
+    putLE (fp, 8, 2);
+    fputs ("data", fp);
+    putLE (fp, n_samples, 4);
+
+    // each element is tone or silence followed by a short gap
+    for (const char *p = pattern; *p; p++) {
+        uint32_t on = (*p == '.' ? SND_SHORT : SND_LONG) * SND_RATE / 1000;
+        uint32_t gap = SND_SHORT * SND_RATE / 1000;
+        for (uint32_t i = 0; i < on; i++)
+            fputc (*p == '_' ? 128 : (uint8_t)(128 + 80*sinf (2*M_PIF*SND_HZ*i/SND_RATE)), fp);
+        for (uint32_t i = 0; i < gap; i++)
+            fputc (128, fp);
+    }
+
+    bool ok = !ferror (fp);
+    fclose (fp);
+    if (!ok || rename (tmp.c_str(), path.c_str()) < 0) {
+        Serial.printf (_FX("AlertSound: %s: %s\n"), path.c_str(), strerror(errno));
+        return (false);
+    }
+    return (true);
+}
+
+/* play the given wav file n_times in a row in the background.
+ * N.B. double fork so the player is never left a zombie and we never wait for it.
+ */
+static void playFile (const std::string &path, int n_times)
+{
+    pid_t pid = fork();
+    if (pid < 0) {
+        Serial.printf (_FX("AlertSound: fork(2): %s\n"), strerror(errno));
+        return;
+    }
+
+    if (pid == 0) {
+        if (fork() == 0) {
+            int null_fd = open ("/dev/null", O_RDWR);
+            dup2 (null_fd, 0);
+            dup2 (null_fd, 1);
+            dup2 (null_fd, 2);
+            for (int fd = 3; fd < 1024; fd++)
+                close (fd);
+            for (int i = 0; i < n_times; i++) {
+                pid_t aplay_pid = fork();
+                if (aplay_pid == 0) {
+                    execlp ("aplay", "aplay", "-q", path.c_str(), (char*)NULL);
+                    _exit(1);
+                }
+                if (aplay_pid < 0)
+                    break;
+                (void) waitpid (aplay_pid, NULL, 0);
+            }
+        }
+        _exit(0);
+    }
+
+    (void) waitpid (pid, NULL, 0);
+}
+
+/* play the sound for the given alert kind, if any, once more for each step of severity.
+ * N.B. caller should skip this during quiet hours, like the other notices.
+ */
+void playAlertSound (const char *kind, AlertSeverity sev)
+{
+    loadSounds();
+
+    AlertSound *asp = findSound (kind);
+    if (!asp || strcmp (asp->sound, "off") == 0)
+        return;
+
+    std::string path;
+    if (isPattern (asp->sound)) {
+        path = our_dir + "alertsound-" + kind + ".wav";
+        if (!writePattern (asp->sound, path))
+            return;
+    } else
+        path = our_dir + SND_DIR + "/" + asp->sound;
+
+    int n_times = sev < ALSEV_N ? sev + 1 : 1;
+    Serial.printf (_FX("AlertSound: %s %s x%d\n"), kind, asp->sound, n_times);
+    playFile (path, n_times);
+}
+
+/* set the sound for the given alert kind.
+ * if trouble put reason in ynot and return false.
+ */
+bool setAlertSound (const char *kind, const char *sound, char *ynot, size_t ynot_len)
+{
+    loadSounds();
+
+    AlertSound *asp = findSound (kind);
+    if (!asp) {
+        size_t l = snprintf (ynot, ynot_len, "kind must be one of:");
+        for (unsigned i = 0; i < N_SOUNDS && l < ynot_len; i++)
+            l += snprintf (ynot+l, ynot_len-l, " %s", sounds[i].kind);
+        return (false);
+    }
+    if (!checkSound (sound, ynot, ynot_len))
+        return (false);
+
+    strcpy (asp->sound, sound);
+    saveSounds();
+    return (true);
+}
+
+/* print the sound for each alert kind on one line with the given prefix
+ */
+void printAlertSounds (WiFiClient &client, const char *prefix)
+{
+    loadSounds();
+
+    char buf[250];
+    size_t l = snprintf (buf, sizeof(buf), "%s", prefix);
+    for (unsigned i = 0; i < N_SOUNDS && l < sizeof(buf); i++)
+        l += snprintf (buf+l, sizeof(buf)-l, "%s%s=%s", i > 0 ? " " : "", sounds[i].kind, sounds[i].sound);
+    client.println (buf);
+}
+
+#else // !_IS_UNIX
+
+/* dummy version for systems with no sound player
+ */
+void playAlertSound (const char *kind, AlertSeverity sev)
+{
+    (void) kind;
+    (void) sev;
+}
+
+#endif // _IS_UNIX
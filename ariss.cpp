@@ -0,0 +1,297 @@
+/* show upcoming ISS activity announced by ARISS, such as SSTV events and school contacts, in a plot pane as a
+ * scrollable list, with frequencies and times. an alert is raised ARISS_LEAD minutes before each event starts.
+ *
+ * ARISS publishes these only as news and web pages, there is no feed we can fetch, so the events are read
+ * from ARISS_FN in our_dir which the user keeps up to date by hand or with their own script from the
+ * upcoming contacts and SSTV event news at ariss.org. Only on UNIX systems because ESP has no files.
+ */
+
+#include "HamClock.h"
+
+
+// local file of events in our_dir, one event per line, any order:
+//   start_unix_time,end_unix_time,kind,MHz,mode,description
+// where kind is SSTV or Contact, eg:
+//   1760616000,1760806800,SSTV,145.800,PD120,Series 26 ARISS 25 years
+//   1760627400,1760628000,Contact,145.800,FM,Lincoln Elementary school Ohio via NA1SS
+// lines starting with # are ignored. if the file is missing there are simply no events.
+#define ARISS_FN        "ariss-events.txt"
+
+// layout
+#define TITLE_COLOR     RA8875_CYAN
+#define SSTV_COLOR      RA8875_YELLOW
+#define CONTACT_COLOR   RA8875_CYAN
+#define ACTIVE_COLOR    RA8875_GREEN
+#define DETAIL_COLOR    GRAY
+#define TITLE_Y0        27              // title dy, match VOACAP title position
+#define LISTING_Y0      36              // first event y down from box top
+#define LISTING_DY      22              // listing row separation, each event uses 2 lines
+#define LISTING_N       ((PLOTBOX_H - LISTING_Y0)/LISTING_DY)       // n rows visible at once
+#define KIND_X          4               // column x offsets from box left
+#define DETAIL_X        10
+#define ARROW_X         146             // scroll arrows x offset from box left
+#define ARROW_W         8               // scroll arrow width
+#define ARROW_H         6               // scroll arrow height
+#define DETAIL_N        ((ARROW_X - DETAIL_X)/6 - 1)                // max detail chars
+
+#define ARISS_LEAD      10              // alert this many minutes before an event starts
+#define ALERT_CHECK_MS  30000           // check for alerts this often, ms
+
+// one event
+typedef struct {
+    time_t start, end;                  // UTC of event
+    char kind[10];                      // SSTV or Contact
+    float MHz;                          // downlink frequency
+    char mode[10];                      // eg FM or PD120
+    char desc[60];                      // description
+    bool alerted;                       // set when alert has been raised
+} ARISSEvent;
+
+static ARISSEvent *events;              // malloced list, soonest first
+static int n_events;                    // n used in events[]
+static int top_event;                   // index of events[] shown in first row
+
+
+/* qsort-style function to sort ARISSEvents by increasing start time
+ */
+static int arissQS (const void *p1, const void *p2)
+{
+    time_t t1 = ((ARISSEvent*)p1)->start;
+    time_t t2 = ((ARISSEvent*)p2)->start;
+    return (t1 < t2 ? -1 : (t1 > t2 ? 1 : 0));
+}
+
+/* crack one line of the event list.
+ * return whether line was sensible and, if so, fill ev.
+ */
+static bool crackARISSLine (const char *line, ARISSEvent &ev)
+{
+    long start, end;
+    memset (&ev, 0, sizeof(ev));
+    if (sscanf (line, "%ld,%ld,%9[^,],%f,%9[^,],%59[^\n]", &start, &end, ev.kind, &ev.MHz, ev.mode,
+                                                                        ev.desc) < 5)
+        return (false);
+    if (end < start || ev.MHz <= 0)
+        return (false);
+    ev.start = start;
+    ev.end = end;
+    return (true);
+}
+
+/* return whether the given event is an SSTV event
+ */
+static bool isSSTV (const ARISSEvent &ev)
+{
+    return (strcasecmp (ev.kind, "SSTV") == 0);
+}
+
+/* draw events[] starting with top_event, with arrows showing whether there are more either way.
+ */
+static void drawARISSListing (const SBox &box)
+{
+    // erase listing area, not the border
+    tft.fillRect (box.x+1, box.y+LISTING_Y0-1, box.w-2, box.h-LISTING_Y0, RA8875_BLACK);
+
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+
+    if (n_events == 0) {
+        tft.setTextColor (DETAIL_COLOR);
+        const char *none = xlate ("None announced");
+        tft.setCursor (box.x + (box.w - getTextWidth(none))/2, box.y + LISTING_Y0 + LISTING_DY);
+        tft.print (none);
+        const char *where = "add to " ARISS_FN;
+        tft.setCursor (box.x + (box.w - getTextWidth(where))/2, box.y + LISTING_Y0 + 2*LISTING_DY);
+        tft.print (where);
+        return;
+    }
+
+    time_t t0 = now();
+    for (int row = 0; row < LISTING_N && top_event + row < n_events; row++) {
+        const ARISSEvent &ev = events[top_event + row];
+        uint16_t y = box.y + LISTING_Y0 + row*LISTING_DY;
+        bool active = ev.start <= t0;
+        char buf[80];
+
+        // kind
+        tft.setTextColor (isSSTV(ev) ? SSTV_COLOR : CONTACT_COLOR);
+        tft.setCursor (box.x + KIND_X, y);
+        tft.print (ev.kind);
+
+        // UTC start, or end if underway, right justified before arrows
+        tft.setTextColor (active ? ACTIVE_COLOR : RA8875_WHITE);
+        if (active)
+            snprintf (buf, sizeof(buf), "til %.3s %d %02d%02dz", monthShortStr(month(ev.end)), day(ev.end),
+                                                        hour(ev.end), minute(ev.end));
+        else
+            snprintf (buf, sizeof(buf), "%.3s %d %02d%02dz", monthShortStr(month(ev.start)), day(ev.start),
+                                                        hour(ev.start), minute(ev.start));
+        tft.setCursor (box.x + ARROW_X - 4 - getTextWidth(buf), y);
+        tft.print (buf);
+
+        // frequency, mode and description below
+        tft.setTextColor (DETAIL_COLOR);
+        snprintf (buf, sizeof(buf), "%.3f %s %s", ev.MHz, ev.mode, ev.desc);
+        buf[DETAIL_N] = '\0';
+        tft.setCursor (box.x + DETAIL_X, y + LISTING_DY/2);
+        tft.print (buf);
+    }
+
+    // scroll arrows
+    uint16_t ax = box.x + ARROW_X;
+    if (top_event > 0) {
+        uint16_t ay = box.y + LISTING_Y0;
+        tft.fillTriangle (ax, ay+ARROW_H, ax+ARROW_W, ay+ARROW_H, ax+ARROW_W/2, ay, RA8875_WHITE);
+    }
+    if (top_event + LISTING_N < n_events) {
+        uint16_t ay = box.y + LISTING_Y0 + (LISTING_N-1)*LISTING_DY + LISTING_DY/2;
+        tft.fillTriangle (ax, ay, ax+ARROW_W, ay, ax+ARROW_W/2, ay+ARROW_H, RA8875_WHITE);
+    }
+}
+
+/* draw the complete pane from events[]
+ */
+static void drawARISS (const SBox &box)
+{
+    prepPlotBox (box);
+
+    // title
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (TITLE_COLOR);
+    const char *title = xlate ("ISS Events");
+    tft.setCursor (box.x + (box.w - getTextWidth(title))/2, box.y + TITLE_Y0);
+    tft.print (title);
+
+    drawARISSListing (box);
+}
+
+/* read the upcoming ISS events from ARISS_FN into events[], keeping whether each was already alerted.
+ * return whether all ok, else why not in ynot.
+ */
+bool retrieveARISS (const char *&ynot)
+{
+#if defined(_IS_UNIX)
+
+    // missing file just means none
+    std::string fn = our_dir + ARISS_FN;
+    FILE *fp = fopen (fn.c_str(), "r");
+    if (!fp && errno != ENOENT) {
+        Serial.printf (_FX("ARISS: %s: %s\n"), fn.c_str(), strerror(errno));
+        ynot = _FX("ISS events file unreadable");
+        return (false);
+    }
+
+    // collect each not yet over, skipping comments, keeping whether already alerted
+    ARISSEvent *new_events = NULL;
+    int n_new = 0;
+    time_t t0 = now();
+    ARISSEvent ev;
+    char line[150];
+    while (fp && fgets (line, sizeof(line), fp)) {
+        foldUTF8 (line);
+        if (line[0] == '#' || !crackARISSLine (line, ev) || ev.end < t0)
+            continue;
+        for (int i = 0; i < n_events; i++) {
+            if (events[i].start == ev.start && strcmp (events[i].kind, ev.kind) == 0) {
+                ev.alerted = events[i].alerted;
+                break;
+            }
+        }
+        new_events = (ARISSEvent *) realloc (new_events, (n_new+1)*sizeof(ARISSEvent));
+        if (!new_events)
+            fatalError (_FX("No memory for %d ISS events"), n_new+1);
+        new_events[n_new++] = ev;
+    }
+    if (fp)
+        fclose (fp);
+    free (events);
+    events = new_events;
+    n_events = n_new;
+    Serial.printf (_FX("ARISS: found %d\n"), n_events);
+
+    // soonest first, keeping the scroll position if still sensible
+    if (n_events > 1)
+        qsort (events, n_events, sizeof(ARISSEvent), arissQS);
+    if (top_event >= n_events)
+        top_event = 0;
+
+    return (true);
+
+#else // !_IS_UNIX
+
+    ynot = _FX("ISS events require UNIX");
+    return (false);
+
+#endif // _IS_UNIX
+}
+
+/* retrieve and display the upcoming ISS events in the given box.
+ * return whether all ok.
+ */
+bool updateARISS (const SBox &box)
+{
+    const char *ynot;
+    if (!retrieveARISS (ynot)) {
+        plotMessage (box, RA8875_RED, ynot);
+        return (false);
+    }
+
+    drawARISS (box);
+    return (true);
+}
+
+/* called often to raise an alert shortly before each known event starts.
+ * events[] is kept fresh by updateARISS while the pane is showing, else by checkBGARISS in wifi.cpp.
+ */
+void checkARISSAlerts()
+{
+    static uint32_t last_check;
+    if (n_events == 0 || !timesUp (&last_check, ALERT_CHECK_MS))
+        return;
+
+    time_t t0 = now();
+    for (int i = 0; i < n_events; i++) {
+        ARISSEvent &ev = events[i];
+        if (ev.alerted || ev.start > t0 + ARISS_LEAD*60 || ev.end < t0)
+            continue;
+        ev.alerted = true;
+
+        char details[100];
+        int mins = (ev.start - t0 + 59)/60;
+        if (mins > 0)
+            snprintf (details, sizeof(details), "ISS %s in %d min on %.3f %s", ev.kind, mins, ev.MHz, ev.mode);
+        else
+            snprintf (details, sizeof(details), "ISS %s now on %.3f %s", ev.kind, ev.MHz, ev.mode);
+        char mhz_str[12];
+        snprintf (mhz_str, sizeof(mhz_str), "%.3f", ev.MHz);
+        (void) notifyAlert ("iss", alertSeverity ("iss"), details, sizeof(details), NULL, ev.MHz*1000,
+                                ev.mode, ev.kind, mhz_str);
+        Serial.printf (_FX("ARISS: %s\n"), details);
+    }
+}
+
+/* called when the given location is tapped below the title of the given box showing the ISS events.
+ * scroll back a page if in upper half of listing, else forward a page.
+ * return whether tap was ours.
+ */
+bool checkARISSTouch (const SCoord &s, const SBox &box)
+{
+    if (!inBox (s, box) || s.y < box.y + LISTING_Y0 || n_events == 0)
+        return (false);
+
+    int new_top = top_event;
+    if (s.y < box.y + LISTING_Y0 + (box.h - LISTING_Y0)/2)
+        new_top -= LISTING_N;
+    else
+        new_top += LISTING_N;
+    if (new_top > n_events - LISTING_N)
+        new_top = n_events - LISTING_N;
+    if (new_top < 0)
+        new_top = 0;
+
+    if (new_top != top_event) {
+        top_event = new_top;
+        drawARISSListing (box);
+    }
+
+    return (true);
+}
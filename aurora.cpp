@@ -0,0 +1,419 @@
+/* aurora visibility alert and, on UNIX, a map layer of the NOAA OVATION aurora nowcast.
+ *
+ * the OVATION nowcast gives the probability of aurora overhead on a 1 degree grid. when it is available we
+ * alert once when any cell within sight of DE reaches AUR_VIEWPCT while it is dark there, and say which way
+ * to look: toward the strongest such cell, or overhead when DE's own cell qualifies.
+ *
+ * without it, eg on ESP or before the first fetch, we fall back to Kp: the equatorward edge of where aurora
+ * can be seen on the horizon is roughly 66.5 - 2.05*Kp degrees geomagnetic, using a centered dipole for DE's
+ * geomagnetic latitude. that is good to a few degrees, which is all Kp can offer anyway.
+ */
+
+#include "HamClock.h"
+
+
+#define AUR_POLE_LAT    80.7F                   // geomagnetic north pole, IGRF-13 2020, degrees N
+#define AUR_POLE_LNG    (-72.7F)                // geomagnetic north pole, IGRF-13 2020, degrees E
+#define AUR_OVERHEAD    5                       // degrees poleward of the view line to be overhead
+#define AUR_SUNALT      (-6)                    // sun must be below this altitude to see aurora, degrees
+#define AUR_MAXAGE      (3*3600)                // ignore Kp or OVATION older than this, secs
+#define AUR_CHECK_MS    60000                   // check period, millis
+
+static uint8_t aur_alert;                       // whether to alert, from NV_AURORAALERT
+static bool aur_alert_init;                     // set once aur_alert is loaded
+static bool aur_alerted;                        // set while the current event has been alerted
+
+
+#if defined(_IS_UNIX)
+
+// backend copy of SWPC ovation_aurora_latest.json: "Observation Time" then "coordinates" with one
+// [lng, lat, pct] for each whole degree of lng 0 .. 359 and lat -90 .. 90
+static const char ovation_page[] = "/ham/HamClock/NOAASpaceWX/ovation_aurora_latest.json";
+
+#define OV_NLAT         181                     // grid rows, lat -90 .. 90
+#define OV_NLNG         360                     // grid columns, lng 0 .. 359
+#define OV_REFRESH      (15*60)                 // refresh period, secs
+#define AUR_VIEWPCT     10                      // min probability worth looking for, percent
+#define AUR_VIEWDEG     9                       // how far away aurora is visible on the horizon, degrees
+#define AUR_LAYERPCT    5                       // min probability to draw on the map, percent
+#define AUR_MAXPCT      90                      // probability drawn as the most intense color, percent
+
+static uint8_t ov_grid[OV_NLAT][OV_NLNG];       // percent probability at [lat+90][lng]
+static time_t ov_time;                          // Observation Time of ov_grid, 0 if none
+static time_t ov_fetched;                       // when ov_grid was last checked, 0 if never
+static HTTPValidators ov_v;                     // to only fetch again if changed
+
+
+/* return the layer color for the given probability, green through yellow to red
+ */
+static uint16_t auroraColor (int pct)
+{
+    if (pct > AUR_MAXPCT)
+        pct = AUR_MAXPCT;
+    int frac = (pct - AUR_LAYERPCT) * 255 / (AUR_MAXPCT - AUR_LAYERPCT);
+    uint8_t r, g, b;
+    hsvtorgb (&r, &g, &b, 85 - frac/3, 255, 255);
+    return (RGB565 (r, g, b));
+}
+
+/* crack the OVATION nowcast from its JSON page into ov_grid.
+ * N.B. the page is about 1 MB all on one line so we scan it a char at a time for the few things we need.
+ */
+static bool crackOvation (WiFiClient &client)
+{
+    char str[40];                               // latest string
+    char key[40] = "";                          // latest complete string, value of a key once ':' follows
+    char arr[40];                               // contents of latest innermost array
+    int sl = 0, al = 0, n_cells = 0;
+    bool in_str = false, in_arr = false, in_coords = false, want_time = false;
+    time_t obs_time = 0;
+
+    char c;
+    while (getChar (client, &c)) {
+        if (in_str) {
+            if (c == '"') {
+                str[sl] = '\0';
+                in_str = false;
+                int yr, mo, dy, hr, mn;
+                if (want_time && sscanf (str, "%d-%d-%dT%d:%d", &yr, &mo, &dy, &hr, &mn) == 5) {
+                    tmElements_t tm;
+                    tm.Year = yr - 1970;
+                    tm.Month = mo;
+                    tm.Day = dy;
+                    tm.Hour = hr;
+                    tm.Minute = mn;
+                    tm.Second = 0;
+                    obs_time = makeTime (tm);
+                }
+                want_time = false;
+                strcpy (key, str);
+            } else if (sl < (int)sizeof(str)-1)
+                str[sl++] = c;
+            continue;
+        }
+
+        switch (c) {
+        case '"':
+            in_str = true;
+            sl = 0;
+            break;
+        case ':':
+            want_time = strcmp (key, "Observation Time") == 0;
+            in_coords = in_coords || strcmp (key, "coordinates") == 0;
+            break;
+        case '[':
+            in_arr = true;
+            al = 0;
+            break;
+        case ']':
+            if (in_arr && in_coords) {
+                arr[al] = '\0';
+                int lng, lat, pct;
+                if (sscanf (arr, "%d , %d , %d", &lng, &lat, &pct) == 3 && lng >= 0 && lng < OV_NLNG
+                                        && lat >= -90 && lat <= 90) {
+                    ov_grid[lat+90][lng] = pct < 0 ? 0 : (pct > 100 ? 100 : pct);
+                    n_cells++;
+                }
+            }
+            in_arr = false;
+            break;
+        default:
+            if (in_arr && al < (int)sizeof(arr)-1)
+                arr[al++] = c;
+            break;
+        }
+    }
+
+    Serial.printf (_FX("Aurora: OVATION %d cells observed %ld\n"), n_cells, (long)obs_time);
+    if (n_cells < OV_NLAT*OV_NLNG/2 || obs_time == 0)
+        return (false);
+    ov_time = obs_time;
+    return (true);
+}
+
+/* fetch the OVATION nowcast if due and it has changed
+ */
+static void checkOvation()
+{
+    time_t t0 = now();
+    if (isOffline() || (ov_fetched && t0 < ov_fetched + OV_REFRESH))
+        return;
+    ov_fetched = t0;
+
+    WiFiClient ov_client;
+    Serial.println (ovation_page);
+    resetWatchdog();
+    if (wifiOk() && ov_client.connect (svr_host, HTTPPORT)) {
+        updateClocks(false);
+        httpGET (ov_client, svr_host, ovation_page, ov_time ? &ov_v : NULL);
+        bool not_modified;
+        HTTPValidators v;
+        memset (&v, 0, sizeof(v));
+        if (!httpSkipHeader (ov_client, v, not_modified))
+            Serial.println (F("Aurora: OVATION header short"));
+        else if (ov_time && not_modified)
+            Serial.println (F("Aurora: OVATION not modified"));
+        else if (crackOvation (ov_client))
+            ov_v = v;
+        else
+            ov_time = 0;
+    } else
+        Serial.println (F("Aurora: OVATION connection failed"));
+    ov_client.stop();
+}
+
+/* return whether the OVATION grid is recent enough to use
+ */
+static bool ovationOk()
+{
+    return (ov_time && now() - ov_time < AUR_MAXAGE);
+}
+
+/* return the OVATION probability at the given location, percent
+ */
+static int ovationPct (float lat_d, float lng_d)
+{
+    int lat = roundf (fmaxf (-90, fminf (90, lat_d)));
+    int lng = ((int)roundf (lng_d) + 2*OV_NLNG) % OV_NLNG;
+    return (ov_grid[lat+90][lng]);
+}
+
+/* use OVATION to decide whether aurora may be visible from DE and where to look
+ */
+static void ovationView (bool &visible, char *where, size_t where_len, char *basis, size_t basis_len)
+{
+    int de_pct = ovationPct (de_ll.lat_d, de_ll.lng_d);
+    snprintf (basis, basis_len, "OVATION %d%% at DE", de_pct);
+    visible = false;
+
+    if (de_pct >= AUR_VIEWPCT) {
+        visible = true;
+        snprintf (where, where_len, "overhead");
+        return;
+    }
+
+    // find strongest cell within sight
+    int best_pct = 0;
+    float best_bear = 0;
+    int lat0 = fmaxf (-90, floorf (de_ll.lat_d - AUR_VIEWDEG));
+    int lat1 = fminf (90, ceilf (de_ll.lat_d + AUR_VIEWDEG));
+    for (int lat = lat0; lat <= lat1; lat++) {
+        for (int lng = 0; lng < OV_NLNG; lng++) {
+            int pct = ov_grid[lat+90][lng];
+            if (pct < AUR_VIEWPCT || pct <= best_pct)
+                continue;
+            LatLong ll;
+            ll.lat_d = lat;
+            ll.lng_d = lng < 180 ? lng : lng - 360;
+            ll.lat = deg2rad (ll.lat_d);
+            ll.lng = deg2rad (ll.lng_d);
+            float dist, bear;
+            propDEDXPath (false, ll, &dist, &bear);
+            if (rad2deg (dist) <= AUR_VIEWDEG) {
+                best_pct = pct;
+                best_bear = rad2deg (bear);
+            }
+        }
+    }
+
+    if (best_pct > 0) {
+        visible = true;
+        snprintf (where, where_len, "low to the %s", heading2Compass (best_bear));
+    }
+}
+
+/* return the OVATION probability at ll, percent, if the nowcast is current
+ */
+bool getAuroraProbability (const LatLong &ll, int &pct)
+{
+    if (!ovationOk())
+        return (false);
+    pct = ovationPct (ll.lat_d, ll.lng_d);
+    return (true);
+}
+
+/* draw the OVATION nowcast as a heat layer over the whole map, with a small legend.
+ * N.B. caller sets the layer opacity
+ */
+void drawAuroraLayer()
+{
+    checkOvation();
+    if (!ovationOk())
+        return;
+
+    resetWatchdog();
+
+    // each map point colored by its probability
+    for (uint16_t y = map_b.y; y < map_b.y + map_b.h; y++) {
+        for (uint16_t x = map_b.x; x < map_b.x + map_b.w; x++) {
+            LatLong ll;
+            if (!s2ll (x, y, ll))
+                continue;
+            int pct = ovationPct (ll.lat_d, ll.lng_d);
+            if (pct >= AUR_LAYERPCT)
+                tft.drawPixel (x, y, auroraColor (pct));
+        }
+    }
+
+    // legend in the lower right: color bar then the range it spans
+    const uint16_t lg_w = 50, lg_h = 6;
+    const uint16_t lg_x = map_b.x + map_b.w - lg_w - 60;
+    const uint16_t lg_y = map_b.y + map_b.h - lg_h - 4;
+    for (uint16_t i = 0; i < lg_w; i++)
+        tft.drawLine (lg_x + i, lg_y, lg_x + i, lg_y + lg_h - 1,
+                                auroraColor (AUR_LAYERPCT + i*(AUR_MAXPCT - AUR_LAYERPCT)/(lg_w-1)));
+    char label[20];
+    snprintf (label, sizeof(label), "Aurora %d-%d%%", AUR_LAYERPCT, AUR_MAXPCT);
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    tft.setTextColor (RA8875_WHITE);
+    tft.setCursor (lg_x + lg_w + 3, lg_y - 1);
+    tft.print (label);
+}
+
+#endif // _IS_UNIX
+
+
+/* load aur_alert from NV if not already
+ */
+static void loadAuroraAlert()
+{
+    if (aur_alert_init)
+        return;
+
+    if (!NVReadUInt8 (NV_AURORAALERT, &aur_alert)) {
+        aur_alert = 0;
+        NVWriteUInt8 (NV_AURORAALERT, aur_alert);
+    }
+    aur_alert_init = true;
+}
+
+/* return geomagnetic latitude of the view line for the given Kp, degrees
+ */
+static float auroraViewLat (float kp)
+{
+    return (66.5F - 2.05F*kp);
+}
+
+/* find DE's geomagnetic latitude, degrees + north, and the bearing from DE to the geomagnetic pole of
+ * its hemisphere, degrees E of N.
+ */
+static void getDEGeomag (float &mlat, float &pole_bear)
+{
+    LatLong pole_ll;
+    pole_ll.lat_d = AUR_POLE_LAT;
+    pole_ll.lng_d = AUR_POLE_LNG;
+    pole_ll.lat = deg2rad (pole_ll.lat_d);
+    pole_ll.lng = deg2rad (pole_ll.lng_d);
+
+    float dist, bear;
+    propDEDXPath (false, pole_ll, &dist, &bear);
+    mlat = 90 - rad2deg (dist);
+
+    // southern hemisphere looks to the antipodal pole, directly opposite
+    pole_bear = rad2deg (bear);
+    if (mlat < 0)
+        pole_bear += 180;
+    pole_bear = fmodf (pole_bear + 360, 360);
+}
+
+/* return whether the sun is far enough below DE's horizon to see aurora
+ */
+static bool darkAtDE()
+{
+    LatLong ss_ll = sun_ss_ll;
+    float sun_dist, sun_bear;
+    propDEDXPath (false, ss_ll, &sun_dist, &sun_bear);
+    return (90 - rad2deg (sun_dist) < AUR_SUNALT);
+}
+
+/* return whether alerts for aurora reaching DE are on
+ */
+bool getAuroraAlert()
+{
+    loadAuroraAlert();
+    return (aur_alert != 0);
+}
+
+/* set whether to alert when aurora may be visible from DE
+ */
+void setAuroraAlert (bool on)
+{
+    loadAuroraAlert();
+    aur_alert = on;
+    NVWriteUInt8 (NV_AURORAALERT, aur_alert);
+    aur_alerted = false;
+}
+
+/* report whether aurora may be visible from DE now, regardless of darkness, from the OVATION nowcast if
+ * current else from the latest Kp. also pass back where to look and a summary of what that was based on.
+ * return false if neither is known.
+ */
+bool getAuroraView (bool &visible, char *where, size_t where_len, char *basis, size_t basis_len)
+{
+    where[0] = '\0';
+
+#if defined(_IS_UNIX)
+    if (ovationOk()) {
+        ovationView (visible, where, where_len, basis, basis_len);
+        return (true);
+    }
+#endif // _IS_UNIX
+
+    SpaceWeather spw;
+    getSpaceWeather (spw);
+    if (spw.kp.age > AUR_MAXAGE)
+        return (false);
+
+    float mlat, pole_bear;
+    getDEGeomag (mlat, pole_bear);
+    float view_lat = auroraViewLat (spw.kp.value);
+    visible = fabsf (mlat) >= view_lat;
+    if (fabsf (mlat) >= view_lat + AUR_OVERHEAD)
+        snprintf (where, where_len, "overhead");
+    else
+        snprintf (where, where_len, "low to the %s", heading2Compass (pole_bear));
+    snprintf (basis, basis_len, "Kp %.0f, DE geomag %.1f, view line %.1f", spw.kp.value, mlat, view_lat);
+
+    return (true);
+}
+
+/* called often to alert once each time aurora may become visible from DE
+ */
+void checkAuroraAlert()
+{
+    static uint32_t check_ms;
+    if (!timesUp (&check_ms, AUR_CHECK_MS) || !getAuroraAlert())
+        return;
+
+#if defined(_IS_UNIX)
+    checkOvation();
+#endif // _IS_UNIX
+
+    // say once when there is no fresh data to decide, such as when the backend can not be reached
+    bool visible;
+    char where[30], basis[60];
+    static bool stale_noted;
+    if (!getAuroraView (visible, where, sizeof(where), basis, sizeof(basis))) {
+        if (!stale_noted)
+            Serial.printf (_FX("Aurora: no Kp or OVATION data newer than %d hours, alert can not fire\n"),
+                                                AUR_MAXAGE/3600);
+        stale_noted = true;
+        return;
+    }
+    stale_noted = false;
+
+    // rearm once it is no longer reaching DE
+    if (!visible) {
+        aur_alerted = false;
+        return;
+    }
+    if (aur_alerted || !darkAtDE())
+        return;
+    aur_alerted = true;
+
+    char details[100];
+    snprintf (details, sizeof(details), "Aurora may be visible %s", where);
+    (void) notifyAlert ("aurora", alertSeverity ("aurora"), details, sizeof(details), NULL, 0, NULL,
+                                where, NULL);
+    Serial.printf (_FX("Aurora: %s, %s\n"), details, basis);
+}
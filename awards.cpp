@@ -0,0 +1,478 @@
+/* award progress shown in a plot pane: DXCC, WAS and WAZ counts worked and confirmed, overall and per band,
+ * for all modes or one mode class. tap the pane to step through Mixed, CW, Phone and Data.
+ *
+ * QSOs come from the same sources as the QSO rate meter: on UNIX the whole ADIF log file named with -q,
+ * then any records appended to it, plus WSJT-X or JTDX QSO Logged messages as they arrive.
+ * a QSO is confirmed if its LoTW, eQSL or card QSL was received.
+ *
+ * DXCC entities are counted by the ADIF DXCC entity number when the record has one, else by the nearest
+ * entity prefix to GRIDSQUARE or LAT/LON; QSOs with none of these are not counted for DXCC. each entity is
+ * also labeled with the prefix nearest its location, the same as DX spots, so they can be compared; an
+ * entity only ever logged without a location is labeled #number and can not match a spot. WAS counts
+ * STATE of QSOs with US entities and WAZ counts CQZ. spots are flagged when they would fill a DXCC band and
+ * mode slot not yet worked; there is no zone or state data for spots.
+ */
+
+#include "HamClock.h"
+
+
+// layout
+#define TITLE_COLOR     RA8875_GREEN
+#define COUNT_COLOR     RA8875_WHITE
+#define DETAIL_COLOR    GRAY
+#define WORKED_COLOR    RGB565(0,80,120)        // worked portion of progress bar
+#define CONFIRMED_COLOR RA8875_CYAN             // confirmed portion of progress bar
+#define TITLE_Y0        27                      // title dy, match VOACAP title position
+#define AWARD_Y0        38                      // first award line y down from box top
+#define AWARD_DY        20                      // award line spacing
+#define BAR_DY          10                      // progress bar y down from award line
+#define BAR_H           4                       // progress bar height
+#define BANDS_Y0        102                     // first band counts line y down from box top
+#define BANDS_DY        11                      // band counts line spacing
+#define BANDS_N         4                       // max band counts lines
+
+#define LOG_CHECK_SECS  10                      // check log file for more QSOs this often, secs
+
+// mode classes as for most awards
+typedef enum {
+    AM_CW,
+    AM_PHONE,
+    AM_DATA,
+    AM_N,
+    AM_MIXED = AM_N                             // any of the above
+} AwardMode;
+static const char *am_names[AM_N+1] = {"CW", "Phone", "Data", "Mixed"};
+
+// one entity, state or zone and the bands, HamBand bit masks, it was worked and confirmed in each mode
+typedef struct {
+    char key[MAX_PREF_LEN+1];
+    uint16_t dxcc;                              // ADIF DXCC entity number, 0 if unknown
+    uint32_t worked[AM_N];
+    uint32_t confirmed[AM_N];
+} AwardSlot;
+
+// one award
+typedef struct {
+    const char *name;
+    uint16_t goal;                              // n slots to earn the basic award
+    uint16_t max_slots;                         // size of slots[]
+    AwardSlot *slots;                           // malloced as needed
+    uint16_t n_slots;                           // n used in slots[]
+} AwardTally;
+
+typedef enum {
+    AW_DXCC,
+    AW_WAS,
+    AW_WAZ,
+    AW_N
+} Award;
+
+static AwardTally awards[AW_N] = {
+    { "DXCC", 100, 400 },
+    { "WAS",  50,  50 },
+    { "WAZ",  40,  40 },
+};
+
+static uint8_t show_mode = AM_MIXED;            // AwardMode shown in pane
+
+// the 50 states for WAS
+static const char was_states[] =
+    "AK AL AR AZ CA CO CT DE FL GA HI IA ID IL IN KS KY LA MA MD ME MI MN MO MS MT NC ND NE NH NJ NM NV NY "
+    "OH OK OR PA RI SC SD TN TX UT VA VT WA WI WV WY";
+
+
+/* return the AwardMode class of the given mode name
+ */
+static AwardMode modeClass (const char *mode)
+{
+    static const char *phone[] = {"SSB", "USB", "LSB", "AM", "FM", "DIGITALVOICE", "PHONE"};
+    if (strcasecmp (mode, "CW") == 0)
+        return (AM_CW);
+    for (unsigned i = 0; i < NARRAY(phone); i++)
+        if (strcasecmp (mode, phone[i]) == 0)
+            return (AM_PHONE);
+    return (AM_DATA);
+}
+
+/* return the slot for key in the given award, adding it if new and there is room, else NULL
+ */
+static AwardSlot *findSlot (AwardTally &aw, const char *key, bool add)
+{
+    for (int i = 0; i < aw.n_slots; i++)
+        if (strcmp (aw.slots[i].key, key) == 0)
+            return (&aw.slots[i]);
+    if (!add || aw.n_slots >= aw.max_slots)
+        return (NULL);
+
+    if (!aw.slots) {
+        aw.slots = (AwardSlot *) calloc (aw.max_slots, sizeof(AwardSlot));
+        if (!aw.slots)
+            fatalError (_FX("No memory for %s award"), aw.name);
+    }
+    AwardSlot *sp = &aw.slots[aw.n_slots++];
+    memset (sp, 0, sizeof(*sp));                // may be reused after resetAwards()
+    snprintf (sp->key, sizeof(sp->key), "%s", key);
+    return (sp);
+}
+
+/* return the DXCC slot for the given ADIF entity number if > 0, else for prefix if not empty, adding it if
+ * new and there is room, else NULL. a slot first found by number takes on prefix as its key once known.
+ */
+static AwardSlot *findDXCCSlot (const char *prefix, int dxcc)
+{
+    AwardTally &aw = awards[AW_DXCC];
+    if (dxcc <= 0)
+        return (prefix[0] ? findSlot (aw, prefix, true) : NULL);
+
+    // by number
+    AwardSlot *pp = prefix[0] ? findSlot (aw, prefix, false) : NULL;
+    for (int i = 0; i < aw.n_slots; i++) {
+        AwardSlot *sp = &aw.slots[i];
+        if (sp->dxcc == dxcc) {
+            if (sp->key[0] == '#' && prefix[0] && !pp)
+                snprintf (sp->key, sizeof(sp->key), "%s", prefix);
+            return (sp);
+        }
+    }
+
+    // else claim the slot of the same prefix if it has no number yet
+    if (pp && pp->dxcc == 0) {
+        pp->dxcc = dxcc;
+        return (pp);
+    }
+
+    // else a new slot named by prefix unless another number already has it
+    char key[MAX_PREF_LEN+1];
+    if (prefix[0] && !pp)
+        snprintf (key, sizeof(key), "%s", prefix);
+    else
+        snprintf (key, sizeof(key), "#%u", (unsigned)dxcc % 1000U);      // all entities are < 1000
+    AwardSlot *sp = findSlot (aw, key, true);
+    if (sp)
+        sp->dxcc = dxcc;
+    return (sp);
+}
+
+/* note slot sp, if any, was worked, and perhaps confirmed, on band in mode class am
+ */
+static void noteSlot (AwardSlot *sp, HamBand band, AwardMode am, bool confirmed)
+{
+    if (!sp || band == HB_NONE)
+        return;
+    sp->worked[am] |= 1UL << band;
+    if (confirmed)
+        sp->confirmed[am] |= 1UL << band;
+}
+
+/* note one QSO in each award it counts for.
+ * ll_ok says whether ll is the location of the station worked; state, cqz and dxcc may be empty.
+ */
+static void noteAwardQSO (bool ll_ok, const LatLong &ll, float kHz, const char *mode, const char *state,
+const char *cqz, const char *dxcc, bool confirmed)
+{
+    HamBand band = findHamBand (kHz);
+    AwardMode am = modeClass (mode);
+
+    char prefix[MAX_PREF_LEN+1];
+    if (!ll_ok || !nearestPrefix (ll, prefix))
+        prefix[0] = '\0';
+    int entity = atoi (dxcc);
+    if (entity >= 1000)
+        entity = 0;                             // not a real entity number
+    noteSlot (findDXCCSlot (prefix, entity), band, am, confirmed);
+
+    // ADIF DXCC 291 is the US, 6 Alaska and 110 Hawaii
+    bool us = !dxcc[0] || entity == 291 || entity == 6 || entity == 110;
+    if (us && strlen (state) == 2) {
+        char st[3] = {(char)toupper(state[0]), (char)toupper(state[1]), '\0'};
+        if (strstr (was_states, st))
+            noteSlot (findSlot (awards[AW_WAS], st, true), band, am, confirmed);
+    }
+
+    int z = atoi (cqz);
+    if (z >= 1 && z <= 40) {
+        char zone[4];
+        snprintf (zone, sizeof(zone), "%d", z);
+        noteSlot (findSlot (awards[AW_WAZ], zone, true), band, am, confirmed);
+    }
+}
+
+/* note a QSO just logged by WSJT-X or JTDX, not yet confirmed
+ */
+void noteAwardLoggedQSO (const char *grid, float kHz, const char *mode)
+{
+    char maid[MAID_CHARLEN];
+    memset (maid, 0, sizeof(maid));
+    strncpy (maid, grid, MAID_CHARLEN-1);
+    LatLong ll;
+    bool ll_ok = maidenhead2ll (ll, maid);
+    noteAwardQSO (ll_ok, ll, kHz, mode, "", "", "", false);
+    updateDXClusterSpotFlags();
+}
+
+
+
+#if defined(_IS_UNIX)
+
+#include <errno.h>
+
+static long adif_pos;                           // file offset just after last complete record we read
+static const char *adif_fn_read;                // file adif_pos refers to
+
+
+// ADIF fields we use, N.B. order must match names in checkAwardLogFile()
+enum {F_FREQ, F_BAND, F_MODE, F_GRID, F_LAT, F_LON, F_STATE, F_CQZ, F_DXCC, F_QSL, F_LOTW, F_EQSL, F_N};
+
+/* note one ADIF record in each award it counts for
+ */
+static void noteAwardRecord (ADIFValue fields[])
+{
+    float kHz = crackADIFkHz (fields[F_FREQ], fields[F_BAND]);
+    bool confirmed = toupper(fields[F_QSL][0]) == 'Y' || toupper(fields[F_QSL][0]) == 'V'
+                        || toupper(fields[F_LOTW][0]) == 'Y' || toupper(fields[F_EQSL][0]) == 'Y';
+    LatLong ll;
+    bool ll_ok = maidenhead2ll (ll, fields[F_GRID]);
+    if (!ll_ok) {
+        // ADIF location is XDDD MM.MMM where X is N S E or W
+        float lat_deg, lat_mn, lng_deg, lng_mn;
+        if (sscanf (fields[F_LAT], "%*c%f %f", &lat_deg, &lat_mn) == 2
+                        && sscanf (fields[F_LON], "%*c%f %f", &lng_deg, &lng_mn) == 2) {
+            ll.lat_d = (lat_deg + lat_mn/60) * (toupper(fields[F_LAT][0]) == 'S' ? -1 : 1);
+            ll.lng_d = (lng_deg + lng_mn/60) * (toupper(fields[F_LON][0]) == 'W' ? -1 : 1);
+            normalizeLL (ll);
+            ll_ok = true;
+        }
+    }
+    noteAwardQSO (ll_ok, ll, kHz, fields[F_MODE], fields[F_STATE], fields[F_CQZ], fields[F_DXCC], confirmed);
+}
+
+/* forget all award progress
+ */
+static void resetAwards (void)
+{
+    for (int a = 0; a < AW_N; a++)
+        awards[a].n_slots = 0;
+}
+
+/* read the whole QSO log file the first time then any complete records appended since last time.
+ * start over if the file shrank, as when a logging program rewrites it.
+ */
+static void checkAwardLogFile (void)
+{
+    static const char *names[F_N] = {
+        "FREQ", "BAND", "MODE", "GRIDSQUARE", "LAT", "LON", "STATE", "CQZ", "DXCC",
+        "QSL_RCVD", "LOTW_QSL_RCVD", "EQSL_QSL_RCVD",
+    };
+
+    const char *adif_fn = getQSOLogFile();
+    if (!adif_fn)
+        return;
+
+    static time_t checked;
+    if (adif_fn == adif_fn_read && now() < checked + LOG_CHECK_SECS)
+        return;
+    checked = now();
+
+    FILE *fp = fopen (adif_fn, "r");
+    if (!fp) {
+        Serial.printf (_FX("Awards: %s: %s\n"), adif_fn, strerror(errno));
+        return;
+    }
+
+    fseek (fp, 0, SEEK_END);
+    long size = ftell (fp);
+    if (adif_fn != adif_fn_read || size < adif_pos) {
+        resetAwards();
+        adif_pos = 0;
+        adif_fn_read = adif_fn;
+    }
+
+    if (size > adif_pos) {
+        adif_pos = readADIFRecords (fp, adif_pos, size, names, F_N, noteAwardRecord);
+        Serial.printf (_FX("Awards: %d DXCC %d WAS %d WAZ worked\n"), awards[AW_DXCC].n_slots,
+                                awards[AW_WAS].n_slots, awards[AW_WAZ].n_slots);
+        updateDXClusterSpotFlags();
+    }
+
+    fclose (fp);
+}
+
+#else // !_IS_UNIX
+
+/* no file system on ESP
+ */
+static void checkAwardLogFile (void)
+{
+}
+
+#endif // _IS_UNIX
+
+
+
+/* called often to pick up QSOs added to the log file
+ */
+void checkAwards (void)
+{
+    checkAwardLogFile();
+}
+
+/* return whether a spot on kHz in the given mode, if known, at the given entity prefix would fill a DXCC
+ * band and mode slot not yet worked.
+ */
+bool awardSlotNeeded (const char *entity, float kHz, const char *mode)
+{
+    HamBand band = findHamBand (kHz);
+    if (!entity[0] || band == HB_NONE || awards[AW_DXCC].n_slots == 0)
+        return (false);
+
+    AwardSlot *sp = findSlot (awards[AW_DXCC], entity, false);
+    if (!sp)
+        return (true);
+
+    uint32_t bit = 1UL << band;
+    if (mode && mode[0])
+        return ((sp->worked[modeClass(mode)] & bit) == 0);
+    for (int m = 0; m < AM_N; m++)
+        if (sp->worked[m] & bit)
+            return (false);
+    return (true);
+}
+
+/* count the slots of the given award worked and confirmed on any band in mode class am, or on the one
+ * given band if not HB_NONE.
+ */
+static void countSlots (const AwardTally &aw, AwardMode am, HamBand band, int &worked, int &confirmed)
+{
+    uint32_t mask = band == HB_NONE ? ~0UL : 1UL << band;
+    worked = confirmed = 0;
+    for (int i = 0; i < aw.n_slots; i++) {
+        uint32_t w = 0, c = 0;
+        for (int m = 0; m < AM_N; m++) {
+            if (am == AM_MIXED || am == m) {
+                w |= aw.slots[i].worked[m];
+                c |= aw.slots[i].confirmed[m];
+            }
+        }
+        if (w & mask)
+            worked++;
+        if (c & mask)
+            confirmed++;
+    }
+}
+
+/* draw one "band w/c" count at x,y, wrapping to the next line if it would extend past the box.
+ * return whether it fit within the BANDS_N lines.
+ */
+static bool drawBandCount (const SBox &box, uint16_t &x, uint16_t &y, const char *name, int w, int c)
+{
+    char buf[30];
+    snprintf (buf, sizeof(buf), "%s %d/%d", name, w, c);
+    uint16_t bw = getTextWidth (buf);
+    if (x + bw > box.x + box.w - 4) {
+        x = box.x + 4;
+        y += BANDS_DY;
+        if (y >= box.y + BANDS_Y0 + BANDS_N*BANDS_DY)
+            return (false);
+    }
+    tft.setCursor (x, y);
+    tft.print (buf);
+    x += bw + 6;
+    return (true);
+}
+
+/* draw award progress in box.
+ * always return true because there is nothing to fetch.
+ */
+bool updateAwards (const SBox &box)
+{
+    checkAwards();
+
+    prepPlotBox (box);
+
+    // title
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (TITLE_COLOR);
+    char buf[100];
+    snprintf (buf, sizeof(buf), "%s %s", xlate ("Awards"), am_names[show_mode]);
+    tft.setCursor (box.x + (box.w - getTextWidth(buf))/2, box.y + TITLE_Y0);
+    tft.print (buf);
+
+    // each award worked and confirmed with a progress bar toward its goal
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    uint16_t bar_x = box.x + 8;
+    uint16_t bar_w = box.w - 16;
+    for (int a = 0; a < AW_N; a++) {
+        const AwardTally &aw = awards[a];
+        int w, c;
+        countSlots (aw, (AwardMode)show_mode, HB_NONE, w, c);
+        uint16_t y = box.y + AWARD_Y0 + a*AWARD_DY;
+        tft.setTextColor (COUNT_COLOR);
+        snprintf (buf, sizeof(buf), "%-4s W %d C %d", aw.name, w, c);
+        tft.setCursor (bar_x, y);
+        tft.print (buf);
+        tft.setTextColor (DETAIL_COLOR);
+        snprintf (buf, sizeof(buf), "/%d", aw.goal);
+        tft.setCursor (bar_x + bar_w - getTextWidth(buf), y);
+        tft.print (buf);
+
+        tft.drawRect (bar_x, y + BAR_DY, bar_w, BAR_H, DETAIL_COLOR);
+        uint16_t ww = bar_w * (w < aw.goal ? w : aw.goal) / aw.goal;
+        uint16_t cw = bar_w * (c < aw.goal ? c : aw.goal) / aw.goal;
+        if (ww > 0)
+            tft.fillRect (bar_x, y + BAR_DY, ww, BAR_H, WORKED_COLOR);
+        if (cw > 0)
+            tft.fillRect (bar_x, y + BAR_DY, cw, BAR_H, CONFIRMED_COLOR);
+    }
+
+    // DXCC worked and confirmed on each band
+    tft.setTextColor (DETAIL_COLOR);
+    uint16_t x = box.x + 4;
+    uint16_t y = box.y + BANDS_Y0;
+    bool fit = true;
+    for (int b = 0; fit && b < HB_N; b++) {
+        int w, c;
+        countSlots (awards[AW_DXCC], (AwardMode)show_mode, (HamBand)b, w, c);
+        if (w > 0)
+            fit = drawBandCount (box, x, y, getHamBandName((HamBand)b), w, c);
+    }
+
+    return (true);
+}
+
+/* a tap in box steps to the next mode class.
+ * return whether we used it.
+ */
+bool checkAwardsTouch (const SCoord &s, const SBox &box)
+{
+    if (!inBox (s, box))
+        return (false);
+
+    show_mode = (show_mode + 1) % (AM_N+1);
+    (void) updateAwards (box);
+    return (true);
+}
+
+/* print each award worked and confirmed in every mode class, overall then per band
+ */
+void printAwards (WiFiClient &client)
+{
+    checkAwards();
+
+    char buf[100];
+    for (int a = 0; a < AW_N; a++) {
+        for (int m = AM_N; m >= 0; m--) {
+            int w, c;
+            countSlots (awards[a], (AwardMode)m, HB_NONE, w, c);
+            snprintf (buf, sizeof(buf), "%-4s %-5s %3d/%-3d", awards[a].name, am_names[m], w, c);
+            client.print (buf);
+            for (int b = 0; b < HB_N; b++) {
+                countSlots (awards[a], (AwardMode)m, (HamBand)b, w, c);
+                if (w > 0) {
+                    snprintf (buf, sizeof(buf), " %s %d/%d", getHamBandName((HamBand)b), w, c);
+                    client.print (buf);
+                }
+            }
+            client.println();
+        }
+    }
+}
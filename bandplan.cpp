@@ -0,0 +1,451 @@
+/* show the IARU band plan segments for one band in a plot pane, with the radio frequency marked
+ * when it lies within the band.
+ *
+ * the plans are simplified from the IARU Region 1, 2 and 3 HF and VHF band plans: each segment is
+ * labeled with its principal use only. national rules always take precedence.
+ */
+
+#include "HamClock.h"
+
+
+// layout
+#define TITLE_COLOR     RA8875_GREEN
+#define LABEL_COLOR     GRAY
+#define TITLE_Y0        27              // title dy, match VOACAP title position
+#define BAR_X           4               // segment bar x offset from box left and right
+#define BAR_Y0          36              // segment bar y down from box top
+#define BAR_H           16              // segment bar height
+#define RIG_Y0          54              // rig marker y down from box top
+#define RIG_H           5               // rig marker height
+#define EDGE_Y0         61              // band edge labels y down from box top
+#define LEGEND_Y0       76              // first segment listing y down from box top
+#define LEGEND_DY       11              // segment listing row spacing
+#define SWATCH_W        8               // segment listing color swatch width
+#define RIG_DY          12              // rig line y up from box bottom
+
+// principal use of each segment
+typedef enum {
+    BPM_CW,
+    BPM_DIG,
+    BPM_PHONE,
+    BPM_BCN,
+    BPM_ALL,
+    BPM_N
+} BandPlanMode;
+
+static const char *bpm_names[BPM_N] = {
+    "CW",
+    "Digital",
+    "Phone",
+    "Beacons",
+    "All modes",
+};
+
+// short names for labeling spots, none for segments open to all modes
+static const char *bpm_abbrs[BPM_N] = {
+    "CW",
+    "DIG",
+    "SSB",
+    "BCN",
+    NULL,
+};
+
+static const uint16_t bpm_colors[BPM_N] = {
+    RA8875_GREEN,
+    RA8875_CYAN,
+    RA8875_YELLOW,
+    RA8875_RED,
+    RA8875_MAGENTA,
+};
+
+// one segment of one band in one region, segments of each band are in increasing frequency
+typedef struct {
+    uint8_t region;                     // IARU region 1..3
+    uint8_t band;                       // band, meters
+    float lo_khz, hi_khz;               // segment edges
+    BandPlanMode mode;                  // principal use
+} BandSegment;
+
+static const BandSegment segments[] = {
+
+    // IARU Region 1: Europe, Africa, Middle East and northern Asia
+
+    {1, 160,   1810,    1838,   BPM_CW},
+    {1, 160,   1838,    1843,   BPM_DIG},
+    {1, 160,   1843,    2000,   BPM_PHONE},
+    {1,  80,   3500,    3570,   BPM_CW},
+    {1,  80,   3570,    3600,   BPM_DIG},
+    {1,  80,   3600,    3800,   BPM_PHONE},
+    {1,  60,   5351.5,  5354,   BPM_CW},
+    {1,  60,   5354,    5366,   BPM_PHONE},
+    {1,  60,   5366,    5366.5, BPM_DIG},
+    {1,  40,   7000,    7040,   BPM_CW},
+    {1,  40,   7040,    7050,   BPM_DIG},
+    {1,  40,   7050,    7200,   BPM_PHONE},
+    {1,  30,  10100,   10130,   BPM_CW},
+    {1,  30,  10130,   10150,   BPM_DIG},
+    {1,  20,  14000,   14070,   BPM_CW},
+    {1,  20,  14070,   14099,   BPM_DIG},
+    {1,  20,  14099,   14101,   BPM_BCN},
+    {1,  20,  14101,   14112,   BPM_DIG},
+    {1,  20,  14112,   14350,   BPM_PHONE},
+    {1,  17,  18068,   18095,   BPM_CW},
+    {1,  17,  18095,   18109,   BPM_DIG},
+    {1,  17,  18109,   18111,   BPM_BCN},
+    {1,  17,  18111,   18168,   BPM_PHONE},
+    {1,  15,  21000,   21070,   BPM_CW},
+    {1,  15,  21070,   21149,   BPM_DIG},
+    {1,  15,  21149,   21151,   BPM_BCN},
+    {1,  15,  21151,   21450,   BPM_PHONE},
+    {1,  12,  24890,   24915,   BPM_CW},
+    {1,  12,  24915,   24929,   BPM_DIG},
+    {1,  12,  24929,   24931,   BPM_BCN},
+    {1,  12,  24931,   24990,   BPM_PHONE},
+    {1,  10,  28000,   28070,   BPM_CW},
+    {1,  10,  28070,   28190,   BPM_DIG},
+    {1,  10,  28190,   28225,   BPM_BCN},
+    {1,  10,  28225,   29700,   BPM_PHONE},
+    {1,   6,  50000,   50100,   BPM_CW},
+    {1,   6,  50100,   50500,   BPM_PHONE},
+    {1,   6,  50500,   52000,   BPM_ALL},
+    {1,   2, 144000,  144150,   BPM_CW},
+    {1,   2, 144150,  144400,   BPM_PHONE},
+    {1,   2, 144400,  144500,   BPM_BCN},
+    {1,   2, 144500,  146000,   BPM_ALL},
+
+    // IARU Region 2: the Americas
+
+    {2, 160,   1800,    1840,   BPM_CW},
+    {2, 160,   1840,    1850,   BPM_DIG},
+    {2, 160,   1850,    2000,   BPM_PHONE},
+    {2,  80,   3500,    3580,   BPM_CW},
+    {2,  80,   3580,    3600,   BPM_DIG},
+    {2,  80,   3600,    4000,   BPM_PHONE},
+    {2,  60,   5351.5,  5354,   BPM_CW},
+    {2,  60,   5354,    5366,   BPM_PHONE},
+    {2,  60,   5366,    5366.5, BPM_DIG},
+    {2,  40,   7000,    7040,   BPM_CW},
+    {2,  40,   7040,    7050,   BPM_DIG},
+    {2,  40,   7050,    7300,   BPM_PHONE},
+    {2,  30,  10100,   10130,   BPM_CW},
+    {2,  30,  10130,   10150,   BPM_DIG},
+    {2,  20,  14000,   14070,   BPM_CW},
+    {2,  20,  14070,   14099,   BPM_DIG},
+    {2,  20,  14099,   14101,   BPM_BCN},
+    {2,  20,  14101,   14112,   BPM_DIG},
+    {2,  20,  14112,   14350,   BPM_PHONE},
+    {2,  17,  18068,   18095,   BPM_CW},
+    {2,  17,  18095,   18109,   BPM_DIG},
+    {2,  17,  18109,   18111,   BPM_BCN},
+    {2,  17,  18111,   18168,   BPM_PHONE},
+    {2,  15,  21000,   21070,   BPM_CW},
+    {2,  15,  21070,   21149,   BPM_DIG},
+    {2,  15,  21149,   21151,   BPM_BCN},
+    {2,  15,  21151,   21450,   BPM_PHONE},
+    {2,  12,  24890,   24915,   BPM_CW},
+    {2,  12,  24915,   24929,   BPM_DIG},
+    {2,  12,  24929,   24931,   BPM_BCN},
+    {2,  12,  24931,   24990,   BPM_PHONE},
+    {2,  10,  28000,   28070,   BPM_CW},
+    {2,  10,  28070,   28190,   BPM_DIG},
+    {2,  10,  28190,   28225,   BPM_BCN},
+    {2,  10,  28225,   29700,   BPM_PHONE},
+    {2,   6,  50000,   50100,   BPM_CW},
+    {2,   6,  50100,   50300,   BPM_PHONE},
+    {2,   6,  50300,   50600,   BPM_DIG},
+    {2,   6,  50600,   54000,   BPM_ALL},
+    {2,   2, 144000,  144100,   BPM_CW},
+    {2,   2, 144100,  144275,   BPM_PHONE},
+    {2,   2, 144275,  144300,   BPM_BCN},
+    {2,   2, 144300,  148000,   BPM_ALL},
+
+    // IARU Region 3: Asia-Pacific
+
+    {3, 160,   1800,    1830,   BPM_CW},
+    {3, 160,   1830,    1840,   BPM_DIG},
+    {3, 160,   1840,    2000,   BPM_PHONE},
+    {3,  80,   3500,    3535,   BPM_CW},
+    {3,  80,   3535,    3600,   BPM_DIG},
+    {3,  80,   3600,    3900,   BPM_PHONE},
+    {3,  60,   5351.5,  5354,   BPM_CW},
+    {3,  60,   5354,    5366,   BPM_PHONE},
+    {3,  60,   5366,    5366.5, BPM_DIG},
+    {3,  40,   7000,    7025,   BPM_CW},
+    {3,  40,   7025,    7040,   BPM_DIG},
+    {3,  40,   7040,    7300,   BPM_PHONE},
+    {3,  30,  10100,   10130,   BPM_CW},
+    {3,  30,  10130,   10150,   BPM_DIG},
+    {3,  20,  14000,   14070,   BPM_CW},
+    {3,  20,  14070,   14099,   BPM_DIG},
+    {3,  20,  14099,   14101,   BPM_BCN},
+    {3,  20,  14101,   14112,   BPM_DIG},
+    {3,  20,  14112,   14350,   BPM_PHONE},
+    {3,  17,  18068,   18095,   BPM_CW},
+    {3,  17,  18095,   18109,   BPM_DIG},
+    {3,  17,  18109,   18111,   BPM_BCN},
+    {3,  17,  18111,   18168,   BPM_PHONE},
+    {3,  15,  21000,   21070,   BPM_CW},
+    {3,  15,  21070,   21149,   BPM_DIG},
+    {3,  15,  21149,   21151,   BPM_BCN},
+    {3,  15,  21151,   21450,   BPM_PHONE},
+    {3,  12,  24890,   24915,   BPM_CW},
+    {3,  12,  24915,   24929,   BPM_DIG},
+    {3,  12,  24929,   24931,   BPM_BCN},
+    {3,  12,  24931,   24990,   BPM_PHONE},
+    {3,  10,  28000,   28070,   BPM_CW},
+    {3,  10,  28070,   28190,   BPM_DIG},
+    {3,  10,  28190,   28225,   BPM_BCN},
+    {3,  10,  28225,   29700,   BPM_PHONE},
+    {3,   6,  50000,   50100,   BPM_CW},
+    {3,   6,  50100,   50500,   BPM_PHONE},
+    {3,   6,  50500,   54000,   BPM_ALL},
+    {3,   2, 144000,  144100,   BPM_CW},
+    {3,   2, 144100,  144400,   BPM_PHONE},
+    {3,   2, 144400,  144500,   BPM_BCN},
+    {3,   2, 144500,  148000,   BPM_ALL},
+};
+
+// bands in the order they are stepped through, meters
+static const uint8_t bp_bands[] = {160, 80, 60, 40, 30, 20, 17, 15, 12, 10, 6, 2};
+#define BP_NBANDS       NARRAY(bp_bands)
+#define BP_DEFBAND      5               // index of 20 m
+
+// current settings, persisted in NV
+static uint8_t bp_region;               // 1..3, 0 until loaded
+static uint8_t bp_band;                 // index into bp_bands[]
+
+
+/* return a reasonable IARU region for the given location
+ */
+static uint8_t defaultRegion (const LatLong &ll)
+{
+    if (ll.lng_d < -30)
+        return (2);
+    if (ll.lng_d > 60 && ll.lat_d < 40)
+        return (3);
+    return (1);
+}
+
+/* insure bp_region and bp_band are loaded from NV and legal
+ */
+static void loadBandPlanNV (void)
+{
+    if (bp_region != 0)
+        return;
+
+    if (!NVReadUInt8 (NV_BPREGION, &bp_region) || bp_region < 1 || bp_region > 3) {
+        bp_region = defaultRegion (de_ll);
+        NVWriteUInt8 (NV_BPREGION, bp_region);
+    }
+    if (!NVReadUInt8 (NV_BPBAND, &bp_band) || bp_band >= BP_NBANDS) {
+        bp_band = BP_DEFBAND;
+        NVWriteUInt8 (NV_BPBAND, bp_band);
+    }
+}
+
+/* find the first segment and count of segments of the given band in the given region.
+ * return whether found.
+ */
+static bool findBandSegments (uint8_t region, uint8_t band, int &first, int &n)
+{
+    n = 0;
+    for (int i = 0; i < (int)NARRAY(segments); i++) {
+        if (segments[i].region == region && segments[i].band == band) {
+            if (n++ == 0)
+                first = i;
+        }
+    }
+    return (n > 0);
+}
+
+/* return x coordinate of the given frequency in the bar showing lo .. hi kHz
+ */
+static uint16_t khz2X (const SBox &box, float khz, float lo_khz, float hi_khz)
+{
+    int bar_w = box.w - 2*BAR_X;
+    return (box.x + BAR_X + roundf ((bar_w-1)*(khz - lo_khz)/(hi_khz - lo_khz)));
+}
+
+/* format the given frequency in MHz with no more decimals than needed, at most 4.
+ */
+static void fmtMHz (char *buf, size_t buf_len, float khz)
+{
+    float mhz = khz/1000;
+    snprintf (buf, buf_len, "%.4f", mhz);
+    for (char *cp = buf + strlen(buf) - 1; *cp == '0' && cp[-1] != '.'; cp--)
+        *cp = '\0';
+}
+
+/* draw the band plan for the current band and region in the given box.
+ * nothing to fetch so always return true.
+ */
+bool updateBandPlan (const SBox &box)
+{
+    loadBandPlanNV();
+    uint8_t band = bp_bands[bp_band];
+
+    prepPlotBox (box);
+
+    // title
+    char buf[40];
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (TITLE_COLOR);
+    snprintf (buf, sizeof(buf), "%dm Region %d", band, bp_region);
+    tft.setCursor (box.x + (box.w - getTextWidth(buf))/2, box.y + TITLE_Y0);
+    tft.print (buf);
+
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+
+    int first = 0, n;
+    if (!findBandSegments (bp_region, band, first, n)) {
+        tft.setTextColor (RA8875_RED);
+        const char *none = "No plan";
+        tft.setCursor (box.x + (box.w - getTextWidth(none))/2, box.y + LEGEND_Y0);
+        tft.print (none);
+        return (true);
+    }
+    float lo_khz = segments[first].lo_khz;
+    float hi_khz = segments[first+n-1].hi_khz;
+
+    // segment bar, each at least 1 pixel wide so beacon slots always show
+    for (int i = first; i < first+n; i++) {
+        const BandSegment &seg = segments[i];
+        uint16_t x0 = khz2X (box, seg.lo_khz, lo_khz, hi_khz);
+        uint16_t x1 = khz2X (box, seg.hi_khz, lo_khz, hi_khz);
+        uint16_t w = x1 > x0 ? x1 - x0 : 1;
+        tft.fillRect (x0, box.y + BAR_Y0, w, BAR_H, bpm_colors[seg.mode]);
+    }
+
+    // band edges
+    tft.setTextColor (LABEL_COLOR);
+    fmtMHz (buf, sizeof(buf), lo_khz);
+    tft.setCursor (box.x + BAR_X, box.y + EDGE_Y0);
+    tft.print (buf);
+    fmtMHz (buf, sizeof(buf), hi_khz);
+    tft.setCursor (box.x + box.w - BAR_X - getTextWidth(buf), box.y + EDGE_Y0);
+    tft.print (buf);
+
+    // segment listing
+    for (int i = 0; i < n; i++) {
+        const BandSegment &seg = segments[first+i];
+        uint16_t y = box.y + LEGEND_Y0 + i*LEGEND_DY;
+        tft.fillRect (box.x + BAR_X, y, SWATCH_W, SWATCH_W-1, bpm_colors[seg.mode]);
+        tft.setTextColor (RA8875_WHITE);
+        tft.setCursor (box.x + BAR_X + SWATCH_W + 4, y);
+        tft.print (bpm_names[seg.mode]);
+        snprintf (buf, sizeof(buf), "%g", seg.lo_khz);
+        tft.setCursor (box.x + box.w - BAR_X - getTextWidth(buf), y);
+        tft.print (buf);
+    }
+
+    // mark the radio frequency if known
+    float rig_khz;
+    if (getRigFrequency (rig_khz)) {
+        bool in_band = rig_khz >= lo_khz && rig_khz <= hi_khz;
+        if (in_band) {
+            uint16_t x = khz2X (box, rig_khz, lo_khz, hi_khz);
+            uint16_t y = box.y + RIG_Y0;
+            tft.fillTriangle (x, y, x-RIG_H, y+RIG_H, x+RIG_H, y+RIG_H, RA8875_WHITE);
+        }
+        char mhz[20];
+        fmtMHz (mhz, sizeof(mhz), rig_khz);
+        snprintf (buf, sizeof(buf), "Rig %s MHz", mhz);
+        tft.setTextColor (in_band ? RA8875_WHITE : LABEL_COLOR);
+        tft.setCursor (box.x + (box.w - getTextWidth(buf))/2, box.y + box.h - RIG_DY);
+        tft.print (buf);
+    }
+
+    return (true);
+}
+
+/* return the short name of the principal use of the given frequency in the current region,
+ * or NULL if outside the plan or open to all modes.
+ */
+const char *findBandPlanMode (float kHz)
+{
+    loadBandPlanNV();
+    for (int i = 0; i < (int)NARRAY(segments); i++) {
+        const BandSegment &seg = segments[i];
+        if (seg.region == bp_region && kHz >= seg.lo_khz && kHz < seg.hi_khz)
+            return (bpm_abbrs[seg.mode]);
+    }
+    return (NULL);
+}
+
+/* redraw the band plan if it is showing now
+ */
+static void refreshBandPlan (void)
+{
+    PlotPane pp = findPaneChoiceNow (PLOT_CH_BANDPLAN);
+    if (pp != PANE_NONE)
+        (void) updateBandPlan (plot_b[pp]);
+}
+
+/* called when the given location is tapped below the title of the given box showing the band plan.
+ * tapping left or right of the bar steps to the previous or next band, tapping the listing
+ * steps to the next region.
+ * return whether tap was ours.
+ */
+bool checkBandPlanTouch (const SCoord &s, const SBox &box)
+{
+    if (!inBox (s, box))
+        return (false);
+
+    loadBandPlanNV();
+    if (s.y < box.y + LEGEND_Y0) {
+        if (s.x < box.x + box.w/2)
+            bp_band = (bp_band + BP_NBANDS - 1) % BP_NBANDS;
+        else
+            bp_band = (bp_band + 1) % BP_NBANDS;
+        NVWriteUInt8 (NV_BPBAND, bp_band);
+    } else {
+        bp_region = bp_region % 3 + 1;
+        NVWriteUInt8 (NV_BPREGION, bp_region);
+    }
+
+    (void) updateBandPlan (box);
+    return (true);
+}
+
+/* pass back the current band plan region and band, meters
+ */
+void getBandPlan (int &region, int &band)
+{
+    loadBandPlanNV();
+    region = bp_region;
+    band = bp_bands[bp_band];
+}
+
+/* set the band plan region 1..3 and/or band, meters; either may be 0 to leave unchanged.
+ * return whether both are legal.
+ */
+bool setBandPlan (int region, int band)
+{
+    loadBandPlanNV();
+
+    if (region != 0 && (region < 1 || region > 3))
+        return (false);
+    int band_i = -1;
+    if (band != 0) {
+        for (int i = 0; i < (int)BP_NBANDS; i++) {
+            if (bp_bands[i] == band) {
+                band_i = i;
+                break;
+            }
+        }
+        if (band_i < 0)
+            return (false);
+    }
+
+    if (region != 0) {
+        bp_region = region;
+        NVWriteUInt8 (NV_BPREGION, bp_region);
+    }
+    if (band_i >= 0) {
+        bp_band = band_i;
+        NVWriteUInt8 (NV_BPBAND, bp_band);
+    }
+
+    refreshBandPlan();
+    return (true);
+}
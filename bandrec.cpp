@@ -0,0 +1,265 @@
+/* recommend which bands to work now and which way to point, ranked best first.
+ *
+ * each band is scored from how it sits below the current MUF, the D layer absorption expected for
+ * the sun elevation at DE, a greyline bonus for the low bands, a penalty for geomagnetic activity
+ * and, when fresh, the VOACAP path reliability to DX. the MUF is modeled from the solar flux and sun
+ * elevation at DE, then corrected by how the nearest ionosonde measures against the same model at its
+ * own location, trusting it less the farther it is from DE and the older its report. each band also
+ * gets a confidence from how much of that it could use. this is a rough guide, not a prediction.
+ */
+
+#include "HamClock.h"
+
+
+// layout
+#define TITLE_COLOR     RA8875_GREEN
+#define HEADING_COLOR   GRAY
+#define TITLE_Y0        27              // title dy, match VOACAP title position
+#define HEADING_Y0      34              // column headings y down from box top
+#define LISTING_Y0      47              // first band y down from box top
+#define LISTING_DY      11              // listing row separation
+#define BAND_X          10              // column x offsets from box left
+#define SCORE_X         50
+#define BEAM_X          96
+#define FOOTER_DY       12              // MUF source line y up from box bottom
+
+// scoring
+#define GREYLINE_ALT    deg2rad(6)      // sun this close to horizon at DE is greyline
+#define GREYLINE_BONUS  20              // score bonus for low bands on greyline
+#define LOWBAND_MHZ     10              // bands below this favor the dark path
+#define OWF_FRAC        0.85F           // optimum working frequency as fraction of MUF
+#define KP_QUIET        4               // Kp above this degrades the bands
+#define KP_PENALTY      8               // score lost per Kp above KP_QUIET
+#define KP_HILAT        50              // penalty doubles at DE latitudes beyond this, degs
+#define MIN_SCORE       20              // bands scoring below this are not recommended
+#define IONO_MAXAGE     (2*3600)        // ionosonde correction fades to nothing at this age, secs
+#define IONO_MAXDIST    3000            // ionosonde correction fades to nothing at this distance from DE, km
+#define PATH_MAXAGE     (2*3600)        // max age of VOACAP path reliability to use, secs
+#define SPW_MAXAGE      (24*3600)       // max age of flux and Kp to use, secs
+#define DEF_FLUX        100             // solar flux to assume if none known
+
+// confidence, percent
+#define CONF_MODEL      40              // scores from the flux model alone
+#define CONF_IONO       40              // most added by a near fresh ionosonde
+#define CONF_VOACAP     20              // added by fresh VOACAP path reliability
+#define CONF_EDGE       0.15F           // bands within this fraction of the MUF lose a quarter
+
+
+/* qsort-style function to sort BandRecs by decreasing score
+ */
+static int bandRecQS (const void *p1, const void *p2)
+{
+    return (((BandRec*)p2)->score - ((BandRec*)p1)->score);
+}
+
+/* return the 8 point compass direction of the given heading, degrees E of N
+ */
+const char *heading2Compass (int heading)
+{
+    static const char *dirs[8] = {"N", "NE", "E", "SE", "S", "SW", "W", "NW"};
+    return (dirs[((heading + 360 + 22) % 360) / 45]);
+}
+
+/* return the great circle angle between two locations, rads
+ */
+static float gcAngle (const LatLong &ll1, const LatLong &ll2)
+{
+    float cosa = sinf(ll1.lat)*sinf(ll2.lat) + cosf(ll1.lat)*cosf(ll2.lat)*cosf(ll1.lng-ll2.lng);
+    return (acosf (fmaxf (-1.0F, fminf (1.0F, cosa))));
+}
+
+/* return the MUF modeled at the given location from the solar flux and the sun elevation there, MHz:
+ * the day value falls by half through twilight to night.
+ */
+float modelMUF (const LatLong &ll)
+{
+    SpaceWeather spw;
+    getSpaceWeather (spw);
+    float sfi = spw.flux.age < SPW_MAXAGE ? spw.flux.value : DEF_FLUX;
+    float day_muf = 10 + sfi/10;
+
+    float sun_alt = M_PI_2F - gcAngle (ll, sun_ss_ll);
+    if (sun_alt > 0)
+        return (day_muf);
+    if (sun_alt < -GREYLINE_ALT)
+        return (day_muf/2);
+    return (day_muf*(1 + sun_alt/GREYLINE_ALT/2));
+}
+
+/* return how much weight to give the ionosonde correction at DE, 0 .. 1, and pass back its ratio of
+ * measured to modeled MUF.
+ */
+static float ionoWeight (float &ratio)
+{
+    LatLong iono_ll;
+    time_t iono_age;
+    if (!getIonosondeCorrection (ratio, iono_ll, iono_age))
+        return (0);
+
+    float km = gcAngle (iono_ll, de_ll) * ERAD_M * 1.609344F;      // mi -> km
+    float w_dist = 1 - km/IONO_MAXDIST;
+    float w_age = 1 - (float)iono_age/IONO_MAXAGE;
+    if (w_dist <= 0 || w_age <= 0)
+        return (0);
+    return (w_dist * w_age);
+}
+
+/* fill recs[] with up to n_recs bands worth working now, best first, and return the count.
+ * also pass back the MUF used, MHz, negative if it is from the model alone without any ionosonde correction.
+ */
+int getBandRecommendations (BandRec recs[], int n_recs, float &muf)
+{
+    // current space weather
+    SpaceWeather spw;
+    getSpaceWeather (spw);
+    bool voacap = spw.pathrel_age < PATH_MAXAGE;
+
+    // sun elevation and bearing from DE
+    LatLong ss_ll = sun_ss_ll;
+    float sun_dist, sun_bear;
+    propDEDXPath (false, ss_ll, &sun_dist, &sun_bear);
+    float sun_alt = M_PI_2F - sun_dist;
+    bool greyline = fabsf (sun_alt) < GREYLINE_ALT;
+
+    // MUF modeled at DE, scaled toward how the nearest ionosonde compares with the same model
+    float ratio;
+    float iono_w = ionoWeight (ratio);
+    muf = modelMUF (de_ll);
+    if (iono_w > 0)
+        muf *= 1 + iono_w*(ratio - 1);
+
+    // fraction of D layer absorption
+    float absorption = sun_alt > 0 ? sinf (sun_alt) : 0;
+
+    // geomagnetic penalty
+    int kp_penalty = 0;
+    if (spw.kp.age < SPW_MAXAGE && spw.kp.value > KP_QUIET) {
+        kp_penalty = (spw.kp.value - KP_QUIET) * KP_PENALTY;
+        if (fabsf (de_ll.lat_d) > KP_HILAT)
+            kp_penalty *= 2;
+    }
+
+    // score each band
+    BandRec all[PROP_MAP_N];
+    float owf = OWF_FRAC * muf;
+    for (int i = 0; i < PROP_MAP_N; i++) {
+        float mhz = propMap2MHz ((PropMapSetting)i);
+        float score;
+
+        // best just below OWF, lower bands lose more the more absorption, none above MUF
+        if (mhz <= owf)
+            score = 100 * (1 - absorption*(1 - mhz/owf));
+        else if (mhz <= muf)
+            score = 100 - 50*(mhz - owf)/(muf - owf);
+        else
+            score = 0;
+
+        if (greyline && mhz < LOWBAND_MHZ)
+            score += GREYLINE_BONUS;
+        if (score > 0)
+            score -= kp_penalty;
+
+        // blend in VOACAP if fresh
+        if (voacap)
+            score = (score + 100*spw.pathrel[i])/2;
+
+        // confidence from what went into it, less near the MUF where the band may open or close
+        float conf = CONF_MODEL + CONF_IONO*iono_w + (voacap ? CONF_VOACAP : 0);
+        if (fabsf (mhz - muf) < CONF_EDGE*muf)
+            conf *= 0.75F;
+
+        // low bands go via the dark side or along the greyline, high bands toward the sunlit side
+        float bear = sun_bear;
+        if (mhz < LOWBAND_MHZ)
+            bear += greyline ? M_PI_2F : M_PIF;
+
+        BandRec &r = all[i];
+        r.band = propMap2Band ((PropMapSetting)i);
+        r.score = score < 0 ? 0 : (score > 100 ? 100 : roundf(score));
+        r.heading = ((int)roundf(rad2deg(bear)) + 360) % 360;
+        r.confidence = roundf (conf);
+    }
+
+    // rank, keep the worthwhile
+    qsort (all, PROP_MAP_N, sizeof(BandRec), bandRecQS);
+    int n = 0;
+    for (int i = 0; i < PROP_MAP_N && n < n_recs && all[i].score >= MIN_SCORE; i++)
+        recs[n++] = all[i];
+
+    if (iono_w <= 0)
+        muf = -muf;
+    return (n);
+}
+
+/* return color to show the given score
+ */
+static uint16_t scoreColor (int score)
+{
+    if (score >= 70)
+        return (RA8875_GREEN);
+    if (score >= 40)
+        return (RA8875_YELLOW);
+    return (RA8875_WHITE);
+}
+
+/* draw the current band recommendations in the given box.
+ * nothing to fetch so always return true.
+ */
+bool updateBandRec (const SBox &box)
+{
+    BandRec recs[PROP_MAP_N];
+    float muf;
+    int n_recs = getBandRecommendations (recs, PROP_MAP_N, muf);
+
+    prepPlotBox (box);
+
+    // title
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (TITLE_COLOR);
+    const char *title = xlate ("Best Bands");
+    tft.setCursor (box.x + (box.w - getTextWidth(title))/2, box.y + TITLE_Y0);
+    tft.print (title);
+
+    // column headings
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    tft.setTextColor (HEADING_COLOR);
+    tft.setCursor (box.x + BAND_X, box.y + HEADING_Y0);
+    tft.print (F("Band"));
+    tft.setCursor (box.x + SCORE_X, box.y + HEADING_Y0);
+    tft.print (F("Score"));
+    tft.setCursor (box.x + BEAM_X, box.y + HEADING_Y0);
+    tft.print (F("Beam"));
+
+    char buf[30];
+    if (n_recs == 0) {
+        tft.setTextColor (RA8875_RED);
+        const char *none = "None open";
+        tft.setCursor (box.x + (box.w - getTextWidth(none))/2, box.y + LISTING_Y0 + LISTING_DY);
+        tft.print (none);
+    }
+    for (int i = 0; i < n_recs; i++) {
+        const BandRec &r = recs[i];
+        uint16_t y = box.y + LISTING_Y0 + i*LISTING_DY;
+
+        tft.setTextColor (scoreColor (r.score));
+        snprintf (buf, sizeof(buf), "%2dm", r.band);
+        tft.setCursor (box.x + BAND_X, y);
+        tft.print (buf);
+
+        snprintf (buf, sizeof(buf), "%3d", r.score);
+        tft.setCursor (box.x + SCORE_X, y);
+        tft.print (buf);
+
+        snprintf (buf, sizeof(buf), "%-2s %3d", heading2Compass(r.heading), r.heading);
+        tft.setCursor (box.x + BEAM_X, y);
+        tft.print (buf);
+    }
+
+    // note MUF source
+    tft.setTextColor (HEADING_COLOR);
+    snprintf (buf, sizeof(buf), "MUF %.0f MHz %s", fabsf(muf), muf < 0 ? "est" : "corr");
+    tft.setCursor (box.x + (box.w - getTextWidth(buf))/2, box.y + box.h - FOOTER_DY);
+    tft.print (buf);
+
+    return (true);
+}
@@ -0,0 +1,65 @@
+/* classify frequencies into amateur bands.
+ *
+ * band edges are the widest allocation in any IARU region so a frequency is assigned to a band if it
+ * is legal there anywhere. use findBandPlanMode() for the sub-band use within the local region.
+ */
+
+#include "HamClock.h"
+
+
+// one band
+typedef struct {
+    const char *name;                   // common name
+    float lo_khz, hi_khz;               // edges
+} BandEdges;
+
+// N.B. must be in the same order as HamBand
+static const BandEdges bands[HB_N] = {
+    { "160m",      1800,    2000 },
+    { "80m",       3500,    4000 },
+    { "60m",       5250,    5450 },
+    { "40m",       7000,    7300 },
+    { "30m",      10100,   10150 },
+    { "20m",      14000,   14350 },
+    { "17m",      18068,   18168 },
+    { "15m",      21000,   21450 },
+    { "12m",      24890,   24990 },
+    { "10m",      28000,   29700 },
+    { "6m",       50000,   54000 },
+    { "4m",       70000,   70500 },
+    { "2m",      144000,  148000 },
+    { "1.25m",   222000,  225000 },
+    { "70cm",    420000,  450000 },
+    { "33cm",    902000,  928000 },
+    { "23cm",   1240000, 1300000 },
+};
+
+
+/* return the band containing the given frequency, else HB_NONE.
+ */
+HamBand findHamBand (float kHz)
+{
+    for (int i = 0; i < HB_N; i++)
+        if (kHz >= bands[i].lo_khz && kHz <= bands[i].hi_khz)
+            return ((HamBand)i);
+    return (HB_NONE);
+}
+
+/* return the common name of the given band, eg "20m", or "?" if HB_NONE.
+ */
+const char *getHamBandName (HamBand b)
+{
+    return (b < HB_N ? bands[b].name : "?");
+}
+
+/* pass back the edges of the given band, kHz.
+ * return whether b is a real band.
+ */
+bool getHamBandEdges (HamBand b, float &lo_khz, float &hi_khz)
+{
+    if (b >= HB_N)
+        return (false);
+    lo_khz = bands[b].lo_khz;
+    hi_khz = bands[b].hi_khz;
+    return (true);
+}
@@ -14,14 +14,18 @@
  *        Gnd
  *
  *
+ * UNIX systems with I2C may instead have a BH1750 ambient light sensor at LUX_I2C. Its lux is mapped to
+ * the same phot range on a log scale so the same dim and bright calibration controls apply.
+ *
  * H/W Capability matrix:
  *   FB0 = _IS_RPI && _USE_FB0
  *   FS  = !IS_ESP && getX11FullScreen()
- *   DSI = _IS_RPI and display is DSI
+ *   BL  = _IS_RPI and display has a backlight in /sys/class/backlight, eg DSI
+ *   LUX = _SUPPORT_LUXSENSOR and BH1750 responds
  *
  *                         On/Off          Bightness     PhotoR
  *  _IS_ESP8266              Y                 Y           Y
- *  (FB0 || FS) && DSI       Y                 Y           N
+ *  (FB0 || FS) && BL        Y                 Y           LUX
  *  FB0 || FS                Y                 N           N
  *  else                     N                 N           N
  *    
@@ -30,6 +34,14 @@
 
 #include "HamClock.h"
 
+#if defined(_SUPPORT_LUXSENSOR)
+#include <Wire.h>
+#endif
+
+#if defined(_IS_LINUX_RPI) || defined(_USE_FB0)
+#include <dirent.h>
+#endif
+
 
 // configuration values
 #define BPWM_MAX        255                     // PWM for 100% brightness
@@ -48,6 +60,8 @@
 #define MARKER_H        3                       // scaler marker height
 #define SCALE_W         5                       // scale width
 #define FOLLOW_DT       100                     // read phot this often, ms
+#define LUX_I2C         0x23                    // BH1750 address with ADDR pin low
+#define LUX_MAX         10000.0F                // lux mapped to PHOT_MAX, full daylight indoors
 
 static int16_t bpwm;                            // current brightness PWM value 0 .. BPWM_M
 static uint16_t phot;                           // current photorestistor value
@@ -67,8 +81,19 @@ static bool support_onoff;                      // whether we support display on
 static bool support_dim;                        // whether we support display fine brightness control
 static bool support_phot;                       // whether we support a photoresistor
 
-// RPi path to set DSI brightness, write 0 .. 255
-static const char dsi_path[] = "/sys/class/backlight/rpi_backlight/brightness";
+// alert severities that may turn the display back on, N.B. index is AlertSeverity+1 and is stored in NV_BR_WAKE.
+// old_wake_names are the alert kinds once used instead, still accepted by setDisplayWake().
+static const char *wake_names[] = { "none", "info", "warning", "critical" };
+static const char *old_wake_names[] = { "none", "watch", "countdown", "alarm" };
+#define N_WAKE          NARRAY(wake_names)
+#define DEF_WAKE        3                       // default wakes only for critical
+static uint8_t wake_level;                      // alerts at least this severe wake, 0 for none
+
+// RPi backlight brightness control, write 0 .. bl_max
+#define BL_DIR          "/sys/class/backlight"  // one subdir per backlight
+static const char rpi_bl[] = "rpi_backlight";   // preferred if there are several
+static char bl_path[100];                       // BL_DIR/name/brightness if found
+static int bl_max;                              // its max_brightness
 
 // forward references
 static void engageDisplayBrightness(bool log);
@@ -91,15 +116,15 @@ static void setDisplayBrightness(bool log)
     #else
 
         if (support_dim) {
-            // control DSI backlight
-            int dsifd = open (dsi_path, O_WRONLY);
+            // control backlight
+            int dsifd = open (bl_path, O_WRONLY);
             if (dsifd < 0) {
-                Serial.printf ("BR: %s: %s\n", dsi_path, strerror(errno));
+                Serial.printf ("BR: %s: %s\n", bl_path, strerror(errno));
             } else {
                 if (log)
                     Serial.printf ("BR: setting bpwm %d\n", bpwm);
                 FILE *dsifp = fdopen (dsifd, "w");
-                fprintf (dsifp, "%d\n", bpwm);
+                fprintf (dsifp, "%d\n", (bpwm*bl_max + BPWM_MAX/2)/BPWM_MAX);
                 fclose (dsifp);
             }
         } else if (support_onoff) {
@@ -113,11 +138,62 @@ static void setDisplayBrightness(bool log)
     #endif
 }
 
+#if defined(_SUPPORT_LUXSENSOR)
+
+/* return whether a BH1750 ambient light sensor responds at LUX_I2C, starting it measuring if so.
+ * only probes once.
+ */
+static bool luxSensorOk()
+{
+        static bool know, found;
+
+        if (!know) {
+            Wire.begin();
+            Wire.beginTransmission (LUX_I2C);
+            Wire.write (0x01);                  // power on
+            found = Wire.endTransmission() == 0;
+            if (found) {
+                Wire.beginTransmission (LUX_I2C);
+                Wire.write (0x10);              // continuous 1 lux resolution
+                found = Wire.endTransmission() == 0;
+            }
+            Serial.printf (_FX("BR: BH1750 light sensor %s\n"), found ? "found" : "not found");
+            know = true;
+        }
+
+        return (found);
+}
+
+/* read the BH1750 and return its lux mapped onto [0..PHOT_MAX] on a log scale, or phot if trouble.
+ */
+static uint16_t readLuxPhot()
+{
+        if (Wire.requestFrom (LUX_I2C, 2) != 2)
+            return (phot);
+        int raw = Wire.read() << 8;
+        raw |= Wire.read();
+        float lux = raw / 1.2F;                 // per data sheet
+        if (lux > LUX_MAX)
+            lux = LUX_MAX;
+        return (PHOT_MAX * log10f (1 + lux) / log10f (1 + LUX_MAX));
+}
+
+#endif // _SUPPORT_LUXSENSOR
+
 /* return current photo detector value, range [0..PHOT_MAX] increasing with brightness.
  */
 static uint16_t readPhot()
 {
-    #if defined(_SUPPORT_PHOT)
+    #if defined(_SUPPORT_LUXSENSOR)
+
+        if (!luxSensorOk())
+            return (0);
+
+        uint16_t new_phot = readLuxPhot();
+
+        return (PHOT_BLEND*new_phot + (1-PHOT_BLEND)*phot);             // smoothing
+
+    #elif defined(_SUPPORT_PHOT)
 
         resetWatchdog();
 
@@ -556,7 +632,35 @@ static void engageDisplayBrightness(bool log)
 
 #if defined(_IS_LINUX_RPI) || defined(_USE_FB0)
 
-/* return whether this is a linux RPi connected to a DSI display
+/* set bl_path and bl_max if name in BL_DIR is a backlight we can write.
+ * return whether ok.
+ */
+static bool tryBacklight (const char *name)
+{
+        char max_path[sizeof(bl_path)];
+        snprintf (max_path, sizeof(max_path), "%s/%s/max_brightness", BL_DIR, name);
+        FILE *fp = fopen (max_path, "r");
+        if (!fp)
+            return (false);
+        int max = 0;
+        bool ok = fscanf (fp, "%d", &max) == 1 && max > 0;
+        fclose (fp);
+        if (!ok)
+            return (false);
+
+        char path[sizeof(bl_path)];
+        snprintf (path, sizeof(path), "%s/%s/brightness", BL_DIR, name);
+        int fd = open (path, O_WRONLY);
+        if (fd < 0)
+            return (false);
+        close (fd);
+
+        strcpy (bl_path, path);
+        bl_max = max;
+        return (true);
+}
+
+/* return whether this is a linux RPi whose display has a backlight we can control, such as DSI.
  */
 static bool isRPiDSI()
 {
@@ -567,16 +671,22 @@ static bool isRPiDSI()
 
             resetWatchdog();
 
-            int dsifd = open (dsi_path, O_WRONLY);
-            if (dsifd >= 0) {
-                Serial.print (_FX("BR: found DSI display\n"));
-                close (dsifd);
-                isdsi = true;
-            } else {
-                Serial.print (_FX("BR: no DSI display\n"));
-                isdsi = false;
+            // prefer the official display, else first that works
+            isdsi = tryBacklight (rpi_bl);
+            DIR *dirp = isdsi ? NULL : opendir (BL_DIR);
+            if (dirp) {
+                struct dirent *dp;
+                while (!isdsi && (dp = readdir (dirp)) != NULL)
+                    if (dp->d_name[0] != '.')
+                        isdsi = tryBacklight (dp->d_name);
+                closedir (dirp);
             }
 
+            if (isdsi)
+                Serial.printf (_FX("BR: found backlight %s max %d\n"), bl_path, bl_max);
+            else
+                Serial.print (_FX("BR: no backlight\n"));
+
             know = true;
         }
 
@@ -626,9 +736,11 @@ bool brOnOffOk()
  */
 static bool photOk()
 {
-        // determine photoresistor support, need ADC only on ESP
+        // determine photoresistor support, need ADC only on ESP or a light sensor to control a backlight
         #if defined(_IS_ESP8266)
             support_phot = true;
+        #elif defined(_SUPPORT_LUXSENSOR)
+            support_phot = support_dim && luxSensorOk();
         #else
             support_phot = false;
         #endif
@@ -662,7 +774,11 @@ void initBrightness()
         for (uint8_t i = 0; i < 10; i++)
             (void) readPhot();
         phot = readPhot();
-        found_phot = phot > 1;  // in case they ever fix the range bug
+        #if defined(_SUPPORT_LUXSENSOR)
+            found_phot = luxSensorOk();         // dark room reads 0
+        #else
+            found_phot = phot > 1;  // in case they ever fix the range bug
+        #endif
         Serial.printf (_FX("BR: phot %d %s\n"), phot, found_phot ? "found" : "not found");
 
         // full on for now
@@ -697,6 +813,12 @@ void setupBrightness()
         setDisplayBrightness(true);
         clock_off = false;
 
+        // init which alerts wake the display
+        if (!NVReadUInt8 (NV_BR_WAKE, &wake_level) || wake_level >= N_WAKE) {
+            wake_level = DEF_WAKE;
+            NVWriteUInt8 (NV_BR_WAKE, wake_level);
+        }
+
         // init idle time and period
         idle_t0 = millis();
         if (!NVReadUInt16 (NV_BR_IDLE, &idle_mins)) {
@@ -1052,6 +1174,40 @@ bool getDisplayOnOffTimes (int dow, uint16_t &on, uint16_t &off)
 }
 
 
+/* called with the kind and severity of each alert as it happens to turn the display back on if it is off
+ * and the alert is at least as severe as the user's wake level.
+ */
+void wakeDisplayForAlert (const char *kind, AlertSeverity sev)
+{
+    if (!clock_off || wake_level == 0 || sev+1 < wake_level)
+        return;
+
+    Serial.printf (_FX("BR: %s %s alert wakes display\n"), alertSeverityName(sev), kind);
+    (void) brightnessOn();
+}
+
+/* return name of the least severe alert that wakes the display, or none
+ */
+const char *getDisplayWakeName(void)
+{
+    return (wake_names[wake_level]);
+}
+
+/* set the least severe alert that wakes the display by name, or by its old kind name,
+ * return whether recognized
+ */
+bool setDisplayWake (const char *name)
+{
+    for (unsigned i = 0; i < N_WAKE; i++) {
+        if (strcmp (name, wake_names[i]) == 0 || strcmp (name, old_wake_names[i]) == 0) {
+            wake_level = i;
+            NVWriteUInt8 (NV_BR_WAKE, wake_level);
+            return (true);
+        }
+    }
+    return (false);
+}
+
 /* call to force full brightness, for example just before shutting down.
  */
 void setFullBrightness()
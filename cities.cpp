@@ -1,5 +1,7 @@
 /* manage list of cities.
  * sparse 2d table contains largest city in each region.
+ * also find the location of a city by name, first from the table, else from a local file the user may
+ * add to for places the table lacks. N.B. there is no network geocoder, only these two local sources.
  */
 
 #include "HamClock.h"
@@ -8,6 +10,8 @@
 
 #if defined(_IS_UNIX)
 
+#include <errno.h>
+
 // one city entry
 typedef struct {
     const char *name;           // malloced name
@@ -28,6 +32,10 @@ static LatRow *latrows;         // malloced list of 180/LAT_SIZ rows
 // name of server file containing cities
 static const char cities_fn[] = "/ham/HamClock/cities.txt";
 
+// name of optional local file in our_dir of more places, one per line as lat lng name\tfull name,
+// eg "32.2217 -110.9265 Home\tTucson, AZ, USA". lines starting with # are ignored.
+static const char geocode_fn[] = "geocode.txt";
+
 /* qsort-style function to compare a pair of pointers to City by lngbin
  */
 static int cityQS (const void *p1, const void *p2)
@@ -36,7 +44,78 @@ static int cityQS (const void *p1, const void *p2)
 }
 
 
+/* fill latrows from the given text of the cities file, one line per city after a bin size line.
+ * return number of cities found, or -1 if the text is not usable.
+ */
+static int crackCities (const char *text)
+{
+        // first line is binning sizes
+        if (sscanf (text, "%d %d", &LAT_SIZ, &LNG_SIZ) != 2 || LAT_SIZ <= 0 || LNG_SIZ <= 0) {
+            Serial.print (F("Cities: bad bin line\n"));
+            return (-1);
+        }
+
+        // create row array
+        latrows = (LatRow *) calloc (180/LAT_SIZ, sizeof(LatRow));
+        if (!latrows) {
+            Serial.print (F("Cities: no latrows memory\n"));
+            return (-1);
+        }
+
+        // read each city
+        int n_cities = 0;
+        for (const char *next = strchr (text, '\n'); next != NULL; next = strchr (next, '\n')) {
+
+            // copy next line
+            char line[100];
+            next += 1;
+            size_t ll = strcspn (next, "\n");
+            if (ll >= sizeof(line))
+                continue;
+            memcpy (line, next, ll);
+            line[ll] = '\0';
+
+            // crack info
+            float rlat, rlng;
+            if (sscanf (line, "%f, %f", &rlat, &rlng) != 2)
+                continue;
+            int latbin = LAT_SIZ*floorf(rlat/LAT_SIZ);
+            int lngbin = LAT_SIZ*floorf(rlng/LNG_SIZ);
+            if (latbin<-90 || latbin>=90 || lngbin<-180 || lngbin>=180)
+                continue;
+            char *city_start = strchr (line, '"');
+            if (!city_start)
+                continue;
+            city_start += 1;
+            char *city_end = strchr (city_start, '"');
+            if (!city_end)
+                continue;
+            *city_end = '\0';
+
+            // append to appropriate latrow, sort later
+            LatRow *lrp = &latrows[(latbin+90)/LAT_SIZ];
+            lrp->lngs = (City *) realloc (lrp->lngs, (lrp->n_lngs+1) * sizeof(City));
+            City *cp = &lrp->lngs[lrp->n_lngs++];
+            cp->name = strdup (city_start);
+            cp->lngbin = lngbin;
+            cp->lat = rlat;
+            cp->lng = rlng;
+
+            // good
+            n_cities++;
+
+        }
+        Serial.printf (_FX("Cities: found %d\n"), n_cities);
+
+        // sort each row
+        for (int i = 0; i < 180/LAT_SIZ; i++)
+            qsort (latrows[i].lngs, latrows[i].n_lngs, sizeof(City), cityQS);
+
+        return (n_cities);
+}
+
 /* query for list of cities, fill regions.
+ * if the server can not be reached use the copy saved the last time it could.
  * harmless if called more than once.
  * N.B. UNIX only.
  */
@@ -67,63 +146,27 @@ void readCities()
                 goto out;
             }
 
-            // first line is binning sizes
+            // collect whole file so we can save it too
+            std::string text;
             char line[100];
-            if (!getTCPLine (cities_client, line, sizeof(line), NULL)) {
-                Serial.print (F("Cities: no bin line\n"));
-                goto out;
-            }
-            if (sscanf (line, "%d %d", &LAT_SIZ, &LNG_SIZ) != 2) {
-                Serial.printf (_FX("Cities: bad bin line: %s\n"), line);
-                goto out;
-            }
-
-            // create row array
-            latrows = (LatRow *) calloc (180/LAT_SIZ, sizeof(LatRow));
-            if (!latrows) {
-                Serial.print (F("Cities: no latrows memory\n"));
-                goto out;
+            while (getTCPLine (cities_client, line, sizeof(line), NULL)) {
+                text += line;
+                text += '\n';
             }
 
-            // read each city
-            int n_cities = 0;
-            while (getTCPLine (cities_client, line, sizeof(line), NULL)) {
+            // use and keep for next time if good
+            if (crackCities (text.c_str()) > 0)
+                saveDataBundle ("cities", text.c_str(), text.size());
 
-                // crack info
-                float rlat, rlng;
-                if (sscanf (line, "%f, %f", &rlat, &rlng) != 2)
-                    continue;
-                int latbin = LAT_SIZ*floorf(rlat/LAT_SIZ);
-                int lngbin = LAT_SIZ*floorf(rlng/LNG_SIZ);
-                if (latbin<-90 || latbin>=90 || lngbin<-180 || lngbin>=180)
-                    continue;
-                char *city_start = strchr (line, '"');
-                if (!city_start)
-                    continue;
-                city_start += 1;
-                char *city_end = strchr (city_start, '"');
-                if (!city_end)
-                    continue;
-                *city_end = '\0';
-
-                // append to appropriate latrow, sort later
-                LatRow *lrp = &latrows[(latbin+90)/LAT_SIZ];
-                lrp->lngs = (City *) realloc (lrp->lngs, (lrp->n_lngs+1) * sizeof(City));
-                City *cp = &lrp->lngs[lrp->n_lngs++];
-                cp->name = strdup (city_start);
-                cp->lngbin = lngbin;
-                cp->lat = rlat;
-                cp->lng = rlng;
-
-                // good
-                n_cities++;
+        } else {
 
+            // try last good copy
+            time_t fetched;
+            char *text = loadDataBundle ("cities", fetched);
+            if (text) {
+                crackCities (text);
+                free (text);
             }
-            Serial.printf (_FX("Cities: found %d\n"), n_cities);
-
-            // sort each row
-            for (int i = 0; i < 180/LAT_SIZ; i++)
-                qsort (latrows[i].lngs, latrows[i].n_lngs, sizeof(City), cityQS);
         }
 
     out:
@@ -162,10 +205,110 @@ const char *getNearestCity (const LatLong &ll, LatLong &city_ll)
 
 }
 
+/* return whether the given city name matches name, ignoring case and anything in city after a comma.
+ */
+static bool cityNameMatches (const char *city, const char *name)
+{
+    size_t nl = strlen (name);
+    return (strncasecmp (city, name, nl) == 0 && (city[nl] == '\0' || city[nl] == ','));
+}
+
+/* look for name in the table of cities.
+ * return whether found and, if so, fill ll and found.
+ */
+static bool findTableCity (const char *name, LatLong &ll, char *found, size_t found_len)
+{
+    if (!latrows)
+        return (false);
+
+    for (int i = 0; i < 180/LAT_SIZ; i++) {
+        LatRow *lrp = &latrows[i];
+        for (int j = 0; j < lrp->n_lngs; j++) {
+            City *cp = &lrp->lngs[j];
+            if (cityNameMatches (cp->name, name)) {
+                ll.lat_d = cp->lat;
+                ll.lng_d = cp->lng;
+                snprintf (found, found_len, "%s", cp->name);
+                return (true);
+            }
+        }
+    }
+
+    return (false);
+}
+
+/* look for name in the user's local file of places.
+ * return whether found and, if so, fill ll and found.
+ */
+static bool findFileCity (const char *name, LatLong &ll, char *found, size_t found_len)
+{
+    std::string path = our_dir + geocode_fn;
+    FILE *fp = fopen (path.c_str(), "r");
+    if (!fp)
+        return (false);
+
+    char line[200];
+    bool ok = false;
+    while (!ok && fgets (line, sizeof(line), fp)) {
+        float lat, lng;
+        char key[100], result[100];
+        if (line[0] != '#' && sscanf (line, "%f %f %99[^\t]\t%99[^\n]", &lat, &lng, key, result) == 4
+                                                        && strcasecmp (key, name) == 0) {
+            ll.lat_d = lat;
+            ll.lng_d = lng;
+            snprintf (found, found_len, "%s", result);
+            ok = true;
+        }
+    }
+
+    fclose (fp);
+    return (ok);
+}
+
+/* find the location of the given city name, eg, "Tucson" or "Paris, France".
+ * return whether found and, if so, fill ll and the full name of the place found,
+ * else fill found with reason for failure.
+ */
+bool geocodeCity (const char *name, LatLong &ll, char *found, size_t found_len)
+{
+    // skip surrounding blanks
+    char key[100];
+    while (isspace(*name))
+        name++;
+    snprintf (key, sizeof(key), "%s", name);
+    for (size_t kl = strlen(key); kl > 0 && isspace(key[kl-1]); )
+        key[--kl] = '\0';
+    if (key[0] == '\0') {
+        snprintf (found, found_len, _FX("no city name"));
+        return (false);
+    }
+
+    // try the table then the user's file
+    bool ok = findTableCity (key, ll, found, found_len) || findFileCity (key, ll, found, found_len);
+    if (!ok)
+        snprintf (found, found_len, _FX("not in cities table or %s"), geocode_fn);
+
+    if (ok) {
+        normalizeLL (ll);
+        Serial.printf (_FX("Geocode: %s: %s at %.4f %.4f\n"), key, found, ll.lat_d, ll.lng_d);
+    } else
+        Serial.printf (_FX("Geocode: %s: %s\n"), key, found);
+
+    return (ok);
+}
+
 #else
 
 // dummies
 
+bool geocodeCity (const char *name, LatLong &ll, char *found, size_t found_len)
+{
+    (void) name;
+    (void) ll;
+    snprintf (found, found_len, _FX("city names require UNIX"));
+    return (false);
+}
+
 const char *getNearestCity (const LatLong &ll, LatLong &city_ll) {
     (void) ll;
     (void) city_ll;
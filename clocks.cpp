@@ -26,6 +26,15 @@ static uint8_t doy_on;                          // show day of year instead of m
 // TimeLib's now() stays at real UTC, but user can adjust time offset
 static int32_t utc_offset;                      // nowWO() offset from UTC, secs
 
+// time zone and hour format used to show scheduled events such as satellite passes
+#define EVT_ZMASK       0x7F                    // NV_EVENTTIMES bits for EventTZ
+#define EVT_12HR        0x80                    // NV_EVENTTIMES bit for 12 hour format
+static uint8_t event_times;                     // EventTZ | EVT_12HR
+static bool event_times_init;                   // set once event_times is loaded from NV
+static const char *event_tz_names[EVTZ_N] = {   // N.B. must match EventTZ order
+    "DE", "UTC", "local"
+};
+
 // display 
 #define UTC_W           14                      // UTC button width
 #define QUESTION_W      28                      // Question mark width
@@ -835,3 +844,114 @@ void drawTZ (const TZInfo &tzi)
     tft.setCursor (tzi.box.x+(tzi.box.w-w)/2, tzi.box.y+(tzi.box.h-h)/2);
     tft.print (buf);
 }
+
+/* load event_times from NV if not already
+ */
+static void loadEventTimes()
+{
+    if (event_times_init)
+        return;
+
+    if (!NVReadUInt8 (NV_EVENTTIMES, &event_times) || (event_times & EVT_ZMASK) >= EVTZ_N) {
+        event_times = EVTZ_DE;
+        NVWriteUInt8 (NV_EVENTTIMES, event_times);
+    }
+    #if !defined(_IS_UNIX)
+        // no time zone database
+        if ((event_times & EVT_ZMASK) == EVTZ_LOCAL)
+            event_times = (event_times & ~EVT_ZMASK) | EVTZ_DE;
+    #endif
+    event_times_init = true;
+}
+
+/* convert the given UTC time to the time zone chosen for showing events.
+ * the local zone uses the system time zone database so it includes daylight time at that moment.
+ */
+time_t eventTime (time_t utc)
+{
+    loadEventTimes();
+
+    switch ((EventTZ)(event_times & EVT_ZMASK)) {
+    case EVTZ_UTC:
+        return (utc);
+    case EVTZ_LOCAL:
+        #if defined(_IS_UNIX)
+        {
+            struct tm tm;
+            localtime_r (&utc, &tm);
+            return (utc + tm.tm_gmtoff);
+        }
+        #endif
+        // fallthru
+    case EVTZ_DE:
+    default:
+        return (utc + de_tz.tz_secs);
+    }
+}
+
+/* format the hours and minutes of the given eventTime() into buf in the chosen 12 or 24 hour format.
+ */
+void formatEventHM (time_t t, char *buf, size_t buf_len)
+{
+    loadEventTimes();
+
+    int hr = hour(t);
+    if (event_times & EVT_12HR)
+        snprintf (buf, buf_len, "%2d:%02d%c", hr%12 ? hr%12 : 12, minute(t), hr < 12 ? 'a' : 'p');
+    else
+        snprintf (buf, buf_len, "%02dh%02d", hr, minute(t));
+}
+
+/* return name of the time zone chosen for showing events.
+ */
+const char *getEventTZName()
+{
+    loadEventTimes();
+    return (event_tz_names[event_times & EVT_ZMASK]);
+}
+
+/* return whether events are shown in 12 hour format
+ */
+bool getEvent12Hr()
+{
+    loadEventTimes();
+    return ((event_times & EVT_12HR) != 0);
+}
+
+/* set the time zone and hour format for showing events, save in NV.
+ * zone is one of event_tz_names[], or NULL for no change.
+ * hours is 12 or 24, or 0 for no change.
+ * return whether all ok.
+ */
+bool setEventTimes (const char *zone, int hours)
+{
+    loadEventTimes();
+
+    // find zone
+    int new_tz = event_times & EVT_ZMASK;
+    if (zone) {
+        for (new_tz = 0; new_tz < EVTZ_N; new_tz++)
+            if (strcasecmp (zone, event_tz_names[new_tz]) == 0)
+                break;
+        if (new_tz == EVTZ_N)
+            return (false);
+        #if !defined(_IS_UNIX)
+            if (new_tz == EVTZ_LOCAL)
+                return (false);
+        #endif
+    }
+
+    // check hours
+    bool new_12hr = (event_times & EVT_12HR) != 0;
+    if (hours == 12)
+        new_12hr = true;
+    else if (hours == 24)
+        new_12hr = false;
+    else if (hours != 0)
+        return (false);
+
+    // ok
+    event_times = new_tz | (new_12hr ? EVT_12HR : 0);
+    NVWriteUInt8 (NV_EVENTTIMES, event_times);
+    return (true);
+}
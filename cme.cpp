@@ -0,0 +1,169 @@
+/* show predicted earth arrivals of coronal mass ejections in a plot pane, soonest first,
+ * with a countdown to each so operators have warning of geomagnetic disturbances.
+ */
+
+#include "HamClock.h"
+
+
+// server file containing CMEs with a modeled earth arrival from the NASA DONKI WSA-Enlil analyses,
+// one per line, any order:
+//   launch_unix_time arrival_unix_time speed_km/s
+// eg: 1617209100 1617400000 1200
+static const char cme_page[] = "/ham/HamClock/NOAASpaceWX/cme.txt";
+
+// layout
+#define TITLE_COLOR     RA8875_GREEN
+#define HEADING_COLOR   GRAY
+#define TITLE_Y0        27              // title dy, match VOACAP title position
+#define HEADING_Y0      34              // column headings y down from box top
+#define LISTING_Y0      47              // first CME y down from box top
+#define LISTING_DY      22              // listing row separation, each CME uses 2 lines
+#define LISTING_N       ((PLOTBOX_H - LISTING_Y0)/LISTING_DY)       // max n list rows
+#define WHEN_X          6               // column x offsets from box left
+#define SPEED_X         100
+
+#define CME_PAST        (12*3600)       // keep showing CMEs whose predicted arrival is this recent, secs
+#define CME_SOON        (24*3600)       // arrivals sooner than this stand out, secs
+
+// one CME
+typedef struct {
+    time_t launch;                      // UTC of launch from sun
+    time_t arrival;                     // UTC of predicted earth arrival
+    int speed;                          // km/s
+} CME;
+
+
+/* qsort-style function to sort CMEs by increasing arrival time
+ */
+static int cmeQS (const void *p1, const void *p2)
+{
+    time_t t1 = ((CME*)p1)->arrival;
+    time_t t2 = ((CME*)p2)->arrival;
+    return (t1 < t2 ? -1 : (t1 > t2 ? 1 : 0));
+}
+
+/* draw the given list of CMEs, already sorted soonest first
+ */
+static void drawCMEs (const SBox &box, const CME *cmes, int n_cmes)
+{
+    prepPlotBox (box);
+
+    // title
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (TITLE_COLOR);
+    const char *title = xlate ("CME Arrivals");
+    tft.setCursor (box.x + (box.w - getTextWidth(title))/2, box.y + TITLE_Y0);
+    tft.print (title);
+
+    // column headings
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    tft.setTextColor (HEADING_COLOR);
+    tft.setCursor (box.x + WHEN_X, box.y + HEADING_Y0);
+    tft.print (F("Impact"));
+    tft.setCursor (box.x + SPEED_X, box.y + HEADING_Y0);
+    tft.print (F("km/s"));
+
+    if (n_cmes == 0) {
+        tft.setTextColor (RA8875_GREEN);
+        const char *none = "None expected";
+        tft.setCursor (box.x + (box.w - getTextWidth(none))/2, box.y + LISTING_Y0 + LISTING_DY);
+        tft.print (none);
+        return;
+    }
+
+    time_t t0 = now();
+    for (int i = 0; i < n_cmes && i < LISTING_N; i++) {
+        const CME &c = cmes[i];
+        uint16_t y = box.y + LISTING_Y0 + i*LISTING_DY;
+        long dt = c.arrival - t0;
+        char buf[30];
+
+        // countdown, or how long ago if passed
+        tft.setTextColor (dt < 0 ? GRAY : (dt < CME_SOON ? RA8875_RED : RA8875_YELLOW));
+        if (dt < 0)
+            snprintf (buf, sizeof(buf), "%ldh ago", -dt/3600);
+        else if (dt < 3600)
+            snprintf (buf, sizeof(buf), "in %ldm", dt/60);
+        else
+            snprintf (buf, sizeof(buf), "in %ldh", dt/3600);
+        tft.setCursor (box.x + WHEN_X, y);
+        tft.print (buf);
+
+        snprintf (buf, sizeof(buf), "%d", c.speed);
+        tft.setCursor (box.x + SPEED_X, y);
+        tft.print (buf);
+
+        // predicted arrival below
+        tft.setTextColor (RA8875_WHITE);
+        snprintf (buf, sizeof(buf), "%.3s %2d %02d:%02d UTC", monthShortStr(month(c.arrival)),
+                                        day(c.arrival), hour(c.arrival), minute(c.arrival));
+        tft.setCursor (box.x + WHEN_X + 6, y + LISTING_DY/2);
+        tft.print (buf);
+    }
+}
+
+/* retrieve and display the CMEs predicted to arrive at earth in the given box.
+ * return whether all ok.
+ */
+bool updateCME (const SBox &box)
+{
+    WiFiClient cme_client;
+    CME *cmes = NULL;
+    int n_cmes = 0;
+    char line[100];
+    bool ok = false;
+
+    Serial.println (cme_page);
+    resetWatchdog();
+    if (wifiOk() && cme_client.connect (svr_host, HTTPPORT)) {
+
+        resetWatchdog();
+        updateClocks(false);
+
+        // fetch page
+        httpGET (cme_client, svr_host, cme_page);
+        if (!httpSkipHeader (cme_client)) {
+            plotMessage (box, RA8875_RED, _FX("CME header short"));
+            goto out;
+        }
+
+        // collect each CME not yet long past
+        time_t t0 = now();
+        while (getTCPLine (cme_client, line, sizeof(line), NULL)) {
+            long lt, at;
+            int speed;
+            if (sscanf (line, "%ld %ld %d", &lt, &at, &speed) != 3)
+                continue;
+            if (t0 - at > CME_PAST)
+                continue;
+            cmes = (CME *) realloc (cmes, (n_cmes+1)*sizeof(CME));
+            if (!cmes)
+                fatalError (_FX("No memory for %d CMEs"), n_cmes+1);
+            CME &c = cmes[n_cmes++];
+            c.launch = lt;
+            c.arrival = at;
+            c.speed = speed;
+        }
+        Serial.printf (_FX("CME: found %d\n"), n_cmes);
+
+        // show soonest first
+        if (n_cmes > 1)
+            qsort (cmes, n_cmes, sizeof(CME), cmeQS);
+        drawCMEs (box, cmes, n_cmes);
+        ok = true;
+
+        // note soonest for getSpaceWeather()
+        if (n_cmes > 0)
+            noteCMEs (cmes[0].arrival, cmes[0].speed);
+        else
+            noteCMEs (0, 0);
+
+    } else
+        plotMessage (box, RA8875_RED, _FX("CME connection failed"));
+
+out:
+    cme_client.stop();
+    free (cmes);
+    printFreeHeap (F("updateCME"));
+    return (ok);
+}
@@ -0,0 +1,231 @@
+/* crash reports on UNIX systems. when HamClock dies from a fatal signal the handler writes a report to
+ * CRASH_DIR in our working directory with a backtrace, the tail of the diagnostic log and a summary of
+ * the settings and state saved shortly before. the next start shows where to find it.
+ *
+ * the handler may only use async-signal-safe calls so everything it writes is prepared ahead of time.
+ */
+
+#include "HamClock.h"
+
+#if defined(_IS_UNIX)
+
+#include <execinfo.h>
+
+
+#define CRASH_DIR       "crash"                 // dir within our_dir for reports
+#define CRASH_PENDING   "pending"               // file in CRASH_DIR naming a report not yet noticed
+#define CRASH_LOGTAIL   8192                    // bytes of diagnostic log to copy
+#define CRASH_MAXBT     50                      // max backtrace depth
+#define STATE_SECS      60                      // state summary refresh period, secs
+
+static char crash_dir[300];                     // full path to CRASH_DIR, empty until initCrashReport()
+static char log_path[300];                      // full path to the diagnostic log, empty if stdout
+static char crash_state[1500];                  // latest settings and state summary
+static size_t crash_state_len;                  // strlen(crash_state)
+static char alt_stack[64*1024];                 // so we can still report a stack overflow
+
+
+/* write n bytes of buf to fd
+ */
+static void writeBuf (int fd, const char *buf, size_t n)
+{
+    if (write (fd, buf, n) < 0)
+        return;         // nothing more we can do
+}
+
+/* write string s to fd
+ */
+static void writeStr (int fd, const char *s)
+{
+    writeBuf (fd, s, strlen(s));
+}
+
+/* write v to fd in decimal
+ */
+static void writeNum (int fd, unsigned long v)
+{
+    char buf[24];
+    int i = sizeof(buf);
+    do {
+        buf[--i] = '0' + v%10;
+        v /= 10;
+    } while (v > 0);
+    writeBuf (fd, buf+i, sizeof(buf)-i);
+}
+
+/* write the last CRASH_LOGTAIL bytes of the diagnostic log to fd
+ */
+static void writeLogTail (int fd)
+{
+    if (!log_path[0]) {
+        writeStr (fd, "(log is on stdout)\n");
+        return;
+    }
+    int log_fd = open (log_path, O_RDONLY);
+    if (log_fd < 0)
+        return;
+    off_t len = lseek (log_fd, 0, SEEK_END);
+    lseek (log_fd, len > CRASH_LOGTAIL ? len - CRASH_LOGTAIL : 0, SEEK_SET);
+    char buf[1024];
+    ssize_t n;
+    while ((n = read (log_fd, buf, sizeof(buf))) > 0)
+        writeBuf (fd, buf, n);
+    close (log_fd);
+}
+
+/* fatal signal handler: write the report, note it for next time, then die of the same signal.
+ */
+static void onCrash (int signo)
+{
+    // report name from time
+    char path[sizeof(crash_dir) + 50];
+    char *pp = stpcpy (stpcpy (path, crash_dir), "/crash-");
+    unsigned long t = time(NULL);
+    char digits[24];
+    int i = sizeof(digits);
+    do {
+        digits[--i] = '0' + t%10;
+        t /= 10;
+    } while (t > 0);
+    memcpy (pp, digits+i, sizeof(digits)-i);
+    strcpy (pp + sizeof(digits)-i, ".txt");
+
+    int fd = open (path, O_WRONLY|O_CREAT|O_TRUNC, 0664);
+    if (fd >= 0) {
+        writeStr (fd, "HamClock ");
+        writeStr (fd, hc_version);
+        writeStr (fd, " crashed with signal ");
+        writeNum (fd, signo);
+        writeStr (fd, "\n\nBacktrace:\n");
+        void *bt[CRASH_MAXBT];
+        int n_bt = backtrace (bt, CRASH_MAXBT);
+        backtrace_symbols_fd (bt, n_bt, fd);
+        writeStr (fd, "\nState:\n");
+        writeBuf (fd, crash_state, crash_state_len);
+        writeStr (fd, "\nRecent log:\n");
+        writeLogTail (fd);
+        close (fd);
+
+        // name it for the next start
+        char pending[sizeof(crash_dir) + 20];
+        stpcpy (stpcpy (stpcpy (pending, crash_dir), "/"), CRASH_PENDING);
+        int pfd = open (pending, O_WRONLY|O_CREAT|O_TRUNC, 0664);
+        if (pfd >= 0) {
+            writeStr (pfd, path);
+            close (pfd);
+        }
+
+        writeStr (1, "Crash report in ");
+        writeStr (1, path);
+        writeStr (1, "\n");
+    }
+
+    // same as onSig
+    setFullBrightness();
+
+    // handler was reset so this terminates with the original signal for any supervisor to see
+    raise (signo);
+}
+
+/* build a fresh crash_state from the current settings and state.
+ */
+static void saveState()
+{
+    int l = 0;
+    const int sz = sizeof(crash_state);
+    char grid[MAID_CHARLEN];
+
+    l += snprintf (crash_state+l, sz-l, "Make      %s\n", our_make);
+    l += snprintf (crash_state+l, sz-l, "Time      %ld\n", (long)now());
+    l += snprintf (crash_state+l, sz-l, "Uptime    %lu s\n", (unsigned long)(millis()/1000));
+    l += snprintf (crash_state+l, sz-l, "Call      %s\n", getCallsign());
+    getNVMaidenhead (NV_DE_GRID, grid);
+    l += snprintf (crash_state+l, sz-l, "DE        %s\n", grid);
+    getNVMaidenhead (NV_DX_GRID, grid);
+    l += snprintf (crash_state+l, sz-l, "DX        %s\n", grid);
+    for (int i = 0; i < PANE_N && l < sz; i++)
+        l += snprintf (crash_state+l, sz-l, "Pane%d     %s\n", i+1, plot_names[plot_ch[i]]);
+    l += snprintf (crash_state+l, sz-l, "Backend   %s\n", svr_host);
+    bool off_forced;
+    time_t off_since;
+    bool off = getOfflineMode (off_forced, off_since);
+    l += snprintf (crash_state+l, sz-l, "Offline   %s\n", off ? "yes" : "no");
+    l += snprintf (crash_state+l, sz-l, "Network   %s\n", getNetStateName (getNetState()));
+
+    crash_state_len = l < sz ? l : sz - 1;
+}
+
+/* prepare for crash reports and connect the fatal signal handlers.
+ * N.B. call after the diagnostic log is open.
+ */
+void initCrashReport()
+{
+    snprintf (crash_dir, sizeof(crash_dir), "%s%s", our_dir.c_str(), CRASH_DIR);
+    if (mkdir (crash_dir, 0775) < 0 && errno != EEXIST) {
+        Serial.printf (_FX("Crash: %s: %s\n"), crash_dir, strerror(errno));
+        crash_dir[0] = '\0';
+        return;
+    }
+
+    // log file if stdout is one
+    struct stat sb;
+    if (fstat (1, &sb) == 0 && S_ISREG(sb.st_mode))
+        snprintf (log_path, sizeof(log_path), "%sdiagnostic-log.txt", our_dir.c_str());
+
+    // first backtrace() loads libgcc, which is not safe to do in a handler
+    void *bt[1];
+    (void) backtrace (bt, 1);
+
+    // run on our own stack in case the crash is an overflow
+    stack_t ss;
+    memset (&ss, 0, sizeof(ss));
+    ss.ss_sp = alt_stack;
+    ss.ss_size = sizeof(alt_stack);
+    if (sigaltstack (&ss, NULL) < 0)
+        Serial.printf (_FX("Crash: sigaltstack: %s\n"), strerror(errno));
+
+    struct sigaction sa;
+    memset (&sa, 0, sizeof(sa));
+    sa.sa_handler = onCrash;
+    sa.sa_flags = SA_ONSTACK | SA_RESETHAND;
+    sigemptyset (&sa.sa_mask);
+    sigaction (SIGSEGV, &sa, NULL);
+    sigaction (SIGBUS, &sa, NULL);
+    sigaction (SIGILL, &sa, NULL);
+    sigaction (SIGFPE, &sa, NULL);
+    sigaction (SIGABRT, &sa, NULL);
+
+    saveState();
+}
+
+/* called often to keep the state summary fresh
+ */
+void checkCrashState()
+{
+    static uint32_t state_ms;
+    if (crash_dir[0] && timesUp (&state_ms, STATE_SECS*1000))
+        saveState();
+}
+
+/* if the previous session left a crash report, say where and forget it.
+ */
+void checkPrevCrash()
+{
+    char pending[sizeof(crash_dir) + 20];
+    snprintf (pending, sizeof(pending), "%s%s/%s", our_dir.c_str(), CRASH_DIR, CRASH_PENDING);
+    FILE *fp = fopen (pending, "r");
+    if (!fp)
+        return;
+
+    char path[400];
+    if (fgets (path, sizeof(path), fp)) {
+        const char *rel = strncmp (path, our_dir.c_str(), our_dir.size()) == 0 ? path + our_dir.size() : path;
+        tftMsg (true, 0, _FX("Previous session crashed, see"));
+        tftMsg (true, 3000, _FX("  %s"), rel);
+        Serial.printf (_FX("Crash: report in %s\n"), path);
+    }
+    fclose (fp);
+    unlink (pending);
+}
+
+#endif // _IS_UNIX
@@ -0,0 +1,251 @@
+/* serve a small progressive web app dashboard at /dashboard/ for phones and other browsers.
+ *
+ * the page has tabs for alerts, conditions, spots and satellite, all filled from the /ws stream (see
+ * websocket.cpp) so it needs no polling. A service worker caches the page shell so it still opens when
+ * HamClock is unreachable, and the manifest lets it be installed to a home screen. N.B. browsers only
+ * allow these over https or from localhost, eg, behind the TLS proxy suggested for the control API.
+ * Only on UNIX systems, like the WebSocket it depends on.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+// one embedded file
+typedef struct {
+    const char *name;                           // name after /dashboard/, "" for index
+    const char *type;                           // Content-Type
+    const char *body;                           // contents
+} DashAsset;
+
+
+static const char index_html[] = R"(<!DOCTYPE html>
+<html lang="en"><head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width,initial-scale=1">
+<meta name="theme-color" content="#000000">
+<link rel="manifest" href="manifest.json">
+<link rel="icon" href="icon.svg">
+<title>HamClock</title>
+<style>
+body{margin:0;background:#000;color:#ddd;font:15px/1.4 sans-serif}
+header{display:flex;align-items:center;justify-content:space-between;padding:8px 12px;background:#111}
+header b{color:#0c0}
+#status{font-size:12px;color:#c00}
+#status.ok{color:#0c0}
+nav{display:flex;background:#111;border-bottom:1px solid #333}
+nav button{flex:1;padding:10px 0;background:none;border:0;color:#aaa;font-size:14px}
+nav button.on{color:#fff;border-bottom:2px solid #0c0}
+section{display:none;padding:8px 12px}
+section.on{display:block}
+table{width:100%;border-collapse:collapse}
+td,th{padding:4px 6px;text-align:left;border-bottom:1px solid #222}
+th{color:#888;font-weight:normal}
+.watch{color:#f0f}
+.alert{color:#f44}
+.none{color:#666}
+a{color:inherit}
+</style>
+</head><body>
+<header><b id="call">HamClock</b><span id="status">connecting</span></header>
+<nav>
+<button data-tab="alerts" class="on">Alerts</button>
+<button data-tab="conditions">Conditions</button>
+<button data-tab="spots">Spots</button>
+<button data-tab="sat">Satellite</button>
+</nav>
+<section id="alerts" class="on"><table><tbody id="alert_rows"><tr><td class="none">No alerts</td></tr></tbody></table></section>
+<section id="conditions"><table><tbody id="spw_rows"><tr><td class="none">Waiting</td></tr></tbody></table></section>
+<section id="spots"><table><thead><tr><th>UT</th><th>Call</th><th>kHz</th><th>Mode</th><th>Path</th><th>Spotter</th></tr></thead>
+<tbody id="spot_rows"></tbody></table></section>
+<section id="sat"><table><tbody id="sat_rows"><tr><td class="none">No satellite chosen</td></tr></tbody></table></section>
+<script>
+var MAX_ROWS = 50;
+var alerts = [], spots = [];
+var spot_age = 0, spot_fade = 1;
+
+function esc(s) {
+    return String(s === null ? '-' : s).replace(/[&<>"]/g, function(c) {
+        return {'&':'&amp;','<':'&lt;','>':'&gt;','"':'&quot;'}[c];
+    });
+}
+function hhmm(t) {
+    return new Date(t*1000).toISOString().substr(11,5);
+}
+function mdhm(t) {
+    return t === null ? null : new Date(t*1000).toISOString().substr(5,11).replace('T',' ');
+}
+function rows(id, html) {
+    document.getElementById(id).innerHTML = html;
+}
+function table(pairs) {
+    return pairs.map(function(p) { return '<tr><th>' + p[0] + '</th><td>' + esc(p[1]) + '</td></tr>'; }).join('');
+}
+
+// spots fade with age then expire the same as on the DX Cluster pane
+function drawSpots() {
+    var now = Date.now()/1000;
+    if (spot_age > 0)
+        spots = spots.filter(function(s) { return now - s.t < 60*spot_age; });
+    rows('spot_rows', spots.map(function(s) {
+        var frac = spot_age > 0 ? Math.max(0, now - s.t)/(60*spot_age) : 0;
+        var call = s.sdr ? '<a href="' + esc(s.sdr) + '" target="_blank">' + esc(s.call) + '</a>' : esc(s.call);
+        return '<tr' + (s.watched ? ' class="watch"' : '') + ' style="opacity:' + (1 - (1 - spot_fade)*frac).toFixed(2)
+            + '"><td>' + esc(s.ut) + '</td><td>' + call
+            + '</td><td>' + esc(s.khz) + '</td><td>' + esc(s.mode) + '</td><td>' + esc(s.path) + '</td><td>' + esc(s.spotter)
+            + '</td></tr>';
+    }).join(''));
+}
+setInterval(drawSpots, 60000);
+
+var handlers = {
+    hello: function(m) {
+        document.getElementById('call').textContent = m.call + ' HamClock ' + m.version;
+        spot_age = m.spot_age;
+        spot_fade = m.spot_fade;
+        drawSpots();
+    },
+    spacewx: function(m) {
+        rows('spw_rows', table([['SSN',m.ssn],['SFI',m.sfi],['Kp',m.kp],['X-Ray',m.xray],
+                ['Solar wind',m.swind],['DRAP',m.drap],['Protons',m.protons],['NOAA scales',m.noaa],
+                ['Largest flare',m.flare],['CME arrival UT',mdhm(m.cme_arrival)],['CME km/s',m.cme_speed],
+                ['Updated',hhmm(m.t)]]));
+    },
+    spot: function(m) {
+        spots.unshift(m);
+        spots.length = Math.min(spots.length, MAX_ROWS);
+        drawSpots();
+    },
+    sat: function(m) {
+        rows('sat_rows', table([['Name',m.name],['Azimuth',m.az],['Elevation',m.el],['Range km',m.range],
+                ['Rate m/s',m.rate],['Updated',hhmm(m.t)]]));
+    },
+    alert: function(m) {
+        alerts.unshift(m);
+        alerts.length = Math.min(alerts.length, MAX_ROWS);
+        rows('alert_rows', alerts.map(function(a) {
+            return '<tr class="alert"><td>' + hhmm(a.t) + '</td><td>' + esc(a.kind) + '</td><td>' + esc(a.details) + '</td></tr>';
+        }).join(''));
+        if (navigator.vibrate)
+            navigator.vibrate(200);
+    },
+};
+
+var retry_ms = 1000;
+function connect() {
+    var st = document.getElementById('status');
+    var ws = new WebSocket((location.protocol === 'https:' ? 'wss://' : 'ws://') + location.host + '/ws');
+    ws.onopen = function() { st.textContent = 'live'; st.className = 'ok'; retry_ms = 1000; };
+    ws.onmessage = function(e) {
+        var m = JSON.parse(e.data);
+        if (handlers[m.type])
+            handlers[m.type](m);
+    };
+    ws.onclose = function() {
+        st.textContent = 'offline'; st.className = '';
+        setTimeout(connect, retry_ms);
+        retry_ms = Math.min(2*retry_ms, 60000);
+    };
+}
+
+document.querySelectorAll('nav button').forEach(function(b) {
+    b.onclick = function() {
+        document.querySelectorAll('nav button, section').forEach(function(e) { e.classList.remove('on'); });
+        b.classList.add('on');
+        document.getElementById(b.dataset.tab).classList.add('on');
+    };
+});
+
+if ('serviceWorker' in navigator)
+    navigator.serviceWorker.register('sw.js');
+connect();
+</script>
+</body></html>
+)";
+
+
+static const char manifest_json[] = R"({
+  "name": "HamClock",
+  "short_name": "HamClock",
+  "start_url": "./",
+  "scope": "./",
+  "display": "standalone",
+  "background_color": "#000000",
+  "theme_color": "#000000",
+  "icons": [ { "src": "icon.svg", "sizes": "any", "type": "image/svg+xml" } ]
+}
+)";
+
+
+// cache the shell, use network first so a running HamClock always serves the latest
+static const char sw_js[] = R"(var CACHE = 'hamclock-shell-v5';
+var SHELL = ['./', 'manifest.json', 'icon.svg'];
+
+self.addEventListener('install', function(e) {
+    e.waitUntil(caches.open(CACHE).then(function(c) { return c.addAll(SHELL); }));
+});
+
+self.addEventListener('activate', function(e) {
+    e.waitUntil(caches.keys().then(function(keys) {
+        return Promise.all(keys.filter(function(k) { return k !== CACHE; })
+                               .map(function(k) { return caches.delete(k); }));
+    }));
+});
+
+self.addEventListener('fetch', function(e) {
+    e.respondWith(fetch(e.request).then(function(r) {
+        var copy = r.clone();
+        caches.open(CACHE).then(function(c) { c.put(e.request, copy); });
+        return r;
+    }).catch(function() {
+        return caches.match(e.request);
+    }));
+});
+)";
+
+
+static const char icon_svg[] = R"(<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64">
+<rect width="64" height="64" rx="12" fill="#000"/>
+<circle cx="32" cy="32" r="22" fill="none" stroke="#0c0" stroke-width="4"/>
+<path d="M32 16v16l10 8" fill="none" stroke="#fff" stroke-width="4" stroke-linecap="round"/>
+</svg>
+)";
+
+
+static const DashAsset assets[] = {
+    { "",               "text/html; charset=utf-8",         index_html },
+    { "index.html",     "text/html; charset=utf-8",         index_html },
+    { "manifest.json",  "application/manifest+json",        manifest_json },
+    { "sw.js",          "text/javascript",                  sw_js },
+    { "icon.svg",       "image/svg+xml",                    icon_svg },
+};
+
+
+/* send the named dashboard file to the given client.
+ * return false with reason in name if not found.
+ */
+bool getWiFiDashboard (WiFiClient *clientp, char name[])
+{
+    for (unsigned i = 0; i < NARRAY(assets); i++) {
+        const DashAsset &a = assets[i];
+        if (strcmp (name, a.name) == 0) {
+            if (clientp) {
+                resetWatchdog();
+                FWIFIPRLN (*clientp, F("HTTP/1.0 200 OK"));
+                sendUserAgent (*clientp);
+                clientp->print (F("Content-Type: "));
+                clientp->println (a.type);
+                FWIFIPRLN (*clientp, F("Cache-Control: no-cache"));
+                FWIFIPRLN (*clientp, F("Connection: close\r\n"));
+                clientp->write ((const uint8_t *)a.body, strlen(a.body));
+            }
+            return (true);
+        }
+    }
+
+    strcpy (name, _FX("No such dashboard file"));
+    return (false);
+}
+
+#endif // _IS_UNIX
@@ -0,0 +1,192 @@
+/* keep the last good copy of each data set fetched from the backend in our_dir/data so HamClock can
+ * carry on from them when the network or backend is unavailable. UNIX only.
+ *
+ * each data set is stored as data/NAME. data/MANIFEST has one line per data set:
+ *   NAME fetched_unix_time size crc32
+ * which serves as its version and lets us detect a damaged copy before using it. Callers save a fresh
+ * copy whenever they fetch one on their own schedule and load the saved copy when fetching fails.
+ * Map images are already kept by mapmanage.cpp and band plans are built in so they need no bundle.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#include <errno.h>
+#include <sys/stat.h>
+
+#define BUNDLE_DIR      "data"                  // dir within our_dir
+#define MANIFEST_FN     "MANIFEST"              // manifest file in BUNDLE_DIR
+#define MAX_BUNDLE      (8*1024*1024)           // largest data set we will load, bytes
+#define MAX_NAMELEN     40                      // longest name
+
+
+/* standard CRC-32 of the given bytes
+ */
+static uint32_t crc32 (const char *buf, size_t len)
+{
+    uint32_t crc = 0xFFFFFFFF;
+    for (size_t i = 0; i < len; i++) {
+        crc ^= (uint8_t)buf[i];
+        for (int b = 0; b < 8; b++)
+            crc = (crc >> 1) ^ (0xEDB88320 & -(crc & 1));
+    }
+    return (~crc);
+}
+
+/* return the path to the given file within BUNDLE_DIR, creating BUNDLE_DIR if necessary
+ */
+static std::string bundlePath (const char *fn)
+{
+    std::string dir = our_dir + BUNDLE_DIR;
+    (void) mkdir (dir.c_str(), 0755);
+    return (dir + "/" + fn);
+}
+
+/* copy name to safe as a file name, ie, with any character other than letters, digits, - or . as _
+ */
+static void safeBundleName (const char *name, char safe[MAX_NAMELEN+1])
+{
+    int i;
+    for (i = 0; i < MAX_NAMELEN && name[i]; i++)
+        safe[i] = isalnum(name[i]) || name[i] == '-' || name[i] == '.' ? name[i] : '_';
+    safe[i] = '\0';
+}
+
+/* find the manifest entry for the given safe name.
+ * return whether found.
+ */
+static bool findManifest (const char *safe, time_t &fetched, size_t &size, uint32_t &crc)
+{
+    FILE *fp = fopen (bundlePath(MANIFEST_FN).c_str(), "r");
+    if (!fp)
+        return (false);
+
+    char line[100], mname[MAX_NAMELEN+1];
+    long f;
+    unsigned long s, c;
+    bool found = false;
+    while (!found && fgets (line, sizeof(line), fp)) {
+        if (sscanf (line, "%40s %ld %lu %lx", mname, &f, &s, &c) == 4 && strcmp (mname, safe) == 0) {
+            fetched = f;
+            size = s;
+            crc = c;
+            found = true;
+        }
+    }
+
+    fclose (fp);
+    return (found);
+}
+
+/* replace or add the manifest entry for the given safe name, via a temp file so it is never partial
+ */
+static void updateManifest (const char *safe, time_t fetched, size_t size, uint32_t crc)
+{
+    std::string path = bundlePath (MANIFEST_FN);
+    std::string tmp_path = path + ".tmp";
+    FILE *out_fp = fopen (tmp_path.c_str(), "w");
+    if (!out_fp) {
+        Serial.printf (_FX("Bundle: %s: %s\n"), tmp_path.c_str(), strerror(errno));
+        return;
+    }
+
+    // copy all others
+    FILE *in_fp = fopen (path.c_str(), "r");
+    if (in_fp) {
+        char line[100], mname[MAX_NAMELEN+1];
+        while (fgets (line, sizeof(line), in_fp))
+            if (sscanf (line, "%40s", mname) == 1 && strcmp (mname, safe) != 0)
+                fputs (line, out_fp);
+        fclose (in_fp);
+    }
+
+    fprintf (out_fp, "%s %ld %lu %08lx\n", safe, (long)fetched, (unsigned long)size, (unsigned long)crc);
+
+    if (fclose (out_fp) != 0 || rename (tmp_path.c_str(), path.c_str()) < 0)
+        Serial.printf (_FX("Bundle: %s: %s\n"), path.c_str(), strerror(errno));
+}
+
+/* save a freshly fetched copy of the given data set.
+ */
+void saveDataBundle (const char *name, const char *data, size_t len)
+{
+    char safe[MAX_NAMELEN+1];
+    safeBundleName (name, safe);
+    std::string path = bundlePath (safe);
+    std::string tmp_path = path + ".tmp";
+
+    FILE *fp = fopen (tmp_path.c_str(), "w");
+    if (!fp) {
+        Serial.printf (_FX("Bundle: %s: %s\n"), tmp_path.c_str(), strerror(errno));
+        return;
+    }
+    bool ok = fwrite (data, 1, len, fp) == len;
+    ok = fclose (fp) == 0 && ok;
+    if (!ok || rename (tmp_path.c_str(), path.c_str()) < 0) {
+        Serial.printf (_FX("Bundle: %s: %s\n"), path.c_str(), strerror(errno));
+        (void) unlink (tmp_path.c_str());
+        return;
+    }
+
+    updateManifest (safe, time(NULL), len, crc32 (data, len));
+}
+
+/* return a malloced copy of the last saved good copy of the given data set, with EOS added, and pass
+ * back when it was fetched. return NULL if none or it is damaged.
+ * N.B. caller must free the result.
+ */
+char *loadDataBundle (const char *name, time_t &fetched)
+{
+    char safe[MAX_NAMELEN+1];
+    safeBundleName (name, safe);
+
+    size_t size;
+    uint32_t crc;
+    if (!findManifest (safe, fetched, size, crc) || size > MAX_BUNDLE)
+        return (NULL);
+
+    std::string path = bundlePath (safe);
+    FILE *fp = fopen (path.c_str(), "r");
+    if (!fp) {
+        Serial.printf (_FX("Bundle: %s: %s\n"), path.c_str(), strerror(errno));
+        return (NULL);
+    }
+    char *data = (char *) malloc (size + 1);
+    if (!data)
+        fatalError (_FX("No memory for %s bundle"), safe);
+    size_t n = fread (data, 1, size + 1, fp);
+    fclose (fp);
+
+    // must be exactly as saved
+    if (n != size || crc32 (data, size) != crc) {
+        Serial.printf (_FX("Bundle: %s is damaged, ignoring\n"), safe);
+        free (data);
+        return (NULL);
+    }
+    data[size] = '\0';
+
+    Serial.printf (_FX("Bundle: using %s saved %ld hours ago\n"), safe, (long)(time(NULL) - fetched)/3600);
+    return (data);
+}
+
+#else // !_IS_UNIX
+
+/* no room to keep copies on ESP
+ */
+void saveDataBundle (const char *name, const char *data, size_t len)
+{
+    (void) name;
+    (void) data;
+    (void) len;
+}
+
+char *loadDataBundle (const char *name, time_t &fetched)
+{
+    (void) name;
+    fetched = 0;
+    return (NULL);
+}
+
+#endif // _IS_UNIX
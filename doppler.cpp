@@ -0,0 +1,351 @@
+/* act as the doppler source for an SDR receive chain while a satellite, or the moon, is being tracked.
+ *
+ * the user sets the nominal downlink frequency, then:
+ *
+ *   serve: we listen on a port speaking enough of the hamlib rigctld protocol for rigctl clients such
+ *     as GPredict or an SDR program's rigctl client to poll the corrected frequency:
+ *       f          reply the doppler corrected frequency in Hz
+ *       F Hz       set the nominal downlink frequency, reply RPRT 0
+ *       t          reply 0, we never transmit
+ *       q          close
+ *     anything else replies RPRT -11, not available. The long forms such as \get_freq also work.
+ *
+ *   push: we connect to the rigctld-style server of an SDR program, such as gqrx or SDR++, and send it
+ *     F with the corrected frequency each second it changes.
+ *
+ * the correction uses the current range rate from getSatAzElNow(). For the moon it is doubled because
+ * the signal is our own echo. With no satellite the nominal frequency is used unchanged.
+ * Only on UNIX systems because ESP has too little memory to hold clients open.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#define MAX_DOPCLI      4                       // max server clients at once
+#define MAX_DOPLINE     50                      // longest client command line
+#define PUSH_MS         1000                    // push interval, ms
+#define PUSH_RETRY_MS   60000                   // push connection retry interval, ms
+#define PUSH_TO_SECS    1                       // push connect and read timeouts, secs
+#define LIGHT_MPS       299792458.0             // speed of light, m/s
+
+static uint32_t dop_hz;                         // nominal downlink frequency, Hz, 0 if not set
+static uint16_t dop_port;                       // server port, 0 if off
+static char push_spec[NV_DOPPLERPUSH_LEN];      // host:port to push to, empty if none
+static char push_host[NV_DOPPLERPUSH_LEN];      // host to push to, empty if none
+static uint16_t push_port;                      // port to push to
+
+static WiFiServer *dop_server;                  // listening server, NULL if none
+static WiFiClient dop_clients[MAX_DOPCLI];      // server clients, unused if !connected()
+static char dop_lines[MAX_DOPCLI][MAX_DOPLINE]; // partial command line from each client
+static WiFiClient push_client;                  // connection to push host
+static uint32_t push_ms;                        // millis() of last push or connection attempt
+static uint32_t push_last_hz;                   // last frequency pushed
+
+
+/* crack "host:port" into push_spec, push_host and push_port.
+ * return whether sensible, which includes empty for none.
+ */
+static bool crackPushSpec (const char *spec)
+{
+    char host[NV_DOPPLERPUSH_LEN];
+    int port = 0;
+    if (spec[0] == '\0')
+        host[0] = '\0';
+    else if (sscanf (spec, _FX("%31[^:]:%d"), host, &port) != 2 || port < 1 || port > 65535)
+        return (false);
+
+    strcpy (push_spec, spec);
+    strcpy (push_host, host);
+    push_port = port;
+    return (true);
+}
+
+/* (re)start the server on dop_port, if any
+ */
+static void startDopplerServer()
+{
+    if (dop_server) {
+        for (int i = 0; i < MAX_DOPCLI; i++)
+            dop_clients[i].stop();
+        dop_server->stop();
+        delete dop_server;
+        dop_server = NULL;
+    }
+
+    if (dop_port) {
+        Serial.printf (_FX("Doppler: serving on port %d\n"), dop_port);
+        dop_server = new WiFiServer (dop_port);
+        dop_server->begin();
+    }
+}
+
+/* read the settings from NV once and start the server if enabled
+ */
+static void loadDoppler()
+{
+    static bool loaded;
+    if (loaded)
+        return;
+    loaded = true;
+
+    if (!NVReadUInt32 (NV_DOPPLERHZ, &dop_hz)) {
+        dop_hz = 0;
+        NVWriteUInt32 (NV_DOPPLERHZ, dop_hz);
+    }
+    if (!NVReadUInt16 (NV_DOPPLERPORT, &dop_port)) {
+        dop_port = 0;
+        NVWriteUInt16 (NV_DOPPLERPORT, dop_port);
+    }
+    char spec[NV_DOPPLERPUSH_LEN];
+    if (!NVReadString (NV_DOPPLERPUSH, spec) || !crackPushSpec (spec)) {
+        spec[0] = '\0';
+        (void) crackPushSpec (spec);
+        NVWriteString (NV_DOPPLERPUSH, spec);
+    }
+
+    startDopplerServer();
+}
+
+/* set the nominal downlink frequency in Hz, 0 to forget
+ */
+static void setDopplerHz (uint32_t hz)
+{
+    dop_hz = hz;
+    NVWriteUInt32 (NV_DOPPLERHZ, dop_hz);
+    push_last_hz = 0;
+}
+
+/* set the nominal downlink frequency, server port and push host:port; each may be 0 or empty for none.
+ * return whether all are sensible, setting none if not.
+ */
+bool setDoppler (uint32_t hz, uint16_t port, const char *push)
+{
+    loadDoppler();
+
+    if (strlen (push) >= NV_DOPPLERPUSH_LEN || !crackPushSpec (push))
+        return (false);
+    NVWriteString (NV_DOPPLERPUSH, push_spec);
+    push_client.stop();
+    push_ms = 0;
+
+    setDopplerHz (hz);
+
+    if (port != dop_port) {
+        dop_port = port;
+        NVWriteUInt16 (NV_DOPPLERPORT, dop_port);
+        startDopplerServer();
+    }
+
+    return (true);
+}
+
+/* pass back the nominal downlink frequency, server port and push spec as host:port or empty,
+ * push must hold NV_DOPPLERPUSH_LEN.
+ * return whether any are active.
+ */
+bool getDoppler (uint32_t &hz, uint16_t &port, char push[])
+{
+    loadDoppler();
+    hz = dop_hz;
+    port = dop_port;
+    strcpy (push, push_spec);
+    return (dop_hz && (dop_port || push_host[0]));
+}
+
+/* return the frequency heard on the ground for a downlink, or to transmit for an uplink, with the given
+ * nominal frequency, Hz, at the given range rate of the current sat, m/s.
+ */
+uint32_t dopplerShiftHz (uint32_t hz, float rate, bool uplink)
+{
+    // rate is positive when receding; an echo off the moon travels both ways
+    if (isSatMoon())
+        rate *= 2;
+    return ((uint32_t) (hz * (uplink ? 1.0 + rate/LIGHT_MPS : 1.0 - rate/LIGHT_MPS) + 0.5));
+}
+
+/* pass back the doppler corrected downlink frequency now, Hz.
+ * return whether a satellite is tracked, else hz is just the nominal frequency.
+ */
+bool getDopplerHz (uint32_t &hz)
+{
+    loadDoppler();
+    hz = dop_hz;
+
+    float az, el, range, rate, raz, saz;
+    if (dop_hz == 0 || !getSatAzElNow (NULL, &az, &el, &range, &rate, &raz, &saz, NULL, NULL))
+        return (false);
+
+    hz = dopplerShiftHz (dop_hz, rate, false);
+    return (true);
+}
+
+/* process one complete command line from server client i
+ */
+static void runDopplerCommand (int i, char *line)
+{
+    WiFiClient &c = dop_clients[i];
+    char reply[50];
+    unsigned long hz;
+
+    if (strcmp (line, "f") == 0 || strcmp (line, "\\get_freq") == 0) {
+        uint32_t now_hz;
+        (void) getDopplerHz (now_hz);
+        snprintf (reply, sizeof(reply), "%u\n", now_hz);
+    } else if (sscanf (line, "F %lu", &hz) == 1 || sscanf (line, "\\set_freq %lu", &hz) == 1) {
+        setDopplerHz (hz);
+        strcpy (reply, "RPRT 0\n");
+    } else if (strcmp (line, "t") == 0 || strcmp (line, "\\get_ptt") == 0) {
+        strcpy (reply, "0\n");
+    } else if (strcmp (line, "q") == 0 || strcmp (line, "\\quit") == 0) {
+        Serial.printf (_FX("Doppler: client %d quit\n"), i);
+        c.stop();
+        return;
+    } else
+        strcpy (reply, "RPRT -11\n");
+
+    c.write ((const uint8_t *)reply, strlen(reply));
+}
+
+/* accept new server clients and run commands from each
+ */
+static void serveDoppler()
+{
+    if (!dop_server)
+        return;
+
+    // accept a new client if room
+    WiFiClient new_client = dop_server->available();
+    if (new_client) {
+        int slot = -1;
+        for (int i = 0; i < MAX_DOPCLI && slot < 0; i++)
+            if (!dop_clients[i].connected())
+                slot = i;
+        if (slot < 0) {
+            Serial.printf (_FX("Doppler: too many clients\n"));
+            new_client.stop();
+        } else {
+            Serial.printf (_FX("Doppler: client %d connected from %s\n"), slot, new_client.remoteIP().c_str());
+            dop_clients[slot] = new_client;
+            dop_lines[slot][0] = '\0';
+        }
+    }
+
+    // collect lines from each client, read() closes on EOF
+    for (int i = 0; i < MAX_DOPCLI; i++) {
+        WiFiClient &c = dop_clients[i];
+        char *line = dop_lines[i];
+        while (c.connected() && c.available()) {
+            int ch = c.read();
+            if (ch < 0)
+                break;
+            size_t ll = strlen (line);
+            if (ch == '\n') {
+                runDopplerCommand (i, line);
+                line[0] = '\0';
+            } else if (ch != '\r' && ll < MAX_DOPLINE-1) {
+                line[ll] = ch;
+                line[ll+1] = '\0';
+            }
+        }
+    }
+}
+
+/* send hz to the open push_client.
+ * return false if the connection failed.
+ */
+static bool sendPushHz (uint32_t hz)
+{
+    // set_freq replies RPRT 0 if ok
+    char line[30];
+    snprintf (line, sizeof(line), "F %u\n", hz);
+    push_client.print (line);
+    if (!getTCPLine (push_client, line, sizeof(line), NULL) || strcmp (line, "RPRT 0") != 0) {
+        Serial.printf (_FX("Doppler: %s:%d set_freq failed\n"), push_host, push_port);
+        return (false);
+    }
+    return (true);
+}
+
+/* send the corrected frequency to the push host if it has changed, over one persistent connection.
+ * connecting and the reply use short timeouts so a missing push host does not stall the main loop.
+ */
+static void pushDoppler()
+{
+    uint32_t t0 = millis();
+    if (push_host[0] == '\0' || dop_hz == 0 || (push_ms && t0 - push_ms < PUSH_MS))
+        return;
+    if (!push_client && push_ms && t0 - push_ms < PUSH_RETRY_MS)
+        return;
+
+    uint32_t hz;
+    (void) getDopplerHz (hz);
+    if (push_client && hz == push_last_hz) {
+        push_ms = t0;
+        return;
+    }
+    push_ms = t0;
+
+    int connect_to = getNetConnectTimeout()/1000;
+    int read_to = getNetReadTimeout()/1000;
+    (void) setNetTimeouts (PUSH_TO_SECS, PUSH_TO_SECS);
+
+    bool ok = true;
+    if (!push_client) {
+        Serial.printf (_FX("Doppler: connecting to %s:%d\n"), push_host, push_port);
+        ok = wifiOk() && push_client.connect (push_host, push_port);
+        if (!ok)
+            Serial.printf (_FX("Doppler: %s:%d connection failed\n"), push_host, push_port);
+    }
+    if (ok)
+        ok = sendPushHz (hz);
+
+    (void) setNetTimeouts (connect_to, read_to);
+
+    if (ok)
+        push_last_hz = hz;
+    else {
+        push_client.stop();
+        push_last_hz = 0;
+    }
+}
+
+/* called often from main loop to serve and push the doppler corrected frequency
+ */
+void checkDoppler()
+{
+    loadDoppler();
+    serveDoppler();
+    pushDoppler();
+}
+
+#else // !_IS_UNIX
+
+bool setDoppler (uint32_t hz, uint16_t port, const char *push)
+{
+    (void) hz;
+    (void) port;
+    (void) push;
+    return (false);
+}
+
+bool getDoppler (uint32_t &hz, uint16_t &port, char push[])
+{
+    hz = 0;
+    port = 0;
+    push[0] = '\0';
+    return (false);
+}
+
+bool getDopplerHz (uint32_t &hz)
+{
+    hz = 0;
+    return (false);
+}
+
+void checkDoppler()
+{
+}
+
+#endif // _IS_UNIX
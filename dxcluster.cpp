@@ -7,6 +7,7 @@
  * WSJT-X:
  *   [ ] packet definition: https://github.com/roelandjansen/wsjt-x/blob/master/NetworkMessage.hpp
  *   [ ] We don't actually enforce the Status ID to be WSJT-X so this may also work for, say, JTCluster.
+ *   [ ] QSO Logged messages are passed to qsorate.cpp.
  */
 
 #include "HamClock.h"
@@ -113,25 +114,107 @@
 // setup 
 #define TITLE_COLOR     RA8875_GREEN
 #define LISTING_COLOR   RA8875_WHITE
+#define HEADING_COLOR   GRAY
+#define SELECT_COLOR    RA8875_YELLOW
+#define WATCH_COLOR     RA8875_MAGENTA
+#define TUNED_COLOR     RA8875_CYAN
+#define LOTW_COLOR      RA8875_GREEN            // underline of calls that upload to LoTW
+#define EQSL_COLOR      RA8875_YELLOW           // underline of eQSL AG calls not on LoTW
+#define NEEDED_COLOR    RGB565(255,130,130)     // spots that would fill an award slot
+#define TUNED_KHZ       2.0F            // spots within this of the rig dial frequency are tuned, kHz
+#define GRAYLINE_COLOR  RGB565(255,140,0)       // spots favoring the grayline
+#define PATH_NSAMPLES   24              // points along each path checked for sun
+#define PATH_TWIDAY     0.05F           // cos sun zenith above which is full day, about 3 degs up
+#define PATH_TWINIGHT   (-0.208F)       // cos sun zenith below which is full night, 12 degs down
+#define PATH_LPADV      0.25F           // long path must be this much better a fraction than short
+#define PATH_LOWKHZ     10500           // bands below this favor darkness, above favor daylight
+#define PATH_GLKHZ      15000           // bands below this may favor the grayline
 #define CLUSTER_TIMEOUT 30000           // send line feed if idle this long, millis
 #define MAX_AGE         300000          // max age to restore spot in list, millis
 #define TITLE_Y0        27              // title dy, match VOACAP title position
 #define HOSTNM_Y0       32              // host name y down from box top
-#define LISTING_Y0      47              // first spot y down from box top
-#define LISTING_DY      16              // listing row separation
+#define HEADING_Y0      43              // column headings y down from box top
+#define LISTING_Y0      54              // first spot y down from box top
+#define LISTING_DY      10              // listing row separation
+#define FOOTER_DY       11              // paging and detail line y up from box bottom
 #define FONT_H          7               // listing font height
 #define FONT_W          6               // listing font width
 #define DWELL_MS        5000            // period to show non-fatal message, ms
-#define LISTING_N       ((PLOTBOX_H - LISTING_Y0)/LISTING_DY)       // max n list rows
+#define LISTING_N       ((PLOTBOX_H - LISTING_Y0 - FOOTER_DY)/LISTING_DY)   // max n list rows
+#define MAX_SPOTS       30              // max n spots retained
+#define FREQ_COL        5               // listing column offsets, chars
+#define CALL_COL        13
+#define MODE_COL        22
+#define MODE_W          3               // listing mode column width, chars
+#define WATCH_ALERT_MS  60000           // draw attention to a newly spotted watched call this long, millis
+#define WATCH_ACK_SECS  3600            // don't alert again for an acknowledged watched call this long, secs
+#define MAX_DXHOSTS     6               // max clusters including the one set in Setup
+#define PRIMARY_RETRY   (30*60000UL)    // check the Setup cluster this often while using another, millis
+#define DEF_SPOTAGE     30              // default minutes for spots to fade and expire
+#define MAX_SPOTAGE     1440            // max spot age that may be set, minutes
+#define AGE_SECS        60              // refresh spot fading this often, secs
+#define DEF_SPOTMERGE   5               // default minutes within which repeat spots are merged
+#define MERGE_KHZ       1.0F            // max frequency difference of spots to merge, kHz
+
+// one cluster we may connect to
+typedef struct {
+        char host[64];                  // name or IPv4 or IPv6 address
+        int port;
+        char login[NV_CALLSIGN_LEN];    // callsign to send when asked to log in
+} DXClusterHost;
+
+// clusters to try in order, the first is the one set in Setup, others are from dxhosts_fn on UNIX
+static DXClusterHost dx_hosts[MAX_DXHOSTS];
+static int n_dxhosts;                   // n in dx_hosts[]
+static int dx_host_i;                   // dx_hosts[] index in use or to try next
+static uint32_t primary_ms;             // millis() when last checked dx_hosts[0] while using another
+#if defined(_IS_UNIX)
+static const char dxhosts_fn[] = "dxhosts.txt"; // in our_dir, "host port [login]" per line
+#endif
 
 // connection info
 static WiFiClient dx_client;            // persistent TCP connection while displayed ...
 static WiFiUDP wsjtx_server;            // or persistent UDP "connection" to WSJT-X client program
 static uint32_t last_action;            // time of most recent spot or user activity, millis()
 
-// spots
-static DXClusterSpot spots[LISTING_N];
-static uint8_t n_spots;                 // n spots already displayed
+// spots, oldest first
+static DXClusterSpot spots[MAX_SPOTS];
+static uint8_t n_spots;                 // n spots in use
+
+// spot table order
+typedef enum {
+    DXS_TIME,                           // newest first
+    DXS_FREQ,                           // increasing frequency
+    DXS_BAND,                           // increasing band, newest first within each
+    DXS_N
+} DXSpotSort;
+static const char *dxs_names[DXS_N] = {"Time", "Freq", "Band"};
+static uint8_t spot_sort = DXS_N;       // DXSpotSort, DXS_N until loaded from NV
+static uint8_t sorted[MAX_SPOTS];       // spots[] indices in table order
+static uint8_t top_row;                 // sorted[] index of first row shown
+static int8_t sel_spot = -1;            // spots[] index of last spot tapped, -1 if none
+static char qr_link[NV_WEBSDR_LEN+30];  // SDR link of sel_spot shown as QR code in place of listing, if any
+
+// watched calls
+static char watch_calls[NV_WATCHCALLS_LEN]; // space separated, upper case
+static bool watch_calls_init;           // set once watch_calls is loaded from NV
+static uint32_t watch_alert_ms;         // millis() when a watched call was spotted, 0 if not alerting
+static char watch_alert_call[MAX_SPOTCALL_LEN]; // watched call that started the alert
+static char watch_ack_call[NV_WATCHACKCALL_LEN]; // watched call whose alert was last acknowledged
+static uint32_t watch_ack_t;            // now() when watch_ack_call was acknowledged, survives restarts
+static char alert_filter[NV_DXALERTFILT_LEN]; // watch alert filter, eg cont=OC,AF&band=40m, empty for none
+static bool alert_filter_init;          // set once alert_filter is loaded from NV
+
+// spot aging
+static uint16_t spot_age;               // minutes for spots to fade and expire, 0 never
+static bool spot_age_init;              // set once spot_age is loaded from NV
+
+// spot merging
+static uint8_t spot_merge;              // minutes within which repeat spots are merged, 0 never
+static bool spot_merge_init;            // set once spot_merge is loaded from NV
+
+// rig dial frequency
+static float tuned_khz;                 // rig frequency used for spots[].tuned, kHz, 0 if unknown
 
 // type
 typedef enum {
@@ -278,6 +361,221 @@ static bool getDXClusterSpotLL (const char *call, LatLong &ll)
         return (true);
 }
 
+/* return the unit vector of the given location
+ */
+static void llUnitVector (float lat, float lng, float v[3])
+{
+        v[0] = cosf(lat)*cosf(lng);
+        v[1] = cosf(lat)*sinf(lng);
+        v[2] = sinf(lat);
+}
+
+/* return the fraction of PATH_NSAMPLES points along the great circle from a in direction u through angle
+ * arc that are dark if want_dark else in daylight, given the subsolar unit vector sun.
+ */
+static float pathSunFraction (const float a[3], const float u[3], float arc, const float sun[3], bool want_dark)
+{
+        int n_ok = 0;
+        for (int i = 0; i < PATH_NSAMPLES; i++) {
+            float phi = arc * (i + 0.5F) / PATH_NSAMPLES;
+            float cp = cosf(phi), sp = sinf(phi);
+            float cos_z = 0;
+            for (int j = 0; j < 3; j++)
+                cos_z += (a[j]*cp + u[j]*sp) * sun[j];
+            if (want_dark ? cos_z < PATH_TWINIGHT : cos_z > PATH_TWIDAY)
+                n_ok++;
+        }
+        return ((float)n_ok / PATH_NSAMPLES);
+}
+
+/* return which path to the given spot is favored now.
+ * both ends in twilight favors the grayline on the lower bands. Otherwise the lower bands favor the path
+ * more in darkness and the higher bands the path more in daylight, the long path only if clearly better.
+ */
+SpotPath getSpotPath (const DXClusterSpot &spot)
+{
+        float de[3], dx[3], sun[3];
+        llUnitVector (de_ll.lat, de_ll.lng, de);
+        llUnitVector (spot.ll.lat, spot.ll.lng, dx);
+        llUnitVector (sun_ss_ll.lat, sun_ss_ll.lng, sun);
+
+        float de_cz = de[0]*sun[0] + de[1]*sun[1] + de[2]*sun[2];
+        float dx_cz = dx[0]*sun[0] + dx[1]*sun[1] + dx[2]*sun[2];
+        if (spot.freq < PATH_GLKHZ && de_cz > PATH_TWINIGHT && de_cz < PATH_TWIDAY
+                                   && dx_cz > PATH_TWINIGHT && dx_cz < PATH_TWIDAY)
+            return (SPATH_GRAY);
+
+        // unit vector perpendicular to de in the plane toward dx, none if same or antipodal
+        float cos_arc = de[0]*dx[0] + de[1]*dx[1] + de[2]*dx[2];
+        float arc = acosf (fmaxf (-1.0F, fminf (1.0F, cos_arc)));
+        float sin_arc = sinf (arc);
+        if (sin_arc < 1e-3F)
+            return (SPATH_SHORT);
+        float u[3];
+        for (int j = 0; j < 3; j++)
+            u[j] = (dx[j] - de[j]*cos_arc) / sin_arc;
+
+        // long path leaves de in the opposite direction and goes the rest of the way around
+        bool want_dark = spot.freq < PATH_LOWKHZ;
+        float sp = pathSunFraction (de, u, arc, sun, want_dark);
+        float neg_u[3] = {-u[0], -u[1], -u[2]};
+        float lp = pathSunFraction (de, neg_u, 2*M_PIF - arc, sun, want_dark);
+        return (lp > sp + PATH_LPADV ? SPATH_LONG : SPATH_SHORT);
+}
+
+/* return the short name of the given path
+ */
+const char *getSpotPathName (SpotPath p)
+{
+        switch (p) {
+        case SPATH_LONG: return ("LP");
+        case SPATH_GRAY: return ("GL");
+        default:         return ("SP");
+        }
+}
+
+/* return the color that marks spots favoring the given path, black for the short path
+ */
+static uint16_t spotPathColor (SpotPath p)
+{
+        switch (p) {
+        case SPATH_LONG: return (getLongPathColor());
+        case SPATH_GRAY: return (GRAYLINE_COLOR);
+        default:         return (RA8875_BLACK);
+        }
+}
+
+/* insure spot_age has been loaded from NV
+ */
+static void loadSpotAge (void)
+{
+        if (spot_age_init)
+            return;
+        spot_age_init = true;
+        if (!NVReadUInt16 (NV_SPOTAGE, &spot_age) || spot_age > MAX_SPOTAGE) {
+            spot_age = DEF_SPOTAGE;
+            NVWriteUInt16 (NV_SPOTAGE, spot_age);
+        }
+}
+
+/* set the minutes for spots to fade and expire, 0 for never, clamped to MAX_SPOTAGE.
+ * the pane shows the change at its next aging refresh.
+ */
+void setSpotAge (uint16_t mins)
+{
+        loadSpotAge();
+        spot_age = mins < MAX_SPOTAGE ? mins : MAX_SPOTAGE;
+        NVWriteUInt16 (NV_SPOTAGE, spot_age);
+}
+
+/* return the minutes for spots to fade and expire, 0 for never
+ */
+uint16_t getSpotAge (void)
+{
+        loadSpotAge();
+        return (spot_age);
+}
+
+/* insure spot_merge has been loaded from NV
+ */
+static void loadSpotMerge (void)
+{
+        if (spot_merge_init)
+            return;
+        spot_merge_init = true;
+        if (!NVReadUInt8 (NV_SPOTMERGE, &spot_merge) || spot_merge > MAX_SPOTMERGE) {
+            spot_merge = DEF_SPOTMERGE;
+            NVWriteUInt8 (NV_SPOTMERGE, spot_merge);
+        }
+}
+
+/* set the minutes within which repeat spots of a station are merged, 0 for never, clamped to MAX_SPOTMERGE
+ */
+void setSpotMerge (uint8_t mins)
+{
+        loadSpotMerge();
+        spot_merge = mins < MAX_SPOTMERGE ? mins : MAX_SPOTMERGE;
+        NVWriteUInt8 (NV_SPOTMERGE, spot_merge);
+}
+
+/* return the minutes within which repeat spots of a station are merged, 0 for never
+ */
+uint8_t getSpotMerge (void)
+{
+        loadSpotMerge();
+        return (spot_merge);
+}
+
+/* return spots[] index of the newest spot of call near kHz still within the merge window, else -1
+ */
+static int findMergeSpot (float kHz, const char call[])
+{
+        loadSpotMerge();
+        if (spot_merge == 0)
+            return (-1);
+
+        time_t oldest = now() - spot_merge*60;
+        for (int i = n_spots; --i >= 0; ) {
+            const DXClusterSpot &s = spots[i];
+            if (s.spotted < oldest)
+                break;                                  // spots[] are oldest first
+            if (fabsf (kHz - s.freq) <= MERGE_KHZ && strcmp (call, s.call) == 0)
+                return (i);
+        }
+        return (-1);
+}
+
+/* merge a new report of s by spotter with the given snr into s: add spotter to the list if new
+ * and keep the best snr along with its spotter.
+ */
+static void mergeSpot (DXClusterSpot &s, const char spotter[], int snr)
+{
+        // add spotter unless already listed, note overflow with a trailing +
+        char key[MAX_SPOTCALL_LEN+2], list[MAX_SPOTTERS_LEN+2];
+        snprintf (key, sizeof(key), ",%s,", spotter);
+        snprintf (list, sizeof(list), ",%s,", s.spotters);
+        if (!strstr (list, key)) {
+            size_t sl = strlen (s.spotters);
+            if (sl + 1 + strlen (spotter) < sizeof(s.spotters))
+                snprintf (s.spotters + sl, sizeof(s.spotters) - sl, ",%s", spotter);
+            else if (sl > 0 && s.spotters[sl-1] != '+') {
+                if (sl + 1 < sizeof(s.spotters))
+                    strcat (s.spotters, "+");
+                else
+                    s.spotters[sl-1] = '+';
+            }
+            if (s.n_spotters < 255)
+                s.n_spotters++;
+        }
+
+        // keep best snr
+        if (snr != SPOT_NO_SNR && (s.snr == SPOT_NO_SNR || snr > s.snr)) {
+            s.snr = snr;
+            strncpy (s.spotter, spotter, MAX_SPOTCALL_LEN-1);
+        }
+
+        Serial.printf (_FX("DXC: merged %s on %g from %s, now %d spotters\n"), s.call, s.freq, spotter,
+                                s.n_spotters);
+}
+
+/* return how far the given spot has aged toward expiry, 0 new .. 1 expired; always 0 if not aging.
+ */
+static float spotAgeFrac (const DXClusterSpot &s)
+{
+        if (getSpotAge() == 0)
+            return (0);
+        float frac = (now() - s.spotted) / (60.0F*spot_age);
+        return (frac < 0 ? 0 : (frac > 1 ? 1 : frac));
+}
+
+/* return the given color dimmed to show the age of the given spot, fading to SPOT_FADE_MIN at expiry
+ */
+static uint16_t fadeSpotColor (uint16_t c, const DXClusterSpot &s)
+{
+        float f = 1 - (1 - SPOT_FADE_MIN)*spotAgeFrac (s);
+        return (RGB565 ((int)(f*RGB565_R(c)), (int)(f*RGB565_G(c)), (int)(f*RGB565_B(c))));
+}
+
 /* set radio and DX from given row, known to be defined
  */
 static void engageRow (DXClusterSpot &s)
@@ -330,69 +628,529 @@ static void setDXClusterSpotMapPosition (DXClusterSpot &s)
 
 static void drawSpotOnMap (DXClusterSpot &s)
 {
-        if (mapDXClusterSpots()) {
+        float prev_opacity;
+        if (mapDXClusterSpots() && beginMapLayer (MAPL_SYMBOLS, &prev_opacity)) {
+            // watched calls stand out, then those near the rig frequency, all fade with age
+            uint16_t fg = s.watched || s.tuned ? RA8875_BLACK : fadeSpotColor (RA8875_WHITE, s);
+            uint16_t bg = s.watched ? WATCH_COLOR : (s.tuned ? TUNED_COLOR : RA8875_BLACK);
+            bg = fadeSpotColor (bg, s);
             if (plotSpotCallsigns()) {
-                drawMapTag (s.call, s.map_b);
+                drawMapTag (s.call, s.map_b, fg, bg);
             } else {
                 char prefix[MAX_PREF_LEN];
                 call2Prefix (s.call, prefix);
-                drawMapTag (prefix, s.map_b);
+                drawMapTag (prefix, s.map_b, fg, bg);
             }
+
+            // outline spots favoring the long path or grayline
+            SpotPath path = getSpotPath (s);
+            if (path != SPATH_SHORT)
+                tft.drawRect (s.map_b.x, s.map_b.y, s.map_b.w, s.map_b.h, spotPathColor (path));
+
+            // spots from other sources get a corner of their color
+            if (s.source)
+                tft.fillRect (s.map_b.x, s.map_b.y, 3, 3, getSpotSourceColor (s.source));
+
+            endMapLayer (prev_opacity);
+            s.on_map = true;
         }
 }
 
-/* draw a spot at the given row in the given box.
+/* qsort-style function to compare two spots[] indices in the current spot_sort order
  */
-static void drawSpotOnList (const SBox &box, uint8_t row)
+static int spotQS (const void *p1, const void *p2)
 {
-        DXClusterSpot *sp = &spots[row];
-        char line[50];
+        int i1 = *(uint8_t*)p1;
+        int i2 = *(uint8_t*)p2;
+        const DXClusterSpot &s1 = spots[i1];
+        const DXClusterSpot &s2 = spots[i2];
+
+        int cmp = 0;
+        switch ((DXSpotSort)spot_sort) {
+        case DXS_FREQ:
+            cmp = s1.freq < s2.freq ? -1 : (s1.freq > s2.freq ? 1 : 0);
+            break;
+        case DXS_BAND:
+            cmp = (int)findHamBand (s1.freq) - (int)findHamBand (s2.freq);
+            break;
+        default:
+            break;
+        }
+
+        // newer spots are later in spots[]
+        return (cmp ? cmp : i2 - i1);
+}
 
+/* insure spot_sort has been loaded from NV
+ */
+static void loadSpotSort (void)
+{
+        if (spot_sort < DXS_N)
+            return;
+        if (!NVReadUInt8 (NV_DXSPOTSORT, &spot_sort) || spot_sort >= DXS_N) {
+            spot_sort = DXS_TIME;
+            NVWriteUInt8 (NV_DXSPOTSORT, spot_sort);
+        }
+}
+
+/* fill sorted[] with all spots[] indices in table order and insure top_row is still sensible
+ */
+static void sortSpots (void)
+{
+        loadSpotSort();
+        for (uint8_t i = 0; i < n_spots; i++)
+            sorted[i] = i;
+        qsort (sorted, n_spots, sizeof(sorted[0]), spotQS);
+        if (top_row >= n_spots)
+            top_row = n_spots > LISTING_N ? n_spots - LISTING_N : 0;
+}
+
+/* return whether spots[i] is in the table rows currently shown
+ */
+static bool spotIsListed (uint8_t i)
+{
+        for (int r = top_row; r < n_spots && r < top_row + LISTING_N; r++)
+            if (sorted[r] == i)
+                return (true);
+        return (false);
+}
+
+/* return whether spots[i] belongs on the map: those in view plus any that are watched
+ */
+static bool spotIsMapped (uint8_t i)
+{
+        return (spots[i].watched || spotIsListed (i));
+}
+
+/* insure watch_calls and the last alert acknowledgement have been loaded from NV
+ */
+static void loadWatchCalls (void)
+{
+        if (watch_calls_init)
+            return;
+        if (!NVReadString (NV_WATCHCALLS, watch_calls)) {
+            watch_calls[0] = '\0';
+            NVWriteString (NV_WATCHCALLS, watch_calls);
+        }
+        if (!NVReadString (NV_WATCHACKCALL, watch_ack_call) || !NVReadUInt32 (NV_WATCHACKTIME, &watch_ack_t)) {
+            watch_ack_call[0] = '\0';
+            watch_ack_t = 0;
+            NVWriteString (NV_WATCHACKCALL, watch_ack_call);
+            NVWriteUInt32 (NV_WATCHACKTIME, watch_ack_t);
+        }
+        watch_calls_init = true;
+}
+
+/* acknowledge the current watched call alert, if any, and remember it so it stays quiet even after
+ * a restart refills the spots.
+ */
+void ackDXClusterWatchAlert (void)
+{
+        if (watch_alert_ms == 0)
+            return;
+        watch_alert_ms = 0;
+
+        loadWatchCalls();
+        strcpy (watch_ack_call, watch_alert_call);
+        watch_ack_t = now();
+        NVWriteString (NV_WATCHACKCALL, watch_ack_call);
+        NVWriteUInt32 (NV_WATCHACKTIME, watch_ack_t);
+}
+
+/* return whether an alert for the given watched call was acknowledged recently
+ */
+static bool watchAlertAcked (const char *call)
+{
+        loadWatchCalls();
+        return (strcmp (call, watch_ack_call) == 0 && now() - watch_ack_t < WATCH_ACK_SECS);
+}
+
+/* return whether upper case word is one of the entries in watch_calls
+ */
+static bool isWatchedWord (const char *word)
+{
+        size_t wl = strlen (word);
+        for (const char *wp = watch_calls; (wp = strstr (wp, word)) != NULL; wp += wl) {
+            bool start_ok = wp == watch_calls || wp[-1] == ' ';
+            bool end_ok = wp[wl] == '\0' || wp[wl] == ' ';
+            if (start_ok && end_ok)
+                return (true);
+        }
+        return (false);
+}
+
+/* return whether the given spotted call is on the watch list.
+ * a watched call matches the whole call or any portion of it separated by /, eg VP2/K1ABC/P is matched by
+ * VP2/K1ABC/P or K1ABC.
+ * a watch list entry of * matches any call.
+ */
+static bool isWatchedCall (const char *call)
+{
+        loadWatchCalls();
+
+        for (const char *wp = watch_calls; (wp = strchr (wp, '*')) != NULL; wp++)
+            if ((wp == watch_calls || wp[-1] == ' ') && (wp[1] == '\0' || wp[1] == ' '))
+                return (true);
+
+        char ucall[MAX_SPOTCALL_LEN];
+        strncpy (ucall, call, sizeof(ucall)-1);
+        ucall[sizeof(ucall)-1] = '\0';
+        for (char *cp = ucall; *cp; cp++)
+            *cp = toupper (*cp);
+
+        // whole call first so entries such as VP8/G4XYZ can match, then each part alone
+        if (isWatchedWord (ucall))
+            return (true);
+        for (char *part = strtok (ucall, "/"); part != NULL; part = strtok (NULL, "/"))
+            if (isWatchedWord (part))
+                return (true);
+        return (false);
+}
+
+/* insure alert_filter has been loaded from NV
+ */
+static void loadAlertFilter (void)
+{
+        if (alert_filter_init)
+            return;
+        alert_filter_init = true;
+        if (!NVReadString (NV_DXALERTFILT, alert_filter)) {
+            alert_filter[0] = '\0';
+            NVWriteString (NV_DXALERTFILT, alert_filter);
+        }
+}
+
+/* copy the value of the given keyword in the alert filter spec, up to the next &, into value.
+ * return whether found.
+ */
+static bool getFilterValue (const char *spec, const char *key, char *value, size_t value_len)
+{
+        size_t kl = strlen (key);
+        for (const char *sp = spec; sp; sp = strchr (sp, '&'), sp = sp ? sp+1 : NULL) {
+            if (strncmp (sp, key, kl) == 0 && sp[kl] == '=') {
+                const char *vp = sp + kl + 1;
+                snprintf (value, value_len, "%.*s", (int)strcspn (vp, "&"), vp);
+                return (true);
+            }
+        }
+        return (false);
+}
+
+/* return whether the given comma separated list contains item, ignoring case
+ */
+static bool filterListHas (const char *list, const char *item)
+{
+        size_t il = strlen (item);
+        for (const char *lp = list; lp; lp = strchr (lp, ','), lp = lp ? lp+1 : NULL)
+            if (strncasecmp (lp, item, il) == 0 && (lp[il] == '\0' || lp[il] == ','))
+                return (true);
+        return (false);
+}
+
+/* return whether the given spot passes each part of the alert filter, if any
+ */
+static bool spotPassesFilter (const DXClusterSpot &s)
+{
+        loadAlertFilter();
+
+        char list[NV_DXALERTFILT_LEN];
+
+        if (getFilterValue (alert_filter, "cont", list, sizeof(list))
+                                && !filterListHas (list, llContinent (s.ll)))
+            return (false);
+
+        if (getFilterValue (alert_filter, "band", list, sizeof(list))) {
+            HamBand b = findHamBand (s.freq);
+            if (b == HB_NONE || !filterListHas (list, getHamBandName (b)))
+                return (false);
+        }
+
+        if (getFilterValue (alert_filter, "pfx", list, sizeof(list))) {
+            // entity without any lower case suffix, eg 3D2c is just 3D2
+            char pfx[MAX_PREF_LEN+1];
+            strcpy (pfx, s.entity);
+            for (size_t l = strlen (pfx); l > 0 && islower (pfx[l-1]); )
+                pfx[--l] = '\0';
+            if (!pfx[0] || !filterListHas (list, pfx))
+                return (false);
+        }
+
+        if (getFilterValue (alert_filter, "qsl", list, sizeof(list))
+                                && !((s.qsl_users & QSLU_LOTW) && filterListHas (list, "lotw"))
+                                && !((s.qsl_users & QSLU_EQSL) && filterListHas (list, "eqsl")))
+            return (false);
+
+        return (true);
+}
+
+/* return whether the given spot is watched and passes the alert filter
+ */
+static bool isWatchedSpot (const DXClusterSpot &s)
+{
+        return (isWatchedCall (s.call) && spotPassesFilter (s));
+}
+
+/* draw the column headings, the one used for sorting stands out
+ */
+static void drawSpotHeadings (const SBox &box)
+{
+        selectFontStyle (LIGHT_FONT, FAST_FONT);
+        uint16_t x = box.x+4;
+        uint16_t y = box.y + HEADING_Y0;
+        tft.fillRect (x, y, box.w-5, LISTING_DY-1, RA8875_BLACK);
+
+        tft.setTextColor (spot_sort == DXS_TIME ? LISTING_COLOR : HEADING_COLOR);
+        tft.setCursor (x, y);
+        tft.print (spotTimeZone());
+
+        tft.setTextColor (spot_sort == DXS_TIME ? HEADING_COLOR : LISTING_COLOR);
+        tft.setCursor (x + FREQ_COL*FONT_W, y);
+        if (spot_sort == DXS_BAND)
+            tft.print (F("Band"));
+        else
+            tft.print (freqUnits());
+
+        tft.setTextColor (HEADING_COLOR);
+        tft.setCursor (x + CALL_COL*FONT_W, y);
+        tft.print (F("Call"));
+        tft.setCursor (x + MODE_COL*FONT_W, y);
+        tft.print (F("Mod"));
+}
+
+/* draw the spot at the given table row in the given box, or erase row if none.
+ */
+static void drawSpotOnList (const SBox &box, uint8_t row)
+{
         selectFontStyle (LIGHT_FONT, FAST_FONT);
-        tft.setTextColor(LISTING_COLOR);
 
         uint16_t x = box.x+4;
         uint16_t y = box.y + LISTING_Y0 + row*LISTING_DY;
         tft.fillRect (x, y, box.w-5, LISTING_DY-1, RA8875_BLACK);
-        tft.setCursor (x, y);
 
-        // pretty freq, fixed 8 chars
-        const char *f_fmt = sp->freq < 1e6 ? "%8.1f" : "%8.0f";
-        (void) sprintf (line, f_fmt, sp->freq);
+        int r = top_row + row;
+        if (r >= n_spots)
+            return;
+        int i = sorted[r];
+        DXClusterSpot *sp = &spots[i];
+        char line[50];
+
+        // time then pretty freq, fixed 7 chars
+        (void) sprintf (line, "%04u ", spotTime (sp->uts));
+        formatFreq (line+FREQ_COL, sizeof(line)-FREQ_COL, sp->freq, CALL_COL-FREQ_COL-1);
 
-        // add remaining fields
-        snprintf (line+8, sizeof(line)-8, _FX(" %-*s %04u"), MAX_SPOTCALL_LEN-1, sp->call, sp->uts);
+        // add remaining fields, truncated to fit
+        snprintf (line+CALL_COL-1, sizeof(line)-(CALL_COL-1), _FX(" %-*.*s %-.*s"),
+                        MODE_COL-CALL_COL-1, MODE_COL-CALL_COL-1, sp->call, MODE_W, sp->mode);
+
+        // selected spot at full brightness, others fade with age
+        tft.setTextColor (i == sel_spot ? SELECT_COLOR : fadeSpotColor (sp->watched ? WATCH_COLOR
+                                : (sp->tuned ? TUNED_COLOR : (sp->needed ? NEEDED_COLOR : LISTING_COLOR)), *sp));
+        tft.setCursor (x, y);
         tft.print (line);
+
+        // mark spots from other sources with their color at the right edge
+        if (sp->source)
+            tft.fillRect (box.x + box.w - 4, y, 2, LISTING_DY-1, fadeSpotColor (getSpotSourceColor(sp->source), *sp));
+
+        // underline calls likely to confirm
+        if (sp->qsl_users) {
+            uint16_t u_x = x + CALL_COL*FONT_W;
+            uint16_t u_w = strlen (sp->call);
+            if (u_w > MODE_COL-CALL_COL-1)
+                u_w = MODE_COL-CALL_COL-1;
+            tft.drawLine (u_x, y+FONT_H+1, u_x + u_w*FONT_W - 2, y+FONT_H+1,
+                        fadeSpotColor ((sp->qsl_users & QSLU_LOTW) ? LOTW_COLOR : EQSL_COLOR, *sp));
+        }
+
+        // time shows the favored path if not short
+        SpotPath path = getSpotPath (*sp);
+        if (path != SPATH_SHORT) {
+            line[4] = '\0';
+            tft.setTextColor (spotPathColor (path));
+            tft.setCursor (x, y);
+            tft.fillRect (x, y, 4*FONT_W, LISTING_DY-1, RA8875_BLACK);
+            tft.print (line);
+        }
+}
+
+/* return whether the given frequency is near the rig dial frequency
+ */
+static bool isTunedFreq (float kHz)
+{
+        return (tuned_khz > 0 && fabsf (kHz - tuned_khz) <= TUNED_KHZ);
+}
+
+/* return spots[] index of the spot nearest the rig dial frequency within the same band, or -1 if none.
+ */
+static int nearestTunedSpot (void)
+{
+        if (tuned_khz <= 0)
+            return (-1);
+
+        HamBand band = findHamBand (tuned_khz);
+        int best = -1;
+        for (int i = 0; i < n_spots; i++) {
+            if (findHamBand (spots[i].freq) != band)
+                continue;
+            if (best < 0 || fabsf (spots[i].freq - tuned_khz) < fabsf (spots[best].freq - tuned_khz))
+                best = i;
+        }
+        return (best);
+}
+
+/* draw the footer: paging arrows around the detail of the selected spot, a new watch alert, the spot
+ * nearest the rig frequency or the rows in view.
+ */
+static void drawSpotFooter (const SBox &box)
+{
+        selectFontStyle (LIGHT_FONT, FAST_FONT);
+        uint16_t y = box.y + box.h - FOOTER_DY;
+        tft.fillRect (box.x+4, y, box.w-5, LISTING_DY-1, RA8875_BLACK);
+
+        tft.setTextColor (top_row > 0 ? LISTING_COLOR : HEADING_COLOR);
+        tft.setCursor (box.x+4, y);
+        tft.print ('<');
+        tft.setTextColor (top_row + LISTING_N < n_spots ? LISTING_COLOR : HEADING_COLOR);
+        tft.setCursor (box.x + box.w - 4 - FONT_W, y);
+        tft.print ('>');
+
+        char buf[40];
+        if (sel_spot >= 0) {
+            const DXClusterSpot &s = spots[sel_spot];
+            const char *path = getSpotPathName (getSpotPath (s));
+            char de[MAX_SPOTCALL_LEN+5];
+            if (s.n_spotters > 1)
+                snprintf (de, sizeof(de), "%s+%d", s.spotter, s.n_spotters-1 < 99 ? s.n_spotters-1 : 99);
+            else
+                snprintf (de, sizeof(de), "%s", s.spotter);
+            const char *label = getSpotSourceLabel (s.source);
+            if (label)
+                snprintf (buf, sizeof(buf), _FX("%s de %s %s"), label, de, s.entity);
+            else if (s.snr != SPOT_NO_SNR)
+                snprintf (buf, sizeof(buf), _FX("de %s %s %+ddB %s"), de, s.entity, s.snr, path);
+            else
+                snprintf (buf, sizeof(buf), _FX("de %s %s %s"), de, s.entity, path);
+            tft.setTextColor (label ? getSpotSourceColor (s.source) : SELECT_COLOR);
+        } else if (watch_alert_ms) {
+            snprintf (buf, sizeof(buf), xlate ("Spotted %s"), watch_alert_call);
+            tft.setTextColor (WATCH_COLOR);
+        } else if (nearestTunedSpot() >= 0) {
+            const DXClusterSpot &s = spots[nearestTunedSpot()];
+            snprintf (buf, sizeof(buf), xlate ("Near %s %+.1f"), s.call, s.freq - tuned_khz);
+            tft.setTextColor (s.tuned ? TUNED_COLOR : HEADING_COLOR);
+        } else {
+            if (n_spots == 0)
+                snprintf (buf, sizeof(buf), "%s", xlate ("No spots"));
+            else
+                snprintf (buf, sizeof(buf), xlate ("%d-%d of %d"), top_row + 1,
+                                top_row + LISTING_N < n_spots ? top_row + LISTING_N : n_spots, n_spots);
+            tft.setTextColor (HEADING_COLOR);
+        }
+        tft.setCursor (box.x + (box.w - getTextWidth(buf))/2, y);
+        tft.print (buf);
 }
 
-/* add a new spot both on map and in list, scrolling list if already full.
+/* restore the map under the given spot's tag
+ */
+static void eraseSpotOnMap (DXClusterSpot &s)
+{
+        for (uint16_t y = s.map_b.y; y < s.map_b.y + s.map_b.h; y++) {
+            for (uint16_t x = s.map_b.x; x < s.map_b.x + s.map_b.w; x++)
+                drawMapCoord (x, y);
+            drawSatPointsOnRow (y);
+        }
+        s.on_map = false;
+}
+
+/* erase from the map each spot that paging, sorting or newer spots have moved out of view, then redraw
+ * those still shown in case their tags overlapped.
+ */
+static void eraseUnlistedSpots (void)
+{
+        bool any = false;
+        for (uint8_t i = 0; i < n_spots; i++) {
+            if (spots[i].on_map && !spotIsMapped (i)) {
+                eraseSpotOnMap (spots[i]);
+                any = true;
+            }
+        }
+        if (any)
+            for (uint8_t i = 0; i < n_spots; i++)
+                if (spots[i].on_map)
+                    drawSpotOnMap (spots[i]);
+}
+
+/* sort and draw the entire spot table
+ */
+static void drawSpotListing (const SBox &box)
+{
+        sortSpots();
+        drawSpotHeadings (box);
+
+        // QR code only while its spot remains selected
+        if (sel_spot < 0)
+            qr_link[0] = '\0';
+        if (qr_link[0]) {
+            SBox qr_b;
+            qr_b.x = box.x + 1;
+            qr_b.y = box.y + LISTING_Y0;
+            qr_b.w = box.w - 2;
+            qr_b.h = LISTING_N*LISTING_DY;
+            tft.fillRect (qr_b.x, qr_b.y, qr_b.w, qr_b.h, RA8875_BLACK);
+            if (!drawQRCode (qr_link, qr_b))
+                qr_link[0] = '\0';
+        }
+        if (!qr_link[0])
+            for (uint8_t row = 0; row < LISTING_N; row++)
+                drawSpotOnList (box, row);
+
+        drawSpotFooter (box);
+        eraseUnlistedSpots();
+}
+
+/* add a new spot both on map and in list, discarding the oldest if already full.
  * use grid to get ll if set, else look up call to set both.
- * return false if same spot again or some error.
+ * snr is the dB report, if any, else SPOT_NO_SNR.
+ * source is 0 for the main cluster or WSJT-X, else the spotsources.cpp source 1 .. n.
+ * a repeat spot of the same station near the same freq within the merge window from any source only adds
+ * its spotter and snr to the earlier spot, so it neither clutters the table nor alerts again.
+ * return false if merged, same spot again or some error.
  */
-static bool addDXClusterSpot (const SBox &box, float kHz, const char call[], const char *grid, uint16_t ut)
+static bool addDXClusterSpot (const SBox &box, float kHz, const char call[], const char spotter[],
+                                        const char *mode, const char *grid, int snr, uint16_t ut,
+                                        uint8_t source)
 {
-        // skip if same station on same freq as previous
+        // merge if repeat
+        int merge_i = findMergeSpot (kHz, call);
+        if (merge_i >= 0) {
+            mergeSpot (spots[merge_i], spotter, snr);
+            drawSpotListing (box);
+            return (false);
+        }
+
+        // skip if same station on same freq as newest
         if (n_spots > 0) {
             DXClusterSpot &spot = spots[n_spots-1];
             if (fabsf(kHz-spot.freq) < 0.1F && strcmp (call, spot.call) == 0)
                 return (false);
         }
 
-        // find next available row, scrolling if necessary
-        if (n_spots == LISTING_N) {
-            // scroll up, discarding top (first) entry
-            for (uint8_t i = 0; i < LISTING_N-1; i++) {
-                spots[i] = spots[i+1];
-                drawSpotOnList (box, i);
-            }
-            n_spots = LISTING_N-1;
-        }
-        DXClusterSpot &spot = spots[n_spots];
-
         // store the easy info
+        DXClusterSpot spot;
+        memset (&spot, 0, sizeof(spot));
         spot.freq = kHz;
-        memcpy (spot.call, call, MAX_SPOTCALL_LEN-1);      // preserve existing EOS
+        strncpy (spot.call, call, MAX_SPOTCALL_LEN-1);
+        strncpy (spot.spotter, spotter, MAX_SPOTCALL_LEN-1);
+        strncpy (spot.spotters, spotter, MAX_SPOTTERS_LEN-1);
+        spot.n_spotters = 1;
+        if (!mode || !mode[0])
+            mode = findBandPlanMode (kHz);              // guess from band plan if not reported
+        if (mode)
+            strncpy (spot.mode, mode, MAX_SPOTMODE_LEN-1);
         spot.uts = ut;
+        spot.spotted = now();
+        spot.snr = snr;
+        spot.source = source;
 
         // find ll and grid some way
         char errmsg[50] = "";
@@ -415,24 +1173,86 @@ static bool addDXClusterSpot (const SBox &box, float kHz, const char call[], con
                 snprintf (errmsg, sizeof(errmsg), _FX("%s ll lookup failed"), call);
         }
         if (!ok) {
-            // error set grid and ll to 0/0
             dxcTrace (errmsg);
-            memset (&spot.ll, 0, sizeof(spot.ll));
-            ll2maidenhead (spot.grid, spot.ll);
             return (false);
         }
+        if (!nearestPrefix (spot.ll, spot.entity))
+            spot.entity[0] = '\0';
+
+        // note if near the rig
+        spot.tuned = isTunedFreq (spot.freq);
+
+        // note whether it confirms through LoTW or eQSL, then alert if watched
+        spot.qsl_users = getQSLUserFlags (call);
+        spot.needed = awardSlotNeeded (spot.entity, spot.freq, spot.mode);
+        spot.watched = isWatchedSpot (spot);
+        bool alert = spot.watched && !watchAlertAcked (spot.call);
+        if (alert) {
+            Serial.printf (_FX("DXC: watched %s spotted on %g\n"), spot.call, spot.freq);
+            strcpy (watch_alert_call, spot.call);
+            watch_alert_ms = millis() | 1;                   // never 0
+        }
 
+        // let scripts and any web socket clients know, alert first so it can react quickly
+        char khz_str[20];
+        snprintf (khz_str, sizeof(khz_str), "%.1f", spot.freq);
+        if (alert) {
+            char details[100];
+            snprintf (details, sizeof(details), "%s %s", spot.call, khz_str);
+            (void) notifyAlert ("watch", alertSeverity ("watch"), details, sizeof(details), spot.call,
+                                spot.freq, spot.mode, spot.call, khz_str);
+        }
+        runEventScript ("on_spot", spot.call, khz_str, spot.mode, spot.spotter, NULL);
+        sendWebSocketSpot (spot);
+
+        // discard oldest if full
+        if (n_spots == MAX_SPOTS) {
+            if (spots[0].on_map)
+                eraseSpotOnMap (spots[0]);
+            memmove (&spots[0], &spots[1], (MAX_SPOTS-1)*sizeof(DXClusterSpot));
+            n_spots = MAX_SPOTS-1;
+            if (sel_spot >= 0)
+                sel_spot--;
+        }
+        uint8_t new_i = n_spots++;
+        spots[new_i] = spot;
 
         // draw
-        drawSpotOnList (box, n_spots);
-        setDXClusterSpotMapPosition (spot);
-        drawSpotOnMap (spot);
+        drawSpotListing (box);
+        setDXClusterSpotMapPosition (spots[new_i]);
+        if (spotIsMapped (new_i))
+            drawSpotOnMap (spots[new_i]);
 
         // ok
-        n_spots++;
         return (true);
 }
 
+/* add a spot from a simulation scenario as if from the cluster, if the DX Cluster pane is up.
+ * return whether it is.
+ */
+bool injectDXClusterSpot (float kHz, const char *call, const char *grid, const char *mode)
+{
+        PlotPane pp = findPaneChoiceNow (PLOT_CH_DXCLUSTER);
+        if (pp == PANE_NONE)
+            return (false);
+
+        char g[MAID_CHARLEN];
+        snprintf (g, sizeof(g), "%s", grid);
+        (void) addDXClusterSpot (plot_b[pp], kHz, call, "SIM", mode, g, SPOT_NO_SNR, hour()*100 + minute(), 0);
+        return (true);
+}
+
+/* add a spot from the given spotsources.cpp source 1 .. n
+ */
+void addDXClusterSourceSpot (const SBox &box, uint8_t source, DXSpotLine &sl)
+{
+        if (sl.ut < 0)
+            sl.ut = hour()*100 + minute();
+        last_action = millis();
+        (void) addDXClusterSpot (box, sl.kHz, sl.call, sl.spotter, sl.mode, sl.grid[0] ? sl.grid : NULL,
+                                        sl.snr, sl.ut, source);
+}
+
 /* given address of pointer into a WSJT-X message, extract bool and advance pointer to next field.
  */
 static bool wsjtx_bool (uint8_t **bpp)
@@ -492,10 +1312,10 @@ static uint64_t wsjtx_quint64 (uint8_t **bpp)
         return (x);
 }
 
-/* return whether the given packet contains a WSJT-X Status packet.
- * if true, leave *bpp positioned just after ID.
+/* return the WSJT-X message type in the given packet, or -1 if it is not from WSJT-X.
+ * if ok, leave *bpp positioned just after ID.
  */
-static bool wsjtxIsStatusMsg (uint8_t **bpp)
+static int wsjtxMsgType (uint8_t **bpp)
 {
         resetWatchdog();
 
@@ -504,28 +1324,54 @@ static bool wsjtxIsStatusMsg (uint8_t **bpp)
         // Serial.printf (_FX("DXC: magic 0x%x\n"), magic);
         if (magic != 0xADBCCBDA) {
             Serial.println (F("DXC: packet received but wrong magic"));
-            return (false);
+            return (-1);
         }
 
         // crack and ignore the max schema value
         (void) wsjtx_quint32 (bpp);                         // skip past max schema
 
-        // crack message type. we only care about Status messages which are type 1
+        // crack message type. we only care about Status messages which are type 1 and QSO Logged which are 5
         uint32_t msgtype = wsjtx_quint32 (bpp);
         // Serial.printf (_FX("DXC: type %d\n"), msgtype);
-        if (msgtype != 1)
-            return (false);
+        if (msgtype != 1 && msgtype != 5)
+            return (msgtype);
 
-        // if we get this far assume packet is what we want.
         // crack ID but ignore to allow compatibility with clones.
         volatile char *id = wsjtx_utf8 (bpp);
         (void)id;           // lint
         // Serial.printf (_FX("DXC: id '%s'\n"), id);
         // if (strcmp ("WSJT-X", id) != 0)
-            // return (false);
+            // return (-1);
 
         // ok!
-        return (true);
+        return (msgtype);
+}
+
+/* parse WSJT-X message known to be QSO Logged and pass it on to the QSO rate meter and award progress.
+ * *bpp is positioned just after ID field.
+ */
+static void wsjtxParseQSOLoggedMsg (uint8_t **bpp)
+{
+        resetWatchdog();
+
+        // Date&Time Off is a QDateTime: julian day, ms since midnight, time spec and offset if spec 2
+        uint64_t jd = wsjtx_quint64 (bpp);
+        uint32_t ms = wsjtx_quint32 (bpp);
+        uint8_t spec = **bpp;
+        *bpp += 1;
+        int32_t offset = spec == 2 ? (int32_t) wsjtx_quint32 (bpp) : 0;
+        time_t t = (time_t)(jd - 2440588)*SECSPERDAY + ms/1000 - offset;     // 2440588 is JD of 1970-1-1
+        if (spec != 1 && spec != 2)
+            t = now();                                          // local or zone, just assume now
+
+        // skip to grid, frequency and mode
+        (void) wsjtx_utf8 (bpp);                            // skip over DX call
+        char *grid = wsjtx_utf8 (bpp);                      // capture DX grid
+        uint64_t tx_freq = wsjtx_quint64 (bpp);             // capture Hz
+        char *mode = wsjtx_utf8 (bpp);                      // capture mode
+
+        noteLoggedQSO (t, tx_freq*1e-3, mode);              // Hz to kHz
+        noteAwardLoggedQSO (grid, tx_freq*1e-3, mode);
 }
 
 /* parse and process WSJT-X message known to be Status.
@@ -539,7 +1385,7 @@ static void wsjtxParseStatusMsg (const SBox &box, uint8_t **bpp)
 
         // crack remaining fields down to grid
         uint64_t dial_freq = wsjtx_quint64 (bpp);           // capture Hz
-        (void) wsjtx_utf8 (bpp);                            // skip over mode
+        char *mode = wsjtx_utf8 (bpp);                      // capture mode
         char *dx_call = wsjtx_utf8 (bpp);                   // capture call
         (void) wsjtx_utf8 (bpp);                            // skip over report
         (void) wsjtx_utf8 (bpp);                            // skip over Tx mode
@@ -548,7 +1394,7 @@ static void wsjtxParseStatusMsg (const SBox &box, uint8_t **bpp)
         (void) wsjtx_bool (bpp);                            // skip over decoding flag
         (void) wsjtx_quint32 (bpp);                         // skip over Rx DF -- not always correct
         (void) wsjtx_quint32 (bpp);                         // skip over Tx DF
-        (void) wsjtx_utf8 (bpp);                            // skip over DE call
+        char *de_call = wsjtx_utf8 (bpp);                   // capture DE call
         (void) wsjtx_utf8 (bpp);                            // skip over DE grid
         char *dx_grid = wsjtx_utf8 (bpp);                   // capture grid
 
@@ -573,7 +1419,7 @@ static void wsjtxParseStatusMsg (const SBox &box, uint8_t **bpp)
         uint16_t ut = hr*100 + mn;
 
         // add to list with actual frequency and set if new
-        if (addDXClusterSpot (box, dial_freq*1e-3, dx_call, dx_grid, ut)) {                  // Hz to kHz
+        if (addDXClusterSpot (box, dial_freq*1e-3, dx_call, de_call, mode, dx_grid, SPOT_NO_SNR, ut, 0)) {  // Hz to kHz
             // Serial.printf (_FX("DXC: WSJT-X %s @ %s\n"), dx_call, dx_grid);
             engageRow (spots[n_spots-1]);
         }
@@ -581,6 +1427,70 @@ static void wsjtxParseStatusMsg (const SBox &box, uint8_t **bpp)
         // printFreeHeap(F("wsjtxParseStatusMsg"));
 }
 
+/* every AGE_SECS remove expired spots and redraw the rest dimmed for their age, if aging at all.
+ * spots[] is oldest first so expired spots are all at the front.
+ */
+static void ageSpots (const SBox &box)
+{
+        static time_t next_age;
+        time_t t0 = now();
+        if (t0 < next_age)
+            return;
+        next_age = t0 + AGE_SECS;
+        if (getSpotAge() == 0 || n_spots == 0)
+            return;
+
+        // remove expired spots from the map then from spots[]
+        uint8_t n_exp = 0;
+        while (n_exp < n_spots && spotAgeFrac (spots[n_exp]) >= 1)
+            n_exp++;
+        if (n_exp > 0) {
+            if (mapDXClusterSpots())
+                for (uint8_t i = 0; i < n_exp; i++)
+                    if (spotIsMapped (i))
+                        eraseSpotOnMap (spots[i]);
+            n_spots -= n_exp;
+            memmove (&spots[0], &spots[n_exp], n_spots*sizeof(DXClusterSpot));
+            if (sel_spot >= 0)
+                sel_spot = sel_spot >= n_exp ? sel_spot - n_exp : -1;
+        }
+
+        // redraw all that remain at their new brightness
+        drawSpotListing (box);
+        for (uint8_t i = 0; i < n_spots; i++)
+            if (spotIsMapped (i))
+                drawSpotOnMap (spots[i]);
+}
+
+/* follow the rig dial frequency: when it changes update which spots are tuned, redrawing any that
+ * changed on the map and in the table, and the footer showing the nearest spot.
+ */
+static void checkTunedSpots (const SBox &box)
+{
+        float kHz;
+        if (!getRigFrequency (kHz))
+            kHz = 0;
+        if (fabsf (kHz - tuned_khz) < 0.05F)
+            return;
+        tuned_khz = kHz;
+
+        bool any = false;
+        for (uint8_t i = 0; i < n_spots; i++) {
+            bool tuned = isTunedFreq (spots[i].freq);
+            if (tuned != spots[i].tuned) {
+                spots[i].tuned = tuned;
+                if (spotIsMapped (i))
+                    drawSpotOnMap (spots[i]);
+                any = true;
+            }
+        }
+
+        if (any)
+            drawSpotListing (box);
+        else
+            drawSpotFooter (box);
+}
+
 /* display the given error message and shut down the connection.
  * draw entire box in case we were not the front pane at time of error.
  */
@@ -592,7 +1502,7 @@ static void showDXClusterErr (const SBox &box, const char *msg)
         // show title and message
         selectFontStyle (LIGHT_FONT, FAST_FONT);
         tft.setTextColor(RA8875_RED);
-        const char *title = "DX Cluster error:";
+        const char *title = xlate ("DX Cluster error:");
         uint16_t tw = getTextWidth (title);
         tft.setCursor (box.x + (box.w-tw)/2, box.y + box.h/3);
         tft.print (title);
@@ -608,14 +1518,251 @@ static void showDXClusterErr (const SBox &box, const char *msg)
 }
 
 
-/* try to connect to the cluster defined by getDXClusterHost():getDXClusterPort().
+/* set cl_type if the given lower case line from the cluster gives a clue about its type
+ */
+static void findDXClusterType (const char *line)
+{
+        if (strstr (line, "dx") && strstr (line, "spider"))
+            cl_type = CT_DXSPIDER;
+    #if defined(_SUPPORT_ARCLUSTER)
+        else if (strstr (line, "ar-cluster") && strstr (line, "ersion") && strchr (line, '6'))
+            cl_type = CT_ARCLUSTER;
+    #endif // _SUPPORT_ARCLUSTER
+}
+
+/* return whether the given lower case text from the cluster ends with a request for our call,
+ * ignoring trailing blanks.
+ */
+static bool isLoginPrompt (const char *text)
+{
+        static const char *prompts[] = {"login:", "call:", "callsign:", "call sign:"};
+
+        size_t tl = strlen (text);
+        while (tl > 0 && text[tl-1] == ' ')
+            tl--;
+        for (unsigned i = 0; i < NARRAY(prompts); i++) {
+            size_t pl = strlen (prompts[i]);
+            if (tl >= pl && strncmp (text + tl - pl, prompts[i], pl) == 0)
+                return (true);
+        }
+        return (false);
+}
+
+/* return whether dx_client sends nothing more for a moment, as when it is waiting for input
+ */
+static bool dxcQuiet()
+{
+        uint32_t t0 = millis();
+        while (!dx_client.available() && !timesUp (&t0, 500))
+            wdDelay (10);
+        return (!dx_client.available());
+}
+
+/* log in to the newly connected dx_client: read until the cluster asks for our call, which normally
+ * does not end with a newline, send login, then read until its first command prompt ending with '>'.
+ * if the cluster never asks, send login anyway in case it is waiting silently.
+ * set cl_type from any clue about the type of cluster along the way.
+ * return whether found the command prompt.
+ */
+static bool loginDXCluster (const char *login)
+{
+        StackMalloc buf_mem(200);
+        char *buf = buf_mem.getMem();
+        size_t bl = 0;
+        bool sent = false;
+
+        cl_type = CT_UNKNOWN;
+        buf[0] = '\0';
+
+        while (dx_client) {
+
+            // next char, send login once if cluster goes quiet before asking
+            char c;
+            if (!getChar (dx_client, &c)) {
+                if (sent || !dx_client)
+                    break;
+                dxcTrace (_FX("no login prompt"));
+                dx_client.println (login);
+                sent = true;
+                continue;
+            }
+
+            // collect next line in lower case
+            if (c == '\r')
+                continue;
+            if (c != '\n') {
+                if (bl < buf_mem.getSize()-1) {
+                    buf[bl++] = tolower(c);
+                    buf[bl] = '\0';
+                }
+
+                // a prompt usually has no newline so check as each char arrives, but only
+                // believe it if the cluster then waits
+                if (!sent && isLoginPrompt (buf) && dxcQuiet()) {
+                    dxcTrace (buf);
+                    dx_client.println (login);
+                    sent = true;
+                    bl = 0;
+                    buf[0] = '\0';
+                } else if (sent && c == '>' && dxcQuiet()) {
+                    findDXClusterType (buf);
+                    return (true);
+                }
+                continue;
+            }
+
+            // whole line
+            findDXClusterType (buf);
+            if (sent && bl > 0 && buf[bl-1] == '>')
+                return (true);
+            bl = 0;
+            buf[0] = '\0';
+        }
+
+        dxcTrace (_FX("no command prompt"));
+        return (false);
+}
+
+/* crack WWV or WCY space weather announcements, return whether line was one.
+ *   WWV de VE7CC <18>:   SFI=150, A=10, K=2, No Storms -> No Storms
+ *   WCY de DK0WCY-1 <11> : K=2 expK=3 A=12 R=78 SFI=148 SA=qui GMF=qui Au=no
+ */
+static bool crackSpaceWxAnnouncement (const char *line)
+{
+        bool wwv = strncmp (line, "WWV de ", 7) == 0;
+        bool wcy = strncmp (line, "WCY de ", 7) == 0;
+        if (!wwv && !wcy)
+            return (false);
+
+        // values follow the first ':'
+        const char *vals = strchr (line, ':');
+        if (!vals)
+            return (false);
+
+        ClusterSpaceWx cspw;
+        memset (&cspw, 0, sizeof(cspw));
+        cspw.sfi = cspw.a = cspw.k = cspw.ssn = -1;
+        if (sscanf (line+7, "%11[^ <:]", cspw.node) != 1)
+            strcpy (cspw.node, "?");
+
+        // N.B. K= must not match expK= nor A= match SA=
+        const char *sp;
+        if ((sp = strstr (vals, "SFI=")) != NULL)
+            cspw.sfi = atof (sp+4);
+        for (sp = vals; (sp = strstr (sp, "K=")) != NULL; sp += 2) {
+            if (!isalpha(sp[-1])) {
+                cspw.k = atof (sp+2);
+                break;
+            }
+        }
+        for (sp = vals; (sp = strstr (sp, "A=")) != NULL; sp += 2) {
+            if (!isalpha(sp[-1])) {
+                cspw.a = atof (sp+2);
+                break;
+            }
+        }
+        if (wcy && (sp = strstr (vals, " R=")) != NULL)
+            cspw.ssn = atof (sp+3);
+        if (wcy && (sp = strstr (vals, "Au=")) != NULL)
+            (void) sscanf (sp+3, "%7[a-zA-Z]", cspw.aurora);
+
+        dxcTrace (line);
+        noteClusterSpaceWeather (cspw);
+        return (true);
+}
+
+/* load dx_hosts[] with the cluster set in Setup followed by any others listed in dxhosts_fn.
+ * hosts may be IPv6 addresses, optionally in [] to look like a url.
+ */
+static void loadDXClusterHosts()
+{
+        // first is always the one set in Setup
+        DXClusterHost *hp = &dx_hosts[0];
+        snprintf (hp->host, sizeof(hp->host), "%s", getDXClusterHost());
+        hp->port = getDXClusterPort();
+        snprintf (hp->login, sizeof(hp->login), "%s", getCallsign());
+        n_dxhosts = 1;
+
+    #if defined(_IS_UNIX)
+        std::string fn = our_dir + dxhosts_fn;
+        FILE *fp = fopen (fn.c_str(), "r");
+        if (fp) {
+            char line[150], host[sizeof(hp->host)+2];
+            while (n_dxhosts < MAX_DXHOSTS && fgets (line, sizeof(line), fp)) {
+                hp = &dx_hosts[n_dxhosts];
+                hp->login[0] = '\0';
+                if (line[0] == '#' || sscanf (line, "%65s %d %11s", host, &hp->port, hp->login) < 2)
+                    continue;
+                if (hp->port <= 0 || hp->port > 65535) {
+                    Serial.printf (_FX("DXC: %s: bad port %d\n"), dxhosts_fn, hp->port);
+                    continue;
+                }
+                char *h = host;
+                size_t hl = strlen (h);
+                if (h[0] == '[' && h[hl-1] == ']') {
+                    h[hl-1] = '\0';
+                    h++;
+                }
+                if (strlen (h) >= sizeof(hp->host)) {
+                    Serial.printf (_FX("DXC: %s: host name too long: %s\n"), dxhosts_fn, h);
+                    continue;
+                }
+                strcpy (hp->host, h);
+                if (hp->login[0] == '\0')
+                    snprintf (hp->login, sizeof(hp->login), "%s", getCallsign());
+                n_dxhosts++;
+            }
+            fclose (fp);
+        }
+    #endif // _IS_UNIX
+
+        if (dx_host_i >= n_dxhosts)
+            dx_host_i = 0;
+}
+
+/* pass back the cluster host and port now in use or to be tried next.
+ */
+void getDXClusterHostInUse (const char **hostp, int *portp)
+{
+        if (n_dxhosts == 0)
+            loadDXClusterHosts();
+        *hostp = dx_hosts[dx_host_i].host;
+        *portp = dx_hosts[dx_host_i].port;
+}
+
+/* while connected to a fallback cluster, occasionally check whether the Setup cluster is reachable
+ * again and if so close so the next update reconnects to it.
+ */
+static void checkPrimaryDXCluster()
+{
+        if (dx_host_i == 0 || !timesUp (&primary_ms, PRIMARY_RETRY) || isOffline())
+            return;
+
+        const DXClusterHost &primary = dx_hosts[0];
+        WiFiClient probe;
+        if (probe.connect (primary.host, primary.port)) {
+            probe.stop();
+            Serial.printf (_FX("DXC: %s:%d is back, switching\n"), primary.host, primary.port);
+            closeDXCluster();
+            dx_host_i = 0;
+        }
+}
+
+/* try to connect to the cluster at dx_hosts[dx_host_i].
  * if success: dx_client or wsjtx_server is live and return true,
- * else: both are closed, display error msg in box, return false.
+ * else: both are closed, display error msg in box, advance dx_host_i to try the next cluster next time
+ * and return false.
  */
 static bool connectDXCluster (const SBox &box)
 {
-        const char *dxhost = getDXClusterHost();
-        int dxport = getDXClusterPort();
+        // freshen list, first is from Setup which may have changed
+        loadDXClusterHosts();
+        const DXClusterHost &dxh = dx_hosts[dx_host_i];
+        const char *dxhost = dxh.host;
+        int dxport = dxh.port;
+
+        // next time try the next, unless this works
+        dx_host_i = (dx_host_i + 1) % n_dxhosts;
 
         Serial.printf (_FX("DXC: Connecting to %s:%d\n"), dxhost, dxport);
         resetWatchdog();
@@ -629,13 +1776,18 @@ static bool connectDXCluster (const SBox &box)
 
                 // record and claim ok so far
                 cl_type = CT_WSJTX;
+                dx_host_i = &dxh - dx_hosts;
                 return (true);
             }
 
         } else {
 
-            // open fresh socket
+            // open fresh socket unless offline
             dx_client.stop();
+            if (isOffline()) {
+                showDXClusterErr (box, _FX("Offline"));
+                return (false);
+            }
             if (wifiOk() && dx_client.connect(dxhost, dxport)) {
 
                 // look alive
@@ -643,26 +1795,10 @@ static bool connectDXCluster (const SBox &box)
                 updateClocks(false);
                 dxcTrace (_FX("connect ok"));
 
-                // assume we have been asked for our callsign
-                dx_client.println (getCallsign());
-
-                // read until find a line ending with '>', looking for clue about type of cluster
-                uint16_t bl;
-                StackMalloc buf_mem(200);
-                char *buf = buf_mem.getMem();
-                cl_type = CT_UNKNOWN;
-                while (getTCPLine (dx_client, buf, buf_mem.getSize(), &bl)) {
-                    // Serial.println (buf);
-                    strtolower(buf);
-                    if (strstr (buf, "dx") && strstr (buf, "spider"))
-                        cl_type = CT_DXSPIDER;
-    #if defined(_SUPPORT_ARCLUSTER)
-                    else if (strstr (buf, "ar-cluster") && strstr (buf, "ersion") && strchr (buf, '6'))
-                        cl_type = CT_ARCLUSTER;
-    #endif // _SUPPORT_ARCLUSTER
-
-                    if (buf[bl-1] == '>')
-                        break;
+                // log in and find type
+                if (!loginDXCluster (dxh.login)) {
+                    showDXClusterErr (box, _FX("Login failed"));
+                    return (false);
                 }
 
                 if (cl_type == CT_UNKNOWN) {
@@ -675,13 +1811,25 @@ static bool connectDXCluster (const SBox &box)
                     return (false);
                 }
 
+                // ask for WWV and WCY space weather announcements, not all nodes send them by default
+                if (cl_type == CT_DXSPIDER) {
+                    StackMalloc buf_mem(100);
+                    char *buf = buf_mem.getMem();
+                    dx_client.println (F("set/wwv"));
+                    (void) lookForDXClusterString (buf, buf_mem.getSize(), ">");
+                    dx_client.println (F("set/wcy"));
+                    (void) lookForDXClusterString (buf, buf_mem.getSize(), ">");
+                }
+
                 // confirm still ok
                 if (!dx_client) {
                     showDXClusterErr (box, _FX("Login failed"));
                     return (false);
                 }
 
-                // all ok so far
+                // all ok so far, stay with this one
+                dx_host_i = &dxh - dx_hosts;
+                primary_ms = millis();
                 return (true);
             }
         }
@@ -695,11 +1843,16 @@ static bool connectDXCluster (const SBox &box)
  */
 static void showHostPort (const SBox &box, uint16_t c)
 {
-        const char *dxhost = getDXClusterHost();
-        int dxport = getDXClusterPort();
+        const char *dxhost;
+        int dxport;
+        getDXClusterHostInUse (&dxhost, &dxport);
 
+        // IPv6 addresses in [] as in urls
         char name[(box.w-2)/FONT_W];
-        snprintf (name, sizeof(name), _FX("%s:%d"), dxhost, dxport);
+        if (strchr (dxhost, ':'))
+            snprintf (name, sizeof(name), _FX("[%s]:%d"), dxhost, dxport);
+        else
+            snprintf (name, sizeof(name), _FX("%s:%d"), dxhost, dxport);
 
         selectFontStyle (LIGHT_FONT, FAST_FONT);
         tft.setTextColor(c);
@@ -816,12 +1969,12 @@ static bool initDXCluster(const SBox &box)
             showHostPort (box, RA8875_GREEN);
 
             // restore known spots if not too old else reset list
-            if (millis() - last_action < MAX_AGE) {
-                for (uint8_t i = 0; i < n_spots; i++)
-                    drawSpotOnList (box, i);
-            } else {
+            if (millis() - last_action >= MAX_AGE) {
                 n_spots = 0;
+                top_row = 0;
+                sel_spot = -1;
             }
+            drawSpotListing (box);
 
             // reinit time
             last_action = millis();
@@ -843,6 +1996,13 @@ static bool initDXCluster(const SBox &box)
  */
 bool updateDXCluster(const SBox &box)
 {
+        // return to the Setup cluster when it is back
+        if (isDXClusterConnected())
+            checkPrimaryDXCluster();
+
+        // follow any other spot sources regardless of the main cluster
+        pollSpotSources (box);
+
         // open if not already
         if (!isDXClusterConnected() && !initDXCluster(box)) {
             // error already shown
@@ -855,8 +2015,7 @@ bool updateDXCluster(const SBox &box)
 
             // roll any new spots into list
             char line[120];
-            char call[30];
-            float kHz;
+            DXSpotLine sl;
             while (dx_client.available() && getTCPLine (dx_client, line, sizeof(line), NULL)) {
                 // DX de KD0AA:     18100.0  JR1FYS       FT8 LOUD in FL!                2156Z EL98
 
@@ -864,6 +2023,9 @@ bool updateDXCluster(const SBox &box)
                 updateClocks(false);
                 resetWatchdog();
 
+                // fold any UTF-8 in comments to ASCII so it is not scattered into blanks below
+                foldUTF8 (line);
+
                 // log but note some clusters embed \a bell in their reports, remove so they don't beep
                 for (char *lp = line; *lp; lp++)
                     if (!isprint(*lp))
@@ -871,16 +2033,19 @@ bool updateDXCluster(const SBox &box)
                 // Serial.println (line);
 
                 // crack
-                if (sscanf (line, _FX("DX de %*s %f %10s"), &kHz, call) == 2) {
+                if (crackSpaceWxAnnouncement (line)) {
+                    last_action = millis();
+                } else if (crackDXSpotLine (line, sl)) {
                     dxcTrace (line);
 
-                    // looks like a spot, extract time also
-                    char *utp = &line[70];
-                    uint16_t ut = atoi(utp) % 2400;
+                    // use now if spot has no time
+                    if (sl.ut < 0)
+                        sl.ut = hour()*100 + minute();
 
-                    // note and display
+                    // note and display, mode is inferred from band plan if not in comment
                     last_action = millis();
-                    (void) addDXClusterSpot (box, kHz, call, NULL, ut);
+                    (void) addDXClusterSpot (box, sl.kHz, sl.call, sl.spotter, sl.mode,
+                                                sl.grid[0] ? sl.grid : NULL, sl.snr, sl.ut, 0);
                 }
             }
 
@@ -916,7 +2081,11 @@ bool updateDXCluster(const SBox &box)
                 resetWatchdog();
                 if (wsjtx_server.read (any_msg, packet_size) > 0) {
                     uint8_t *bp = any_msg;
-                    if (wsjtxIsStatusMsg (&bp)) {
+                    int msgtype = wsjtxMsgType (&bp);
+                    if (msgtype == 5) {
+                        // count each logged QSO now, they are not superceded as Status is
+                        wsjtxParseQSOLoggedMsg (&bp);
+                    } else if (msgtype == 1) {
                         // save from bp to the end in prep for wsjtxParseStatusMsg()
                         int n_skip = bp - any_msg;
                         // Serial.printf (_FX("DXC: skip= %d packet_size= %d\n"), n_skip, packet_size);
@@ -938,6 +2107,24 @@ bool updateDXCluster(const SBox &box)
                 free (any_msg);
         }
 
+        // follow the rig
+        checkTunedSpots (box);
+
+        // fade and expire old spots
+        ageSpots (box);
+
+        // draw attention to a newly spotted watched call for a while
+        static bool prev_alerting;
+        bool alerting = watch_alert_ms != 0 && millis() - watch_alert_ms < WATCH_ALERT_MS;
+        if (alerting || prev_alerting) {
+            drawAlertBorder (box, WATCH_COLOR, alerting);
+            if (!alerting) {
+                watch_alert_ms = 0;
+                drawSpotFooter (box);
+            }
+            prev_alerting = alerting;
+        }
+
         // didn't break
         return (true);
 }
@@ -955,6 +2142,7 @@ void closeDXCluster()
             wsjtx_server.stop();
             Serial.printf (_FX("DXC: WSTJ-X disconnect %s\n"), wsjtx_server ?"failed":"ok");
         }
+        closeSpotSources();
 }
 
 /* try to set DX from the touched spot.
@@ -966,17 +2154,57 @@ bool checkDXClusterTouch (const SCoord &s, const SBox &box)
         if (!inBox (s, box))
             return (false);
 
+        // any tap acknowledges a watched call alert, next update restores border and footer
+        ackDXClusterWatchAlert();
+
         // tapping title always leaves this pane
         if (s.y < box.y + TITLE_Y0) {
+            qr_link[0] = '\0';
             closeDXCluster();             // insure disconnected
             last_action = millis();       // in case op wants to come back soon
             return (false);
         }
 
-        // engage tapped row, if defined
+        // tapping host or headings steps to the next sort order
+        if (s.y < box.y + LISTING_Y0 - 1) {
+            spot_sort = (spot_sort + 1) % DXS_N;
+            NVWriteUInt8 (NV_DXSPOTSORT, spot_sort);
+            top_row = 0;
+            sel_spot = -1;
+            drawSpotListing (box);
+            return (true);
+        }
+
+        // tapping footer pages back if left half else forward
+        if (s.y >= box.y + box.h - FOOTER_DY - 1) {
+            if (s.x < box.x + box.w/2)
+                top_row = top_row > LISTING_N ? top_row - LISTING_N : 0;
+            else if (top_row + LISTING_N < n_spots)
+                top_row += LISTING_N;
+            sel_spot = -1;
+            drawSpotListing (box);
+            return (true);
+        }
+
+        // any tap on a QR code restores the listing
+        if (qr_link[0]) {
+            qr_link[0] = '\0';
+            drawSpotListing (box);
+            return (true);
+        }
+
+        // select and engage tapped row, if defined; tapping the selected row again shows its SDR link
         int click_row = ((s.y+LISTING_DY/2-FONT_H/2-box.y-LISTING_Y0)/LISTING_DY);
-        if (click_row >= 0 && click_row < n_spots && spots[click_row].call[0] != '\0' && isDXClusterConnected())
-            engageRow (spots[click_row]);
+        int r = top_row + click_row;
+        if (click_row >= 0 && click_row < LISTING_N && r < n_spots && isDXClusterConnected()) {
+            if (sorted[r] == sel_spot && getSpotSDRLink (spots[sel_spot], qr_link, sizeof(qr_link))) {
+                drawSpotListing (box);
+                return (true);
+            }
+            sel_spot = sorted[r];
+            drawSpotListing (box);
+            engageRow (spots[sel_spot]);
+        }
 
         // ours
         return (true);
@@ -1013,7 +2241,8 @@ void drawDXClusterSpotsOnMap ()
             return;
 
         for (uint8_t i = 0; i < n_spots; i++)
-            drawSpotOnMap (spots[i]);
+            if (spotIsMapped (i))
+                drawSpotOnMap (spots[i]);
 }
 
 /* return whether the given screen coord lies over any spot label.
@@ -1026,7 +2255,7 @@ bool overAnyDXClusterSpots(const SCoord &s)
             return (false);
 
         for (uint8_t i = 0; i < n_spots; i++)
-            if (inBox (s, spots[i].map_b))
+            if (spotIsMapped (i) && inBox (s, spots[i].map_b))
                 return (true);
 
         return (false);
@@ -1038,3 +2267,183 @@ bool isDXClusterConnected()
 {
         return (useDXCluster() && (dx_client || wsjtx_server));
 }
+
+/* return the name of the current spot table sort order
+ */
+const char *getDXClusterSortName()
+{
+        loadSpotSort();
+        return (dxs_names[spot_sort]);
+}
+
+/* set the spot table sort order by name, case insensitive.
+ * return whether name is known.
+ */
+bool setDXClusterSortName (const char *name)
+{
+        for (int i = 0; i < DXS_N; i++) {
+            if (strcasecmp (name, dxs_names[i]) == 0) {
+                spot_sort = i;
+                NVWriteUInt8 (NV_DXSPOTSORT, spot_sort);
+                top_row = 0;
+                sel_spot = -1;
+                PlotPane pp = findPaneChoiceNow (PLOT_CH_DXCLUSTER);
+                if (pp != PANE_NONE && isDXClusterConnected())
+                    drawSpotListing (plot_b[pp]);
+                return (true);
+            }
+        }
+        return (false);
+}
+
+/* return the space separated list of calls to watch for, possibly empty
+ */
+const char *getDXClusterWatchCalls()
+{
+        loadWatchCalls();
+        return (watch_calls);
+}
+
+/* set the calls to watch for from a list separated by spaces or commas, or "none".
+ * return whether list is sensible and fits.
+ */
+bool setDXClusterWatchCalls (const char *calls)
+{
+        char new_calls[NV_WATCHCALLS_LEN];
+        size_t n = 0;
+
+        if (strcasecmp (calls, "none") != 0) {
+            bool sep = true;
+            for (const char *cp = calls; *cp; cp++) {
+                if (*cp == ' ' || *cp == ',') {
+                    sep = true;
+                } else if (isalnum (*cp) || *cp == '/' || *cp == '*') {
+                    if (sep && n > 0)
+                        new_calls[n++] = ' ';
+                    sep = false;
+                    if (n >= sizeof(new_calls)-2)
+                        return (false);
+                    new_calls[n++] = toupper (*cp);
+                } else
+                    return (false);
+            }
+        }
+        new_calls[n] = '\0';
+
+        strcpy (watch_calls, new_calls);
+        watch_calls_init = true;
+        NVWriteString (NV_WATCHCALLS, watch_calls);
+
+        // update existing spots
+        for (uint8_t i = 0; i < n_spots; i++)
+            spots[i].watched = isWatchedSpot (spots[i]);
+
+        return (true);
+}
+
+/* refresh which QSL services each spotted call uses and which would fill an award slot after the QSL
+ * lists or the log change
+ */
+void updateDXClusterSpotFlags (void)
+{
+        for (uint8_t i = 0; i < n_spots; i++) {
+            spots[i].qsl_users = getQSLUserFlags (spots[i].call);
+            spots[i].needed = awardSlotNeeded (spots[i].entity, spots[i].freq, spots[i].mode);
+            spots[i].watched = isWatchedSpot (spots[i]);
+        }
+}
+
+/* return the watch alert filter, empty if none
+ */
+const char *getDXAlertFilter()
+{
+        loadAlertFilter();
+        return (alert_filter);
+}
+
+/* set the watch alert filter from a spec of any of cont=list, band=list, pfx=list and qsl=lotw,eqsl joined
+ * by &, where each list is separated by commas, or "none". a watched call only alerts if its spot matches
+ * each part; qsl matches calls known to use any of the listed QSL services.
+ * if trouble put reason in ynot and return false.
+ */
+bool setDXAlertFilter (const char *spec, char *ynot, size_t ynot_len)
+{
+        char new_filter[NV_DXALERTFILT_LEN];
+
+        if (strcasecmp (spec, "none") == 0)
+            new_filter[0] = '\0';
+        else {
+            if (strlen (spec) >= sizeof(new_filter)) {
+                snprintf (ynot, ynot_len, "filter must be shorter than %d chars", NV_DXALERTFILT_LEN);
+                return (false);
+            }
+
+            // check each keyword and list, saving in canonical case
+            static const char *keys[] = {"cont", "band", "pfx", "qsl"};
+            static const char *conts[] = {"NA", "SA", "EU", "AF", "AS", "OC", "AN"};
+            size_t n = 0;
+            for (const char *sp = spec; sp; sp = strchr (sp, '&'), sp = sp ? sp+1 : NULL) {
+                const char *eq = strchr (sp, '=');
+                size_t kl = eq ? (size_t)(eq - sp) : 0;
+                int k = -1;
+                for (unsigned i = 0; i < NARRAY(keys) && k < 0; i++)
+                    if (strlen (keys[i]) == kl && strncmp (sp, keys[i], kl) == 0)
+                        k = i;
+                if (k < 0) {
+                    snprintf (ynot, ynot_len, "keyword must be cont, band, pfx or qsl");
+                    return (false);
+                }
+                char list[NV_DXALERTFILT_LEN];
+                snprintf (list, sizeof(list), "%.*s", (int)strcspn (eq+1, "&"), eq+1);
+                if (!list[0] || list[strlen(list)-1] == ',' || strstr (list, ",,")) {
+                    snprintf (ynot, ynot_len, "%s list is empty", keys[k]);
+                    return (false);
+                }
+                for (char *lp = list; *lp; lp++) {
+                    if (!isalnum (*lp) && *lp != ',') {
+                        snprintf (ynot, ynot_len, "%s list may only contain letters, digits and commas",
+                                                keys[k]);
+                        return (false);
+                    }
+                    *lp = k == 1 || k == 3 ? tolower (*lp) : toupper (*lp);
+                }
+                for (const char *lp = list; lp; lp = strchr (lp, ','), lp = lp ? lp+1 : NULL) {
+                    size_t il = strcspn (lp, ",");
+                    bool ok = true;
+                    if (k == 0) {
+                        ok = false;
+                        for (unsigned c = 0; c < NARRAY(conts) && !ok; c++)
+                            ok = il == 2 && strncmp (lp, conts[c], 2) == 0;
+                    } else if (k == 1) {
+                        ok = false;
+                        for (int b = 0; b < HB_N && !ok; b++)
+                            ok = strlen (getHamBandName ((HamBand)b)) == il
+                                                && strncmp (lp, getHamBandName ((HamBand)b), il) == 0;
+                    } else if (k == 3)
+                        ok = il == 4 && (strncmp (lp, "lotw", 4) == 0 || strncmp (lp, "eqsl", 4) == 0);
+                    if (!ok) {
+                        snprintf (ynot, ynot_len, "unknown %s %.*s", keys[k], (int)il, lp);
+                        return (false);
+                    }
+                }
+                n += snprintf (new_filter+n, sizeof(new_filter)-n, "%s%s=%s", n > 0 ? "&" : "", keys[k],list);
+            }
+        }
+
+        strcpy (alert_filter, new_filter);
+        alert_filter_init = true;
+        NVWriteString (NV_DXALERTFILT, alert_filter);
+
+        // update existing spots
+        for (uint8_t i = 0; i < n_spots; i++)
+            spots[i].watched = isWatchedSpot (spots[i]);
+
+        return (true);
+}
+
+/* return whether the given call is on the watch list
+ */
+bool isDXClusterWatchedCall (const char *call)
+{
+        return (isWatchedCall (call));
+}
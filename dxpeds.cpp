@@ -0,0 +1,232 @@
+/* show current and upcoming DXpeditions in a plot pane as a scrollable list, active ones first.
+ * DXpeditions whose call is on the DX cluster watch list stand out.
+ */
+
+#include "HamClock.h"
+
+
+// server file derived from the NG3K Announced DX Operations list, one per line, any order:
+//   start_unix_time,end_unix_time,call,entity,bands,qsl
+// eg: 1697068800,1698278400,3Y0J,Bouvet,160-6m,LoTW
+static const char dxpeds_page[] = "/ham/HamClock/dxpeds/dxpeditions.txt";
+
+// layout
+#define TITLE_COLOR     RA8875_GREEN
+#define ACTIVE_COLOR    RA8875_GREEN
+#define UPCOMING_COLOR  RA8875_WHITE
+#define WATCH_COLOR     RA8875_MAGENTA
+#define DETAIL_COLOR    GRAY
+#define TITLE_Y0        27              // title dy, match VOACAP title position
+#define LISTING_Y0      36              // first DXpedition y down from box top
+#define LISTING_DY      22              // listing row separation, each DXpedition uses 2 lines
+#define LISTING_N       ((PLOTBOX_H - LISTING_Y0)/LISTING_DY)       // n rows visible at once
+#define CALL_X          4               // column x offsets from box left
+#define DETAIL_X        10
+#define ARROW_X         146             // scroll arrows x offset from box left
+#define ARROW_W         8               // scroll arrow width
+#define ARROW_H         6               // scroll arrow height
+#define DETAIL_N        ((ARROW_X - DETAIL_X)/6 - 1)                // max detail chars
+
+// one DXpedition
+typedef struct {
+    time_t start, end;                  // UTC of operating dates
+    char call[11];                      // call
+    char entity[24];                    // DXCC entity
+    char bands[16];                     // bands planned
+    char qsl[16];                       // QSL route
+} DXPed;
+
+static DXPed *dxpeds;                   // malloced list, active first
+static int n_dxpeds;                    // n used in dxpeds[]
+static int top_dxped;                   // index of dxpeds[] shown in first row
+
+
+/* qsort-style function to sort DXPeds by increasing start time
+ */
+static int dxpedQS (const void *p1, const void *p2)
+{
+    time_t t1 = ((DXPed*)p1)->start;
+    time_t t2 = ((DXPed*)p2)->start;
+    return (t1 < t2 ? -1 : (t1 > t2 ? 1 : 0));
+}
+
+/* crack one line of the DXpedition list.
+ * return whether line was sensible and, if so, fill dp.
+ */
+static bool crackDXPedLine (const char *line, DXPed &dp)
+{
+    long start, end;
+    memset (&dp, 0, sizeof(dp));
+    if (sscanf (line, "%ld,%ld,%10[^,],%23[^,],%15[^,],%15[^\n]", &start, &end, dp.call, dp.entity,
+                                                                        dp.bands, dp.qsl) < 4)
+        return (false);
+    if (end < start)
+        return (false);
+    dp.start = start;
+    dp.end = end;
+    return (true);
+}
+
+/* draw dxpeds[] starting with top_dxped, with arrows showing whether there are more either way.
+ */
+static void drawDXPedListing (const SBox &box)
+{
+    // erase listing area, not the border
+    tft.fillRect (box.x+1, box.y+LISTING_Y0-1, box.w-2, box.h-LISTING_Y0, RA8875_BLACK);
+
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+
+    if (n_dxpeds == 0) {
+        tft.setTextColor (DETAIL_COLOR);
+        const char *none = xlate ("None announced");
+        tft.setCursor (box.x + (box.w - getTextWidth(none))/2, box.y + LISTING_Y0 + LISTING_DY);
+        tft.print (none);
+        return;
+    }
+
+    time_t t0 = now();
+    for (int row = 0; row < LISTING_N && top_dxped + row < n_dxpeds; row++) {
+        const DXPed &dp = dxpeds[top_dxped + row];
+        uint16_t y = box.y + LISTING_Y0 + row*LISTING_DY;
+        bool active = dp.start <= t0;
+        char buf[60];
+
+        // call, watched stand out
+        tft.setTextColor (isDXClusterWatchedCall (dp.call) ? WATCH_COLOR
+                                                : (active ? ACTIVE_COLOR : UPCOMING_COLOR));
+        tft.setCursor (box.x + CALL_X, y);
+        tft.print (dp.call);
+
+        // dates, right justified before arrows
+        if (active)
+            snprintf (buf, sizeof(buf), "til %.3s %d", monthShortStr(month(dp.end)), day(dp.end));
+        else if (month(dp.start) == month(dp.end))
+            snprintf (buf, sizeof(buf), "%.3s %d-%d", monthShortStr(month(dp.start)), day(dp.start),
+                                                        day(dp.end));
+        else {
+            int l = snprintf (buf, sizeof(buf), "%.3s %d-", monthShortStr(month(dp.start)), day(dp.start));
+            snprintf (buf+l, sizeof(buf)-l, "%.3s %d", monthShortStr(month(dp.end)), day(dp.end));
+        }
+        tft.setCursor (box.x + ARROW_X - 4 - getTextWidth(buf), y);
+        tft.print (buf);
+
+        // entity, bands and QSL below
+        tft.setTextColor (DETAIL_COLOR);
+        snprintf (buf, sizeof(buf), "%s %s %s", dp.entity, dp.bands, dp.qsl);
+        buf[DETAIL_N] = '\0';
+        tft.setCursor (box.x + DETAIL_X, y + LISTING_DY/2);
+        tft.print (buf);
+    }
+
+    // scroll arrows
+    uint16_t ax = box.x + ARROW_X;
+    if (top_dxped > 0) {
+        uint16_t ay = box.y + LISTING_Y0;
+        tft.fillTriangle (ax, ay+ARROW_H, ax+ARROW_W, ay+ARROW_H, ax+ARROW_W/2, ay, RA8875_WHITE);
+    }
+    if (top_dxped + LISTING_N < n_dxpeds) {
+        uint16_t ay = box.y + LISTING_Y0 + (LISTING_N-1)*LISTING_DY + LISTING_DY/2;
+        tft.fillTriangle (ax, ay, ax+ARROW_W, ay, ax+ARROW_W/2, ay+ARROW_H, RA8875_WHITE);
+    }
+}
+
+/* draw the complete pane from dxpeds[]
+ */
+static void drawDXPeds (const SBox &box)
+{
+    prepPlotBox (box);
+
+    // title
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (TITLE_COLOR);
+    const char *title = xlate ("DXpeditions");
+    tft.setCursor (box.x + (box.w - getTextWidth(title))/2, box.y + TITLE_Y0);
+    tft.print (title);
+
+    drawDXPedListing (box);
+}
+
+/* retrieve and display the current and upcoming DXpeditions in the given box.
+ * return whether all ok.
+ */
+bool updateDXPeds (const SBox &box)
+{
+    WiFiClient dxpeds_client;
+    char line[120];
+    bool ok = false;
+
+    Serial.println (dxpeds_page);
+    resetWatchdog();
+    if (wifiOk() && dxpeds_client.connect (svr_host, HTTPPORT)) {
+
+        resetWatchdog();
+        updateClocks(false);
+
+        // fetch page
+        httpGET (dxpeds_client, svr_host, dxpeds_page);
+        if (!httpSkipHeader (dxpeds_client)) {
+            plotMessage (box, RA8875_RED, _FX("DXpeditions header short"));
+            goto out;
+        }
+
+        // collect each not yet over, skipping comments
+        free (dxpeds);
+        dxpeds = NULL;
+        n_dxpeds = 0;
+        time_t t0 = now();
+        DXPed dp;
+        while (getTCPLine (dxpeds_client, line, sizeof(line), NULL)) {
+            foldUTF8 (line);
+            if (line[0] == '#' || !crackDXPedLine (line, dp) || dp.end < t0)
+                continue;
+            dxpeds = (DXPed *) realloc (dxpeds, (n_dxpeds+1)*sizeof(DXPed));
+            if (!dxpeds)
+                fatalError (_FX("No memory for %d DXpeditions"), n_dxpeds+1);
+            dxpeds[n_dxpeds++] = dp;
+        }
+        Serial.printf (_FX("DXPeds: found %d\n"), n_dxpeds);
+
+        // active first because they started earliest
+        if (n_dxpeds > 1)
+            qsort (dxpeds, n_dxpeds, sizeof(DXPed), dxpedQS);
+        top_dxped = 0;
+
+        // show
+        drawDXPeds (box);
+        ok = true;
+
+    } else
+        plotMessage (box, RA8875_RED, _FX("DXpeditions connection failed"));
+
+out:
+    dxpeds_client.stop();
+    printFreeHeap (F("updateDXPeds"));
+    return (ok);
+}
+
+/* called when the given location is tapped below the title of the given box showing the DXpeditions.
+ * scroll back a page if in upper half of listing, else forward a page.
+ * return whether tap was ours.
+ */
+bool checkDXPedsTouch (const SCoord &s, const SBox &box)
+{
+    if (!inBox (s, box) || s.y < box.y + LISTING_Y0 || n_dxpeds == 0)
+        return (false);
+
+    int new_top = top_dxped;
+    if (s.y < box.y + LISTING_Y0 + (box.h - LISTING_Y0)/2)
+        new_top -= LISTING_N;
+    else
+        new_top += LISTING_N;
+    if (new_top > n_dxpeds - LISTING_N)
+        new_top = n_dxpeds - LISTING_N;
+    if (new_top < 0)
+        new_top = 0;
+
+    if (new_top != top_dxped) {
+        top_dxped = new_top;
+        drawDXPedListing (box);
+    }
+
+    return (true);
+}
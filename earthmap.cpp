@@ -280,55 +280,29 @@ static void drawLLGrid (int lat_step, int lng_step)
 
         // lines of latitude, exclude the poles
         for (float lat = -90+lat_step; lat < 90; lat += lat_step) {
-            SCoord s0, s1;
-            ll2s (deg2rad(lat), deg2rad(-180), s0, 0);
-            bool s0_left = s0.x < map_b.x+map_b.w/2;
-            for (float lng = -180+lng_step; lng <= 180; lng += lng_step) {
-                ll2s (deg2rad(lat), deg2rad(lng), s1, 0);
-                bool s1_left = s1.x < map_b.x+map_b.w/2;
-                if (s0_left == s1_left && !overRSS(s0) && !overRSS(s1)) {
-                    // full deg spacing on same hemisphere
-                    tft.drawLine (s0.x, s0.y, s1.x, s1.y, lat == 0 ? GRIDC00 : GRIDC);
-                } else {
-                    // backfill with finer fine_steps
-                    for (float lg = lng-lng_step+fine_step; lg <= lng; lg += fine_step) {
-                        ll2s (deg2rad(lat), deg2rad(lg), s1, 0);
-                        s1_left = s1.x < map_b.x+map_b.w/2;
-                        if (s0_left == s1_left && !overRSS(s0) && !overRSS(s1))
-                            tft.drawLine (s0.x, s0.y, s1.x, s1.y, lat == 0 ? GRIDC00 : GRIDC);
-                        s0 = s1;
-                        s0_left = s1_left;
-                    }
-                }
-                s0 = s1;
-                s0_left = s1_left;
+            LatLong ll0, ll1;
+            ll0.lat_d = ll1.lat_d = lat;
+            ll0.lng_d = -180;
+            normalizeLL (ll0);
+            for (float lng = -180+fine_step; lng <= 180; lng += fine_step) {
+                ll1.lng_d = lng;
+                normalizeLL (ll1);
+                drawGeoSegment (ll0, ll1, 1, lat == 0 ? GRIDC00 : GRIDC);
+                ll0 = ll1;
             }
         }
 
         // lines of longitude -- pole to pole
         for (float lng = -180; lng < 180; lng += lng_step) {
-            SCoord s0, s1;
-            ll2s (deg2rad(-90), deg2rad(lng), s0, 0);
-            bool s0_left = s0.x < map_b.x+map_b.w/2;
-            for (float lat = -90+lat_step; lat <= 90; lat += lat_step) {
-                ll2s (deg2rad(lat), deg2rad(lng), s1, 0);
-                bool s1_left = s1.x < map_b.x+map_b.w/2;
-                if (s0_left == s1_left && !overRSS(s0) && !overRSS(s1)) {
-                    // full deg spacing on same hemisphere
-                    tft.drawLine (s0.x, s0.y, s1.x, s1.y, lng == 0 ? GRIDC00 : GRIDC);
-                } else {
-                    // backfill with finer fine_steps
-                    for (float lt = lat-lat_step+fine_step; lt <= lat; lt += fine_step) {
-                        ll2s (deg2rad(lt), deg2rad(lng), s1, 0);
-                        s1_left = s1.x < map_b.x+map_b.w/2;
-                        if (s0_left == s1_left && !overRSS(s0) && !overRSS(s1))
-                            tft.drawLine (s0.x, s0.y, s1.x, s1.y, lng == 0 ? GRIDC00 : GRIDC);
-                        s0 = s1;
-                        s0_left = s1_left;
-                    }
-                }
-                s0 = s1;
-                s0_left = s1_left;
+            LatLong ll0, ll1;
+            ll0.lng_d = ll1.lng_d = lng;
+            ll0.lat_d = -90;
+            normalizeLL (ll0);
+            for (float lat = -90+fine_step; lat <= 90; lat += fine_step) {
+                ll1.lat_d = lat;
+                normalizeLL (ll1);
+                drawGeoSegment (ll0, ll1, 1, lng == 0 ? GRIDC00 : GRIDC);
+                ll0 = ll1;
             }
         }
 
@@ -391,18 +365,17 @@ static void drawMapGrid()
         if (azm_on) {
 
             // just 2 lines at lat +- 23.5
-            SCoord s00, s01, s10, s11;
-            ll2s (deg2rad(-23.5F), deg2rad(-180), s00, 0);
-            ll2s (deg2rad(23.5F), deg2rad(-180), s10, 0);
-            for (float lng = -180; lng <= 180; lng += 1) {
-                ll2s (deg2rad(-23.5), deg2rad(lng), s01, 0);
-                ll2s (deg2rad(23.5), deg2rad(lng), s11, 0);
-                if (segmentSpanOk(s00,s01))
-                    tft.drawLine (s00.x, s00.y, s01.x, s01.y, GRIDC);
-                s00 = s01;
-                if (segmentSpanOk(s10,s11))
-                    tft.drawLine (s10.x, s10.y, s11.x, s11.y, GRIDC);
-                s10 = s11;
+            for (float lat = -23.5F; lat < 24; lat += 47) {
+                LatLong ll0, ll1;
+                ll0.lat_d = ll1.lat_d = lat;
+                ll0.lng_d = -180;
+                normalizeLL (ll0);
+                for (float lng = -179; lng <= 180; lng += 1) {
+                    ll1.lng_d = lng;
+                    normalizeLL (ll1);
+                    drawGeoSegment (ll0, ll1, 1, GRIDC);
+                    ll0 = ll1;
+                }
             }
 
         } else {
@@ -934,11 +907,37 @@ void drawMoreEarth()
     if ((moremap_s.y += 1) >= map_b.y + EARTH_H) {
         moremap_s.y = map_b.y;
 
-        drawMapGrid();
-        drawHeadingPath();
-        drawSatPathAndFoot();
-        drawSatNameOnRow (0);
-        drawAllSymbols(false);
+        // draw each visible overlay in order with its opacity
+        for (int i = 0; i < MAPL_NOVER; i++) {
+            MapLayer ml = getMapOverlay (i);
+            float opacity = getMapLayerOpacity (ml);
+            if (opacity == 0)
+                continue;
+            tft.setDrawOpacity (opacity);
+            switch (ml) {
+            case MAPL_GRID:
+                drawMapGrid();
+                break;
+            case MAPL_PATH:
+                drawHeadingPath();
+                drawEclipsePath();
+                break;
+            case MAPL_SAT:
+                drawSatPathAndFoot();
+                drawSatNameOnRow (0);
+                break;
+            case MAPL_SYMBOLS:
+                drawAllSymbols(false);
+                break;
+            case MAPL_AURORA:
+                drawAuroraLayer();
+                break;
+            default:
+                break;
+            }
+        }
+        tft.setDrawOpacity (1);
+
         if (waiting4DXPath())
             drawDXPath();
         drawMouseLoc();
@@ -1086,6 +1085,26 @@ float lngDiff (float dlng)
     return (fdiff);
 }
 
+#if !defined(_IS_ESP8266)
+
+/* return fraction of full daylight at the given location: 1 when sunlit, 0 when night, else blend
+ * through twilight. always 1 if not showing night.
+ */
+static float mapFractDay (const LatLong &ll)
+{
+    // cos of angle from subsolar point
+    float cos_t = ssslat*sinf(ll.lat) + csslat*cosf(ll.lat)*cosf(sun_ss_ll.lng-ll.lng);
+
+    if (!night_on || cos_t > 0)
+        return (1);
+
+    // night fraction is dimmed by the night layer opacity
+    float fract_night = cos_t > GRAYLINE_COS ? powf(cos_t/GRAYLINE_COS, GRAYLINE_POW) : 1;
+    return (1 - fract_night * getMapLayerOpacity (MAPL_NIGHT));
+}
+
+#endif
+
 #if defined(_IS_ESP8266)
 
 /* given lat/lng and cos of angle from terminator, return earth map pixel.
@@ -1286,28 +1305,15 @@ void drawMapCoord (const SCoord &s)
         if (!s2ll(sd,lld))
             lld = lls;
 
-        // find angle between subsolar point and any visible near this location
-        // TODO: actually different at each subpixel, this causes striping
-        float clat = cosf(lls.lat);
-        float slat = sinf(lls.lat);
-        float cos_t = ssslat*slat + csslat*clat*cosf(sun_ss_ll.lng-lls.lng);
-
-        // decide day, night or twilight
-        float fract_day;
-        if (!night_on || cos_t > 0) {
-            // < 90 deg: sunlit
-            fract_day = 1;
-        } else if (cos_t > GRAYLINE_COS) {
-            // blend from day to night
-            fract_day = 1 - powf(cos_t/GRAYLINE_COS, GRAYLINE_POW);
-        } else {
-            // night side
-            fract_day = 0;
-        }
+        // find day fraction here and at r and d so plotEarth can blend smoothly across each subpixel
+        float fract_day = mapFractDay (lls);
+        float fract_day_r = mapFractDay (llr);
+        float fract_day_d = mapFractDay (lld);
 
         // draw the full res map point
         tft.plotEarth (s.x, s.y, lls.lat_d, lls.lng_d, llr.lat_d - lls.lat_d, llr.lng_d - lls.lng_d,
-                    lld.lat_d - lls.lat_d, lld.lng_d - lls.lng_d, fract_day);
+                    lld.lat_d - lls.lat_d, lld.lng_d - lls.lng_d,
+                    fract_day, fract_day_r - fract_day, fract_day_d - fract_day);
 
     #endif  // _IS_ESP8266
 
@@ -1519,13 +1525,3 @@ void antipode (LatLong &to, const LatLong &from)
     to.lng_d = from.lng_d+180;
     normalizeLL(to);
 }
-
-/* return whether the given line segment spans a reasonable portion of the map.
- * beware map edge, wrap and crossing center of azm map
- */
-bool segmentSpanOk (SCoord &s0, SCoord &s1)
-{
-    return (s0.x - s1.x < tft.width()/2 && s1.x - s0.x < tft.width()/2
-                && (!azm_on || ((s0.x < map_b.x+map_b.w/2) == (s1.x < map_b.x+map_b.w/2)))
-                && overMap(s0) && overMap(s1));
-}
@@ -45,9 +45,16 @@ bool dx_info_for_sat;                   // global to indicate whether dx_info_b
 #define N_COLS          4               // n cols in name table
 #define Q_TO            5               // question timeout
 #define CELL_W          (tft.width()/N_COLS)                    // display cell width
-#define N_ROWS          ((tft.height()-TBORDER)/CELL_H)         // n rows in name table
+#define SETTINGS_H      40              // height of picker settings strip below name table
+#define SETTINGS_Y      (tft.height()-SETTINGS_H)               // top of picker settings strip
+#define N_ROWS          ((tft.height()-TBORDER-SETTINGS_H)/CELL_H)      // n rows in name table
 #define MAX_NSAT        (N_ROWS*N_COLS)                         // max names we can display
 #define MAX_PASS_STEPS  30              // max lines to draw for pass map
+#define VIS_MIN_EL      10.0F           // sat must rise above this to be seen, degrees
+#define VIS_SUN_EL      (-6.0F)         // sun must be below this at DE to see sat, degrees
+#define VIS_STEP        30              // visibility check step size, seconds
+#define VIS_STAR_R      6               // visible pass star marker radius, pixels
+#define VIS_COLOR       RA8875_YELLOW   // visible pass star marker color
 
 // used so findNextPass() can be used for contexts other than the current sat now
 // TODO: make another for az/el/range/rate and use them with getSatAzElNow()
@@ -55,7 +62,7 @@ typedef struct {
     DateTime rise_time, set_time;       // next pass times
     bool rise_ok, set_ok;               // whether rise_time and set_time are valid
     float rise_az, set_az;              // rise and set az, degrees, if valid
-    bool ever_up, ever_down;            // whether sat is ever above or below SAT_MIN_EL in next day
+    bool ever_up, ever_down;            // whether sat is ever above or below its min el in next day
 } SatRiseSet;
 
 
@@ -72,10 +79,18 @@ static const float foot_alts[N_FOOT] = {0.0F, 30.0F, 60.0F};
 static uint16_t n_foot[N_FOOT];         // actual dots along each altitude 
 static SBox map_name_b;                 // location of sat name on map
 static SBox ok_b = {730,10,55,35};      // Ok button
+static SBox sce_b = {200,445,140,30};   // picker settings: enabled
+static SBox scp_b = {350,445,140,30};   // picker settings: priority
+static SBox scel_b = {500,445,140,30};  // picker settings: min elevation
+static SBox scm_b = {650,445,140,30};   // picker settings: mode
 static char sat_name[NV_SATNAME_LEN];   // NV_SATNAME cache (spaces are underscores)
 #define SAT_NAME_IS_SET()               (sat_name[0])           // whether there is a sat name defined
+#define SAT_USERNAME_LEN                (NV_SATNAME_LEN+10)     // sat name plus room for its mode
 static time_t tle_refresh;              // last TLE update
 static bool new_pass;                   // set when new pass is ready
+static bool sat_rs_visible;             // whether the pass in sat_rs can be seen by eye
+static uint8_t vis_alert;               // NV_SATVISALERT cache: whether to alert only for visible passes
+static bool vis_alert_init;             // set once vis_alert is loaded from NV
 
 
 /* completely undefine the current sat
@@ -244,6 +259,7 @@ static void findNextPass(const char *name, time_t t, SatRiseSet &rs)
     DateTime t_now = userDateTime(t);         // search starting time
     DateTime t_srch = t_now + -FINE_DT; // search time, start beyond any previous solution
     float tel, taz, trange, trate;      // target el and az, degrees
+    float min_el = name ? getSatMinEl (name) : SAT_MIN_EL;     // pass threshold for this sat, degrees
 
     // init pel and make first step
     sat->predict (t_srch);
@@ -261,9 +277,9 @@ static void findNextPass(const char *name, time_t t, SatRiseSet &rs)
         sat->topo (obs, tel, taz, trange, trate);
 
         // check for rising or setting events
-        if (tel >= SAT_MIN_EL) {
+        if (tel >= min_el) {
             rs.ever_up = true;
-            if (pel < SAT_MIN_EL) {
+            if (pel < min_el) {
                 if (dt == FINE_DT) {
                     // found a refined set event (recall we are going backwards),
                     // record and resume forward time.
@@ -280,7 +296,7 @@ static void findNextPass(const char *name, time_t t, SatRiseSet &rs)
             }
         } else {
             rs.ever_down = true;
-            if (pel > SAT_MIN_EL) {
+            if (pel > min_el) {
                 if (dt == FINE_DT) {
                     // found a refined rise event (recall we are going backwards).
                     // record and resume forward time but skip if set is within COARSE_DT because we
@@ -289,7 +305,7 @@ static void findNextPass(const char *name, time_t t, SatRiseSet &rs)
                     DateTime check_set = t_srch + COARSE_DT;
                     sat->predict (check_set);
                     sat->topo (obs, check_tel, check_taz, trange, trate);
-                    if (check_tel >= SAT_MIN_EL) {
+                    if (check_tel >= min_el) {
                         rs.rise_time = t_srch;
                         rs.rise_az = taz;
                         rs.rise_ok = true;
@@ -324,6 +340,96 @@ static void findNextPass(const char *name, time_t t, SatRiseSet &rs)
 
 }
 
+/* return whether the current sat can be seen by eye at DE sometime between the given UTC rise and set:
+ * high enough, lit by the sun and the sky at DE dark enough.
+ */
+bool isSatPassVisible (time_t rise, time_t set)
+{
+    if (!sat || !obs || isSatMoon())
+        return (false);
+
+    Sun sun;
+    for (time_t t = rise; t <= set; t += VIS_STEP) {
+
+        // sky must be dark
+        AstroCir cir;
+        getSolarCir (t, de_ll, cir);
+        if (rad2deg(cir.el) > VIS_SUN_EL)
+            continue;
+
+        // sat must be high enough
+        DateTime dt = userDateTime(t);
+        float el, az, range, rate;
+        sat->predict (dt);
+        sat->topo (obs, el, az, range, rate);
+        if (el < VIS_MIN_EL)
+            continue;
+
+        // and in sunlight
+        sun.predict (dt);
+        if (!sat->eclipsed (&sun))
+            return (true);
+    }
+
+    return (false);
+}
+
+/* find the next pass of the current sat into sat_rs and note whether it is visible.
+ */
+static void findCurrentPass()
+{
+    time_t t0 = nowWO();
+    findNextPass(sat_name, t0, sat_rs);
+
+    sat_rs_visible = false;
+    if (sat_rs.rise_ok && sat_rs.set_ok) {
+        DateTime t0dt = userDateTime(t0);
+        time_t st = t0 + SECSPERDAY*(sat_rs.set_time - t0dt);
+        time_t rt = sat_rs.rise_time < sat_rs.set_time ? t0 + SECSPERDAY*(sat_rs.rise_time - t0dt) : t0;
+        sat_rs_visible = isSatPassVisible (rt, st);
+    }
+}
+
+/* load vis_alert from NV if not already
+ */
+static void loadSatVisAlert()
+{
+    if (vis_alert_init)
+        return;
+
+    if (!NVReadUInt8 (NV_SATVISALERT, &vis_alert)) {
+        vis_alert = 0;
+        NVWriteUInt8 (NV_SATVISALERT, vis_alert);
+    }
+    vis_alert_init = true;
+}
+
+/* return whether rise alarms are only for visible passes
+ */
+bool getSatVisAlert()
+{
+    loadSatVisAlert();
+    return (vis_alert != 0);
+}
+
+/* set whether rise alarms are only for visible passes
+ */
+void setSatVisAlert (bool on)
+{
+    loadSatVisAlert();
+    vis_alert = on;
+    NVWriteUInt8 (NV_SATVISALERT, vis_alert);
+}
+
+/* draw a small star marker centered at x,y
+ */
+static void drawVisStar (uint16_t x, uint16_t y)
+{
+    uint16_t r = VIS_STAR_R, h = VIS_STAR_R/2;
+    tft.fillTriangle (x-r, y-h, x+r, y-h, x, y+r, VIS_COLOR);
+    tft.fillTriangle (x-r, y+h, x+r, y+h, x, y-r, VIS_COLOR);
+}
+
 /* display next pass on sky dome.
  * N.B. we assume findNextPass has been called to fill sat_rs
  */
@@ -499,6 +605,60 @@ static void drawNextPass()
     printFreeHeap (F("drawNextPass"));
 }
 
+/* fill az[] and el[], degrees, with up to max_pts points evenly spaced along the current or next pass of the
+ * current sat, from rise, or from now if the pass is underway, until set. pass back the number of points
+ * used and whether they start at rise.
+ * return false if no sat or it never rises.
+ */
+bool getSatPassTrack (float az[], float el[], int max_pts, int &n_pts, bool &full_pass)
+{
+    n_pts = 0;
+    full_pass = false;
+    if (!sat || !obs || !sat_rs.ever_up || !sat_rs.rise_ok || !sat_rs.set_ok || max_pts < 2)
+        return (false);
+
+    // start at rise, or now if already up
+    DateTime t_now = userDateTime(nowWO());
+    DateTime t = sat_rs.rise_time;
+    float pass_duration = sat_rs.set_time - sat_rs.rise_time;
+    if (pass_duration < 0) {
+        pass_duration = sat_rs.set_time - t_now;
+        t = t_now;
+    } else
+        full_pass = true;
+
+    // one step per PASS_STEP seconds but no more than max_pts, always including both ends
+    int n_steps = pass_duration/(PASS_STEP/SECSPERDAY) + 1;
+    if (n_steps > max_pts - 1)
+        n_steps = max_pts - 1;
+    float step_dt = pass_duration/n_steps;
+
+    for (int i = 0; i <= n_steps; i++) {
+        resetWatchdog();
+        float range, rate;
+        sat->predict (t);
+        sat->topo (obs, el[i], az[i], range, rate);
+        t += step_dt;
+    }
+    n_pts = n_steps + 1;
+
+    return (true);
+}
+
+/* fill user_name with the name of the current satellite without '_', followed by its preferred mode if any.
+ * N.B. the mode is only shown as a reminder, it does not change any frequency or tracking.
+ */
+static void getSatUserName (char user_name[SAT_USERNAME_LEN])
+{
+    strncpySubChar (user_name, sat_name, ' ', '_', NV_SATNAME_LEN);
+    SatConfig sc;
+    getSatConfig (sat_name, sc);
+    if (sc.mode != SATMODE_ANY) {
+        size_t ul = strlen (user_name);
+        snprintf (user_name+ul, SAT_USERNAME_LEN-ul, " %s", getSatModeName (sc.mode));
+    }
+}
+
 /* draw name of current satellite if used in dx_info box
  */
 static void drawSatName()
@@ -508,9 +668,8 @@ static void drawSatName()
 
     resetWatchdog();
 
-    // retrieve saved name without '_'
-    char user_name[NV_SATNAME_LEN];
-    strncpySubChar (user_name, sat_name, ' ', '_', NV_SATNAME_LEN);
+    char user_name[SAT_USERNAME_LEN];
+    getSatUserName (user_name);
 
     // erase
     tft.fillRect (dx_info_b.x, dx_info_b.y+1, dx_info_b.w, dx_info_b.h-1, RA8875_BLACK);
@@ -530,9 +689,8 @@ static void drawSatName()
  */
 static void setSatMapNameLoc()
 {
-    // retrieve saved name without '_'
-    char user_name[NV_SATNAME_LEN];
-    strncpySubChar (user_name, sat_name, ' ', '_', NV_SATNAME_LEN);
+    char user_name[SAT_USERNAME_LEN];
+    getSatUserName (user_name);
 
     // get size
     selectFontStyle (LIGHT_FONT, SMALL_FONT);
@@ -727,6 +885,40 @@ static void showSelectionBox (uint16_t x, uint16_t y, bool on)
 }
 
 
+/* return the name of the data bundle holding the last TLE fetched for sat_name
+ */
+static std::string satBundleName()
+{
+    return (std::string("tle-") + sat_name);
+}
+
+/* set up sat from the last TLE we saved for sat_name, for use when the backend can not be reached.
+ * return whether found a good one.
+ * N.B. the usual epoch checks still decide whether it is too old to use.
+ */
+static bool satLookupBundle()
+{
+    time_t fetched;
+    char *bundle = loadDataBundle (satBundleName().c_str(), fetched);
+    if (!bundle)
+        return (false);
+
+    // lines are name, TLE 1, TLE 2
+    char *name = strtok (bundle, "\n");
+    char *t1 = strtok (NULL, "\n");
+    char *t2 = strtok (NULL, "\n");
+    bool ok = name && t1 && t2 && strcasecmp (name, sat_name) == 0
+                        && tleHasValidChecksum (t1) && tleHasValidChecksum (t2);
+    if (ok) {
+        sat = new Satellite (t1, t2);
+        tle_refresh = nowWO();
+        Serial.printf (_FX("Using %s TLE saved %ld hours ago\n"), sat_name, (long)(time(NULL)-fetched)/3600);
+    }
+
+    free (bundle);
+    return (ok);
+}
+
 /* look up sat_name. if found set up sat, else inform user and remove sat altogether.
  * return whether found it.
  */
@@ -798,6 +990,15 @@ static bool satLookup ()
         tle_refresh = nowWO();
         ok = true;
 
+        // keep a copy in case we can not get it next time
+        char bundle[3*TLE_LINEL+NV_SATNAME_LEN];
+        int bl = snprintf (bundle, sizeof(bundle), "%s\n%s\n%s\n", sat_name, t1.getMem(), t2.getMem());
+        saveDataBundle (satBundleName().c_str(), bundle, bl);
+
+    } else if (satLookupBundle()) {
+
+        ok = true;
+
     } else {
 
         fatalSatError (_FX("network error"));
@@ -812,7 +1013,53 @@ out:
     return (ok);
 }
 
-/* show all names and allow op to choose one or none.
+/* draw the rise time text and name of one sat in the picker at cell x,y, gray if disabled.
+ */
+static void drawPickerCell (uint16_t x, uint16_t y, const char *rise, uint16_t rise_color, const char *name,
+bool enabled)
+{
+    tft.fillRect (x + CB_SIZE + 1, y + 1, CELL_W - CB_SIZE - 2, CELL_H - 2, RA8875_BLACK);
+    tft.setCursor (x + CB_SIZE + 8, y + FONT_H);
+    tft.setTextColor (enabled ? rise_color : GRAY);
+    tft.print (rise);
+
+    char user_name[NV_SATNAME_LEN];
+    strncpySubChar (user_name, name, ' ', '_', NV_SATNAME_LEN);
+    tft.setTextColor (enabled ? RA8875_WHITE : GRAY);
+    tft.print (user_name);
+}
+
+/* draw the picker settings strip for sc, or a hint if sc is NULL
+ */
+static void drawPickerSettings (const SatConfig *sc)
+{
+    tft.fillRect (0, SETTINGS_Y, tft.width(), SETTINGS_H, RA8875_BLACK);
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (RA8875_WHITE);
+    tft.setCursor (5, SETTINGS_Y + SETTINGS_H - 12);
+
+    if (!sc) {
+        tft.print (F("Tap a name to change its settings"));
+        return;
+    }
+
+    char user_name[NV_SATNAME_LEN];
+    strncpySubChar (user_name, sc->name, ' ', '_', NV_SATNAME_LEN);
+    tft.print (user_name);
+
+    char buf[30];
+    drawStringInBox (sc->enabled ? "Enabled" : "Disabled", sce_b, false, sc->enabled ? SOON_COLOR : GRAY);
+    snprintf (buf, sizeof(buf), "Priority %d", sc->priority);
+    drawStringInBox (buf, scp_b, false, RA8875_WHITE);
+    snprintf (buf, sizeof(buf), "Min El %d", sc->min_el);
+    drawStringInBox (buf, scel_b, false, RA8875_WHITE);
+    snprintf (buf, sizeof(buf), "Mode %s", getSatModeName (sc->mode));
+    drawStringInBox (buf, scm_b, false, RA8875_WHITE);
+}
+
+/* show all names and allow op to choose one or none, highest priority first.
+ * tapping a check box selects that sat, tapping its name shows its settings below the list where each
+ * can be changed by tapping.
  * save selection in sat_name, even if empty for no selection.
  * return whether sat was selected.
  */
@@ -859,14 +1106,27 @@ static bool askSat()
     drawStringInBox ("Ok", ok_b, false, RA8875_WHITE);
 
     /// setup
-    StackMalloc t1(TLE_LINEL);
-    StackMalloc t2(TLE_LINEL);
     typedef char SatNames[MAX_NSAT][NV_SATNAME_LEN];
+    typedef char SatTLEs[MAX_NSAT][2][TLE_LINEL];
+    typedef struct {
+        char text[12];                          // rise time or state
+        uint16_t color;                         // rise text color
+    } SatRise;
     StackMalloc name_mem(sizeof(SatNames));
+    StackMalloc tle_mem(sizeof(SatTLEs));
+    StackMalloc rise_mem(MAX_NSAT*sizeof(SatRise));
+    StackMalloc order_mem(MAX_NSAT*sizeof(uint8_t));
+    StackMalloc cfg_mem(MAX_NSAT*sizeof(SatConfig));
     SatNames *sat_names = (SatNames *) name_mem.getMem();
+    SatTLEs *sat_tles = (SatTLEs *) tle_mem.getMem();
+    SatRise *sat_rise = (SatRise *) rise_mem.getMem();
+    uint8_t *order = (uint8_t *) order_mem.getMem();            // sat index in each cell
+    SatConfig *sat_cfg = (SatConfig *) cfg_mem.getMem();
     uint16_t prev_sel_x = 0, prev_sel_y = 0;
     int8_t sel_idx = NO_SAT;
+    int8_t cfg_idx = NO_SAT;
     uint8_t n_sat = 0;
+    uint8_t n_shown = 0;
 
     // open connection
     WiFiClient sat_client;
@@ -880,20 +1140,33 @@ static bool askSat()
     if (!httpSkipHeader (sat_client))
         goto out;
 
-    // read and display each sat, allow tapping part way through to stop
-    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    // read each sat and its settings, inserting into order by decreasing priority
     for (n_sat = 0; n_sat < MAX_NSAT; n_sat++) {
-
-        // read name and 2 lines, done when eof or tap
         if (!getTCPLine (sat_client, &(*sat_names)[n_sat][0], NV_SATNAME_LEN, NULL)
-                         || !getTCPLine (sat_client, t1.getMem(), TLE_LINEL, NULL)
-                         || !getTCPLine (sat_client, t2.getMem(), TLE_LINEL, NULL)) {
+                         || !getTCPLine (sat_client, (*sat_tles)[n_sat][0], TLE_LINEL, NULL)
+                         || !getTCPLine (sat_client, (*sat_tles)[n_sat][1], TLE_LINEL, NULL)) {
             break;
         }
+        getSatConfig ((*sat_names)[n_sat], sat_cfg[n_sat]);
+        int k = n_sat;
+        while (k > 0 && sat_cfg[order[k-1]].priority < sat_cfg[n_sat].priority) {
+            order[k] = order[k-1];
+            k--;
+        }
+        order[k] = n_sat;
+    }
+
+    // close connection
+    sat_client.stop();
+
+    // display each sat, allow tapping part way through to stop
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    for (n_shown = 0; n_shown < n_sat; n_shown++) {
 
         // find row and column, col-major order
-        uint8_t r = n_sat % N_ROWS;
-        uint8_t c = n_sat / N_ROWS;
+        uint8_t r = n_shown % N_ROWS;
+        uint8_t c = n_shown / N_ROWS;
+        uint8_t idx = order[n_shown];
 
         // ul corner
         uint16_t x = c*CELL_W;
@@ -909,8 +1182,8 @@ static bool askSat()
         }
 
         // show tick box, pre-select if saved before
-        if (strcmp (sat_name, (*sat_names)[n_sat]) == 0) {
-            sel_idx = n_sat;
+        if (strcmp (sat_name, (*sat_names)[idx]) == 0 && sat_cfg[idx].enabled) {
+            sel_idx = idx;
             showSelectionBox (x, y, true);
             prev_sel_x = x;
             prev_sel_y = y;
@@ -918,58 +1191,50 @@ static bool askSat()
             showSelectionBox (x, y, false);
         }
 
-        // display next rise time of this sat
+        // find next rise time of this sat
         if (sat)
             delete sat;
-        sat = new Satellite (t1.getMem(), t2.getMem());
+        sat = new Satellite ((*sat_tles)[idx][0], (*sat_tles)[idx][1]);
         SatRiseSet rs;
-        findNextPass((*sat_names)[n_sat], nowWO(), rs);
-        tft.setTextColor (RA8875_WHITE);
-        tft.setCursor (x + CB_SIZE + 8, y + FONT_H);
+        findNextPass((*sat_names)[idx], nowWO(), rs);
+        SatRise &sr = sat_rise[idx];
+        sr.text[0] = '\0';
+        sr.color = RA8875_WHITE;
         if (rs.rise_ok) {
             DateTime t_now = userDateTime(nowWO());
             if (rs.rise_time < rs.set_time) {
                 // pass lies ahead
                 float hrs_to_rise = (rs.rise_time - t_now)*24.0;
                 if (hrs_to_rise*60 < SOON_MINS)
-                    tft.setTextColor (SOON_COLOR);
+                    sr.color = SOON_COLOR;
                 uint8_t mins_to_rise = (hrs_to_rise - (uint16_t)hrs_to_rise)*60;
                 if (hrs_to_rise < 1 && mins_to_rise < 1)
                     mins_to_rise = 1;   // 00:00 looks wrong
-                if (hrs_to_rise < 10)
-                    tft.print ('0');
-                tft.print ((uint16_t)hrs_to_rise);
-                tft.print (':');
-                if (mins_to_rise < 10)
-                    tft.print ('0');
-                tft.print (mins_to_rise);
-                tft.print (' ');
+                snprintf (sr.text, sizeof(sr.text), "%02u:%02u ", (uint16_t)hrs_to_rise, mins_to_rise);
             } else {
                 // pass in progress
-                tft.setTextColor (SAT_COLOR);
-                tft.print (F("Up "));
+                sr.color = SAT_COLOR;
+                strcpy (sr.text, "Up ");
             }
         } else if (!rs.ever_up) {
-            tft.setTextColor (GRAY);
-            tft.print (F("NoR "));
+            sr.color = GRAY;
+            strcpy (sr.text, "NoR ");
         } else if (!rs.ever_down) {
-            tft.setTextColor (SAT_COLOR);
-            tft.print (F("NoS "));
+            sr.color = SAT_COLOR;
+            strcpy (sr.text, "NoS ");
         }
 
         // followed by scrubbed name
-        char user_name[NV_SATNAME_LEN];
-        strncpySubChar (user_name, (*sat_names)[n_sat], ' ', '_', NV_SATNAME_LEN);
-        tft.print (user_name);
+        drawPickerCell (x, y, sr.text, sr.color, (*sat_names)[idx], sat_cfg[idx].enabled);
     }
 
-    // close connection
-    sat_client.stop();
-
     // bale if no satellites displayed
     if (n_sat == 0)
         goto out;
 
+    // invite settings changes
+    drawPickerSettings (NULL);
+
     // make box for whole screen so we can use waitForTap()
     SBox screen_b;
     screen_b.x = 0;
@@ -978,7 +1243,6 @@ static bool askSat()
     screen_b.h = tft.height();
 
     // follow touches to make selection, done when tap Ok
-    selectFontStyle (BOLD_FONT, SMALL_FONT);
     SCoord s_tap;
     while (stop_tap || waitForTap (screen_b, screen_b, NULL, MENU_TO, s_tap)) {
 
@@ -991,36 +1255,76 @@ static bool askSat()
         // tap Ok button?
         if (inBox (s_tap, ok_b)) {
             // show Ok button toggle
+            selectFontStyle (BOLD_FONT, SMALL_FONT);
             drawStringInBox ("Ok", ok_b, true, RA8875_WHITE);
             goto out;
         }
 
-        // else toggle tapped sat, if any
+        // tap a setting of the sat being configured?
         resetWatchdog();
+        if (cfg_idx != NO_SAT && s_tap.y >= SETTINGS_Y) {
+            SatConfig &sc = sat_cfg[cfg_idx];
+            if (inBox (s_tap, sce_b))
+                sc.enabled = !sc.enabled;
+            else if (inBox (s_tap, scp_b))
+                sc.priority = (sc.priority + 1) % (SATCFG_MAXPRI + 1);
+            else if (inBox (s_tap, scel_b))
+                sc.min_el = (sc.min_el + 5) % (SATCFG_MAXEL + 5);
+            else if (inBox (s_tap, scm_b))
+                sc.mode = (sc.mode + 1) % SATMODE_N;
+            else
+                continue;
+            setSatConfig (sc);
+            drawPickerSettings (&sc);
+
+            // redraw its cell and drop selection if now disabled
+            for (uint8_t k = 0; k < n_shown; k++) {
+                if (order[k] == cfg_idx) {
+                    uint16_t x = (k / N_ROWS) * CELL_W;
+                    uint16_t y = TBORDER + (k % N_ROWS) * CELL_H;
+                    drawPickerCell (x, y, sat_rise[cfg_idx].text, sat_rise[cfg_idx].color, sc.name, sc.enabled);
+                    if (!sc.enabled && sel_idx == cfg_idx) {
+                        showSelectionBox (x, y, false);
+                        sel_idx = NO_SAT;
+                        prev_sel_y = 0;
+                    }
+                }
+            }
+            continue;
+        }
+
+        // else find tapped sat, if any
+        if (s_tap.y < TBORDER || s_tap.y >= TBORDER + N_ROWS*CELL_H)
+            continue;
         uint8_t r = (s_tap.y - TBORDER)/CELL_H;
         uint8_t c = s_tap.x/CELL_W;
-        if (s_tap.y < TBORDER || s_tap.x - c*CELL_W > CELL_W/4)
-            continue;                           // require tapping in left quarter of cell
-        uint8_t tap_idx = c*N_ROWS + r;         // column major order
-        if (tap_idx < n_sat) {
-            // toggle
-            uint16_t x = c * CELL_W;
-            uint16_t y = TBORDER + r * CELL_H;
-            if (tap_idx == sel_idx) {
-                // already on: forget and toggle off
-                // Serial.printf (_FX("forget %s\n"), (*sat_names)[sel_idx]);
-                showSelectionBox (x, y, false);
-                sel_idx = NO_SAT;
-            } else {
-                // toggle previous selection off (if any) and show selected
-                if (prev_sel_y > 0)
-                    showSelectionBox (prev_sel_x, prev_sel_y, false);
-                sel_idx = tap_idx;
-                prev_sel_x = x;
-                prev_sel_y = y;
-                // Serial.printf (_FX("select %s\n"), (*sat_names)[sel_idx]);
-                showSelectionBox (x, y, true);
-            }
+        uint8_t tap_k = c*N_ROWS + r;           // column major order
+        if (tap_k >= n_shown)
+            continue;
+        uint8_t tap_idx = order[tap_k];
+        uint16_t x = c * CELL_W;
+        uint16_t y = TBORDER + r * CELL_H;
+
+        // tapping right of the check box shows its settings
+        if (s_tap.x - x > CELL_W/4) {
+            cfg_idx = tap_idx;
+            drawPickerSettings (&sat_cfg[cfg_idx]);
+            continue;
+        }
+
+        // toggle, but only enabled sats may be selected
+        if (tap_idx == sel_idx) {
+            // already on: forget and toggle off
+            showSelectionBox (x, y, false);
+            sel_idx = NO_SAT;
+        } else if (sat_cfg[tap_idx].enabled) {
+            // toggle previous selection off (if any) and show selected
+            if (prev_sel_y > 0)
+                showSelectionBox (prev_sel_x, prev_sel_y, false);
+            sel_idx = tap_idx;
+            prev_sel_x = x;
+            prev_sel_y = y;
+            showSelectionBox (x, y, true);
         }
     }
 
@@ -1108,6 +1412,22 @@ float *razp, float *sazp, float *rdtp, float *sdtp)
 }
 
 
+/* find the current sat topocentric az and el, degrees, range, km, and range rate, m/s, at UTC t.
+ * N.B. works on a copy so the position of sat for now is not disturbed.
+ * return false if no sat.
+ */
+bool getSatTopoAt (time_t t, float &az, float &el, float &range, float &rate)
+{
+    if (!obs || !sat || !SAT_NAME_IS_SET())
+        return (false);
+
+    Satellite sat_at = *sat;
+    sat_at.predict (userDateTime(t));
+    sat_at.topo (obs, el, az, range, rate);
+    return (true);
+}
+
+
 /* called by main loop() to update pass info.
  * once per second is enough, not needed at all if no sat named or !dx_info_for_sat
  * the _path_ is updated much less often in updateSatPath().
@@ -1162,10 +1482,12 @@ void updateSatPass()
 
     if (sat_rs.rise_time < sat_rs.set_time) {
         if (t_now < sat_rs.rise_time) {
-            // pass lies ahead
-            drawSatTime ("Rise in ", days_to_rise);
-            // flash at 1 Hz when about to rise
-            risetAlarm(days_to_rise < RISE_ALARM_DT && !((last_run/1000) & 1));
+            // pass lies ahead, star marks one that can be seen
+            drawSatTime (sat_rs_visible ? "*Rise in " : "Rise in ", days_to_rise);
+            // flash at 1 Hz when about to rise, perhaps only if visible
+            loadSatVisAlert();
+            bool alert_ok = sat_rs_visible || !vis_alert;
+            risetAlarm(alert_ok && days_to_rise < RISE_ALARM_DT && !((last_run/1000) & 1));
         } else if (t_now < sat_rs.set_time) {
             // pass in progress
             drawSatTime (" Set in ", days_to_set);
@@ -1209,7 +1531,7 @@ void updateSatPath()
         if (!satLookup())
             return;
         // init pass info for updateSatPass()
-        findNextPass(sat_name, nowWO(), sat_rs);
+        findCurrentPass();
     }
 
     // confirm epoch is still valid
@@ -1224,7 +1546,7 @@ void updateSatPath()
             return;
         }
         // init pass info for updateSatPass()
-        findNextPass(sat_name, nowWO(), sat_rs);
+        findCurrentPass();
     }
 
     // from here we have a valid sat to report
@@ -1365,9 +1687,8 @@ void drawSatNameOnRow(uint16_t y0)
 
     resetWatchdog();
 
-    // retrieve saved name without '_'
-    char user_name[NV_SATNAME_LEN];
-    strncpySubChar (user_name, sat_name, ' ', '_', NV_SATNAME_LEN);
+    char user_name[SAT_USERNAME_LEN];
+    getSatUserName (user_name);
 
     selectFontStyle (LIGHT_FONT, SMALL_FONT);
     tft.setTextColor (getSatFootColor());
@@ -1426,11 +1747,27 @@ void displaySatInfo()
             return;
     }
 
-    findNextPass(sat_name, nowWO(), sat_rs);
+    findCurrentPass();
     drawSatName();
     drawNextPass();
 }
 
+/* the settings of the given sat have changed: drop it if it is the current sat and is now disabled, else
+ * find its pass again in case its min elevation changed.
+ */
+void satConfigChanged (const char *name)
+{
+    if (!SAT_NAME_IS_SET() || strcasecmp (name, sat_name) != 0)
+        return;
+
+    if (!satIsEnabled (sat_name)) {
+        Serial.printf (_FX("%s is now disabled\n"), sat_name);
+        unsetSat();
+        initScreen();
+    } else
+        displaySatInfo();
+}
+
 /* retrieve list of satellites and let user select up to one, preselecting last known if any.
  * save name in sat_name and NVRAM, even if empty to signify no satellite.
  * return whether a sat was chosen or not.
@@ -1447,7 +1784,7 @@ bool querySatSelection()
         Serial.printf (_FX("Selected sat '%s'\n"), sat_name);
         if (!satLookup())
             return (false);
-        findNextPass(sat_name, nowWO(), sat_rs);
+        findCurrentPass();
     } else {
         delete sat;
         sat = NULL;
@@ -1473,11 +1810,13 @@ bool setSatFromName (const char *new_name)
         return (true);
     }
 
-    // build internal name, done if already engaged
+    // build internal name, done if already engaged, refuse if disabled
     char tmp_name[NV_SATNAME_LEN];
     strncpySubChar (tmp_name, new_name, '_', ' ', NV_SATNAME_LEN);
     if (strcmp (tmp_name, sat_name) == 0)
         return (true);
+    if (!satIsEnabled (tmp_name))
+        return (false);
     strcpy (sat_name, tmp_name);
 
     // lookup
@@ -1529,6 +1868,8 @@ bool setSatFromTLE (const char *name, const char *t1, const char *t2)
 bool initSatSelection()
 {
     NVReadString (NV_SATNAME, sat_name);
+    if (SAT_NAME_IS_SET() && !satIsEnabled (sat_name))
+        sat_name[0] = '\0';
     return (SAT_NAME_IS_SET());
 }
 
@@ -1659,7 +2000,7 @@ int nextSatRSEvents (time_t **rises, time_t **sets)
     return (n_table);
 }
 
-/* display table of several local DE rise/set events for the current sat overlaid on the main map.
+/* display table of several DE rise/set events for the current sat overlaid on the main map.
  * return after user has clicked ok and map has been primed for fresh redraw.
  */
 void showNextSatEvents ()
@@ -1683,6 +2024,11 @@ void showNextSatEvents ()
     tft.setCursor (x, y);
     tft.setTextColor (RA8875_GREEN);
     tft.print (F("Day     Rise     Set       Up"));
+    tft.setCursor (x+_SNS_DAY_W+3*_SNS_HHMM_W, y);
+    tft.print (getEventTZName());
+    drawVisStar (x+_SNS_DAY_W+4*_SNS_HHMM_W, y-VIS_STAR_R);
+    tft.setCursor (x+_SNS_DAY_W+4*_SNS_HHMM_W+2*VIS_STAR_R, y);
+    tft.print (F("Visible"));
 
     // draw resume button box
     SBox resume_b;
@@ -1720,10 +2066,10 @@ void showNextSatEvents ()
             // font is variable width so we must space each column separately
             char buf[30];
 
-            // convert to DE local time
-            time_t rt = rises[i] + de_tz.tz_secs;
-            time_t st = sets[i] + de_tz.tz_secs;
-            int up = st - rt;       // nextSatRSEvents assures us this will be > 0
+            // convert to event time zone
+            time_t rt = eventTime (rises[i]);
+            time_t st = eventTime (sets[i]);
+            int up = sets[i] - rises[i];        // nextSatRSEvents assures us this will be > 0
 
             // detect crossing midnight by comparing weekday
             int rt_wd = weekday(rt);
@@ -1734,7 +2080,7 @@ void showNextSatEvents ()
             tft.setCursor (x, y);
             tft.print (buf);
 
-            snprintf (buf, sizeof(buf), "%02dh%02d", hour(rt), minute(rt));
+            formatEventHM (rt, buf, sizeof(buf));
             tft.setCursor (x+_SNS_DAY_W, y);
             tft.print (buf);
 
@@ -1757,7 +2103,7 @@ void showNextSatEvents ()
             }
 
             // show set time
-            snprintf (buf, sizeof(buf), "%02dh%02d", hour(st), minute(st));
+            formatEventHM (st, buf, sizeof(buf));
             tft.setCursor (x+_SNS_DAY_W+_SNS_HHMM_W, y);
             tft.print (buf);
 
@@ -1769,6 +2115,10 @@ void showNextSatEvents ()
             tft.setCursor (x+_SNS_DAY_W+2*_SNS_HHMM_W, y);
             tft.print (buf);
 
+            // mark passes that can be seen
+            if (isSatPassVisible (rises[i], sets[i]))
+                drawVisStar (tft.getCursorX()+2*VIS_STAR_R, y-VIS_STAR_R);
+
             // draw as each is computed
             tft.drawPR();
 
@@ -0,0 +1,174 @@
+/* upcoming solar eclipses, which are popular times for propagation experiments such as those organized by
+ * HamSCI. When the next one is within the chosen number of days its central path is drawn on the map
+ * with a countdown to greatest eclipse. Only on UNIX systems because ESP draws the map row by row.
+ *
+ * the paths are coarse centerline waypoints from west to east, good enough to show who is under the
+ * shadow, not to plan an observing site.
+ */
+
+#include "HamClock.h"
+
+
+#define DEF_ECLIPSEDAYS 7               // default days ahead to show the next eclipse
+#define MAX_ECLIPSEDAYS 60              // max days ahead
+#define PATH_SECS       (2*3600)        // shadow is on the earth about this long each side of greatest
+#define PATH_STEP       2.0F            // max path segment, degrees
+#define MAX_WAYPTS      8               // max waypoints per path
+#define PATH_COLOR      RGB565(255,160,0)
+#define LABEL_COLOR     RA8875_WHITE
+
+// one eclipse
+typedef struct {
+    const char *kind;                   // Total, Annular or Hybrid
+    time_t greatest;                    // UTC of greatest eclipse
+    uint8_t n_waypts;                   // n used in waypts[]
+    float waypts[MAX_WAYPTS][2];        // centerline lat, lng, degrees +N +E
+} SolarEclipse;
+
+// upcoming central solar eclipses, in time order
+static const SolarEclipse eclipses[] = {
+    { "Annular", 1801929600, 6,         // 2027 Feb 6 16:00Z
+        { {-45,-95}, {-43,-72}, {-38,-55}, {-31,-48}, {-15,-20}, {6,2} } },
+    { "Total",   1817201220, 8,         // 2027 Aug 2 10:07Z
+        { {38,-40}, {36,-6}, {35,10}, {30,22}, {25,33}, {21,39}, {13,48}, {-5,62} } },
+    { "Annular", 1832512080, 6,         // 2028 Jan 26 15:08Z
+        { {-3,-100}, {-2,-80}, {-2,-62}, {3,-51}, {20,-30}, {38,-8} } },
+    { "Total",   1847847360, 7,         // 2028 Jul 22 02:56Z
+        { {-5,95}, {-12,118}, {-16,127}, {-25,140}, {-34,151}, {-46,170}, {-50,-170} } },
+    { "Annular", 1906525740, 8,         // 2030 Jun 1 06:29Z
+        { {30,-10}, {36,10}, {40,25}, {44,40}, {52,60}, {57,80}, {57,120}, {43,143} } },
+    { "Total",   1921819860, 7,         // 2030 Nov 25 06:51Z
+        { {-20,5}, {-26,16}, {-30,28}, {-40,55}, {-44,71}, {-40,110}, {-32,136} } },
+};
+
+static uint8_t eclipse_days;            // NV_ECLIPSEDAYS cache, 0 to never show
+
+
+/* read eclipse_days from NV once
+ */
+static void loadEclipseDays()
+{
+    static bool loaded;
+    if (loaded)
+        return;
+    loaded = true;
+
+    if (!NVReadUInt8 (NV_ECLIPSEDAYS, &eclipse_days) || eclipse_days > MAX_ECLIPSEDAYS) {
+        eclipse_days = DEF_ECLIPSEDAYS;
+        NVWriteUInt8 (NV_ECLIPSEDAYS, eclipse_days);
+    }
+}
+
+/* set how many days ahead to show the next eclipse, 0 for never.
+ * return whether sensible.
+ */
+bool setEclipseDays (int days)
+{
+    if (days < 0 || days > MAX_ECLIPSEDAYS)
+        return (false);
+    loadEclipseDays();
+    eclipse_days = days;
+    NVWriteUInt8 (NV_ECLIPSEDAYS, eclipse_days);
+    return (true);
+}
+
+/* return how many days ahead to show the next eclipse, 0 for never
+ */
+int getEclipseDays()
+{
+    loadEclipseDays();
+    return (eclipse_days);
+}
+
+/* return the next eclipse whose shadow has not yet left the earth, else NULL.
+ * N.B. uses the user's time offset so the map agrees with the sun and moon.
+ */
+static const SolarEclipse *nextEclipse()
+{
+    time_t t0 = nowWO();
+    for (unsigned i = 0; i < NARRAY(eclipses); i++)
+        if (eclipses[i].greatest + PATH_SECS > t0)
+            return (&eclipses[i]);
+    return (NULL);
+}
+
+/* pass back the kind and UTC of greatest eclipse of the next solar eclipse.
+ * return false if we know of none to come.
+ */
+bool getNextEclipse (const char **kind, time_t &greatest)
+{
+    const SolarEclipse *ep = nextEclipse();
+    if (!ep)
+        return (false);
+    *kind = ep->kind;
+    greatest = ep->greatest;
+    return (true);
+}
+
+/* format the time from now until t into buf as days and hours, or hours and minutes within a day.
+ */
+void formatEclipseCountdown (time_t t, char *buf, size_t buf_len)
+{
+    long secs = (long)(t - nowWO());
+    if (secs <= 0)
+        snprintf (buf, buf_len, "now");
+    else if (secs >= SECSPERDAY)
+        snprintf (buf, buf_len, "%ldd %02ldh", secs/SECSPERDAY, (secs%SECSPERDAY)/3600);
+    else
+        snprintf (buf, buf_len, "%ldh %02ldm", secs/3600, (secs%3600)/60);
+}
+
+#if defined(_IS_UNIX)
+
+/* draw the path of the next eclipse on the map with a countdown label if it is within eclipse_days.
+ */
+void drawEclipsePath()
+{
+    loadEclipseDays();
+    const SolarEclipse *ep = nextEclipse();
+    if (!eclipse_days || !ep || ep->greatest > nowWO() + eclipse_days*SECSPERDAY)
+        return;
+
+    resetWatchdog();
+
+    // connect waypoints in short steps so the path bends properly on any projection
+    for (int i = 1; i < ep->n_waypts; i++) {
+        LatLong wp0, wp1;
+        wp0.lat_d = ep->waypts[i-1][0];
+        wp0.lng_d = ep->waypts[i-1][1];
+        normalizeLL (wp0);
+        wp1.lat_d = ep->waypts[i][0];
+        wp1.lng_d = ep->waypts[i][1];
+        normalizeLL (wp1);
+        float dlng = fabsf (wp1.lng_d - wp0.lng_d);
+        if (dlng > 180)
+            dlng = 360 - dlng;
+        int n_steps = ceilf (fmaxf (fabsf(wp1.lat_d - wp0.lat_d), dlng)/PATH_STEP);
+        LatLong ll0 = wp0, ll1;
+        for (int j = 1; j <= n_steps; j++) {
+            geoLerpLL (wp0, wp1, (float)j/n_steps, ll1);
+            drawGeoSegment (ll0, ll1, 3, PATH_COLOR);
+            ll0 = ll1;
+        }
+    }
+
+    // countdown near the middle of the path
+    const float *mid = ep->waypts[ep->n_waypts/2];
+    SCoord s;
+    ll2s (deg2rad(mid[0]), deg2rad(mid[1]), s, 1);
+    char cd[20], label[50];
+    formatEclipseCountdown (ep->greatest, cd, sizeof(cd));
+    snprintf (label, sizeof(label), xlate ("%s eclipse %s"), xlate (ep->kind), cd);
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    uint16_t lw = getTextWidth (label);
+    uint16_t x = s.x + 6;
+    if (x + lw > map_b.x + map_b.w - 2)
+        x = s.x > lw + 6 ? s.x - lw - 6 : map_b.x + 2;
+    uint16_t y = s.y > map_b.y + 12 ? s.y - 10 : s.y + 4;
+    tft.fillRect (x-1, y-1, lw+2, 10, RA8875_BLACK);
+    tft.setTextColor (LABEL_COLOR);
+    tft.setCursor (x, y);
+    tft.print (label);
+}
+
+#endif // _IS_UNIX
@@ -0,0 +1,182 @@
+/* plan EME contacts between DE and DX: show when the moon is up at each end over the next 48 hours
+ * and when it is up at both, colored by the expected degradation from moon distance and sky noise.
+ *
+ * degradation is relative to the best case of the moon at perigee against a cold sky. sky noise is a
+ * rough model for 2 m of a cold sky plus a bright region around the galactic center.
+ */
+
+#include "HamClock.h"
+
+
+// layout
+#define TITLE_COLOR     RA8875_GREEN
+#define LABEL_COLOR     GRAY
+#define UP_COLOR        RA8875_CYAN
+#define TITLE_Y0        27              // title dy, match VOACAP title position
+#define BAR_X           24              // bar x offset from box left
+#define BAR_H           8               // DE and DX bar heights
+#define DE_Y0           36              // DE bar y down from box top
+#define DX_Y0           48              // DX bar y down from box top
+#define BOTH_Y0         60              // mutual bar y down from box top
+#define BOTH_H          12              // mutual bar height
+#define TICK_Y0         74              // hour ticks y down from box top
+#define TICK_H          3               // hour tick height
+#define TICKLBL_Y0      79              // hour tick labels y down from box top
+#define INFO_Y0         96              // first info line y down from box top
+#define INFO_DY         12              // info line spacing
+
+// planning
+#define EME_HOURS       48              // timeline duration, hours
+#define EME_MIN_EL      deg2rad(3)      // moon must be at least this high at both ends, rads
+#define EME_PERIGEE     356500.0F       // closest moon distance, km
+#define EME_TSYS        100.0F          // receiving system noise temperature, K
+#define EME_TCOLD       200.0F          // cold sky temperature at 2 m, K
+#define EME_TGAL        2800.0F         // additional sky temperature at the galactic center, K
+#define EME_GALR        deg2rad(30)     // galactic hot spot radius, rads
+#define EME_GC_RA       deg2rad(266.4F) // galactic center RA, rads
+#define EME_GC_DEC      deg2rad(-28.9F) // galactic center Dec, rads
+#define EME_GOOD_DB     1.5F            // degradation below this is good, dB
+#define EME_FAIR_DB     3.0F            // degradation below this is fair, dB
+
+
+/* return the EME degradation, dB, for the given lunar circumstances.
+ */
+static float emeDegradation (const AstroCir &cir)
+{
+    // path loss varies as the 4th power of distance
+    float dist_db = 40*log10f(cir.dist/EME_PERIGEE);
+
+    // sky noise behind the moon from angular separation to the galactic center
+    float csep = sinf(cir.dec)*sinf(EME_GC_DEC) + cosf(cir.dec)*cosf(EME_GC_DEC)*cosf(cir.ra-EME_GC_RA);
+    float sep = acosf (fmaxf (-1, fminf (1, csep)));
+    float tsky = EME_TCOLD + EME_TGAL*expf(-(sep*sep)/(EME_GALR*EME_GALR));
+    float sky_db = 10*log10f((tsky + EME_TSYS)/(EME_TCOLD + EME_TSYS));
+
+    return (dist_db + sky_db);
+}
+
+/* return color to show the given degradation
+ */
+static uint16_t emeColor (float db)
+{
+    if (db < EME_GOOD_DB)
+        return (RA8875_GREEN);
+    if (db < EME_FAIR_DB)
+        return (RA8875_YELLOW);
+    return (RA8875_RED);
+}
+
+/* draw a bar label
+ */
+static void drawEMELabel (const SBox &box, uint16_t y, const char *label)
+{
+    tft.setTextColor (LABEL_COLOR);
+    tft.setCursor (box.x + 3, box.y + y);
+    tft.print (label);
+}
+
+/* draw the EME plan for the next EME_HOURS in the given box.
+ * nothing to fetch so always return true.
+ */
+bool updateEME (const SBox &box)
+{
+    prepPlotBox (box);
+
+    // title
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (TITLE_COLOR);
+    const char *title = xlate ("EME Planner");
+    tft.setCursor (box.x + (box.w - getTextWidth(title))/2, box.y + TITLE_Y0);
+    tft.print (title);
+
+    // labels
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    drawEMELabel (box, DE_Y0, "DE");
+    drawEMELabel (box, DX_Y0, "DX");
+    drawEMELabel (box, BOTH_Y0+2, "Both");
+
+    // scan one step per pixel column, noting the first mutual window
+    const int bar_w = box.w - BAR_X - 4;
+    const time_t t0 = nowWO();
+    const float step = EME_HOURS*3600.0F/bar_w;
+    time_t win_start = 0, win_end = 0;
+    float win_db = 0;
+    int win_n = 0;
+    bool win_done = false;
+    for (int i = 0; i < bar_w; i++) {
+        time_t t = t0 + i*step;
+        uint16_t x = box.x + BAR_X + i;
+
+        AstroCir de_cir, dx_cir;
+        getLunarCir (t, de_ll, de_cir);
+        getLunarCir (t, dx_ll, dx_cir);
+        bool de_up = de_cir.el >= EME_MIN_EL;
+        bool dx_up = dx_cir.el >= EME_MIN_EL;
+
+        if (de_up)
+            tft.drawLine (x, box.y + DE_Y0, x, box.y + DE_Y0 + BAR_H - 1, UP_COLOR);
+        if (dx_up)
+            tft.drawLine (x, box.y + DX_Y0, x, box.y + DX_Y0 + BAR_H - 1, UP_COLOR);
+
+        if (de_up && dx_up) {
+            float db = emeDegradation (de_cir);
+            tft.drawLine (x, box.y + BOTH_Y0, x, box.y + BOTH_Y0 + BOTH_H - 1, emeColor(db));
+            if (!win_done) {
+                if (win_n == 0)
+                    win_start = t;
+                win_end = t;
+                win_db += db;
+                win_n++;
+            }
+        } else if (win_n > 0)
+            win_done = true;
+    }
+
+    // hour ticks, labeled every 12
+    tft.setTextColor (LABEL_COLOR);
+    for (int h = 0; h <= EME_HOURS; h += 6) {
+        uint16_t x = box.x + BAR_X + h*(bar_w-1)/EME_HOURS;
+        tft.drawLine (x, box.y + TICK_Y0, x, box.y + TICK_Y0 + TICK_H, LABEL_COLOR);
+        if (h % 12 == 0) {
+            char buf[10];
+            snprintf (buf, sizeof(buf), "%d", h);
+            uint16_t lw = getTextWidth (buf);
+            tft.setCursor (x - (h == EME_HOURS ? lw : (h == 0 ? 0 : lw/2)), box.y + TICKLBL_Y0);
+            tft.print (buf);
+        }
+    }
+
+    // first mutual window
+    char buf[40];
+    tft.setTextColor (RA8875_WHITE);
+    tft.setCursor (box.x + 4, box.y + INFO_Y0);
+    if (win_n == 0) {
+        tft.print (F("No mutual window"));
+    } else {
+        int dur = win_end - win_start + step;
+        if (win_start <= t0)
+            snprintf (buf, sizeof(buf), "Open now for %dh%02d", dur/3600, (dur%3600)/60);
+        else {
+            int wait = win_start - t0;
+            snprintf (buf, sizeof(buf), "In %dh%02d for %dh%02d", wait/3600, (wait%3600)/60,
+                                                        dur/3600, (dur%3600)/60);
+        }
+        tft.print (buf);
+
+        float db = win_db/win_n;
+        tft.setTextColor (emeColor(db));
+        tft.setCursor (box.x + 4, box.y + INFO_Y0 + INFO_DY);
+        snprintf (buf, sizeof(buf), "Degradation %.1f dB", db);
+        tft.print (buf);
+    }
+
+    // current declination and distance
+    AstroCir cir;
+    getLunarCir (t0, de_ll, cir);
+    tft.setTextColor (LABEL_COLOR);
+    tft.setCursor (box.x + 4, box.y + INFO_Y0 + 2*INFO_DY);
+    snprintf (buf, sizeof(buf), "Dec %+.0f  Dist %.0fk km", rad2deg(cir.dec), cir.dist/1000);
+    tft.print (buf);
+
+    return (true);
+}
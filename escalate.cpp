@@ -0,0 +1,309 @@
+/* escalate critical alerts that are not acknowledged. each critical alert kind may have a policy of an
+ * interval in minutes and a chain of sinks: each time another interval passes without an ack the alert is
+ * sent again to the next sink in the chain, until the chain runs out or the alert is acknowledged.
+ *
+ * sinks are:
+ *   ws         web socket clients again, where browsers may raise a desktop notification
+ *   wake       turn the display back on if it is off
+ *   script     run the on_escalate script with kind, step and details, eg to publish to MQTT or send email
+ *
+ * policies are saved in ESC_FN in our_dir, one per line: kind mins sink,sink,...
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#define ESC_FN          "escalate.txt"          // policy file in our_dir
+#define ESC_MAXCHAIN    4                       // max sinks in one chain
+#define ESC_MAXMINS     (24*60)                 // max interval, mins
+#define ESC_DETAILS     40                      // max details length
+
+typedef enum {
+    ES_WS,
+    ES_WAKE,
+    ES_SCRIPT,
+    ES_N
+} EscSink;
+
+// sink names, N.B. index is EscSink
+static const char *sink_names[ES_N] = { "ws", "wake", "script" };
+
+// one policy and the escalation in progress, if any
+typedef struct {
+    const char *kind;                           // critical alert kind
+    int mins;                                   // interval between steps, 0 if no policy
+    uint8_t chain[ESC_MAXCHAIN];                // EscSink for each step
+    int n_chain;                                // n used in chain[]
+    time_t t0;                                  // time of unacknowledged alert, 0 if none
+    int step;                                   // next chain[] to notify
+    char details[ESC_DETAILS];                  // from the original alert
+} EscPolicy;
+
+// one for each kind of alert with ALSEV_CRITICAL, see alertSeverity()
+static EscPolicy policies[] = {
+    { "alarm" },
+    { "swr" },
+};
+#define N_POLICIES NARRAY(policies)
+
+static bool policies_loaded;
+
+
+/* return the policy for the given kind, else NULL
+ */
+static EscPolicy *findPolicy (const char *kind)
+{
+    for (unsigned i = 0; i < N_POLICIES; i++)
+        if (strcmp (kind, policies[i].kind) == 0)
+            return (&policies[i]);
+    return (NULL);
+}
+
+/* crack a comma separated list of sink names into ep's chain.
+ * if trouble put reason in ynot and return false.
+ */
+static bool crackChain (const char *chain_str, EscPolicy &ep, char *ynot, size_t ynot_len)
+{
+    char copy[100];
+    snprintf (copy, sizeof(copy), "%s", chain_str);
+
+    ep.n_chain = 0;
+    char *save;
+    for (char *name = strtok_r (copy, ",", &save); name != NULL; name = strtok_r (NULL, ",", &save)) {
+        if (ep.n_chain == ESC_MAXCHAIN) {
+            snprintf (ynot, ynot_len, "max %d sinks", ESC_MAXCHAIN);
+            return (false);
+        }
+        int s;
+        for (s = 0; s < ES_N; s++)
+            if (strcmp (name, sink_names[s]) == 0)
+                break;
+        if (s == ES_N) {
+            snprintf (ynot, ynot_len, "unknown sink %s", name);
+            return (false);
+        }
+        ep.chain[ep.n_chain++] = s;
+    }
+
+    if (ep.n_chain == 0) {
+        snprintf (ynot, ynot_len, "empty chain");
+        return (false);
+    }
+    return (true);
+}
+
+/* format ep's chain as a comma separated list of sink names
+ */
+static void formatChain (const EscPolicy &ep, char *chain_str, size_t chain_len)
+{
+    size_t l = 0;
+    chain_str[0] = '\0';
+    for (int i = 0; i < ep.n_chain && l < chain_len; i++)
+        l += snprintf (chain_str+l, chain_len-l, "%s%s", i > 0 ? "," : "", sink_names[ep.chain[i]]);
+}
+
+/* read ESC_FN once
+ */
+static void loadPolicies()
+{
+    if (policies_loaded)
+        return;
+    policies_loaded = true;
+
+    std::string fn = our_dir + ESC_FN;
+    FILE *fp = fopen (fn.c_str(), "r");
+    if (!fp)
+        return;
+
+    char line[150];
+    while (fgets (line, sizeof(line), fp)) {
+        char kind[20], chain[100];
+        int mins;
+        if (line[0] == '#' || sscanf (line, "%19s %d %99s", kind, &mins, chain) != 3)
+            continue;
+        EscPolicy *ep = findPolicy (kind);
+        char ynot[50];
+        if (!ep || mins <= 0 || mins > ESC_MAXMINS || !crackChain (chain, *ep, ynot, sizeof(ynot))) {
+            Serial.printf (_FX("Escalate: %s: ignoring %s"), ESC_FN, line);
+            if (ep)
+                ep->mins = 0;
+            continue;
+        }
+        ep->mins = mins;
+    }
+    fclose (fp);
+}
+
+/* write all policies to ESC_FN
+ */
+static void savePolicies()
+{
+    std::string fn = our_dir + ESC_FN;
+    FILE *fp = fopen (fn.c_str(), "w");
+    if (!fp) {
+        Serial.printf (_FX("Escalate: %s: %s\n"), fn.c_str(), strerror(errno));
+        return;
+    }
+
+    fprintf (fp, "# kind mins sink,sink,...\n");
+    for (unsigned i = 0; i < N_POLICIES; i++) {
+        EscPolicy &ep = policies[i];
+        if (ep.mins > 0) {
+            char chain[100];
+            formatChain (ep, chain, sizeof(chain));
+            fprintf (fp, "%s %d %s\n", ep.kind, ep.mins, chain);
+        }
+    }
+    fclose (fp);
+}
+
+/* send ep's pending alert to its next sink
+ */
+static void notifySink (EscPolicy &ep)
+{
+    int step = ep.step + 1;
+    char details[ESC_DETAILS+30];
+    snprintf (details, sizeof(details), "escalated %d: %s", step, ep.details);
+    Serial.printf (_FX("Escalate: %s %s to %s\n"), ep.kind, details, sink_names[ep.chain[ep.step]]);
+
+    switch ((EscSink)ep.chain[ep.step]) {
+    case ES_WS:
+        sendWebSocketAlert (ep.kind, details);
+        break;
+    case ES_WAKE:
+        (void) brightnessOn();
+        break;
+    case ES_SCRIPT: {
+        char step_str[12];
+        snprintf (step_str, sizeof(step_str), "%d", step);
+        runEventScript ("on_escalate", ep.kind, step_str, ep.details, NULL);
+        } break;
+    case ES_N:
+        break;
+    }
+}
+
+/* called when an alert of the given kind notifies to start escalating it if it has a policy.
+ * N.B. an alert that is already escalating keeps its place in the chain.
+ */
+void escalateAlert (const char *kind, const char *details)
+{
+    loadPolicies();
+
+    EscPolicy *ep = findPolicy (kind);
+    if (!ep || ep->mins == 0 || ep->t0 != 0)
+        return;
+
+    ep->t0 = now();
+    ep->step = 0;
+    snprintf (ep->details, sizeof(ep->details), "%s", details);
+}
+
+/* acknowledge the given kind of alert, or all if NULL, to stop its escalation
+ */
+void ackAlertEscalations (const char *kind)
+{
+    for (unsigned i = 0; i < N_POLICIES; i++) {
+        EscPolicy &ep = policies[i];
+        if (ep.t0 && (!kind || strcmp (kind, ep.kind) == 0)) {
+            Serial.printf (_FX("Escalate: %s acknowledged after %d steps\n"), ep.kind, ep.step);
+            ep.t0 = 0;
+        }
+    }
+}
+
+/* called often to move each unacknowledged alert along its chain
+ */
+void checkAlertEscalations()
+{
+    static uint32_t check_ms;
+    if (!timesUp (&check_ms, 1000))
+        return;
+
+    time_t t = now();
+    for (unsigned i = 0; i < N_POLICIES; i++) {
+        EscPolicy &ep = policies[i];
+        if (ep.t0 && t >= ep.t0 + (ep.step+1)*ep.mins*60) {
+            notifySink (ep);
+            if (++ep.step == ep.n_chain)
+                ep.t0 = 0;
+        }
+    }
+}
+
+/* set the escalation policy for the given kind, mins 0 to remove it.
+ * if trouble put reason in ynot and return false.
+ */
+bool setAlertEscalation (const char *kind, int mins, const char *chain, char *ynot, size_t ynot_len)
+{
+    loadPolicies();
+
+    EscPolicy *ep = findPolicy (kind);
+    if (!ep) {
+        size_t l = snprintf (ynot, ynot_len, "kind must be a critical alert:");
+        for (unsigned i = 0; i < N_POLICIES && l < ynot_len; i++)
+            l += snprintf (ynot+l, ynot_len-l, " %s", policies[i].kind);
+        return (false);
+    }
+    if (mins < 0 || mins > ESC_MAXMINS) {
+        snprintf (ynot, ynot_len, "mins must be 0 .. %d", ESC_MAXMINS);
+        return (false);
+    }
+
+    if (mins > 0) {
+        EscPolicy new_ep = *ep;
+        if (!crackChain (chain, new_ep, ynot, ynot_len))
+            return (false);
+        *ep = new_ep;
+    }
+    ep->mins = mins;
+    ep->t0 = 0;
+
+    savePolicies();
+    return (true);
+}
+
+/* print each policy, and its progress if escalating, one per line with the given prefix
+ */
+void printAlertEscalations (WiFiClient &client, const char *prefix)
+{
+    loadPolicies();
+
+    char buf[150];
+    bool any = false;
+    for (unsigned i = 0; i < N_POLICIES; i++) {
+        EscPolicy &ep = policies[i];
+        if (ep.mins == 0)
+            continue;
+        char chain[100];
+        formatChain (ep, chain, sizeof(chain));
+        int l = snprintf (buf, sizeof(buf), "%s%s every %d min to %s", prefix, ep.kind, ep.mins, chain);
+        if (ep.t0)
+            snprintf (buf+l, sizeof(buf)-l, ", unacknowledged at step %d", ep.step);
+        client.println (buf);
+        any = true;
+    }
+    if (!any) {
+        snprintf (buf, sizeof(buf), "%snone", prefix);
+        client.println (buf);
+    }
+}
+
+#else // !_IS_UNIX
+
+/* dummy versions for systems with no policy file
+ */
+void escalateAlert (const char *kind, const char *details)
+{
+    (void) kind;
+    (void) details;
+}
+
+void ackAlertEscalations (const char *kind)
+{
+    (void) kind;
+}
+
+#endif // _IS_UNIX
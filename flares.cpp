@@ -0,0 +1,191 @@
+/* show a list of the solar flares of the last 24 hours in a plot pane, most recent first,
+ * with the peak time, X-ray class and active region of each. X class flares stand out.
+ */
+
+#include "HamClock.h"
+
+
+// server file containing recent flares from the SWPC events product, one per line, any order:
+//   peak_unix_time class region
+// eg: 1617209100 M1.2 2814
+// region is 0 if not known.
+static const char flares_page[] = "/ham/HamClock/NOAASpaceWX/flares.txt";
+
+// layout
+#define TITLE_COLOR     RA8875_GREEN
+#define HEADING_COLOR   GRAY
+#define TITLE_Y0        27              // title dy, match VOACAP title position
+#define HEADING_Y0      34              // column headings y down from box top
+#define LISTING_Y0      47              // first flare y down from box top
+#define LISTING_DY      11              // listing row separation
+#define LISTING_N       ((PLOTBOX_H - LISTING_Y0)/LISTING_DY)       // max n list rows
+#define TIME_X          10              // column x offsets from box left
+#define CLASS_X         64
+#define REGION_X        112
+
+#define FLARES_AGE      (24*3600)       // show flares peaking no longer ago than this, secs
+
+// one flare
+typedef struct {
+    time_t peak;                        // UTC of peak
+    char cls[8];                        // X-ray class, eg M1.2
+    int region;                         // NOAA active region number, 0 if unknown
+} Flare;
+
+
+/* qsort-style function to sort Flares by decreasing peak time
+ */
+static int flareQS (const void *p1, const void *p2)
+{
+    time_t t1 = ((Flare*)p1)->peak;
+    time_t t2 = ((Flare*)p2)->peak;
+    return (t1 < t2 ? 1 : (t1 > t2 ? -1 : 0));
+}
+
+/* return the peak X-ray flux of the given flare class, W/m^2, eg M1.2 is 1.2e-5
+ */
+static float flareFlux (const char *cls)
+{
+    const char *lp = strchr ("ABCMX", toupper(cls[0]));
+    if (!lp || !cls[0])
+        return (0);
+    return (atof (cls+1) * powf (10, (lp - "ABCMX") - 8));
+}
+
+/* return color to show the given flare class
+ */
+static uint16_t flareColor (const char *cls)
+{
+    switch (toupper(cls[0])) {
+    case 'X': return (RA8875_RED);
+    case 'M': return (RA8875_YELLOW);
+    default:  return (RA8875_WHITE);
+    }
+}
+
+/* draw the given list of flares, already sorted most recent first
+ */
+static void drawFlares (const SBox &box, const Flare *flares, int n_flares)
+{
+    prepPlotBox (box);
+
+    // title
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (TITLE_COLOR);
+    const char *title = xlate ("Flares 24 hrs");
+    tft.setCursor (box.x + (box.w - getTextWidth(title))/2, box.y + TITLE_Y0);
+    tft.print (title);
+
+    // column headings
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    tft.setTextColor (HEADING_COLOR);
+    tft.setCursor (box.x + TIME_X, box.y + HEADING_Y0);
+    tft.print (F("Peak UTC"));
+    tft.setCursor (box.x + CLASS_X, box.y + HEADING_Y0);
+    tft.print (F("Class"));
+    tft.setCursor (box.x + REGION_X, box.y + HEADING_Y0);
+    tft.print (F("Region"));
+
+    if (n_flares == 0) {
+        tft.setTextColor (RA8875_GREEN);
+        const char *none = "None";
+        tft.setCursor (box.x + (box.w - getTextWidth(none))/2, box.y + LISTING_Y0 + LISTING_DY);
+        tft.print (none);
+        return;
+    }
+
+    for (int i = 0; i < n_flares && i < LISTING_N; i++) {
+        const Flare &f = flares[i];
+        uint16_t y = box.y + LISTING_Y0 + i*LISTING_DY;
+        uint16_t color = flareColor (f.cls);
+        char buf[20];
+
+        // X class flares get a highlight bar
+        if (toupper(f.cls[0]) == 'X') {
+            tft.fillRect (box.x+2, y-1, box.w-4, LISTING_DY-1, RGB565(80,0,0));
+            color = RA8875_WHITE;
+        }
+        tft.setTextColor (color);
+
+        snprintf (buf, sizeof(buf), "%02d:%02d", hour(f.peak), minute(f.peak));
+        tft.setCursor (box.x + TIME_X, y);
+        tft.print (buf);
+
+        tft.setCursor (box.x + CLASS_X, y);
+        tft.print (f.cls);
+
+        if (f.region > 0) {
+            snprintf (buf, sizeof(buf), "%d", f.region);
+            tft.setCursor (box.x + REGION_X, y);
+            tft.print (buf);
+        }
+    }
+}
+
+/* retrieve and display the flares of the last 24 hours in the given box.
+ * return whether all ok.
+ */
+bool updateFlares (const SBox &box)
+{
+    WiFiClient flares_client;
+    Flare *flares = NULL;
+    int n_flares = 0;
+    char line[100];
+    bool ok = false;
+
+    Serial.println (flares_page);
+    resetWatchdog();
+    if (wifiOk() && flares_client.connect (svr_host, HTTPPORT)) {
+
+        resetWatchdog();
+        updateClocks(false);
+
+        // fetch page
+        httpGET (flares_client, svr_host, flares_page);
+        if (!httpSkipHeader (flares_client)) {
+            plotMessage (box, RA8875_RED, _FX("Flares header short"));
+            goto out;
+        }
+
+        // collect each recent flare
+        time_t t0 = now();
+        while (getTCPLine (flares_client, line, sizeof(line), NULL)) {
+            Flare f;
+            long ut;
+            if (sscanf (line, "%ld %7s %d", &ut, f.cls, &f.region) != 3 || !isalpha(f.cls[0]))
+                continue;
+            f.peak = ut;
+            if (t0 - f.peak > FLARES_AGE)
+                continue;
+            flares = (Flare *) realloc (flares, (n_flares+1)*sizeof(Flare));
+            if (!flares)
+                fatalError (_FX("No memory for %d flares"), n_flares+1);
+            flares[n_flares++] = f;
+        }
+        Serial.printf (_FX("Flares: found %d\n"), n_flares);
+
+        // show most recent first
+        if (n_flares > 1)
+            qsort (flares, n_flares, sizeof(Flare), flareQS);
+        drawFlares (box, flares, n_flares);
+        ok = true;
+
+        // note largest for getSpaceWeather()
+        int largest = -1;
+        for (int i = 0; i < n_flares; i++)
+            if (largest < 0 || flareFlux (flares[i].cls) > flareFlux (flares[largest].cls))
+                largest = i;
+        if (largest >= 0)
+            noteFlares (flares[largest].cls, flares[largest].peak);
+        else
+            noteFlares ("", 0);
+
+    } else
+        plotMessage (box, RA8875_RED, _FX("Flares connection failed"));
+
+out:
+    flares_client.stop();
+    free (flares);
+    printFreeHeap (F("updateFlares"));
+    return (ok);
+}
@@ -0,0 +1,179 @@
+/* render great circle arcs on the map.
+ *
+ * each arc is described by a GCArc: where it starts, its initial bearing and length, plus its width and the
+ * colors before and after a split point, so the same code draws the DX short and long path and the antenna
+ * beam edges. lines are not antialiased. drawing has three stages:
+ *
+ *   tessellate: walk the arc in GCARC_STEP steps to get lat/long vertices
+ *   project:    convert each segment to screen coords with geoClipSegment(), which breaks the arc where
+ *               it leaves the map, wraps at the dateline in Mercator, crosses the edge of a hemisphere
+ *               in the azimuthal projection or passes over a pole
+ *   draw:       connect the remaining vertices with lines of the arc width
+ *
+ * the projected vertices are saved so the arc can later be erased by restoring the map beneath it.
+ */
+
+#include "HamClock.h"
+
+
+#define GCARC_STEP      deg2rad(0.5F)           // tessellation step, rads
+#define GCARC_MAXPTS    ((int)(2*M_PIF/GCARC_STEP)+2)   // max vertices in one arc
+#define GCARC_BREAK     0xFFFF                  // SCoord.x marking a break in a saved path
+
+
+/* init arc to start at from with the given initial bearing and length, both in radians.
+ * the whole arc is drawn in color with the given width.
+ */
+void initGCArc (GCArc &arc, const LatLong &from, float bear, float dist, uint16_t color, uint8_t width)
+{
+    arc.from = from;
+    arc.bear = bear;
+    arc.dist = dist;
+    arc.split = dist;
+    arc.color = arc.color2 = color;
+    arc.width = width ? width : 1;
+}
+
+/* fill pts[] with vertices along arc, no more than max_pts.
+ * return number of vertices used, always including both ends if max_pts >= 2.
+ */
+static int tessellateGCArc (const GCArc &arc, LatLong pts[], int max_pts)
+{
+    if (max_pts < 2)
+        return (0);
+
+    // subdivide into equal segments no longer than GCARC_STEP
+    int n_seg = (int) ceilf (arc.dist / GCARC_STEP);
+    if (n_seg < 1)
+        n_seg = 1;
+    if (n_seg > max_pts - 1)
+        n_seg = max_pts - 1;
+    float step = arc.dist / n_seg;
+
+    float sflat = sinf (arc.from.lat);
+    float cflat = cosf (arc.from.lat);
+    for (int i = 0; i <= n_seg; i++) {
+        float ca, B;
+        solveSphere (arc.bear, i*step, sflat, cflat, &ca, &B);
+        pts[i].lat_d = rad2deg (asinf (ca));
+        pts[i].lng_d = rad2deg (arc.from.lng + B);
+        normalizeLL (pts[i]);
+    }
+
+    return (n_seg + 1);
+}
+
+/* return whether a square of the given width at s lies entirely over the earth so it can be erased.
+ */
+static bool fatOverEarth (const SCoord &s, uint8_t width)
+{
+    LatLong ll;
+    return (s2ll (s.x + width, s.y, ll) && s2ll (s.x, s.y + width, ll)
+                && s2ll (s.x + width, s.y + width, ll) && s2ll (s.x > width ? s.x - width : 0, s.y, ll)
+                && s2ll (s.x, s.y > width ? s.y - width : 0, ll));
+}
+
+/* draw one projected segment of arc
+ */
+static void drawGCSegment (const GCArc &arc, const SCoord &s0, const SCoord &s1, uint16_t color)
+{
+    // thick lines would leave unerasable pixels off the edge of earth
+    if (arc.width > 1 && fatOverEarth (s0, arc.width) && fatOverEarth (s1, arc.width))
+        tft.drawLine (s0.x, s0.y, s1.x, s1.y, arc.width, color);
+    else
+        tft.drawLine (s0.x, s0.y, s1.x, s1.y, color);
+}
+
+/* append s to path, growing as needed
+ */
+static void addGCPathPoint (SCoord *&path, uint16_t &n_path, uint16_t &n_malloc, const SCoord &s)
+{
+    if (n_path == n_malloc) {
+        n_malloc += GCARC_MAXPTS;
+        path = (SCoord *) realloc (path, n_malloc * sizeof(SCoord));
+        if (!path)
+            fatalError ("No memory for great circle arc");
+    }
+    path[n_path++] = s;
+}
+
+/* draw arc on the map and save its screen vertices in path, which is (re)malloced as needed.
+ * return number of entries in path, which may include breaks to be passed on to eraseGCArc().
+ */
+uint16_t drawGCArc (const GCArc &arc, SCoord *&path)
+{
+    // tessellate
+    LatLong *pts = (LatLong *) malloc (GCARC_MAXPTS * sizeof(LatLong));
+    if (!pts)
+        fatalError ("No memory for great circle arc");
+    int n_pts = tessellateGCArc (arc, pts, GCARC_MAXPTS);
+
+    // project each segment in pieces that do not wrap across the map, then draw each piece
+    uint16_t n_path = 0, n_malloc = 0;
+    SCoord brk = {GCARC_BREAK, GCARC_BREAK};
+    float step = n_pts > 1 ? arc.dist / (n_pts - 1) : 0;
+    for (int i = 1; i < n_pts; i++) {
+        SCoord s0[GEO_MAXPIECES], s1[GEO_MAXPIECES];
+        int n_pieces = geoClipSegment (pts[i-1], pts[i], 1, s0, s1);
+        uint16_t c = (i - 0.5F) * step < arc.split ? arc.color : arc.color2;
+        for (int j = 0; j < n_pieces; j++) {
+            bool joined = n_path > 0 && memcmp (&path[n_path-1], &s0[j], sizeof(SCoord)) == 0;
+            if (!joined) {
+                if (n_path > 0)
+                    addGCPathPoint (path, n_path, n_malloc, brk);
+                addGCPathPoint (path, n_path, n_malloc, s0[j]);
+            }
+            if (memcmp (&s0[j], &s1[j], sizeof(SCoord)) == 0)
+                continue;
+            drawGCSegment (arc, s0[j], s1[j], c);
+            addGCPathPoint (path, n_path, n_malloc, s1[j]);
+        }
+    }
+
+    // lone vertices are still drawn as a dot
+    for (uint16_t i = 0; i < n_path; i++) {
+        bool alone = (i == 0 || path[i-1].x == GCARC_BREAK) && (i == n_path-1 || path[i+1].x == GCARC_BREAK);
+        if (alone && path[i].x != GCARC_BREAK)
+            tft.drawPixel (path[i].x, path[i].y, arc.color);
+    }
+
+    free (pts);
+
+    return (n_path);
+}
+
+/* restore the map beneath one pixel and its neighbors within width
+ */
+static void eraseGCPoint (int x, int y, uint8_t width)
+{
+    int r = width/2 + 1;
+    for (int dy = -r; dy <= r; dy++)
+        for (int dx = -r; dx <= r; dx += 2)            // drawMapCoord draws x and x+1
+            if (x+dx >= 0 && y+dy >= 0)
+                drawMapCoord (x+dx, y+dy);
+}
+
+/* erase an arc drawn with drawGCArc by restoring the map along each of its n_path saved segments.
+ */
+void eraseGCArc (const SCoord *path, uint16_t n_path, uint8_t width)
+{
+    if (!path)
+        return;
+
+    for (uint16_t i = 0; i < n_path; i++) {
+        if (path[i].x == GCARC_BREAK)
+            continue;
+
+        if (i == 0 || path[i-1].x == GCARC_BREAK) {
+            eraseGCPoint (path[i].x, path[i].y, width);
+            continue;
+        }
+
+        // step along the segment from the previous vertex one pixel at a time
+        int x0 = path[i-1].x, y0 = path[i-1].y;
+        int dx = path[i].x - x0, dy = path[i].y - y0;
+        int n = abs(dx) > abs(dy) ? abs(dx) : abs(dy);
+        for (int j = 1; j <= n; j++)
+            eraseGCPoint (x0 + (dx*j + n/2)/n, y0 + (dy*j + n/2)/n, width);
+    }
+}
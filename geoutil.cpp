@@ -0,0 +1,183 @@
+/* map geometry helpers shared by everything that draws lines between lat/long locations.
+ *
+ * a straight screen line between the projections of two nearby locations is only correct if both lie on
+ * the same continuous sheet of the current projection. in Mercator the sheet is cut at the longitude
+ * opposite the map center, in the azimuthal projection at the edge of each hemisphere. a line that
+ * crosses a cut would be drawn all the way across the map, so these functions split each segment at the
+ * cut, split it again if it passes over a pole, then clip each piece to where map content may be drawn.
+ *
+ * all assume segments are short, a few degrees at most; callers subdivide longer ones.
+ */
+
+#include "HamClock.h"
+
+
+#define GEO_BISECT      16                      // bisection steps when finding a crossing
+#define GEO_POLEDLNG    90                      // a segment changing lng this much passes over a pole, degs
+
+
+/* set m to the location fraction t of the way from a to b, going the short way in longitude.
+ */
+void geoLerpLL (const LatLong &a, const LatLong &b, float t, LatLong &m)
+{
+    float dlng = b.lng_d - a.lng_d;
+    if (dlng > 180)
+        dlng -= 360;
+    else if (dlng < -180)
+        dlng += 360;
+
+    m.lat_d = a.lat_d + t*(b.lat_d - a.lat_d);
+    m.lng_d = a.lng_d + t*dlng;
+    normalizeLL (m);
+}
+
+/* return whether a segment from a to b passes over a pole, in which case interpolating its longitude
+ * would instead draw it around the pole.
+ */
+bool geoCrossesPole (const LatLong &a, const LatLong &b)
+{
+    float dlng = fabsf (b.lng_d - a.lng_d);
+    if (dlng > 180)
+        dlng = 360 - dlng;
+    return (dlng > GEO_POLEDLNG && (a.lat_d > 0) == (b.lat_d > 0));
+}
+
+/* return whether a segment from a to b stays on one sheet of the current projection.
+ */
+bool geoSameSheet (const LatLong &a, const LatLong &b)
+{
+    if (azm_on) {
+        // cut at the edge of the DE hemisphere
+        float ca_a, ca_b;
+        solveSphere (a.lng - de_ll.lng, M_PI_2F-a.lat, sdelat, cdelat, &ca_a, NULL);
+        solveSphere (b.lng - de_ll.lng, M_PI_2F-b.lat, sdelat, cdelat, &ca_b, NULL);
+        return ((ca_a > 0) == (ca_b > 0));
+    } else {
+        // cut at the longitude opposite the center, ie, where the x offset from the left edge jumps
+        float xa = fmodf (a.lng_d - getCenterLng() + 540, 360);
+        float xb = fmodf (b.lng_d - getCenterLng() + 540, 360);
+        return (fabsf (xa - xb) <= 180);
+    }
+}
+
+/* find the last location from a towards b for which same() is true and the first for which it is not.
+ */
+static void geoBisect (const LatLong &a, const LatLong &b, bool (*same)(const LatLong &, const LatLong &),
+LatLong &last_a, LatLong &first_b)
+{
+    float t0 = 0, t1 = 1;
+    for (int i = 0; i < GEO_BISECT; i++) {
+        float t = (t0 + t1)/2;
+        LatLong m;
+        geoLerpLL (a, b, t, m);
+        if ((*same)(a, m))
+            t0 = t;
+        else
+            t1 = t;
+    }
+    geoLerpLL (a, b, t0, last_a);
+    geoLerpLL (a, b, t1, first_b);
+}
+
+/* return whether ll projects onto the drawable portion of the map
+ */
+static bool geoOnMap (const LatLong &ll, uint8_t edge)
+{
+    SCoord s;
+    ll2s (ll, s, edge);
+    return (overMap (s));
+}
+
+/* return whether a and b are both on or both off the drawable map
+ */
+static bool geoSameOnMap (const LatLong &a, const LatLong &b)
+{
+    return (geoOnMap (a, 0) == geoOnMap (b, 0));
+}
+
+/* add the screen segment from a to b that lies on the drawable map to s0[n] and s1[n].
+ * return number added, 0 or 1.
+ */
+static int geoClipPiece (const LatLong &a, const LatLong &b, uint8_t edge, SCoord s0[], SCoord s1[])
+{
+    bool a_on = geoOnMap (a, edge);
+    bool b_on = geoOnMap (b, edge);
+    if (!a_on && !b_on)
+        return (0);
+
+    LatLong a_in = a, b_in = b;
+    if (!b_on) {
+        LatLong out;
+        geoBisect (a, b, geoSameOnMap, b_in, out);
+    } else if (!a_on) {
+        LatLong out;
+        geoBisect (b, a, geoSameOnMap, a_in, out);
+    }
+
+    ll2s (a_in, s0[0], edge);
+    ll2s (b_in, s1[0], edge);
+    return (1);
+}
+
+/* add the pieces of segment a to b that do not cross a projection cut to s0[] and s1[].
+ * return number added, 0 .. 2.
+ */
+static int geoSheetPieces (const LatLong &a, const LatLong &b, uint8_t edge, SCoord s0[], SCoord s1[])
+{
+    if (geoSameSheet (a, b))
+        return (geoClipPiece (a, b, edge, s0, s1));
+
+    LatLong a_end, b_start;
+    geoBisect (a, b, geoSameSheet, a_end, b_start);
+    int n = geoClipPiece (a, a_end, edge, s0, s1);
+    n += geoClipPiece (b_start, b, edge, s0+n, s1+n);
+    return (n);
+}
+
+/* split the segment from a to b into screen pieces that can each be drawn as a straight line without
+ * wrapping across the map. edge is passed to ll2s().
+ * piece i runs from s0[i] to s1[i], both arrays must hold GEO_MAXPIECES.
+ * return number of pieces, 0 if none of the segment is on the map.
+ */
+int geoClipSegment (const LatLong &a, const LatLong &b, uint8_t edge, SCoord s0[], SCoord s1[])
+{
+    if (!geoCrossesPole (a, b))
+        return (geoSheetPieces (a, b, edge, s0, s1));
+
+    // go up one meridian to the pole then down the other
+    LatLong pole;
+    pole.lat_d = a.lat_d > 0 ? 90 : -90;
+    pole.lng_d = a.lng_d;
+    normalizeLL (pole);
+    int n = geoSheetPieces (a, pole, edge, s0, s1);
+    pole.lng_d = b.lng_d;
+    normalizeLL (pole);
+    n += geoSheetPieces (pole, b, edge, s0+n, s1+n);
+    return (n);
+}
+
+/* draw the segment from a to b with the given width and color, broken as needed for the projection.
+ */
+void drawGeoSegment (const LatLong &a, const LatLong &b, uint8_t width, uint16_t color)
+{
+    SCoord s0[GEO_MAXPIECES], s1[GEO_MAXPIECES];
+    int n = geoClipSegment (a, b, 1, s0, s1);
+    for (int i = 0; i < n; i++) {
+        if (width > 1)
+            tft.drawLine (s0[i].x, s0[i].y, s1[i].x, s1[i].y, width, color);
+        else
+            tft.drawLine (s0[i].x, s0[i].y, s1[i].x, s1[i].y, color);
+    }
+}
+
+/* return whether the given screen line segment spans a reasonable portion of the map.
+ * this is for paths that are only known in screen coords; beware map edge, wrap and crossing center of
+ * azm map.
+ */
+bool segmentSpanOk (SCoord &s0, SCoord &s1)
+{
+    return (s0.x - s1.x < map_b.w/2 && s1.x - s0.x < map_b.w/2
+                && s0.y - s1.y < map_b.h/2 && s1.y - s0.y < map_b.h/2
+                && (!azm_on || ((s0.x < map_b.x+map_b.w/2) == (s1.x < map_b.x+map_b.w/2)))
+                && overMap(s0) && overMap(s1));
+}
@@ -0,0 +1,75 @@
+/* C ABI for HamClock plugins on UNIX systems.
+ *
+ * A plugin is a shared library placed in ~/.hamclock/plugins with a name ending in .so. At startup
+ * HamClock dlopen()s each and calls its HCP_ENTRY function to get a description of the plugin. Plugins
+ * with a matching abi_version are then shown, one at a time, in the Plugins pane.
+ *
+ * fetch() is called every fetch_secs while the plugin is showing to collect new data, then render()
+ * draws the plugin in the pane. Both run in the main thread so they must return promptly. All drawing
+ * must be done through the HCPluginHost functions, which are confined to the pane area below the title.
+ *
+ * Example:
+ *
+ *   #include <stdio.h>
+ *   #include "hamclock_plugin.h"
+ *
+ *   static int n;
+ *   static int fetch (const HCPluginHost *host) { n++; return (0); }
+ *   static void render (const HCPluginHost *host, int w, int h) {
+ *       char buf[20];
+ *       snprintf (buf, sizeof(buf), "%d fetches", n);
+ *       host->drawText (10, h/2, 0xFFFF, buf);
+ *   }
+ *   static const HCPlugin me = { HCP_ABI_VERSION, "Counter", 60, fetch, render, NULL };
+ *   const HCPlugin *hamclock_plugin (void) { return (&me); }
+ *
+ *   cc -shared -fPIC -o ~/.hamclock/plugins/counter.so counter.c
+ */
+
+#ifndef _HAMCLOCK_PLUGIN_H
+#define _HAMCLOCK_PLUGIN_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define HCP_ABI_VERSION 1                       // bump when either struct below changes
+#define HCP_ENTRY       "hamclock_plugin"       // name of the function each plugin must export
+
+/* services HamClock provides to plugins.
+ * x and y are pixels from the upper left of the plugin area below the pane title, all drawing is clipped
+ * to that area.
+ * colors are RGB565.
+ */
+typedef struct {
+    void (*fillRect) (int x, int y, int w, int h, uint16_t color);
+    void (*drawLine) (int x0, int y0, int x1, int y1, uint16_t color);
+    void (*drawText) (int x, int y, uint16_t color, const char *str);  // small font, y is top
+    int (*textWidth) (const char *str);                                 // pixels drawText would use
+    void (*getDE) (float *lat_d, float *lng_d);                         // DE location, degrees +N +E
+    const char *(*getCall) (void);                                      // DE call sign
+    void (*log) (const char *msg);                                      // add a line to the log
+} HCPluginHost;
+
+/* what a plugin provides to HamClock
+ */
+typedef struct {
+    int abi_version;                            // HCP_ABI_VERSION when plugin was built
+    const char *name;                           // short name shown as the pane title
+    int fetch_secs;                             // seconds between calls to fetch(), 0 to call just once
+    int (*fetch) (const HCPluginHost *host);    // collect new data, return 0 if ok; may be NULL
+    void (*render) (const HCPluginHost *host, int w, int h);    // draw in pane w x h, already erased
+    void (*tap) (const HCPluginHost *host, int x, int y);       // pane tapped below title; may be NULL
+} HCPlugin;
+
+/* the function each plugin exports as HCP_ENTRY
+ */
+typedef const HCPlugin *(*HCPluginEntry) (void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // _HAMCLOCK_PLUGIN_H
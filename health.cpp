@@ -0,0 +1,163 @@
+/* liveness checks for supervised installs: no recent pass through the main loop took too long, data
+ * fetches are succeeding or deliberately paused, and the working directory is writable. the results are
+ * served as /healthz.
+ *
+ * on UNIX started by systemd with Type=notify, READY=1 is sent as soon as we start so a long wait in Setup
+ * does not count against the start timeout. with WatchdogSec= also set, WATCHDOG=1 is sent from
+ * resetWatchdog(), which the main loop and every blocking path call, so slow fetches and the Setup screen
+ * are not mistaken for a hang. but once the main loop has started, pings stop if it has not come around
+ * for HEALTH_LOOP_SECS, so a HamClock wedged anywhere is still restarted automatically.
+ */
+
+#include "HamClock.h"
+
+#if defined(_IS_UNIX)
+#include <stddef.h>
+#include <sys/socket.h>
+#include <sys/un.h>
+#endif
+
+
+#define HEALTH_LOOP_SECS        120             // main loop must run at least this often, secs
+#define HEALTH_FETCH_SECS       (2*3600)        // some data source must refresh at least this often, secs
+#define HEALTH_WORST_SECS       3600            // period over which the longest loop pass is reported, secs
+
+static uint32_t last_loop_ms;                   // millis() when loop last called checkHealth()
+static uint32_t worst_loop_ms;                  // longest loop pass within HEALTH_WORST_SECS, millis
+static uint32_t worst_loop_at;                  // millis() when worst_loop_ms was seen
+
+
+#if defined(_IS_UNIX)
+
+/* send the given state message to systemd, if it started us with a notify socket
+ */
+static void sdNotify (const char *msg)
+{
+    const char *path = getenv ("NOTIFY_SOCKET");
+    if (!path || (path[0] != '/' && path[0] != '@'))
+        return;
+
+    struct sockaddr_un sa;
+    memset (&sa, 0, sizeof(sa));
+    sa.sun_family = AF_UNIX;
+    size_t pl = strlen (path);
+    if (pl >= sizeof(sa.sun_path))
+        return;
+    memcpy (sa.sun_path, path, pl);
+    if (sa.sun_path[0] == '@')
+        sa.sun_path[0] = '\0';                  // abstract namespace
+    socklen_t sl = offsetof (struct sockaddr_un, sun_path) + pl;
+
+    int fd = ::socket (AF_UNIX, SOCK_DGRAM|SOCK_CLOEXEC, 0);
+    if (fd < 0)
+        return;
+    if (::sendto (fd, msg, strlen(msg), 0, (struct sockaddr *)&sa, sl) < 0)
+        Serial.printf (_FX("Health: %s: %s\n"), path, strerror(errno));
+    ::close (fd);
+}
+
+#endif // _IS_UNIX
+
+/* called from resetWatchdog() to tell systemd we are ready the first time, then ping its watchdog at half
+ * the interval it asked for as long as the main loop has not stalled.
+ */
+void pingSystemd()
+{
+#if defined(_IS_UNIX)
+    static bool ready;
+    static uint32_t wd_ms;                      // watchdog ping interval, 0 if not requested
+    static uint32_t last_ping_ms;
+
+    if (!ready) {
+        const char *wd_usec = getenv ("WATCHDOG_USEC");
+        if (wd_usec)
+            wd_ms = atol (wd_usec)/2000;
+        sdNotify ("READY=1");
+        if (wd_ms)
+            Serial.printf (_FX("Health: pinging systemd watchdog every %u ms\n"), wd_ms);
+        ready = true;
+    }
+
+    // N.B. before the loop starts we may be in Setup, which waits indefinitely for the user
+    bool loop_ok = last_loop_ms == 0 || millis() - last_loop_ms < HEALTH_LOOP_SECS*1000U;
+    if (wd_ms && loop_ok && timesUp (&last_ping_ms, wd_ms))
+        sdNotify ("WATCHDOG=1");
+#endif // _IS_UNIX
+}
+
+/* called each time through the main loop to show it is alive
+ */
+void checkHealth()
+{
+    // note the longest pass recently, N.B. this call is the end of the previous pass
+    uint32_t ms = millis();
+    if (last_loop_ms) {
+        uint32_t pass_ms = ms - last_loop_ms;
+        if (pass_ms >= worst_loop_ms || ms - worst_loop_at > HEALTH_WORST_SECS*1000U) {
+            worst_loop_ms = pass_ms;
+            worst_loop_at = ms;
+        }
+    }
+    last_loop_ms = ms;
+
+    pingSystemd();
+}
+
+/* fill report with one line per check, each "name ok|FAIL detail".
+ * return whether all checks pass.
+ */
+bool getHealth (char *report, size_t report_len)
+{
+    size_t rl = 0;
+    bool all_ok = true;
+
+    // main loop, N.B. we are called from within it so just report how long recent passes took
+    long worst = (long)worst_loop_ms/1000;
+    bool loop_ok = last_loop_ms && worst < HEALTH_LOOP_SECS;
+    if (!last_loop_ms)
+        rl += snprintf (report+rl, report_len-rl, "loop     FAIL not started\n");
+    else
+        rl += snprintf (report+rl, report_len-rl, "loop     %s longest pass %ld s in last %d min\n",
+                                                loop_ok ? "ok" : "FAIL", worst, HEALTH_WORST_SECS/60);
+    all_ok = all_ok && loop_ok;
+
+    // data fetches, fine while offline on purpose or still starting up
+    bool off_forced;
+    time_t off_since;
+    if (getOfflineMode (off_forced, off_since)) {
+        rl += snprintf (report+rl, report_len-rl, "fetch    ok paused, offline %s\n",
+                                                                off_forced ? "forced" : "auto");
+    } else {
+        const RefreshSource *rs;
+        int n_rs = getRefreshSources (&rs);
+        time_t newest = 0;
+        for (int i = 0; i < n_rs; i++)
+            if (rs[i].last_ok > newest)
+                newest = rs[i].last_ok;
+        if (newest) {
+            long fetch_age = now() - newest;
+            bool fetch_ok = fetch_age < HEALTH_FETCH_SECS;
+            rl += snprintf (report+rl, report_len-rl, "fetch    %s last success %ld s ago\n",
+                                                                fetch_ok ? "ok" : "FAIL", fetch_age);
+            all_ok = all_ok && fetch_ok;
+        } else {
+            bool fetch_ok = millis()/1000 < HEALTH_FETCH_SECS;
+            rl += snprintf (report+rl, report_len-rl, "fetch    %s no success yet\n", fetch_ok ? "ok" : "FAIL");
+            all_ok = all_ok && fetch_ok;
+        }
+    }
+
+    // storage
+#if defined(_IS_UNIX)
+    bool dir_ok = access (our_dir.c_str(), W_OK) == 0;
+    if (dir_ok)
+        rl += snprintf (report+rl, report_len-rl, "storage  ok %s writable\n", our_dir.c_str());
+    else
+        rl += snprintf (report+rl, report_len-rl, "storage  FAIL %s: %s\n", our_dir.c_str(), strerror(errno));
+    all_ok = all_ok && dir_ok;
+#else
+    rl += snprintf (report+rl, report_len-rl, "storage  ok EEPROM\n");
+#endif
+
+    return (all_ok);
+}
@@ -0,0 +1,212 @@
+/* keep a local copy of each downloaded image so it can be drawn again without asking the server
+ * until it is older than the caller's time-to-live. after that the server is asked for the image only
+ * if it has changed, so an unchanged image costs just a header exchange. UNIX only.
+ *
+ * each image is stored in our_dir as imgcache-HASH.bmp where HASH is formed from the url, along with
+ * imgcache-HASH.txt containing the server's Last-Modified time and the url on the first line and its
+ * ETag, if any, on the second. the file modification time of the image is when it was fetched or last
+ * confirmed unchanged. the set of urls is small so no pruning is needed.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#include <errno.h>
+#include <sys/stat.h>
+#include <utime.h>
+
+// lookups since startup, for getImageCacheStats()
+static int n_hits;                              // served from cache within ttl
+static int n_renews;                            // server said cached copy is unchanged
+static int n_misses;                            // fetched afresh
+
+/* return path to the cached image or its info file for the given url.
+ */
+static std::string imageCachePath (const char *url, const char *suffix)
+{
+    // djb2
+    uint32_t hash = 5381;
+    for (const char *cp = url; *cp; cp++)
+        hash = hash*33 + (uint8_t)*cp;
+
+    char name[40];
+    snprintf (name, sizeof(name), "imgcache-%08x%s", hash, suffix);
+    return (our_dir + name);
+}
+
+/* read the info file for the given url and pass back its validators.
+ * return whether it exists and matches url.
+ */
+static bool readImageCacheInfo (const char *url, HTTPValidators &v)
+{
+    // info file must match url to guard against hash collisions
+    std::string info_path = imageCachePath (url, ".txt");
+    FILE *info_fp = fopen (info_path.c_str(), "r");
+    if (!info_fp)
+        return (false);
+    char line[200];
+    unsigned long lastmod = 0;
+    int n_url = 0;
+    bool match = fgets (line, sizeof(line), info_fp) && sscanf (line, "%lu %n", &lastmod, &n_url) == 1
+                        && strncmp (line + n_url, url, strlen(url)) == 0;
+
+    // optional ETag, older files do not have one
+    if (match) {
+        v.lastmod = lastmod;
+        v.etag[0] = '\0';
+        if (fgets (line, sizeof(line), info_fp)) {
+            line[strcspn (line, "\r\n")] = '\0';
+            if (strlen (line) < sizeof(v.etag))
+                strcpy (v.etag, line);
+        }
+    }
+
+    fclose (info_fp);
+    return (match);
+}
+
+/* return a cached copy of the given url open for reading if it is younger than ttl seconds, else NULL.
+ * also pass back its validators.
+ */
+FILE *openImageCache (const char *url, int ttl, HTTPValidators &v)
+{
+    std::string img_path = imageCachePath (url, ".bmp");
+    struct stat sbuf;
+    if (stat (img_path.c_str(), &sbuf) < 0 || time(NULL) - sbuf.st_mtime > ttl)
+        return (NULL);
+
+    if (!readImageCacheInfo (url, v))
+        return (NULL);
+
+    FILE *fp = fopen (img_path.c_str(), "r");
+    if (fp)
+        n_hits++;
+    return (fp);
+}
+
+/* pass back the validators of the cached copy of the given url regardless of its age, for asking the
+ * server whether it has changed. return whether there is such a copy.
+ */
+bool getImageCacheValidators (const char *url, HTTPValidators &v)
+{
+    std::string img_path = imageCachePath (url, ".bmp");
+    struct stat sbuf;
+    return (stat (img_path.c_str(), &sbuf) == 0 && readImageCacheInfo (url, v));
+}
+
+/* the server says the cached copy of the given url is still current: restart its time-to-live and
+ * return it open for reading, else NULL if trouble.
+ */
+FILE *renewImageCache (const char *url)
+{
+    std::string img_path = imageCachePath (url, ".bmp");
+    if (utime (img_path.c_str(), NULL) < 0) {
+        Serial.printf (_FX("ImgCache: %s: %s\n"), img_path.c_str(), strerror(errno));
+        return (NULL);
+    }
+    FILE *fp = fopen (img_path.c_str(), "r");
+    if (fp)
+        n_renews++;
+    return (fp);
+}
+
+/* return a new temporary file for writing a fresh copy of the given url, or NULL if trouble.
+ * N.B. caller must pass it to commitImageCache() when finished.
+ */
+FILE *createImageCache (const char *url)
+{
+    std::string tmp_path = imageCachePath (url, ".tmp");
+    FILE *fp = fopen (tmp_path.c_str(), "w");
+    if (fp)
+        n_misses++;
+    else
+        Serial.printf (_FX("ImgCache: %s: %s\n"), tmp_path.c_str(), strerror(errno));
+    return (fp);
+}
+
+/* close a file from createImageCache() and, if ok, install it as the cached copy of the given url
+ * with the given validators. otherwise discard it.
+ */
+void commitImageCache (const char *url, FILE *fp, bool ok, const HTTPValidators &v)
+{
+    std::string tmp_path = imageCachePath (url, ".tmp");
+    ok = (fclose (fp) == 0) && ok;
+    if (!ok) {
+        (void) unlink (tmp_path.c_str());
+        return;
+    }
+
+    std::string info_path = imageCachePath (url, ".txt");
+    FILE *info_fp = fopen (info_path.c_str(), "w");
+    if (!info_fp) {
+        Serial.printf (_FX("ImgCache: %s: %s\n"), info_path.c_str(), strerror(errno));
+        (void) unlink (tmp_path.c_str());
+        return;
+    }
+    fprintf (info_fp, "%lu %s\n", (unsigned long)v.lastmod, url);
+    if (v.etag[0])
+        fprintf (info_fp, "%s\n", v.etag);
+    fclose (info_fp);
+
+    std::string img_path = imageCachePath (url, ".bmp");
+    if (rename (tmp_path.c_str(), img_path.c_str()) < 0)
+        Serial.printf (_FX("ImgCache: %s: %s\n"), img_path.c_str(), strerror(errno));
+}
+
+/* pass back how many lookups since startup were served from the cache, renewed after asking the server
+ * or fetched afresh.
+ */
+void getImageCacheStats (int &hits, int &renews, int &misses)
+{
+    hits = n_hits;
+    renews = n_renews;
+    misses = n_misses;
+}
+
+#else
+
+// dummies
+
+FILE *openImageCache (const char *url, int ttl, HTTPValidators &v)
+{
+    (void) url;
+    (void) ttl;
+    (void) v;
+    return (NULL);
+}
+
+bool getImageCacheValidators (const char *url, HTTPValidators &v)
+{
+    (void) url;
+    (void) v;
+    return (false);
+}
+
+FILE *renewImageCache (const char *url)
+{
+    (void) url;
+    return (NULL);
+}
+
+FILE *createImageCache (const char *url)
+{
+    (void) url;
+    return (NULL);
+}
+
+void commitImageCache (const char *url, FILE *fp, bool ok, const HTTPValidators &v)
+{
+    (void) url;
+    (void) fp;
+    (void) ok;
+    (void) v;
+}
+
+void getImageCacheStats (int &hits, int &renews, int &misses)
+{
+    hits = renews = misses = 0;
+}
+
+#endif // _IS_UNIX
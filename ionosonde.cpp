@@ -0,0 +1,232 @@
+/* show the latest measurements from the ionosonde nearest DE in a plot pane: critical frequency foF2,
+ * maximum usable frequency for a 3000 km path and height of the F2 layer, and how the measured MUF compares
+ * with the simple flux model of modelMUF() at the station. bandrec.cpp uses that ratio to correct the model
+ * near DE, so the report is also kept fresh in the background when the pane is not showing.
+ */
+
+#include "HamClock.h"
+
+
+// server file containing the latest report from each ionosonde, one per line:
+//   code lat lng unix_time foF2_MHz MUF3000_MHz hmF2_km name...
+// eg: AU930 30.40 -97.70 1617209100 6.44 21.05 298 Austin
+static const char iono_page[] = "/ham/HamClock/ionosonde/ionosondes.txt";
+
+// layout
+#define TITLE_COLOR     RA8875_GREEN
+#define LABEL_COLOR     GRAY
+#define VALUE_COLOR     RA8875_WHITE
+#define MUF_COLOR       RA8875_GREEN
+#define TITLE_Y0        27              // title dy, match VOACAP title position
+#define STATION_Y0      40              // station name y down from box top
+#define LISTING_Y0      62              // first value y down from box top
+#define LISTING_DY      15              // value row separation
+#define LABEL_X         8               // label x offset from box left
+#define VALUE_X         60              // value x offset from box left
+
+#define IONO_MAXAGE     (2*3600)        // prefer stations that reported more recently than this, secs
+
+// one ionosonde report
+typedef struct {
+    char code[8];                       // URSI code
+    char name[32];                      // station name
+    LatLong ll;                         // location
+    time_t t;                           // UTC of report
+    float fof2;                         // F2 critical frequency, MHz
+    float mufd;                         // MUF for 3000 km path, MHz
+    float hmf2;                         // F2 peak height, km
+} IonoReport;
+
+// last report retrieved, for getIonosondeCorrection()
+static IonoReport last_ir;              // t is 0 until one is known
+static float last_ratio;                // measured / modeled MUF at the station when retrieved
+
+
+/* crack one line of the ionosonde file.
+ * return whether line is a valid report and, if so, fill ir.
+ */
+static bool crackIonoLine (const char *line, IonoReport &ir)
+{
+    long ut;
+    int n_name = 0;
+    if (sscanf (line, "%7s %f %f %ld %f %f %f %n", ir.code, &ir.ll.lat_d, &ir.ll.lng_d, &ut,
+                                                &ir.fof2, &ir.mufd, &ir.hmf2, &n_name) != 7 || n_name == 0)
+        return (false);
+    snprintf (ir.name, sizeof(ir.name), "%s", line + n_name);
+    if (ir.name[0] == '\0')
+        snprintf (ir.name, sizeof(ir.name), "%s", ir.code);
+    ir.t = ut;
+    normalizeLL (ir.ll);
+    return (true);
+}
+
+/* draw one labeled value row
+ */
+static void drawIonoRow (const SBox &box, int row, const char *label, uint16_t color, const char *value)
+{
+    uint16_t y = box.y + LISTING_Y0 + row*LISTING_DY;
+    tft.setTextColor (LABEL_COLOR);
+    tft.setCursor (box.x + LABEL_X, y);
+    tft.print (label);
+    tft.setTextColor (color);
+    tft.setCursor (box.x + VALUE_X, y);
+    tft.print (value);
+}
+
+/* draw the given report in the given box, dist is great circle angle from DE, rads.
+ */
+static void drawIonosonde (const SBox &box, const IonoReport &ir, float dist)
+{
+    prepPlotBox (box);
+
+    // title
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (TITLE_COLOR);
+    const char *title = xlate ("Ionosonde");
+    tft.setCursor (box.x + (box.w - getTextWidth(title))/2, box.y + TITLE_Y0);
+    tft.print (title);
+
+    // station and distance
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    char buf[50];
+    snprintf (buf, sizeof(buf), "%s %.0f %s", ir.name, distFromMiles(dist*ERAD_M), distUnits());
+    (void) maxStringW (buf, box.w-4);
+    tft.setTextColor (VALUE_COLOR);
+    tft.setCursor (box.x + (box.w - getTextWidth(buf))/2, box.y + STATION_Y0);
+    tft.print (buf);
+
+    // values, with the model at the station and how far the measurement is from it
+    snprintf (buf, sizeof(buf), "%.1f MHz", ir.mufd);
+    drawIonoRow (box, 0, "MUF", MUF_COLOR, buf);
+    float model = modelMUF (ir.ll);
+    snprintf (buf, sizeof(buf), "%.1f %+.1f", model, ir.mufd - model);
+    drawIonoRow (box, 1, "Model", VALUE_COLOR, buf);
+    snprintf (buf, sizeof(buf), "%.1f MHz", ir.fof2);
+    drawIonoRow (box, 2, "foF2", VALUE_COLOR, buf);
+    snprintf (buf, sizeof(buf), "%.0f km", ir.hmf2);
+    drawIonoRow (box, 3, "hmF2", VALUE_COLOR, buf);
+
+    // age, stale reports stand out
+    long age = now() - ir.t;
+    if (age < 3600)
+        snprintf (buf, sizeof(buf), "%ld mins", age/60);
+    else
+        snprintf (buf, sizeof(buf), "%.1f hrs", age/3600.0F);
+    drawIonoRow (box, 4, "Age", age < IONO_MAXAGE ? VALUE_COLOR : RA8875_YELLOW, buf);
+
+    // note MUF path
+    tft.setTextColor (LABEL_COLOR);
+    tft.setCursor (box.x + LABEL_X, box.y + LISTING_Y0 + 5*LISTING_DY);
+    tft.print (F("MUF for 3000 km path"));
+}
+
+/* retrieve the latest ionosonde reports and pick the one nearest DE, preferring the nearest that has
+ * reported recently, else the nearest of all. save it for getIonosondeCorrection().
+ * return whether all ok with report in ir and its great circle angle from DE in dist, else why not in ynot.
+ */
+static bool retrieveIonosonde (IonoReport &ir, float &dist, const char *&ynot)
+{
+    WiFiClient iono_client;
+    char line[120];
+    bool ok = false;
+
+    Serial.println (iono_page);
+    resetWatchdog();
+    if (wifiOk() && iono_client.connect (svr_host, HTTPPORT)) {
+
+        resetWatchdog();
+        updateClocks(false);
+
+        // fetch page
+        httpGET (iono_client, svr_host, iono_page);
+        if (!httpSkipHeader (iono_client)) {
+            ynot = _FX("Ionosonde header short");
+            goto out;
+        }
+
+        // find nearest fresh and nearest overall
+        IonoReport fresh, any, r;
+        float fresh_dist = 1e10, any_dist = 1e10;
+        time_t t0 = now();
+        while (getTCPLine (iono_client, line, sizeof(line), NULL)) {
+            if (line[0] == '#' || !crackIonoLine (line, r))
+                continue;
+            float d, bear;
+            propDEDXPath (false, r.ll, &d, &bear);
+            if (d < any_dist) {
+                any = r;
+                any_dist = d;
+            }
+            if (t0 - r.t < IONO_MAXAGE && d < fresh_dist) {
+                fresh = r;
+                fresh_dist = d;
+            }
+        }
+
+        if (fresh_dist < 1e10) {
+            ir = fresh;
+            dist = fresh_dist;
+            ok = true;
+        } else if (any_dist < 1e10) {
+            ir = any;
+            dist = any_dist;
+            ok = true;
+        } else
+            ynot = _FX("No ionosonde data");
+
+    } else
+        ynot = _FX("Ionosonde connection failed");
+
+    if (ok) {
+        float model = modelMUF (ir.ll);
+        last_ir = ir;
+        last_ratio = model > 0 ? ir.mufd / model : 1;
+        Serial.printf (_FX("Ionosonde: %s MUF %g model %g%s\n"), ir.code, ir.mufd, model,
+                                        now() - ir.t < IONO_MAXAGE ? "" : " but stale");
+    }
+
+out:
+    iono_client.stop();
+    printFreeHeap (F("retrieveIonosonde"));
+    return (ok);
+}
+
+/* retrieve the latest ionosonde reports and display the one nearest DE in the given box.
+ * return whether all ok.
+ */
+bool updateIonosonde (const SBox &box)
+{
+    IonoReport ir;
+    float dist;
+    const char *ynot;
+    if (!retrieveIonosonde (ir, dist, ynot)) {
+        plotMessage (box, RA8875_RED, ynot);
+        return (false);
+    }
+
+    drawIonosonde (box, ir, dist);
+    return (true);
+}
+
+/* retrieve the latest ionosonde reports just to keep the correction fresh, as when no pane shows them.
+ * return whether all ok, else why not in ynot.
+ */
+bool refreshIonosonde (const char *&ynot)
+{
+    IonoReport ir;
+    float dist;
+    return (retrieveIonosonde (ir, dist, ynot));
+}
+
+/* pass back the ratio of measured to modeled MUF at the ionosonde nearest DE, where it is and the age of
+ * its report in secs. return whether any report is known.
+ */
+bool getIonosondeCorrection (float &ratio, LatLong &ll, time_t &age)
+{
+    if (last_ir.t == 0)
+        return (false);
+    ratio = last_ratio;
+    ll = last_ir.ll;
+    age = now() - last_ir.t;
+    return (true);
+}
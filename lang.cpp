@@ -0,0 +1,245 @@
+/* show on-screen text in the user's language using a gettext-style catalog.
+ *
+ * text to be translated is passed through xlate(), which returns the translation from the catalog for
+ * the chosen language, else the English unchanged. A catalog is our_dir/lang/CODE.po, eg lang/de.po,
+ * in the usual PO format so the common translation tools can edit it:
+ *
+ *   # comment
+ *   msgid "Best Bands"
+ *   msgstr "Beste Baender"
+ *
+ * strings may continue on following lines that contain only another quoted string, and may use \" \\ \n
+ * escapes. Translations are folded to ASCII with foldUTF8() because our fonts can draw nothing else.
+ * A translation must contain the same printf conversions in the same order as its English so it is safe
+ * to use as a format; those that do not are ignored. English strings without a translation are logged
+ * once so translators can find them. The language is set with set_language and kept in NV; "en" means
+ * no catalog. Only UNIX has room for a catalog; ESP always shows English.
+ *
+ * N.B. so far only pane titles, axis labels and pane status lines pass through xlate(). Setup, menus, map
+ * labels and alert messages are still English; alert text is also parsed by scripts and web socket clients
+ * so it should stay English and be translated, if at all, by whatever shows it.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#include <errno.h>
+
+#define LANG_DIR        "lang"                  // dir within our_dir containing catalogs
+#define MAX_POLINE      1024                    // longest catalog line
+
+// one catalog entry
+typedef struct {
+    char *english;                              // msgid, malloced
+    char *local;                                // msgstr, malloced
+} Translation;
+
+static Translation *catalog;                    // malloced, sorted by english
+static int n_catalog;                           // n used in catalog[]
+static char lang_code[NV_LANGUAGE_LEN];         // current language code, "en" if none
+
+
+/* qsort and bsearch style function to compare two Translations by english
+ */
+static int translationQS (const void *p1, const void *p2)
+{
+    return (strcmp (((Translation *)p1)->english, ((Translation *)p2)->english));
+}
+
+/* return whether the printf conversions in a and b are the same and in the same order
+ */
+static bool sameConversions (const char *a, const char *b)
+{
+    while (true) {
+        a = strchr (a, '%');
+        b = strchr (b, '%');
+        if (!a || !b)
+            return (!a && !b);
+        size_t al = strcspn (a+1, "diouxXeEfgGcsp%") + 2;
+        size_t bl = strcspn (b+1, "diouxXeEfgGcsp%") + 2;
+        if (al != bl || strncmp (a, b, al) != 0)
+            return (false);
+        a += al;
+        b += bl;
+    }
+}
+
+/* append the quoted string starting at or after line to str, decoding escapes.
+ * return whether line contained a quoted string.
+ */
+static bool appendPOString (std::string &str, const char *line)
+{
+    const char *q = strchr (line, '"');
+    if (!q)
+        return (false);
+    for (const char *lp = q+1; *lp && *lp != '"'; lp++) {
+        if (*lp == '\\' && lp[1]) {
+            lp++;
+            str += *lp == 'n' ? '\n' : (*lp == 't' ? '\t' : *lp);
+        } else
+            str += *lp;
+    }
+    return (true);
+}
+
+/* add one msgid and msgstr pair to catalog[], if sensible
+ */
+static void addTranslation (const std::string &english, const std::string &local, const char *fn, int lineno)
+{
+    // the header has an empty msgid and entries not yet translated have empty msgstr
+    if (english.empty() || local.empty())
+        return;
+
+    if (!sameConversions (english.c_str(), local.c_str())) {
+        Serial.printf (_FX("Lang: %s:%d: conversions differ from English, ignoring\n"), fn, lineno);
+        return;
+    }
+
+    catalog = (Translation *) realloc (catalog, (n_catalog+1)*sizeof(Translation));
+    if (!catalog)
+        fatalError (_FX("No memory for %d translations"), n_catalog+1);
+    Translation &t = catalog[n_catalog++];
+    t.english = strdup (english.c_str());
+    t.local = strdup (local.c_str());
+    if (!t.english || !t.local)
+        fatalError (_FX("No memory for translation"));
+    foldUTF8 (t.local);
+}
+
+/* discard catalog[]
+ */
+static void freeCatalog()
+{
+    for (int i = 0; i < n_catalog; i++) {
+        free (catalog[i].english);
+        free (catalog[i].local);
+    }
+    free (catalog);
+    catalog = NULL;
+    n_catalog = 0;
+}
+
+/* load the catalog for the given language code into catalog[].
+ * return whether the file could be read.
+ */
+static bool loadCatalog (const char *code)
+{
+    std::string path = our_dir + LANG_DIR + "/" + code + ".po";
+    FILE *fp = fopen (path.c_str(), "r");
+    if (!fp) {
+        Serial.printf (_FX("Lang: %s: %s\n"), path.c_str(), strerror(errno));
+        return (false);
+    }
+
+    freeCatalog();
+
+    // each msgid "..." is followed by msgstr "...", either may continue on more "..." lines
+    char line[MAX_POLINE];
+    std::string english, local;
+    std::string *cont = NULL;                   // where a continuation line goes, if any
+    int lineno = 0, id_lineno = 0;
+    while (fgets (line, sizeof(line), fp)) {
+        lineno++;
+        const char *lp = line + strspn (line, " \t");
+        if (strncmp (lp, "msgid", 5) == 0) {
+            addTranslation (english, local, path.c_str(), id_lineno);
+            english.clear();
+            local.clear();
+            cont = appendPOString (english, lp) ? &english : NULL;
+            id_lineno = lineno;
+        } else if (strncmp (lp, "msgstr", 6) == 0) {
+            cont = appendPOString (local, lp) ? &local : NULL;
+        } else if (*lp == '"' && cont) {
+            appendPOString (*cont, lp);
+        } else
+            cont = NULL;
+    }
+    addTranslation (english, local, path.c_str(), id_lineno);
+    fclose (fp);
+
+    qsort (catalog, n_catalog, sizeof(Translation), translationQS);
+    Serial.printf (_FX("Lang: %d %s translations\n"), n_catalog, code);
+    return (true);
+}
+
+/* return the translation of the given English text, else the English itself.
+ */
+const char *xlate (const char *english)
+{
+    if (n_catalog == 0)
+        return (english);
+
+    Translation key;
+    key.english = (char *) english;
+    Translation *t = (Translation *) bsearch (&key, catalog, n_catalog, sizeof(Translation), translationQS);
+    if (t)
+        return (t->local);
+
+    // add the English with itself as translation so it is logged just once
+    Serial.printf (_FX("Lang: untranslated \"%s\"\n"), english);
+    addTranslation (english, english, "untranslated", 0);
+    qsort (catalog, n_catalog, sizeof(Translation), translationQS);
+    return (english);
+}
+
+/* set the language by code, eg de, or en for none.
+ * return whether its catalog could be loaded.
+ */
+bool setLanguage (const char *code)
+{
+    if (strlen (code) >= sizeof(lang_code) || strspn (code, "abcdefghijklmnopqrstuvwxyz_ABCDEFGHIJKLMNOPQRSTUVWXYZ")
+                                                != strlen (code))
+        return (false);
+
+    if (strcmp (code, "en") == 0)
+        freeCatalog();
+    else if (!loadCatalog (code))
+        return (false);
+
+    strcpy (lang_code, code);
+    NVWriteString (NV_LANGUAGE, lang_code);
+    return (true);
+}
+
+/* return the current language code
+ */
+const char *getLanguage()
+{
+    return (lang_code);
+}
+
+/* load the language saved in NV, if any
+ */
+void initLanguage()
+{
+    char code[NV_LANGUAGE_LEN];
+    if (!NVReadString (NV_LANGUAGE, code) || !setLanguage (code))
+        (void) setLanguage ("en");
+}
+
+#else // !_IS_UNIX
+
+/* no room for catalogs on ESP
+ */
+const char *xlate (const char *english)
+{
+    return (english);
+}
+
+bool setLanguage (const char *code)
+{
+    return (strcmp (code, "en") == 0);
+}
+
+const char *getLanguage()
+{
+    return ("en");
+}
+
+void initLanguage()
+{
+}
+
+#endif // _IS_UNIX
@@ -0,0 +1,161 @@
+/* local K index from a nearby magnetometer, shown beside planetary Kp because high latitude stations often
+ * see disturbances the planetary average misses.
+ *
+ * the value comes from either or both of:
+ *   a plain http page set with set_localk?url=, polled every LOCALK_REFRESH. the last line whose final
+ *     field is a number 0 .. 9 is the K index, so a bare "3" or "2026-10-16 12:00 3" both work.
+ *   pushes with set_localk?K=n&station=name, eg from a bridge subscribed to a personal station over MQTT.
+ * the most recent from either wins.
+ */
+
+#include "HamClock.h"
+
+
+#define LOCALK_REFRESH  (15*60)                 // url poll period, secs
+#define LOCALK_MAXAGE   (3*3600)                // K covers 3 hours, ignore if older, secs
+
+static char localk_url[NV_LOCALKURL_LEN];       // polled page, empty if none
+static float localk_value;                      // latest local K
+static time_t localk_time;                      // when localk_value arrived, 0 if never
+static char localk_station[20];                 // where it came from
+static time_t localk_polled;                    // when localk_url was last polled, 0 to poll asap
+
+
+/* read the url from NV once
+ */
+static void loadLocalK()
+{
+    static bool loaded;
+    if (loaded)
+        return;
+    loaded = true;
+
+    if (!NVReadString (NV_LOCALKURL, localk_url)) {
+        localk_url[0] = '\0';
+        NVWriteString (NV_LOCALKURL, localk_url);
+    }
+}
+
+/* note a new local K value from the given station
+ */
+static void noteLocalK (float k, const char *station)
+{
+    localk_value = k;
+    localk_time = now();
+    snprintf (localk_station, sizeof(localk_station), "%.*s", (int)sizeof(localk_station)-1, station);
+    Serial.printf (_FX("LocalK: %g from %s\n"), k, localk_station);
+}
+
+/* fetch localk_url and note its K value if found
+ */
+static void pollLocalK()
+{
+    // crack http://host[:port]/path
+    char host[NV_LOCALKURL_LEN];
+    int port = 80;
+    const char *hp = localk_url + 7;
+    const char *slash = strchr (hp, '/');
+    size_t hl = slash ? (size_t)(slash - hp) : strlen (hp);
+    const char *path = slash ? slash : "/";
+    snprintf (host, sizeof(host), "%.*s", (int)hl, hp);
+    char *colon = strchr (host, ':');
+    if (colon) {
+        port = atoi (colon+1);
+        *colon = '\0';
+    }
+
+    WiFiClient lk_client;
+    Serial.println (localk_url);
+    resetWatchdog();
+    if (wifiOk() && lk_client.connect (host, port)) {
+        updateClocks(false);
+        httpGET (lk_client, host, path);
+        if (!httpSkipHeader (lk_client)) {
+            Serial.println (F("LocalK: header short"));
+        } else {
+            // last line ending with a sensible number wins
+            char line[100];
+            float k = -1;
+            while (getTCPLine (lk_client, line, sizeof(line), NULL)) {
+                char *last = strrchr (line, ' ');
+                char *endp;
+                float v = strtof (last ? last+1 : line, &endp);
+                if (endp != (last ? last+1 : line) && *endp == '\0' && v >= 0 && v <= 9)
+                    k = v;
+            }
+            if (k >= 0)
+                noteLocalK (k, host);
+            else
+                Serial.println (F("LocalK: no K value found"));
+        }
+    } else
+        Serial.printf (_FX("LocalK: %s connection failed\n"), host);
+    lk_client.stop();
+}
+
+/* called often to poll the url, if any, when due
+ */
+void checkLocalK()
+{
+    loadLocalK();
+
+    if (localk_url[0] && !isOffline() && (!localk_polled || now() >= localk_polled + LOCALK_REFRESH)) {
+        localk_polled = now();
+        pollLocalK();
+    }
+}
+
+/* set the url to poll, or "off" for none.
+ * return whether sensible.
+ */
+bool setLocalKURL (const char *url)
+{
+    loadLocalK();
+
+    if (strcmp (url, "off") == 0) {
+        localk_url[0] = '\0';
+        NVWriteString (NV_LOCALKURL, localk_url);
+        return (true);
+    }
+
+    if (strncmp (url, "http://", 7) != 0 || strlen (url) <= 7 || strchr (url, ' ')
+                                || strlen (url) >= NV_LOCALKURL_LEN)
+        return (false);
+
+    strcpy (localk_url, url);
+    NVWriteString (NV_LOCALKURL, localk_url);
+    localk_polled = 0;                          // poll new source right away
+    return (true);
+}
+
+/* return the url being polled, empty if none
+ */
+const char *getLocalKURL()
+{
+    loadLocalK();
+    return (localk_url);
+}
+
+/* accept a local K value pushed from the given station.
+ * return whether sensible.
+ */
+bool setLocalK (float k, const char *station)
+{
+    if (k < 0 || k > 9)
+        return (false);
+    noteLocalK (k, station && station[0] ? station : "push");
+    return (true);
+}
+
+/* pass back the latest local K, its age and where it came from.
+ * return false if none or too old to mean anything.
+ */
+bool getLocalK (float &k, time_t &age, const char **station)
+{
+    if (!localk_time || now() - localk_time > LOCALK_MAXAGE)
+        return (false);
+    k = localk_value;
+    age = now() - localk_time;
+    *station = localk_station;
+    return (true);
+}
@@ -0,0 +1,252 @@
+/* runtime control of the layers drawn on the earth map: the opacity of each and the order in which
+ * the overlays are stacked above the night shading. Only on UNIX systems because ESP draws the map and
+ * its symbols row by row with no canvas to blend into.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#define OPAC_MAX        10                      // opacity units per fully opaque, ie, tenths
+#define OPAC_BITS       4                       // NV bits per opacity
+#define ORDER_BITS      3                       // NV bits per overlay order entry
+
+// hot keys to step down opacity of each layer: Shift+1 .. Shift+6 on US keyboards
+static const char opac_keys[MAPL_N] = { '!', '@', '#', '$', '%', '^' };
+
+// layer names, N.B. must be in same order as MapLayer
+static const char *layer_names[MAPL_N] = {
+    "Night",
+    "Grid",
+    "Path",
+    "Sat",
+    "Symbols",
+    "Aurora",
+};
+
+static uint8_t layer_opac[MAPL_N];              // opacity of each MapLayer, 0 .. OPAC_MAX
+static uint8_t overlay_order[MAPL_NOVER];       // MapLayer overlays in drawing order from the bottom
+static bool layers_init;                        // set once loaded from NV
+
+
+/* save layer_opac[] and overlay_order[] in NV
+ */
+static void saveMapLayers()
+{
+    uint32_t opac = 0;
+    for (int i = 0; i < MAPL_N; i++)
+        opac |= (uint32_t)layer_opac[i] << (i*OPAC_BITS);
+    NVWriteUInt32 (NV_MAPLAYEROPAC, opac);
+
+    uint16_t order = 0;
+    for (int i = 0; i < MAPL_NOVER; i++)
+        order |= (uint16_t)overlay_order[i] << (i*ORDER_BITS);
+    NVWriteUInt16 (NV_MAPLAYERORDER, order);
+}
+
+/* set the default layers: all opaque except aurora which must be asked for, overlays in original fixed
+ * order with aurora at the bottom.
+ */
+static void defaultMapLayers()
+{
+    for (int i = 0; i < MAPL_N; i++)
+        layer_opac[i] = OPAC_MAX;
+    layer_opac[MAPL_AURORA] = 0;
+    overlay_order[0] = MAPL_AURORA;
+    for (int i = 1; i < MAPL_NOVER; i++)
+        overlay_order[i] = MAPL_GRID + i - 1;
+}
+
+/* load layer_opac[] and overlay_order[] from NV if not already, else set and save defaults.
+ */
+static void loadMapLayers()
+{
+    if (layers_init)
+        return;
+    layers_init = true;
+
+    uint32_t opac;
+    uint16_t order;
+    if (!NVReadUInt32 (NV_MAPLAYEROPAC, &opac) || !NVReadUInt16 (NV_MAPLAYERORDER, &order)) {
+        defaultMapLayers();
+        saveMapLayers();
+        return;
+    }
+
+    // layers saved before aurora existed have it off and no place in the order, so put it at the bottom
+    if (((order >> ((MAPL_NOVER-1)*ORDER_BITS)) & ((1 << ORDER_BITS) - 1)) == MAPL_NIGHT) {
+        order = (order << ORDER_BITS) | MAPL_AURORA;
+        opac &= ~(((1UL << OPAC_BITS) - 1) << (MAPL_AURORA*OPAC_BITS));
+    }
+
+    // unpack, insisting each opacity is in range and order has each overlay exactly once
+    uint8_t seen = 0;
+    for (int i = 0; i < MAPL_N; i++) {
+        layer_opac[i] = (opac >> (i*OPAC_BITS)) & ((1 << OPAC_BITS) - 1);
+        if (layer_opac[i] > OPAC_MAX)
+            seen = 0xff;
+    }
+    for (int i = 0; i < MAPL_NOVER; i++) {
+        overlay_order[i] = (order >> (i*ORDER_BITS)) & ((1 << ORDER_BITS) - 1);
+        if (overlay_order[i] == MAPL_NIGHT || overlay_order[i] >= MAPL_N)
+            seen = 0xff;
+        else
+            seen |= 1 << overlay_order[i];
+    }
+    if (seen != ((1 << MAPL_N) - 2)) {
+        defaultMapLayers();
+        saveMapLayers();
+    }
+}
+
+/* return the MapLayer with the given name, ignoring case, else MAPL_N
+ */
+static MapLayer findMapLayer (const char *name, size_t len)
+{
+    for (int i = 0; i < MAPL_N; i++)
+        if (strlen (layer_names[i]) == len && strncasecmp (name, layer_names[i], len) == 0)
+            return ((MapLayer)i);
+    return (MAPL_N);
+}
+
+/* return the name of the given layer
+ */
+const char *getMapLayerName (MapLayer ml)
+{
+    return (ml < MAPL_N ? layer_names[ml] : "?");
+}
+
+/* return the opacity of the given layer, 0 .. 1
+ */
+float getMapLayerOpacity (MapLayer ml)
+{
+    loadMapLayers();
+    return (ml < MAPL_N ? (float)layer_opac[ml]/OPAC_MAX : 1.0F);
+}
+
+/* return the i'th overlay in drawing order, 0 is drawn first just above the night shading.
+ */
+MapLayer getMapOverlay (int i)
+{
+    loadMapLayers();
+    return ((MapLayer)overlay_order[i]);
+}
+
+/* set the opacity of the named layer, percent 0 .. 100 rounded to the nearest 10, then save and
+ * restart the map if changed.
+ * return whether name and percent are valid.
+ */
+bool setMapLayerOpacity (const char *name, int percent)
+{
+    loadMapLayers();
+
+    MapLayer ml = findMapLayer (name, strlen(name));
+    if (ml == MAPL_N || percent < 0 || percent > 100)
+        return (false);
+
+    uint8_t new_opac = (percent*OPAC_MAX + 50)/100;
+    if (new_opac != layer_opac[ml]) {
+        layer_opac[ml] = new_opac;
+        saveMapLayers();
+        initEarthMap();
+    }
+
+    return (true);
+}
+
+/* set the overlay drawing order from a comma-separated list of overlay names, bottom first, then
+ * save and restart the map if changed. Overlays not listed keep their relative order above those listed.
+ * return whether all names are valid overlays listed at most once.
+ */
+bool setMapOverlayOrder (const char *names)
+{
+    loadMapLayers();
+
+    uint8_t new_order[MAPL_NOVER];
+    uint8_t seen = 0;
+    int n_new = 0;
+
+    // collect each listed overlay
+    while (*names) {
+        const char *comma = strchr (names, ',');
+        size_t len = comma ? (size_t)(comma - names) : strlen (names);
+        MapLayer ml = findMapLayer (names, len);
+        if (ml == MAPL_N || ml == MAPL_NIGHT || (seen & (1 << ml)))
+            return (false);
+        seen |= 1 << ml;
+        new_order[n_new++] = ml;
+        names += len;
+        if (*names == ',')
+            names++;
+    }
+
+    // append the rest in their current order
+    for (int i = 0; i < MAPL_NOVER; i++)
+        if (!(seen & (1 << overlay_order[i])))
+            new_order[n_new++] = overlay_order[i];
+
+    if (memcmp (new_order, overlay_order, sizeof(overlay_order)) != 0) {
+        memcpy (overlay_order, new_order, sizeof(overlay_order));
+        saveMapLayers();
+        initEarthMap();
+    }
+
+    return (true);
+}
+
+/* called with each keyboard char to step down the opacity of a layer if it is one of opac_keys[],
+ * wrapping from invisible back to fully opaque.
+ */
+void checkMapLayerKey (char kbc)
+{
+    if (!kbc)
+        return;
+
+    for (int i = 0; i < MAPL_N; i++) {
+        if (kbc == opac_keys[i]) {
+            loadMapLayers();
+            int percent = layer_opac[i] > 0 ? (layer_opac[i]-1)*100/OPAC_MAX : 100;
+            (void) setMapLayerOpacity (layer_names[i], percent);
+            Serial.printf (_FX("Map layer %s opacity now %d%%\n"), layer_names[i], percent);
+            break;
+        }
+    }
+}
+
+/* called before drawing any part of the given layer from anywhere, not just the full map redraw.
+ * return false if the layer is hidden, else set its opacity for subsequent drawing, save the prior
+ * opacity in *prev and return true. N.B. call endMapLayer(*prev) when finished.
+ */
+bool beginMapLayer (MapLayer ml, float *prev)
+{
+    float opacity = getMapLayerOpacity (ml);
+    if (opacity == 0)
+        return (false);
+    *prev = tft.getDrawOpacity();
+    tft.setDrawOpacity (opacity);
+    return (true);
+}
+
+/* called after drawing a layer for which beginMapLayer() returned true
+ */
+void endMapLayer (float prev)
+{
+    tft.setDrawOpacity (prev);
+}
+
+#else // !_IS_UNIX
+
+bool beginMapLayer (MapLayer ml, float *prev)
+{
+    (void) ml;
+    *prev = 1;
+    return (true);
+}
+
+void endMapLayer (float prev)
+{
+    (void) prev;
+}
+
+#endif // _IS_UNIX
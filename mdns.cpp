@@ -0,0 +1,148 @@
+/* advertise our web server with mDNS/DNS-SD as _hamclock._tcp so phones and other shack computers can
+ * find it without knowing our IP. TXT records tell clients what is available and where.
+ *
+ * ESP uses the mDNS responder in the core library. On UNIX systems the host normally already runs a
+ * responder, avahi or mDNSResponder, so rather than compete with it for port 5353 we keep its command
+ * line publishing tool running for as long as we are.
+ */
+
+#include "HamClock.h"
+
+#define MDNS_SERVICE    "hamclock"              // service name, without leading _
+#define MDNS_PROTO      "tcp"                   // service protocol, without leading _
+
+
+#if defined(_IS_ESP8266)
+
+#include <ESP8266mDNS.h>
+
+static bool mdns_ok;                            // set once running
+
+/* start advertising, call once after the web server is running
+ */
+void initMDNS()
+{
+    // host name from call, mDNS names may not contain /
+    char host[NV_CALLSIGN_LEN+10];
+    snprintf (host, sizeof(host), "hamclock-%s", getCallsign());
+    for (char *cp = host; *cp; cp++)
+        *cp = *cp == '/' ? '-' : tolower(*cp);
+
+    if (!MDNS.begin (host)) {
+        Serial.println (F("mDNS: start failed"));
+        return;
+    }
+    MDNS.addService (MDNS_SERVICE, MDNS_PROTO, svr_port);
+    MDNS.addServiceTxt (MDNS_SERVICE, MDNS_PROTO, "version", HC_VERSION);
+    MDNS.addServiceTxt (MDNS_SERVICE, MDNS_PROTO, "call", getCallsign());
+    mdns_ok = true;
+    Serial.printf (_FX("mDNS: advertising %s.local\n"), host);
+}
+
+/* called often from main loop to answer queries
+ */
+void checkMDNS()
+{
+    if (mdns_ok)
+        MDNS.update();
+}
+
+#else // !_IS_ESP8266
+
+#include <sys/wait.h>
+#if defined(_IS_LINUX)
+#include <sys/prctl.h>
+#endif
+
+#define MDNS_RETRY      300                     // secs to wait before restarting a publisher that quit
+
+static pid_t mdns_pid;                          // publisher process, 0 if not running
+static time_t mdns_restart;                     // when to restart publisher, 0 never
+
+
+/* kill the publisher so our service does not outlive us
+ */
+static void killMDNS()
+{
+    if (mdns_pid > 0)
+        kill (mdns_pid, SIGTERM);
+}
+
+/* start the publisher for our service.
+ */
+static void startMDNS()
+{
+    char name[NV_CALLSIGN_LEN+20], port[10], call[NV_CALLSIGN_LEN+10], version[20];
+    snprintf (name, sizeof(name), "HamClock %s", getCallsign());
+    snprintf (port, sizeof(port), "%d", svr_port);
+    snprintf (call, sizeof(call), "call=%s", getCallsign());
+    snprintf (version, sizeof(version), "version=%s", HC_VERSION);
+    const char *type = "_" MDNS_SERVICE "._" MDNS_PROTO;
+
+#if defined(__APPLE__)
+    const char *argv[] = {"dns-sd", "-R", name, type, "local", port, version, call, "path=/dashboard/",
+                                "api=/api/control/", "ws=/ws", NULL};
+#else
+    const char *argv[] = {"avahi-publish-service", name, type, port, version, call, "path=/dashboard/",
+                                "api=/api/control/", "ws=/ws", NULL};
+#endif
+
+    pid_t pid = fork();
+    if (pid < 0) {
+        Serial.printf (_FX("mDNS: fork(2): %s\n"), strerror(errno));
+        mdns_restart = now() + MDNS_RETRY;
+        return;
+    }
+
+    if (pid == 0) {
+        // child: die with us if possible, quiet, then become the publisher
+#if defined(_IS_LINUX)
+        prctl (PR_SET_PDEATHSIG, SIGTERM);
+#endif
+        int null_fd = open ("/dev/null", O_RDWR);
+        dup2 (null_fd, 0);
+        dup2 (null_fd, 1);
+        dup2 (null_fd, 2);
+        for (int fd = 3; fd < 1024; fd++)
+            close (fd);
+        execvp (argv[0], (char * const *)argv);
+        _exit(127);
+    }
+
+    mdns_pid = pid;
+    mdns_restart = 0;
+    Serial.printf (_FX("mDNS: %s advertising %s %s on port %s\n"), argv[0], name, type, port);
+}
+
+/* start advertising, call once after the web server is running
+ */
+void initMDNS()
+{
+    if (mdns_pid == 0) {
+        startMDNS();
+        atexit (killMDNS);
+    }
+}
+
+/* called often from main loop to notice if the publisher quit and restart it later.
+ * N.B. we only wait for our own pid so we don't steal status from others using fork.
+ */
+void checkMDNS()
+{
+    if (mdns_pid > 0) {
+        int wstatus;
+        if (waitpid (mdns_pid, &wstatus, WNOHANG) == mdns_pid) {
+            mdns_pid = 0;
+            if (WIFEXITED(wstatus) && WEXITSTATUS(wstatus) == 127) {
+                Serial.println (F("mDNS: publisher not installed, not advertising"));
+            } else {
+                Serial.printf (_FX("mDNS: publisher quit, retry in %d secs\n"), MDNS_RETRY);
+                mdns_restart = now() + MDNS_RETRY;
+            }
+        }
+    } else if (mdns_restart && now() >= mdns_restart) {
+        startMDNS();
+    }
+}
+
+#endif // _IS_ESP8266
@@ -190,7 +190,11 @@ void updateBeacons (bool erase_too, bool immediate, bool force)
                 erased_any = true;
             }
         } else if (overMap(bp->s) && !overRSS (bp->call_b)) {
-            drawBeacon (*bp);
+            float prev_opacity;
+            if (beginMapLayer (MAPL_SYMBOLS, &prev_opacity)) {
+                drawBeacon (*bp);
+                endMapLayer (prev_opacity);
+            }
         }
     }
 
@@ -314,3 +318,76 @@ void drawBeaconBox()
                   NCDXF_b.x+NCDXF_b.w, NCDXF_b.y+NCDXF_b.h, BORDER_COL);
 
 }
+
+/* draw the beacon now transmitting on each frequency in the given pane box, with its short path
+ * bearing and distance from DE. colors match the map symbols.
+ * nothing to fetch so always return true.
+ */
+bool updateBeaconPane (const SBox &box)
+{
+    #define _BP_TITLE_Y0    27              // title dy, match VOACAP title position
+    #define _BP_HEADING_Y0  38              // column headings y down from box top
+    #define _BP_LISTING_Y0  54              // first row y down from box top
+    #define _BP_LISTING_DY  17              // row spacing
+    #define _BP_FREQ_X      4               // column x offsets from box left
+    #define _BP_CALL_X      46
+    #define _BP_BEAR_X      96
+    #define _BP_DIST_X      124
+
+    static const char *freqs[5] = {"14.100", "18.110", "21.150", "24.930", "28.200"};
+    static const uint16_t colors[5] = {BCOL_14, BCOL_18, BCOL_21, BCOL_24, BCOL_28};
+
+    prepPlotBox (box);
+
+    // title
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (RA8875_GREEN);
+    const char *title = xlate ("NCDXF Beacons");
+    tft.setCursor (box.x + (box.w - getTextWidth(title))/2, box.y + _BP_TITLE_Y0);
+    tft.print (title);
+
+    // column headings
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    tft.setTextColor (GRAY);
+    tft.setCursor (box.x + _BP_FREQ_X, box.y + _BP_HEADING_Y0);
+    tft.print (F("MHz"));
+    tft.setCursor (box.x + _BP_CALL_X, box.y + _BP_HEADING_Y0);
+    tft.print (F("Call"));
+    tft.setCursor (box.x + _BP_BEAR_X, box.y + _BP_HEADING_Y0);
+    tft.print (F("Brg"));
+    tft.setCursor (box.x + _BP_DIST_X, box.y + _BP_HEADING_Y0);
+    tft.print (distUnits());
+
+    // same schedule as setBeaconStates()
+    time_t t = nowWO();
+    uint16_t s_10 = (60*minute(t) + second(t))/10;
+    for (int i = 0; i < 5; i++) {
+        NCDXFBeacon &nb = blist[(s_10-i+NBEACONS)%NBEACONS];
+        uint16_t y = box.y + _BP_LISTING_Y0 + i*_BP_LISTING_DY;
+        char buf[20];
+
+        LatLong ll;
+        ll.lat_d = nb.lat;
+        ll.lng_d = nb.lng;
+        normalizeLL (ll);
+        float dist, bear;
+        propDEDXPath (false, ll, &dist, &bear);
+        dist = distFromMiles (dist*ERAD_M);
+
+        tft.setTextColor (colors[i]);
+        tft.setCursor (box.x + _BP_FREQ_X, y);
+        tft.print (freqs[i]);
+        tft.setCursor (box.x + _BP_CALL_X, y);
+        tft.print (nb.call);
+
+        tft.setTextColor (RA8875_WHITE);
+        snprintf (buf, sizeof(buf), "%3.0f", rad2deg(bear));
+        tft.setCursor (box.x + _BP_BEAR_X, y);
+        tft.print (buf);
+        snprintf (buf, sizeof(buf), "%5.0f", dist);
+        tft.setCursor (box.x + _BP_DIST_X, y);
+        tft.print (buf);
+    }
+
+    return (true);
+}
@@ -0,0 +1,174 @@
+/* small network monitor that tells apart the ways the backend can be out of reach: no link, a failed DNS
+ * lookup, a captive portal that intercepts web requests, or a server that does not answer. the offline
+ * logic in wifi.cpp uses this so all the pane fetches pause together instead of each timing out on its own.
+ *
+ * the probe is a plain GET of a small backend page whose body is just the current version number: a
+ * captive portal answers with a redirect, 511 or a login page in its place. the probe runs in the main
+ * loop so on UNIX it uses short timeouts. when connections go through a proxy the proxy resolves the
+ * backend name, so the local DNS check is skipped.
+ */
+
+#include "HamClock.h"
+
+#if defined(_IS_UNIX)
+#include <netdb.h>
+#endif
+
+
+// probe page, small and always plain text
+static const char probe_page[] = "/ham/HamClock/version.pl";
+
+#define NETMON_OK_SECS  300             // check interval while all is well, secs
+#define NETMON_BAD_SECS 30              // check interval while something is wrong, secs
+#define NETMON_TO_SECS  3               // probe connect and read timeouts, secs
+
+static NetState net_state = NET_OK;     // result of most recent check, assume ok until we know better
+static time_t net_checked;              // when net_state was last set, 0 if never
+
+
+/* return whether svr_host can be resolved to an address
+ */
+static bool hostResolves()
+{
+#if defined(_IS_UNIX)
+    if (netUsesProxy (svr_host))
+        return (true);
+
+    struct addrinfo hints, *aip;
+    memset (&hints, 0, sizeof(hints));
+    hints.ai_family = AF_INET;
+    hints.ai_socktype = SOCK_STREAM;
+    int error = ::getaddrinfo (svr_host, NULL, &hints, &aip);
+    if (error) {
+        Serial.printf (_FX("NetMon: %s: %s\n"), svr_host, gai_strerror(error));
+        return (false);
+    }
+    freeaddrinfo (aip);
+    return (true);
+#else
+    IPAddress ip;
+    return (WiFi.hostByName (svr_host, ip) == 1);
+#endif
+}
+
+/* return whether line looks like a version number, eg 4.03 or 4.04b2
+ */
+static bool isVersionLine (const char *line)
+{
+    if (!isdigit (line[0]) || !strchr (line, '.'))
+        return (false);
+    for (const char *lp = line; *lp; lp++)
+        if (!isalnum (*lp) && *lp != '.')
+            return (false);
+    return (true);
+}
+
+/* GET probe_page and decide whether the reply came from the backend or from a captive portal.
+ */
+static NetState probeServer()
+{
+    WiFiClient probe_client;
+    NetState ns = NET_NOSERVER;
+    char line[100];
+
+#if defined(_IS_UNIX)
+    // don't stall the main loop for the usual timeouts
+    int connect_to = getNetConnectTimeout()/1000;
+    int read_to = getNetReadTimeout()/1000;
+    (void) setNetTimeouts (NETMON_TO_SECS, NETMON_TO_SECS);
+#endif
+
+    line[0] = '\0';
+    resetWatchdog();
+    if (!probe_client.connect (svr_host, HTTPPORT))
+        goto out;
+
+    httpGET (probe_client, svr_host, probe_page);
+
+    // status line must say 200, portals typically redirect or say 511 Network Authentication Required
+    int status;
+    if (!getTCPLine (probe_client, line, sizeof(line), NULL)
+                        || sscanf (line, "HTTP/%*s %d", &status) != 1) {
+        Serial.printf (_FX("NetMon: bogus reply: %s\n"), line);
+        ns = NET_PORTAL;
+        goto out;
+    }
+    if (status != 200) {
+        Serial.printf (_FX("NetMon: %s\n"), line);
+        ns = (status >= 300 && status < 400) || status == 511 ? NET_PORTAL : NET_NOSERVER;
+        goto out;
+    }
+
+    // skip the rest of the header
+    while (getTCPLine (probe_client, line, sizeof(line), NULL) && line[0] != '\0')
+        continue;
+
+    // body must be our version number, anything else such as a login page means a portal
+    if (!getTCPLine (probe_client, line, sizeof(line), NULL)) {
+        Serial.printf (_FX("NetMon: no probe body\n"));
+        ns = NET_NOSERVER;
+    } else if (isVersionLine (line))
+        ns = NET_OK;
+    else {
+        Serial.printf (_FX("NetMon: unexpected probe body: %.40s\n"), line);
+        ns = NET_PORTAL;
+    }
+
+out:
+    probe_client.stop();
+#if defined(_IS_UNIX)
+    (void) setNetTimeouts (connect_to, read_to);
+#endif
+    return (ns);
+}
+
+/* check the network now, in order of increasing reach, and return the result.
+ */
+NetState checkNetState()
+{
+    NetState prev_state = net_state;
+
+    if (!wifiOk())
+        net_state = NET_NOLINK;
+    else if (!hostResolves())
+        net_state = NET_DNS;
+    else
+        net_state = probeServer();
+    net_checked = now();
+
+    if (net_state != prev_state)
+        Serial.printf (_FX("NetMon: %s -> %s\n"), getNetStateName(prev_state), getNetStateName(net_state));
+    return (net_state);
+}
+
+/* called often to check the network now and then, more often while something is wrong.
+ * return the current state.
+ */
+NetState pollNetState()
+{
+    int interval = net_state == NET_OK ? NETMON_OK_SECS : NETMON_BAD_SECS;
+    if (!net_checked || now() >= net_checked + interval)
+        checkNetState();
+    return (net_state);
+}
+
+/* return the most recent network state without checking
+ */
+NetState getNetState()
+{
+    return (net_state);
+}
+
+/* return a short name for the given state
+ */
+const char *getNetStateName (NetState ns)
+{
+    switch (ns) {
+    case NET_OK:        return ("ok");
+    case NET_NOLINK:    return ("no link");
+    case NET_DNS:       return ("DNS failed");
+    case NET_PORTAL:    return ("captive portal");
+    case NET_NOSERVER:  return ("server unreachable");
+    default:            return ("?");
+    }
+}
@@ -107,6 +107,49 @@ static const uint8_t nv_sizes[NV_N] = {
     4,                          // NV_PANE3ROTSET
     1,                          // NV_DOY_ON
     2,                          // NV_ALARMCLOCK
+    2,                          // NV_ANTENNAHEADINGCOLOR
+    2,                          // NV_ANTENNABACKCOLOR
+    2,                          // NV_SCREENSHOTMINS
+    1,                          // NV_ALERTSTYLE
+    1,                          // NV_EVENTTIMES
+    2,                          // NV_SWXHISTDAYS
+    1,                          // NV_SATVISALERT
+    4,                          // NV_PANE1ROTSETHI
+    4,                          // NV_PANE2ROTSETHI
+    4,                          // NV_PANE3ROTSETHI
+    1,                          // NV_BPREGION
+    1,                          // NV_BPBAND
+    1,                          // NV_DXSPOTSORT
+    NV_WATCHCALLS_LEN,          // NV_WATCHCALLS
+    1,                          // NV_MAPCENTER
+    4,                          // NV_MAPLAYEROPAC
+    2,                          // NV_MAPLAYERORDER
+    1,                          // NV_SWDISPLAY
+    NV_WATCHACKCALL_LEN,        // NV_WATCHACKCALL
+    4,                          // NV_WATCHACKTIME
+    1,                          // NV_BR_WAKE
+    4,                          // NV_CONTESTSTART
+    4,                          // NV_CONTESTEND
+    NV_RIGHOST_LEN,             // NV_RIGHOST
+    2,                          // NV_RIGPORT
+    2,                          // NV_SWRALERT
+    NV_LANGUAGE_LEN,            // NV_LANGUAGE
+    1,                          // NV_TEMPUNITS
+    1,                          // NV_SPOTTIME
+    1,                          // NV_FREQFMT
+    4,                          // NV_DOPPLERHZ
+    2,                          // NV_DOPPLERPORT
+    NV_DOPPLERPUSH_LEN,         // NV_DOPPLERPUSH
+    NV_WEBSDR_LEN,              // NV_WEBSDR
+    2,                          // NV_SPOTAGE
+    1,                          // NV_ECLIPSEDAYS
+    1,                          // NV_OFFLINE
+    NV_QUIETHOURS_LEN,          // NV_QUIETHOURS
+    1,                          // NV_QUIETKEEP
+    1,                          // NV_AURORAALERT
+    NV_LOCALKURL_LEN,           // NV_LOCALKURL
+    1,                          // NV_SPOTMERGE
+    NV_DXALERTFILT_LEN,         // NV_DXALERTFILT
 };
 
 
@@ -0,0 +1,330 @@
+/* show the NOAA 27 day space weather outlook in a plot pane as a scrollable calendar of
+ * predicted 10.7 cm flux, planetary A index and largest Kp for each day.
+ *
+ * wifi.cpp schedules fetching along with all other data sources. We keep the table so switching back
+ * to the pane need not fetch it again, and save each good copy as a data bundle so a failed fetch can
+ * still show the last one, marked as stale.
+ */
+
+#include "HamClock.h"
+
+
+// server file containing the NOAA 27-day outlook table, 27DO.txt
+static const char outlook_page[] = "/ham/HamClock/NOAASpaceWX/27-day-outlook.txt";
+
+// layout
+#define TITLE_COLOR     RA8875_GREEN
+#define STALE_COLOR     RA8875_YELLOW   // title color when showing an old copy
+#define HEADING_COLOR   GRAY
+#define TITLE_Y0        27              // title dy, match VOACAP title position
+#define HEADING_Y0      34              // column headings y down from box top
+#define LISTING_Y0      47              // first day y down from box top
+#define LISTING_DY      11              // listing row separation
+#define LISTING_N       ((PLOTBOX_H - LISTING_Y0)/LISTING_DY)       // n rows visible at once
+#define DATE_X          4               // column x offsets from box left
+#define FLUX_X          64
+#define AP_X            96
+#define KP_X            126
+#define ARROW_X         146             // scroll arrows x offset from box left
+#define ARROW_W         8               // scroll arrow width
+#define ARROW_H         6               // scroll arrow height
+
+// one day's prediction
+typedef struct {
+    time_t date;                        // UTC midnight
+    int16_t flux;                       // 10.7 cm radio flux
+    int16_t ap;                         // planetary A index
+    int16_t kp;                         // largest Kp index
+} OutlookDay;
+
+#define MAX_OUTLOOK_DAYS 30             // room for 27 plus a few to spare
+static OutlookDay days[MAX_OUTLOOK_DAYS];
+static int n_days;                      // n used in days[]
+static int top_day;                     // index of days[] shown in first row
+static time_t days_fetched;             // when days[] was fetched, 0 if never
+static bool days_stale;                 // set if days[] is an old copy because fetching failed
+
+
+/* crack one line of the 27DO table, eg "2021 Jan 25     80           5          2".
+ * return whether line was a table entry and, if so, fill od.
+ */
+static bool crackOutlookLine (const char *line, OutlookDay &od)
+{
+    int yr, dy, flux, ap, kp;
+    char mon[4];
+    if (sscanf (line, "%d %3s %d %d %d %d", &yr, mon, &dy, &flux, &ap, &kp) != 6)
+        return (false);
+
+    // find month
+    int mo;
+    for (mo = 1; mo <= 12; mo++)
+        if (strncasecmp (mon, monthShortStr(mo), 3) == 0)
+            break;
+    if (mo > 12)
+        return (false);
+
+    tmElements_t tm;
+    memset (&tm, 0, sizeof(tm));
+    tm.Year = CalendarYrToTm (yr);
+    tm.Month = mo;
+    tm.Day = dy;
+    od.date = makeTime (tm);
+    od.flux = flux;
+    od.ap = ap;
+    od.kp = kp;
+    return (true);
+}
+
+/* crack each table line of the given 27DO text into days[], skipping comments, and set top_day to today.
+ * return number of days found.
+ */
+static int crackOutlook (const char *text)
+{
+    n_days = 0;
+    while (n_days < MAX_OUTLOOK_DAYS && *text) {
+        char line[100];
+        size_t ll = strcspn (text, "\n");
+        size_t lc = ll < sizeof(line) ? ll : sizeof(line)-1;
+        memcpy (line, text, lc);
+        line[lc] = '\0';
+        text += text[ll] ? ll + 1 : ll;
+        if (line[0] == ':' || line[0] == '#')
+            continue;
+        if (crackOutlookLine (line, days[n_days]))
+            n_days++;
+    }
+
+    // start with today
+    time_t t0 = now();
+    time_t today = t0 - t0 % SECSPERDAY;
+    for (top_day = 0; top_day < n_days-1 && days[top_day].date < today; top_day++)
+        continue;
+    if (top_day > n_days - LISTING_N)
+        top_day = n_days > LISTING_N ? n_days - LISTING_N : 0;
+
+    return (n_days);
+}
+
+/* return color to show the given flux, high is good
+ */
+static uint16_t fluxColor (int flux)
+{
+    if (flux >= 150)
+        return (RA8875_GREEN);
+    if (flux >= 100)
+        return (RA8875_YELLOW);
+    return (RA8875_RED);
+}
+
+/* return color to show the given A index, low is good
+ */
+static uint16_t apColor (int ap)
+{
+    if (ap < 10)
+        return (RA8875_GREEN);
+    if (ap < 30)
+        return (RA8875_YELLOW);
+    return (RA8875_RED);
+}
+
+/* return color to show the given Kp index, low is good
+ */
+static uint16_t kpColor (int kp)
+{
+    if (kp < 4)
+        return (RA8875_GREEN);
+    if (kp < 5)
+        return (RA8875_YELLOW);
+    return (RA8875_RED);
+}
+
+/* draw the days[] listing starting with top_day, with arrows showing whether there are more either way.
+ */
+static void drawOutlookListing (const SBox &box)
+{
+    // erase listing area, not the border
+    tft.fillRect (box.x+1, box.y+LISTING_Y0-1, box.w-2, box.h-LISTING_Y0, RA8875_BLACK);
+
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    for (int row = 0; row < LISTING_N && top_day + row < n_days; row++) {
+        const OutlookDay &od = days[top_day + row];
+        uint16_t y = box.y + LISTING_Y0 + row*LISTING_DY;
+        char buf[20];
+
+        // date, weekends stand out for planning contests
+        int wd = weekday (od.date);
+        tft.setTextColor (wd == 1 || wd == 7 ? RA8875_CYAN : RA8875_WHITE);
+        snprintf (buf, sizeof(buf), "%.2s %.3s %2d", dayShortStr(wd), monthShortStr(month(od.date)),
+                                                        day(od.date));
+        tft.setCursor (box.x + DATE_X, y);
+        tft.print (buf);
+
+        // values
+        tft.setTextColor (fluxColor (od.flux));
+        snprintf (buf, sizeof(buf), "%3d", od.flux);
+        tft.setCursor (box.x + FLUX_X, y);
+        tft.print (buf);
+
+        tft.setTextColor (apColor (od.ap));
+        snprintf (buf, sizeof(buf), "%3d", od.ap);
+        tft.setCursor (box.x + AP_X, y);
+        tft.print (buf);
+
+        tft.setTextColor (kpColor (od.kp));
+        snprintf (buf, sizeof(buf), "%d", od.kp);
+        tft.setCursor (box.x + KP_X, y);
+        tft.print (buf);
+    }
+
+    // scroll arrows
+    uint16_t ax = box.x + ARROW_X;
+    if (top_day > 0) {
+        uint16_t ay = box.y + LISTING_Y0;
+        tft.fillTriangle (ax, ay+ARROW_H, ax+ARROW_W, ay+ARROW_H, ax+ARROW_W/2, ay, RA8875_WHITE);
+    }
+    if (top_day + LISTING_N < n_days) {
+        uint16_t ay = box.y + LISTING_Y0 + (LISTING_N-1)*LISTING_DY;
+        tft.fillTriangle (ax, ay, ax+ARROW_W, ay, ax+ARROW_W/2, ay+ARROW_H, RA8875_WHITE);
+    }
+}
+
+/* draw the complete pane from days[]
+ */
+static void drawOutlook (const SBox &box)
+{
+    prepPlotBox (box);
+
+    // title, or its age if stale
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    char title[40];
+    if (days_stale) {
+        tft.setTextColor (STALE_COLOR);
+        snprintf (title, sizeof(title), _FX("Outlook %ldh old"), (long)(now() - days_fetched)/3600);
+    } else {
+        tft.setTextColor (TITLE_COLOR);
+        strcpy (title, _FX("27 Day Outlook"));
+    }
+    tft.setCursor (box.x + (box.w - getTextWidth(title))/2, box.y + TITLE_Y0);
+    tft.print (title);
+
+    // column headings
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    tft.setTextColor (HEADING_COLOR);
+    tft.setCursor (box.x + DATE_X, box.y + HEADING_Y0);
+    tft.print (F("Date UTC"));
+    tft.setCursor (box.x + FLUX_X, box.y + HEADING_Y0);
+    tft.print (F("SFI"));
+    tft.setCursor (box.x + AP_X + 6, box.y + HEADING_Y0);
+    tft.print (F("Ap"));
+    tft.setCursor (box.x + KP_X, box.y + HEADING_Y0);
+    tft.print (F("Kp"));
+
+    drawOutlookListing (box);
+}
+
+/* fetch a fresh copy of the outlook into days[] and save it for next time.
+ * return whether ok, else why not in ynot.
+ */
+static bool fetchOutlook (char ynot[], size_t ynot_len)
+{
+    WiFiClient outlook_client;
+    bool ok = false;
+
+    Serial.println (outlook_page);
+    resetWatchdog();
+    if (wifiOk() && outlook_client.connect (svr_host, HTTPPORT)) {
+
+        resetWatchdog();
+        updateClocks(false);
+
+        // fetch page
+        httpGET (outlook_client, svr_host, outlook_page);
+        if (!httpSkipHeader (outlook_client)) {
+            snprintf (ynot, ynot_len, _FX("Outlook header short"));
+            goto out;
+        }
+
+        // collect whole file so we can save it too
+        std::string text;
+        char line[100];
+        while (getTCPLine (outlook_client, line, sizeof(line), NULL)) {
+            text += line;
+            text += '\n';
+        }
+        if (crackOutlook (text.c_str()) == 0) {
+            snprintf (ynot, ynot_len, _FX("Outlook has no data"));
+            goto out;
+        }
+        Serial.printf (_FX("Outlook: found %d days\n"), n_days);
+        saveDataBundle ("outlook", text.c_str(), text.size());
+        days_fetched = now();
+        ok = true;
+
+    } else
+        snprintf (ynot, ynot_len, _FX("Outlook connection failed"));
+
+out:
+    outlook_client.stop();
+    printFreeHeap (F("fetchOutlook"));
+    return (ok);
+}
+
+/* display the 27 day outlook in the given box, first fetching a fresh copy if fetch.
+ * if fetching fails show the previous copy, from memory or the saved bundle, marked as stale.
+ * return whether a fresh copy is showing.
+ */
+bool updateOutlook (const SBox &box, bool fetch)
+{
+    char ynot[50];
+
+    if (fetch) {
+        days_stale = !fetchOutlook (ynot, sizeof(ynot));
+        if (days_stale)
+            noteFetchDetail (ynot);
+        if (days_stale && n_days == 0) {
+            time_t fetched;
+            char *text = loadDataBundle ("outlook", fetched);
+            if (text) {
+                if (crackOutlook (text) > 0)
+                    days_fetched = fetched;
+                free (text);
+            }
+        }
+    } else if (n_days == 0)
+        return (false);
+
+    if (n_days == 0) {
+        plotMessage (box, RA8875_RED, ynot);
+        return (false);
+    }
+
+    drawOutlook (box);
+    return (!days_stale);
+}
+
+/* called when the given location is tapped below the title of the given box showing the outlook.
+ * scroll back a page if in upper half of listing, else forward a page.
+ * return whether tap was ours.
+ */
+bool checkOutlookTouch (const SCoord &s, const SBox &box)
+{
+    if (!inBox (s, box) || s.y < box.y + LISTING_Y0 || n_days == 0)
+        return (false);
+
+    int new_top = top_day;
+    if (s.y < box.y + LISTING_Y0 + (box.h - LISTING_Y0)/2)
+        new_top -= LISTING_N;
+    else
+        new_top += LISTING_N;
+    if (new_top > n_days - LISTING_N)
+        new_top = n_days - LISTING_N;
+    if (new_top < 0)
+        new_top = 0;
+
+    if (new_top != top_day) {
+        top_day = new_top;
+        drawOutlookListing (box);
+    }
+
+    return (true);
+}
@@ -0,0 +1,248 @@
+/* plan one upcoming pass of the current satellite for operators without CAT control: its az and el and
+ * the doppler corrected downlink, and optionally uplink, frequency every step from rise to set, so a
+ * handheld radio can be programmed with a channel per step ahead of time.
+ *
+ * get_passplan?pass=N&format=csv|json&down=MHz&up=MHz&step=secs
+ * where all are optional:
+ *   pass    1 for the next pass, 2 the one after and so on, as listed by get_satellite.txt; default 1
+ *   format  default csv
+ *   down    nominal downlink, default the doppler frequency set with set_doppler
+ *   up      nominal uplink, default none
+ *   step    seconds between rows, default 60
+ * UNIX only.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#define PLAN_MAXSTEPS   500                     // max rows in one plan
+#define PLAN_MINSTEP    10                      // smallest step, secs
+#define PLAN_MAXSTEP    600                     // largest step, secs
+#define PLAN_MAXSPEC    200                     // longest spec we accept, chars
+
+typedef enum {
+    PF_CSV,
+    PF_JSON,
+} PlanFormat;
+
+// what to plan
+typedef struct {
+    int pass;                                   // 1-based pass number
+    PlanFormat format;
+    uint32_t down_hz, up_hz;                    // nominal frequencies, Hz, 0 if none
+    int step;                                   // secs between rows
+} PlanSpec;
+
+
+/* crack one frequency in MHz into Hz.
+ * return whether sensible.
+ */
+static bool crackPlanMHz (const char *str, uint32_t &hz)
+{
+    char *endp;
+    double mhz = strtod (str, &endp);
+    if (endp == str || *endp != '\0' || mhz <= 0 || mhz > 4000)
+        return (false);
+    hz = (uint32_t) (mhz*1e6 + 0.5);
+    return (true);
+}
+
+/* crack a plan spec. if trouble put reason in errmsg and return false.
+ */
+static bool crackPlanSpec (const char *spec_str, PlanSpec &spec, char *errmsg, size_t errmsg_len)
+{
+    spec.pass = 1;
+    spec.format = PF_CSV;
+    uint16_t port;
+    char push[NV_DOPPLERPUSH_LEN];
+    (void) getDoppler (spec.down_hz, port, push);
+    spec.up_hz = 0;
+    spec.step = 60;
+
+    char copy[PLAN_MAXSPEC];
+    if (strlen (spec_str) >= sizeof(copy)) {
+        snprintf (errmsg, errmsg_len, "too long");
+        return (false);
+    }
+    strcpy (copy, spec_str);
+    char *save;
+    for (char *kw = strtok_r (copy, "&", &save); kw != NULL; kw = strtok_r (NULL, "&", &save)) {
+        char *eq = strchr (kw, '=');
+        if (!eq) {
+            snprintf (errmsg, errmsg_len, "%s is not name=value", kw);
+            return (false);
+        }
+        *eq++ = '\0';
+        if (strcmp (kw, "pass") == 0) {
+            spec.pass = atoi (eq);
+            if (spec.pass < 1) {
+                snprintf (errmsg, errmsg_len, "pass must be 1 or more");
+                return (false);
+            }
+        } else if (strcmp (kw, "format") == 0) {
+            if (strcmp (eq, "csv") == 0)
+                spec.format = PF_CSV;
+            else if (strcmp (eq, "json") == 0)
+                spec.format = PF_JSON;
+            else {
+                snprintf (errmsg, errmsg_len, "format must be csv or json");
+                return (false);
+            }
+        } else if (strcmp (kw, "down") == 0 || strcmp (kw, "up") == 0) {
+            if (!crackPlanMHz (eq, kw[0] == 'd' ? spec.down_hz : spec.up_hz)) {
+                snprintf (errmsg, errmsg_len, "%s must be MHz", kw);
+                return (false);
+            }
+        } else if (strcmp (kw, "step") == 0) {
+            spec.step = atoi (eq);
+            if (spec.step < PLAN_MINSTEP || spec.step > PLAN_MAXSTEP) {
+                snprintf (errmsg, errmsg_len, "step must be %d .. %d secs", PLAN_MINSTEP, PLAN_MAXSTEP);
+                return (false);
+            }
+        } else {
+            snprintf (errmsg, errmsg_len, "unknown %s", kw);
+            return (false);
+        }
+    }
+
+    if (!spec.down_hz) {
+        snprintf (errmsg, errmsg_len, "no downlink: set down=MHz or set_doppler");
+        return (false);
+    }
+
+    return (true);
+}
+
+/* format UTC t as ISO 8601 into buf
+ */
+static void planISOTime (time_t t, char *buf, size_t buf_len)
+{
+    snprintf (buf, buf_len, "%04d-%02d-%02dT%02d:%02d:%02dZ", year(t), month(t), day(t),
+                                hour(t), minute(t), second(t));
+}
+
+/* add one row of the plan at UTC t to out
+ */
+static void formatPlanRow (const PlanSpec &spec, time_t t, bool first, std::string &out)
+{
+    float az, el, range, rate;
+    if (!getSatTopoAt (t, az, el, range, rate))
+        return;
+
+    char iso[30], buf[200];
+    planISOTime (t, iso, sizeof(iso));
+    uint32_t down = dopplerShiftHz (spec.down_hz, rate, false);
+    uint32_t up = spec.up_hz ? dopplerShiftHz (spec.up_hz, rate, true) : 0;
+
+    switch (spec.format) {
+    case PF_CSV:
+        if (spec.up_hz)
+            snprintf (buf, sizeof(buf), "%s,%.1f,%.1f,%.0f,%.0f,%u,%u\n", iso, az, el, range, rate, down, up);
+        else
+            snprintf (buf, sizeof(buf), "%s,%.1f,%.1f,%.0f,%.0f,%u\n", iso, az, el, range, rate, down);
+        break;
+    case PF_JSON:
+        if (spec.up_hz)
+            snprintf (buf, sizeof(buf), "%s\n  {\"utc\":\"%s\",\"az\":%.1f,\"el\":%.1f,\"range_km\":%.0f,"
+                        "\"rate_mps\":%.0f,\"down_hz\":%u,\"up_hz\":%u}", first ? "" : ",", iso, az, el,
+                        range, rate, down, up);
+        else
+            snprintf (buf, sizeof(buf), "%s\n  {\"utc\":\"%s\",\"az\":%.1f,\"el\":%.1f,\"range_km\":%.0f,"
+                        "\"rate_mps\":%.0f,\"down_hz\":%u}", first ? "" : ",", iso, az, el, range, rate, down);
+        break;
+    }
+    out += buf;
+}
+
+/* build the plan described by spec_str in out and set ctype to its Content-Type.
+ * if trouble put reason in errmsg and return false.
+ */
+static bool makePassPlan (const char *spec_str, std::string &out, const char *&ctype, char *errmsg,
+size_t errmsg_len)
+{
+    PlanSpec spec;
+    if (!crackPlanSpec (spec_str, spec, errmsg, errmsg_len))
+        return (false);
+
+    // find the pass
+    char name[NV_SATNAME_LEN];
+    float az, el, range, rate, raz, saz;
+    if (!getSatAzElNow (name, &az, &el, &range, &rate, &raz, &saz, NULL, NULL)) {
+        snprintf (errmsg, errmsg_len, "no satellite");
+        return (false);
+    }
+    time_t *rises, *sets;
+    int n_passes = nextSatRSEvents (&rises, &sets);
+    if (spec.pass > n_passes) {
+        snprintf (errmsg, errmsg_len, "only %d passes are known", n_passes);
+        if (n_passes > 0) {
+            free ((void*)rises);
+            free ((void*)sets);
+        }
+        return (false);
+    }
+    time_t rise = rises[spec.pass-1];
+    time_t set = sets[spec.pass-1];
+    free ((void*)rises);
+    free ((void*)sets);
+
+    // heading
+    char rise_iso[30], set_iso[30], buf[300];
+    planISOTime (rise, rise_iso, sizeof(rise_iso));
+    planISOTime (set, set_iso, sizeof(set_iso));
+    switch (spec.format) {
+    case PF_CSV:
+        ctype = "text/csv";
+        out = spec.up_hz ? "utc,az,el,range_km,rate_mps,down_hz,up_hz\n" : "utc,az,el,range_km,rate_mps,down_hz\n";
+        break;
+    case PF_JSON:
+        ctype = "application/json";
+        snprintf (buf, sizeof(buf), "{\"sat\":\"%s\",\"rise\":\"%s\",\"set\":\"%s\",\"nominal_down_hz\":%u,"
+                        "\"nominal_up_hz\":%u,\"steps\":[", name, rise_iso, set_iso, spec.down_hz, spec.up_hz);
+        out = buf;
+        break;
+    }
+
+    // each step from rise, always ending with set
+    int n_rows = 0;
+    for (time_t t = rise; t < set && n_rows < PLAN_MAXSTEPS; t += spec.step) {
+        resetWatchdog();
+        formatPlanRow (spec, t, n_rows++ == 0, out);
+    }
+    formatPlanRow (spec, set, n_rows == 0, out);
+
+    if (spec.format == PF_JSON)
+        out += "\n]}\n";
+
+    return (true);
+}
+
+/* web command to export a pass plan.
+ */
+bool getWiFiPassPlan (WiFiClient *clientp, char line[])
+{
+    std::string out;
+    const char *ctype = "text/plain";
+    char errmsg[100];
+    if (!makePassPlan (line, out, ctype, errmsg, sizeof(errmsg))) {
+        strcpy (line, errmsg);
+        return (false);
+    }
+
+    if (clientp) {
+        resetWatchdog();
+        FWIFIPRLN (*clientp, F("HTTP/1.0 200 OK"));
+        sendUserAgent (*clientp);
+        clientp->print (F("Content-Type: "));
+        clientp->print (ctype);
+        FWIFIPRLN (*clientp, F("; charset=us-ascii"));
+        FWIFIPRLN (*clientp, F("Connection: close\r\n"));
+        clientp->write ((const uint8_t *)out.c_str(), out.size());
+    }
+
+    return (true);
+}
+
+#endif // _IS_UNIX
@@ -0,0 +1,175 @@
+/* performance overlay toggled with a hot key, drawn over the upper right corner of the map.
+ * shows main loop rate and worst loop time with a history graph of the latter, display drawing
+ * operations and frames per second, process memory, pending work and image cache effectiveness.
+ * handy when tuning on slow hardware such as a Raspberry Pi. UNIX only.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#define HUD_KEY         ('F'-'@')               // hot key to toggle the overlay: Control-F
+#define HUD_W           160                     // overlay width
+#define HUD_H           92                      // overlay height
+#define HUD_LDY         10                      // text line spacing
+#define HUD_NHIST       (HUD_W-10)              // n loop time history samples, one per pixel
+#define HUD_GH          25                      // graph height
+#define HUD_GMAXMS      200                     // loop time at top of graph, ms
+#define HUD_BG          RGB565(20,20,20)        // background color
+#define HUD_FG          RGB565(200,200,200)     // text color
+
+static bool hud_on;                             // whether overlay is showing
+static uint32_t period_ms;                      // millis() at start of this stats period
+static uint32_t loop_ms;                        // millis() at start of this loop
+static uint32_t n_loops;                        // loops this period
+static uint32_t worst_ms;                       // longest loop this period
+static uint32_t draws0, frames0;                // render stats at start of this period
+static uint16_t hist[HUD_NHIST];                // worst loop ms each period, newest last
+
+
+/* return our resident memory size in KB, or 0 if unknown
+ */
+static long residentKB(void)
+{
+    long kb = 0;
+    FILE *fp = fopen ("/proc/self/statm", "r");
+    if (fp) {
+        long size, resident;
+        if (fscanf (fp, "%ld %ld", &size, &resident) == 2)
+            kb = resident * (sysconf(_SC_PAGESIZE)/1024);
+        fclose (fp);
+    }
+    return (kb);
+}
+
+/* return the overlay box, in the upper right corner of the map
+ */
+static SBox hudBox(void)
+{
+    SBox b;
+    b.w = HUD_W;
+    b.h = HUD_H;
+    b.x = map_b.x + map_b.w - HUD_W - 2;
+    b.y = map_b.y + 2;
+    return (b);
+}
+
+/* return graph color for the given loop time
+ */
+static uint16_t loopColor (uint16_t ms)
+{
+    if (ms < 50)
+        return (RA8875_GREEN);
+    if (ms < 150)
+        return (RA8875_YELLOW);
+    return (RA8875_RED);
+}
+
+/* draw the overlay with the given stats for the period just ended
+ */
+static void drawHUD (float lps, uint32_t draws_ps, uint32_t frames_ps)
+{
+    SBox b = hudBox();
+    tft.fillRect (b.x, b.y, b.w, b.h, HUD_BG);
+    tft.drawRect (b.x, b.y, b.w, b.h, RA8875_WHITE);
+
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    tft.setTextColor (HUD_FG);
+    char buf[40];
+    uint16_t y = b.y + 4;
+
+    snprintf (buf, sizeof(buf), "Loop %5.1f/s max %5ums", lps, (unsigned)hist[HUD_NHIST-1]);
+    tft.setCursor (b.x + 4, y);
+    tft.print (buf);
+    y += HUD_LDY;
+
+    snprintf (buf, sizeof(buf), "Draw %5u/s frm %3u/s", (unsigned)draws_ps, (unsigned)frames_ps);
+    tft.setCursor (b.x + 4, y);
+    tft.print (buf);
+    y += HUD_LDY;
+
+    DXClusterSpot *spots;
+    uint8_t n_spots = 0;
+    (void) getDXClusterSpots (&spots, &n_spots);
+    snprintf (buf, sizeof(buf), "RSS %6ldK spt %d ws %d", residentKB(), n_spots, nWebSockets());
+    tft.setCursor (b.x + 4, y);
+    tft.print (buf);
+    y += HUD_LDY;
+
+    int hits, renews, misses;
+    getImageCacheStats (hits, renews, misses);
+    int n_look = hits + renews + misses;
+    if (n_look > 0)
+        snprintf (buf, sizeof(buf), "Img cache %3d%% of %d", 100*(hits+renews)/n_look, n_look);
+    else
+        snprintf (buf, sizeof(buf), "Img cache unused");
+    tft.setCursor (b.x + 4, y);
+    tft.print (buf);
+    y += HUD_LDY + 4;
+
+    // worst loop time history, one pixel column per period
+    uint16_t gy = y + HUD_GH;
+    tft.drawLine (b.x + 4, gy, b.x + 4 + HUD_NHIST, gy, RA8875_WHITE);
+    for (int i = 0; i < HUD_NHIST; i++) {
+        if (hist[i] == 0)
+            continue;
+        uint16_t h = hist[i] >= HUD_GMAXMS ? HUD_GH : hist[i] * HUD_GH / HUD_GMAXMS;
+        if (h > 0)
+            tft.drawLine (b.x + 5 + i, gy - 1, b.x + 5 + i, gy - h, loopColor (hist[i]));
+    }
+
+    tft.drawPR();
+}
+
+/* called once each main loop with the most recent keyboard char, if any, to gather stats, toggle the
+ * overlay if kbc is the hot key and redraw the overlay once per second while it is showing.
+ */
+void checkPerfHUD (char kbc)
+{
+    uint32_t now = millis();
+
+    // loop time is time since last call
+    if (loop_ms) {
+        uint32_t dt = now - loop_ms;
+        if (dt > worst_ms)
+            worst_ms = dt;
+        n_loops++;
+    } else {
+        period_ms = now;
+        tft.getRenderStats (&draws0, &frames0);
+    }
+    loop_ms = now;
+
+    if (kbc == HUD_KEY) {
+        hud_on = !hud_on;
+        Serial.printf (_FX("PerfHUD: %s\n"), hud_on ? "on" : "off");
+        if (!hud_on)
+            initEarthMap();                     // erase
+    }
+
+    // new stats each second
+    uint32_t dt = now - period_ms;
+    if (dt < 1000)
+        return;
+
+    uint32_t draws, frames;
+    tft.getRenderStats (&draws, &frames);
+    float lps = 1000.0F * n_loops / dt;
+    uint32_t draws_ps = 1000ULL * (draws - draws0) / dt;
+    uint32_t frames_ps = 1000ULL * (frames - frames0) / dt;
+
+    memmove (hist, hist+1, (HUD_NHIST-1)*sizeof(hist[0]));
+    hist[HUD_NHIST-1] = worst_ms > 0xFFFF ? 0xFFFF : worst_ms;
+
+    if (hud_on)
+        drawHUD (lps, draws_ps, frames_ps);
+
+    // start next period after drawing so the overlay does not count against itself
+    period_ms = loop_ms = millis();
+    n_loops = 0;
+    worst_ms = 0;
+    tft.getRenderStats (&draws0, &frames0);
+}
+
+#endif // _IS_UNIX
@@ -14,6 +14,10 @@
 // forward declarations
 static int tickmarks (float min, float max, int numdiv, float ticks[]);
 
+// where the large value of the last plot ends, for plotTrend()
+static SBox label_box;
+static uint16_t label_x, label_y;
+
 
 /* plot the given data within the given box.
  * if y_min == y_max: auto scale min and max from data
@@ -234,6 +238,11 @@ uint16_t color, float y_min, float y_max, char *label_str)
         uint16_t text_y = box.y+TGAP+(box.h-TGAP-BGAP)/25+lh;
         tft.setCursor (text_x, text_y);
         tft.print (label_str);
+
+        // save for plotTrend
+        label_box = box;
+        label_x = text_x + lw/2;
+        label_y = text_y;
     }
 
     // printFreeHeap (F("plotXYstr"));
@@ -242,6 +251,43 @@ uint16_t color, float y_min, float y_max, char *label_str)
     return (true);
 }
 
+/* draw an up, down or steady arrow and the given change, with prec decimals, centered below the large
+ * value of the plot just drawn in box, eg after plotXY. changes that would show as 0 are steady.
+ */
+void plotTrend (const SBox &box, float delta, int prec)
+{
+    #define TREND_ARROW 7                       // arrow size, pixels
+
+    // only if box has a fresh plot
+    if (box.x != label_box.x || box.y != label_box.y || box.w != label_box.w || box.h != label_box.h)
+        return;
+
+    // steady if it would print as 0
+    char buf[20];
+    float unit = prec > 0 ? powf (10, -prec) : 1;
+    int dir = delta >= unit/2 ? 1 : (delta <= -unit/2 ? -1 : 0);
+    if (dir)
+        snprintf (buf, sizeof(buf), "%+.*f", prec, delta);
+    else
+        strcpy (buf, "0");
+
+    selectFontStyle (BOLD_FONT, FAST_FONT);
+    tft.setTextColor (BRGRAY);
+    uint16_t tw = getTextWidth (buf);
+    uint16_t ax = label_x - (TREND_ARROW + 3 + tw)/2;
+    uint16_t ay = label_y + 4;
+
+    if (dir > 0)
+        tft.fillTriangle (ax, ay+TREND_ARROW, ax+TREND_ARROW, ay+TREND_ARROW, ax+TREND_ARROW/2, ay, BRGRAY);
+    else if (dir < 0)
+        tft.fillTriangle (ax, ay, ax+TREND_ARROW, ay, ax+TREND_ARROW/2, ay+TREND_ARROW, BRGRAY);
+    else
+        tft.fillRect (ax, ay+TREND_ARROW/2-1, TREND_ARROW, 3, BRGRAY);
+
+    tft.setCursor (ax + TREND_ARROW + 3, ay);
+    tft.print (buf);
+}
+
 /* shorten str IN PLACE as needed to be less that maxw pixels wide.
  * return final width in pixels.
  */
@@ -275,8 +321,8 @@ void plotWX (const SBox &box, uint16_t color, const WXInfo &wi)
     // large temperature with degree symbol and units
     tft.setTextColor(color);
     selectFontStyle (BOLD_FONT, LARGE_FONT);
-    f = useMetricUnits() ? wi.temperature_c : 9*wi.temperature_c/5+32;
-    sprintf (buf, "%.0f %c", f, useMetricUnits() ? 'C' : 'F');
+    f = tempFromC (wi.temperature_c);
+    sprintf (buf, "%.0f %c", f, tempUnits());
     w = maxStringW (buf, box.w-indent);
     tft.setCursor (box.x+(box.w-indent-w)/2, box.y+dy);
     tft.print(buf);
@@ -299,12 +345,12 @@ void plotWX (const SBox &box, uint16_t color, const WXInfo &wi)
     dy += ddy;
 
     // wind
-    f = (useMetricUnits() ? 3.6 : 2.237) * wi.wind_speed_mps; // kph or mph
-    sprintf (buf, "%s @ %.0f %s", wi.wind_dir_name, f, useMetricUnits() ? "kph" : "mph");
+    f = speedFromMps (wi.wind_speed_mps);
+    sprintf (buf, "%s @ %.0f %s", wi.wind_dir_name, f, speedUnits());
     w = maxStringW (buf, box.w-indent);
     if (buf[strlen(buf)-1] != 'h') {
         // try shorter string in case of huge speed
-        sprintf (buf, "%s @ %.0f%s", wi.wind_dir_name, f, useMetricUnits() ? "k/h" : "m/h");
+        sprintf (buf, "%s @ %.0f%s", wi.wind_dir_name, f, show_km ? "k/h" : "m/h");
         w = maxStringW (buf, box.w-indent);
     }
     tft.setCursor (box.x+(box.w-indent-w)/2, box.y+dy);
@@ -420,7 +466,7 @@ void plotBandConditions (const SBox &box, int busy, const BandMatrix *bmp, char
     // center title across the top
     selectFontStyle (LIGHT_FONT, SMALL_FONT);
     tft.setTextColor(RA8875_WHITE);
-    const char *title = "VOACAP DE-DX";
+    const char *title = xlate ("VOACAP DE-DX");
     uint16_t tw = getTextWidth (title);
     tft.setCursor (box.x+(box.w-tw)/2, box.y + TOP_B);
     tft.print ((char*)title);
@@ -492,7 +538,7 @@ void plotBandConditions (const SBox &box, int busy, float rel_tbl[PROP_MAP_N], c
         // center title across the top
         selectFontStyle (LIGHT_FONT, SMALL_FONT);
         tft.setTextColor(RA8875_WHITE);
-        const char *title = "VOACAP DE-DX";
+        const char *title = xlate ("VOACAP DE-DX");
         uint16_t bw = getTextWidth (title);
         tft.setCursor (box.x+(box.w-bw)/2, ty);
         tft.print ((char*)title);
@@ -547,7 +593,22 @@ void plotBandConditions (const SBox &box, int busy, float rel_tbl[PROP_MAP_N], c
 
 #endif // _OLD_TABLE_STYLE
 
+/* return the background color of a NOAA scale badge at the given level 0 .. 5, similar to NOAA's own.
+ */
+static uint16_t noaaScaleColor (int level)
+{
+    switch (level) {
+    case 0:  return (RA8875_BLACK);
+    case 1:  return (RGB565(255,255,0));
+    case 2:  return (RGB565(255,200,0));
+    case 3:  return (RGB565(255,150,0));
+    case 4:  return (RGB565(255,0,0));
+    default: return (RGB565(160,0,0));
+    }
+}
+
 /* print the NOAA RSG Space Weather Scales in the given box.
+ * current level of each is shown as a badge such as G2, followed by the predictions.
  */
 void plotNOAASWx (const SBox &box, const NOAASpaceWx &noaaspw)
 {
@@ -568,16 +629,28 @@ void plotNOAASWx (const SBox &box, const NOAASpaceWx &noaaspw)
     // print each line
     for (int i = 0; i < N_NOAASW_C; i++) {
 
-        uint16_t w = box.w/7-1;
         h += box.h/4;
-        tft.setCursor (box.x+w+(i==2?-2:0), box.y+h);   // tweak G to better center
-        tft.setTextColor(GRAY);
-        tft.print (noaaspw.cat[i]);
 
-        w += box.w/10;
-        for (int j = 0; j < N_NOAASW_V; j++) {
+        // current level as a badge
+        int level = noaaspw.val[i][0];
+        uint16_t badge_c = noaaScaleColor (level);
+        char badge[4];
+        snprintf (badge, sizeof(badge), "%c%d", noaaspw.cat[i], level);
+        uint16_t bx = box.x + box.w/16;
+        uint16_t by = box.y + h - 18;
+        uint16_t bdw = box.w/4;
+        uint16_t bdh = 24;
+        tft.fillRect (bx, by, bdw, bdh, badge_c);
+        tft.drawRect (bx, by, bdw, bdh, level == 0 ? RA8875_GREEN : badge_c);
+        tft.setTextColor (level == 0 ? RA8875_GREEN : (level < 5 ? RA8875_BLACK : RA8875_WHITE));
+        tft.setCursor (bx + (bdw-getTextWidth(badge))/2, box.y+h);
+        tft.print (badge);
+
+        // predictions
+        uint16_t w = box.w/16 + box.w/4;
+        for (int j = 1; j < N_NOAASW_V; j++) {
             int val = noaaspw.val[i][j];
-            w += box.w/7;
+            w += box.w/5;
             tft.setCursor (box.x+w, box.y+h);
             tft.setTextColor(val == 0 ? RA8875_GREEN : (val <= 3 ? RA8875_YELLOW : RA8875_RED));
             tft.print (val);
@@ -590,8 +663,10 @@ void plotNOAASWx (const SBox &box, const NOAASpaceWx &noaaspw)
  */
 void plotMessage (const SBox &box, uint16_t color, const char *message)
 {
-    // log
+    // log and note errors in case this is a failed refresh
     Serial.printf (_FX("PlotMsg: %s\n"), message);
+    if (color == RA8875_RED)
+        noteFetchDetail (message);
 
     // prep font
     selectFontStyle (BOLD_FONT, FAST_FONT);
@@ -667,6 +742,30 @@ bool checkPlotTouch (const SCoord &s, PlotPane pp, TouchType tt)
         if (checkGimbalTouch (s, box))
             return (true);
         break;
+    case PLOT_CH_OUTLOOK:
+        if (!in_top && checkOutlookTouch (s, box))
+            return (true);
+        break;
+    case PLOT_CH_BANDPLAN:
+        if (!in_top && checkBandPlanTouch (s, box))
+            return (true);
+        break;
+    case PLOT_CH_DXPEDS:
+        if (!in_top && checkDXPedsTouch (s, box))
+            return (true);
+        break;
+    case PLOT_CH_ARISS:
+        if (!in_top && checkARISSTouch (s, box))
+            return (true);
+        break;
+    case PLOT_CH_AWARDS:
+        if (!in_top && checkAwardsTouch (s, box))
+            return (true);
+        break;
+    case PLOT_CH_PLUGINS:
+        if (!in_top && checkPluginsTouch (s, box))
+            return (true);
+        break;
     case PLOT_CH_COUNTDOWN:
         if (!in_top) {
             checkStopwatchTouch(tt);
@@ -681,7 +780,7 @@ bool checkPlotTouch (const SCoord &s, PlotPane pp, TouchType tt)
             if (setPlotChoice (pp, PLOT_CH_PRESSURE)
                             || setPlotChoice (pp, PLOT_CH_HUMIDITY)
                             || setPlotChoice (pp, PLOT_CH_DEWPOINT)) {
-                plot_rotset[pp] = (1 << plot_ch[pp]);   // no auto rotation
+                plot_rotset[pp] = (1ULL << plot_ch[pp]);   // no auto rotation
                 savePlotOps();
                 return (true);
             }
@@ -692,7 +791,7 @@ bool checkPlotTouch (const SCoord &s, PlotPane pp, TouchType tt)
             if (setPlotChoice (pp, PLOT_CH_HUMIDITY)
                             || setPlotChoice (pp, PLOT_CH_DEWPOINT)
                             || setPlotChoice (pp, PLOT_CH_TEMPERATURE)) {
-                plot_rotset[pp] = (1 << plot_ch[pp]);   // no auto rotation
+                plot_rotset[pp] = (1ULL << plot_ch[pp]);   // no auto rotation
                 savePlotOps();
                 return (true);
             }
@@ -703,7 +802,7 @@ bool checkPlotTouch (const SCoord &s, PlotPane pp, TouchType tt)
             if (setPlotChoice (pp, PLOT_CH_DEWPOINT)
                             || setPlotChoice (pp, PLOT_CH_TEMPERATURE)
                             || setPlotChoice (pp, PLOT_CH_PRESSURE)) {
-                plot_rotset[pp] = (1 << plot_ch[pp]);   // no auto rotation
+                plot_rotset[pp] = (1ULL << plot_ch[pp]);   // no auto rotation
                 savePlotOps();
                 return (true);
             }
@@ -714,7 +813,7 @@ bool checkPlotTouch (const SCoord &s, PlotPane pp, TouchType tt)
             if (setPlotChoice (pp, PLOT_CH_TEMPERATURE)
                             || setPlotChoice (pp, PLOT_CH_PRESSURE)
                             || setPlotChoice (pp, PLOT_CH_HUMIDITY)) {
-                plot_rotset[pp] = (1 << plot_ch[pp]);   // no auto rotation
+                plot_rotset[pp] = (1ULL << plot_ch[pp]);   // no auto rotation
                 savePlotOps();
                 return (true);
             }
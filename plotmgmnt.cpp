@@ -13,7 +13,7 @@ SBox plot_b[PANE_N] = {
 };
 PlotChoice plot_ch[PANE_N];
 time_t next_rotationT[PANE_N];
-uint32_t plot_rotset[PANE_N];
+uint64_t plot_rotset[PANE_N];
 const char *plot_names[PLOT_CH_N] = {
 
     // N.B. must be in same order as PLOT_CH_* 
@@ -43,12 +43,29 @@ const char *plot_names[PLOT_CH_N] = {
     "DRAP",             // PLOT_CH_DRAP,
     "Countdown",        // PLOT_CH_COUNTDOWN,
     "STEREO_A",         // PLOT_CH_STEREO_A,
+    "27_Day_SWx",       // PLOT_CH_OUTLOOK,
+    "Ionosonde",        // PLOT_CH_IONOSONDE,
+    "Flares",           // PLOT_CH_FLARES,
+    "CME",              // PLOT_CH_CME,
+    "Best_Bands",       // PLOT_CH_BANDREC,
+    "EME_Plan",         // PLOT_CH_EME,
+    "SDO_304A",         // PLOT_CH_SDO_5,
+    "SDO_Cycle",        // PLOT_CH_SDO_CYCLE,
+    "NCDXF",            // PLOT_CH_NCDXF,
+    "Band_Plan",        // PLOT_CH_BANDPLAN,
+    "DXpeditions",      // PLOT_CH_DXPEDS,
+    "Plugins",          // PLOT_CH_PLUGINS,
+    "QSO_Rate",         // PLOT_CH_QSORATE,
+    "Tx_Meter",         // PLOT_CH_TXMETER,
+    "Sat_Sky",          // PLOT_CH_SATSKY,
+    "ISS_Events",       // PLOT_CH_ARISS,
+    "Awards",           // PLOT_CH_AWARDS,
 };
 
-/* return number of bits set in the given uint32_t
+/* return number of bits set in the given uint64_t
  * Brian Kernighan's bit counting algorithm
  */
-static int nBitsSet (uint32_t n)
+static int nBitsSet (uint64_t n)
 {
     int count = 0;
     while (n) {
@@ -58,6 +75,24 @@ static int nBitsSet (uint32_t n)
     return (count);
 }
 
+/* read a rotset stored as low and high 32 bit halves, either may be undefined
+ */
+static void readPaneRotSet (NV_Name lo_e, NV_Name hi_e, uint64_t *rotsetp)
+{
+    uint32_t lo = 0, hi = 0;
+    (void) NVReadUInt32 (lo_e, &lo);
+    (void) NVReadUInt32 (hi_e, &hi);
+    *rotsetp = ((uint64_t)hi << 32) | lo;
+}
+
+/* save a rotset as low and high 32 bit halves
+ */
+static void writePaneRotSet (NV_Name lo_e, NV_Name hi_e, uint64_t rotset)
+{
+    NVWriteUInt32 (lo_e, (uint32_t)rotset);
+    NVWriteUInt32 (hi_e, (uint32_t)(rotset >> 32));
+}
+
 
 /* retrieve the plot choice for the given pane from NV, if set
  */
@@ -97,7 +132,7 @@ static void setDefaultPaneChoice (PlotPane pp)
     // check rotset first
     if (plot_rotset[pp]) {
         for (int i = 0; i < PLOT_CH_N; i++) {
-            if (plot_rotset[pp] & (1 << i)) {
+            if (plot_rotset[pp] & (1ULL << i)) {
                 plot_ch[pp] = (PlotChoice) i;
                 break;
             }
@@ -105,7 +140,7 @@ static void setDefaultPaneChoice (PlotPane pp)
     } else {
         const PlotChoice ch_defaults[PANE_N] = {PLOT_CH_SSN, PLOT_CH_XRAY, PLOT_CH_SDO_1};
         plot_ch[pp] = ch_defaults[pp];
-        plot_rotset[pp] = (1 << plot_ch[pp]);
+        plot_rotset[pp] = (1ULL << plot_ch[pp]);
         Serial.printf (_FX("PANE: Setting pane %d to default %s\n"), (int)pp+1, plot_names[plot_ch[pp]]);
     }
 }
@@ -128,6 +163,11 @@ bool plotChoiceIsAvailable (PlotChoice ch)
 
     case PLOT_CH_DXCLUSTER:     return (useDXCluster());
     case PLOT_CH_GIMBAL:        return (haveGimbal());
+    case PLOT_CH_PLUGINS:       return (havePlugins());
+    case PLOT_CH_QSORATE:       return (haveQSORate());
+    case PLOT_CH_AWARDS:        return (haveQSORate());         // same QSO sources
+    case PLOT_CH_TXMETER:       return (haveTxMeter());
+    case PLOT_CH_SATSKY:        return (haveSatSky());
     case PLOT_CH_TEMPERATURE:   return (getNBMEConnected() > 0);
     case PLOT_CH_PRESSURE:      return (getNBMEConnected() > 0);
     case PLOT_CH_HUMIDITY:      return (getNBMEConnected() > 0);
@@ -150,6 +190,18 @@ bool plotChoiceIsAvailable (PlotChoice ch)
     case PLOT_CH_SOLWIND:       // fallthru
     case PLOT_CH_DRAP:          // fallthru
     case PLOT_CH_STEREO_A:      // fallthru
+    case PLOT_CH_OUTLOOK:       // fallthru
+    case PLOT_CH_IONOSONDE:     // fallthru
+    case PLOT_CH_FLARES:        // fallthru
+    case PLOT_CH_CME:           // fallthru
+    case PLOT_CH_BANDREC:       // fallthru
+    case PLOT_CH_EME:           // fallthru
+    case PLOT_CH_SDO_5:         // fallthru
+    case PLOT_CH_SDO_CYCLE:     // fallthru
+    case PLOT_CH_NCDXF:         // fallthru
+    case PLOT_CH_BANDPLAN:      // fallthru
+    case PLOT_CH_DXPEDS:        // fallthru
+    case PLOT_CH_ARISS:         // fallthru
         return (true);
         break;
 
@@ -168,7 +220,7 @@ void logPaneRotSet (PlotPane pp, PlotChoice ch)
 {
     Serial.printf (_FX("Pane %d choices:\n"), (int)pp+1);
     for (int i = 0; i < PLOT_CH_N; i++)
-        if (plot_rotset[pp] & (1 << i))
+        if (plot_rotset[pp] & (1ULL << i))
             Serial.printf (_FX("    %c%s\n"), i == ch ? '*' : ' ', plot_names[i]);
 }
 
@@ -199,7 +251,7 @@ PlotChoice askPaneChoice (PlotPane pp)
             menu.items = (MenuItem *) realloc (menu.items, (menu.n_items+1)*sizeof(MenuItem));
             MenuItem &mi = menu.items[menu.n_items++];
             mi.type = MENU_AL1OFN;
-            mi.set = (plot_rotset[pp] & (1 << ch)) ? true : false;
+            mi.set = (plot_rotset[pp] & (1ULL << ch)) ? true : false;
             mi.label = plot_names[ch];
             mi.indent = 4;
         }
@@ -229,7 +281,7 @@ PlotChoice askPaneChoice (PlotPane pp)
                 // find which choice this refers to by matching labels
                 for (int j = 0; j < PLOT_CH_N; j++) {
                     if (strcmp (plot_names[j], menu.items[i].label) == 0) {
-                        plot_rotset[pp] |= (1 << j);
+                        plot_rotset[pp] |= (1ULL << j);
                         break;
                     }
                 }
@@ -238,9 +290,9 @@ PlotChoice askPaneChoice (PlotPane pp)
         savePlotOps();
 
         // return current choice if still in rotset, else just pick one
-        if (!(plot_rotset[pp] & (1 << return_ch))) {
+        if (!(plot_rotset[pp] & (1ULL << return_ch))) {
             for (int i = 0; i < PLOT_CH_N; i++) {
-                if (plot_rotset[pp] & (1 << i)) {
+                if (plot_rotset[pp] & (1ULL << i)) {
                     return_ch = (PlotChoice)i;
                     break;
                 }
@@ -293,7 +345,7 @@ PlotPane findPaneForChoice (PlotChoice ch)
     if (PANE_N != 3)
         fatalError (_FX("Bug! PANE_N != 3"));
 
-    uint32_t mask = 1 << ch;
+    uint64_t mask = 1ULL << ch;
     if (plot_rotset[PANE_1] & mask)
         return (PANE_1);
     if (plot_rotset[PANE_2] & mask)
@@ -313,7 +365,7 @@ PlotChoice getNextRotationChoice (PlotPane pp, PlotChoice pc)
         int pc_test = ((int)pc + i) % PLOT_CH_N;
 
         // done if in selection set
-        if (plot_rotset[pp] & (1 << pc_test))
+        if (plot_rotset[pp] & (1ULL << pc_test))
             return ((PlotChoice)pc_test);
     }
 
@@ -331,7 +383,7 @@ PlotChoice getAnyAvailableChoice()
         if (plotChoiceIsAvailable (ch)) {
             bool inuse = false;
             for (int j = 0; !inuse && j < PANE_N; j++) {
-                if (plot_ch[j] == ch || (plot_rotset[j] & (1 << ch))) {
+                if (plot_ch[j] == ch || (plot_rotset[j] & (1ULL << ch))) {
                     inuse = true;
                 }
             }
@@ -360,8 +412,8 @@ void insureCountdownPaneSensible()
     uint32_t sw_timer;
     if (getSWEngineState(sw_timer) != SWE_COUNTDOWN) {
         for (int i = 0; i < PANE_N; i++) {
-            if (plot_rotset[i] & (1 << PLOT_CH_COUNTDOWN)) {
-                plot_rotset[i] &= ~(1 << PLOT_CH_COUNTDOWN);
+            if (plot_rotset[i] & (1ULL << PLOT_CH_COUNTDOWN)) {
+                plot_rotset[i] &= ~(1ULL << PLOT_CH_COUNTDOWN);
                 if (plot_ch[i] == PLOT_CH_COUNTDOWN) {
                     setDefaultPaneChoice((PlotPane)i);
                     if (!setPlotChoice ((PlotPane)i, plot_ch[i])) {
@@ -379,15 +431,15 @@ void insureCountdownPaneSensible()
 void initPlotPanes()
 {
     // retrieve rotation sets -- ok to leave 0 for now if not yet defined
-    NVReadUInt32 (NV_PANE1ROTSET, &plot_rotset[PANE_1]);
-    NVReadUInt32 (NV_PANE2ROTSET, &plot_rotset[PANE_2]);
-    NVReadUInt32 (NV_PANE3ROTSET, &plot_rotset[PANE_3]);
+    readPaneRotSet (NV_PANE1ROTSET, NV_PANE1ROTSETHI, &plot_rotset[PANE_1]);
+    readPaneRotSet (NV_PANE2ROTSET, NV_PANE2ROTSETHI, &plot_rotset[PANE_2]);
+    readPaneRotSet (NV_PANE3ROTSET, NV_PANE3ROTSETHI, &plot_rotset[PANE_3]);
 
     // rm any rotset not available
     for (int i = 0; i < PANE_N; i++) {
         for (int j = 0; j < PLOT_CH_N; j++) {
-            if ((plot_rotset[i] & (1 << j)) && !plotChoiceIsAvailable ((PlotChoice)j)) {
-                plot_rotset[i] &= ~(1 << j);
+            if ((plot_rotset[i] & (1ULL << j)) && !plotChoiceIsAvailable ((PlotChoice)j)) {
+                plot_rotset[i] &= ~(1ULL << j);
                 Serial.printf (_FX("PANE: Removing %s from pane %d: not available\n"), plot_names[j], i+1);
             }
         }
@@ -395,7 +447,7 @@ void initPlotPanes()
 
     // if current selection not yet defined or not in rotset pick one from rotset or set a default
     for (int i = 0; i < PANE_N; i++) {
-        if (!getPlotChoiceNV ((PlotPane)i, &plot_ch[i]) || !(plot_rotset[i] & (1 << plot_ch[i])))
+        if (!getPlotChoiceNV ((PlotPane)i, &plot_ch[i]) || !(plot_rotset[i] & (1ULL << plot_ch[i])))
             setDefaultPaneChoice ((PlotPane)i);
     }
 
@@ -410,8 +462,8 @@ void initPlotPanes()
                         Serial.printf (_FX("PANE: Reassigning dup pane %d from %s to %s\n"), j+1,
                                         plot_names[plot_ch[j]], plot_names[new_ch]);
                         // remove dup from rotation set then replace with new choice
-                        plot_rotset[j] &= ~(1 << plot_ch[j]);
-                        plot_rotset[j] |= (1 << new_ch);
+                        plot_rotset[j] &= ~(1ULL << plot_ch[j]);
+                        plot_rotset[j] |= (1ULL << new_ch);
                         plot_ch[j] = new_ch;
                         break;
                     }
@@ -422,7 +474,7 @@ void initPlotPanes()
 
     // one last bit of paranoia: insure each pane choice is in its rotation set
     for (int i = 0; i < PANE_N; i++)
-        plot_rotset[i] |= (1 << plot_ch[i]);
+        plot_rotset[i] |= (1ULL << plot_ch[i]);
 
     // log and save final arrangement
     for (int i = 0; i < PANE_N; i++)
@@ -434,9 +486,9 @@ void initPlotPanes()
  */
 void savePlotOps()
 {
-    NVWriteUInt32 (NV_PANE1ROTSET, plot_rotset[PANE_1]);
-    NVWriteUInt32 (NV_PANE2ROTSET, plot_rotset[PANE_2]);
-    NVWriteUInt32 (NV_PANE3ROTSET, plot_rotset[PANE_3]);
+    writePaneRotSet (NV_PANE1ROTSET, NV_PANE1ROTSETHI, plot_rotset[PANE_1]);
+    writePaneRotSet (NV_PANE2ROTSET, NV_PANE2ROTSETHI, plot_rotset[PANE_2]);
+    writePaneRotSet (NV_PANE3ROTSET, NV_PANE3ROTSETHI, plot_rotset[PANE_3]);
 
     NVWriteUInt8 (NV_PLOT_1, plot_ch[PANE_1]);
     NVWriteUInt8 (NV_PLOT_2, plot_ch[PANE_2]);
@@ -452,31 +504,101 @@ void showRotatingBorder (bool soon, PlotPane pp)
 
 }
 
+/* source of bmp bytes for drawHTTPBMP
+ */
+typedef struct {
+    WiFiClient *clientp;                // network source unless in_fp
+    FILE *in_fp;                        // local cache source, if any
+    FILE *save_fp;                      // also save network bytes here, if any
+} BMPSource;
+
+/* get next byte from the given bmp source, return whether ok.
+ */
+static bool getBMPChar (BMPSource &src, char *cp)
+{
+    if (src.in_fp) {
+        int c = fgetc (src.in_fp);
+        if (c == EOF)
+            return (false);
+        *cp = (char)c;
+        return (true);
+    }
+
+    if (!getChar (*src.clientp, cp))
+        return (false);
+    if (src.save_fp)
+        fputc (*cp, src.save_fp);
+    return (true);
+}
+
 /* download the given url containing a bmp image and display in the given box.
  * show error messages in the given color.
  * return whether all ok
  */
 bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
+{
+    return (drawHTTPBMP (url, box, color, NULL, 0));
+}
+
+/* same as drawHTTPBMP but also pass back the image Last-Modified UNIX time, or 0 if not known.
+ */
+bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color, uint32_t *lastmodp)
+{
+    return (drawHTTPBMP (url, box, color, lastmodp, 0));
+}
+
+/* same as drawHTTPBMP but draw from the local image cache if the copy there is younger than
+ * cache_ttl seconds, else download and save a fresh copy. 0 means never use the cache.
+ * an older copy is still used if the server says the image has not changed since.
+ */
+bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color, uint32_t *lastmodp, int cache_ttl)
 {
     WiFiClient client;
+    BMPSource src = {&client, NULL, NULL};
+    HTTPValidators v;
+    memset (&v, 0, sizeof(v));
+    bool complete = false;
     bool ok = false;
 
     Serial.println(url);
     resetWatchdog();
-    if (wifiOk() && client.connect(svr_host, HTTPPORT)) {
+    bool cached = cache_ttl > 0 && (src.in_fp = openImageCache (url, cache_ttl, v)) != NULL;
+    if (cached || (wifiOk() && client.connect(svr_host, HTTPPORT))) {
         updateClocks(false);
 
         // composite types
         union { char c[4]; uint32_t x; } i32;
         union { char c[2]; uint16_t x; } i16;
 
-        // query web page
-        httpGET (client, svr_host, url);
+        if (cached) {
+            Serial.println (F("  from cache"));
+        } else {
+            // query web page, only if changed if we have an older copy
+            HTTPValidators old_v;
+            bool stale = cache_ttl > 0 && getImageCacheValidators (url, old_v);
+            httpGET (client, svr_host, url, stale ? &old_v : NULL);
+
+            // skip response header
+            bool not_modified;
+            memset (&v, 0, sizeof(v));
+            if (!httpSkipHeader (client, v, not_modified)) {
+                plotMessage (box, color, _FX("image header short"));
+                goto out;
+            }
 
-        // skip response header
-        if (!httpSkipHeader (client)) {
-            plotMessage (box, color, _FX("image header short"));
-            goto out;
+            if (stale && not_modified) {
+                // use older copy again
+                v = old_v;
+                src.in_fp = renewImageCache (url);
+                if (!src.in_fp) {
+                    plotMessage (box, color, _FX("image cache error"));
+                    goto out;
+                }
+                Serial.println (F("  not modified, from cache"));
+            } else if (cache_ttl > 0) {
+                // save a copy as it arrives
+                src.save_fp = createImageCache (url);
+            }
         }
 
         // keep track of our offset in the image file
@@ -484,7 +606,7 @@ bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
         char c;
 
         // read first two bytes to confirm correct format
-        if (!getChar(client,&c) || c != 'B' || !getChar(client,&c) || c != 'M') {
+        if (!getBMPChar(src,&c) || c != 'B' || !getBMPChar(src,&c) || c != 'M') {
             plotMessage (box, color, _FX("bad file"));
             goto out;
         }
@@ -492,13 +614,13 @@ bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
 
         // skip down to byte 10 which is the offset to the pixels offset
         while (byte_os++ < 10) {
-            if (!getChar(client,&c)) {
+            if (!getBMPChar(src,&c)) {
                 plotMessage (box, color, _FX("header offset error"));
                 goto out;
             }
         }
         for (uint8_t i = 0; i < 4; i++, byte_os++) {
-            if (!getChar(client,&i32.c[i])) {
+            if (!getBMPChar(src,&i32.c[i])) {
                 plotMessage (box, color, _FX("pix_start error"));
                 goto out;
             }
@@ -508,7 +630,7 @@ bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
 
         // next word is subheader size, must be 40 BITMAPINFOHEADER
         for (uint8_t i = 0; i < 4; i++, byte_os++) {
-            if (!getChar(client,&i32.c[i])) {
+            if (!getBMPChar(src,&i32.c[i])) {
                 plotMessage (box, color, _FX("hdr size error"));
                 goto out;
             }
@@ -522,7 +644,7 @@ bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
 
         // next word is width
         for (uint8_t i = 0; i < 4; i++, byte_os++) {
-            if (!getChar(client,&i32.c[i])) {
+            if (!getBMPChar(src,&i32.c[i])) {
                 plotMessage (box, color, _FX("width error"));
                 goto out;
             }
@@ -531,7 +653,7 @@ bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
 
         // next word is height
         for (uint8_t i = 0; i < 4; i++, byte_os++) {
-            if (!getChar(client,&i32.c[i])) {
+            if (!getBMPChar(src,&i32.c[i])) {
                 plotMessage (box, color, _FX("height error"));
                 goto out;
             }
@@ -542,7 +664,7 @@ bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
 
         // next short is n color planes
         for (uint8_t i = 0; i < 2; i++, byte_os++) {
-            if (!getChar(client,&i16.c[i])) {
+            if (!getBMPChar(src,&i16.c[i])) {
                 plotMessage (box, color, _FX("planes error"));
                 goto out;
             }
@@ -556,7 +678,7 @@ bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
 
         // next short is bits per pixel
         for (uint8_t i = 0; i < 2; i++, byte_os++) {
-            if (!getChar(client,&i16.c[i])) {
+            if (!getBMPChar(src,&i16.c[i])) {
                 plotMessage (box, color, _FX("bits/pix error"));
                 goto out;
             }
@@ -570,7 +692,7 @@ bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
 
         // next word is compression method
         for (uint8_t i = 0; i < 4; i++, byte_os++) {
-            if (!getChar(client,&i32.c[i])) {
+            if (!getBMPChar(src,&i32.c[i])) {
                 plotMessage (box, color, _FX("compression error"));
                 goto out;
             }
@@ -584,7 +706,7 @@ bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
 
         // skip down to start of pixels
         while (byte_os++ <= pix_start) {
-            if (!getChar(client,&c)) {
+            if (!getBMPChar(src,&c)) {
                 plotMessage (box, color, _FX("header 3 error"));
                 goto out;
             }
@@ -616,7 +738,7 @@ bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
                 char b, g, r;
 
                 // read next pixel -- note order!
-                if (!getChar (client, &b) || !getChar (client, &g) || !getChar (client, &r)) {
+                if (!getBMPChar (src, &b) || !getBMPChar (src, &g) || !getBMPChar (src, &r)) {
                     // allow a little loss because ESP TCP stack can fall behind while also drawing
                     int32_t n_draw = img_y*img_w + img_x;
                     if (n_draw > 9*n_pix/10) {
@@ -648,7 +770,7 @@ bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
             uint8_t extra = img_w % 4;
             if (extra > 0) {
                 for (uint8_t i = 0; i < 4 - extra; i++) {
-                    if (!getChar(client,&c)) {
+                    if (!getBMPChar(src,&c)) {
                         plotMessage (box, color, _FX("row padding error"));
                         goto out;
                     }
@@ -657,6 +779,7 @@ bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
         }
 
         // Serial.println (F("image complete"));
+        complete = true;
         ok = true;
 
     } else {
@@ -664,7 +787,13 @@ bool drawHTTPBMP (const char *url, const SBox &box, uint16_t color)
     }
 
 out:
+    if (src.in_fp)
+        fclose (src.in_fp);
+    if (src.save_fp)
+        commitImageCache (url, src.save_fp, complete, v);
     client.stop();
+    if (lastmodp)
+        *lastmodp = v.lastmod;
     return (ok);
 }
 
@@ -0,0 +1,320 @@
+/* load third party plugins from shared libraries and show them one at a time in the Plugins pane.
+ * see hamclock_plugin.h for the ABI.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#include <dlfcn.h>
+#include <dirent.h>
+
+#include "hamclock_plugin.h"
+
+#define PLUGINS_DIR     "plugins"               // dir within our_dir to search for *.so
+#define MAX_PLUGINS     10                      // max plugins loaded
+#define FETCH_RETRY     60                      // secs to wait before retrying a failed fetch()
+#define TITLE_COLOR     RA8875_GREEN
+#define TITLE_Y0        27                      // title dy, match VOACAP title position
+#define AREA_Y0         32                      // plugin drawing area dy from box top
+#define FOOTER_DY       11                      // plugin selector height at bottom when more than 1
+#define FOOTER_COLOR    GRAY
+
+// one loaded plugin
+typedef struct {
+    void *handle;                               // from dlopen()
+    const HCPlugin *hcp;                        // from its HCP_ENTRY
+    time_t next_fetch;                          // time of next fetch(), 0 asap
+    bool fetch_ok;                              // whether most recent fetch() succeeded
+} LoadedPlugin;
+
+static LoadedPlugin plugins[MAX_PLUGINS];       // all loaded plugins
+static int n_plugins;                           // n used in plugins[]
+static int cur_plugin;                          // index of plugins[] showing
+static SBox area_b;                             // screen area the current plugin draws within
+
+
+/* clip the line x0,y0 to x1,y1 to area_b using Liang-Barsky then make it absolute screen coords.
+ * return whether any part remains.
+ */
+static bool clipLineToArea (int &x0, int &y0, int &x1, int &y1)
+{
+    float t0 = 0, t1 = 1;
+    float dx = x1 - x0, dy = y1 - y0;
+    const float p[4] = {-dx, dx, -dy, dy};
+    const float q[4] = {(float)x0, (float)(area_b.w - 1 - x0), (float)y0, (float)(area_b.h - 1 - y0)};
+
+    for (int i = 0; i < 4; i++) {
+        if (p[i] == 0) {
+            if (q[i] < 0)
+                return (false);                 // parallel to and outside this edge
+        } else {
+            float t = q[i]/p[i];
+            if (p[i] < 0) {
+                if (t > t1)
+                    return (false);
+                if (t > t0)
+                    t0 = t;
+            } else {
+                if (t < t0)
+                    return (false);
+                if (t < t1)
+                    t1 = t;
+            }
+        }
+    }
+
+    int cx0 = roundf (x0 + t0*dx), cy0 = roundf (y0 + t0*dy);
+    int cx1 = roundf (x0 + t1*dx), cy1 = roundf (y0 + t1*dy);
+    x0 = area_b.x + cx0;
+    y0 = area_b.y + cy0;
+    x1 = area_b.x + cx1;
+    y1 = area_b.y + cy1;
+    return (true);
+}
+
+/* HCPluginHost functions
+ */
+static void hostFillRect (int x, int y, int w, int h, uint16_t color)
+{
+    // clip rather than clamp so a rect partly outside does not smear along the edge
+    int x1 = x + w - 1, y1 = y + h - 1;
+    if (x < 0) x = 0;
+    if (y < 0) y = 0;
+    if (x1 >= area_b.w) x1 = area_b.w - 1;
+    if (y1 >= area_b.h) y1 = area_b.h - 1;
+    if (x <= x1 && y <= y1)
+        tft.fillRect (area_b.x + x, area_b.y + y, x1 - x + 1, y1 - y + 1, color);
+}
+static void hostDrawLine (int x0, int y0, int x1, int y1, uint16_t color)
+{
+    if (clipLineToArea (x0, y0, x1, y1))
+        tft.drawLine (x0, y0, x1, y1, color);
+}
+static void hostDrawText (int x, int y, uint16_t color, const char *str)
+{
+    // clip rather than clamp so text never spills out of the pane
+    char buf[50];
+    strncpy (buf, str, sizeof(buf)-1);
+    buf[sizeof(buf)-1] = '\0';
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    while (buf[0] && x + getTextWidth(buf) > area_b.w)
+        buf[strlen(buf)-1] = '\0';
+    if (x < 0 || y < 0 || y + 8 > area_b.h || !buf[0])
+        return;
+    tft.setTextColor (color);
+    tft.setCursor (area_b.x + x, area_b.y + y);
+    tft.print (buf);
+}
+static int hostTextWidth (const char *str)
+{
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    return (getTextWidth (str));
+}
+static void hostGetDE (float *lat_d, float *lng_d)
+{
+    *lat_d = de_ll.lat_d;
+    *lng_d = de_ll.lng_d;
+}
+static const char *hostGetCall (void)
+{
+    return (getCallsign());
+}
+static void hostLog (const char *msg)
+{
+    Serial.printf (_FX("Plugin %s: %s\n"), n_plugins > 0 ? plugins[cur_plugin].hcp->name : "?", msg);
+}
+
+static const HCPluginHost host = {
+    hostFillRect,
+    hostDrawLine,
+    hostDrawText,
+    hostTextWidth,
+    hostGetDE,
+    hostGetCall,
+    hostLog,
+};
+
+
+/* try to load the given shared library as a plugin.
+ */
+static void loadPlugin (const char *path)
+{
+    if (n_plugins == MAX_PLUGINS) {
+        Serial.printf (_FX("Plugins: already have max %d, ignoring %s\n"), MAX_PLUGINS, path);
+        return;
+    }
+
+    void *handle = dlopen (path, RTLD_NOW | RTLD_LOCAL);
+    if (!handle) {
+        Serial.printf (_FX("Plugins: %s\n"), dlerror());
+        return;
+    }
+
+    HCPluginEntry entry = (HCPluginEntry) dlsym (handle, HCP_ENTRY);
+    const HCPlugin *hcp = entry ? (*entry)() : NULL;
+    if (!hcp || !hcp->name || !hcp->render) {
+        Serial.printf (_FX("Plugins: %s: missing %s or render\n"), path, HCP_ENTRY);
+        dlclose (handle);
+        return;
+    }
+    if (hcp->abi_version != HCP_ABI_VERSION) {
+        Serial.printf (_FX("Plugins: %s: ABI version %d but want %d\n"), path, hcp->abi_version,
+                                                        HCP_ABI_VERSION);
+        dlclose (handle);
+        return;
+    }
+
+    LoadedPlugin &lp = plugins[n_plugins++];
+    lp.handle = handle;
+    lp.hcp = hcp;
+    lp.next_fetch = 0;
+    lp.fetch_ok = true;
+    Serial.printf (_FX("Plugins: loaded %s from %s\n"), hcp->name, path);
+}
+
+/* load each *.so in PLUGINS_DIR, in name order so the pane order is predictable.
+ */
+void initPlugins()
+{
+    std::string dir = our_dir + PLUGINS_DIR;
+    struct dirent **names;
+    int n = scandir (dir.c_str(), &names, NULL, alphasort);
+    if (n < 0)
+        return;             // no dir is fine
+
+    for (int i = 0; i < n; i++) {
+        const char *name = names[i]->d_name;
+        size_t len = strlen (name);
+        if (len > 3 && strcmp (name + len - 3, ".so") == 0) {
+            std::string path = dir + "/" + name;
+            loadPlugin (path.c_str());
+        }
+        free (names[i]);
+    }
+    free (names);
+}
+
+/* return whether any plugins are loaded
+ */
+bool havePlugins()
+{
+    return (n_plugins > 0);
+}
+
+/* draw the current plugin in the given box
+ */
+static void drawPlugin (const SBox &box)
+{
+    const LoadedPlugin &lp = plugins[cur_plugin];
+
+    prepPlotBox (box);
+
+    // title
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (TITLE_COLOR);
+    tft.setCursor (box.x + (box.w - getTextWidth(lp.hcp->name))/2, box.y + TITLE_Y0);
+    tft.print (lp.hcp->name);
+
+    // selector if more than one
+    int footer_h = 0;
+    if (n_plugins > 1) {
+        char buf[30];
+        snprintf (buf, sizeof(buf), "< %d of %d >", cur_plugin+1, n_plugins);
+        selectFontStyle (LIGHT_FONT, FAST_FONT);
+        tft.setTextColor (FOOTER_COLOR);
+        tft.setCursor (box.x + (box.w - getTextWidth(buf))/2, box.y + box.h - FOOTER_DY + 1);
+        tft.print (buf);
+        footer_h = FOOTER_DY;
+    }
+
+    // let plugin draw the rest
+    area_b.x = box.x + 1;
+    area_b.y = box.y + AREA_Y0;
+    area_b.w = box.w - 2;
+    area_b.h = box.h - AREA_Y0 - footer_h - 1;
+    if (!lp.fetch_ok)
+        plotMessage (box, RA8875_RED, _FX("Plugin fetch failed"));
+    else
+        (*lp.hcp->render) (&host, area_b.w, area_b.h);
+}
+
+/* fetch the current plugin if due then draw it in the given box.
+ * return whether its fetch was ok.
+ */
+bool updatePlugins (const SBox &box)
+{
+    if (n_plugins == 0)
+        return (false);
+
+    LoadedPlugin &lp = plugins[cur_plugin];
+    bool again = lp.hcp->fetch_secs > 0 || !lp.fetch_ok;
+    if (lp.hcp->fetch && (lp.next_fetch == 0 || (again && now() >= lp.next_fetch))) {
+        resetWatchdog();
+        lp.fetch_ok = (*lp.hcp->fetch) (&host) == 0;
+        lp.next_fetch = now() + (lp.fetch_ok ? lp.hcp->fetch_secs : FETCH_RETRY);
+        if (!lp.fetch_ok)
+            hostLog (_FX("fetch failed"));
+    }
+
+    drawPlugin (box);
+
+    return (lp.fetch_ok);
+}
+
+/* called when the given location is tapped below the title of the given box showing the Plugins.
+ * tapping the selector shows the previous or next plugin, else pass tap to the plugin.
+ * return whether tap was ours.
+ */
+bool checkPluginsTouch (const SCoord &s, const SBox &box)
+{
+    if (!inBox (s, box) || s.y < box.y + AREA_Y0 || n_plugins == 0)
+        return (false);
+
+    if (n_plugins > 1 && s.y >= box.y + box.h - FOOTER_DY) {
+        // left half goes back, right half forward
+        if (s.x < box.x + box.w/2)
+            cur_plugin = (cur_plugin + n_plugins - 1) % n_plugins;
+        else
+            cur_plugin = (cur_plugin + 1) % n_plugins;
+        (void) updatePlugins (box);
+    } else {
+        const LoadedPlugin &lp = plugins[cur_plugin];
+        if (lp.hcp->tap) {
+            (*lp.hcp->tap) (&host, s.x - area_b.x, s.y - area_b.y);
+            drawPlugin (box);
+        }
+    }
+
+    return (true);
+}
+
+#else // !_IS_UNIX
+
+/* dummy versions for systems that can not load shared libraries
+ */
+
+void initPlugins()
+{
+}
+
+bool havePlugins()
+{
+    return (false);
+}
+
+bool updatePlugins (const SBox &box)
+{
+    (void) box;
+    return (false);
+}
+
+bool checkPluginsTouch (const SCoord &s, const SBox &box)
+{
+    (void) s;
+    (void) box;
+    return (false);
+}
+
+#endif // _IS_UNIX
@@ -655,3 +655,57 @@ bool nearestPrefix (const LatLong &ll, char prefix[MAX_PREF_LEN+1])
     return (true);
 }
 
+
+/* return the rough continent at ll as a two letter code NA, SA, EU, AF, AS, OC or AN.
+ * boundaries are simple boxes that follow the DXCC continent of most entities but may be wrong for some
+ * islands and near the edges.
+ */
+const char *llContinent (const LatLong &ll)
+{
+    float lat = ll.lat_d;
+    float lng = ll.lng_d;
+
+    if (lat < -60)
+        return ("AN");
+
+    // Pacific, Americas and Greenland
+    if (lng <= -170)
+        return (lat > 45 ? "NA" : "OC");                        // Aleutians else Samoa etc
+    if (lng > 170 && lat > 45)
+        return ("NA");                                          // western Aleutians
+    if (lng < -120 && lat < 30)
+        return ("OC");                                          // Hawaii and eastern Pacific
+    if (lng < -32 || (lng < -10 && lat > 67) || (lng < -20 && lat < -50))
+        return (lat >= 13 || (lng < -77.5F && lat >= 7) ? "NA" : "SA");
+
+    // Africa, Europe and western Asia
+    if (lng < 60) {
+        if (lat < 12)
+            return ("AF");
+        if (lat < 22 && lng < 38 + (22-lat)/2)
+            return ("AF");                                      // west of the Red Sea
+        if (lng >= 34.5F && lat < 35.5F)
+            return ("AS");                                      // Middle East
+        if ((lng < -1 && lat < 35.95F) || (lng < 11 && lat < 37.5F) || (lng < 25 && lat < 34)
+                                || (lng < 34.5F && lat < 31.7F))
+            return ("AF");                                      // north African coast
+        if (lat < 41.2F && lng >= 29)
+            return ("AS");                                      // Anatolia and Cyprus
+        if (lat < 44 && lng > 37)
+            return ("AS");                                      // Caucasus
+        if (lat < 51.5F && lng > 46)
+            return ("AS");                                      // western Kazakhstan
+        return ("EU");
+    }
+
+    // rest of Asia and Oceania
+    if (lat < 7 && lng >= 95 && !(lat > 1 && lng > 99.5F && lng < 104.5F))
+        return ("OC");                                          // Indonesia, East Malaysia etc
+    if (lat < 21.5F && lng >= 116 && lng <= 127)
+        return ("OC");                                          // Philippines
+    if (lat < 24 && lng >= 130)
+        return ("OC");                                          // western Pacific
+    if (lat < 0)
+        return (lng < 95 ? "AF" : "OC");                        // Indian Ocean else Australia etc
+    return ("AS");
+}
@@ -0,0 +1,345 @@
+/* encode and draw short text, such as a URL, as a QR code so it can be scanned with a phone.
+ *
+ * supports byte mode at error correction level L in versions 1 through 5, so up to 106 bytes, which
+ * each have just one Reed-Solomon block. The mask with the lowest penalty per ISO 18004 is used.
+ * Loosely based on the QR Code generator library by Project Nayuki, MIT license.
+ */
+
+#include "HamClock.h"
+
+
+#define QR_MAXV         5                       // largest version supported
+#define QR_QUIET        2                       // quiet zone, modules; spec asks 4 but scanners cope
+
+// data and ECC codewords for each version at level L, index 0 unused
+static const uint8_t qr_ndata[QR_MAXV+1] = {0, 19, 34, 55, 80, 108};
+static const uint8_t qr_necc[QR_MAXV+1]  = {0,  7, 10, 15, 20,  26};
+
+// working state while building one code
+typedef struct {
+    int n;                                      // modules on a side
+    uint8_t dark[QR_MAXN][QR_MAXN];             // [y][x] 1 if dark
+    uint8_t func[QR_MAXN][QR_MAXN];             // [y][x] 1 if part of a function pattern
+} QRWork;
+
+
+/* multiply two elements of GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1
+ */
+static uint8_t gfMul (uint8_t a, uint8_t b)
+{
+    uint8_t z = 0;
+    for (int i = 7; i >= 0; i--) {
+        z = (uint8_t)((z << 1) ^ ((z >> 7) * 0x11D));
+        if ((b >> i) & 1)
+            z ^= a;
+    }
+    return (z);
+}
+
+/* compute the n_ecc Reed-Solomon check codewords for the n_data codewords in data[]
+ */
+static void qrECC (const uint8_t data[], int n_data, uint8_t ecc[], int n_ecc)
+{
+    // generator polynomial coefficients, highest power first without the leading 1
+    uint8_t gen[QR_MAXN];
+    memset (gen, 0, n_ecc);
+    gen[n_ecc-1] = 1;
+    uint8_t root = 1;
+    for (int i = 0; i < n_ecc; i++) {
+        for (int j = 0; j < n_ecc; j++) {
+            gen[j] = gfMul (gen[j], root);
+            if (j+1 < n_ecc)
+                gen[j] ^= gen[j+1];
+        }
+        root = gfMul (root, 0x02);
+    }
+
+    // polynomial division remainder
+    memset (ecc, 0, n_ecc);
+    for (int i = 0; i < n_data; i++) {
+        uint8_t factor = data[i] ^ ecc[0];
+        memmove (ecc, ecc+1, n_ecc-1);
+        ecc[n_ecc-1] = 0;
+        for (int j = 0; j < n_ecc; j++)
+            ecc[j] ^= gfMul (gen[j], factor);
+    }
+}
+
+/* append the nb low bits of val to the bit stream in cw[] at n_bits, msb first
+ */
+static void qrPutBits (uint8_t cw[], int &n_bits, int val, int nb)
+{
+    for (int b = nb-1; b >= 0; b--, n_bits++)
+        if ((val >> b) & 1)
+            cw[n_bits/8] |= 0x80 >> (n_bits%8);
+}
+
+/* set module x,y as part of a function pattern
+ */
+static void qrSetFunc (QRWork &w, int x, int y, bool dark)
+{
+    w.dark[y][x] = dark;
+    w.func[y][x] = 1;
+}
+
+/* draw a finder pattern and its separator centered at x,y
+ */
+static void qrFinder (QRWork &w, int x, int y)
+{
+    for (int dy = -4; dy <= 4; dy++) {
+        for (int dx = -4; dx <= 4; dx++) {
+            int xx = x + dx, yy = y + dy;
+            if (xx < 0 || xx >= w.n || yy < 0 || yy >= w.n)
+                continue;
+            int dist = abs(dx) > abs(dy) ? abs(dx) : abs(dy);
+            qrSetFunc (w, xx, yy, dist != 2 && dist != 4);
+        }
+    }
+}
+
+/* draw the format bits for the given mask at level L
+ */
+static void qrFormat (QRWork &w, int mask)
+{
+    int data = (1 << 3) | mask;                 // level L is 01
+    int rem = data;
+    for (int i = 0; i < 10; i++)
+        rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+    int bits = ((data << 10) | rem) ^ 0x5412;
+
+    // first copy around the top left finder
+    for (int i = 0; i <= 5; i++)
+        qrSetFunc (w, 8, i, (bits >> i) & 1);
+    qrSetFunc (w, 8, 7, (bits >> 6) & 1);
+    qrSetFunc (w, 8, 8, (bits >> 7) & 1);
+    qrSetFunc (w, 7, 8, (bits >> 8) & 1);
+    for (int i = 9; i < 15; i++)
+        qrSetFunc (w, 14 - i, 8, (bits >> i) & 1);
+
+    // second copy split between the other two finders
+    for (int i = 0; i < 8; i++)
+        qrSetFunc (w, w.n - 1 - i, 8, (bits >> i) & 1);
+    for (int i = 8; i < 15; i++)
+        qrSetFunc (w, 8, w.n - 15 + i, (bits >> i) & 1);
+    qrSetFunc (w, 8, w.n - 8, true);            // always dark
+}
+
+/* return whether mask pattern m darkens non-function module x,y
+ */
+static bool qrMaskBit (int m, int x, int y)
+{
+    switch (m) {
+    case 0: return ((x + y) % 2 == 0);
+    case 1: return (y % 2 == 0);
+    case 2: return (x % 3 == 0);
+    case 3: return ((x + y) % 3 == 0);
+    case 4: return ((x/3 + y/2) % 2 == 0);
+    case 5: return (x*y % 2 + x*y % 3 == 0);
+    case 6: return ((x*y % 2 + x*y % 3) % 2 == 0);
+    default: return (((x + y) % 2 + x*y % 3) % 2 == 0);
+    }
+}
+
+/* toggle all non-function modules with mask m, applying twice restores the original
+ */
+static void qrApplyMask (QRWork &w, int m)
+{
+    for (int y = 0; y < w.n; y++)
+        for (int x = 0; x < w.n; x++)
+            if (!w.func[y][x] && qrMaskBit (m, x, y))
+                w.dark[y][x] ^= 1;
+}
+
+/* return the module at x,y in row or column order, outside counts as light
+ */
+static int qrAt (const QRWork &w, bool rows, int i, int j)
+{
+    if (j < 0 || j >= w.n)
+        return (0);
+    return (rows ? w.dark[i][j] : w.dark[j][i]);
+}
+
+/* return the ISO 18004 penalty score of the current modules
+ */
+static long qrPenalty (const QRWork &w)
+{
+    static const uint8_t finder[7] = {1, 0, 1, 1, 1, 0, 1};
+    long penalty = 0;
+
+    // runs of 5 or more and finder-like patterns with 4 light on either side, in rows then columns
+    for (int pass = 0; pass < 2; pass++) {
+        bool rows = pass == 0;
+        for (int i = 0; i < w.n; i++) {
+            int run = 1;
+            for (int j = 1; j <= w.n; j++) {
+                if (j < w.n && qrAt (w, rows, i, j) == qrAt (w, rows, i, j-1)) {
+                    run++;
+                } else {
+                    if (run >= 5)
+                        penalty += 3 + (run - 5);
+                    run = 1;
+                }
+            }
+            for (int j = 0; j + 7 <= w.n; j++) {
+                bool match = true;
+                for (int k = 0; k < 7 && match; k++)
+                    match = qrAt (w, rows, i, j+k) == finder[k];
+                if (!match)
+                    continue;
+                bool light_before = true, light_after = true;
+                for (int k = 1; k <= 4; k++) {
+                    light_before = light_before && !qrAt (w, rows, i, j-k);
+                    light_after = light_after && !qrAt (w, rows, i, j+6+k);
+                }
+                if (light_before || light_after)
+                    penalty += 40;
+            }
+        }
+    }
+
+    // 2x2 blocks of one color
+    for (int y = 0; y < w.n-1; y++)
+        for (int x = 0; x < w.n-1; x++)
+            if (w.dark[y][x] == w.dark[y][x+1] && w.dark[y][x] == w.dark[y+1][x]
+                                               && w.dark[y][x] == w.dark[y+1][x+1])
+                penalty += 3;
+
+    // balance of dark and light
+    int n_dark = 0;
+    for (int y = 0; y < w.n; y++)
+        for (int x = 0; x < w.n; x++)
+            n_dark += w.dark[y][x];
+    int total = w.n * w.n;
+    int k = (abs (n_dark*20 - total*10) + total - 1) / total - 1;
+    if (k > 0)
+        penalty += k * 10;
+
+    return (penalty);
+}
+
+/* encode text as a QR code into qr[y][x], 1 for dark.
+ * return the number of modules on a side, or 0 if text is too long.
+ */
+int makeQRCode (const char *text, uint8_t qr[QR_MAXN][QR_MAXN])
+{
+    // smallest version that holds text in byte mode with its 4 bit mode and 8 bit count
+    int len = strlen (text);
+    int v;
+    for (v = 1; v <= QR_MAXV; v++)
+        if (len <= (qr_ndata[v]*8 - 12) / 8)
+            break;
+    if (v > QR_MAXV)
+        return (0);
+    int n_data = qr_ndata[v];
+    int n_ecc = qr_necc[v];
+
+    // data bit stream: mode, count, bytes, terminator, then alternating pad bytes
+    uint8_t codewords[QR_MAXN*QR_MAXN/8];
+    memset (codewords, 0, n_data);
+    int n_bits = 0;
+    qrPutBits (codewords, n_bits, 0x4, 4);
+    qrPutBits (codewords, n_bits, len, 8);
+    for (int i = 0; i < len; i++)
+        qrPutBits (codewords, n_bits, (uint8_t)text[i], 8);
+    int term = n_data*8 - n_bits;
+    n_bits += term < 4 ? term : 4;
+    n_bits = (n_bits + 7) / 8 * 8;
+    for (int pad = 0xEC; n_bits < n_data*8; pad ^= 0xEC ^ 0x11)
+        qrPutBits (codewords, n_bits, pad, 8);
+
+    // one block so the check codewords simply follow the data
+    qrECC (codewords, n_data, codewords + n_data, n_ecc);
+    int n_codewords = n_data + n_ecc;
+
+    // function patterns
+    QRWork *wp = (QRWork *) calloc (1, sizeof(QRWork));
+    if (!wp)
+        return (0);
+    QRWork &w = *wp;
+    w.n = 17 + 4*v;
+    for (int i = 0; i < w.n; i++) {
+        qrSetFunc (w, 6, i, i % 2 == 0);
+        qrSetFunc (w, i, 6, i % 2 == 0);
+    }
+    qrFinder (w, 3, 3);
+    qrFinder (w, w.n - 4, 3);
+    qrFinder (w, 3, w.n - 4);
+    if (v >= 2) {
+        int a = w.n - 7;
+        for (int dy = -2; dy <= 2; dy++)
+            for (int dx = -2; dx <= 2; dx++)
+                qrSetFunc (w, a + dx, a + dy, (abs(dx) > abs(dy) ? abs(dx) : abs(dy)) != 1);
+    }
+    qrFormat (w, 0);                            // reserve format areas, real mask set below
+
+    // codewords zigzag up and down pairs of columns from the right, skipping the vertical timing column
+    int bit = 0;
+    for (int right = w.n - 1; right >= 1; right -= 2) {
+        if (right == 6)
+            right = 5;
+        for (int vert = 0; vert < w.n; vert++) {
+            for (int j = 0; j < 2; j++) {
+                int x = right - j;
+                bool upward = ((right + 1) & 2) == 0;
+                int y = upward ? w.n - 1 - vert : vert;
+                if (!w.func[y][x] && bit < n_codewords*8) {
+                    w.dark[y][x] = (codewords[bit/8] >> (7 - bit%8)) & 1;
+                    bit++;
+                }
+            }
+        }
+    }
+
+    // find the mask with the lowest penalty
+    int best_mask = 0;
+    long best_penalty = 0;
+    for (int m = 0; m < 8; m++) {
+        qrApplyMask (w, m);
+        qrFormat (w, m);
+        long p = qrPenalty (w);
+        if (m == 0 || p < best_penalty) {
+            best_mask = m;
+            best_penalty = p;
+        }
+        qrApplyMask (w, m);
+    }
+    qrApplyMask (w, best_mask);
+    qrFormat (w, best_mask);
+
+    // pass back
+    for (int y = 0; y < w.n; y++)
+        memcpy (qr[y], w.dark[y], w.n);
+    int n = w.n;
+    free (wp);
+    return (n);
+}
+
+/* draw text as a QR code as large as fits centered in box, dark on white with a quiet zone.
+ * return false if text is too long or box is too small.
+ */
+bool drawQRCode (const char *text, const SBox &box)
+{
+    StackMalloc qr_mem(QR_MAXN*QR_MAXN);
+    uint8_t (*qr)[QR_MAXN] = (uint8_t (*)[QR_MAXN]) qr_mem.getMem();
+    int n = makeQRCode (text, qr);
+    if (n == 0)
+        return (false);
+
+    int side = box.w < box.h ? box.w : box.h;
+    int mod = side / (n + 2*QR_QUIET);
+    if (mod < 2)
+        return (false);
+
+    int size = mod * (n + 2*QR_QUIET);
+    uint16_t x0 = box.x + (box.w - size)/2;
+    uint16_t y0 = box.y + (box.h - size)/2;
+    tft.fillRect (x0, y0, size, size, RA8875_WHITE);
+    x0 += mod*QR_QUIET;
+    y0 += mod*QR_QUIET;
+    for (int y = 0; y < n; y++)
+        for (int x = 0; x < n; x++)
+            if (qr[y][x])
+                tft.fillRect (x0 + x*mod, y0 + y*mod, mod, mod, RA8875_BLACK);
+
+    return (true);
+}
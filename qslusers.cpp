@@ -0,0 +1,223 @@
+/* flag spotted stations that confirm QSOs through LoTW or are eQSL Authenticity Guaranteed members, for
+ * award chasers who only want to work stations likely to confirm.
+ *
+ * each list is downloaded weekly with curl into our_dir then loaded into a sorted table. LoTW users count
+ * only if they uploaded within QSLU_LOTWDAYS.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#include <fcntl.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+
+#define QSLU_REFRESH    (7*SECSPERDAY)          // download each list this often, secs
+#define QSLU_RETRY      3600                    // but try no more often than this, secs
+#define QSLU_LOTWDAYS   365                     // ignore LoTW users who have not uploaded this long, days
+#define QSLU_CALLLEN    MAX_SPOTCALL_LEN        // table entry length, including EOS
+
+// one list of users
+typedef struct {
+    const char *name;                           // for messages
+    const char *url;                            // where to get it
+    const char *fn;                             // saved in our_dir
+    uint8_t flag;                               // QSLU_ bit for its users
+    char *calls;                                // malloced sorted table of n_calls QSLU_CALLLEN entries
+    int n_calls;                                // n entries in calls
+    time_t loaded;                              // mtime of fn when loaded, 0 if not
+    time_t tried;                               // when download was last started, 0 if never
+} QSLUserList;
+
+static QSLUserList lists[] = {
+    { "LoTW",  "https://lotw.arrl.org/lotw-user-activity.csv",                  "lotw-users.csv", QSLU_LOTW },
+    { "eQSL",  "https://www.eqsl.cc/qslcard/DownloadedFiles/AGMemberList.txt", "eqsl-ag.txt",    QSLU_EQSL },
+};
+#define N_LISTS NARRAY(lists)
+
+
+/* qsort and bsearch-style function to compare two table entries
+ */
+static int callQS (const void *p1, const void *p2)
+{
+    return (strcmp ((const char *)p1, (const char *)p2));
+}
+
+/* start downloading the given list in the background.
+ * N.B. double fork so curl is never left a zombie and we never wait for it; curl writes to a tmp file
+ *   that is renamed only when complete so we never load a partial list.
+ */
+static void downloadList (QSLUserList &l)
+{
+    l.tried = now();
+
+    std::string path = our_dir + l.fn;
+    std::string cmd = std::string("curl -fsSL --max-time 300 -o '") + path + ".tmp' '" + l.url
+                                + "' && mv '" + path + ".tmp' '" + path + "'";
+    Serial.printf (_FX("QSLUsers: downloading %s\n"), l.url);
+
+    pid_t pid = fork();
+    if (pid < 0) {
+        Serial.printf (_FX("QSLUsers: fork(2): %s\n"), strerror(errno));
+        return;
+    }
+
+    if (pid == 0) {
+        if (fork() == 0) {
+            int null_fd = open ("/dev/null", O_RDWR);
+            dup2 (null_fd, 0);
+            dup2 (null_fd, 1);
+            dup2 (null_fd, 2);
+            for (int fd = 3; fd < 1024; fd++)
+                close (fd);
+            execl ("/bin/sh", "sh", "-c", cmd.c_str(), (char*)NULL);
+        }
+        _exit(0);
+    }
+
+    (void) waitpid (pid, NULL, 0);
+}
+
+/* load the given list from its file whose mtime is given.
+ */
+static void loadList (QSLUserList &l, time_t mtime)
+{
+    l.loaded = mtime;
+
+    std::string path = our_dir + l.fn;
+    FILE *fp = fopen (path.c_str(), "r");
+    if (!fp) {
+        Serial.printf (_FX("QSLUsers: %s: %s\n"), path.c_str(), strerror(errno));
+        return;
+    }
+
+    // LoTW lines are call,yyyy-mm-dd,hh:mm:ss of last upload, ignore those too old
+    char oldest[20];
+    time_t t0 = now() - QSLU_LOTWDAYS*SECSPERDAY;
+    snprintf (oldest, sizeof(oldest), "%04d-%02d-%02d", year(t0), month(t0), day(t0));
+
+    // collect each sensible call, growing table as needed
+    char *calls = NULL;
+    int n_calls = 0, n_malloc = 0;
+    char line[100];
+    while (fgets (line, sizeof(line), fp)) {
+        size_t cl = strcspn (line, ",\r\n");
+        if (cl < 3 || cl >= QSLU_CALLLEN || strspn (line, "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789/") != cl)
+            continue;
+        if (l.flag == QSLU_LOTW && (line[cl] != ',' || strncmp (line+cl+1, oldest, 10) < 0))
+            continue;
+        if (n_calls == n_malloc) {
+            n_malloc += 10000;
+            char *new_calls = (char *) realloc (calls, n_malloc * QSLU_CALLLEN);
+            if (!new_calls) {
+                Serial.printf (_FX("QSLUsers: no memory for %s after %d\n"), l.name, n_calls);
+                break;
+            }
+            calls = new_calls;
+        }
+        snprintf (calls + n_calls++ * QSLU_CALLLEN, QSLU_CALLLEN, "%.*s", (int)cl, line);
+    }
+    fclose (fp);
+
+    qsort (calls, n_calls, QSLU_CALLLEN, callQS);
+
+    free (l.calls);
+    l.calls = calls;
+    l.n_calls = n_calls;
+    Serial.printf (_FX("QSLUsers: %d %s users\n"), n_calls, l.name);
+}
+
+/* return whether the given list contains call
+ */
+static bool listHas (const QSLUserList &l, const char *call)
+{
+    return (l.n_calls > 0 && bsearch (call, l.calls, l.n_calls, QSLU_CALLLEN, callQS) != NULL);
+}
+
+/* called often to download each list when due and load it when it changes
+ */
+void checkQSLUsers (void)
+{
+    bool changed = false;
+
+    for (unsigned i = 0; i < N_LISTS; i++) {
+        QSLUserList &l = lists[i];
+
+        struct stat s;
+        std::string path = our_dir + l.fn;
+        bool have = stat (path.c_str(), &s) == 0;
+
+        if ((!have || now() - s.st_mtime > QSLU_REFRESH) && (!l.tried || now() - l.tried > QSLU_RETRY)
+                                && !isOffline())
+            downloadList (l);
+
+        if (have && s.st_mtime != l.loaded) {
+            loadList (l, s.st_mtime);
+            changed = true;
+        }
+    }
+
+    if (changed)
+        updateDXClusterSpotFlags();
+}
+
+/* return the QSLU_ bits of each list the given call is on.
+ * a portable call such as VP2/K1ABC/P matches any of its portions.
+ */
+uint8_t getQSLUserFlags (const char *call)
+{
+    char ucall[QSLU_CALLLEN];
+    snprintf (ucall, sizeof(ucall), "%s", call);
+    for (char *cp = ucall; *cp; cp++)
+        *cp = toupper (*cp);
+
+    uint8_t flags = 0;
+    for (unsigned i = 0; i < N_LISTS; i++) {
+        if (listHas (lists[i], ucall)) {
+            flags |= lists[i].flag;
+            continue;
+        }
+        if (strchr (ucall, '/')) {
+            char parts[QSLU_CALLLEN];
+            strcpy (parts, ucall);
+            for (char *part = strtok (parts, "/"); part != NULL; part = strtok (NULL, "/"))
+                if (strlen (part) >= 3 && listHas (lists[i], part))
+                    flags |= lists[i].flag;
+        }
+    }
+    return (flags);
+}
+
+/* print the size and age of each list on one line with the given prefix
+ */
+void printQSLUsers (WiFiClient &client, const char *prefix)
+{
+    char buf[150];
+    size_t l = snprintf (buf, sizeof(buf), "%s", prefix);
+    for (unsigned i = 0; i < N_LISTS && l < sizeof(buf); i++) {
+        if (lists[i].loaded)
+            l += snprintf (buf+l, sizeof(buf)-l, "%s%s %d users %ld days old", i > 0 ? ", " : "",
+                        lists[i].name, lists[i].n_calls, (long)((now() - lists[i].loaded)/SECSPERDAY));
+        else
+            l += snprintf (buf+l, sizeof(buf)-l, "%s%s not loaded", i > 0 ? ", " : "", lists[i].name);
+    }
+    client.println (buf);
+}
+
+#else // !_IS_UNIX
+
+/* dummy versions for systems with no curl
+ */
+void checkQSLUsers (void)
+{
+}
+
+uint8_t getQSLUserFlags (const char *call)
+{
+    (void) call;
+    return (0);
+}
+
+#endif // _IS_UNIX
@@ -0,0 +1,415 @@
+/* QSO rate meter shown in a plot pane: rates over the last 10 and 100 QSOs, a graph of QSOs per
+ * hour over recent hours and counts per band and mode for this session.
+ *
+ * QSOs arrive from WSJT-X or JTDX QSO Logged messages while the DX cluster pane is listening to them
+ * and, on UNIX, from an ADIF log file named with -q that we tail as the logging program appends to it.
+ * the session starts when HamClock starts or is reset from the web server.
+ */
+
+#include "HamClock.h"
+
+
+// layout
+#define TITLE_COLOR     RA8875_GREEN
+#define RATE_COLOR      RA8875_WHITE
+#define DETAIL_COLOR    GRAY
+#define BAR_COLOR       RA8875_CYAN
+#define TITLE_Y0        27              // title dy, match VOACAP title position
+#define RATE_Y0         36              // rates y down from box top
+#define GRAPH_Y0        58              // graph top y down from box top
+#define GRAPH_H         40              // graph height
+#define GRAPH_X0        8               // graph left x offset from box left
+#define GRAPH_NBIN      24              // n graph bars
+#define GRAPH_BINSECS   (10*60)         // seconds per bar
+#define GRAPH_BARW      6               // bar width, includes gap
+#define COUNTS_Y0       112             // first band and mode counts line y down from box top
+#define COUNTS_DY       11              // counts line spacing
+#define COUNTS_N        3               // max counts lines
+
+#define QR_NQSO         300             // QSO times we keep, newest last
+#define QR_NMODES       8               // max distinct modes we count
+#define QR_MODELEN      8               // longest mode name we count, including EOS
+
+// one mode and how many times it was logged
+typedef struct {
+    char name[QR_MODELEN];
+    uint16_t n;
+} ModeCount;
+
+static time_t qso_t[QR_NQSO];           // times of the most recent QSOs, oldest first
+static int n_qso_t;                     // n used in qso_t[]
+static uint32_t n_session;              // total QSOs this session
+static time_t session_t0;               // session start time
+static uint16_t band_n[HB_N+1];         // QSOs per HamBand this session, HB_NONE last
+static ModeCount modes[QR_NMODES];      // QSOs per mode this session
+static int n_modes;                     // n used in modes[]
+
+
+
+#if defined(_IS_UNIX)
+
+#include <errno.h>
+
+#define ADIF_CHUNK      65536           // read log file this much at a time
+
+static const char *adif_fn;             // ADIF log file we tail, if any
+static long adif_pos;                   // file offset just after last complete record we read
+
+/* set the ADIF log file to follow for QSO rates.
+ * N.B. fn must remain valid, such as an argv element.
+ */
+void setQSOLogFile (const char *fn)
+{
+    adif_fn = fn;
+    adif_pos = 0;
+}
+
+/* return the ADIF log file we follow, else NULL
+ */
+const char *getQSOLogFile (void)
+{
+    return (adif_fn);
+}
+
+/* crack QSO_DATE YYYYMMDD and TIME_ON HHMM[SS] into a UTC time.
+ * return whether both look sensible.
+ */
+static bool crackADIFTime (const char *date, const char *time_on, time_t &t)
+{
+    int yr, mo, dy, hr, mn, sc = 0;
+    if (sscanf (date, "%4d%2d%2d", &yr, &mo, &dy) != 3 || sscanf (time_on, "%2d%2d%2d", &hr, &mn, &sc) < 2)
+        return (false);
+
+    tmElements_t tm;
+    tm.Year = yr - 1970;
+    tm.Month = mo;
+    tm.Day = dy;
+    tm.Hour = hr;
+    tm.Minute = mn;
+    tm.Second = sc;
+    t = makeTime (tm);
+    return (true);
+}
+
+/* call cb with the values of names[], empty if absent, for each complete record in buf[0..len-1].
+ * N.B. n_names must be no more than MAX_ADIF_NAMES.
+ * return length of buf consumed through the last <EOR> or <EOH>.
+ */
+static size_t walkADIFRecords (const char *buf, size_t len, const char *names[], int n_names, ADIFRecordCB cb)
+{
+    ADIFValue values[MAX_ADIF_NAMES];
+    memset (values, 0, sizeof(values));
+    size_t used = 0;
+
+    for (size_t i = 0; i < len; i++) {
+
+        // fields look like <NAME:len[:type]>value or <EOR>
+        if (buf[i] != '<')
+            continue;
+        const char *name = buf + i + 1;
+        const char *gt = (const char *) memchr (name, '>', len - (i+1));
+        if (!gt)
+            break;                              // field not complete yet
+        size_t name_len = strcspn (name, ":>");
+        size_t vlen = name[name_len] == ':' ? atol (name + name_len + 1) : 0;
+        const char *value = gt + 1;
+        if (value + vlen > buf + len)
+            break;                              // value not complete yet
+        i = (value + vlen) - buf - 1;
+
+        if (name_len == 3 && strncasecmp (name, "EOR", 3) == 0) {
+            (*cb) (values);
+            memset (values, 0, sizeof(values));
+            used = i + 1;
+        } else if (name_len == 3 && strncasecmp (name, "EOH", 3) == 0) {
+            memset (values, 0, sizeof(values));
+            used = i + 1;
+        } else {
+            for (int f = 0; f < n_names; f++) {
+                if (strlen (names[f]) == name_len && strncasecmp (name, names[f], name_len) == 0) {
+                    snprintf (values[f], MAX_ADIF_FIELD+1, "%.*s",
+                                        (int)(vlen < MAX_ADIF_FIELD ? vlen : MAX_ADIF_FIELD), value);
+                    break;
+                }
+            }
+        }
+    }
+
+    return (used);
+}
+
+/* read the complete ADIF records in fp from offset pos up to size, ADIF_CHUNK at a time, calling cb for
+ * each as with walkADIFRecords(). a full chunk without any record end, such as from a huge header or a
+ * damaged file, is skipped up to its last tag so the reader keeps moving instead of stalling there.
+ * return offset just after the last complete record.
+ */
+long readADIFRecords (FILE *fp, long pos, long size, const char *names[], int n_names, ADIFRecordCB cb)
+{
+    char *buf = (char *) malloc (ADIF_CHUNK);
+    if (!buf)
+        fatalError (_FX("No memory for ADIF chunk"));
+
+    while (pos < size) {
+        fseek (fp, pos, SEEK_SET);
+        size_t len = fread (buf, 1, ADIF_CHUNK, fp);
+        size_t used = walkADIFRecords (buf, len, names, n_names, cb);
+        if (used == 0) {
+            if (len < ADIF_CHUNK)
+                break;                          // rest of record not written yet
+            size_t last_lt = len;
+            while (--last_lt > 0 && buf[last_lt] != '<')
+                continue;
+            used = last_lt > 0 ? last_lt : len;
+            Serial.printf (_FX("ADIF: no record end in %d bytes at %ld, skipping %ld\n"), ADIF_CHUNK, pos,
+                                        (long)used);
+        }
+        pos += used;
+    }
+
+    free (buf);
+    return (pos);
+}
+
+/* return kHz from an ADIF FREQ in MHz, else the middle of BAND, else 0
+ */
+float crackADIFkHz (const char *freq, const char *band)
+{
+    float kHz = atof (freq) * 1000;
+    if (kHz == 0) {
+        float lo, hi;
+        for (int b = 0; b < HB_N; b++) {
+            if (strcasecmp (band, getHamBandName((HamBand)b)) == 0 && getHamBandEdges ((HamBand)b, lo, hi)) {
+                kHz = (lo + hi)/2;
+                break;
+            }
+        }
+    }
+    return (kHz);
+}
+
+/* ADIF fields we use, N.B. order must match names in checkQSOLogFile()
+ */
+enum {QF_DATE, QF_TIME, QF_FREQ, QF_BAND, QF_MODE, QF_N};
+
+/* note one ADIF record if it was logged this session
+ */
+static void noteADIFRecord (ADIFValue values[])
+{
+    time_t t;
+    if (crackADIFTime (values[QF_DATE], values[QF_TIME], t) && t >= session_t0)
+        noteLoggedQSO (t, crackADIFkHz (values[QF_FREQ], values[QF_BAND]), values[QF_MODE]);
+}
+
+/* read any complete records appended to adif_fn since last time.
+ * start over if the file shrank, as when a logging program rewrites it.
+ */
+static void checkQSOLogFile(void)
+{
+    static const char *names[QF_N] = {"QSO_DATE", "TIME_ON", "FREQ", "BAND", "MODE"};
+
+    if (!adif_fn)
+        return;
+
+    FILE *fp = fopen (adif_fn, "r");
+    if (!fp) {
+        Serial.printf (_FX("QSORate: %s: %s\n"), adif_fn, strerror(errno));
+        return;
+    }
+
+    fseek (fp, 0, SEEK_END);
+    long size = ftell (fp);
+    if (size < adif_pos)
+        adif_pos = 0;
+    adif_pos = readADIFRecords (fp, adif_pos, size, names, QF_N, noteADIFRecord);
+
+    fclose (fp);
+}
+
+#else // !_IS_UNIX
+
+/* no file system on ESP
+ */
+static void checkQSOLogFile(void)
+{
+}
+
+#endif // _IS_UNIX
+
+
+
+/* return QSOs per hour over the most recent n QSOs, or fewer if we don't have that many.
+ * the period runs from the oldest of those until now but is at least one minute.
+ */
+static int recentQSORate (int n)
+{
+    if (n > n_qso_t)
+        n = n_qso_t;
+    if (n == 0)
+        return (0);
+    long dt = (long)(now() - qso_t[n_qso_t - n]);
+    if (dt < 60)
+        dt = 60;
+    return ((int)(3600L * n / dt));
+}
+
+/* record one QSO logged at time t on the given frequency and mode.
+ */
+void noteLoggedQSO (time_t t, float kHz, const char *mode)
+{
+    // add to qso_t[] in time order, discarding the oldest if full
+    if (n_qso_t == QR_NQSO)
+        memmove (qso_t, qso_t+1, (--n_qso_t)*sizeof(qso_t[0]));
+    int i = n_qso_t;
+    while (i > 0 && qso_t[i-1] > t) {
+        qso_t[i] = qso_t[i-1];
+        i--;
+    }
+    qso_t[i] = t;
+    n_qso_t++;
+    n_session++;
+
+    // count band and mode
+    band_n[findHamBand(kHz)]++;
+    for (i = 0; i < n_modes; i++)
+        if (strcasecmp (modes[i].name, mode) == 0)
+            break;
+    if (i == n_modes && n_modes < QR_NMODES) {
+        snprintf (modes[i].name, sizeof(modes[i].name), "%s", mode[0] ? mode : "?");
+        n_modes++;
+    }
+    if (i < n_modes)
+        modes[i].n++;
+
+    Serial.printf (_FX("QSORate: %.1f kHz %s, %u this session\n"), kHz, mode, (unsigned)n_session);
+}
+
+/* forget all QSOs and start a new session now
+ */
+void resetQSORate(void)
+{
+    n_qso_t = 0;
+    n_session = 0;
+    n_modes = 0;
+    memset (band_n, 0, sizeof(band_n));
+    session_t0 = now();
+}
+
+/* return whether we have any source of logged QSOs
+ */
+bool haveQSORate(void)
+{
+#if defined(_IS_UNIX)
+    if (adif_fn)
+        return (true);
+#endif // _IS_UNIX
+    const char *host = getDXClusterHost();
+    return (useDXCluster() && (!strcasecmp (host, "WSJT-X") || !strcasecmp (host, "JTDX")));
+}
+
+/* pass back the recent rates and session total after checking for new QSOs
+ */
+void getQSORates (int &last10, int &last100, uint32_t &session)
+{
+    if (session_t0 == 0)
+        session_t0 = now();
+    checkQSOLogFile();
+
+    last10 = recentQSORate (10);
+    last100 = recentQSORate (100);
+    session = n_session;
+}
+
+/* draw one "name n" count at x,y, wrapping to the next line if it would extend past the box.
+ * return whether it fit within the COUNTS_N lines.
+ */
+static bool drawQSOCount (const SBox &box, uint16_t &x, uint16_t &y, const char *name, unsigned n)
+{
+    char buf[30];
+    snprintf (buf, sizeof(buf), "%s %u", name, n);
+    uint16_t w = getTextWidth (buf);
+    if (x + w > box.x + box.w - 4) {
+        x = box.x + 4;
+        y += COUNTS_DY;
+        if (y >= box.y + COUNTS_Y0 + COUNTS_N*COUNTS_DY)
+            return (false);
+    }
+    tft.setCursor (x, y);
+    tft.print (buf);
+    x += w + 8;
+    return (true);
+}
+
+/* draw the QSO rates, graph and session counts in box.
+ * always return true because there is nothing to fetch.
+ */
+bool updateQSORate (const SBox &box)
+{
+    int last10, last100;
+    uint32_t session;
+    getQSORates (last10, last100, session);
+
+    prepPlotBox (box);
+
+    // title
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (TITLE_COLOR);
+    const char *title = xlate ("QSO Rate");
+    tft.setCursor (box.x + (box.w - getTextWidth(title))/2, box.y + TITLE_Y0);
+    tft.print (title);
+
+    // rates
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    tft.setTextColor (RATE_COLOR);
+    char buf[100];
+    snprintf (buf, sizeof(buf), "10: %d/h  100: %d/h", last10, last100);
+    tft.setCursor (box.x + (box.w - getTextWidth(buf))/2, box.y + RATE_Y0);
+    tft.print (buf);
+    tft.setTextColor (DETAIL_COLOR);
+    snprintf (buf, sizeof(buf), xlate ("%u this session"), (unsigned)session);
+    tft.setCursor (box.x + (box.w - getTextWidth(buf))/2, box.y + RATE_Y0 + COUNTS_DY);
+    tft.print (buf);
+
+    // count QSOs in each graph bin, newest on the right
+    uint16_t bins[GRAPH_NBIN];
+    memset (bins, 0, sizeof(bins));
+    time_t t0 = now();
+    for (int i = 0; i < n_qso_t; i++) {
+        long age = (long)(t0 - qso_t[i]);
+        if (age >= 0 && age < GRAPH_NBIN*GRAPH_BINSECS)
+            bins[GRAPH_NBIN - 1 - age/GRAPH_BINSECS]++;
+    }
+    uint16_t max_bin = 1;
+    for (int i = 0; i < GRAPH_NBIN; i++)
+        if (bins[i] > max_bin)
+            max_bin = bins[i];
+
+    // graph, scaled to the busiest bin, labeled with its hourly rate
+    uint16_t gx = box.x + GRAPH_X0;
+    uint16_t gy = box.y + GRAPH_Y0 + GRAPH_H;
+    tft.drawLine (gx, gy, gx + GRAPH_NBIN*GRAPH_BARW, gy, DETAIL_COLOR);
+    for (int i = 0; i < GRAPH_NBIN; i++) {
+        uint16_t h = bins[i] * GRAPH_H / max_bin;
+        if (h > 0)
+            tft.fillRect (gx + i*GRAPH_BARW, gy - h, GRAPH_BARW - 1, h, BAR_COLOR);
+    }
+    snprintf (buf, sizeof(buf), "%u/h", (unsigned)(max_bin * 3600 / GRAPH_BINSECS));
+    tft.setCursor (gx, box.y + GRAPH_Y0 - 2);
+    tft.print (buf);
+    snprintf (buf, sizeof(buf), "-%dh", GRAPH_NBIN*GRAPH_BINSECS/3600);
+    tft.setCursor (gx, gy + 3);
+    tft.print (buf);
+    tft.setCursor (gx + GRAPH_NBIN*GRAPH_BARW - getTextWidth("now"), gy + 3);
+    tft.print ("now");
+
+    // band then mode counts, wrapped as needed
+    uint16_t x = box.x + 4;
+    uint16_t y = box.y + COUNTS_Y0;
+    bool fit = true;
+    for (int b = 0; fit && b <= HB_N; b++)
+        if (band_n[b] > 0)
+            fit = drawQSOCount (box, x, y, getHamBandName((HamBand)b), band_n[b]);
+    for (int m = 0; fit && m < n_modes; m++)
+        fit = drawQSOCount (box, x, y, modes[m].name, modes[m].n);
+
+    return (true);
+}
@@ -0,0 +1,163 @@
+/* quiet hours: a weekly schedule of times when alerts below a chosen severity do not run the on_alert
+ * script, notify web socket clients, wake the display or play a sound. they are still logged to the alert
+ * history.
+ *
+ * each DE week day may have one window, start and end in minutes from DE local midnight. a window whose end
+ * is before its start runs past midnight into the next day; start == end means no quiet time that day.
+ */
+
+#include "HamClock.h"
+
+
+// alert severity names, N.B. index is AlertSeverity and is stored in NV_QUIETKEEP
+static const char *sev_names[ALSEV_N] = { "info", "warning", "critical" };
+
+// alerts at least this severe still notify during quiet hours, ALSEV_N to silence them all
+#define DEF_KEEP        ALSEV_CRITICAL
+static uint8_t quiet_keep;
+
+// 7 start then 7 end times, mins from midnight, same layout as NV_DAILYONOFF
+static uint16_t quiet_times[2*DAYSPERWEEK];
+static bool quiet_loaded;
+
+
+/* load the schedule and keep level from NV once
+ */
+static void loadQuietHours()
+{
+    if (quiet_loaded)
+        return;
+
+    if (!NVReadString (NV_QUIETHOURS, (char*)quiet_times)) {
+        memset (quiet_times, 0, sizeof(quiet_times));
+        NVWriteString (NV_QUIETHOURS, (char*)quiet_times);
+    }
+    if (!NVReadUInt8 (NV_QUIETKEEP, &quiet_keep) || quiet_keep > ALSEV_N) {
+        quiet_keep = DEF_KEEP;
+        NVWriteUInt8 (NV_QUIETKEEP, quiet_keep);
+    }
+
+    quiet_loaded = true;
+}
+
+/* return the severity of the given alert kind
+ */
+AlertSeverity alertSeverity (const char *kind)
+{
+    if (strcmp (kind, "swr") == 0 || strcmp (kind, "alarm") == 0)
+        return (ALSEV_CRITICAL);
+    if (strcmp (kind, "countdown") == 0)
+        return (ALSEV_WARNING);
+    return (ALSEV_INFO);
+}
+
+/* return name of the given severity, or "none" for ALSEV_N
+ */
+const char *alertSeverityName (int sev)
+{
+    return (sev >= 0 && sev < ALSEV_N ? sev_names[sev] : "none");
+}
+
+/* set sev from the given name, including "none" for ALSEV_N. return whether recognized.
+ */
+bool crackAlertSeverity (const char *name, int &sev)
+{
+    for (int i = 0; i < ALSEV_N; i++) {
+        if (strncasecmp (name, sev_names[i], strlen(sev_names[i])) == 0) {
+            sev = i;
+            return (true);
+        }
+    }
+    if (strncasecmp (name, "none", 4) == 0) {
+        sev = ALSEV_N;
+        return (true);
+    }
+    return (false);
+}
+
+/* set the quiet window for the given DE week day 1..7 Sun..Sat, start == end for none.
+ * return false if any value is out of range.
+ */
+bool setQuietHours (int dow, uint16_t start_mins, uint16_t end_mins)
+{
+    if (dow < 1 || dow > DAYSPERWEEK || start_mins >= MINSPERDAY || end_mins >= MINSPERDAY)
+        return (false);
+
+    loadQuietHours();
+    quiet_times[dow-1] = start_mins;
+    quiet_times[dow-1+DAYSPERWEEK] = end_mins;
+    NVWriteString (NV_QUIETHOURS, (char*)quiet_times);
+
+    Serial.printf (_FX("Quiet: %s %02d:%02d-%02d:%02d\n"), dayShortStr(dow),
+                                start_mins/60, start_mins%60, end_mins/60, end_mins%60);
+    return (true);
+}
+
+/* get the quiet window for the given DE week day 1..7 Sun..Sat.
+ * N.B. we do not validate dow
+ */
+void getQuietHours (int dow, uint16_t &start_mins, uint16_t &end_mins)
+{
+    loadQuietHours();
+    start_mins = quiet_times[dow-1];
+    end_mins = quiet_times[dow-1+DAYSPERWEEK];
+}
+
+/* set the lowest severity that still notifies during quiet hours, ALSEV_N for none
+ */
+void setQuietKeep (int sev)
+{
+    loadQuietHours();
+    quiet_keep = sev < 0 || sev > ALSEV_N ? DEF_KEEP : sev;
+    NVWriteUInt8 (NV_QUIETKEEP, quiet_keep);
+}
+
+/* return the lowest severity that still notifies during quiet hours, ALSEV_N for none
+ */
+int getQuietKeep()
+{
+    loadQuietHours();
+    return (quiet_keep);
+}
+
+/* return whether DE local time is now within a quiet window
+ */
+bool quietHoursNow()
+{
+    loadQuietHours();
+
+    time_t de_local = nowWO() + de_tz.tz_secs;
+    int today = weekday (de_local) - 1;
+    int yesterday = (today + DAYSPERWEEK - 1) % DAYSPERWEEK;
+    uint16_t mins = hour (de_local)*60 + minute (de_local);
+
+    // today's window, either all within today or from start to midnight
+    uint16_t start = quiet_times[today];
+    uint16_t end = quiet_times[today+DAYSPERWEEK];
+    if (start < end ? (mins >= start && mins < end) : (start > end && mins >= start))
+        return (true);
+
+    // or the part of yesterday's window that ran past midnight
+    start = quiet_times[yesterday];
+    end = quiet_times[yesterday+DAYSPERWEEK];
+    return (start > end && mins < end);
+}
+
+/* called as each alert happens to decide whether to hold back its notifications.
+ * N.B. caller should still record the alert in the history.
+ */
+bool alertIsQuiet (const char *kind)
+{
+    return (alertIsQuiet (kind, alertSeverity (kind)));
+}
+
+/* same but for an alert given the stated severity instead of that of its kind
+ */
+bool alertIsQuiet (const char *kind, AlertSeverity sev)
+{
+    if (!quietHoursNow() || (int)sev >= getQuietKeep())
+        return (false);
+
+    Serial.printf (_FX("Quiet: %s alert silenced\n"), kind);
+    return (true);
+}
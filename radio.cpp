@@ -22,6 +22,10 @@ static char cleanup_cmds[] = ";SB0;FR0;FT0;RT0;XT0;RC;";
  */
 static char setfreq_fmt[] = ";FA%011.0f;";
 
+/* last frequency sent to the radio, kHz, 0 if none yet
+ */
+static float rig_khz;
+
 
 #if defined(_IS_ESP8266)
 
@@ -98,6 +102,7 @@ void setRadioSpot (float kHz)
     // ignore if not to use GPIO or baud 0
     if (!GPIOOk() || getKX3Baud() == 0)
         return;
+    rig_khz = kHz;
 
     // one-time IO setup
     static bool ready;
@@ -259,6 +264,7 @@ void setRadioSpot (float kHz)
     // ignore if not to use GPIO or baud 0
     if (!GPIOOk() || getKX3Baud() == 0)
         return;
+    rig_khz = kHz;
 
     // one-time IO setup
     static bool ready;
@@ -279,6 +285,16 @@ void setRadioSpot (float kHz)
 
 #endif // _SUPPORT_GPIO
 
+/* pass back the frequency last sent to the radio, if any.
+ */
+bool getRadioSpot (float &kHz)
+{
+    if (rig_khz <= 0 || !GPIOOk() || getKX3Baud() == 0)
+        return (false);
+    kHz = rig_khz;
+    return (true);
+}
+
 #else  // !_SUPPORT_KX3
 
 
@@ -290,5 +306,261 @@ void setRadioSpot (float kHz)
     (void) kHz;
 }
 
+bool getRadioSpot (float &kHz)
+{
+    (void) kHz;
+    return (false);
+}
+
 #endif // _SUPPORT_KX3
 
+
+
+
+/**********************************************************************************
+ *
+ *
+ *  follow the transmitter PTT state, meters and frequency from hamlib rigctld and keep transmit time.
+ *
+ *
+ **********************************************************************************
+ */
+
+
+#define RIGCTLD_POLL_MS         500             // PTT polling interval, ms
+#define RIGCTLD_RETRY_MS        60000           // connection retry interval, ms
+#define RIGCTLD_BACKOFF         3               // most doublings of RIGCTLD_RETRY_MS after repeated failures
+#define RIGCTLD_TO_SECS         1               // connect and read timeouts, secs
+#define DUTY_NMINS              10              // duty cycle period, minutes
+
+static char rig_host[NV_RIGHOST_LEN];           // rigctld host, empty if none
+static uint16_t rig_port;                       // rigctld port
+static WiFiClient rig_client;                   // persistent connection to rigctld
+static uint32_t rig_poll_ms;                    // millis() of last poll or connection attempt
+static uint8_t rig_fails;                       // consecutive failed connections or polls
+static bool rig_ptt;                            // last PTT state reported by rigctld
+static bool rig_no_watts, rig_no_swr;           // set if rigctld can not report these meters
+static float rig_dial_khz;                      // last frequency reported by rigctld, 0 if unknown
+
+static uint32_t tx_ms;                          // millis() when transmitting was last noted, 0 if not
+static uint32_t tx_total_secs;                  // total transmit time since we started
+static uint16_t tx_min_secs[DUTY_NMINS];        // transmit secs in each recent minute
+static time_t tx_min;                           // minute of tx_min_secs[tx_min%DUTY_NMINS]
+
+
+/* load the rigctld host and port from NV once
+ */
+static void loadRigctld()
+{
+    static bool loaded;
+    if (loaded)
+        return;
+    loaded = true;
+
+    if (!NVReadString (NV_RIGHOST, rig_host)) {
+        rig_host[0] = '\0';
+        NVWriteString (NV_RIGHOST, rig_host);
+    }
+    if (!NVReadUInt16 (NV_RIGPORT, &rig_port)) {
+        rig_port = 4532;
+        NVWriteUInt16 (NV_RIGPORT, rig_port);
+    }
+}
+
+/* set the rigctld host and port to poll for PTT, or stop if host is empty.
+ * return whether host is short enough to be saved.
+ */
+bool setRigctld (const char *host, uint16_t port)
+{
+    if (strlen (host) >= sizeof(rig_host))
+        return (false);
+
+    loadRigctld();
+    rig_client.stop();
+    rig_ptt = false;
+    rig_poll_ms = 0;
+    rig_fails = 0;
+    rig_no_watts = rig_no_swr = false;
+    rig_dial_khz = 0;
+
+    strcpy (rig_host, host);
+    rig_port = port;
+    NVWriteString (NV_RIGHOST, rig_host);
+    NVWriteUInt16 (NV_RIGPORT, rig_port);
+
+    return (true);
+}
+
+/* pass back the rigctld host and port. return whether one is set.
+ */
+bool getRigctld (char host[NV_RIGHOST_LEN], uint16_t &port)
+{
+    loadRigctld();
+    strcpy (host, rig_host);
+    port = rig_port;
+    return (rig_host[0] != '\0');
+}
+
+/* ask rigctld for the given meter level.
+ * return 1 if ok, 0 if the rig does not support it, -1 if the connection failed.
+ */
+static int getRigLevel (const char *level, float &value)
+{
+    char line[30];
+    snprintf (line, sizeof(line), "l %s\n", level);
+    rig_client.print (line);
+    if (!getTCPLine (rig_client, line, sizeof(line), NULL))
+        return (-1);
+    if (strncmp (line, "RPRT", 4) == 0) {
+        Serial.printf (_FX("Rig: rigctld can not report %s\n"), level);
+        return (0);
+    }
+    value = atof (line);
+    return (1);
+}
+
+/* query rigctld on the open rig_client for PTT, frequency and, while transmitting, the meters.
+ * return false if the connection failed.
+ */
+static bool pollRigctld()
+{
+    // get_ptt replies 0 or 1, else RPRT with an error code
+    char line[30];
+    rig_client.print ("t\n");
+    if (!getTCPLine (rig_client, line, sizeof(line), NULL) || (line[0] != '0' && line[0] != '1')) {
+        Serial.printf (_FX("Rig: rigctld PTT query failed\n"));
+        return (false);
+    }
+
+    rig_ptt = line[0] == '1';
+
+    // get_freq replies Hz
+    rig_client.print ("f\n");
+    if (!getTCPLine (rig_client, line, sizeof(line), NULL)) {
+        Serial.printf (_FX("Rig: rigctld frequency query failed\n"));
+        return (false);
+    }
+    rig_dial_khz = strncmp (line, "RPRT", 4) == 0 ? 0 : atof (line) * 1e-3;
+
+    // meters are only meaningful while transmitting
+    if (rig_ptt && (!rig_no_watts || !rig_no_swr)) {
+        float watts = 0, swr = 0;
+        int ok_w = rig_no_watts ? 0 : getRigLevel ("RFPOWER_METER_WATTS", watts);
+        int ok_s = rig_no_swr || ok_w < 0 ? 0 : getRigLevel ("SWR", swr);
+        if (ok_w < 0 || ok_s < 0) {
+            Serial.printf (_FX("Rig: rigctld meter query failed\n"));
+            return (false);
+        }
+        rig_no_watts = !ok_w;
+        rig_no_swr = !ok_s;
+        if (ok_w || ok_s)
+            noteTxMeter (watts, swr);
+    }
+
+    return (true);
+}
+
+/* return whether rigctld reports PTT is on, polling at most every RIGCTLD_POLL_MS over one persistent
+ * connection. also record the dial frequency and, while transmitting, pass any forward power and SWR meter
+ * readings to the Tx meter.
+ * N.B. any trouble closes the connection and reports off until a retry succeeds. retries back off from
+ *   RIGCTLD_RETRY_MS while rigctld stays unreachable, and connecting and reading use short timeouts so a
+ *   missing rigctld does not stall the main loop.
+ */
+bool getRigPTT()
+{
+    loadRigctld();
+    if (rig_host[0] == '\0')
+        return (false);
+
+    uint32_t t0 = millis();
+    if (rig_client) {
+        if (t0 - rig_poll_ms < RIGCTLD_POLL_MS)
+            return (rig_ptt);
+    } else {
+        uint32_t retry_ms = RIGCTLD_RETRY_MS << (rig_fails < RIGCTLD_BACKOFF ? rig_fails : RIGCTLD_BACKOFF);
+        if (rig_poll_ms && t0 - rig_poll_ms < retry_ms)
+            return (false);
+    }
+    rig_poll_ms = t0;
+
+#if defined(_IS_UNIX)
+    int connect_to = getNetConnectTimeout()/1000;
+    int read_to = getNetReadTimeout()/1000;
+    (void) setNetTimeouts (RIGCTLD_TO_SECS, RIGCTLD_TO_SECS);
+#endif
+
+    bool ok = true;
+    if (!rig_client) {
+        Serial.printf (_FX("Rig: connecting to rigctld %s:%d\n"), rig_host, rig_port);
+        ok = wifiOk() && rig_client.connect (rig_host, rig_port);
+        if (!ok)
+            Serial.printf (_FX("Rig: %s:%d connection failed\n"), rig_host, rig_port);
+    }
+    if (ok)
+        ok = pollRigctld();
+
+#if defined(_IS_UNIX)
+    (void) setNetTimeouts (connect_to, read_to);
+#endif
+
+    if (ok)
+        rig_fails = 0;
+    else {
+        rig_client.stop();
+        rig_ptt = false;
+        if (rig_fails < 255)
+            rig_fails++;
+    }
+
+    return (rig_ptt);
+}
+
+/* pass back the current rig dial frequency, from rigctld if connected else as last sent to the radio.
+ * return whether known.
+ */
+bool getRigFrequency (float &kHz)
+{
+    if (rig_client && rig_dial_khz > 0) {
+        kHz = rig_dial_khz;
+        return (true);
+    }
+    return (getRadioSpot (kHz));
+}
+
+/* called often with whether we are transmitting to accumulate transmit time
+ */
+void noteTransmitting (bool on)
+{
+    uint32_t t0 = millis();
+    time_t min = now()/60;
+
+    // start fresh minutes as time passes, clearing any skipped
+    if (min != tx_min) {
+        for (time_t m = tx_min+1; m <= min && m <= tx_min + DUTY_NMINS; m++)
+            tx_min_secs[m%DUTY_NMINS] = 0;
+        tx_min = min;
+    }
+
+    // add whole seconds since last noted, carrying the fraction
+    if (on && tx_ms) {
+        uint32_t secs = (t0 - tx_ms)/1000;
+        if (secs > 0) {
+            tx_total_secs += secs;
+            tx_min_secs[min%DUTY_NMINS] += secs;
+            tx_ms += secs*1000;
+        }
+    } else
+        tx_ms = on ? t0 : 0;
+}
+
+/* pass back total transmit time since we started and percent of the last DUTY_NMINS minutes
+ */
+void getTransmitTime (uint32_t &total_secs, int &duty_pct)
+{
+    uint32_t secs = 0;
+    for (int i = 0; i < DUTY_NMINS; i++)
+        secs += tx_min_secs[i];
+    total_secs = tx_total_secs;
+    duty_pct = 100*secs/(DUTY_NMINS*60);
+}
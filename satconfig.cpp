@@ -0,0 +1,227 @@
+/* per-satellite settings chosen in the satellite picker or with set_satconfig: whether each may be
+ * tracked, its priority in the picker, the lowest elevation that counts as a pass and the preferred
+ * operating mode. a satellite with no settings uses the defaults.
+ *
+ * on UNIX the settings are saved in SATCFG_FN in our_dir, one satellite per line:
+ *   name enabled priority min_el mode
+ * where name has underscores for blanks like the sat server, for example:
+ *   ISS 1 9 10 FM
+ * ESP keeps them only until restart.
+ */
+
+#include "HamClock.h"
+
+
+#define SATCFG_FN       "satconfig.txt"         // settings file in our_dir
+#define SATCFG_MAXN     50                      // max satellites with settings
+
+// mode names, N.B. must be in same order as SatMode
+static const char *mode_names[SATMODE_N] = {
+    "Any",
+    "FM",
+    "SSB",
+    "CW",
+    "Data",
+};
+
+static SatConfig *configs;                      // malloced list of settings that differ from defaults
+static int n_configs;                           // n used in configs[]
+static bool configs_loaded;                     // set once loaded
+
+
+/* set sc to the defaults for the given name
+ */
+static void defaultSatConfig (const char *name, SatConfig &sc)
+{
+    memset (&sc, 0, sizeof(sc));
+    snprintf (sc.name, sizeof(sc.name), "%.*s", (int)sizeof(sc.name)-1, name);
+    sc.enabled = true;
+    sc.priority = SATCFG_DEFPRI;
+    sc.min_el = 0;
+    sc.mode = SATMODE_ANY;
+}
+
+/* return index of configs[] for the given name, else -1
+ */
+static int findSatConfig (const char *name)
+{
+    for (int i = 0; i < n_configs; i++)
+        if (strcasecmp (configs[i].name, name) == 0)
+            return (i);
+    return (-1);
+}
+
+/* add sc to configs[], or replace the entry with the same name.
+ * return whether there was room.
+ */
+static bool addSatConfig (const SatConfig &sc)
+{
+    int i = findSatConfig (sc.name);
+    if (i < 0) {
+        if (n_configs == SATCFG_MAXN)
+            return (false);
+        configs = (SatConfig *) realloc (configs, (n_configs+1) * sizeof(SatConfig));
+        if (!configs)
+            fatalError (_FX("No memory for satellite settings"));
+        i = n_configs++;
+    }
+    configs[i] = sc;
+    return (true);
+}
+
+#if defined(_IS_UNIX)
+
+/* load configs[] from SATCFG_FN if not already
+ */
+static void loadSatConfigs()
+{
+    if (configs_loaded)
+        return;
+    configs_loaded = true;
+
+    std::string fn = our_dir + SATCFG_FN;
+    FILE *fp = fopen (fn.c_str(), "r");
+    if (!fp)
+        return;
+
+    char line[100];
+    while (fgets (line, sizeof(line), fp)) {
+        char name[40], mode[20];
+        int enabled, priority, min_el;
+        SatConfig sc;
+        if (line[0] == '#' || sscanf (line, "%39s %d %d %d %19s", name, &enabled, &priority, &min_el, mode) != 5)
+            continue;
+        defaultSatConfig (name, sc);
+        sc.enabled = enabled != 0;
+        sc.priority = priority;
+        sc.min_el = min_el;
+        if (strlen (name) >= NV_SATNAME_LEN || priority < 0 || priority > SATCFG_MAXPRI
+                        || min_el < 0 || min_el > SATCFG_MAXEL || !crackSatMode (mode, sc.mode)) {
+            Serial.printf (_FX("SatCfg: %s: ignoring %s"), SATCFG_FN, line);
+            continue;
+        }
+        if (!addSatConfig (sc))
+            break;
+    }
+    fclose (fp);
+
+    Serial.printf (_FX("SatCfg: loaded %d satellites\n"), n_configs);
+}
+
+/* save configs[] in SATCFG_FN
+ */
+static void saveSatConfigs()
+{
+    std::string fn = our_dir + SATCFG_FN;
+    FILE *fp = fopen (fn.c_str(), "w");
+    if (!fp) {
+        Serial.printf (_FX("SatCfg: %s: %s\n"), fn.c_str(), strerror(errno));
+        return;
+    }
+
+    fprintf (fp, "# name enabled priority min_el mode\n");
+    for (int i = 0; i < n_configs; i++) {
+        const SatConfig &sc = configs[i];
+        fprintf (fp, "%s %d %d %d %s\n", sc.name, sc.enabled, sc.priority, sc.min_el, mode_names[sc.mode]);
+    }
+    fclose (fp);
+}
+
+#else // !_IS_UNIX
+
+static void loadSatConfigs()
+{
+    configs_loaded = true;
+}
+
+static void saveSatConfigs()
+{
+}
+
+#endif // _IS_UNIX
+
+
+/* return name of the given SatMode
+ */
+const char *getSatModeName (uint8_t mode)
+{
+    return (mode < SATMODE_N ? mode_names[mode] : "?");
+}
+
+/* find the SatMode with the given name, ignoring case.
+ * return whether found.
+ */
+bool crackSatMode (const char *name, uint8_t &mode)
+{
+    for (int i = 0; i < SATMODE_N; i++) {
+        if (strcasecmp (name, mode_names[i]) == 0) {
+            mode = i;
+            return (true);
+        }
+    }
+    return (false);
+}
+
+/* fill sc with the settings for the given satellite name, the defaults if none.
+ */
+void getSatConfig (const char *name, SatConfig &sc)
+{
+    loadSatConfigs();
+
+    int i = findSatConfig (name);
+    if (i >= 0)
+        sc = configs[i];
+    else
+        defaultSatConfig (name, sc);
+}
+
+/* save the settings in sc.
+ * return false if there is no room for another satellite.
+ */
+bool setSatConfig (const SatConfig &sc)
+{
+    loadSatConfigs();
+
+    if (!addSatConfig (sc))
+        return (false);
+    saveSatConfigs();
+
+    Serial.printf (_FX("SatCfg: %s %s priority %d min el %d mode %s\n"), sc.name,
+                sc.enabled ? "enabled" : "disabled", sc.priority, sc.min_el, mode_names[sc.mode]);
+    return (true);
+}
+
+/* return whether the given satellite may be tracked
+ */
+bool satIsEnabled (const char *name)
+{
+    SatConfig sc;
+    getSatConfig (name, sc);
+    return (sc.enabled);
+}
+
+/* return the lowest elevation that counts as a pass of the given satellite, degrees
+ */
+float getSatMinEl (const char *name)
+{
+    SatConfig sc;
+    getSatConfig (name, sc);
+    return (sc.min_el);
+}
+
+/* print each satellite with settings, one per line
+ */
+void printSatConfigs (WiFiClient &client)
+{
+    loadSatConfigs();
+
+    char buf[100];
+    snprintf (buf, sizeof(buf), "%-*s Enabled Priority MinEl Mode", NV_SATNAME_LEN-1, "Name");
+    client.println (buf);
+    for (int i = 0; i < n_configs; i++) {
+        const SatConfig &sc = configs[i];
+        snprintf (buf, sizeof(buf), "%-*s %-7s %8d %5d %s", NV_SATNAME_LEN-1, sc.name,
+                        sc.enabled ? "yes" : "no", sc.priority, sc.min_el, mode_names[sc.mode]);
+        client.println (buf);
+    }
+}
@@ -0,0 +1,137 @@
+/* polar sky plot of the current satellite pass shown in a plot pane: azimuth ring with north up and east
+ * right, elevation circles at 30 and 60 degrees, the pass track from rise to set and the current position.
+ * this is the classic view for aiming a handheld antenna during a pass.
+ */
+
+#include "HamClock.h"
+
+
+// layout
+#define TITLE_COLOR     RA8875_RED
+#define GRID_COLOR      RGB565(50,90,50)
+#define RING_COLOR      BRGRAY
+#define TRACK_COLOR     RA8875_RED
+#define RISE_COLOR      RA8875_GREEN
+#define NOW_COLOR       RA8875_YELLOW
+#define LABEL_COLOR     GRAY
+#define TITLE_Y0        27              // title dy, match VOACAP title position
+#define SKY_R           50              // horizon radius
+#define SKY_Y0          (TITLE_Y0+10+SKY_R)     // sky center down from box top
+#define INFO_Y0         (PLOTBOX_H-10)  // info line down from box top
+#define NOW_R           4               // current position marker radius
+#define RISE_R          2               // rise position marker radius
+#define MAX_TRACK       40              // max points along the pass track
+
+
+/* return whether there is a satellite to show
+ */
+bool haveSatSky()
+{
+    return (dx_info_for_sat);
+}
+
+/* find the screen location of the given az and el, degrees, in the sky of box
+ */
+static void azel2sky (const SBox &box, float az, float el, uint16_t &x, uint16_t &y)
+{
+    if (el < 0)
+        el = 0;
+    float r = SKY_R*(90-el)/90;                                 // zenith at center
+    x = box.x + box.w/2 + lroundf (r*sinf(deg2rad(az)));        // east right
+    y = box.y + SKY_Y0 - lroundf (r*cosf(deg2rad(az)));         // north up
+}
+
+/* draw the sky grid in box
+ */
+static void drawSkyGrid (const SBox &box)
+{
+    uint16_t xc = box.x + box.w/2;
+    uint16_t yc = box.y + SKY_Y0;
+
+    tft.drawCircle (xc, yc, SKY_R, RING_COLOR);
+    for (uint8_t el = 30; el < 90; el += 30)
+        tft.drawCircle (xc, yc, SKY_R*(90-el)/90, GRID_COLOR);
+    tft.drawLine (xc - SKY_R, yc, xc + SKY_R, yc, GRID_COLOR);
+    tft.drawLine (xc, yc - SKY_R, xc, yc + SKY_R, GRID_COLOR);
+
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    tft.setTextColor (LABEL_COLOR);
+    tft.setCursor (xc - 2, yc - SKY_R - 9);
+    tft.print ('N');
+    tft.setCursor (xc + SKY_R + 3, yc - 3);
+    tft.print ('E');
+    tft.setCursor (xc - 2, yc + SKY_R + 2);
+    tft.print ('S');
+    tft.setCursor (xc - SKY_R - 9, yc - 3);
+    tft.print ('W');
+}
+
+/* draw the sky plot of the current sat pass in box.
+ * always return true because there is nothing to fetch.
+ */
+bool updateSatSky (const SBox &box)
+{
+    prepPlotBox (box);
+
+    // title is the sat name
+    char title[NV_SATNAME_LEN];
+    float az, el, range, rate, raz, saz, rdt = 0, sdt = 0;
+    bool have_sat = getSatAzElNow (title, &az, &el, &range, &rate, &raz, &saz, &rdt, &sdt);
+    if (!have_sat)
+        snprintf (title, sizeof(title), "%s", xlate ("Sat Sky"));
+    selectFontStyle (LIGHT_FONT, SMALL_FONT);
+    tft.setTextColor (TITLE_COLOR);
+    uint16_t tw = maxStringW (title, box.w - 4);
+    tft.setCursor (box.x + (box.w - tw)/2, box.y + TITLE_Y0);
+    tft.print (title);
+
+    drawSkyGrid (box);
+
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    tft.setTextColor (RA8875_WHITE);
+    char info[40];
+
+    if (!have_sat) {
+        snprintf (info, sizeof(info), "%s", xlate ("No satellite"));
+        tft.setCursor (box.x + (box.w - getTextWidth(info))/2, box.y + INFO_Y0);
+        tft.print (info);
+        return (true);
+    }
+
+    // pass track, start marked
+    float track_az[MAX_TRACK], track_el[MAX_TRACK];
+    int n_track;
+    bool full_pass;
+    if (getSatPassTrack (track_az, track_el, MAX_TRACK, n_track, full_pass)) {
+        uint16_t px = 0, py = 0;
+        for (int i = 0; i < n_track; i++) {
+            uint16_t x, y;
+            azel2sky (box, track_az[i], track_el[i], x, y);
+            if (i > 0 && (x != px || y != py))
+                tft.drawLine (px, py, x, y, TRACK_COLOR);
+            px = x;
+            py = y;
+        }
+        if (full_pass) {
+            azel2sky (box, track_az[0], track_el[0], px, py);
+            tft.fillCircle (px, py, RISE_R, RISE_COLOR);
+        }
+    }
+
+    // current position if up, else when it rises
+    if (el >= SAT_MIN_EL) {
+        uint16_t x, y;
+        azel2sky (box, az, el, x, y);
+        tft.fillCircle (x, y, NOW_R, NOW_COLOR);
+        tft.drawCircle (x, y, NOW_R, RA8875_BLACK);
+        snprintf (info, sizeof(info), "Az %.0f  El %.0f", az, el);
+    } else if (raz != SAT_NOAZ) {
+        int mins = lroundf (rdt*60);
+        snprintf (info, sizeof(info), xlate ("Rise in %d:%02d at %.0f"), mins/60, mins%60, raz);
+    } else
+        snprintf (info, sizeof(info), "%s", xlate ("No rise"));
+    tft.setCursor (box.x + (box.w - getTextWidth(info))/2, box.y + INFO_Y0);
+    tft.print (info);
+
+    return (true);
+}
@@ -0,0 +1,360 @@
+/* read back the screen pixels, and on UNIX systems encode them as PNG to serve or save to files,
+ * either on demand, from a hot key or periodically. Browsers can also watch a periodically refreshed
+ * web page of the screen at reduced size.
+ */
+
+#include "HamClock.h"
+
+
+/* prepare to read back screen pixels starting at the top left, see readScreenPixel().
+ */
+void beginScreenRead()
+{
+    tft.graphicsMode();
+    tft.setXY(0,0);
+    tft.writeCommand(RA8875_MRWC);
+    static bool first = true;
+    if (first) {
+        // skip first pixel first time
+        tft.readData();
+        tft.readData();
+        first = false;
+    }
+}
+
+/* return the next screen pixel as RGB565, must call beginScreenRead() first.
+ */
+uint16_t readScreenPixel()
+{
+    uint16_t msb = tft.readData();
+    uint16_t lsb = tft.readData();
+    return ((msb << 8) | lsb);
+}
+
+
+#if defined(_IS_UNIX)
+
+#include <errno.h>
+
+#define SS_KEY          ('P'-'@')               // hot key to save a screenshot: Control-P
+#define SS_MAXMINS      (24*60)                 // max period, minutes
+#define PNG_SBLK        65535                   // max bytes in one stored deflate block
+
+static uint16_t ss_mins;                        // minutes between screenshots, 0 for off
+static uint32_t ss_ms;                          // time of previous periodic screenshot
+static bool ss_init;                            // set once ss_mins is loaded from NV
+
+
+/* return crc32 of the given bytes, continuing from crc, as used in PNG chunks and zip.
+ */
+static uint32_t crc32 (uint32_t crc, const uint8_t *bytes, size_t n)
+{
+    static uint32_t table[256];
+    if (table[1] == 0) {
+        for (uint32_t i = 0; i < 256; i++) {
+            uint32_t c = i;
+            for (int k = 0; k < 8; k++)
+                c = (c & 1) ? (0xEDB88320UL ^ (c >> 1)) : (c >> 1);
+            table[i] = c;
+        }
+    }
+
+    crc = ~crc;
+    while (n-- > 0)
+        crc = table[(crc ^ *bytes++) & 0xFF] ^ (crc >> 8);
+    return (~crc);
+}
+
+/* store v at p in big-endian order, return p advanced past it
+ */
+static uint8_t *putBE32 (uint8_t *p, uint32_t v)
+{
+    *p++ = v >> 24;
+    *p++ = v >> 16;
+    *p++ = v >> 8;
+    *p++ = v;
+    return (p);
+}
+
+/* start a PNG chunk of the given type and data length at p, return pointer to its data
+ */
+static uint8_t *startChunk (uint8_t *p, const char type[4], uint32_t len)
+{
+    p = putBE32 (p, len);
+    memcpy (p, type, 4);
+    return (p + 4);
+}
+
+/* finish the chunk whose data started at data and ended at p, return p advanced past the crc
+ */
+static uint8_t *endChunk (uint8_t *data, uint8_t *p)
+{
+    return (putBE32 (p, crc32 (0, data-4, p - (data-4))));
+}
+
+/* add the given bytes to the zlib stream at *pp as stored deflate blocks, updating its adler32.
+ * zs holds the running state: total bytes left to store, bytes left in current block, adler a and b.
+ */
+typedef struct {
+    uint32_t raw_left, blk_left;
+    uint32_t adler_a, adler_b;
+} ZState;
+static void storeZBytes (uint8_t **pp, ZState &zs, const uint8_t *bytes, uint32_t n)
+{
+    uint8_t *p = *pp;
+
+    while (n-- > 0) {
+
+        // start a new stored block when the previous one is full
+        if (zs.blk_left == 0) {
+            zs.blk_left = zs.raw_left < PNG_SBLK ? zs.raw_left : PNG_SBLK;
+            *p++ = zs.raw_left == zs.blk_left;          // BFINAL on last, BTYPE 00 for stored
+            *p++ = zs.blk_left & 0xFF;
+            *p++ = zs.blk_left >> 8;
+            *p++ = ~zs.blk_left & 0xFF;
+            *p++ = (~zs.blk_left >> 8) & 0xFF;
+        }
+
+        uint8_t byte = *bytes++;
+        *p++ = byte;
+        zs.adler_a = (zs.adler_a + byte) % 65521;
+        zs.adler_b = (zs.adler_b + zs.adler_a) % 65521;
+        zs.blk_left--;
+        zs.raw_left--;
+    }
+
+    *pp = p;
+}
+
+/* capture the screen as a PNG image in a malloced buffer.
+ * shrink reduces the full-resolution size by averaging each shrink x shrink pixel square, 1 for full size.
+ * the image data use uncompressed deflate blocks so no compression library is required.
+ * return buffer which caller must free, and its length, else NULL if no memory.
+ */
+uint8_t *captureScreenPNG (size_t *n_png, int shrink)
+{
+    uint32_t fb_rows = tft.SCALESZ*tft.height();
+    uint32_t fb_cols = tft.SCALESZ*tft.width();
+    if (shrink < 1)
+        shrink = 1;
+    uint32_t nrows = fb_rows/shrink;
+    uint32_t ncols = fb_cols/shrink;
+
+    // raw scanlines are each a filter type 0 followed by RGB888 pixels
+    uint32_t rowbytes = 1 + 3*ncols;
+    uint32_t rawbytes = nrows*rowbytes;
+    uint32_t nblocks = (rawbytes + PNG_SBLK - 1)/PNG_SBLK;
+    uint32_t zbytes = 2 + rawbytes + 5*nblocks + 4;    // zlib header, blocks each with header, adler32
+
+    // total: signature, IHDR, IDAT and IEND chunks each with 12 bytes of length, type and crc
+    size_t n = 8 + (12+13) + (12+zbytes) + 12;
+    uint8_t *png = (uint8_t *) malloc (n);
+    uint8_t *row = (uint8_t *) malloc (rowbytes);
+    uint32_t *sums = (uint32_t *) malloc (3*ncols*sizeof(uint32_t));
+    if (!png || !row || !sums) {
+        Serial.printf (_FX("PNG: no memory for %u bytes\n"), (unsigned)n);
+        free (png);
+        free (row);
+        free (sums);
+        return (NULL);
+    }
+
+    // signature
+    static const uint8_t sig[8] = {0x89, 'P', 'N', 'G', '\r', '\n', 0x1A, '\n'};
+    memcpy (png, sig, sizeof(sig));
+    uint8_t *p = png + sizeof(sig);
+
+    // header: size, 8 bit depth, truecolor, deflate, filter 0, no interlace
+    uint8_t *data = startChunk (p, "IHDR", 13);
+    p = putBE32 (data, ncols);
+    p = putBE32 (p, nrows);
+    *p++ = 8;
+    *p++ = 2;
+    *p++ = 0;
+    *p++ = 0;
+    *p++ = 0;
+    p = endChunk (data, p);
+
+    // pixels as a zlib stream of stored blocks
+    data = startChunk (p, "IDAT", zbytes);
+    p = data;
+    *p++ = 0x78;                                // deflate, 32K window
+    *p++ = 0x01;                                // no preset dictionary, fastest, check bits
+    ZState zs = {rawbytes, 0, 1, 0};
+
+    resetWatchdog();
+    beginScreenRead();
+    uint32_t nsum = shrink*shrink;
+    for (uint32_t r = 0; r < nrows; r++) {
+        resetWatchdog();
+
+        // sum each shrink x shrink square of screen pixels
+        memset (sums, 0, 3*ncols*sizeof(uint32_t));
+        for (int dr = 0; dr < shrink; dr++) {
+            for (uint32_t c = 0; c < fb_cols; c++) {
+                uint16_t pix16 = readScreenPixel();
+                uint32_t sc = c/shrink;
+                if (sc < ncols) {
+                    uint32_t *sp = &sums[3*sc];
+                    sp[0] += RGB565_R(pix16);
+                    sp[1] += RGB565_G(pix16);
+                    sp[2] += RGB565_B(pix16);
+                }
+            }
+        }
+
+        // store as one scanline
+        row[0] = 0;
+        for (uint32_t i = 0; i < 3*ncols; i++)
+            row[i+1] = sums[i]/nsum;
+        storeZBytes (&p, zs, row, rowbytes);
+    }
+    p = putBE32 (p, (zs.adler_b << 16) | zs.adler_a);
+    p = endChunk (data, p);
+
+    // end
+    data = startChunk (p, "IEND", 0);
+    p = endChunk (data, data);
+
+    free (row);
+    free (sums);
+
+    *n_png = p - png;
+    return (png);
+}
+
+/* encode the given image of nrows x ncols RGB888 pixels, top row first, as a PNG image in a malloced buffer.
+ * return buffer which caller must free, and its length, else NULL if no memory.
+ */
+uint8_t *encodePNG (const uint8_t *rgb, uint32_t ncols, uint32_t nrows, size_t *n_png)
+{
+    uint32_t rowbytes = 1 + 3*ncols;
+    uint32_t rawbytes = nrows*rowbytes;
+    uint32_t nblocks = (rawbytes + PNG_SBLK - 1)/PNG_SBLK;
+    uint32_t zbytes = 2 + rawbytes + 5*nblocks + 4;
+
+    size_t n = 8 + (12+13) + (12+zbytes) + 12;
+    uint8_t *png = (uint8_t *) malloc (n);
+    if (!png) {
+        Serial.printf (_FX("PNG: no memory for %u bytes\n"), (unsigned)n);
+        return (NULL);
+    }
+
+    // signature and header, same as captureScreenPNG()
+    static const uint8_t sig[8] = {0x89, 'P', 'N', 'G', '\r', '\n', 0x1A, '\n'};
+    memcpy (png, sig, sizeof(sig));
+    uint8_t *p = png + sizeof(sig);
+    uint8_t *data = startChunk (p, "IHDR", 13);
+    p = putBE32 (data, ncols);
+    p = putBE32 (p, nrows);
+    *p++ = 8;
+    *p++ = 2;
+    *p++ = 0;
+    *p++ = 0;
+    *p++ = 0;
+    p = endChunk (data, p);
+
+    // each row preceded by filter type 0
+    data = startChunk (p, "IDAT", zbytes);
+    p = data;
+    *p++ = 0x78;
+    *p++ = 0x01;
+    ZState zs = {rawbytes, 0, 1, 0};
+    static const uint8_t filter = 0;
+    for (uint32_t r = 0; r < nrows; r++) {
+        resetWatchdog();
+        storeZBytes (&p, zs, &filter, 1);
+        storeZBytes (&p, zs, rgb + r*3*ncols, 3*ncols);
+    }
+    p = putBE32 (p, (zs.adler_b << 16) | zs.adler_a);
+    p = endChunk (data, p);
+
+    data = startChunk (p, "IEND", 0);
+    p = endChunk (data, data);
+
+    *n_png = p - png;
+    return (png);
+}
+
+/* save the screen as a time-stamped PNG file in our working directory.
+ * if fn is not NULL fill with the file name, else with reason for failure.
+ * return whether successful.
+ */
+bool saveScreenshot (char *fn, size_t fn_len)
+{
+    char ynot[100];
+    char path[1000];
+
+    time_t t = now();
+    snprintf (path, sizeof(path), _FX("%shamclock-%04d%02d%02d-%02d%02d%02d.png"), our_dir.c_str(),
+                year(t), month(t), day(t), hour(t), minute(t), second(t));
+
+    size_t n_png;
+    uint8_t *png = captureScreenPNG (&n_png, 1);
+    bool ok = false;
+    if (png) {
+        FILE *fp = fopen (path, "w");
+        if (fp) {
+            if (fwrite (png, 1, n_png, fp) == n_png)
+                ok = true;
+            else
+                snprintf (ynot, sizeof(ynot), "%s", strerror(errno));
+            fclose (fp);
+        } else
+            snprintf (ynot, sizeof(ynot), "%s", strerror(errno));
+        free (png);
+    } else
+        snprintf (ynot, sizeof(ynot), "%s", _FX("no memory for screenshot"));
+
+    if (ok)
+        Serial.printf (_FX("Screenshot: saved %s\n"), path);
+    else
+        Serial.printf (_FX("Screenshot: %s: %s\n"), path, ynot);
+
+    if (fn)
+        snprintf (fn, fn_len, "%s", ok ? path : ynot);
+
+    return (ok);
+}
+
+/* set minutes between periodic screenshots, 0 to turn off.
+ * return whether in range.
+ */
+bool setScreenshotPeriod (int mins)
+{
+    if (mins < 0 || mins > SS_MAXMINS)
+        return (false);
+
+    ss_mins = mins;
+    ss_ms = millis();
+    ss_init = true;
+    NVWriteUInt16 (NV_SCREENSHOTMINS, ss_mins);
+    return (true);
+}
+
+/* return minutes between periodic screenshots, 0 if off.
+ */
+int getScreenshotPeriod()
+{
+    if (!ss_init) {
+        if (!NVReadUInt16 (NV_SCREENSHOTMINS, &ss_mins) || ss_mins > SS_MAXMINS)
+            ss_mins = 0;
+        ss_ms = millis();
+        ss_init = true;
+    }
+    return (ss_mins);
+}
+
+/* called often with the most recent keyboard char, if any, to save a screenshot if it is the hot key
+ * or it's time for the next periodic one.
+ */
+void checkScreenshots (char kbc)
+{
+    if (kbc == SS_KEY)
+        (void) saveScreenshot (NULL, 0);
+
+    if (getScreenshotPeriod() > 0 && timesUp (&ss_ms, ss_mins*60000UL))
+        (void) saveScreenshot (NULL, 0);
+}
+
+#endif // _IS_UNIX
@@ -0,0 +1,235 @@
+/* run user scripts when certain events occur.
+ *
+ * each event runs the executable of the same name in our_dir/scripts, if present, with details as
+ * arguments. Scripts can be written in any language, eg toggle a GPIO, write a file or send a message.
+ * Events and their arguments are:
+ *
+ *   on_alert      kind details...      kind is watch (call kHz), alarm (HH:MM), countdown, swr (SWR),
+ *                                      iss (SSTV|Contact MHz) or aurora (where)
+ *                                      not run for alerts held back by quiet hours
+ *                                      details are TEST for alerts from set_testalert
+ *   on_escalate   kind step details    critical alert still not acknowledged, see escalate.cpp
+ *   on_spot       call kHz mode spotter
+ *   on_kp_change  new old
+ *   on_air        on|off               transmitter keyed from GPIO21 or rigctld PTT
+ *
+ * when MAX_SCRIPTS are already running, further events wait in alertqueue.cpp until one finishes.
+ *
+ * scripts run detached so they never stall HamClock, but are confined to limit the harm a bad one can do:
+ * they start in the scripts dir with a minimal environment, no inherited files except stdout and stderr
+ * which append to scripts.log, lower priority, limits on cpu time and file size, and are killed if
+ * still running after SCRIPT_MAXSECS.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#include <signal.h>
+#include <fcntl.h>
+#include <sys/wait.h>
+#include <sys/resource.h>
+
+#define SCRIPTS_DIR     "scripts"               // dir within our_dir containing event scripts
+#define SCRIPTS_LOG     "scripts.log"           // file in SCRIPTS_DIR collecting script output
+#define MAX_SCRIPTS     4                       // max scripts running at once
+#define MAX_ARGS        6                       // max args to each script after its name
+#define SCRIPT_MAXSECS  30                      // wall clock limit, secs
+#define SCRIPT_CPUSECS  10                      // cpu limit, secs
+#define SCRIPT_MAXFILE  (1024*1024)             // largest file a script may write, bytes
+#define SCRIPT_NICE     10                      // priority reduction
+
+// one running script
+typedef struct {
+    pid_t pid;                                  // process id, 0 if unused
+    time_t start;                               // when started
+    char event[16];                             // event name, just for logging
+} RunningScript;
+
+static RunningScript running[MAX_SCRIPTS];
+
+static bool startScript (RunningScript &rs, const char *argv[]);
+
+
+/* return a free running[] slot, else NULL
+ */
+static RunningScript *freeSlot()
+{
+    for (int i = 0; i < MAX_SCRIPTS; i++)
+        if (running[i].pid == 0)
+            return (&running[i]);
+    return (NULL);
+}
+
+/* start scripts that are waiting in the queue while there are free slots
+ */
+static void startQueuedScripts()
+{
+    RunningScript *rsp;
+    AlertQItem item;
+    while ((rsp = freeSlot()) != NULL && alertQPop (AQ_SCRIPT, -1, item)) {
+        // unpack the NUL separated event name and args
+        const char *argv[MAX_ARGS+2];
+        int argc = 0;
+        for (size_t i = 0; i < item.len && argc < MAX_ARGS+1; i += strlen (item.data+i) + 1)
+            argv[argc++] = item.data + i;
+        argv[argc] = NULL;
+        if (argc > 0 && startScript (*rsp, argv))
+            noteAlertQSent (AQ_SCRIPT, &item);
+        else
+            noteAlertQDropped (AQ_SCRIPT);
+    }
+}
+
+/* collect any finished scripts, kill any that have run too long and start any that are waiting.
+ * N.B. we only wait for our own pids so we don't steal status from others using fork.
+ */
+void checkEventScripts()
+{
+    time_t t0 = time(NULL);
+
+    for (int i = 0; i < MAX_SCRIPTS; i++) {
+        RunningScript &rs = running[i];
+        if (rs.pid == 0)
+            continue;
+
+        int wstatus;
+        pid_t wpid = waitpid (rs.pid, &wstatus, WNOHANG);
+        if (wpid == rs.pid || wpid < 0) {
+            if (wpid == rs.pid && (!WIFEXITED(wstatus) || WEXITSTATUS(wstatus) != 0))
+                Serial.printf (_FX("Scripts: %s pid %d failed\n"), rs.event, (int)rs.pid);
+            rs.pid = 0;
+        } else if (t0 - rs.start > SCRIPT_MAXSECS) {
+            Serial.printf (_FX("Scripts: %s pid %d ran too long, killing\n"), rs.event, (int)rs.pid);
+            kill (-rs.pid, SIGKILL);                    // whole process group
+        }
+    }
+
+    startQueuedScripts();
+}
+
+/* run the script for the given event, if any, with the given NULL-terminated list of string arguments.
+ */
+void runEventScript (const char *event, ...)
+{
+    // done if no script for this event
+    std::string dir = our_dir + SCRIPTS_DIR;
+    std::string path = dir + "/" + event;
+    if (access (path.c_str(), X_OK) < 0)
+        return;
+
+    // build argv
+    const char *argv[MAX_ARGS+2];
+    int argc = 0;
+    argv[argc++] = event;
+    va_list ap;
+    va_start (ap, event);
+    const char *arg;
+    while (argc < MAX_ARGS+1 && (arg = va_arg (ap, const char *)) != NULL)
+        argv[argc++] = arg;
+    va_end (ap);
+    argv[argc] = NULL;
+
+    // start now if there is a free slot and none are waiting ahead of us, else wait
+    checkEventScripts();
+    RunningScript *rsp = freeSlot();
+    if (!rsp || alertQDepth (AQ_SCRIPT, -1) > 0) {
+        char data[AQ_ITEMLEN];
+        size_t len = 0;
+        for (int i = 0; i < argc && len < sizeof(data); i++)
+            len += snprintf (data+len, sizeof(data)-len, "%s", argv[i]) + 1;
+        if (alertQDepth (AQ_SCRIPT, -1) == 0) {
+            Serial.printf (_FX("Scripts: %d already running, queuing %s\n"), MAX_SCRIPTS, event);
+            noteAlertQLag (AQ_SCRIPT);
+        }
+        (void) alertQPush (AQ_SCRIPT, 0, data, len < sizeof(data) ? len : sizeof(data));
+        return;
+    }
+
+    if (startScript (*rsp, argv))
+        noteAlertQSent (AQ_SCRIPT, NULL);
+    else
+        noteAlertQDropped (AQ_SCRIPT);
+}
+
+/* run the script argv[0] in the given slot with the given NULL-terminated argv.
+ * return whether it started.
+ */
+static bool startScript (RunningScript &rs, const char *argv[])
+{
+    const char *event = argv[0];
+    std::string dir = our_dir + SCRIPTS_DIR;
+    std::string path = dir + "/" + event;
+
+    // build the entire environment now, child may only use async-signal-safe functions after fork
+    const char *home = getenv ("HOME");
+    std::string home_env = std::string("HOME=") + (home ? home : "/");
+    std::string call_env = std::string("HAMCLOCK_CALL=") + getCallsign();
+    const char *envp[] = {"PATH=/usr/local/bin:/usr/bin:/bin", home_env.c_str(), call_env.c_str(), NULL};
+
+    // stdout and stderr append to log
+    std::string log_path = dir + "/" + SCRIPTS_LOG;
+    int log_fd = open (log_path.c_str(), O_WRONLY|O_APPEND|O_CREAT, 0644);
+
+    pid_t pid = fork();
+    if (pid < 0) {
+        Serial.printf (_FX("Scripts: fork(2) %s: %s\n"), event, strerror(errno));
+        if (log_fd >= 0)
+            close (log_fd);
+        return (false);
+    }
+
+    if (pid == 0) {
+        // child
+
+        // own process group so signals to HamClock don't reach it and vice versa
+        setpgid (0, 0);
+
+        // stdin from nowhere, stdout and stderr to log, close all else
+        int null_fd = open ("/dev/null", O_RDWR);
+        dup2 (null_fd, 0);
+        dup2 (log_fd >= 0 ? log_fd : null_fd, 1);
+        dup2 (log_fd >= 0 ? log_fd : null_fd, 2);
+        for (int fd = 3; fd < 1024; fd++)
+            close (fd);
+
+        // confine
+        struct rlimit rl;
+        rl.rlim_cur = rl.rlim_max = SCRIPT_CPUSECS;
+        setrlimit (RLIMIT_CPU, &rl);
+        rl.rlim_cur = rl.rlim_max = SCRIPT_MAXFILE;
+        setrlimit (RLIMIT_FSIZE, &rl);
+        setpriority (PRIO_PROCESS, 0, SCRIPT_NICE);
+        if (chdir (dir.c_str()) < 0)
+            _exit(1);
+
+        execve (path.c_str(), (char * const *)argv, (char * const *)envp);
+        _exit(127);
+    }
+
+    // parent
+    if (log_fd >= 0)
+        close (log_fd);
+    rs.pid = pid;
+    rs.start = time(NULL);
+    strncpy (rs.event, event, sizeof(rs.event)-1);
+    Serial.printf (_FX("Scripts: started %s pid %d\n"), event, (int)pid);
+    return (true);
+}
+
+#else // !_IS_UNIX
+
+/* dummy versions for systems that can not run scripts
+ */
+
+void runEventScript (const char *event, ...)
+{
+    (void) event;
+}
+
+void checkEventScripts()
+{
+}
+
+#endif // _IS_UNIX
@@ -19,3 +19,89 @@ void selectFontStyle (FontWeight w, FontSize s)
         tft.setFont(NULL);
     }
 }
+
+/* ASCII stand-ins for U+00A0 .. U+00FF, none longer than the 2 byte UTF-8 it replaces
+ */
+static const char *latin1_ascii[96] = {
+    " ", "!", "c", "L", "?", "Y", "|", "S", "\"", "c", "a", "<<", "-", "-", "R", "-",
+    "o", "+-", "2", "3", "'", "u", "P", ".", ",", "1", "o", ">>", "?", "?", "?", "?",
+    "A", "A", "A", "A", "A", "A", "AE", "C", "E", "E", "E", "E", "I", "I", "I", "I",
+    "D", "N", "O", "O", "O", "O", "O", "x", "O", "U", "U", "U", "U", "Y", "Th", "ss",
+    "a", "a", "a", "a", "a", "a", "ae", "c", "e", "e", "e", "e", "i", "i", "i", "i",
+    "d", "n", "o", "o", "o", "o", "o", "/", "o", "u", "u", "u", "u", "y", "th", "y",
+};
+
+/* base letters for U+0100 .. U+017F Latin Extended-A
+ */
+static const char latina_ascii[] =
+    "AaAaAaCcCcCcCcDdDdEeEeEeEeEeGgGgGgGgHhHhIiIiIiIiIiIiJjKkkLlLlLlLlLlNnNnNnnNnOoOoOoOoRrRrRrSsSsSsSs"
+    "TtTtTtUuUuUuUuUuUuWwYyYZzZzZzs";
+
+/* return ASCII stand-in for the given code point beyond ASCII which arrived as n UTF-8 bytes,
+ * or NULL to use "?".
+ */
+static const char *foldCodePoint (uint32_t cp, int n)
+{
+    if (cp >= 0xA0 && cp <= 0xFF)
+        return (latin1_ascii[cp-0xA0]);
+    if (cp >= 0x100 && cp <= 0x17F) {
+        static char one[2];
+        one[0] = latina_ascii[cp-0x100];
+        return (one);
+    }
+    if (cp >= 0x2010 && cp <= 0x2015)
+        return ("-");
+    if (cp >= 0x2018 && cp <= 0x201B)
+        return ("'");
+    if (cp >= 0x201C && cp <= 0x201F)
+        return ("\"");
+    if (cp == 0x2022)
+        return ("*");
+    if (cp == 0x2026 && n >= 3)
+        return ("...");
+    return (NULL);
+}
+
+/* our fonts only have glyphs for printable ASCII, so replace IN PLACE each UTF-8 character in str with
+ * an ASCII stand-in, such as e for é or ss for ß, else ? so the reader can at least see something is
+ * there. Malformed bytes also become ?. Fonts for other scripts would not fit in ESP flash.
+ * N.B. str never grows because no stand-in is longer than the UTF-8 it replaces.
+ */
+void foldUTF8 (char *str)
+{
+    char *to = str;
+    const uint8_t *from = (const uint8_t *) str;
+
+    while (*from) {
+        uint8_t c = *from;
+        if (c < 0x80) {
+            *to++ = *from++;
+            continue;
+        }
+
+        // decode one sequence, n is its length in bytes if well formed
+        int n = c >= 0xF8 ? 0 : (c >= 0xF0 ? 4 : (c >= 0xE0 ? 3 : (c >= 0xC0 ? 2 : 0)));
+        uint32_t cp = n == 4 ? c & 0x07 : (n == 3 ? c & 0x0F : c & 0x1F);
+        for (int i = 1; i < n; i++) {
+            if ((from[i] & 0xC0) != 0x80) {
+                n = 0;
+                break;
+            }
+            cp = (cp << 6) | (from[i] & 0x3F);
+        }
+
+        if (n == 0) {
+            *to++ = '?';
+            from++;
+        } else {
+            const char *ascii = foldCodePoint (cp, n);
+            if (!ascii)
+                ascii = "?";
+            while (*ascii)
+                *to++ = *ascii++;
+            from += n;
+        }
+    }
+
+    *to = '\0';
+}
@@ -54,6 +54,20 @@ static float pres_corr[MAX_N_BME];
 static int16_t center_lng;
 static int16_t alt_center_lng;
 static bool alt_center_lng_set;
+static uint8_t map_center_mode;                 // MapCenterMode
+static int16_t sun_center_lng;                  // center when MCM_SUN, only changes in SUN_CENTER_STEP
+static bool sun_center_set;                     // whether sun_center_lng has been found yet
+
+
+// map center modes, N.B. must be in same order as MapCenterMode
+static const char *map_center_names[MCM_N] = {
+    "Fixed",
+    "DE",
+    "Zero",
+    "Sun",
+};
+#define SUN_CENTER_STEP 5                       // follow-sun map recenter step, degrees
+#define SUN_CENTER_MS   60000                   // follow-sun check interval, millis
 
 
 // layout constants
@@ -128,6 +142,11 @@ static bool alt_center_lng_set;
 
 // NV_X11FLAGS bit defns
 #define X11BIT_FULLSCREEN       0x1
+#define X11BIT_AA_SHIFT         1                       // anti-aliasing quality, 0/1/2 for 1x/2x/4x
+#define X11BIT_AA_MASK          (0x3 << X11BIT_AA_SHIFT)
+
+// line and text anti-aliasing quality 1, 2 or 4, persists in NV_X11FLAGS
+static uint8_t aa_quality = 1;
 
 
 // define a string prompt
@@ -223,6 +242,8 @@ typedef enum {
     NTPSET_BPR,
     UNITS_BPR,
     X11_FULLSCRN_BPR,
+    AAON_BPR,
+    AAHI_BPR,
     GPIOOK_BPR,
     KX3ON_BPR,
     KX3BAUD_BPR,
@@ -233,7 +254,7 @@ typedef enum {
 /* bool prompts. N.B. must match BPIds order
  * N.B. cluster map and kx3 use two "entangled" bools to create 3 states which means
  *      one bool turns a feature on and off, the second holds possible values
- *      when on, eg CLMAP_BPR+CLLABEL_BPR, KX3ON_BPR+KX3BAUD_BPR
+ *      when on, eg CLMAP_BPR+CLLABEL_BPR, KX3ON_BPR+KX3BAUD_BPR, AAON_BPR+AAHI_BPR
  */
 static BoolPrompt bool_pr[N_BPR] = {
 
@@ -253,6 +274,8 @@ static BoolPrompt bool_pr[N_BPR] = {
                                                                 // prefix must be the false state
 
     {1, {10,  R2Y(1),  90, PR_H},  {110, R2Y(1), 110, PR_H}, false, "NTP?", "Default set", NULL},
+    {1, {510, R2Y(1),  90, PR_H},  {610, R2Y(1), 50,  PR_H}, false, "Smooth?", "No", NULL},      // aa on/off
+    {1, {610, R2Y(1),   0, PR_H},  {610, R2Y(1), 50,  PR_H}, false, NULL, "2x", "4x"},           // quality
 
     {1, {10,  R2Y(2),  90, PR_H},  {110, R2Y(2), 110, PR_H}, false, "Units?", "Imperial", "Metric"},
 
@@ -423,6 +446,21 @@ static bool boolIsRelevant (BoolPrompt *bp)
             return (false);
     }
 
+    if (bp == &bool_pr[AAON_BPR]) {
+        #if !defined(_IS_UNIX)
+            return (false);
+        #endif
+    }
+
+    if (bp == &bool_pr[AAHI_BPR]) {
+        #if !defined(_IS_UNIX)
+            return (false);
+        #else
+            if (!bool_pr[AAON_BPR].state)
+                return (false);
+        #endif
+    }
+
     if (bp == &bool_pr[FLIP_BPR]) {
         #if !defined(_SUPPORT_FLIP)
             return (false);
@@ -545,6 +583,8 @@ static void nextTabFocus()
         { NULL, &bool_pr[CLLABEL_BPR] },
         { NULL, &bool_pr[NTPSET_BPR] },
         {       &string_pr[NTPHOST_SPR], NULL},
+        { NULL, &bool_pr[AAON_BPR] },
+        { NULL, &bool_pr[AAHI_BPR] },
         { NULL, &bool_pr[UNITS_BPR] },
         {       &string_pr[TEMPCORR_SPR], NULL},
         {       &string_pr[TEMPCORR2_SPR], NULL},
@@ -1328,13 +1368,28 @@ static bool validateStringPrompts()
     // check lat/long unless using something else
     if (!bool_pr[GEOIP_BPR].state && !bool_pr[GPSD_BPR].state) {
 
+        // lat field may instead be a grid locator or city name, in which case it sets both
         char *lat_str = string_pr[LAT_SPR].v_str;
-        if (!latSpecIsValid (lat_str, de_ll.lat_d))
-            badsid[n_badsid++] = LAT_SPR;
-
         char *lng_str = string_pr[LNG_SPR].v_str;
-        if (!lngSpecIsValid (lng_str, de_ll.lng_d))
-            badsid[n_badsid++] = LNG_SPR;
+        if (latSpecIsValid (lat_str, de_ll.lat_d)) {
+            if (!lngSpecIsValid (lng_str, de_ll.lng_d))
+                badsid[n_badsid++] = LNG_SPR;
+        } else if (placeSpecIsValid (lat_str, de_ll)) {
+            snprintf (lat_str, string_pr[LAT_SPR].v_len, "%.2f%c", fabsf(de_ll.lat_d),
+                                                                de_ll.lat_d < 0 ? 'S' : 'N');
+            snprintf (lng_str, string_pr[LNG_SPR].v_len, "%.2f%c", fabsf(de_ll.lng_d),
+                                                                de_ll.lng_d < 0 ? 'W' : 'E');
+            if (string_pr[LAT_SPR].page == cur_page) {
+                eraseSPValue (&string_pr[LAT_SPR]);
+                drawSPValue (&string_pr[LAT_SPR]);
+                eraseSPValue (&string_pr[LNG_SPR]);
+                drawSPValue (&string_pr[LNG_SPR]);
+            }
+        } else {
+            badsid[n_badsid++] = LAT_SPR;
+            if (!lngSpecIsValid (lng_str, de_ll.lng_d))
+                badsid[n_badsid++] = LNG_SPR;
+        }
     }
 
     // check cluster host and port if used
@@ -1699,6 +1754,10 @@ static void initSetup()
         Serial.printf ("x11flags found 0x%02X\n", x11flags);
         bool_pr[X11_FULLSCRN_BPR].state = (x11flags & X11BIT_FULLSCREEN) == X11BIT_FULLSCREEN;
         tft.X11OptionsEngageNow(getX11FullScreen());
+        aa_quality = 1 << ((x11flags & X11BIT_AA_MASK) >> X11BIT_AA_SHIFT);
+        if (aa_quality > 4)
+            aa_quality = 1;
+        tft.setAAQuality (aa_quality);
     } else {
         // set typical defaults but wait for user choices to save
         bool_pr[X11_FULLSCRN_BPR].state = false;
@@ -1709,6 +1768,8 @@ static void initSetup()
             tft.X11OptionsEngageNow(getX11FullScreen());
         }
     }
+    bool_pr[AAON_BPR].state = aa_quality > 1;
+    bool_pr[AAHI_BPR].state = aa_quality == 4;
 
     // init and validate daily on-off times
 
@@ -1764,6 +1825,10 @@ static void initSetup()
         center_lng = 0;
         NVWriteInt16 (NV_CENTERLNG, center_lng);
     }
+    if (!NVReadUInt8 (NV_MAPCENTER, &map_center_mode) || map_center_mode >= MCM_N) {
+        map_center_mode = MCM_FIXED;
+        NVWriteUInt8 (NV_MAPCENTER, map_center_mode);
+    }
 
     // init night option
     if (!NVReadUInt8 (NV_NIGHT_ON, &night_on)) {
@@ -1870,7 +1935,7 @@ static void initDisplay()
     cur_page = -1;
 
     // init shadow strings. N.B. free() before leaving
-    snprintf (string_pr[LAT_SPR].v_str = (char*)malloc(8), string_pr[LAT_SPR].v_len = 8,
+    snprintf (string_pr[LAT_SPR].v_str = (char*)malloc(32), string_pr[LAT_SPR].v_len = 32,
                                         "%.2f%c", fabsf(de_ll.lat_d), de_ll.lat_d < 0 ? 'S' : 'N');
     snprintf (string_pr[LNG_SPR].v_str = (char*)malloc(9), string_pr[LNG_SPR].v_len = 9,
                                         "%.2f%c", fabsf(de_ll.lng_d), de_ll.lng_d < 0 ? 'W' : 'E');
@@ -2125,6 +2190,14 @@ static void runSetup()
                     drawBPPromptState (&bool_pr[CLMAP_BPR]);
             }
 
+            else if (bp == &bool_pr[AAON_BPR]) {
+                // show anti-aliasing quality, or none
+                if (bp->state)
+                    drawBPPromptState (&bool_pr[AAHI_BPR]);
+                else
+                    drawBPPromptState (&bool_pr[AAON_BPR]);
+            }
+
           #if defined(_SUPPORT_GPIO) && defined(_SUPPORT_ENVSENSOR)
             else if (bp == &bool_pr[GPIOOK_BPR]) {
                 // toggle KX3 and env sensors
@@ -2225,8 +2298,11 @@ static void finishSettingUp()
     uint16_t x11flags = 0;
     if (bool_pr[X11_FULLSCRN_BPR].state)
         x11flags |= X11BIT_FULLSCREEN;
+    aa_quality = bool_pr[AAON_BPR].state ? (bool_pr[AAHI_BPR].state ? 4 : 2) : 1;
+    x11flags |= (aa_quality == 4 ? 2 : (aa_quality == 2 ? 1 : 0)) << X11BIT_AA_SHIFT;
     NVWriteUInt16 (NV_X11FLAGS, x11flags);
     tft.X11OptionsEngageNow(getX11FullScreen());
+    tft.setAAQuality (aa_quality);
 
     // save colors
     for (uint8_t i = 0; i < N_CSPR; i++) {
@@ -2275,6 +2351,149 @@ void drawStringInBox (const char str[], const SBox &b, bool inverted, uint16_t c
 }
 
 
+#if defined(_IS_UNIX)
+
+/* settings that may be given with HAMCLOCK_* environment variables so a container or other headless install
+ * can start fully configured and, with -k, never show Setup. Each is checked then saved to NVRAM as if
+ * entered in Setup, so the environment wins over the NVRAM file on each start, but a command line option
+ * that affects the same setting, such as -g, -i or -l, wins over both. Bad values are logged and ignored.
+ * See docs/wiki/Environment-Configuration.md.
+ */
+
+/* set call sign from value
+ */
+static bool envCallsign (const char *value)
+{
+    if (strlen (value) >= NV_CALLSIGN_LEN)
+        return (false);
+    strcpy (call, value);
+    NVWriteString (NV_CALLSIGN, call);
+    return (true);
+}
+
+/* set DE location from a grid locator or lat,lng
+ */
+static bool envDE (const char *value)
+{
+    LatLong ll;
+    char c;
+    if (sscanf (value, "%f,%f%c", &ll.lat_d, &ll.lng_d, &c) == 2) {
+        if (ll.lat_d < -90 || ll.lat_d > 90 || ll.lng_d < -180 || ll.lng_d > 180)
+            return (false);
+    } else if (!placeSpecIsValid (value, ll))
+        return (false);
+
+    normalizeLL (ll);
+    de_ll = ll;
+    NVWriteFloat (NV_DE_LAT, de_ll.lat_d);
+    NVWriteFloat (NV_DE_LNG, de_ll.lng_d);
+    setNVMaidenhead (NV_DE_GRID, de_ll);
+    de_tz.tz_secs = getTZ (de_ll);
+    NVWriteInt32 (NV_DE_TZ, de_tz.tz_secs);
+    return (true);
+}
+
+/* set units from metric or imperial
+ */
+static bool envUnits (const char *value)
+{
+    if (strcasecmp (value, "metric") == 0)
+        bool_pr[UNITS_BPR].state = true;
+    else if (strcasecmp (value, "imperial") == 0)
+        bool_pr[UNITS_BPR].state = false;
+    else
+        return (false);
+    NVWriteUInt8 (NV_METRIC_ON, bool_pr[UNITS_BPR].state);
+    return (true);
+}
+
+/* set and enable DX cluster from host:port
+ */
+static bool envDXCluster (const char *value)
+{
+    const char *colon = strchr (value, ':');
+    int port;
+    char c;
+    if (!colon || colon == value || colon - value >= NV_DXHOST_LEN
+                        || sscanf (colon+1, "%d%c", &port, &c) != 1 || port <= 0 || port > 65535)
+        return (false);
+    memcpy (dxhost, value, colon - value);
+    dxhost[colon - value] = '\0';
+    dxport = port;
+    bool_pr[CLUSTER_BPR].state = true;
+    NVWriteString (NV_DXHOST, dxhost);
+    NVWriteUInt16 (NV_DXPORT, dxport);
+    NVWriteUInt8 (NV_USEDXCLUSTER, 1);
+    return (true);
+}
+
+/* set and enable NTP host
+ */
+static bool envNTP (const char *value)
+{
+    if (strlen (value) >= NV_NTPHOST_LEN)
+        return (false);
+    strcpy (ntphost, value);
+    bool_pr[NTPSET_BPR].state = true;
+    NVWriteString (NV_NTPHOST, ntphost);
+    NVWriteUInt8 (NV_NTPSET, 1);
+    return (true);
+}
+
+/* set and enable gpsd host
+ */
+static bool envGPSD (const char *value)
+{
+    if (strlen (value) >= NV_GPSDHOST_LEN)
+        return (false);
+    strcpy (gpsdhost, value);
+    bool_pr[GPSD_BPR].state = true;
+    NVWriteString (NV_GPSDHOST, gpsdhost);
+    NVWriteUInt8 (NV_USEGPSD, 1);
+    return (true);
+}
+
+typedef struct {
+    const char *env;                            // variable name
+    bool (*set)(const char *value);             // check and save value, return whether ok
+    const char *help;                           // value format
+} EnvSetting;
+
+static const EnvSetting env_settings[] = {
+    { "HAMCLOCK_CALLSIGN",      envCallsign,    "call sign" },
+    { "HAMCLOCK_DE",            envDE,          "grid locator or lat,lng in degrees +N +E" },
+    { "HAMCLOCK_UNITS",         envUnits,       "metric or imperial" },
+    { "HAMCLOCK_DXCLUSTER",     envDXCluster,   "host:port, also enables the DX cluster" },
+    { "HAMCLOCK_NTP",           envNTP,         "host, also enables using it" },
+    { "HAMCLOCK_GPSD",          envGPSD,        "host, also enables using it" },
+};
+
+/* apply any env_settings found in the environment
+ */
+static void envSetup()
+{
+    for (unsigned i = 0; i < NARRAY(env_settings); i++) {
+        const EnvSetting &es = env_settings[i];
+        const char *value = getenv (es.env);
+        if (!value || !*value)
+            continue;
+        if ((*es.set)(value))
+            Serial.printf (_FX("Setup: %s=%s\n"), es.env, value);
+        else
+            Serial.printf (_FX("Setup: ignoring %s=%s, must be %s\n"), es.env, value, es.help);
+    }
+}
+
+/* print env_settings for usage()
+ */
+void printEnvSettings (FILE *fp)
+{
+    for (unsigned i = 0; i < NARRAY(env_settings); i++)
+        fprintf (fp, "  %-19s : %s\n", env_settings[i].env, env_settings[i].help);
+}
+
+#endif // _IS_UNIX
+
 /* grab everything from NV, setting defaults if first time, then allow user to change,
  * saving to NV if needed.
  */
@@ -2289,6 +2508,11 @@ void clockSetup()
     // load values from nvram, else set defaults
     initSetup();
 
+#if defined(_IS_UNIX)
+    // then let the environment override them
+    envSetup();
+#endif // _IS_UNIX
+
     // ask user whether they want to run setup
     if (!askRun())
         return;
@@ -2357,13 +2581,56 @@ bool lngSpecIsValid (const char *lng_spec, float &lng)
     return (true);
 }
 
+/* return whether the given string is a maidenhead grid locator or the name of a city, set ll if so.
+ * a grid is set to the location of its center.
+ */
+bool placeSpecIsValid (const char *place_spec, LatLong &ll)
+{
+    size_t sl = strlen (place_spec);
+    if ((sl == 4 || sl == 6) && maidenhead2ll (ll, place_spec)) {
+        if (sl == 4) {
+            ll.lat_d += 0.5F;
+            ll.lng_d += 1.0F;
+        } else {
+            ll.lat_d += 1.25F/60.0F;
+            ll.lng_d += 2.5F/60.0F;
+        }
+        normalizeLL (ll);
+        return (true);
+    }
+
+    char found[100];
+    return (geocodeCity (place_spec, ll, found, sizeof(found)));
+}
+
 
 
 /* only for main() to call once very early to allow setting initial default
  */
 void setX11FullScreen (bool on)
 {
-    uint16_t x11flags = (on ? X11BIT_FULLSCREEN : 0);
+    uint16_t x11flags = 0;
+    (void) NVReadUInt16 (NV_X11FLAGS, &x11flags);
+    x11flags = (x11flags & ~X11BIT_FULLSCREEN) | (on ? X11BIT_FULLSCREEN : 0);
+    NVWriteUInt16 (NV_X11FLAGS, x11flags);
+}
+
+/* only for main() to call once very early to set the X11 window position, or -1 to let the window
+ * manager decide, and whether to keep it on top and whether to remove its decorations.
+ */
+void setX11Placement (int x, int y, bool on_top, bool borderless)
+{
+    tft.setX11Placement (x, y, on_top, borderless);
+}
+
+/* only for main() to call once very early to set the anti-aliasing quality, 1 2 or 4
+ */
+void setX11AAQuality (int q)
+{
+    uint16_t x11flags = 0;
+    (void) NVReadUInt16 (NV_X11FLAGS, &x11flags);
+    x11flags &= ~X11BIT_AA_MASK;
+    x11flags |= (q == 4 ? 2 : (q == 2 ? 1 : 0)) << X11BIT_AA_SHIFT;
     NVWriteUInt16 (NV_X11FLAGS, x11flags);
 }
 
@@ -2442,7 +2709,7 @@ bool useDXCluster()
     return (bool_pr[CLUSTER_BPR].state);
 }
 
-/* return whether to rotate the screen
+/* return whether to rotate the screen 180 degrees
  */
 bool rotateScreen()
 {
@@ -2600,6 +2867,13 @@ bool getX11FullScreen(void)
     return (bool_pr[X11_FULLSCRN_BPR].state);
 }
 
+/* return anti-aliasing quality, 1 2 or 4
+ */
+int getX11AAQuality(void)
+{
+    return (aa_quality);
+}
+
 /* whether demo mode is requested
  */
 bool getDemoMode(void)
@@ -2614,12 +2888,43 @@ void setDemoMode(bool on)
     bool_pr[DEMO_BPR].state = on;
 }
 
+/* return the given longitude, degrees, wrapped to [-180, 180)
+ */
+static int16_t normCenterLng (int l)
+{
+    return (((l + (180+360*10)) % 360) - 180);
+}
+
+/* return the subsolar longitude now rounded to a multiple of SUN_CENTER_STEP.
+ * N.B. computed here rather than using sun_ss_ll because that is only set while the map is being drawn.
+ */
+static int16_t sunCenterLng()
+{
+    AstroCir cir;
+    getSolarCir (nowWO(), de_ll, cir);
+    return (normCenterLng (SUN_CENTER_STEP * (int)roundf (-rad2deg(cir.gha)/SUN_CENTER_STEP)));
+}
+
 /* return desired mercator map center longitude.
  * caller may assume -180 <= x < 180
  */
 int16_t getCenterLng()
 {
-    return (alt_center_lng_set ? alt_center_lng : center_lng);
+    if (alt_center_lng_set)
+        return (alt_center_lng);
+
+    switch ((MapCenterMode)map_center_mode) {
+    case MCM_DE:    return (normCenterLng (roundf (de_ll.lng_d)));
+    case MCM_ZERO:  return (0);
+    case MCM_SUN:
+        // find on first use so the first map is already centered on the sun
+        if (!sun_center_set) {
+            sun_center_lng = sunCenterLng();
+            sun_center_set = true;
+        }
+        return (sun_center_lng);
+    default:        return (center_lng);
+    }
 }
 
 /* set desired mercator map center longitude.
@@ -2627,7 +2932,73 @@ int16_t getCenterLng()
  */
 void setCenterLng (int16_t l)
 {
-    l = ((l + (180+360*10)) % 360) - 180;       // enforce [-180, 180)
-    alt_center_lng = l;
+    alt_center_lng = normCenterLng (l);
     alt_center_lng_set = true;
 }
+
+/* return how the mercator map center longitude is chosen
+ */
+MapCenterMode getMapCenterMode()
+{
+    return ((MapCenterMode)map_center_mode);
+}
+
+/* return name of the current map center mode
+ */
+const char *getMapCenterModeName()
+{
+    return (map_center_names[map_center_mode]);
+}
+
+/* engage and save a new map center mode.
+ * return whether the mercator map must be restarted because its center moved.
+ */
+static bool setMapCenterMode (MapCenterMode mcm)
+{
+    int16_t prev_lng = getCenterLng();
+
+    map_center_mode = mcm;
+    NVWriteUInt8 (NV_MAPCENTER, map_center_mode);
+    sun_center_set = false;
+
+    Serial.printf (_FX("Map center mode now %s\n"), getMapCenterModeName());
+
+    return (!azm_on && getCenterLng() != prev_lng);
+}
+
+/* set map center mode by name, ignoring case, and set moved if the caller must restart the map.
+ * return whether name is recognized.
+ */
+bool setMapCenterModeName (const char *name, bool &moved)
+{
+    for (int i = 0; i < MCM_N; i++) {
+        if (strcasecmp (name, map_center_names[i]) == 0) {
+            moved = setMapCenterMode ((MapCenterMode)i);
+            return (true);
+        }
+    }
+    return (false);
+}
+
+/* advance to the next map center mode, eg from the hot key, restarting the map if it moves.
+ */
+void cycleMapCenterMode()
+{
+    if (setMapCenterMode ((MapCenterMode)((map_center_mode + 1) % MCM_N)))
+        initEarthMap();
+}
+
+/* called often to restart the mercator map when the sun has moved far enough from center to follow it.
+ */
+void checkMapCenter()
+{
+    static uint32_t check_ms;
+    if (map_center_mode != MCM_SUN || azm_on || !sun_center_set || !timesUp (&check_ms, SUN_CENTER_MS))
+        return;
+
+    int16_t new_lng = sunCenterLng();
+    if (new_lng != sun_center_lng) {
+        sun_center_lng = new_lng;
+        initEarthMap();
+    }
+}
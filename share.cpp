@@ -0,0 +1,354 @@
+/* "share conditions": a small PNG card and matching JSON summary of current conditions suitable for posting
+ * to a club chat. the card shows the call sign, time, SFI, Kp, SSN, X-ray class, a thumbnail of the map with
+ * its greyline and the VOACAP DE-DX reliability for each band. both are served by the web server and may
+ * be saved in our working directory. only on UNIX systems because the card is built in memory.
+ */
+
+#include "HamClock.h"
+
+#if defined(_IS_UNIX)
+
+#include <errno.h>
+
+
+// card layout, in screen coordinates at 800x480; all scale by tft.SCALESZ
+#define CARD_W          480                     // card size
+#define CARD_H          300
+#define CARD_BG         RGB565(20,20,30)        // background color
+#define HEAD_Y          30                      // baseline of call and time
+#define VALUE_X         12                      // left of the values column
+#define VALUE_Y0        80                      // baseline of first value
+#define VALUE_DY        34                      // value row spacing
+#define THUMB_X         226                     // map thumbnail position and size
+#define THUMB_Y         48
+#define THUMB_W         240
+#define THUMB_H         120
+#define BAND_Y0         200                     // top of band bars
+#define BAND_H          62                      // full height of a band bar
+#define BAND_LY         (CARD_H-8)              // baseline of band labels
+#define STALE_SECS      (6*3600)                // values older than this are shown as unknown
+
+
+// card being built
+typedef struct {
+    uint8_t *rgb;                               // RGB888 pixels, top row first
+    int w, h;                                   // size in pixels
+    int s;                                      // scale from 800x480 layout to pixels
+} Card;
+
+
+/* fill the given rectangle in layout units with color
+ */
+static void cardRect (Card &c, int x, int y, int w, int h, uint16_t color)
+{
+    uint8_t r = RGB565_R(color), g = RGB565_G(color), b = RGB565_B(color);
+    for (int py = y*c.s; py < (y+h)*c.s && py < c.h; py++) {
+        for (int px = x*c.s; px < (x+w)*c.s && px < c.w; px++) {
+            uint8_t *p = &c.rgb[3*(py*c.w + px)];
+            p[0] = r;
+            p[1] = g;
+            p[2] = b;
+        }
+    }
+}
+
+/* return the width of str in the given font, in layout units
+ */
+static int cardTextWidth (const Card &c, const GFXfont *font, const char *str)
+{
+    int w = 0;
+    for (; *str; str++)
+        if (*str >= font->first && *str <= font->last)
+            w += font->glyph[*str - font->first].xAdvance;
+    return (w/c.s);
+}
+
+/* draw str in the given font and color with its baseline starting at x,y in layout units
+ */
+static void cardText (Card &c, const GFXfont *font, int x, int y, uint16_t color, const char *str)
+{
+    uint8_t r = RGB565_R(color), g = RGB565_G(color), b = RGB565_B(color);
+    int cx = x*c.s;
+    int cy = y*c.s;
+
+    for (; *str; str++) {
+        if (*str < font->first || *str > font->last)
+            continue;
+        const GFXglyph *gp = &font->glyph[*str - font->first];
+        const uint8_t *bitmap = font->bitmap;
+        uint32_t bo = gp->bitmapOffset;
+        uint8_t bits = 0, bit = 0;
+        for (int yy = 0; yy < gp->height; yy++) {
+            for (int xx = 0; xx < gp->width; xx++) {
+                if (!(bit++ & 7))
+                    bits = bitmap[bo++];
+                int px = cx + gp->xOffset + xx;
+                int py = cy + gp->yOffset + yy;
+                if ((bits & 0x80) && px >= 0 && px < c.w && py >= 0 && py < c.h) {
+                    uint8_t *p = &c.rgb[3*(py*c.w + px)];
+                    p[0] = r;
+                    p[1] = g;
+                    p[2] = b;
+                }
+                bits <<= 1;
+            }
+        }
+        cx += gp->xAdvance;
+    }
+}
+
+/* copy a reduced image of the map as now shown on screen into the thumbnail area of the card
+ */
+static void cardMapThumb (Card &c)
+{
+    int fb_w = tft.SCALESZ*tft.width();
+    int fb_h = tft.SCALESZ*tft.height();
+    int mx = map_b.x*c.s, my = map_b.y*c.s;
+    int mw = map_b.w*c.s, mh = map_b.h*c.s;
+    int tw = THUMB_W*c.s, th = THUMB_H*c.s;
+
+    // screen can only be read in order, so pick out thumbnail pixels as they go by
+    beginScreenRead();
+    for (int r = 0; r < fb_h; r++) {
+        resetWatchdog();
+        int ty = r >= my && r < my+mh ? (r - my)*th/mh : -1;
+        for (int col = 0; col < fb_w; col++) {
+            uint16_t pix16 = readScreenPixel();
+            if (ty < 0 || col < mx || col >= mx+mw)
+                continue;
+            int tx = (col - mx)*tw/mw;
+            uint8_t *p = &c.rgb[3*((THUMB_Y*c.s + ty)*c.w + THUMB_X*c.s + tx)];
+            p[0] = RGB565_R(pix16);
+            p[1] = RGB565_G(pix16);
+            p[2] = RGB565_B(pix16);
+        }
+    }
+}
+
+/* return color for the given path reliability, 0..1
+ */
+static uint16_t relColor (float rel)
+{
+    if (rel >= 0.6F)
+        return (RA8875_GREEN);
+    if (rel >= 0.3F)
+        return (RA8875_YELLOW);
+    return (RA8875_RED);
+}
+
+/* build the conditions card as a PNG image in a malloced buffer.
+ * return buffer which caller must free, and its length, else NULL if no memory.
+ */
+static uint8_t *shareCardPNG (size_t *n_png)
+{
+    Card c;
+    c.s = tft.SCALESZ;
+    c.w = CARD_W*c.s;
+    c.h = CARD_H*c.s;
+    c.rgb = (uint8_t *) malloc (3*c.w*c.h);
+    if (!c.rgb) {
+        Serial.printf (_FX("Share: no memory for %dx%d card\n"), c.w, c.h);
+        return (NULL);
+    }
+
+    const GFXfont *bold = &Germano_Bold16pt7b;
+    const GFXfont *reg = &Germano_Regular16pt7b;
+    SpaceWeather spw;
+    getSpaceWeather (spw);
+    char buf[50];
+
+    // background and map
+    cardRect (c, 0, 0, CARD_W, CARD_H, CARD_BG);
+    cardMapThumb (c);
+
+    // call and time
+    cardText (c, bold, VALUE_X, HEAD_Y, RGB565(255,160,0), getCallsign());
+    time_t t = now();
+    snprintf (buf, sizeof(buf), "%04d-%02d-%02d %02d:%02dZ", year(t), month(t), day(t), hour(t), minute(t));
+    cardText (c, reg, CARD_W - VALUE_X - cardTextWidth (c, reg, buf), HEAD_Y, RA8875_WHITE, buf);
+
+    // values
+    int y = VALUE_Y0;
+    if (spw.flux.age < STALE_SECS)
+        snprintf (buf, sizeof(buf), "SFI %.0f", spw.flux.value);
+    else
+        strcpy (buf, "SFI -");
+    cardText (c, reg, VALUE_X, y, RA8875_WHITE, buf);
+    y += VALUE_DY;
+    if (spw.kp.age < STALE_SECS)
+        snprintf (buf, sizeof(buf), "Kp %.0f", spw.kp.value);
+    else
+        strcpy (buf, "Kp -");
+    cardText (c, reg, VALUE_X, y, spw.kp.value >= 5 ? RA8875_RED : RA8875_WHITE, buf);
+    y += VALUE_DY;
+    if (spw.ssn.age < STALE_SECS)
+        snprintf (buf, sizeof(buf), "SSN %.0f", spw.ssn.value);
+    else
+        strcpy (buf, "SSN -");
+    cardText (c, reg, VALUE_X, y, RA8875_WHITE, buf);
+    y += VALUE_DY;
+    snprintf (buf, sizeof(buf), "X-Ray %s", spw.xray.age < STALE_SECS && spw.xray_class[0] ? spw.xray_class : "-");
+    cardText (c, reg, VALUE_X, y, RA8875_WHITE, buf);
+
+    // band reliability bars with band labels below
+    bool rel_ok = spw.pathrel_age < STALE_SECS;
+    int col_w = (CARD_W - 2*VALUE_X)/PROP_MAP_N;
+    for (int i = 0; i < PROP_MAP_N; i++) {
+        int x = VALUE_X + i*col_w;
+        float rel = rel_ok ? spw.pathrel[i] : 0;
+        int bh = rel*BAND_H;
+        cardRect (c, x + col_w/4, BAND_Y0, col_w/2, BAND_H, RGB565(50,50,60));
+        if (bh > 0)
+            cardRect (c, x + col_w/4, BAND_Y0 + BAND_H - bh, col_w/2, bh, relColor (rel));
+        snprintf (buf, sizeof(buf), "%d", propMap2Band ((PropMapSetting)i));
+        cardText (c, reg, x + (col_w - cardTextWidth (c, reg, buf))/2, BAND_LY, GRAY, buf);
+    }
+
+    uint8_t *png = encodePNG (c.rgb, c.w, c.h, n_png);
+    free (c.rgb);
+    return (png);
+}
+
+/* build the JSON summary matching the card in out
+ */
+static void shareJSON (std::string &out)
+{
+    SpaceWeather spw;
+    getSpaceWeather (spw);
+    char grid[MAID_CHARLEN];
+    getNVMaidenhead (NV_DE_GRID, grid);
+    time_t t = now();
+    char buf[200];
+
+    snprintf (buf, sizeof(buf), "{\n  \"call\":\"%s\",\n  \"grid\":\"%s\",\n"
+                "  \"utc\":\"%04d-%02d-%02dT%02d:%02d:%02dZ\",\n", getCallsign(), grid,
+                year(t), month(t), day(t), hour(t), minute(t), second(t));
+    out = buf;
+
+    // each value with its age in seconds, null if never read or stale
+    const struct {
+        const char *name;
+        const SPWxValue &v;
+    } values[] = {
+        { "sfi", spw.flux },
+        { "kp",  spw.kp },
+        { "ssn", spw.ssn },
+    };
+    for (unsigned i = 0; i < NARRAY(values); i++) {
+        if (values[i].v.age < STALE_SECS)
+            snprintf (buf, sizeof(buf), "  \"%s\":%g,\n  \"%s_age\":%ld,\n", values[i].name, values[i].v.value,
+                                values[i].name, (long)values[i].v.age);
+        else
+            snprintf (buf, sizeof(buf), "  \"%s\":null,\n", values[i].name);
+        out += buf;
+    }
+    if (spw.xray.age < STALE_SECS && spw.xray_class[0])
+        snprintf (buf, sizeof(buf), "  \"xray\":\"%s\",\n", spw.xray_class);
+    else
+        snprintf (buf, sizeof(buf), "  \"xray\":null,\n");
+    out += buf;
+
+    // VOACAP DE-DX reliability by band, percent
+    out += "  \"bands\":{";
+    for (int i = 0; i < PROP_MAP_N; i++) {
+        if (spw.pathrel_age < STALE_SECS)
+            snprintf (buf, sizeof(buf), "%s\"%dm\":%.0f", i ? "," : "", propMap2Band ((PropMapSetting)i),
+                                100*spw.pathrel[i]);
+        else
+            snprintf (buf, sizeof(buf), "%s\"%dm\":null", i ? "," : "", propMap2Band ((PropMapSetting)i));
+        out += buf;
+    }
+    out += "}\n}\n";
+}
+
+/* web command to send the conditions card as PNG
+ */
+bool getWiFiSharePNG (WiFiClient *clientp, char line[])
+{
+    size_t n_png;
+    uint8_t *png = shareCardPNG (&n_png);
+    if (!png) {
+        strcpy (line, _FX("No memory for conditions card"));
+        return (false);
+    }
+
+    resetWatchdog();
+    FWIFIPRLN (*clientp, F("HTTP/1.0 200 OK"));
+    sendUserAgent (*clientp);
+    FWIFIPRLN (*clientp, F("Content-Type: image/png"));
+    FWIFIPRLN (*clientp, F("Cache-Control: no-cache"));
+    FWIFIPR (*clientp, F("Content-Length: ")); clientp->println ((int)n_png);
+    FWIFIPRLN (*clientp, F("Connection: close\r\n"));
+    clientp->write (png, n_png);
+    free (png);
+
+    return (true);
+}
+
+/* web command to send the conditions summary as JSON
+ */
+bool getWiFiShareJSON (WiFiClient *clientp, char line[])
+{
+    (void) line;
+
+    std::string out;
+    shareJSON (out);
+
+    resetWatchdog();
+    FWIFIPRLN (*clientp, F("HTTP/1.0 200 OK"));
+    sendUserAgent (*clientp);
+    FWIFIPRLN (*clientp, F("Content-Type: application/json; charset=us-ascii"));
+    FWIFIPRLN (*clientp, F("Cache-Control: no-cache"));
+    FWIFIPRLN (*clientp, F("Connection: close\r\n"));
+    clientp->write ((const uint8_t *)out.c_str(), out.size());
+
+    return (true);
+}
+
+/* save the conditions card and summary as time-stamped PNG and JSON files in our working directory.
+ * if fn is not NULL fill with the common base name, else with reason for failure.
+ * return whether successful.
+ */
+bool saveShareCard (char *fn, size_t fn_len)
+{
+    char base[300], path[320], ynot[400];
+    time_t t = now();
+    snprintf (base, sizeof(base), _FX("%shamclock-share-%04d%02d%02d-%02d%02d%02d"), our_dir.c_str(),
+                year(t), month(t), day(t), hour(t), minute(t), second(t));
+    bool ok = false;
+
+    size_t n_png;
+    uint8_t *png = shareCardPNG (&n_png);
+    if (png) {
+        snprintf (path, sizeof(path), "%s.png", base);
+        FILE *fp = fopen (path, "w");
+        if (fp) {
+            std::string json;
+            shareJSON (json);
+            ok = fwrite (png, 1, n_png, fp) == n_png;
+            fclose (fp);
+            snprintf (path, sizeof(path), "%s.json", base);
+            if (ok && (fp = fopen (path, "w")) != NULL) {
+                ok = fputs (json.c_str(), fp) >= 0;
+                fclose (fp);
+            } else
+                ok = false;
+        }
+        if (!ok)
+            snprintf (ynot, sizeof(ynot), "%s: %s", path, strerror(errno));
+        free (png);
+    } else
+        snprintf (ynot, sizeof(ynot), "%s", _FX("no memory for conditions card"));
+
+    if (ok)
+        Serial.printf (_FX("Share: saved %s.png and .json\n"), base);
+    else
+        Serial.printf (_FX("Share: %s\n"), ynot);
+
+    if (fn)
+        snprintf (fn, fn_len, "%s", ok ? base : ynot);
+
+    return (ok);
+}
+
+#endif // _IS_UNIX
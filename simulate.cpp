@@ -0,0 +1,372 @@
+/* play a scripted scenario of events, such as a Kp ramp to 9, a CME on its way, a satellite pass or a
+ * DX pileup, on an accelerated clock so layouts and alerts can be shown or checked without waiting for
+ * the real thing. Started with -s file. UNIX only.
+ *
+ * the scenario file has one event per line in order of time, blank lines and # comments are ignored:
+ *
+ *   speed N                    run N scenario seconds per real second, default 1
+ *   repeat                     start over after the last event
+ *   T kp|sfi|ssn|swind|drap|protons V
+ *                              at T scenario seconds set the given space weather value
+ *   T xray C                   set the X-ray level to class C, eg M5.2
+ *   T flare C                  report a flare of class C peaking now
+ *   T cme H S                  predict a CME arriving H scenario hours from now at S km/s
+ *   T spot kHz call grid [mode]
+ *                              spot call at grid on kHz, shown if the DX Cluster pane is up
+ *   T set_...                  run the given web server set_ command, eg set_satname?ISS
+ *
+ * space weather set this way takes precedence over fetched values for the rest of the run. kp and sfi also
+ * replace the current value shown in the Kp and solar flux panes, which refresh when either is set.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#include <stddef.h>
+
+#define MAX_SIM_EVENTS  500                     // most events in one scenario
+#define MAX_SIM_ARGS    100                     // longest event arguments
+
+// one scenario event
+typedef struct {
+    float t;                                    // scenario secs from start
+    char kind[24];                              // kind, or the web command name
+    char args[MAX_SIM_ARGS];                    // remainder of line
+} SimEvent;
+
+// space weather values a scenario may set, by name, see applySimSpaceWeather()
+typedef struct {
+    const char *name;                           // name in scenario
+    size_t offset;                              // offset of SPWxValue within SpaceWeather
+    bool set;                                   // whether set by scenario
+    float value;                                // value when set
+    time_t t;                                   // when set
+} SimSPW;
+
+static SimSPW sim_spw[] = {
+    { "kp",             offsetof (SpaceWeather, kp),            false, 0, 0 },
+    { "sfi",            offsetof (SpaceWeather, flux),          false, 0, 0 },
+    { "ssn",            offsetof (SpaceWeather, ssn),           false, 0, 0 },
+    { "swind",          offsetof (SpaceWeather, swind),         false, 0, 0 },
+    { "drap",           offsetof (SpaceWeather, drap),          false, 0, 0 },
+    { "protons",        offsetof (SpaceWeather, protons),       false, 0, 0 },
+};
+
+static SimEvent *sim_events;                    // malloced list of events, in time order
+static int n_sim_events;                        // n events in sim_events[]
+static int sim_next;                            // index of next event to run
+static float sim_speed = 1;                     // scenario secs per real sec
+static bool sim_repeat;                         // whether to start over when done
+static uint32_t sim_t0;                         // millis() when scenario started, 0 until then
+
+static char sim_xray[10];                       // X-ray class set by scenario, "" if none
+static time_t sim_xray_t;                       // when set
+static char sim_flare[8];                       // flare class set by scenario, "" if none
+static time_t sim_flare_t;                      // when set
+static time_t sim_cme_arrival;                  // CME arrival set by scenario, 0 if none
+static int sim_cme_speed;                       // its speed, km/s
+static time_t sim_cme_t;                        // when set
+
+
+/* return whether cls looks like an X-ray class, eg M5.2
+ */
+static bool isXRayClass (const char *cls)
+{
+    return (strchr ("ABCMX", cls[0]) && cls[0] && isdigit(cls[1]) && strlen(cls) < sizeof(sim_flare));
+}
+
+/* return the X-ray flux of the given class, W/m^2, eg M5.2 is 5.2e-5
+ */
+static float xrayClassFlux (const char *cls)
+{
+    return (atof (cls+1) * powf (10, (strchr ("ABCMX", cls[0]) - "ABCMX") - 8));
+}
+
+/* crack and check the given event, and run it if apply.
+ * return whether ok, else why not in ynot.
+ */
+static bool crackSimEvent (const SimEvent &ev, bool apply, char ynot[], size_t ynot_len)
+{
+    // space weather values
+    for (unsigned i = 0; i < NARRAY(sim_spw); i++) {
+        SimSPW &s = sim_spw[i];
+        if (strcmp (ev.kind, s.name) == 0) {
+            float v;
+            if (sscanf (ev.args, "%f", &v) != 1) {
+                snprintf (ynot, ynot_len, "%s requires a value", s.name);
+                return (false);
+            }
+            if (apply) {
+                s.value = v;
+                s.set = true;
+                s.t = now();
+                refreshSWxPanes();
+            }
+            return (true);
+        }
+    }
+
+    if (strcmp (ev.kind, "xray") == 0 || strcmp (ev.kind, "flare") == 0) {
+        char cls[10];
+        if (sscanf (ev.args, "%9s", cls) != 1 || !isXRayClass (cls)) {
+            snprintf (ynot, ynot_len, "%s requires a class such as M5.2", ev.kind);
+            return (false);
+        }
+        if (apply) {
+            if (ev.kind[0] == 'x') {
+                strcpy (sim_xray, cls);
+                sim_xray_t = now();
+            } else {
+                strcpy (sim_flare, cls);
+                sim_flare_t = now();
+            }
+        }
+        return (true);
+    }
+
+    if (strcmp (ev.kind, "cme") == 0) {
+        float hrs;
+        int speed;
+        if (sscanf (ev.args, "%f %d", &hrs, &speed) != 2 || speed <= 0) {
+            snprintf (ynot, ynot_len, "cme requires hours and km/s");
+            return (false);
+        }
+        if (apply) {
+            sim_cme_arrival = now() + hrs*3600/sim_speed;
+            sim_cme_speed = speed;
+            sim_cme_t = now();
+        }
+        return (true);
+    }
+
+    if (strcmp (ev.kind, "spot") == 0) {
+        float kHz;
+        char call[MAX_SPOTCALL_LEN], grid[MAID_CHARLEN], mode[MAX_SPOTMODE_LEN] = "";
+        LatLong ll;
+        if (sscanf (ev.args, "%f %11s %6s %7s", &kHz, call, grid, mode) < 3 || kHz <= 0
+                                                                || !maidenhead2ll (ll, grid)) {
+            snprintf (ynot, ynot_len, "spot requires kHz, call and grid");
+            return (false);
+        }
+        if (apply && !injectDXClusterSpot (kHz, call, grid, mode))
+            Serial.printf (_FX("Sim: DX Cluster pane is not up for %s\n"), call);
+        return (true);
+    }
+
+    if (strncmp (ev.kind, "set_", 4) == 0) {
+        if (apply) {
+            char cmd[sizeof(ev.kind) + sizeof(ev.args)];
+            snprintf (cmd, sizeof(cmd), "%s%s", ev.kind, ev.args);
+            if (!runInternalWebCommand (cmd))
+                Serial.printf (_FX("Sim: unknown command %s\n"), cmd);
+        }
+        return (true);
+    }
+
+    snprintf (ynot, ynot_len, "unknown event %s", ev.kind);
+    return (false);
+}
+
+/* load the given scenario file, checking each event.
+ * return whether ok, else why not in ynot.
+ */
+bool loadSimulation (const char *fn, char ynot[], size_t ynot_len)
+{
+    FILE *fp = fopen (fn, "r");
+    if (!fp) {
+        snprintf (ynot, ynot_len, "%s: %s", fn, strerror(errno));
+        return (false);
+    }
+
+    char line[200];
+    int lineno = 0;
+    bool ok = true;
+    while (ok && fgets (line, sizeof(line), fp)) {
+        lineno++;
+
+        // skip comments and blank lines
+        char *hash = strchr (line, '#');
+        if (hash)
+            *hash = '\0';
+        char first[20];
+        int n;
+        if (sscanf (line, "%19s%n", first, &n) != 1)
+            continue;
+
+        // options
+        if (strcmp (first, "speed") == 0) {
+            if (sscanf (line+n, "%f", &sim_speed) != 1 || sim_speed <= 0) {
+                snprintf (ynot, ynot_len, "%s:%d: speed must be > 0", fn, lineno);
+                ok = false;
+            }
+            continue;
+        }
+        if (strcmp (first, "repeat") == 0) {
+            sim_repeat = true;
+            continue;
+        }
+
+        // event, web commands are one word with their args
+        SimEvent ev;
+        char *endp;
+        ev.t = strtof (first, &endp);
+        const char *rest = line + n;
+        while (isspace(*rest))
+            rest++;
+        size_t kind_len = strncmp (rest, "set_", 4) == 0 ? strcspn (rest, "?") : strcspn (rest, " \t\r\n");
+        size_t args_len = strlen (rest + kind_len);
+        if (*endp != '\0' || ev.t < 0) {
+            snprintf (ynot, ynot_len, "%s:%d: bad time %s", fn, lineno, first);
+            ok = false;
+        } else if (kind_len == 0 || kind_len >= sizeof(ev.kind) || args_len >= sizeof(ev.args)) {
+            snprintf (ynot, ynot_len, "%s:%d: missing or too long event", fn, lineno);
+            ok = false;
+        } else if (n_sim_events > 0 && ev.t < sim_events[n_sim_events-1].t) {
+            snprintf (ynot, ynot_len, "%s:%d: times may not decrease", fn, lineno);
+            ok = false;
+        } else if (n_sim_events == MAX_SIM_EVENTS) {
+            snprintf (ynot, ynot_len, "%s:%d: more than %d events", fn, lineno, MAX_SIM_EVENTS);
+            ok = false;
+        } else {
+            memcpy (ev.kind, rest, kind_len);
+            ev.kind[kind_len] = '\0';
+            strcpy (ev.args, rest + kind_len);
+            ev.args[strcspn (ev.args, "\r\n")] = '\0';
+            if (strncmp (ev.kind, "set_", 4) != 0) {
+                // trim leading blanks from args of our own events
+                char *ap = ev.args;
+                while (isspace(*ap))
+                    ap++;
+                memmove (ev.args, ap, strlen(ap)+1);
+            }
+            char why[80];
+            if (crackSimEvent (ev, false, why, sizeof(why))) {
+                sim_events = (SimEvent *) realloc (sim_events, (n_sim_events+1)*sizeof(SimEvent));
+                if (!sim_events)
+                    fatalError ("No memory for %d sim events", n_sim_events+1);
+                sim_events[n_sim_events++] = ev;
+            } else {
+                snprintf (ynot, ynot_len, "%s:%d: %s", fn, lineno, why);
+                ok = false;
+            }
+        }
+    }
+    fclose (fp);
+
+    if (ok && n_sim_events == 0) {
+        snprintf (ynot, ynot_len, "%s: no events", fn);
+        ok = false;
+    }
+
+    return (ok);
+}
+
+/* called from main loop() to run all scenario events that are now due, if any.
+ */
+void runSimulation()
+{
+    // out fast if not simulating or done
+    if (n_sim_events == 0 || sim_next >= n_sim_events)
+        return;
+
+    // start the clock on the first call, ie, once everything is showing
+    if (sim_t0 == 0) {
+        sim_t0 = millis();
+        if (sim_t0 == 0)
+            sim_t0 = 1;
+        Serial.printf (_FX("Sim: starting %d events at %g x\n"), n_sim_events, sim_speed);
+    }
+
+    float sim_t = (millis() - sim_t0) * sim_speed / 1000.0F;
+    while (sim_next < n_sim_events && sim_events[sim_next].t <= sim_t) {
+        const SimEvent &ev = sim_events[sim_next++];
+        Serial.printf (_FX("Sim @ %g s: %s %s\n"), ev.t, ev.kind, ev.args);
+        char ynot[80];
+        (void) crackSimEvent (ev, true, ynot, sizeof(ynot));
+    }
+
+    if (sim_next == n_sim_events) {
+        if (sim_repeat) {
+            Serial.println (F("Sim: repeating"));
+            sim_next = 0;
+            sim_t0 = 0;
+        } else
+            Serial.println (F("Sim: scenario complete"));
+    }
+}
+
+/* replace any values in spw that the scenario has set.
+ */
+void applySimSpaceWeather (SpaceWeather &spw)
+{
+    if (n_sim_events == 0)
+        return;
+
+    time_t t0 = now();
+
+    for (unsigned i = 0; i < NARRAY(sim_spw); i++) {
+        const SimSPW &s = sim_spw[i];
+        if (s.set) {
+            SPWxValue *vp = (SPWxValue *)((char *)&spw + s.offset);
+            vp->value = s.value;
+            vp->age = t0 - s.t;
+        }
+    }
+
+    if (sim_xray[0]) {
+        strcpy (spw.xray_class, sim_xray);
+        spw.xray.value = xrayClassFlux (sim_xray);
+        spw.xray.age = t0 - sim_xray_t;
+    }
+
+    if (sim_flare[0]) {
+        strcpy (spw.flare_class, sim_flare);
+        spw.flare_peak = sim_flare_t;
+        spw.flares_age = t0 - sim_flare_t;
+    }
+
+    if (sim_cme_arrival) {
+        spw.cme_arrival = sim_cme_arrival;
+        spw.cme_speed = sim_cme_speed;
+        spw.cmes_age = t0 - sim_cme_t;
+    }
+}
+
+/* pass back the named space weather value, as in sim_spw[], if the scenario has set it.
+ * return whether it has.
+ */
+bool getSimSpaceWeather (const char *name, float &value)
+{
+    for (unsigned i = 0; i < NARRAY(sim_spw); i++) {
+        const SimSPW &s = sim_spw[i];
+        if (s.set && strcmp (name, s.name) == 0) {
+            value = s.value;
+            return (true);
+        }
+    }
+    return (false);
+}
+
+#else // !_IS_UNIX
+
+/* no scenarios on ESP
+ */
+void runSimulation()
+{
+}
+
+void applySimSpaceWeather (SpaceWeather &spw)
+{
+    (void) spw;
+}
+
+bool getSimSpaceWeather (const char *name, float &value)
+{
+    (void) name;
+    (void) value;
+    return (false);
+}
+
+#endif // _IS_UNIX
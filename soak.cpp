@@ -0,0 +1,161 @@
+/* long running soak test to catch memory leaks before they reach an always-on kiosk. started with
+ * -S hours[,MB], HamClock runs as usual while this feeds it a steady churn of synthetic DX spots and test
+ * alerts. every SOAK_SAMPLE seconds it logs the resident memory and the depth of the spot table and web
+ * socket list. after SOAK_WARMUP the resident size becomes the baseline; if it ever grows more than MB
+ * beyond that, default SOAK_DEFMB, HamClock exits with status 1, else with 0 after the given hours.
+ *
+ * use -k too so Setup does not wait for a tap. spots are only added while the DX Cluster pane is up.
+ * UNIX only.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#define SOAK_WARMUP     (15*60)                 // secs to let caches fill before taking the baseline
+#define SOAK_SAMPLE     60                      // secs between memory samples
+#define SOAK_SPOTSECS   5                       // secs between synthetic spots
+#define SOAK_ALERTSECS  300                     // secs between synthetic alerts
+#define SOAK_DEFMB      20                      // default allowed growth over baseline, MB
+#define SOAK_MAXHRS     (24*30)                 // longest soak, hours
+
+static float soak_hrs;                          // requested duration, 0 if not soaking
+static long soak_maxkb;                         // allowed growth over baseline, KB
+static uint32_t soak_t0;                        // millis() when soak started, 0 until then
+static long soak_basekb;                        // resident KB after warmup, 0 until then
+static long soak_peakkb;                        // largest resident KB seen
+static uint32_t soak_nspots, soak_nalerts;      // n synthetic spots and alerts sent
+
+
+/* return our resident memory size in KB, or 0 if unknown
+ */
+static long soakResidentKB(void)
+{
+    long kb = 0;
+    FILE *fp = fopen ("/proc/self/statm", "r");
+    if (fp) {
+        long size, resident;
+        if (fscanf (fp, "%ld %ld", &size, &resident) == 2)
+            kb = resident * (sysconf(_SC_PAGESIZE)/1024);
+        fclose (fp);
+    }
+    return (kb);
+}
+
+/* crack -S hours[,MB].
+ * return whether ok, else why not in ynot.
+ */
+bool setSoakTest (const char *spec, char ynot[], size_t ynot_len)
+{
+    float hrs;
+    int mb = SOAK_DEFMB;
+    int n = sscanf (spec, "%f,%d", &hrs, &mb);
+    if (n < 1 || hrs < 1 || hrs > SOAK_MAXHRS) {
+        snprintf (ynot, ynot_len, "-S hours must be 1 .. %d", SOAK_MAXHRS);
+        return (false);
+    }
+    if (mb <= 0) {
+        snprintf (ynot, ynot_len, "-S MB must be > 0");
+        return (false);
+    }
+
+    soak_hrs = hrs;
+    soak_maxkb = mb * 1024L;
+    return (true);
+}
+
+/* add one synthetic spot of a made up call on a random band
+ */
+static void soakSpot(void)
+{
+    static const char *prefixes[] = {"K", "W", "VE", "G", "DL", "JA", "VK", "PY", "ZS", "UA"};
+    static const float bands[] = {1840, 3573, 7074, 10136, 14074, 18100, 21074, 24915, 28074, 50313};
+
+    char call[NV_CALLSIGN_LEN], grid[MAID_CHARLEN];
+    snprintf (call, sizeof(call), "%s%ld%c%c%c", prefixes[random(NARRAY(prefixes))], random(10),
+                (char)('A'+random(26)), (char)('A'+random(26)), (char)('A'+random(26)));
+    snprintf (grid, sizeof(grid), "%c%c%ld%ld", (char)('A'+random(18)), (char)('A'+random(18)),
+                random(10), random(10));
+    float kHz = bands[random(NARRAY(bands))] + random(30)/10.0F;
+
+    if (injectDXClusterSpot (kHz, call, grid, "FT8"))
+        soak_nspots++;
+}
+
+/* send one synthetic alert of low severity so it churns the notifications without escalating
+ */
+static void soakAlert(void)
+{
+    bool quiet;
+    char ynot[100];
+    if (sendTestAlert ("watch", ALSEV_INFO, quiet, ynot, sizeof(ynot)))
+        soak_nalerts++;
+    else
+        Serial.printf (_FX("Soak: alert failed: %s\n"), ynot);
+}
+
+/* log one sample and end the soak if memory has grown too much or time is up
+ */
+static void soakSample (uint32_t secs)
+{
+    long kb = soakResidentKB();
+    if (kb > soak_peakkb)
+        soak_peakkb = kb;
+
+    DXClusterSpot *spots;
+    uint8_t n_spots = 0;
+    (void) getDXClusterSpots (&spots, &n_spots);
+
+    Serial.printf (_FX("Soak: %u s RSS %ld KB peak %ld KB base %ld KB spots %u ws %d sent %u spots %u alerts\n"),
+                secs, kb, soak_peakkb, soak_basekb, n_spots, nWebSockets(), soak_nspots, soak_nalerts);
+
+    if (soak_basekb == 0 && secs >= SOAK_WARMUP) {
+        soak_basekb = kb;
+        Serial.printf (_FX("Soak: baseline %ld KB, limit %ld KB\n"), soak_basekb, soak_basekb + soak_maxkb);
+    }
+
+    if (soak_basekb && kb > soak_basekb + soak_maxkb) {
+        Serial.printf (_FX("Soak: FAIL after %u s: RSS grew %ld KB beyond baseline, limit %ld KB\n"),
+                secs, kb - soak_basekb, soak_maxkb);
+        exit (1);
+    }
+
+    if (secs >= soak_hrs*3600) {
+        Serial.printf (_FX("Soak: PASS after %u s: RSS %ld KB, peak growth %ld KB\n"), secs, kb,
+                soak_basekb ? soak_peakkb - soak_basekb : 0);
+        exit (0);
+    }
+}
+
+/* called often from the main loop to run the soak, if requested
+ */
+void checkSoakTest(void)
+{
+    static uint32_t spot_ms, alert_ms, sample_ms;
+
+    if (soak_hrs == 0)
+        return;
+
+    if (soak_t0 == 0) {
+        soak_t0 = millis();
+        if (soak_t0 == 0)
+            soak_t0 = 1;
+        Serial.printf (_FX("Soak: starting %g hours, allowing %ld KB growth\n"), soak_hrs, soak_maxkb);
+    }
+
+    if (timesUp (&spot_ms, SOAK_SPOTSECS*1000))
+        soakSpot();
+    if (timesUp (&alert_ms, SOAK_ALERTSECS*1000))
+        soakAlert();
+    if (timesUp (&sample_ms, SOAK_SAMPLE*1000))
+        soakSample ((millis() - soak_t0)/1000);
+}
+
+#else // !_IS_UNIX
+
+void checkSoakTest(void)
+{
+}
+
+#endif // _IS_UNIX
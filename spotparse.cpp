@@ -0,0 +1,359 @@
+/* crack DX cluster spot lines.
+ *
+ * real spots only loosely follow the classic column layout, eg:
+ *
+ *   DX de KD0AA:     18100.0  JR1FYS       FT8 LOUD in FL!                2156Z EL98
+ *   DX de W3LPL-#:    7012.5  K1ABC        CW 23 dB 18 WPM CQ             2157Z
+ *   DX de OH6BG:     50313.0  EA8TL        FT8 -12dB KP32MQ<ES>IL18RF     1200Z KP32
+ *   DX de N1XYZ:14074.0 VE3ABC FT8 -5 dB FN03
+ *
+ * so rather than rely on fixed columns we take the spotter, frequency and call as the first words, the
+ * time as the last word of the form HHMMZ and everything between as the comment. The comment is then
+ * searched for a mode, a signal report in dB and the DX grid. Mode is left empty when not mentioned so
+ * the caller can infer it from the band plan.
+ *
+ * unit test, runs the corpus of real spot lines below or cracks each line given as an argument:
+ *   g++ -D_UNIT_TEST -Wall -o spotparse{,.cpp}
+ *   ./spotparse
+ *   ./spotparse "DX de KD0AA:     18100.0  JR1FYS       FT8 LOUD in FL!                2156Z EL98"
+ */
+
+#ifdef _UNIT_TEST
+
+// stand-alone test program
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <ctype.h>
+#include <string.h>
+#include <strings.h>
+
+#define MAID_CHARLEN            7       // maidenhead string length, including EOS
+#define MAX_SPOTCALL_LEN        12
+#define MAX_SPOTMODE_LEN        8
+#define SPOT_NO_SNR             (-128)
+
+typedef struct {
+    char spotter[MAX_SPOTCALL_LEN];
+    char call[MAX_SPOTCALL_LEN];
+    float kHz;
+    char mode[MAX_SPOTMODE_LEN];
+    char grid[MAID_CHARLEN];
+    int snr;
+    int ut;
+} DXSpotLine;
+
+#define NARRAY(a)       (sizeof(a)/sizeof(a[0]))
+
+#else
+
+// part of HamClock
+
+#include "HamClock.h"
+
+#endif // !_UNIT_TEST
+
+
+#define MAX_SPOTWORD    24                      // longest comment word we examine
+
+
+/* copy the next white-space delimited word at *sp to word, truncating to len-1 chars, and advance *sp
+ * past it. return false if no more words.
+ */
+static bool nextSpotWord (const char **sp, char *word, size_t len)
+{
+    const char *s = *sp;
+    while (isspace(*s))
+        s++;
+    if (!*s)
+        return (false);
+
+    size_t n = 0;
+    while (*s && !isspace(*s)) {
+        if (n < len-1)
+            word[n++] = *s;
+        s++;
+    }
+    word[n] = '\0';
+
+    *sp = s;
+    return (true);
+}
+
+/* return whether word is a grid square of 4 or 6 chars, optionally only 6
+ */
+static bool isSpotGrid (const char *word, bool six_only)
+{
+    size_t l = strlen (word);
+    if (l != 6 && (six_only || l != 4))
+        return (false);
+
+    char f0 = toupper(word[0]), f1 = toupper(word[1]);
+    if (f0 < 'A' || f0 > 'R' || f1 < 'A' || f1 > 'R' || !isdigit(word[2]) || !isdigit(word[3]))
+        return (false);
+    if (l == 6) {
+        char s0 = toupper(word[4]), s1 = toupper(word[5]);
+        if (s0 < 'A' || s0 > 'X' || s1 < 'A' || s1 > 'X')
+            return (false);
+    }
+
+    // RR73 is a valid square but much more likely the FT8 sign-off
+    return (strcasecmp (word, "RR73") != 0);
+}
+
+/* return whether word is an HHMMZ time and if so pass back HHMM
+ */
+static bool isSpotTime (const char *word, int &ut)
+{
+    if (strlen (word) != 5 || !isdigit(word[0]) || !isdigit(word[1]) || !isdigit(word[2])
+                        || !isdigit(word[3]) || toupper(word[4]) != 'Z')
+        return (false);
+
+    int hhmm = atoi (word);
+    if (hhmm/100 > 23 || hhmm%100 > 59)
+        return (false);
+
+    ut = hhmm;
+    return (true);
+}
+
+/* return whether word is a plausible signed dB report and if so pass back its value
+ */
+static bool isSpotDB (const char *word, int &db)
+{
+    const char *w = word;
+    if (*w == '+' || *w == '-')
+        w++;
+    if (!isdigit(*w))
+        return (false);
+    while (isdigit(*w))
+        w++;
+    if (*w != '\0' && strcasecmp (w, "dB") != 0)
+        return (false);
+
+    db = atoi (word);
+    return (db > -60 && db < 100);
+}
+
+/* if word is one of the modes we know pass back its canonical name and return true
+ */
+static bool isSpotMode (const char *word, char mode[MAX_SPOTMODE_LEN])
+{
+    static const char *modes[] = {
+        "FT8", "FT4", "CW", "SSB", "USB", "LSB", "RTTY", "PSK31", "PSK", "JT65", "JT9",
+        "JS8", "MSK144", "Q65", "FSK441", "SSTV", "OLIVIA", "AM", "FM",
+    };
+
+    for (int i = 0; i < (int)NARRAY(modes); i++) {
+        if (strcasecmp (word, modes[i]) == 0) {
+            if (strcasecmp (word, "USB") == 0 || strcasecmp (word, "LSB") == 0)
+                strcpy (mode, "SSB");
+            else
+                strcpy (mode, modes[i]);
+            return (true);
+        }
+    }
+    return (false);
+}
+
+/* search the given spot comment for the first mode, dB report and DX grid mentioned.
+ * a grid pair such as KP32MQ<ES>IL18 is spotter<propagation>DX so we take the second. A lone grid is
+ * only taken if it has 6 chars because 4 char words like RR73 or 5W02 are too often something else.
+ */
+static void crackSpotComment (const char *comment, DXSpotLine &sl)
+{
+    char word[MAX_SPOTWORD];
+    int db;
+
+    while (nextSpotWord (&comment, word, sizeof(word))) {
+
+        // strip trailing punctuation such as FT8, or -10dB:
+        for (size_t l = strlen(word); l > 0 && strchr (",;:!.", word[l-1]); )
+            word[--l] = '\0';
+
+        if (!sl.mode[0] && isSpotMode (word, sl.mode))
+            continue;
+
+        if (sl.snr == SPOT_NO_SNR && isSpotDB (word, db)) {
+            // accept a number alone only if dB follows
+            const char *next = comment;
+            char unit[MAX_SPOTWORD];
+            if (!isdigit(word[strlen(word)-1]))
+                sl.snr = db;
+            else if (nextSpotWord (&next, unit, sizeof(unit)) && strcasecmp (unit, "dB") == 0) {
+                sl.snr = db;
+                comment = next;
+            }
+            continue;
+        }
+
+        if (!sl.grid[0]) {
+            const char *gt = strchr (word, '>');
+            if (word[0] != '<' && strchr (word, '<') && gt) {
+                if (isSpotGrid (gt+1, false))
+                    strcpy (sl.grid, gt+1);
+            } else if (isSpotGrid (word, true))
+                strcpy (sl.grid, word);
+        }
+    }
+}
+
+/* crack the given cluster line into sl if it looks like a DX spot.
+ * mode and grid are empty, snr is SPOT_NO_SNR and ut is -1 if not found.
+ * return whether line is a spot.
+ */
+bool crackDXSpotLine (const char *line, DXSpotLine &sl)
+{
+    memset (&sl, 0, sizeof(sl));
+    sl.snr = SPOT_NO_SNR;
+    sl.ut = -1;
+
+    // DX de
+    while (isspace(*line))
+        line++;
+    if (strncasecmp (line, "DX de", 5) != 0)
+        return (false);
+    line += 5;
+
+    // spotter, ends at : even if no space follows
+    while (isspace(*line))
+        line++;
+    size_t n = 0;
+    while (*line && *line != ':' && !isspace(*line)) {
+        if (n < sizeof(sl.spotter)-1)
+            sl.spotter[n++] = *line;
+        line++;
+    }
+    sl.spotter[n] = '\0';
+    if (*line == ':')
+        line++;
+    if (n == 0)
+        return (false);
+
+    // frequency
+    char word[MAX_SPOTWORD];
+    char *endp;
+    if (!nextSpotWord (&line, word, sizeof(word)))
+        return (false);
+    sl.kHz = strtof (word, &endp);
+    if (*endp != '\0' || sl.kHz <= 0)
+        return (false);
+
+    // call
+    if (!nextSpotWord (&line, sl.call, sizeof(sl.call)))
+        return (false);
+
+    // comment runs to the last time word, if any; anything after is the spotter's grid
+    const char *comment = line;
+    const char *end_comment = line + strlen(line);
+    const char *wp = line;
+    const char *word_start;
+    for (;;) {
+        while (isspace(*wp))
+            wp++;
+        word_start = wp;
+        if (!nextSpotWord (&wp, word, sizeof(word)))
+            break;
+        if (isSpotTime (word, sl.ut))
+            end_comment = word_start;
+    }
+
+    char cbuf[100];
+    n = end_comment - comment;
+    if (n > sizeof(cbuf)-1)
+        n = sizeof(cbuf)-1;
+    memcpy (cbuf, comment, n);
+    cbuf[n] = '\0';
+    crackSpotComment (cbuf, sl);
+
+    return (true);
+}
+
+
+#ifdef _UNIT_TEST
+
+// one line and what we expect from it
+typedef struct {
+    const char *line;
+    const char *spotter, *call;
+    float kHz;
+    const char *mode, *grid;
+    int snr, ut;
+} SpotFixture;
+
+static const SpotFixture corpus[] = {
+    {"DX de KD0AA:     18100.0  JR1FYS       FT8 LOUD in FL!                2156Z EL98",
+                "KD0AA", "JR1FYS", 18100.0, "FT8", "", SPOT_NO_SNR, 2156},
+    {"DX de W3LPL-#:    7012.5  K1ABC        CW 23 dB 18 WPM CQ             2157Z",
+                "W3LPL-#", "K1ABC", 7012.5, "CW", "", 23, 2157},
+    {"DX de OH6BG:     50313.0  EA8TL        FT8 -12dB KP32MQ<ES>IL18RF     1200Z KP32",
+                "OH6BG", "EA8TL", 50313.0, "FT8", "IL18RF", -12, 1200},
+    {"DX de N1XYZ:14074.0 VE3ABC FT8 -5 dB FN03",
+                "N1XYZ", "VE3ABC", 14074.0, "FT8", "", -5, -1},
+    {"DX de G4ABC:     14195.0  3B8M         usb 59 into UK, tnx            0915Z IO91",
+                "G4ABC", "3B8M", 14195.0, "SSB", "", SPOT_NO_SNR, 915},
+    {"DX de JA1XYZ:    21074.0  ZL2ABC       -10 dB RR73 PM95               0402Z",
+                "JA1XYZ", "ZL2ABC", 21074.0, "", "", -10, 402},
+    {"DX de EA5XX:     28074.0  PY2ABC       JN11<TEP>GG66 FT8 +03 dB       1430Z",
+                "EA5XX", "PY2ABC", 28074.0, "FT8", "GG66", 3, 1430},
+    {"DX de K3LR:       3525.0  VK9XX        599 at 0300z wkd 0300Z          0301Z",
+                "K3LR", "VK9XX", 3525.0, "", "", SPOT_NO_SNR, 301},
+    {"DX de VE7CC-1:   10136.0  W1AW         in FN31pr                      1800Z",
+                "VE7CC-1", "W1AW", 10136.0, "", "FN31pr", SPOT_NO_SNR, 1800},
+    {"DX de F5ABC:      1840.0  5T5PA                                        2359Z JN18",
+                "F5ABC", "5T5PA", 1840.0, "", "", SPOT_NO_SNR, 2359},
+    {"WWV de W0MU <18>:   SFI=170, A=6, K=2, No Storms -> No Storms",
+                NULL, NULL, 0, NULL, NULL, 0, 0},
+    {"DX de K1ABC:      not-a-freq  W1AW  CW                                0000Z",
+                NULL, NULL, 0, NULL, NULL, 0, 0},
+    {"To ALL de W1AW: spots look good",
+                NULL, NULL, 0, NULL, NULL, 0, 0},
+};
+
+static void printSpot (const DXSpotLine &sl)
+{
+    printf ("  spotter %s call %s kHz %.1f mode '%s' grid '%s' snr %d ut %d\n",
+                sl.spotter, sl.call, sl.kHz, sl.mode, sl.grid, sl.snr, sl.ut);
+}
+
+int main (int ac, char *av[])
+{
+    DXSpotLine sl;
+
+    // crack any lines given
+    if (ac > 1) {
+        for (int i = 1; i < ac; i++) {
+            printf ("%s\n", av[i]);
+            if (crackDXSpotLine (av[i], sl))
+                printSpot (sl);
+            else
+                printf ("  not a spot\n");
+        }
+        return (0);
+    }
+
+    // else run the corpus
+    int n_bad = 0;
+    for (unsigned i = 0; i < NARRAY(corpus); i++) {
+        const SpotFixture &f = corpus[i];
+        bool is_spot = crackDXSpotLine (f.line, sl);
+        bool ok;
+        if (!f.spotter)
+            ok = !is_spot;
+        else
+            ok = is_spot && strcmp (sl.spotter, f.spotter) == 0 && strcmp (sl.call, f.call) == 0
+                        && sl.kHz == f.kHz && strcmp (sl.mode, f.mode) == 0
+                        && strcmp (sl.grid, f.grid) == 0 && sl.snr == f.snr && sl.ut == f.ut;
+        if (!ok) {
+            printf ("FAIL: %s\n", f.line);
+            if (is_spot)
+                printSpot (sl);
+            n_bad++;
+        }
+    }
+
+    printf ("%d of %d passed\n", (int)NARRAY(corpus) - n_bad, (int)NARRAY(corpus));
+    return (n_bad ? 1 : 0);
+}
+
+#endif // _UNIT_TEST
@@ -0,0 +1,471 @@
+/* additional spot sources, such as a club cluster or a private skimmer network, followed alongside the
+ * main DX cluster while the DX Cluster pane is up. their spots join the same table and map, each marked
+ * with the label and color of its source.
+ *
+ * sources are listed in SRC_FN in our_dir, one per line:
+ *   label host port telnet|ws[/path] #RRGGBB [login]
+ * for example:
+ *   CLUB  cluster.myclub.org  7300 telnet    #FF8000
+ *   SKIM  skimmer.myclub.org  8080 ws/spots  #00FF00
+ *
+ * telnet sources are DX clusters sending lines like "DX de ..."; we log in with login, default our call.
+ * ws sources are web sockets sending one JSON object per text message, such as
+ *   {"call":"K1ABC","freq":14074.0,"spotter":"W1AW","mode":"FT8","snr":-5,"grid":"FN42"}
+ * where freq is kHz and only call and freq are required.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#define SRC_FN          "spotsources.txt"       // source list in our_dir
+#define MAX_SOURCES     4                       // max sources we follow
+#define MAX_LABEL       7                       // max label length, including EOS
+#define SRC_RETRY       120                     // reconnect a lost source this often, secs
+#define SRC_FEED        300                     // send a telnet source a newline if quiet this long, secs
+#define SRC_BUFLEN      1024                    // max telnet line or ws message length
+
+typedef enum {
+    SP_TELNET,
+    SP_WS,
+} SourceProto;
+
+// one source and its connection
+typedef struct {
+    char label[MAX_LABEL];                      // short name shown with its spots
+    char host[64];                              // name or address
+    int port;
+    SourceProto proto;
+    char path[32];                              // ws path
+    uint16_t color;                             // RGB565 marker color
+    char login[NV_CALLSIGN_LEN];                // telnet login
+    WiFiClient client;                          // connection, if any
+    bool logged_in;                             // telnet login sent
+    time_t retry_t;                             // when to try connecting again, 0 asap
+    time_t rx_t;                                // when last heard from
+    uint8_t *buf;                               // malloced SRC_BUFLEN for incoming line or ws frame
+    size_t buf_n;                               // n used in buf
+    unsigned n_spots;                           // spots received since starting
+} SpotSource;
+
+static SpotSource sources[MAX_SOURCES];
+static int n_sources;
+static bool sources_loaded;
+
+
+/* read SRC_FN once
+ */
+static void loadSpotSources()
+{
+    if (sources_loaded)
+        return;
+    sources_loaded = true;
+
+    std::string fn = our_dir + SRC_FN;
+    FILE *fp = fopen (fn.c_str(), "r");
+    if (!fp)
+        return;
+
+    char line[200];
+    while (n_sources < MAX_SOURCES && fgets (line, sizeof(line), fp)) {
+        SpotSource &s = sources[n_sources];
+        char label[20], proto[40], color[20];
+        s.login[0] = '\0';
+        if (line[0] == '#' || sscanf (line, "%19s %63s %d %39s %19s %11s", label, s.host, &s.port, proto,
+                                                color, s.login) < 5)
+            continue;
+
+        unsigned r, g, b;
+        if (strlen (label) >= MAX_LABEL || s.port <= 0 || s.port > 65535
+                                || sscanf (color, "#%2x%2x%2x", &r, &g, &b) != 3) {
+            Serial.printf (_FX("SpotSrc: %s: ignoring %s"), SRC_FN, line);
+            continue;
+        }
+        if (strcmp (proto, "telnet") == 0)
+            s.proto = SP_TELNET;
+        else if (strncmp (proto, "ws", 2) == 0 && (proto[2] == '\0' || proto[2] == '/')) {
+            s.proto = SP_WS;
+            snprintf (s.path, sizeof(s.path), "%.*s", (int)sizeof(s.path)-1, proto[2] ? proto+2 : "/");
+        } else {
+            Serial.printf (_FX("SpotSrc: %s: protocol must be telnet or ws: %s"), SRC_FN, line);
+            continue;
+        }
+
+        strcpy (s.label, label);
+        s.color = RGB565 (r, g, b);
+        if (!s.login[0])
+            snprintf (s.login, sizeof(s.login), "%s", getCallsign());
+        s.buf = (uint8_t *) malloc (SRC_BUFLEN);
+        if (!s.buf)
+            fatalError (_FX("No memory for spot source %s"), s.label);
+        Serial.printf (_FX("SpotSrc: %s %s:%d %s\n"), s.label, s.host, s.port, proto);
+        n_sources++;
+    }
+    fclose (fp);
+}
+
+/* close the given source and schedule a retry
+ */
+static void dropSource (SpotSource &s, const char *why)
+{
+    Serial.printf (_FX("SpotSrc: %s: %s\n"), s.label, why);
+    s.client.stop();
+    s.retry_t = now() + SRC_RETRY;
+}
+
+/* send the web socket upgrade request to the newly connected source and check its reply.
+ * return whether it agreed.
+ */
+static bool upgradeWebSocket (SpotSource &s)
+{
+    char req[250];
+    snprintf (req, sizeof(req),
+        "GET %s HTTP/1.1\r\n"
+        "Host: %s:%d\r\n"
+        "Upgrade: websocket\r\n"
+        "Connection: Upgrade\r\n"
+        "Sec-WebSocket-Key: SGFtQ2xvY2tTcG90U3JjIQ==\r\n"
+        "Sec-WebSocket-Version: 13\r\n"
+        "\r\n", s.path, s.host, s.port);
+    s.client.print (req);
+
+    char line[100];
+    if (!getTCPLine (s.client, line, sizeof(line), NULL) || !strstr (line, " 101")) {
+        Serial.printf (_FX("SpotSrc: %s: no upgrade: %s\n"), s.label, line);
+        return (false);
+    }
+    while (getTCPLine (s.client, line, sizeof(line), NULL))
+        if (line[0] == '\0')
+            return (true);
+    return (false);
+}
+
+/* try to connect the given source.
+ * return whether successful.
+ */
+static bool connectSource (SpotSource &s)
+{
+    if (isOffline()) {
+        dropSource (s, "offline");
+        return (false);
+    }
+
+    Serial.printf (_FX("SpotSrc: %s: connecting to %s:%d\n"), s.label, s.host, s.port);
+    resetWatchdog();
+    if (!wifiOk() || !s.client.connect (s.host, s.port)) {
+        dropSource (s, "connection failed");
+        return (false);
+    }
+    if (s.proto == SP_WS && !upgradeWebSocket (s)) {
+        dropSource (s, "web socket upgrade failed");
+        return (false);
+    }
+
+    s.logged_in = false;
+    s.buf_n = 0;
+    s.rx_t = now();
+    return (true);
+}
+
+/* return whether text ends with a login prompt
+ */
+static bool isPrompt (const char *text)
+{
+    static const char *prompts[] = {"login:", "call:", "callsign:", "call sign:"};
+
+    size_t tl = strlen (text);
+    while (tl > 0 && text[tl-1] == ' ')
+        tl--;
+    for (unsigned i = 0; i < NARRAY(prompts); i++) {
+        size_t pl = strlen (prompts[i]);
+        if (tl >= pl && strncasecmp (text + tl - pl, prompts[i], pl) == 0)
+            return (true);
+    }
+    return (false);
+}
+
+/* find "key": in json and copy its value, without quotes if a string, into value.
+ * return whether found.
+ */
+static bool jsonValue (const char *json, const char *key, char *value, size_t value_len)
+{
+    char qkey[30];
+    snprintf (qkey, sizeof(qkey), "\"%s\"", key);
+    const char *kp = strstr (json, qkey);
+    if (!kp)
+        return (false);
+    kp += strlen (qkey);
+    kp += strspn (kp, " \t");
+    if (*kp++ != ':')
+        return (false);
+    kp += strspn (kp, " \t");
+
+    size_t vl;
+    if (*kp == '"')
+        vl = strcspn (++kp, "\"");
+    else
+        vl = strcspn (kp, ",} \t");
+    snprintf (value, value_len, "%.*s", (int)vl, kp);
+    return (true);
+}
+
+/* crack one JSON spot into sl.
+ * return whether it has at least a call and frequency.
+ */
+static bool crackJSONSpot (const char *json, const SpotSource &s, DXSpotLine &sl)
+{
+    memset (&sl, 0, sizeof(sl));
+    sl.snr = SPOT_NO_SNR;
+    sl.ut = -1;
+
+    char value[40];
+    if (!jsonValue (json, "call", sl.call, sizeof(sl.call)) || !sl.call[0])
+        return (false);
+    if (!jsonValue (json, "freq", value, sizeof(value)) || (sl.kHz = atof (value)) <= 0)
+        return (false);
+    if (!jsonValue (json, "spotter", sl.spotter, sizeof(sl.spotter)))
+        snprintf (sl.spotter, sizeof(sl.spotter), "%s", s.label);
+    (void) jsonValue (json, "mode", sl.mode, sizeof(sl.mode));
+    if (jsonValue (json, "grid", value, sizeof(value)) && strlen (value) < MAID_CHARLEN)
+        strcpy (sl.grid, value);
+    if (jsonValue (json, "snr", value, sizeof(value)))
+        sl.snr = atoi (value);
+    for (char *cp = sl.call; *cp; cp++)
+        *cp = toupper (*cp);
+    return (true);
+}
+
+/* process one complete telnet line from source si
+ */
+static void crackTelnetLine (const SBox &box, int si, char *line)
+{
+    SpotSource &s = sources[si];
+
+    // fold any UTF-8 in comments to ASCII, and some clusters embed \a bell in their reports
+    foldUTF8 (line);
+    for (char *lp = line; *lp; lp++)
+        if (!isprint(*lp))
+            *lp = ' ';
+
+    DXSpotLine sl;
+    if (!s.logged_in && isPrompt (line)) {
+        s.client.println (s.login);
+        s.logged_in = true;
+    } else if (crackDXSpotLine (line, sl)) {
+        s.n_spots++;
+        addDXClusterSourceSpot (box, si+1, sl);
+    }
+}
+
+/* read whatever is available from telnet source si
+ */
+static void readTelnetSource (const SBox &box, int si)
+{
+    SpotSource &s = sources[si];
+    char *line = (char *) s.buf;
+
+    int c;
+    while (s.client.available() && (c = s.client.read()) >= 0) {
+        if (c == '\r')
+            continue;
+        if (c == '\n') {
+            line[s.buf_n] = '\0';
+            crackTelnetLine (box, si, line);
+            s.buf_n = 0;
+        } else if (s.buf_n < SRC_BUFLEN-1)
+            line[s.buf_n++] = c;
+    }
+
+    // a login prompt normally arrives without a newline
+    line[s.buf_n] = '\0';
+    if (!s.logged_in && isPrompt (line)) {
+        s.client.println (s.login);
+        s.logged_in = true;
+        s.buf_n = 0;
+    }
+
+    // keep quiet connections alive
+    if (now() - s.rx_t > SRC_FEED) {
+        s.client.println ("");
+        s.rx_t = now();
+    }
+}
+
+/* send a web socket control frame of the given opcode and payload, masked as required from a client
+ */
+static void sendWSFrame (SpotSource &s, uint8_t opcode, const uint8_t *payload, size_t len)
+{
+    uint8_t frame[2+4+125];
+    if (len > 125)
+        len = 125;
+    frame[0] = 0x80 | opcode;
+    frame[1] = 0x80 | len;
+    for (int i = 0; i < 4; i++)
+        frame[2+i] = random (256);
+    for (size_t i = 0; i < len; i++)
+        frame[6+i] = payload[i] ^ frame[2 + i%4];
+    s.client.write (frame, 6 + len);
+}
+
+/* read whatever is available from web socket source si and process each complete message
+ */
+static void readWSSource (const SBox &box, int si)
+{
+    SpotSource &s = sources[si];
+
+    int c;
+    while (s.client.available() && s.buf_n < SRC_BUFLEN && (c = s.client.read()) >= 0)
+        s.buf[s.buf_n++] = c;
+
+    // process each complete frame: 2 header bytes, extended length, optional mask, payload
+    while (s.buf_n >= 2) {
+        uint8_t opcode = s.buf[0] & 0x0f;
+        bool fin = (s.buf[0] & 0x80) != 0;
+        bool masked = (s.buf[1] & 0x80) != 0;
+        size_t len = s.buf[1] & 0x7f;
+        size_t hdr = 2;
+        if (len == 126) {
+            if (s.buf_n < 4)
+                break;
+            len = (s.buf[2] << 8) | s.buf[3];
+            hdr = 4;
+        } else if (len == 127) {
+            dropSource (s, "web socket message too long");
+            return;
+        }
+        if (masked)
+            hdr += 4;
+        if (hdr + len > SRC_BUFLEN) {
+            dropSource (s, "web socket message too long");
+            return;
+        }
+        if (s.buf_n < hdr + len)
+            break;
+
+        uint8_t *payload = s.buf + hdr;
+        if (masked)
+            for (size_t i = 0; i < len; i++)
+                payload[i] ^= s.buf[hdr - 4 + i%4];
+
+        if (opcode == 0x8) {
+            dropSource (s, "web socket closed by server");
+            return;
+        } else if (opcode == 0x9) {
+            sendWSFrame (s, 0xA, payload, len);
+        } else if (opcode == 0x1 && fin) {
+            // text message, N.B. we ignore messages split into fragments
+            char json[SRC_BUFLEN+1];
+            memcpy (json, payload, len);
+            json[len] = '\0';
+            DXSpotLine sl;
+            if (crackJSONSpot (json, s, sl)) {
+                s.n_spots++;
+                addDXClusterSourceSpot (box, si+1, sl);
+            }
+        }
+
+        s.buf_n -= hdr + len;
+        memmove (s.buf, s.buf + hdr + len, s.buf_n);
+    }
+}
+
+/* called while the DX Cluster pane is up to connect each source as needed and add any new spots
+ */
+void pollSpotSources (const SBox &box)
+{
+    loadSpotSources();
+
+    for (int si = 0; si < n_sources; si++) {
+        SpotSource &s = sources[si];
+
+        if (!s.client) {
+            if (now() < s.retry_t || !connectSource (s))
+                continue;
+        }
+
+        resetWatchdog();
+        if (s.client.available())
+            s.rx_t = now();
+        if (s.proto == SP_TELNET)
+            readTelnetSource (box, si);
+        else
+            readWSSource (box, si);
+
+        if (s.client && !s.client.connected())
+            dropSource (s, "lost connection");
+    }
+}
+
+/* close all sources, as when the DX Cluster pane closes
+ */
+void closeSpotSources (void)
+{
+    for (int si = 0; si < n_sources; si++) {
+        if (sources[si].client) {
+            sources[si].client.stop();
+            sources[si].retry_t = 0;
+        }
+    }
+}
+
+/* return the label of spot source 1 .. n, else NULL
+ */
+const char *getSpotSourceLabel (uint8_t source)
+{
+    loadSpotSources();
+    return (source >= 1 && source <= n_sources ? sources[source-1].label : NULL);
+}
+
+/* return the marker color of spot source 1 .. n
+ */
+uint16_t getSpotSourceColor (uint8_t source)
+{
+    loadSpotSources();
+    return (source >= 1 && source <= n_sources ? sources[source-1].color : RA8875_WHITE);
+}
+
+/* print each source and its state on one line with the given prefix
+ */
+void printSpotSources (WiFiClient &client, const char *prefix)
+{
+    loadSpotSources();
+
+    char buf[250];
+    size_t l = snprintf (buf, sizeof(buf), "%s", prefix);
+    if (n_sources == 0)
+        l += snprintf (buf+l, sizeof(buf)-l, "none");
+    for (int si = 0; si < n_sources && l < sizeof(buf); si++) {
+        SpotSource &s = sources[si];
+        l += snprintf (buf+l, sizeof(buf)-l, "%s%s %s:%d %s %u spots", si > 0 ? ", " : "", s.label,
+                        s.host, s.port, s.client ? "connected" : "idle", s.n_spots);
+    }
+    client.println (buf);
+}
+
+#else // !_IS_UNIX
+
+/* dummy versions for systems with no source list
+ */
+void pollSpotSources (const SBox &box)
+{
+    (void) box;
+}
+
+void closeSpotSources (void)
+{
+}
+
+const char *getSpotSourceLabel (uint8_t source)
+{
+    (void) source;
+    return (NULL);
+}
+
+uint16_t getSpotSourceColor (uint8_t source)
+{
+    (void) source;
+    return (RA8875_WHITE);
+}
+
+#endif // _IS_UNIX
@@ -188,6 +188,9 @@ static void setAlarmPin (bool set) { (void) set; }
 #define SW_CDP_W        ALM_W                   // countdown period display box width
 #define SW_CD_WARNDT    60000                   // countdown warning time, ms
 
+// alert highlight params
+#define ALERT_PULSE_MS  2000                    // ALERT_PULSE period, ms
+
 // big analog clock params
 #define BAC_X0          400                     // x center
 #define BAC_Y0          240                     // y center
@@ -258,6 +261,20 @@ static uint32_t start_t, stop_dt;               // millis() at start, since stop
 static uint16_t alarm_hrmn;                     // alarm time, hr*60 + min
 static time_t alarm_ringtime;                   // now() when alarm started ringing
 static AlarmState alarm_state;                  // whether off, armed or ringing
+static AlertStyle alert_style;                  // how alerts draw attention
+static time_t contest_start, contest_end;       // UTC contest period shown on main page, both 0 if none
+
+// stopwatch hot keys, see checkStopwatchKey()
+#define SWRUN_KEY       ('S'-'@')               // Control-S
+#define SWRESET_KEY     ('R'-'@')               // Control-R
+#define SWCD_KEY        ('D'-'@')               // Control-D
+
+// names of each AlertStyle, N.B. must be in same order
+static const char *alert_style_names[ALERT_N] = {
+    "flash",
+    "pulse",
+    "steady",
+};
 
 // button labels
 static char cd_lbl[] = "Count down";
@@ -530,7 +547,7 @@ static void determineCDVisuals (uint32_t ms_left, SWCDState &cds, uint16_t &colo
         return;
     }
 
-    bool flash_on = (millis()%500) < 250;               // flip at 2 Hz
+    bool flash_on = alert_style != ALERT_FLASH || (millis()%500) < 250;        // flip at 2 Hz if flashing
     if (ms_left > 0) {
         if (flash_on) {
             cds = SWCDS_WARN_ON;
@@ -550,6 +567,37 @@ static void determineCDVisuals (uint32_t ms_left, SWCDState &cds, uint16_t &colo
     }
 }
 
+/* draw the border of the given pane box in color highlighted according to alert_style if alerting,
+ * else restore the normal border.
+ */
+void drawAlertBorder (const SBox &b, uint16_t color, bool alerting)
+{
+    uint16_t c = GRAY;
+    if (alerting) {
+        switch (alert_style) {
+        case ALERT_FLASH:
+            c = (millis()%500) < 250 ? color : RA8875_BLACK;
+            break;
+        case ALERT_PULSE: {
+                float level = 0.55F + 0.45F*sinf (2*M_PIF*(millis()%ALERT_PULSE_MS)/ALERT_PULSE_MS);
+                c = RGB565 (level*RGB565_R(color), level*RGB565_G(color), level*RGB565_B(color));
+            }
+            break;
+        case ALERT_STEADY:      // fallthru
+        default:
+            c = color;
+            break;
+        }
+    }
+
+    // same sides as prepPlotBox()
+    uint16_t rx = b.x+b.w-1;
+    uint16_t by = b.y+b.h-1;
+    tft.drawLine (b.x, b.y, b.x, by, c);
+    tft.drawLine (b.x, b.y, rx, b.y, c);
+    tft.drawLine (rx, b.y, rx, by, c);
+}
+
 /* draw alarm_hrmn, pin and label if requested in various ways depending on sws_display
  */
 static void drawAlarmIndicator (bool label_too)
@@ -628,6 +676,16 @@ static void drawCDTimeRemaining(bool force)
     // get ms remaining 
     uint32_t ms_left = getCountdownLeft();
 
+    // run alert script and tell web sockets once when time runs out
+    static bool prev_timedout;
+    bool timedout = ms_left == 0;
+    if (timedout && !prev_timedout) {
+        char details[100] = "";
+        (void) notifyAlert ("countdown", alertSeverity ("countdown"), details, sizeof(details), NULL, 0, NULL,
+                                NULL, NULL);
+    }
+    prev_timedout = timedout;
+
     // determine range and color
     SWCDState cds;
     uint16_t color;
@@ -665,6 +723,16 @@ static void drawCDTimeRemaining(bool force)
         ms_left -= mn * 60000U;
         uint8_t sc = ms_left/1000U;
 
+        // highlight the countdown pane border while warning or timed out
+        static bool prev_alerting;
+        bool alerting = cds == SWCDS_WARN_ON || cds == SWCDS_TIMEOUT_ON
+                                || (cds == SWCDS_OFF && prev_alerting);         // flashing off
+        PlotPane cd_pane = findPaneChoiceNow(PLOT_CH_COUNTDOWN);
+        if (sws_display == SWD_NONE && cd_pane != PANE_NONE && (alerting || prev_alerting)) {
+            drawAlertBorder (plot_b[cd_pane], ms_left > 0 ? DYELLOW : RA8875_RED, alerting);
+            prev_alerting = alerting;
+        }
+
         // avoid repeating the same time and color
         static uint8_t prev_sc;
         static uint16_t prev_color;
@@ -1210,6 +1278,18 @@ static void drawBigClock (bool all)
 }
 
 
+/* save sws_display in NV if it has changed so restoreSWDisplay() can show it again after a restart
+ */
+static void saveSWDisplay()
+{
+    static uint8_t saved_swd = SWD_NONE;
+
+    if (sws_display != saved_swd) {
+        NVWriteUInt8 (NV_SWDISPLAY, sws_display);
+        saved_swd = sws_display;
+    }
+}
+
 /* draw the main stopwatch page controls.
  * N.B. we do not erase screen, leave that to caller
  */
@@ -1252,6 +1332,9 @@ static void drawSWMainPage()
  */
 static bool checkExternalTurnOff()
 {
+    // also a convenient place to highlight the alarm pane
+    drawAlertBorder (plot_b[PANE_2], RA8875_RED, true);
+
     return (alarmPinIsSet() || alarm_state != ALMS_RINGING);
 }
 
@@ -1299,9 +1382,10 @@ static void showAlarmRinging()
         selectFontStyle (LIGHT_FONT, SMALL_FONT);
         drawStringInBox (" Cancel ", dismiss_b, false, BRGRAY);
 
-        // wait for tap or timeout
+        // wait for tap or timeout, a tap or the cancel pin acknowledges
         SCoord s;
-        (void) waitForTap (dismiss_b, b, checkExternalTurnOff, ALM_RINGTO, s);
+        if (waitForTap (dismiss_b, b, checkExternalTurnOff, ALM_RINGTO, s) || alarmPinIsSet())
+            ackAlertEscalations ("alarm");
 
         // off
         alarm_state = ALMS_ARMED;
@@ -1419,6 +1503,7 @@ static void checkSWPageTouch()
                 break;
             case ALMS_RINGING:
                 alarm_state = ALMS_ARMED;
+                ackAlertEscalations ("alarm");
                 break;
             }
             drawAlarmIndicator  (true);
@@ -1581,6 +1666,7 @@ static void checkSWPageTouch()
         } else if (inBox (s, bcalarm_b)) {
             if (alarm_state == ALMS_RINGING) {
                 alarm_state = ALMS_ARMED;
+                ackAlertEscalations ("alarm");
                 drawAlarmIndicator(false);
                 logState();
             }
@@ -1620,13 +1706,71 @@ void initStopwatch()
         alarm_state = ALMS_ARMED;
     }
 
+    // read how alerts draw attention
+    uint8_t as;
+    if (!NVReadUInt8 (NV_ALERTSTYLE, &as) || as >= ALERT_N) {
+        as = ALERT_FLASH;
+        NVWriteUInt8 (NV_ALERTSTYLE, as);
+    }
+    alert_style = (AlertStyle) as;
+
+    // read contest period
+    uint32_t cs, ce;
+    if (NVReadUInt32 (NV_CONTESTSTART, &cs) && NVReadUInt32 (NV_CONTESTEND, &ce) && ce > cs) {
+        contest_start = cs;
+        contest_end = ce;
+    }
+
     // insure output pins are off
     setLEDState (SWCDS_OFF);
     setAlarmPin (false);
 }
 
+/* return whether a contest period is set that has not yet ended
+ */
+static bool contestPending()
+{
+    return (contest_end > 0 && now() < contest_end);
+}
+
+/* draw time until the contest starts, or time left while it runs, in stopwatch_b.
+ * N.B. we assume contestPending()
+ */
+static void drawContestClock (bool force)
+{
+    // time to next edge, minus before start
+    time_t t = now();
+    bool before = t < contest_start;
+    long secs = before ? contest_start - t : contest_end - t;
+    uint16_t color = before ? RA8875_CYAN : (secs < 3600 ? DYELLOW : RA8875_GREEN);
+
+    // just once per minute unless force
+    static long prev_mins;
+    long mins = (secs + 59)/60;
+    if (mins == prev_mins && !force)
+        return;
+    prev_mins = mins;
+
+    // format
+    char buf[32];
+    int hr = mins/60;
+    if (hr >= 100)
+        snprintf (buf, sizeof(buf), "%s%dd", before ? "-" : "", hr/24);
+    else
+        snprintf (buf, sizeof(buf), "%s%dh%02ld", before ? "-" : "", hr, mins%60);
+
+    // overwrite stopwatch icon
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    uint16_t w = getTextWidth(buf);
+    tft.fillRect (stopwatch_b.x, stopwatch_b.y, stopwatch_b.w, stopwatch_b.h, RA8875_BLACK);
+    tft.setTextColor (color);
+    tft.setCursor (stopwatch_b.x + (stopwatch_b.w-w)/2, stopwatch_b.y+stopwatch_b.h/4);
+    tft.print (buf);
+}
+
 /* draw the main HamClock page stopwatch icon or count down time remaining or alarm is set in stopwatch_b
- *   and/or pane if showing, all depending on sws_engine.
+ *   and/or pane if showing, all depending on sws_engine. A pending contest shows its time when not
+ *   counting down.
  */
 void drawMainPageStopwatch (bool force)
 {
@@ -1634,6 +1778,10 @@ void drawMainPageStopwatch (bool force)
 
         drawCDTimeRemaining(force);
 
+    } else if (contestPending()) {
+
+        drawContestClock(force);
+
     } else if (force) {
 
         // draw icon
@@ -1691,6 +1839,30 @@ void drawMainPageStopwatch (bool force)
 }
 
 
+/* called once at startup after the main page is up to return to whichever stopwatch page was showing
+ * when the display state last changed, so a restart comes back looking the same.
+ */
+void restoreSWDisplay()
+{
+    uint8_t swd;
+    if (!NVReadUInt8 (NV_SWDISPLAY, &swd) || swd == SWD_NONE)
+        return;
+
+    Serial.println(F("SW: restore"));
+
+    // same as entering from the main page
+    hideClocks();
+    eraseScreen();
+    drawSWMainPage();
+
+    // then on to a big clock if that was showing
+    if (swd == SWD_BCDIGITAL || swd == SWD_BCANALOG) {
+        sws_display = (bc_bits & SW_BCDIGBIT) ? SWD_BCDIGITAL : SWD_BCANALOG;
+        drawBigClock (true);
+        logState();
+    }
+}
+
 /* stopwatch_b has been touched from HamClock Main page:
  * if tapped while counting down just reset and continue main HamClock page, else start main SW page.
  */
@@ -1730,11 +1902,19 @@ bool runStopwatch()
         alarm_ringtime = now();
         alarm_state = ALMS_RINGING;
         logState();
+        char hhmm[10];
+        snprintf (hhmm, sizeof(hhmm), "%02d:%02d", alarm_hrmn/60, alarm_hrmn%60);
+        char details[100];
+        strcpy (details, hhmm);
+        (void) notifyAlert ("alarm", alertSeverity ("alarm"), details, sizeof(details), NULL, 0, NULL,
+                                hhmm, NULL);
         showAlarmRinging();
     }
     if (alarm_state == ALMS_RINGING) {
         if (alarmPinIsSet() || now() - alarm_ringtime >= ALM_RINGTO/1000) {
             // op hit the cancel pin or timed out
+            if (alarmPinIsSet())
+                ackAlertEscalations ("alarm");
             alarm_state = ALMS_ARMED;
             logState();
             if (sws_display == SWD_NONE)
@@ -1751,6 +1931,7 @@ bool runStopwatch()
         // check for our button taps.
         // N.B. this may update sws_display so check again afterwards
         checkSWPageTouch();
+        saveSWDisplay();
 
         switch (sws_display) {
 
@@ -1787,9 +1968,15 @@ bool runStopwatch()
 
     } else {
 
-        // main hamclock page is up, update count if counting down
-        if (sws_engine == SWE_COUNTDOWN)
+        // main hamclock page is up, update count if counting down or contest is pending,
+        // restore icon when contest ends
+        static bool prev_contest;
+        bool contest = contestPending();
+        if (sws_engine == SWE_COUNTDOWN || contest)
             drawMainPageStopwatch (false);
+        else if (prev_contest)
+            drawMainPageStopwatch (true);
+        prev_contest = contest;
 
         // not up
         return (false);
@@ -1902,6 +2089,8 @@ void setAlarmState (const AlarmState &as, uint16_t hr, uint16_t mn)
 {
     if (as == ALMS_OFF) {
         // minimal state downgrade, leave time unchanged
+        if (alarm_state == ALMS_RINGING)
+            ackAlertEscalations ("alarm");
         alarm_state = alarm_state == ALMS_RINGING ? ALMS_ARMED : ALMS_OFF;
     } else {
         // set new state and time
@@ -1921,3 +2110,75 @@ SWBCBits getBigClockBits(void)
 {
     return ((SWBCBits)bc_bits);
 }
+
+/* return name of the current alert style
+ */
+const char *getAlertStyleName(void)
+{
+    return (alert_style_names[alert_style]);
+}
+
+/* set alert style by name, return whether recognized
+ */
+bool setAlertStyle (const char *name)
+{
+    for (int i = 0; i < ALERT_N; i++) {
+        if (strcmp (name, alert_style_names[i]) == 0) {
+            alert_style = (AlertStyle) i;
+            NVWriteUInt8 (NV_ALERTSTYLE, i);
+            return (true);
+        }
+    }
+    return (false);
+}
+
+/* set the contest period to show on the main page, or none if end <= start.
+ */
+void setContestPeriod (time_t start, time_t end)
+{
+    if (end <= start)
+        start = end = 0;
+    contest_start = start;
+    contest_end = end;
+    NVWriteUInt32 (NV_CONTESTSTART, contest_start);
+    NVWriteUInt32 (NV_CONTESTEND, contest_end);
+
+    if (sws_display == SWD_NONE)
+        drawMainPageStopwatch (true);
+}
+
+/* pass back the contest period, return whether one is set that has not yet ended
+ */
+bool getContestPeriod (time_t &start, time_t &end)
+{
+    start = contest_start;
+    end = contest_end;
+    return (contestPending());
+}
+
+/* called with each keyboard char to control the stopwatch from hot keys:
+ *   Control-S  run or stop
+ *   Control-R  reset
+ *   Control-D  start count down from the current period
+ */
+void checkStopwatchKey (char kbc)
+{
+    bool ok;
+
+    switch (kbc) {
+    case SWRUN_KEY:
+        ok = setSWEngineState (sws_engine == SWE_RUN ? SWE_STOP : SWE_RUN, 0);
+        break;
+    case SWRESET_KEY:
+        ok = setSWEngineState (SWE_RESET, 0);
+        break;
+    case SWCD_KEY:
+        ok = setSWEngineState (SWE_COUNTDOWN, countdown_period);
+        break;
+    default:
+        return;
+    }
+
+    if (ok)
+        insureCountdownPaneSensible();
+}
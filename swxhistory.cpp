@@ -0,0 +1,246 @@
+/* keep a local history of each space weather value as it is fetched so trends can be shown
+ * over weeks without asking the server again. UNIX only.
+ *
+ * history is one text file in our_dir with one sample per line: unix_time kind value
+ * samples older than the retention period are pruned at startup and once each day.
+ */
+
+#include "HamClock.h"
+
+
+// names of each kind, N.B. must match SWxHistKind order
+static const char *swxh_names[SWXH_N] = {
+    "SSN", "SFI", "Kp", "SolarWind", "XRay", "DRAP",
+};
+
+#if defined(_IS_UNIX)
+
+#include <errno.h>
+
+#define SWXH_DEFDAYS    30                      // default retention, days
+#define SWXH_MAXDAYS    366                     // max retention, days
+#define SWXH_PRUNE_MS   (24*3600*1000UL)        // prune interval, millis
+
+static const char swxh_fn[] = "swx-history.txt";
+static const char swxh_tmp_fn[] = "swx-history.tmp";
+
+static uint16_t swxh_days;                      // retention, days, 0 to not record at all
+static bool swxh_init;                          // set once swxh_days is loaded from NV
+static uint32_t prune_ms;                       // time of last prune
+
+
+/* load swxh_days from NV if not already
+ */
+static void loadSWxHistDays()
+{
+    if (swxh_init)
+        return;
+
+    if (!NVReadUInt16 (NV_SWXHISTDAYS, &swxh_days) || swxh_days > SWXH_MAXDAYS) {
+        swxh_days = SWXH_DEFDAYS;
+        NVWriteUInt16 (NV_SWXHISTDAYS, swxh_days);
+    }
+    swxh_init = true;
+}
+
+/* crack one history file line.
+ * return whether line is valid and, if so, fill in the fields.
+ */
+static bool crackSWxHistLine (const char *line, time_t &t, SWxHistKind &kind, float &value)
+{
+    long ut;
+    char name[20];
+    if (sscanf (line, "%ld %19s %f", &ut, name, &value) != 3)
+        return (false);
+
+    for (int i = 0; i < SWXH_N; i++) {
+        if (strcmp (name, swxh_names[i]) == 0) {
+            kind = (SWxHistKind)i;
+            t = ut;
+            return (true);
+        }
+    }
+
+    return (false);
+}
+
+/* remove all samples older than the retention period by copying the rest to a new file.
+ */
+static void pruneSWxHistory()
+{
+    std::string path = our_dir + swxh_fn;
+    std::string tmp_path = our_dir + swxh_tmp_fn;
+
+    FILE *in_fp = fopen (path.c_str(), "r");
+    if (!in_fp)
+        return;                                 // fine if none yet
+    FILE *out_fp = fopen (tmp_path.c_str(), "w");
+    if (!out_fp) {
+        Serial.printf (_FX("SWxHist: %s: %s\n"), tmp_path.c_str(), strerror(errno));
+        fclose (in_fp);
+        return;
+    }
+
+    time_t oldest = now() - (time_t)swxh_days*SECSPERDAY;
+    int n_kept = 0, n_dropped = 0;
+    char line[100];
+    while (fgets (line, sizeof(line), in_fp)) {
+        time_t t;
+        SWxHistKind kind;
+        float value;
+        if (crackSWxHistLine (line, t, kind, value) && t >= oldest) {
+            fputs (line, out_fp);
+            n_kept++;
+        } else
+            n_dropped++;
+    }
+
+    fclose (in_fp);
+    fclose (out_fp);
+
+    if (rename (tmp_path.c_str(), path.c_str()) < 0)
+        Serial.printf (_FX("SWxHist: %s: %s\n"), path.c_str(), strerror(errno));
+    else
+        Serial.printf (_FX("SWxHist: kept %d dropped %d\n"), n_kept, n_dropped);
+}
+
+/* append a newly fetched sample of the given kind to the history, if enabled.
+ * also prune old samples if it has been a while.
+ */
+void recordSWxSample (SWxHistKind kind, float value)
+{
+    loadSWxHistDays();
+    if (swxh_days == 0)
+        return;
+
+    if (prune_ms == 0 || timesUp (&prune_ms, SWXH_PRUNE_MS)) {
+        prune_ms = millis();
+        pruneSWxHistory();
+    }
+
+    std::string path = our_dir + swxh_fn;
+    FILE *fp = fopen (path.c_str(), "a");
+    if (!fp) {
+        Serial.printf (_FX("SWxHist: %s: %s\n"), path.c_str(), strerror(errno));
+        return;
+    }
+    fprintf (fp, "%ld %s %g\n", (long)now(), swxh_names[kind], value);
+    fclose (fp);
+}
+
+/* return count of parallel lists of times and values of the given kind at or after from, oldest first.
+ * N.B. caller must free each list iff return > 0.
+ */
+int getSWxHistory (SWxHistKind kind, time_t from, time_t **times, float **values)
+{
+    std::string path = our_dir + swxh_fn;
+    FILE *fp = fopen (path.c_str(), "r");
+    if (!fp)
+        return (0);
+
+    int n = 0, n_max = 0;
+    char line[100];
+    while (fgets (line, sizeof(line), fp)) {
+        time_t t;
+        SWxHistKind k;
+        float value;
+        if (crackSWxHistLine (line, t, k, value) && k == kind && t >= from) {
+            if (n == 0) {
+                *times = NULL;
+                *values = NULL;
+            }
+            if (n == n_max) {
+                // grow geometrically, a long history has many thousands of samples
+                n_max = n_max ? 2*n_max : 64;
+                *times = (time_t *) realloc (*times, n_max*sizeof(time_t));
+                *values = (float *) realloc (*values, n_max*sizeof(float));
+                if (!*times || !*values)
+                    fatalError (_FX("No memory for %d space weather samples"), n_max);
+            }
+            (*times)[n] = t;
+            (*values)[n] = value;
+            n++;
+        }
+    }
+
+    fclose (fp);
+    return (n);
+}
+
+/* return history retention period, days. 0 means not recording.
+ */
+int getSWxHistoryDays()
+{
+    loadSWxHistDays();
+    return (swxh_days);
+}
+
+/* set history retention period, days, 0 to stop recording. prune now if shorter.
+ * return whether in range.
+ */
+bool setSWxHistoryDays (int days)
+{
+    if (days < 0 || days > SWXH_MAXDAYS)
+        return (false);
+
+    loadSWxHistDays();
+    bool shorter = days < swxh_days;
+    swxh_days = days;
+    NVWriteUInt16 (NV_SWXHISTDAYS, swxh_days);
+    if (shorter && days > 0)
+        pruneSWxHistory();
+    return (true);
+}
+
+#else
+
+// dummies
+
+void recordSWxSample (SWxHistKind kind, float value)
+{
+    (void) kind;
+    (void) value;
+}
+
+int getSWxHistory (SWxHistKind kind, time_t from, time_t **times, float **values)
+{
+    (void) kind;
+    (void) from;
+    (void) times;
+    (void) values;
+    return (0);
+}
+
+int getSWxHistoryDays()
+{
+    return (0);
+}
+
+bool setSWxHistoryDays (int days)
+{
+    (void) days;
+    return (false);
+}
+
+#endif // _IS_UNIX
+
+/* return the name of the given kind
+ */
+const char *getSWxHistName (SWxHistKind kind)
+{
+    return (swxh_names[kind]);
+}
+
+/* find the kind with the given name, ignoring case.
+ * return whether found.
+ */
+bool findSWxHistKind (const char *name, SWxHistKind &kind)
+{
+    for (int i = 0; i < SWXH_N; i++) {
+        if (strcasecmp (name, swxh_names[i]) == 0) {
+            kind = (SWxHistKind)i;
+            return (true);
+        }
+    }
+    return (false);
+}
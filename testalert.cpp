@@ -0,0 +1,46 @@
+/* send a synthetic alert through the same notifyAlert() as real ones so users can check their on_alert
+ * script, web socket dashboards, display wake, sounds, escalations and quiet hours without waiting for the
+ * real thing. the details say TEST and the alert history records it like any other.
+ *
+ * send one with set_testalert?kind=K&severity=S or, on UNIX, hamclock -A kind=K&severity=S to ask the
+ * HamClock already running on this host.
+ */
+
+#include "HamClock.h"
+
+
+// same kinds as alerthist.cpp
+static const char *test_kinds[] = {
+    "watch", "countdown", "alarm", "swr", "iss", "aurora",
+};
+
+
+/* send a test alert of the given kind as if it had the given severity, ALSEV_N for its usual severity.
+ * set quiet if quiet hours held back its notifications.
+ * if trouble put reason in ynot and return false.
+ */
+bool sendTestAlert (const char *kind, int sev, bool &quiet, char *ynot, size_t ynot_len)
+{
+    bool known = false;
+    for (unsigned i = 0; i < NARRAY(test_kinds) && !known; i++)
+        known = strcmp (kind, test_kinds[i]) == 0;
+    if (!known) {
+        size_t l = snprintf (ynot, ynot_len, "kind must be one of:");
+        for (unsigned i = 0; i < NARRAY(test_kinds) && l < ynot_len; i++)
+            l += snprintf (ynot+l, ynot_len-l, " %s", test_kinds[i]);
+        return (false);
+    }
+    if (sev < 0 || sev > ALSEV_N) {
+        snprintf (ynot, ynot_len, "bad severity");
+        return (false);
+    }
+    if (sev == ALSEV_N)
+        sev = alertSeverity (kind);
+
+    char details[100];
+    snprintf (details, sizeof(details), "TEST %s %s alert", alertSeverityName(sev), kind);
+    quiet = !notifyAlert (kind, (AlertSeverity)sev, details, sizeof(details), NULL, 0, NULL, "TEST", NULL);
+    Serial.printf (_FX("TestAlert: %s\n"), details);
+
+    return (true);
+}
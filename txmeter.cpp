@@ -0,0 +1,226 @@
+/* transmitter meter shown in a plot pane: forward power and SWR bars with peak hold, and an alert when
+ * SWR reaches a chosen limit.
+ *
+ * readings come from rigctld while transmitting, see radio.cpp, or from an external wattmeter whose
+ * readings are pushed with set_txmeter?fwd=W&swr=S, for example by a script reading its serial port or
+ * subscribing to its MQTT topic.
+ */
+
+#include "HamClock.h"
+
+
+// layout
+#define TITLE_COLOR     RA8875_GREEN
+#define LABEL_COLOR     GRAY
+#define FWD_COLOR       RA8875_GREEN
+#define SWR_OK_COLOR    RA8875_GREEN
+#define SWR_HI_COLOR    RA8875_RED
+#define PEAK_COLOR      RA8875_WHITE
+#define TITLE_Y0        27              // title dy, match VOACAP title position
+#define BAR_X0          8               // bars left x offset from box left
+#define BAR_W           (PLOTBOX_W-2*BAR_X0)    // bar width
+#define BAR_H           14              // bar height
+#define FWD_Y0          50              // forward power bar y down from box top
+#define SWR_Y0          100             // SWR bar y down from box top
+#define SWR_MAX         5.0F            // SWR at right end of bar
+
+#define STALE_MS        3000            // readings older than this show as idle, ms
+#define PEAK_MS         2000            // peak hold time, ms
+#define REALERT_MS      60000           // min time between SWR alerts, ms
+#define DEF_SWRALERT    30              // default SWR alert level, tenths
+
+static float fwd_watts, swr;            // latest readings
+static uint32_t reading_ms;             // millis() of latest reading, 0 if never
+static float peak_watts;                // peak hold forward power
+static uint32_t peak_ms;                // millis() when peak_watts was set
+static uint16_t swr_alert10;            // SWR alert level, tenths, 0 to disable
+static uint32_t alert_ms;               // millis() of last SWR alert, 0 if none yet
+static bool swr_alerting;               // whether SWR is at or above the alert level
+
+
+
+/* read the SWR alert level from NV once
+ */
+static void loadSWRAlert()
+{
+    static bool loaded;
+    if (loaded)
+        return;
+    loaded = true;
+
+    if (!NVReadUInt16 (NV_SWRALERT, &swr_alert10)) {
+        swr_alert10 = DEF_SWRALERT;
+        NVWriteUInt16 (NV_SWRALERT, swr_alert10);
+    }
+}
+
+/* set the SWR at which to alert, 0 for never
+ */
+void setSWRAlert (float level)
+{
+    loadSWRAlert();
+    swr_alert10 = level > 0 ? (uint16_t)(level*10 + 0.5F) : 0;
+    NVWriteUInt16 (NV_SWRALERT, swr_alert10);
+}
+
+/* return the SWR at which to alert, 0 for never
+ */
+float getSWRAlert()
+{
+    loadSWRAlert();
+    return (swr_alert10/10.0F);
+}
+
+/* record a new forward power and SWR reading, either may be 0 if unknown.
+ * alert if SWR reached the alert level, then not again until it drops back or REALERT_MS passes.
+ */
+void noteTxMeter (float watts, float new_swr)
+{
+    uint32_t t0 = millis();
+
+    fwd_watts = watts;
+    swr = new_swr;
+    reading_ms = t0;
+    if (watts >= peak_watts || t0 - peak_ms > PEAK_MS) {
+        peak_watts = watts;
+        peak_ms = t0;
+    }
+
+    loadSWRAlert();
+    bool high = swr_alert10 > 0 && swr*10 >= swr_alert10;
+    if (high && (!swr_alerting || t0 - alert_ms > REALERT_MS)) {
+        char details[100];
+        snprintf (details, sizeof(details), "SWR %.1f at %.0f W", swr, watts);
+        char swr_str[10];
+        snprintf (swr_str, sizeof(swr_str), "%.1f", swr);
+        (void) notifyAlert ("swr", alertSeverity ("swr"), details, sizeof(details), NULL, 0, NULL, swr_str, NULL);
+        Serial.printf (_FX("TxMeter: %s\n"), details);
+        alert_ms = t0;
+    }
+    swr_alerting = high;
+}
+
+/* pass back the latest forward power and SWR.
+ * return whether they are recent.
+ */
+bool getTxMeter (float &watts, float &swr_now)
+{
+    watts = fwd_watts;
+    swr_now = swr;
+    return (reading_ms != 0 && millis() - reading_ms < STALE_MS);
+}
+
+/* return whether we have any source of meter readings
+ */
+bool haveTxMeter()
+{
+    char host[NV_RIGHOST_LEN];
+    uint16_t port;
+    return (reading_ms != 0 || getRigctld (host, port));
+}
+
+/* return a full scale power of 1, 2 or 5 times a power of 10 above w, at least 10
+ */
+static float powerScale (float w)
+{
+    float scale = 10;
+    while (scale < w) {
+        if (w <= scale*2)
+            return (scale*2);
+        if (w <= scale*5)
+            return (scale*5);
+        scale *= 10;
+    }
+    return (scale);
+}
+
+/* draw one bar at y down from box top with the given fill fraction, color and peak fraction, if any,
+ * and the given value labeled at its upper right.
+ */
+static void drawMeterBar (const SBox &box, uint16_t y, float frac, uint16_t color, float peak_frac, const char *label)
+{
+    uint16_t x0 = box.x + BAR_X0;
+    uint16_t by = box.y + y;
+    uint16_t fill_w = frac > 1 ? BAR_W-2 : (uint16_t)(frac*(BAR_W-2));
+
+    // erase value label, leaving bar name, and bar interior
+    tft.fillRect (x0 + 30, by - 12, BAR_W - 30, 11, RA8875_BLACK);
+    tft.fillRect (x0+1, by+1, BAR_W-2, BAR_H-2, RA8875_BLACK);
+
+    tft.drawRect (x0, by, BAR_W, BAR_H, LABEL_COLOR);
+    if (fill_w > 0)
+        tft.fillRect (x0+1, by+1, fill_w, BAR_H-2, color);
+    if (peak_frac > 0 && peak_frac <= 1) {
+        uint16_t px = x0 + 1 + (uint16_t)(peak_frac*(BAR_W-3));
+        tft.drawLine (px, by+1, px, by+BAR_H-2, PEAK_COLOR);
+    }
+
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    tft.setTextColor (RA8875_WHITE);
+    tft.setCursor (x0 + BAR_W - getTextWidth(label), by - 11);
+    tft.print (label);
+}
+
+/* draw the meter in box: everything if all, else just the bars.
+ * always return true because there is nothing to fetch.
+ */
+bool updateTxMeter (const SBox &box, bool all)
+{
+    float watts, swr_now;
+    bool live = getTxMeter (watts, swr_now);
+    if (!live)
+        watts = swr_now = 0;
+    float peak = live && millis() - peak_ms < PEAK_MS ? peak_watts : watts;
+    float scale = powerScale (peak);
+    float alert = getSWRAlert();
+
+    if (all) {
+        prepPlotBox (box);
+
+        // title
+        selectFontStyle (LIGHT_FONT, SMALL_FONT);
+        tft.setTextColor (TITLE_COLOR);
+        const char *title = xlate ("Tx Meter");
+        tft.setCursor (box.x + (box.w - getTextWidth(title))/2, box.y + TITLE_Y0);
+        tft.print (title);
+
+        // bar names
+        selectFontStyle (LIGHT_FONT, FAST_FONT);
+        tft.setTextColor (LABEL_COLOR);
+        tft.setCursor (box.x + BAR_X0, box.y + FWD_Y0 - 11);
+        tft.print ("Fwd");
+        tft.setCursor (box.x + BAR_X0, box.y + SWR_Y0 - 11);
+        tft.print ("SWR");
+    }
+
+    char label[30];
+
+    // forward power, scaled to fit the peak
+    if (live)
+        snprintf (label, sizeof(label), "%.0f W / %.0f", watts, scale);
+    else
+        snprintf (label, sizeof(label), "%s", xlate ("idle"));
+    drawMeterBar (box, FWD_Y0, watts/scale, FWD_COLOR, peak/scale, label);
+
+    // SWR from 1 to SWR_MAX, red at or above alert level which is marked as the peak
+    if (live && swr_now >= 1)
+        snprintf (label, sizeof(label), "%.1f", swr_now);
+    else
+        snprintf (label, sizeof(label), "-");
+    bool high = alert > 0 && swr_now >= alert;
+    drawMeterBar (box, SWR_Y0, swr_now >= 1 ? (swr_now-1)/(SWR_MAX-1) : 0, high ? SWR_HI_COLOR : SWR_OK_COLOR,
+                        alert > 1 ? (alert-1)/(SWR_MAX-1) : 0, label);
+
+    if (all) {
+        selectFontStyle (LIGHT_FONT, FAST_FONT);
+        tft.setTextColor (LABEL_COLOR);
+        if (alert > 0)
+            snprintf (label, sizeof(label), xlate ("Alert at SWR %.1f"), alert);
+        else
+            snprintf (label, sizeof(label), "%s", xlate ("SWR alert off"));
+        tft.setCursor (box.x + (box.w - getTextWidth(label))/2, box.y + SWR_Y0 + BAR_H + 12);
+        tft.print (label);
+    }
+
+    return (true);
+}
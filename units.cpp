@@ -0,0 +1,195 @@
+/* units and formatting preferences shared by all displays so each is chosen once:
+ *   temperatures in C or F,
+ *   distances and speeds in km or miles, this is the same choice made by tapping the path distance;
+ *   DX spot times in UTC or DE local time;
+ *   frequencies in kHz or MHz with a chosen number of decimals.
+ * each defaults to the metric choice in Setup until changed with set_units.
+ */
+
+#include "HamClock.h"
+
+
+#define FF_MHZBIT       0x80                    // NV_FREQFMT bit set for MHz, else kHz
+#define FF_DECMASK      0x03                    // NV_FREQFMT bits with n decimals
+#define DEF_KHZDEC      1                       // default kHz decimals, below 100 MHz
+
+
+static uint8_t temp_c;                          // show temperatures in C, else F
+static uint8_t spot_local;                      // show spot times in DE local time, else UTC
+static uint8_t freq_fmt;                        // FF_MHZBIT | decimals
+
+
+
+/* read the preferences from NV once, setting any missing from useMetricUnits()
+ */
+static void loadUnits()
+{
+    static bool loaded;
+    if (loaded)
+        return;
+    loaded = true;
+
+    if (!NVReadUInt8 (NV_TEMPUNITS, &temp_c)) {
+        temp_c = useMetricUnits();
+        NVWriteUInt8 (NV_TEMPUNITS, temp_c);
+    }
+    if (!NVReadUInt8 (NV_SPOTTIME, &spot_local)) {
+        spot_local = false;
+        NVWriteUInt8 (NV_SPOTTIME, spot_local);
+    }
+    if (!NVReadUInt8 (NV_FREQFMT, &freq_fmt)) {
+        freq_fmt = DEF_KHZDEC;
+        NVWriteUInt8 (NV_FREQFMT, freq_fmt);
+    }
+}
+
+/* return whether to show temperatures in C, else F
+ */
+bool showTempC()
+{
+    loadUnits();
+    return (temp_c);
+}
+
+/* set whether to show temperatures in C, else F
+ */
+void setTempC (bool c)
+{
+    loadUnits();
+    temp_c = c;
+    NVWriteUInt8 (NV_TEMPUNITS, temp_c);
+}
+
+/* convert the given temperature in C to the preferred units
+ */
+float tempFromC (float c)
+{
+    return (showTempC() ? c : 9*c/5 + 32);
+}
+
+/* return the preferred temperature units, C or F
+ */
+char tempUnits()
+{
+    return (showTempC() ? 'C' : 'F');
+}
+
+/* set whether to show distances and speeds in km, else miles
+ */
+void setDistKm (bool km)
+{
+    show_km = km;
+    NVWriteUInt8 (NV_DIST_KM, show_km);
+}
+
+/* convert the given distance in miles to the preferred units
+ */
+float distFromMiles (float miles)
+{
+    return (show_km ? miles*1.609344F : miles);
+}
+
+/* return the preferred distance units, km or mi
+ */
+const char *distUnits()
+{
+    return (show_km ? "km" : "mi");
+}
+
+/* convert the given speed in meters per second to the preferred units
+ */
+float speedFromMps (float mps)
+{
+    return (show_km ? 3.6F*mps : 2.237F*mps);
+}
+
+/* return the preferred speed units, kph or mph
+ */
+const char *speedUnits()
+{
+    return (show_km ? "kph" : "mph");
+}
+
+/* return whether to show spot times in DE local time, else UTC
+ */
+bool showSpotLocalTime()
+{
+    loadUnits();
+    return (spot_local);
+}
+
+/* set whether to show spot times in DE local time, else UTC
+ */
+void setSpotLocalTime (bool local)
+{
+    loadUnits();
+    spot_local = local;
+    NVWriteUInt8 (NV_SPOTTIME, spot_local);
+}
+
+/* convert the given spot time as UTC HHMM to the preferred zone
+ */
+uint16_t spotTime (uint16_t uts)
+{
+    if (!showSpotLocalTime())
+        return (uts);
+    int mins = (uts/100)*60 + uts%100 + de_tz.tz_secs/60;
+    mins = ((mins % 1440) + 1440) % 1440;
+    return ((mins/60)*100 + mins%60);
+}
+
+/* return the name of the preferred spot time zone, 3 chars
+ */
+const char *spotTimeZone()
+{
+    return (showSpotLocalTime() ? "DE " : "UTC");
+}
+
+/* set frequencies to show in MHz, else kHz, with the given number of decimals, 0 .. 3.
+ * return whether decimals is in range.
+ */
+bool setFreqFormat (bool mhz, int decimals)
+{
+    if (decimals < 0 || decimals > FF_DECMASK)
+        return (false);
+    loadUnits();
+    freq_fmt = (mhz ? FF_MHZBIT : 0) | decimals;
+    NVWriteUInt8 (NV_FREQFMT, freq_fmt);
+    return (true);
+}
+
+/* pass back whether frequencies show in MHz, else kHz, and with how many decimals
+ */
+void getFreqFormat (bool &mhz, int &decimals)
+{
+    loadUnits();
+    mhz = (freq_fmt & FF_MHZBIT) != 0;
+    decimals = freq_fmt & FF_DECMASK;
+}
+
+/* return the preferred frequency units, kHz or MHz
+ */
+const char *freqUnits()
+{
+    loadUnits();
+    return ((freq_fmt & FF_MHZBIT) ? "MHz" : "kHz");
+}
+
+/* format the given frequency in kHz into buf in the preferred units, right justified in width chars.
+ * decimals are dropped as needed to fit within width. if kHz still do not fit, such as microwave spots of
+ * 10 GHz and above, show whole MHz marked with a trailing M instead.
+ */
+void formatFreq (char buf[], size_t buf_len, float kHz, int width)
+{
+    bool mhz;
+    int decimals;
+    getFreqFormat (mhz, decimals);
+
+    float f = mhz ? kHz/1000 : kHz;
+    for (; decimals >= 0; --decimals)
+        if (snprintf (buf, buf_len, "%*.*f", width, decimals, f) <= width)
+            return;
+
+    if (!mhz)
+        snprintf (buf, buf_len, "%*.0fM", width-1, kHz/1000);
+}
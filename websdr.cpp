@@ -0,0 +1,121 @@
+/* build links that open a KiwiSDR or WebSDR receiver tuned to a DX spot.
+ *
+ * the receiver is set with set_websdr as its kind and base URL, eg kiwi=http://kiwi.example.org:8073.
+ * A spot on 14074 kHz FT8 then links to:
+ *
+ *   kiwi:   http://kiwi.example.org:8073/?f=14074.00usb
+ *   websdr: http://websdr.example.org:8901/?tune=14074usb
+ *
+ * the links are shown on the dashboard spot table and as a QR code on the DX cluster pane by tapping
+ * a selected spot again.
+ */
+
+#include "HamClock.h"
+
+
+// receiver kinds, matching the NV_WEBSDR prefix
+static const char kiwi_kind[] = "kiwi";
+static const char websdr_kind[] = "websdr";
+
+static char sdr_kind[10];                       // kiwi_kind or websdr_kind, empty if none
+static char sdr_url[NV_WEBSDR_LEN];             // base URL without trailing /
+
+
+/* read the receiver from NV once, stored as "kind url"
+ */
+static void loadWebSDR()
+{
+    static bool loaded;
+    if (loaded)
+        return;
+    loaded = true;
+
+    char nv[NV_WEBSDR_LEN];
+    if (!NVReadString (NV_WEBSDR, nv)) {
+        nv[0] = '\0';
+        NVWriteString (NV_WEBSDR, nv);
+    }
+    if (sscanf (nv, "%9s %63s", sdr_kind, sdr_url) != 2) {
+        sdr_kind[0] = '\0';
+        sdr_url[0] = '\0';
+    }
+}
+
+/* set the receiver kind, kiwi or websdr, and its base URL, or kind "off" for none.
+ * return whether sensible.
+ */
+bool setWebSDR (const char *kind, const char *url)
+{
+    loadWebSDR();
+
+    if (strcmp (kind, "off") == 0) {
+        sdr_kind[0] = '\0';
+        sdr_url[0] = '\0';
+        NVWriteString (NV_WEBSDR, "");
+        return (true);
+    }
+
+    // kind and URL must fit in NV with a space between
+    size_t ul = strlen (url);
+    if ((strcmp (kind, kiwi_kind) != 0 && strcmp (kind, websdr_kind) != 0)
+                || (strncmp (url, "http://", 7) != 0 && strncmp (url, "https://", 8) != 0)
+                || strchr (url, ' ') || strlen (kind) + 1 + ul >= NV_WEBSDR_LEN)
+        return (false);
+
+    strcpy (sdr_kind, kind);
+    strcpy (sdr_url, url);
+    while (ul > 0 && sdr_url[ul-1] == '/')
+        sdr_url[--ul] = '\0';
+
+    char nv[NV_WEBSDR_LEN];
+    snprintf (nv, sizeof(nv), "%s %s", sdr_kind, sdr_url);
+    NVWriteString (NV_WEBSDR, nv);
+    return (true);
+}
+
+/* pass back the receiver kind and base URL.
+ * return whether one is set.
+ */
+bool getWebSDR (const char **kind, const char **url)
+{
+    loadWebSDR();
+    *kind = sdr_kind;
+    *url = sdr_url;
+    return (sdr_kind[0] != '\0');
+}
+
+/* return the receiver demodulator name for the given spot mode and frequency
+ */
+static const char *sdrMode (const char *mode, float kHz)
+{
+    if (strcasecmp (mode, "CW") == 0)
+        return ("cw");
+    if (strcasecmp (mode, "AM") == 0)
+        return ("am");
+    if (strcasecmp (mode, "FM") == 0)
+        return ("fm");
+    if (strcasecmp (mode, "LSB") == 0)
+        return ("lsb");
+
+    // phone without a sideband follows the usual convention, all data modes use USB
+    if (mode[0] == '\0' || strcasecmp (mode, "SSB") == 0)
+        return (kHz < 10000 ? "lsb" : "usb");
+    return ("usb");
+}
+
+/* build the link that tunes the receiver to the given spot.
+ * return false if no receiver is set.
+ */
+bool getSpotSDRLink (const DXClusterSpot &spot, char link[], size_t link_len)
+{
+    loadWebSDR();
+
+    if (strcmp (sdr_kind, kiwi_kind) == 0)
+        snprintf (link, link_len, "%s/?f=%.2f%s", sdr_url, spot.freq, sdrMode (spot.mode, spot.freq));
+    else if (strcmp (sdr_kind, websdr_kind) == 0)
+        snprintf (link, link_len, "%s/?tune=%.0f%s", sdr_url, spot.freq, sdrMode (spot.mode, spot.freq));
+    else
+        return (false);
+
+    return (true);
+}
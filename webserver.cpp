@@ -182,24 +182,16 @@ static bool getWiFiScreenCapture(WiFiClient *clientp, char *line)
 
     // send the pixels
     resetWatchdog();
-    tft.graphicsMode();
-    tft.setXY(0,0);
-    tft.writeCommand(RA8875_MRWC);
-    static bool first = true;
-    if (first) {
-        // skip first pixel first time
-        tft.readData();
-        tft.readData();
-        first = false;
-    }
+    beginScreenRead();
     uint16_t bufl = 0;
     for (uint32_t i = 0; i < npix; i++) {
         if ((i % tft.width()) == 0)
             resetWatchdog();
 
         // swap bytes
-        buf[bufl+1] = tft.readData();
-        buf[bufl+0] = tft.readData();
+        uint16_t pix16 = readScreenPixel();
+        buf[bufl+1] = pix16 >> 8;
+        buf[bufl+0] = pix16 & 0xFF;
         bufl += 2;
 
         if (bufl == sizeof(buf) || i == npix-1) {
@@ -224,6 +216,439 @@ static bool getWiFiScreenCapture(WiFiClient *clientp, char *line)
     return (true);
 }
 
+#if defined(_IS_UNIX)
+
+/* send screen capture as PNG reduced in size by the given factor.
+ * return false with reason in line if trouble.
+ */
+static bool sendScreenPNG (WiFiClient *clientp, char *line, int shrink)
+{
+    size_t n_png;
+    uint8_t *png = captureScreenPNG (&n_png, shrink);
+    if (!png) {
+        strcpy (line, _FX("No memory for screen capture"));
+        return (false);
+    }
+
+    // send the web page header
+    resetWatchdog();
+    FWIFIPRLN (*clientp, F("HTTP/1.0 200 OK"));
+    sendUserAgent (*clientp);
+    FWIFIPRLN (*clientp, F("Content-Type: image/png"));
+    FWIFIPRLN (*clientp, F("Cache-Control: no-cache"));
+    FWIFIPR (*clientp, F("Content-Length: ")); clientp->println ((int)n_png);
+    FWIFIPRLN (*clientp, F("Connection: close\r\n"));
+
+    // send the image
+    clientp->write (png, n_png);
+    free (png);
+
+    return (true);
+}
+
+/* send screen capture as PNG
+ */
+static bool getWiFiScreenCapturePNG (WiFiClient *clientp, char *line)
+{
+    return (sendScreenPNG (clientp, line, 1));
+}
+
+/* crack optional shrink=1|2|4 from the given live args, default 1.
+ * return false with reason in line if bad.
+ */
+static bool crackLiveShrink (char *line, int &shrink)
+{
+    shrink = 1;
+    char *sp = strstr (line, "shrink=");
+    if (sp) {
+        shrink = atoi (sp+7);
+        if (shrink != 1 && shrink != 2 && shrink != 4) {
+            strcpy (line, _FX("shrink must be 1, 2 or 4"));
+            return (false);
+        }
+    }
+    return (true);
+}
+
+/* send screen capture as PNG for the live web page: shrink=1|2|4, anything else is ignored such as
+ * the unique value the page adds to defeat browser caches.
+ */
+static bool getWiFiLivePNG (WiFiClient *clientp, char *line)
+{
+    int shrink;
+    if (!crackLiveShrink (line, shrink))
+        return (false);
+    return (sendScreenPNG (clientp, line, shrink));
+}
+
+/* send a web page that shows the screen and refreshes it periodically, so any browser can serve
+ * as a remote display: refresh=secs&shrink=1|2|4, both optional.
+ */
+static bool getWiFiLiveHTML (WiFiClient *clientp, char *line)
+{
+    int shrink;
+    if (!crackLiveShrink (line, shrink))
+        return (false);
+
+    int refresh = 10;
+    char *rp = strstr (line, "refresh=");
+    if (rp) {
+        refresh = atoi (rp+8);
+        if (refresh < 1 || refresh > 3600) {
+            strcpy (line, _FX("refresh must be 1 .. 3600 seconds"));
+            return (false);
+        }
+    }
+
+    resetWatchdog();
+    FWIFIPRLN (*clientp, F("HTTP/1.0 200 OK"));
+    sendUserAgent (*clientp);
+    FWIFIPRLN (*clientp, F("Content-Type: text/html; charset=us-ascii"));
+    FWIFIPRLN (*clientp, F("Connection: close\r\n"));
+
+    // load each new image off screen then swap it in so there is no flashing
+    char buf[200];
+    FWIFIPRLN (*clientp, F("<!DOCTYPE html>"));
+    FWIFIPRLN (*clientp, F("<html><head><title>HamClock</title>"));
+    FWIFIPRLN (*clientp, F("<style>body{margin:0;background:black;}img{width:100%;height:auto;display:block;}</style>"));
+    FWIFIPRLN (*clientp, F("</head><body>"));
+    snprintf (buf, sizeof(buf), _FX("<img id='screen' src='get_live.png?shrink=%d'>"), shrink);
+    clientp->println (buf);
+    FWIFIPRLN (*clientp, F("<script>"));
+    FWIFIPRLN (*clientp, F("var screen_img = document.getElementById('screen');"));
+    FWIFIPRLN (*clientp, F("function nextScreen() {"));
+    FWIFIPRLN (*clientp, F("    var img = new Image();"));
+    snprintf (buf, sizeof(buf), _FX("    img.onload = function() { screen_img.src = img.src; setTimeout(nextScreen, %d); };"),
+                                1000*refresh);
+    clientp->println (buf);
+    snprintf (buf, sizeof(buf), _FX("    img.onerror = function() { setTimeout(nextScreen, %d); };"), 1000*refresh);
+    clientp->println (buf);
+    snprintf (buf, sizeof(buf), _FX("    img.src = 'get_live.png?shrink=%d&t=' + Date.now();"), shrink);
+    clientp->println (buf);
+    FWIFIPRLN (*clientp, F("}"));
+    snprintf (buf, sizeof(buf), _FX("setTimeout(nextScreen, %d);"), 1000*refresh);
+    clientp->println (buf);
+    FWIFIPRLN (*clientp, F("</script>"));
+    FWIFIPRLN (*clientp, F("</body></html>"));
+
+    return (true);
+}
+
+/* remote command to save a screenshot now or set the period between periodic screenshots
+ */
+static bool setWiFiScreenshots (WiFiClient *clientp, char line[])
+{
+    char buf[200];
+    int mins;
+
+    if (strcmp (line, "now") == 0) {
+        if (!saveScreenshot (buf, sizeof(buf))) {
+            strncpy (line, buf, 100);
+            line[99] = '\0';
+            return (false);
+        }
+        if (clientp) {
+            startPlainText (*clientp);
+            strcat (buf, "\n");
+            clientp->print (buf);
+        }
+    } else if (sscanf (line, "every=%d", &mins) == 1) {
+        if (!setScreenshotPeriod (mins)) {
+            strcpy (line, _FX("every must be 0 .. 1440 minutes"));
+            return (false);
+        }
+        if (clientp) {
+            startPlainText (*clientp);
+            if (mins == 0)
+                snprintf (buf, sizeof(buf), _FX("periodic screenshots off\n"));
+            else
+                snprintf (buf, sizeof(buf), _FX("screenshot every %d minutes\n"), mins);
+            clientp->print (buf);
+        }
+    } else {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    return (true);
+}
+
+/* remote command to save the share conditions card and summary now
+ */
+static bool setWiFiShare (WiFiClient *clientp, char line[])
+{
+    char buf[300];
+
+    if (strcmp (line, "save") != 0) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+    if (!saveShareCard (buf, sizeof(buf))) {
+        strncpy (line, buf, 100);
+        line[99] = '\0';
+        return (false);
+    }
+    if (clientp) {
+        startPlainText (*clientp);
+        clientp->print (buf);
+        FWIFIPRLN (*clientp, F(".png and .json"));
+    }
+
+    return (true);
+}
+
+/* remote command to set the escalation policy for a critical alert kind
+ * kind=K&mins=N&chain=sink,sink,... or kind=K&mins=0 to remove
+ */
+static bool setWiFiEscalate (WiFiClient *clientp, char line[])
+{
+    char kind[20], chain[100] = "";
+    int mins;
+    char *chain_str = strstr (line, "chain=");
+    if (sscanf (line, "kind=%19[^&]&mins=%d", kind, &mins) != 2
+                        || (mins > 0 && (!chain_str || sscanf (chain_str+6, "%99[^&]", chain) != 1))) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    char ynot[100];
+    if (!setAlertEscalation (kind, mins, chain, ynot, sizeof(ynot))) {
+        strcpy (line, ynot);
+        return (false);
+    }
+
+    if (clientp) {
+        startPlainText (*clientp);
+        printAlertEscalations (*clientp, "Escalate  ");
+    }
+
+    return (true);
+}
+
+/* remote command to set the message template for an alert kind, empty for the default message.
+ * kind=K&msg=template, where { and } may also be sent as %7B and %7D
+ */
+static bool setWiFiAlertMsg (WiFiClient *clientp, char line[])
+{
+    char kind[20];
+    char *M = strstr (line, "&msg=");
+    if (sscanf (line, "kind=%19[^&]", kind) != 1 || !M) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    // decode braces IN PLACE, rest of line is the template
+    char *tmpl = M + 5;
+    char *to = tmpl;
+    for (char *from = tmpl; *from; ) {
+        if (strncasecmp (from, "%7B", 3) == 0) {
+            *to++ = '{';
+            from += 3;
+        } else if (strncasecmp (from, "%7D", 3) == 0) {
+            *to++ = '}';
+            from += 3;
+        } else
+            *to++ = *from++;
+    }
+    *to = '\0';
+
+    char ynot[100];
+    if (!setAlertTemplate (kind, tmpl, ynot, sizeof(ynot))) {
+        strcpy (line, ynot);
+        return (false);
+    }
+
+    if (clientp) {
+        startPlainText (*clientp);
+        printAlertTemplates (*clientp, "AlertMsg  ");
+    }
+
+    return (true);
+}
+
+/* remote command to set the policy of an alert sink queue when it is full
+ * sink=ws|script&policy=drop-oldest|drop-newest
+ */
+static bool setWiFiAlertQueue (WiFiClient *clientp, char line[])
+{
+    char sink[20], policy[20];
+    if (sscanf (line, "sink=%19[^&]&policy=%19[^&]", sink, policy) != 2) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    char ynot[100];
+    if (!setAlertQPolicy (sink, policy, ynot, sizeof(ynot))) {
+        strcpy (line, ynot);
+        return (false);
+    }
+
+    if (clientp) {
+        startPlainText (*clientp);
+        printAlertQueues (*clientp, "AlertQ   ");
+    }
+
+    return (true);
+}
+
+/* remote command to set the sound for an alert kind
+ * kind=K&sound=off|pattern|file.wav
+ */
+static bool setWiFiAlertSound (WiFiClient *clientp, char line[])
+{
+    char kind[20], sound[30];
+    if (sscanf (line, "kind=%19[^&]&sound=%29[^&]", kind, sound) != 2) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    char ynot[100];
+    if (!setAlertSound (kind, sound, ynot, sizeof(ynot))) {
+        strcpy (line, ynot);
+        return (false);
+    }
+
+    if (clientp) {
+        startPlainText (*clientp);
+        printAlertSounds (*clientp, "Sounds    ");
+    }
+
+    return (true);
+}
+
+/* report map layer opacities and overlay order
+ */
+static void reportMapLayers (WiFiClient *clientp)
+{
+    char buf[100];
+    int l = snprintf (buf, sizeof(buf), "MapOpac   ");
+    for (int i = 0; i < MAPL_N; i++)
+        l += snprintf (buf+l, sizeof(buf)-l, "%s%s %.0f%%", i > 0 ? ", " : "",
+                            getMapLayerName((MapLayer)i), 100*getMapLayerOpacity((MapLayer)i));
+    clientp->println (buf);
+
+    l = snprintf (buf, sizeof(buf), "MapOrder  ");
+    for (int i = 0; i < MAPL_NOVER; i++)
+        l += snprintf (buf+l, sizeof(buf)-l, "%s%s", i > 0 ? "," : "", getMapLayerName(getMapOverlay(i)));
+    clientp->println (buf);
+}
+
+/* remote command to set map layer opacities and overlay order
+ * set_maplayers?Night=pct&Grid=pct&Path=pct&Sat=pct&Symbols=pct&Aurora=pct&Order=bottom,...,top
+ * all keywords optional but require at least 1, engaged in order given.
+ */
+static bool setWiFiMapLayers (WiFiClient *clientp, char line[])
+{
+    if (line[0] == '\0') {
+        strcpy_P (line, PSTR("bad args"));
+        return (false);
+    }
+
+    for (char *kw = strtok (line, "&"); kw != NULL; kw = strtok (NULL, "&")) {
+        char *eq = strchr (kw, '=');
+        if (!eq) {
+            strcpy (line, garbcmd);
+            return (false);
+        }
+        *eq = '\0';
+        if (strcmp (kw, "Order") == 0) {
+            if (!setMapOverlayOrder (eq+1)) {
+                strcpy_P (line, PSTR("Order must list from Grid, Path, Sat, Symbols and Aurora"));
+                return (false);
+            }
+        } else {
+            char *endp;
+            int pct = strtol (eq+1, &endp, 10);
+            if (endp == eq+1 || *endp != '\0' || !setMapLayerOpacity (kw, pct)) {
+                strcpy_P (line, PSTR("unknown layer or opacity not 0 .. 100"));
+                return (false);
+            }
+        }
+    }
+
+    // ack with new state
+    if (clientp) {
+        startPlainText (*clientp);
+        reportMapLayers (clientp);
+    }
+
+    return (true);
+}
+
+#endif // _IS_UNIX
+
+/* remote command to report the QSO rates
+ */
+static bool getWiFiQSORate (WiFiClient *clientp, char *unused)
+{
+    (void) unused;
+
+    startPlainText(*clientp);
+
+    int last10, last100;
+    uint32_t session;
+    getQSORates (last10, last100, session);
+
+    char buf[50];
+    snprintf (buf, sizeof(buf), _FX("Last10    %d/h\n"), last10);
+    clientp->print (buf);
+    snprintf (buf, sizeof(buf), _FX("Last100   %d/h\n"), last100);
+    clientp->print (buf);
+    snprintf (buf, sizeof(buf), _FX("Session   %u\n"), (unsigned)session);
+    clientp->print (buf);
+
+    return (true);
+}
+
+/* send award progress, worked/confirmed overall and per band for each mode class
+ */
+static bool getWiFiAwards (WiFiClient *clientp, char *unused)
+{
+    (void) unused;
+
+    startPlainText (*clientp);
+    printAwards (*clientp);
+
+    return (true);
+}
+
+/* remote command to start a new QSO rate session
+ */
+static bool setWiFiQSORate (WiFiClient *clientp, char line[])
+{
+    if (strcmp (line, "reset") != 0) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    resetQSORate();
+
+    // ack
+    if (clientp)
+        return (getWiFiQSORate (clientp, line));
+    else
+        return (true);
+}
+
+/* print the quiet hours state, keep level and schedule
+ */
+static void reportQuietHours (WiFiClient *clientp)
+{
+    char buf[100];
+
+    snprintf (buf, sizeof(buf), _FX("Quiet     %s, keep %s\n"), quietHoursNow() ? "now" : "not now",
+                                alertSeverityName (getQuietKeep()));
+    clientp->print (buf);
+
+    for (int dow = 1; dow <= DAYSPERWEEK; dow++) {
+        uint16_t start_mins, end_mins;
+        getQuietHours (dow, start_mins, end_mins);
+        snprintf (buf, sizeof(buf), "Quiet_%s %02d:%02d %02d:%02d\n", dayShortStr(dow),
+                                start_mins/60, start_mins%60, end_mins/60, end_mins%60);
+        clientp->print (buf);
+    }
+}
+
 /* remote command to report the current stopwatch timer value, in seconds
  */
 static bool getWiFiStopwatch (WiFiClient *clientp, char *unused)
@@ -232,7 +657,7 @@ static bool getWiFiStopwatch (WiFiClient *clientp, char *unused)
 
     startPlainText(*clientp);
 
-    char buf[50];
+    char buf[100];
 
     // get current state and time
     uint32_t ms;
@@ -269,6 +694,15 @@ static bool getWiFiStopwatch (WiFiClient *clientp, char *unused)
 
     clientp->print (buf);
 
+    // report contest period
+    time_t start, end;
+    if (getContestPeriod (start, end)) {
+        snprintf (buf, sizeof(buf), _FX("Contest %04d-%02d-%02dT%02d:%02dZ %ld hours\n"), year(start),
+                month(start), day(start), hour(start), minute(start), (long)(end-start)/3600);
+        clientp->print (buf);
+    } else
+        FWIFIPRLN (*clientp, F("Contest none"));
+
     return (true);
 }
 
@@ -422,13 +856,13 @@ static bool getWiFiDEDXInfo_helper (WiFiClient *clientp, char *unused, bool want
     StackMalloc wxi_mem(sizeof(WXInfo));
     WXInfo *wip = (WXInfo *) wxi_mem.getMem();
     if (getCurrentWX (ll, want_de, wip, buf)) {
-        float x = useMetricUnits() ? wip->temperature_c : 9*wip->temperature_c/5+32;
-        snprintf (buf, sizeof(buf), _FX("%sWxTemp     %.1f %c\n"), prefix, x, useMetricUnits() ? 'C' : 'F');
+        float x = tempFromC (wip->temperature_c);
+        snprintf (buf, sizeof(buf), _FX("%sWxTemp     %.1f %c\n"), prefix, x, tempUnits());
         clientp->print(buf);
         snprintf (buf, sizeof(buf), _FX("%sWxHumidity %.1f %%\n"), prefix, wip->humidity_percent);
         clientp->print(buf);
-        x = (useMetricUnits() ? 3.6 : 2.237) * wip->wind_speed_mps; // kph or mph
-        snprintf (buf, sizeof(buf), _FX("%sWxWindSpd  %.1f %s\n"), prefix, x, useMetricUnits()?"kph":"mph");
+        x = speedFromMps (wip->wind_speed_mps);
+        snprintf (buf, sizeof(buf), _FX("%sWxWindSpd  %.1f %s\n"), prefix, x, speedUnits());
         clientp->print(buf);
         snprintf (buf, sizeof(buf), _FX("%sWxWindDir  %s\n"), prefix, wip->wind_dir_name);
         clientp->print(buf);
@@ -480,12 +914,12 @@ static bool getWiFiDXSpots (WiFiClient *clientp, char *line)
     startPlainText (*clientp);
 
     // print each row, similar to drawDXSpot()
-    FWIFIPR (*clientp, F("#  kHz   Call        UTC  Grid    Lat     Lng       Dist   Bear\n"));
+    FWIFIPR (*clientp, F("#  kHz   Call        UTC  Grid    Lat     Lng       Dist   Bear   Spotter     Mode    Entity Band\n"));
     float sdelat = sinf(de_ll.lat);
     float cdelat = cosf(de_ll.lat);
     for (uint8_t i = 0; i < nspots; i++) {
         DXClusterSpot *sp = &spots[i];
-        char line[100];
+        char line[150];
 
         // pretty freq, fixed 8 chars
         const char *f_fmt = sp->freq < 1e6 ? "%8.1f" : "%8.0f";
@@ -507,10 +941,28 @@ static bool getWiFiDXSpots (WiFiClient *clientp, char *line)
             dist *= 1.609344F;                          // miles -> km
 
         // print together
-        snprintf (line+8, sizeof(line)-8, _FX(" %-*s %04u %s   %6.2f %7.2f   %6.0f   %4.0f\n"),
-                MAX_SPOTCALL_LEN-1, sp->call, sp->uts, sp->grid, sp->ll.lat_d, sp->ll.lng_d, dist, bear);
+        snprintf (line+8, sizeof(line)-8, _FX(" %-*s %04u %s   %6.2f %7.2f   %6.0f   %4.0f   %-*s %-*s %-6s %-5s%s"),
+                MAX_SPOTCALL_LEN-1, sp->call, sp->uts, sp->grid, sp->ll.lat_d, sp->ll.lng_d, dist, bear,
+                MAX_SPOTCALL_LEN-1, sp->spotter, MAX_SPOTMODE_LEN-1, sp->mode, sp->entity,
+                getHamBandName (findHamBand (sp->freq)), sp->watched ? " *" : "");
         clientp->print(line);
-    }
+        if (sp->qsl_users & QSLU_LOTW)
+            clientp->print (" LoTW");
+        if (sp->qsl_users & QSLU_EQSL)
+            clientp->print (" eQSL");
+        const char *label = getSpotSourceLabel (sp->source);
+        if (label) {
+            clientp->print (" via ");
+            clientp->print (label);
+        }
+
+        // all spotters if merged
+        if (sp->n_spotters > 1) {
+            clientp->print (" de ");
+            clientp->print (sp->spotters);
+        }
+        clientp->println();
+    }
 
     // ok
     return (true);
@@ -549,6 +1001,15 @@ static bool getWiFiConfig (WiFiClient *clientp, char *unused)
     else
         FWIFIPRLN (*clientp, F("Mercator"));
 
+    // report mercator center
+    snprintf (buf, sizeof(buf), _FX("MapCenter %s at %d\n"), getMapCenterModeName(), getCenterLng());
+    clientp->print (buf);
+
+    #if defined(_IS_UNIX)
+        // report map layers
+        reportMapLayers (clientp);
+    #endif // _IS_UNIX
+
     // report grid overlay
     FWIFIPR (*clientp, F("MapGrid   "));
     switch (mapgrid_choice) {
@@ -612,10 +1073,22 @@ static bool getWiFiConfig (WiFiClient *clientp, char *unused)
                 break;
         }
 
+        // display wake on alerts
+        FWIFIPR (*clientp, F("DpyWake   "));
+        clientp->println (getDisplayWakeName());
+
     } else
         FWIFIPRLN (*clientp, not_sup);
 
 
+    // report alert quiet hours and escalations
+    reportQuietHours (clientp);
+#if defined(_IS_UNIX)
+    printAlertEscalations (*clientp, "Escalate  ");
+    printAlertSounds (*clientp, "Sounds    ");
+    printAlertTemplates (*clientp, "AlertMsg  ");
+#endif // _IS_UNIX
+
     // report alarm
     FWIFIPR (*clientp, F("Alarm     "));
     AlarmState as;
@@ -634,6 +1107,65 @@ static bool getWiFiConfig (WiFiClient *clientp, char *unused)
     }
     clientp->print (buf);
 
+    // report alert style
+    FWIFIPR (*clientp, F("Alerts    "));
+    clientp->println (getAlertStyleName());
+
+    // report how scheduled events are shown
+    snprintf (buf, sizeof(buf), _FX("Events    %s time, %d hour\n"), getEventTZName(), getEvent12Hr() ? 12 : 24);
+    clientp->print (buf);
+
+    // report which sat passes sound the rise alarm
+    FWIFIPR (*clientp, F("SatAlarm  "));
+    if (getSatVisAlert())
+        FWIFIPRLN (*clientp, F("Visible passes only"));
+    else
+        FWIFIPRLN (*clientp, F("All passes"));
+
+    // report aurora alert and where DE stands
+    FWIFIPR (*clientp, F("Aurora    "));
+    char where[24], basis[50];
+    bool visible;
+    if (getAuroraView (visible, where, sizeof(where), basis, sizeof(basis)))
+        snprintf (buf, sizeof(buf), _FX("alert %s, %s%s%s\n"), getAuroraAlert() ? "on" : "off", basis,
+                                visible ? ", visible " : "", visible ? where : "");
+    else
+        snprintf (buf, sizeof(buf), _FX("alert %s, Kp and OVATION not known\n"), getAuroraAlert() ? "on" : "off");
+    clientp->print (buf);
+
+    // report local K source and latest value
+    FWIFIPR (*clientp, F("LocalK    "));
+    float local_k;
+    time_t local_age;
+    const char *station;
+    const char *lk_url = getLocalKURL();
+    if (getLocalK (local_k, local_age, &station))
+        snprintf (buf, sizeof(buf), _FX("%.0f from %s %ld min ago, url "), local_k, station, (long)local_age/60);
+    else
+        snprintf (buf, sizeof(buf), _FX("none recent, url "));
+    clientp->print (buf);
+    clientp->println (lk_url[0] ? lk_url : "none");
+
+    // report DX cluster spot table order and watched calls
+    FWIFIPR (*clientp, F("DXSort    "));
+    clientp->println (getDXClusterSortName());
+    const char *watch_calls = getDXClusterWatchCalls();
+    FWIFIPR (*clientp, F("DXWatch   "));
+    clientp->println (watch_calls[0] ? watch_calls : "none");
+    const char *alert_filter = getDXAlertFilter();
+    FWIFIPR (*clientp, F("DXFilter  "));
+    clientp->println (alert_filter[0] ? alert_filter : "none");
+#if defined(_IS_UNIX)
+    printQSLUsers (*clientp, "QSLUsers  ");
+    printSpotSources (*clientp, "SpotSrcs  ");
+#endif // _IS_UNIX
+
+    // report band plan choice
+    int bp_region, bp_band;
+    getBandPlan (bp_region, bp_band);
+    snprintf (buf, sizeof(buf), _FX("BandPlan  Region %d, %d m\n"), bp_region, bp_band);
+    clientp->print (buf);
+
 
     // time source
     FWIFIPR (*clientp, F("TimeFrom  "));
@@ -689,7 +1221,10 @@ static bool getWiFiConfig (WiFiClient *clientp, char *unused)
     // report dxcluster state
     FWIFIPR (*clientp, F("DXCluster "));
     if (useDXCluster()) {
-        snprintf (buf, sizeof(buf), _FX("%s:%d %sconnected\n"), getDXClusterHost(), getDXClusterPort(),
+        const char *dxhost;
+        int dxport;
+        getDXClusterHostInUse (&dxhost, &dxport);
+        snprintf (buf, sizeof(buf), _FX("%s:%d %sconnected\n"), dxhost, dxport,
                                         isDXClusterConnected() ? "" : "dis");
         clientp->print (buf);
     } else
@@ -701,6 +1236,12 @@ static bool getWiFiConfig (WiFiClient *clientp, char *unused)
         FWIFIPRLN (*clientp, F("metric"));
     else
         FWIFIPRLN (*clientp, F("imperial"));
+    bool mhz;
+    int decimals;
+    getFreqFormat (mhz, decimals);
+    snprintf (buf, sizeof(buf), _FX("UnitPrefs temp=%c dist=%s time=%s freq=%s decimals=%d\n"), tempUnits(),
+                    distUnits(), showSpotLocalTime() ? "DE" : "UTC", freqUnits(), decimals);
+    clientp->print (buf);
 
     // report BME info
     FWIFIPR (*clientp, F("BME280    "));
@@ -752,6 +1293,96 @@ static bool getWiFiConfig (WiFiClient *clientp, char *unused)
         FWIFIPRLN (*clientp, not_sup);
     #endif // _SUPPORT_GPIO
 
+    // report rigctld and transmit time
+    FWIFIPR (*clientp, F("Rigctld   "));
+    char rig_host[NV_RIGHOST_LEN];
+    uint16_t rig_port;
+    if (getRigctld (rig_host, rig_port)) {
+        snprintf (buf, sizeof(buf), _FX("%s:%d PTT %s\n"), rig_host, rig_port, getRigPTT() ? "on" : "off");
+        clientp->print (buf);
+    } else
+        FWIFIPRLN (*clientp, F("off"));
+    uint32_t tx_secs;
+    int duty;
+    getTransmitTime (tx_secs, duty);
+    snprintf (buf, sizeof(buf), _FX("TxTime    %02u:%02u:%02u, %d%% of last 10 mins\n"),
+                (unsigned)(tx_secs/3600), (unsigned)((tx_secs/60)%60), (unsigned)(tx_secs%60), duty);
+    clientp->print (buf);
+
+    // report doppler source
+    FWIFIPR (*clientp, F("Doppler   "));
+    uint32_t dop_hz;
+    uint16_t dop_port;
+    char dop_push[NV_DOPPLERPUSH_LEN];
+    if (getDoppler (dop_hz, dop_port, dop_push)) {
+        snprintf (buf, sizeof(buf), _FX("%.6f MHz port %d push %s\n"), dop_hz*1e-6, dop_port,
+                                dop_push[0] ? dop_push : "none");
+        clientp->print (buf);
+    } else
+        FWIFIPRLN (*clientp, F("off"));
+
+    // report spot receiver links
+    FWIFIPR (*clientp, F("WebSDR    "));
+    const char *sdr_kind, *sdr_url;
+    if (getWebSDR (&sdr_kind, &sdr_url)) {
+        snprintf (buf, sizeof(buf), _FX("%s %s\n"), sdr_kind, sdr_url);
+        clientp->print (buf);
+    } else
+        FWIFIPRLN (*clientp, F("off"));
+
+    // report spot aging
+    FWIFIPR (*clientp, F("SpotAge   "));
+    if (getSpotAge()) {
+        snprintf (buf, sizeof(buf), _FX("%d mins\n"), getSpotAge());
+        clientp->print (buf);
+    } else
+        FWIFIPRLN (*clientp, F("off"));
+
+    // report spot merging
+    FWIFIPR (*clientp, F("SpotMerge "));
+    if (getSpotMerge()) {
+        snprintf (buf, sizeof(buf), _FX("%d mins\n"), getSpotMerge());
+        clientp->print (buf);
+    } else
+        FWIFIPRLN (*clientp, F("off"));
+
+    // report offline mode
+    FWIFIPR (*clientp, F("Offline   "));
+    bool off_forced;
+    time_t off_since;
+    if (getOfflineMode (off_forced, off_since)) {
+        snprintf (buf, sizeof(buf), _FX("yes, %s, for %ld s\n"), off_forced ? "forced" : "auto",
+                                (long)(now() - off_since));
+        clientp->print (buf);
+    } else
+        FWIFIPRLN (*clientp, off_forced ? F("no, forced") : F("no, auto"));
+
+    // report network monitor state
+    FWIFIPR (*clientp, F("Network   "));
+    clientp->println (getNetStateName (getNetState()));
+
+    // report next solar eclipse and how far ahead it is shown on the map
+    FWIFIPR (*clientp, F("Eclipse   "));
+    const char *ecl_kind;
+    time_t ecl_t;
+    if (getNextEclipse (&ecl_kind, ecl_t)) {
+        char ecl_cd[20];
+        formatEclipseCountdown (ecl_t, ecl_cd, sizeof(ecl_cd));
+        snprintf (buf, sizeof(buf), _FX("%s %04d-%02d-%02d %02d:%02dZ in %s, "), ecl_kind, year(ecl_t),
+                        month(ecl_t), day(ecl_t), hour(ecl_t), minute(ecl_t), ecl_cd);
+        clientp->print (buf);
+    } else
+        FWIFIPR (*clientp, F("none known, "));
+    if (getEclipseDays()) {
+        snprintf (buf, sizeof(buf), _FX("map %d days ahead\n"), getEclipseDays());
+        clientp->print (buf);
+    } else
+        FWIFIPRLN (*clientp, F("map off"));
+
+    // report on-screen language
+    FWIFIPR (*clientp, F("Language  "));
+    clientp->println (getLanguage());
+
     // report photosensor info
     FWIFIPR (*clientp, F("Photocell "));
     #if defined(_SUPPORT_PHOT)
@@ -800,12 +1431,21 @@ static bool getWiFiSatellite (WiFiClient *clientp, char *unused)
     }
 
     FWIFIPR (*clientp, F("Name  ")); clientp->println (name);
+    SatConfig sc;
+    getSatConfig (name, sc);
+    FWIFIPR (*clientp, F("Mode  ")); clientp->println (getSatModeName (sc.mode));
+    FWIFIPR (*clientp, F("MinEl ")); clientp->print (sc.min_el); FWIFIPRLN(*clientp, F(" deg"));
     FWIFIPR (*clientp, F("Alt   ")); clientp->print (el); FWIFIPRLN(*clientp, F(" deg"));
     FWIFIPR (*clientp, F("Az    ")); clientp->print (az); FWIFIPRLN(*clientp, F(" deg"));
-    FWIFIPR (*clientp, F("Range ")); clientp->print (range); FWIFIPRLN(*clientp, F(" km"));
+    FWIFIPR (*clientp, F("Range ")); clientp->print (distFromMiles(range/1.609344F));
+    clientp->print (' '); clientp->println (distUnits());
     FWIFIPR (*clientp, F("Rate  ")); clientp->print (rate); FWIFIPRLN(*clientp, F(" m/s"));
     FWIFIPR (*clientp, F("144MHzDoppler ")); clientp->print (-rate*144000/3e8); FWIFIPRLN(*clientp,F(" kHz"));
     FWIFIPR (*clientp, F("440MHzDoppler ")); clientp->print (-rate*440000/3e8); FWIFIPRLN(*clientp,F(" kHz"));
+    uint32_t dop_hz;
+    if (getDopplerHz (dop_hz)) {
+        FWIFIPR (*clientp, F("DownlinkHz ")); clientp->println (dop_hz);
+    }
 
     // add table of next several events, if any
     time_t *rises, *sets;
@@ -814,39 +1454,46 @@ static bool getWiFiSatellite (WiFiClient *clientp, char *unused)
         FWIFIPR (*clientp, F("No rise or set\n"));
     } else {
         // print heading
-        FWIFIPR (*clientp, F("  Upcoming DE Passes\n"));
-        FWIFIPR (*clientp, F("Day  Rise    Set    Up\n"));
+        FWIFIPR (*clientp, F("  Upcoming DE Passes, "));
+        clientp->print (getEventTZName());
+        FWIFIPR (*clientp, F(" time\n"));
+        FWIFIPR (*clientp, F("Day  Rise    Set    Up     Visible\n"));
         // snprintf (line, sizeof(line), "%.3s  %02dh%02d  %02dh%02d  %02d:%02d\n"
 
         // print table
         for (int i = 0; i < n_times; i++) {
             char line[60];
 
-            // DE timezone
-            time_t rt = rises[i] + de_tz.tz_secs;
-            time_t st = sets[i] + de_tz.tz_secs;
-            int up = st - rt;
+            // event timezone
+            time_t rt = eventTime (rises[i]);
+            time_t st = eventTime (sets[i]);
+            int up = sets[i] - rises[i];
+            char rt_hm[10], st_hm[10];
+            formatEventHM (rt, rt_hm, sizeof(rt_hm));
+            formatEventHM (st, st_hm, sizeof(st_hm));
 
             // detect crossing midnight by comparing weekday
             int rt_wd = weekday(rt);
             int st_wd = weekday(st);
 
             // start with rise day and time for sure
-            size_t l = snprintf (line, sizeof(line), "%.3s  %02dh%02d", dayShortStr(rt_wd),
-                                                                                hour(rt), minute(rt));
+            size_t l = snprintf (line, sizeof(line), "%.3s  %s", dayShortStr(rt_wd), rt_hm);
 
             // if set time is tomorrow start new line with set day and blank rise
             if (rt_wd != st_wd)
-                l += snprintf (line+l, sizeof(line)-l, "\n%s  %s", dayShortStr(st_wd), "     ");
+                l += snprintf (line+l, sizeof(line)-l, "\n%s  %*s", dayShortStr(st_wd), (int)strlen(rt_hm), "");
 
             // show set time
-            l += snprintf (line+l, sizeof(line)-l, "  %02dh%02d  ", hour(st), minute(st));
+            l += snprintf (line+l, sizeof(line)-l, "  %s  ", st_hm);
 
             // show up time, beware longer than 1 hour (moon!)
             if (up >= 3600)
-                l += snprintf (line+l, sizeof(line)-l, "%02dh%02d\n", up/3600, (up-3600*(up/3600))/60);
+                l += snprintf (line+l, sizeof(line)-l, "%02dh%02d", up/3600, (up-3600*(up/3600))/60);
             else
-                l += snprintf (line+l, sizeof(line)-l, "%02d:%02d\n", up/60, up-60*(up/60));
+                l += snprintf (line+l, sizeof(line)-l, "%02d:%02d", up/60, up-60*(up/60));
+
+            // mark passes that can be seen
+            l += snprintf (line+l, sizeof(line)-l, "%s\n", isSatPassVisible(rises[i],sets[i]) ? "  *" : "");
 
             // done!
             clientp->print (line);
@@ -861,6 +1508,18 @@ static bool getWiFiSatellite (WiFiClient *clientp, char *unused)
 }
 
 
+/* report the settings of each satellite that has any
+ */
+static bool getWiFiSatConfig (WiFiClient *clientp, char *unused)
+{
+    (void) unused;
+
+    startPlainText (*clientp);
+    printSatConfigs (*clientp);
+
+    return (true);
+}
+
 /* report all available satellites.
  */
 static bool getWiFiAllSatellites (WiFiClient *clientp, char *line)
@@ -946,6 +1605,35 @@ static char *ageStr (long secs, char *str)
     return (str);
 }
 
+/* send the current band recommendations to client, best first, eg for automatic antenna switching
+ */
+static bool getWiFiBandRec (WiFiClient *clientp, char *unused)
+{
+    (void) unused;
+
+    // send html header
+    startPlainText(*clientp);
+
+    // collect info
+    BandRec recs[PROP_MAP_N];
+    float muf;
+    int n_recs = getBandRecommendations (recs, PROP_MAP_N, muf);
+
+    // send
+    char buf[100];
+    snprintf (buf, sizeof(buf), _FX("MUF %.1f MHz %s\n"), fabsf(muf), muf < 0 ? "estimated" : "corrected");
+    clientp->print (buf);
+    clientp->print (F("Band Score Heading    Confidence\n"));
+    for (int i = 0; i < n_recs; i++) {
+        snprintf (buf, sizeof(buf), _FX("%3dm  %4d  %4d %-2s  %4d\n"), recs[i].band, recs[i].score,
+                            recs[i].heading, heading2Compass(recs[i].heading), recs[i].confidence);
+        clientp->print (buf);
+    }
+
+    // ok
+    return (true);
+}
+
 /* send the current space weather stats to client
  */
 static bool getWiFiSpaceWx (WiFiClient *clientp, char *unused)
@@ -956,12 +1644,8 @@ static bool getWiFiSpaceWx (WiFiClient *clientp, char *unused)
     startPlainText(*clientp);
 
     // collect info
-    SPWxValue ssn, flux, kp, swind, drap;
-    NOAASpaceWx noaaspw;
-    float path[PROP_MAP_N];
-    char xray[10];
-    time_t noaaspw_age, xray_age, path_age;
-    getSpaceWeather (ssn, flux, kp, swind, drap, noaaspw, noaaspw_age, xray, xray_age, path, path_age);
+    SpaceWeather spw;
+    getSpaceWeather (spw);
 
     // send values and ages
     char buf[100];
@@ -970,37 +1654,90 @@ static bool getWiFiSpaceWx (WiFiClient *clientp, char *unused)
     clientp->print (F(" Datum   Value    Age\n"));
     clientp->print (F("-------- -----  -------\n"));
 
-    snprintf (buf, sizeof(buf), _FX("SSN      %5.1f  %s\n"), ssn.value, ageStr(ssn.age, age));
+    snprintf (buf, sizeof(buf), _FX("SSN      %5.1f  %s\n"), spw.ssn.value, ageStr(spw.ssn.age, age));
+    clientp->print (buf);
+
+    snprintf (buf, sizeof(buf), _FX("KP        %4.0f  %s\n"), spw.kp.value, ageStr(spw.kp.age, age));
     clientp->print (buf);
 
-    snprintf (buf, sizeof(buf), _FX("KP        %4.0f  %s\n"), kp.value, ageStr(kp.age, age));
+    float local_k;
+    time_t local_age;
+    const char *station;
+    if (getLocalK (local_k, local_age, &station)) {
+        snprintf (buf, sizeof(buf), _FX("LOCALK    %4.0f  %s\n"), local_k, ageStr(local_age, age));
+        clientp->print (buf);
+    }
+
+    snprintf (buf, sizeof(buf), _FX("FLUX     %5.1f  %s\n"), spw.flux.value, ageStr(spw.flux.age, age));
     clientp->print (buf);
 
-    snprintf (buf, sizeof(buf), _FX("FLUX     %5.1f  %s\n"), flux.value, ageStr(flux.age, age));
+    snprintf (buf, sizeof(buf), _FX("XRAY      %4s  %s\n"), spw.xray_class, ageStr(spw.xray.age, age));
     clientp->print (buf);
 
-    snprintf (buf, sizeof(buf), _FX("XRAY      %4s  %s\n"), xray, ageStr(xray_age, age));
+    snprintf (buf, sizeof(buf), _FX("SOLWIND   %4.1f  %s\n"), spw.swind.value, ageStr(spw.swind.age, age));
     clientp->print (buf);
 
-    snprintf (buf, sizeof(buf), _FX("SOLWIND   %4.1f  %s\n"), swind.value, ageStr(swind.age, age));
+    snprintf (buf, sizeof(buf), _FX("DRAP      %4.1f  %s\n"), spw.drap.value, ageStr(spw.drap.age, age));
     clientp->print (buf);
 
-    snprintf (buf, sizeof(buf), _FX("DRAP      %4.1f  %s\n"), drap.value, ageStr(drap.age, age));
+    snprintf (buf, sizeof(buf), _FX("PROTONS  %5.1f  %s\n"), spw.protons.value, ageStr(spw.protons.age, age));
     clientp->print (buf);
 
     for (int i = 0; i < PROP_MAP_N; i++) {
         int band = propMap2Band ((PropMapSetting)i);
         // match format in plotBandConditions()
-        snprintf (buf, sizeof(buf), _FX("DEDX_%02dm  %4.0f  %s\n"), band, 99*path[i], ageStr(path_age, age));
+        snprintf (buf, sizeof(buf), _FX("DEDX_%02dm  %4.0f  %s\n"), band, 99*spw.pathrel[i], ageStr(spw.pathrel_age, age));
         clientp->print (buf);
     }
 
     for (int i = 0; i < N_NOAASW_C; i++) {
         for (int j = 0; j < N_NOAASW_V; j++) {
-            snprintf (buf, sizeof(buf), _FX("NSPW_%c%d   %4d  %s\n"), noaaspw.cat[i], j, noaaspw.val[i][j],
-                    ageStr(noaaspw_age, age));
+            snprintf (buf, sizeof(buf), _FX("NSPW_%c%d   %4d  %s\n"), spw.noaaspw.cat[i], j, spw.noaaspw.val[i][j],
+                    ageStr(spw.noaaspw_age, age));
+            clientp->print (buf);
+        }
+    }
+
+    // largest recent flare and soonest CME, if any
+    if (spw.flare_class[0]) {
+        snprintf (buf, sizeof(buf), _FX("FLARE     %4s  %s\n"), spw.flare_class, ageStr(spw.flares_age, age));
+        clientp->print (buf);
+    }
+    if (spw.cme_arrival) {
+        snprintf (buf, sizeof(buf), _FX("CME_HRS  %5.1f  %s\n"), (spw.cme_arrival - now())/3600.0F,
+                                                ageStr(spw.cmes_age, age));
+        clientp->print (buf);
+        snprintf (buf, sizeof(buf), _FX("CME_KMS   %4d  %s\n"), spw.cme_speed, age);
+        clientp->print (buf);
+    }
+
+    // latest DX cluster WWV or WCY announcement, if any
+    const ClusterSpaceWx &cspw = spw.cluster;
+    if (cspw.t) {
+        ageStr (now() - cspw.t, age);
+        if (cspw.sfi >= 0) {
+            snprintf (buf, sizeof(buf), _FX("CL_FLUX  %5.1f  %s\n"), cspw.sfi, age);
+            clientp->print (buf);
+        }
+        if (cspw.a >= 0) {
+            snprintf (buf, sizeof(buf), _FX("CL_A      %4.0f  %s\n"), cspw.a, age);
+            clientp->print (buf);
+        }
+        if (cspw.k >= 0) {
+            snprintf (buf, sizeof(buf), _FX("CL_K      %4.0f  %s\n"), cspw.k, age);
+            clientp->print (buf);
+        }
+        if (cspw.ssn >= 0) {
+            snprintf (buf, sizeof(buf), _FX("CL_SSN   %5.1f  %s\n"), cspw.ssn, age);
+            clientp->print (buf);
+        }
+        if (cspw.aurora[0]) {
+            snprintf (buf, sizeof(buf), _FX("CL_AUR    %4s  %s\n"), cspw.aurora, age);
             clientp->print (buf);
         }
+        snprintf (buf, sizeof(buf), _FX("CL_NODE  %s%s\n"), cspw.node,
+                                cspw.differs ? _FX(" differs from backend") : "");
+        clientp->print (buf);
     }
 
     // ok
@@ -1008,6 +1745,79 @@ static bool getWiFiSpaceWx (WiFiClient *clientp, char *unused)
     return (true);
 }
 
+#if defined(_IS_UNIX)
+/* send the local history of one space weather value: kind=SSN|SFI|Kp|SolarWind|XRay|DRAP&days=N
+ * days is optional and defaults to all retained.
+ */
+static bool getWiFiSpaceWxHistory (WiFiClient *clientp, char *line)
+{
+    // crack kind
+    char name[20];
+    SWxHistKind kind;
+    char *kp = strstr (line, "kind=");
+    if (!kp || sscanf (kp+5, "%19[^&]", name) != 1 || !findSWxHistKind (name, kind)) {
+        strcpy (line, _FX("kind must be SSN, SFI, Kp, SolarWind, XRay or DRAP"));
+        return (false);
+    }
+
+    // crack optional days
+    int days = getSWxHistoryDays();
+    char *dp = strstr (line, "days=");
+    if (dp) {
+        days = atoi (dp+5);
+        if (days < 1) {
+            strcpy (line, _FX("days must be at least 1"));
+            return (false);
+        }
+    }
+
+    // collect
+    time_t *times = NULL;
+    float *values = NULL;
+    int n = getSWxHistory (kind, now() - (time_t)days*SECSPERDAY, &times, &values);
+
+    // send, one per line
+    startPlainText(*clientp);
+    char buf[100];
+    snprintf (buf, sizeof(buf), _FX("# %s history for the last %d days: UNIX_time ISO_time value\n"),
+                                getSWxHistName(kind), days);
+    clientp->print (buf);
+    for (int i = 0; i < n; i++) {
+        time_t t = times[i];
+        snprintf (buf, sizeof(buf), "%ld %04d-%02d-%02dT%02d:%02d:%02dZ %g\n", (long)t,
+                        year(t), month(t), day(t), hour(t), minute(t), second(t), values[i]);
+        clientp->print (buf);
+    }
+
+    if (n > 0) {
+        free (times);
+        free (values);
+    }
+
+    return (true);
+}
+
+/* set how many days of space weather history to keep: days=N, 0 to stop recording
+ */
+static bool setWiFiSpaceWxHistory (WiFiClient *clientp, char line[])
+{
+    int days;
+    if (sscanf (line, "days=%d", &days) != 1 || !setSWxHistoryDays (days)) {
+        strcpy (line, _FX("days must be 0 .. 366"));
+        return (false);
+    }
+
+    if (clientp) {
+        startPlainText (*clientp);
+        char buf[50];
+        snprintf (buf, sizeof(buf), _FX("keeping %d days of space weather history\n"), days);
+        clientp->print (buf);
+    }
+
+    return (true);
+}
+#endif // defined(_IS_UNIX)
+
 
 
 /* send some misc system info
@@ -1056,6 +1866,11 @@ static bool getWiFiSys (WiFiClient *clientp, char *unused)
         FWIFIPR (*clientp, F("UpTime   ")); clientp->print (buf);
     }
 
+#if defined(_IS_UNIX)
+    // show alert sink queues
+    printAlertQueues (*clientp, "AlertQ   ");
+#endif // _IS_UNIX
+
     // show NTP servers
     const NTPServer *ntp_list;
     int n_ntp = getNTPServers (&ntp_list);
@@ -1095,6 +1910,85 @@ static bool getWiFiSys (WiFiClient *clientp, char *unused)
 }
 
 
+/* send the refresh interval of each network data source, how long ago it was last refreshed and how
+ * long until it is due again. sources not shown in any pane are never refreshed.
+ */
+static bool getWiFiRefresh (WiFiClient *clientp, char *unused)
+{
+    (void) unused;
+    char buf[100];
+
+    // send html header
+    startPlainText(*clientp);
+
+    // get list
+    const RefreshSource *rs_list;
+    int n_rs = getRefreshSources (&rs_list);
+
+    // send
+    resetWatchdog();
+    time_t t0 = now();
+    clientp->print (F("Source     Interval  Default  Minimum      Age  Remaining  Fails\n"));
+    for (int i = 0; i < n_rs; i++) {
+        const RefreshSource &rs = rs_list[i];
+        int bl = snprintf (buf, sizeof(buf), "%-10s %8d %8d %8d", rs.name, rs.secs, rs.def_secs, rs.min_secs);
+        if (rs.last_ok) {
+            long age = t0 - rs.last_ok;
+            long remaining = rs.secs - age;
+            snprintf (buf+bl, sizeof(buf)-bl, " %8ld %10ld %6d\n", age, remaining > 0 ? remaining : 0L,
+                                                rs.n_fails);
+        } else
+            snprintf (buf+bl, sizeof(buf)-bl, " %8s %10s %6d\n", "-", "-", rs.n_fails);
+        clientp->print (buf);
+    }
+
+    // most recent failure of each, if any
+    clientp->print (F("\nSource     Failed ago  Kind     HTTP  Retry  Page and reason\n"));
+    for (int i = 0; i < n_rs; i++) {
+        const RefreshSource &rs = rs_list[i];
+        const FetchError &fe = rs.err;
+        if (!fe.t)
+            continue;
+        char status[10] = "-";
+        if (fe.http_status)
+            snprintf (status, sizeof(status), "%d", fe.http_status);
+        snprintf (buf, sizeof(buf), "%-10s %10ld  %-7s %5s  %-5s  ", rs.name, (long)(t0 - fe.t),
+                        fetchErrorName (fe.kind), status, fe.retryable ? "yes" : "no");
+        clientp->print (buf);
+        clientp->print (fe.page[0] ? fe.page : "-");
+        if (fe.detail[0]) {
+            clientp->print (": ");
+            clientp->print (fe.detail);
+        }
+        clientp->print ("\n");
+    }
+
+    return (true);
+}
+
+/* send the liveness checks, with status 503 if any fail so supervisors and load balancers can tell.
+ */
+static bool getWiFiHealth (WiFiClient *clientp, char *unused)
+{
+    (void) unused;
+
+    char report[300];
+    bool ok = getHealth (report, sizeof(report));
+
+    resetWatchdog();
+    if (ok)
+        FWIFIPRLN (*clientp, F("HTTP/1.0 200 OK"));
+    else
+        FWIFIPRLN (*clientp, F("HTTP/1.0 503 Service Unavailable"));
+    sendUserAgent (*clientp);
+    FWIFIPRLN (*clientp, F("Content-Type: text/plain; charset=us-ascii"));
+    FWIFIPRLN (*clientp, F("Connection: close\r\n"));             // include extra blank line
+
+    clientp->print (report);
+
+    return (true);
+}
+
 /* send current clock time
  */
 static bool getWiFiTime (WiFiClient *clientp, char *unused)
@@ -1214,126 +2108,893 @@ static bool setWiFiTitle (WiFiClient *clientp, char line[])
         }
     }
 
-    // all good: update definitions
-    if (msg) {
-        free (cs_info.call);
-        cs_info.call = strdup(msg);
-    }
-    if (fg)
-        cs_info.fg_color = fg_c;
-    if (bg) {
-        if (rainbow)
-            cs_info.bg_rainbow = 1;
-        else {
-            cs_info.bg_color = bg_c;
-            cs_info.bg_rainbow = 0;
+    // all good: update definitions
+    if (msg) {
+        free (cs_info.call);
+        cs_info.call = strdup(msg);
+    }
+    if (fg)
+        cs_info.fg_color = fg_c;
+    if (bg) {
+        if (rainbow)
+            cs_info.bg_rainbow = 1;
+        else {
+            cs_info.bg_color = bg_c;
+            cs_info.bg_rainbow = 0;
+        }
+    }
+
+    // or restore default if no args
+    if (!msg && !fg && !bg) {
+        free (cs_info.call);
+        cs_info.call = strdup(getCallsign());
+        NVReadUInt16 (NV_CALL_FG_COLOR, &cs_info.fg_color);
+        NVReadUInt16 (NV_CALL_BG_COLOR, &cs_info.bg_color);
+        NVReadUInt8 (NV_CALL_BG_RAINBOW, &cs_info.bg_rainbow);
+    }
+
+
+    // engage
+    drawCallsign (true);
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        clientp->print (_FX("ok\n"));
+    }
+
+    return (true);
+}
+
+/* remote command to set up alarm clock
+ *   state=off|armed&time=HR:MN
+ */
+static bool setWiFiAlarm (WiFiClient *clientp, char line[])
+{
+    // crack
+    char state[10];
+    int hr = 0, mn = 0;
+    int ns = sscanf (line, "%10[^&]&time=%d:%d", state, &hr, &mn);
+
+    // get current state
+    AlarmState as;
+    uint16_t hr16, mn16;
+    getAlarmState (as, hr16, mn16);
+
+    // parse
+    if (ns == 1 && strcmp (state, "off") == 0) {
+        // turn off but retain current time
+        as = ALMS_OFF;
+    } else if (ns == 1 && strcmp (state, "armed") == 0) {
+        // turn on at current time
+        as = ALMS_ARMED;
+    } else if (ns == 3 && strcmp (state, "armed") == 0 && hr >= 0 && hr < 24 && mn >= 0 && mn < 60) {
+        // turn on at given time
+        as = ALMS_ARMED;
+        hr16 = hr;
+        mn16 = mn;
+    } else {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    // engage
+    setAlarmState (as, hr16, mn16);
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        if (as == ALMS_OFF)
+            clientp->print (_FX("alarm off\n"));
+        else {
+            char buf[30];
+            snprintf (buf, sizeof(buf), "armed at %02d:%02d\n", hr16, mn16);
+            clientp->print (buf);
+        }
+    }
+
+    return (true);
+}
+
+/* remote command to set how alerts draw attention
+ */
+static bool setWiFiAlert (WiFiClient *clientp, char line[])
+{
+    // crack
+    char style[20];
+    if (sscanf (line, "style=%19s", style) != 1 || !setAlertStyle (style)) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        char buf[50];
+        snprintf (buf, sizeof(buf), _FX("alert style %s\n"), getAlertStyleName());
+        clientp->print (buf);
+    }
+
+    return (true);
+}
+
+/* remote command to set time zone and hour format of scheduled events such as satellite passes
+ * syntax: zone=DE|UTC|local&hours=12|24
+ * either keyword is optional but require at least 1.
+ */
+static bool setWiFiEventTimes (WiFiClient *clientp, char line[])
+{
+    // look for each keyword
+    char *Z = strstr (line, _FX("zone="));
+    char *H = strstr (line, _FX("hours="));
+    if (!Z && !H) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    // crack zone, if any
+    char zone[10];
+    if (Z && sscanf (Z+5, "%9[^&]", zone) != 1) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    // engage
+    if (!setEventTimes (Z ? zone : NULL, H ? atoi(H+6) : 0)) {
+        #if defined(_IS_UNIX)
+            strcpy (line, _FX("zone must be DE, UTC or local and hours 12 or 24"));
+        #else
+            strcpy (line, _FX("zone must be DE or UTC and hours 12 or 24"));
+        #endif
+        return (false);
+    }
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        char buf[50];
+        snprintf (buf, sizeof(buf), _FX("events %s time, %d hour\n"), getEventTZName(),
+                                                getEvent12Hr() ? 12 : 24);
+        clientp->print (buf);
+    }
+
+    return (true);
+}
+
+/* remote command to set whether the sat rise alarm is only for passes that can be seen by eye
+ * set_satvisalert?on|off
+ */
+static bool setWiFiSatVisAlert (WiFiClient *clientp, char line[])
+{
+    bool on;
+    if (strcmp (line, "on") == 0)
+        on = true;
+    else if (strcmp (line, "off") == 0)
+        on = false;
+    else {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    setSatVisAlert (on);
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        if (on)
+            FWIFIPRLN (*clientp, F("sat alarm for visible passes only"));
+        else
+            FWIFIPRLN (*clientp, F("sat alarm for all passes"));
+    }
+
+    return (true);
+}
+
+/* remote command to set whether to alert when aurora may be visible from DE
+ * set_auroraalert?on|off
+ */
+static bool setWiFiAuroraAlert (WiFiClient *clientp, char line[])
+{
+    bool on;
+    if (strcmp (line, "on") == 0)
+        on = true;
+    else if (strcmp (line, "off") == 0)
+        on = false;
+    else {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    setAuroraAlert (on);
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        if (on)
+            FWIFIPRLN (*clientp, F("alert when aurora may be visible from DE"));
+        else
+            FWIFIPRLN (*clientp, F("no aurora alerts"));
+    }
+
+    return (true);
+}
+
+/* remote command to send a test alert through all the usual notifications
+ * set_testalert?kind=K&severity=info|warning|critical, severity is optional
+ */
+static bool setWiFiTestAlert (WiFiClient *clientp, char line[])
+{
+    char kind[20];
+    int sev = ALSEV_N;
+    char *S = strstr (line, "&severity=");
+    if (sscanf (line, "kind=%19[^&]", kind) != 1 || (S && (!crackAlertSeverity (S+10, sev) || sev == ALSEV_N))) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    bool quiet;
+    char ynot[100];
+    if (!sendTestAlert (kind, sev, quiet, ynot, sizeof(ynot))) {
+        strcpy (line, ynot);
+        return (false);
+    }
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        char buf[100];
+        snprintf (buf, sizeof(buf), _FX("test %s alert sent%s\n"), kind,
+                                quiet ? ", notifications held back by quiet hours" : "");
+        clientp->print (buf);
+    }
+
+    return (true);
+}
+
+/* remote command to set the DX cluster spot table sort order
+ * set_dxsort?time|freq|band
+ */
+static bool setWiFiDXSort (WiFiClient *clientp, char line[])
+{
+    if (!setDXClusterSortName (line)) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        FWIFIPR (*clientp, F("DX spots sorted by "));
+        clientp->println (getDXClusterSortName());
+    }
+
+    return (true);
+}
+
+/* remote command to set the calls to watch for in DX cluster spots
+ * set_dxwatch?call,call,...|*|none where * watches any call that passes set_dxalertfilter
+ */
+static bool setWiFiDXWatch (WiFiClient *clientp, char line[])
+{
+    if (!setDXClusterWatchCalls (line)) {
+        strcpy (line, _FX("Invalid or too many calls"));
+        return (false);
+    }
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        const char *watch_calls = getDXClusterWatchCalls();
+        FWIFIPR (*clientp, F("watching for "));
+        clientp->println (watch_calls[0] ? watch_calls : "none");
+    }
+
+    return (true);
+}
+
+/* remote command to limit DX watch alerts to spots in given continents, bands and/or prefixes
+ * set_dxalertfilter?cont=OC,AF&band=40m&pfx=VK,ZL&qsl=lotw,eqsl|none
+ */
+static bool setWiFiDXAlertFilter (WiFiClient *clientp, char line[])
+{
+    char ynot[100];
+    if (!setDXAlertFilter (line, ynot, sizeof(ynot))) {
+        strcpy (line, ynot);
+        return (false);
+    }
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        const char *alert_filter = getDXAlertFilter();
+        FWIFIPR (*clientp, F("watch alert filter "));
+        clientp->println (alert_filter[0] ? alert_filter : "none");
+    }
+
+    return (true);
+}
+
+/* remote command to set the band plan pane region and/or band
+ * set_bandplan?region=1|2|3&band=m
+ */
+static bool setWiFiBandPlan (WiFiClient *clientp, char line[])
+{
+    // look for each keyword, if any
+    char *R = strstr (line, _FX("region="));
+    char *B = strstr (line, _FX("band="));
+
+    // look for unknown keywords
+    for (char *sep = line-1, *kw = line; sep != NULL; sep = strchr (kw, '&'), kw = sep + 1) {
+        if (*kw && R != kw && B != kw) {
+            strcpy (line, garbcmd);
+            return (false);
+        }
+    }
+    if (!R && !B) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    int region = 0, band = 0;
+    if ((R && sscanf (R+7, "%d", &region) != 1) || (B && sscanf (B+5, "%d", &band) != 1)) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+    if (!setBandPlan (region, band)) {
+        strcpy (line, _FX("Invalid region or band"));
+        return (false);
+    }
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        char buf[50];
+        getBandPlan (region, band);
+        snprintf (buf, sizeof(buf), _FX("band plan region %d, %d m\n"), region, band);
+        clientp->print (buf);
+    }
+
+    return (true);
+}
+
+/* remote command to set display on or off
+ */
+static bool setWiFiDisplayOnOff (WiFiClient *clientp, char line[])
+{
+    if (brOnOffOk()) {
+
+        // parse
+        if (strcmp (line, "on") == 0)
+            brightnessOn();
+        else if (strcmp (line, "off") == 0)
+            brightnessOff();
+        else {
+            strcpy (line, _FX("Specify on or off"));
+            return (false);
+        }
+
+        // ack with same state
+        if (clientp) {
+            startPlainText (*clientp);
+            FWIFIPR (*clientp, F("display "));
+            clientp->println (line);
+        }
+
+        // ok
+        return (true);
+
+    } else {
+
+        strcpy (line, notsupp);
+        return (false);
+
+    }
+}
+
+/* remote command to set the least severe alert that turns the display back on
+ */
+static bool setWiFiDisplayWake (WiFiClient *clientp, char line[])
+{
+    if (!brOnOffOk()) {
+        strcpy (line, notsupp);
+        return (false);
+    }
+
+    if (!setDisplayWake (line)) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        char buf[50];
+        snprintf (buf, sizeof(buf), _FX("display wakes for %s\n"), getDisplayWakeName());
+        clientp->print (buf);
+    }
+
+    return (true);
+}
+
+/* return whether the keyword value at v is exactly want, ie, followed by & or EOS
+ */
+static bool argIs (const char *v, const char *want)
+{
+    size_t wl = strlen (want);
+    return (strncmp (v, want, wl) == 0 && (v[wl] == '&' || v[wl] == '\0'));
+}
+
+/* remote command to set the KiwiSDR or WebSDR receiver used for spot links, see websdr.cpp:
+ *   kiwi=URL
+ *   websdr=URL
+ *   off
+ */
+static bool setWiFiWebSDR (WiFiClient *clientp, char line[])
+{
+    char *eq = strchr (line, '=');
+    bool ok;
+    if (eq) {
+        *eq = '\0';
+        ok = setWebSDR (line, eq+1);
+    } else
+        ok = setWebSDR (line, "");
+    if (!ok) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        const char *kind, *url;
+        if (getWebSDR (&kind, &url)) {
+            char buf[NV_WEBSDR_LEN+20];
+            snprintf (buf, sizeof(buf), _FX("%s %s\n"), kind, url);
+            clientp->print (buf);
+        } else
+            FWIFIPRLN (*clientp, F("websdr off"));
+    }
+
+    return (true);
+}
+
+/* remote command to set the window within which repeat DX spots of a station are merged:
+ *   mins=N
+ *   off
+ */
+static bool setWiFiSpotMerge (WiFiClient *clientp, char line[])
+{
+    int mins;
+    if (strcmp (line, "off") == 0)
+        mins = 0;
+    else if (sscanf (line, "mins=%d", &mins) != 1 || mins < 0 || mins > MAX_SPOTMERGE) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+    setSpotMerge (mins);
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        char buf[50];
+        if (mins)
+            snprintf (buf, sizeof(buf), _FX("repeat spots merge within %d mins\n"), mins);
+        else
+            snprintf (buf, sizeof(buf), _FX("repeat spots never merge\n"));
+        clientp->print (buf);
+    }
+
+    return (true);
+}
+
+/* remote command to set how long DX spots fade before they expire:
+ *   mins=N
+ *   off
+ */
+static bool setWiFiSpotAge (WiFiClient *clientp, char line[])
+{
+    int mins;
+    if (strcmp (line, "off") == 0)
+        mins = 0;
+    else if (sscanf (line, "mins=%d", &mins) != 1 || mins < 0 || mins > 1440) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+    setSpotAge (mins);
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        char buf[40];
+        if (mins)
+            snprintf (buf, sizeof(buf), _FX("spots expire after %d mins\n"), mins);
+        else
+            snprintf (buf, sizeof(buf), _FX("spots never expire\n"));
+        clientp->print (buf);
+    }
+
+    return (true);
+}
+
+/* remote command to force offline mode or let connectivity decide:
+ *   on
+ *   auto
+ */
+static bool setWiFiOffline (WiFiClient *clientp, char line[])
+{
+    if (strcmp (line, "on") == 0)
+        setOfflineMode (true);
+    else if (strcmp (line, "auto") == 0)
+        setOfflineMode (false);
+    else {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        bool forced;
+        time_t since;
+        if (getOfflineMode (forced, since))
+            FWIFIPRLN (*clientp, forced ? F("offline, forced") : F("offline, waiting for network"));
+        else
+            FWIFIPRLN (*clientp, F("online"));
+    }
+
+    return (true);
+}
+
+/* remote command to set how many days ahead the next solar eclipse path is shown on the map:
+ *   days=N
+ *   off
+ */
+static bool setWiFiEclipse (WiFiClient *clientp, char line[])
+{
+    int days;
+    if (strcmp (line, "off") == 0)
+        days = 0;
+    else if (sscanf (line, "days=%d", &days) != 1 || !setEclipseDays (days)) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+    (void) setEclipseDays (days);
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        char buf[50];
+        if (days)
+            snprintf (buf, sizeof(buf), _FX("eclipse path shown %d days ahead\n"), days);
+        else
+            snprintf (buf, sizeof(buf), _FX("eclipse path off\n"));
+        clientp->print (buf);
+    }
+
+    return (true);
+}
+
+/* remote command to set the satellite doppler source, see doppler.cpp:
+ *   freq=MHz&port=N&push=host:port
+ *   off
+ * missing keywords are unchanged; port=0 and push=none turn those off.
+ */
+static bool setWiFiDoppler (WiFiClient *clientp, char line[])
+{
+    uint32_t hz;
+    uint16_t port;
+    char push[NV_DOPPLERPUSH_LEN];
+    (void) getDoppler (hz, port, push);
+
+    if (strcmp (line, "off") == 0) {
+        port = 0;
+        push[0] = '\0';
+    } else {
+        // look for each keyword
+        char *Q = strstr (line, _FX("freq="));
+        char *P = strstr (line, _FX("port="));
+        char *U = strstr (line, _FX("push="));
+
+        // require at least 1
+        if (!Q && !P && !U) {
+            strcpy_P (line, PSTR("bad args"));
+            return (false);
+        }
+
+        // look for unknown keywords
+        for (char *sep = line-1, *kw = line; sep != NULL; sep = strchr (kw, '&'), kw = sep + 1) {
+            if (Q != kw && P != kw && U != kw) {
+                strcpy_P (line, PSTR("unknown keyword"));
+                return (false);
+            }
+        }
+
+        float mhz;
+        int new_port;
+        if (Q) {
+            if (sscanf (Q+5, "%f", &mhz) != 1 || mhz <= 0 || mhz > 4000) {
+                strcpy (line, garbcmd);
+                return (false);
+            }
+            hz = (uint32_t)(mhz*1e6 + 0.5);
+        }
+        if (P) {
+            if (sscanf (P+5, "%d", &new_port) != 1 || new_port < 0 || new_port > 65535) {
+                strcpy (line, garbcmd);
+                return (false);
+            }
+            port = new_port;
+        }
+        if (U) {
+            if (argIs (U+5, "none"))
+                push[0] = '\0';
+            else if (sscanf (U+5, _FX("%31[^&]"), push) != 1) {
+                strcpy (line, garbcmd);
+                return (false);
+            }
+        }
+    }
+
+    if (!setDoppler (hz, port, push)) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        char buf[100];
+        snprintf (buf, sizeof(buf), _FX("doppler %.6f MHz port %d push %s\n"), hz*1e-6, port,
+                                push[0] ? push : "none");
+        clientp->print (buf);
+    }
+
+    return (true);
+}
+
+/* remote command to set the local K index source or push a new value
+ * set_localk?url=http://host[:port]/path|off
+ * set_localk?K=n&station=name
+ */
+static bool setWiFiLocalK (WiFiClient *clientp, char line[])
+{
+    char *U = strstr (line, _FX("url="));
+    char *K = strstr (line, _FX("K="));
+    char *S = strstr (line, _FX("station="));
+
+    // require url or K, station only with K
+    if ((!U && !K) || (U && (K || S))) {
+        strcpy_P (line, PSTR("bad args"));
+        return (false);
+    }
+
+    // look for unknown keywords
+    for (char *sep = line-1, *kw = line; sep != NULL; sep = strchr (kw, '&'), kw = sep + 1) {
+        if (U != kw && K != kw && S != kw) {
+            strcpy_P (line, PSTR("unknown keyword"));
+            return (false);
+        }
+    }
+
+    if (U) {
+        char url[NV_LOCALKURL_LEN];
+        if (sscanf (U+4, _FX("%63[^&]"), url) != 1 || !setLocalKURL (url)) {
+            strcpy (line, garbcmd);
+            return (false);
+        }
+    } else {
+        float k;
+        char station[20] = "";
+        if (sscanf (K+2, "%f", &k) != 1 || (S && sscanf (S+8, _FX("%19[^&]"), station) != 1)
+                                || !setLocalK (k, station)) {
+            strcpy (line, garbcmd);
+            return (false);
         }
     }
 
-    // or restore default if no args
-    if (!msg && !fg && !bg) {
-        free (cs_info.call);
-        cs_info.call = strdup(getCallsign());
-        NVReadUInt16 (NV_CALL_FG_COLOR, &cs_info.fg_color);
-        NVReadUInt16 (NV_CALL_BG_COLOR, &cs_info.bg_color);
-        NVReadUInt8 (NV_CALL_BG_RAINBOW, &cs_info.bg_rainbow);
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        float k;
+        time_t age;
+        const char *station;
+        const char *url = getLocalKURL();
+        char buf[150];
+        if (getLocalK (k, age, &station))
+            snprintf (buf, sizeof(buf), _FX("local K %.0f from %s, url %s\n"), k, station, url[0] ? url : "none");
+        else
+            snprintf (buf, sizeof(buf), _FX("no local K yet, url %s\n"), url[0] ? url : "none");
+        clientp->print (buf);
     }
 
+    return (true);
+}
 
-    // engage
-    drawCallsign (true);
+/* remote command to set the on-screen language by code, eg de, or en for English.
+ * the catalog is lang/CODE.po in our working directory, see lang.cpp.
+ */
+static bool setWiFiLanguage (WiFiClient *clientp, char line[])
+{
+    if (!setLanguage (line)) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
 
-    // ack
+    // ack first because initScreen takes a while
     if (clientp) {
         startPlainText (*clientp);
-        clientp->print (_FX("ok\n"));
+        char buf[50];
+        snprintf (buf, sizeof(buf), _FX("language %s\n"), getLanguage());
+        clientp->print (buf);
     }
 
+    // redraw everything in the new language
+    initScreen();
+
     return (true);
 }
 
-/* remote command to set up alarm clock
- *   state=off|armed&time=HR:MN
+/* remote command to poll hamlib rigctld for PTT:
+ *   host=H&port=P
+ *   off
  */
-static bool setWiFiAlarm (WiFiClient *clientp, char line[])
+static bool setWiFiRigctld (WiFiClient *clientp, char line[])
 {
-    // crack
-    char state[10];
-    int hr = 0, mn = 0;
-    int ns = sscanf (line, "%10[^&]&time=%d:%d", state, &hr, &mn);
-
-    // get current state
-    AlarmState as;
-    uint16_t hr16, mn16;
-    getAlarmState (as, hr16, mn16);
-
-    // parse
-    if (ns == 1 && strcmp (state, "off") == 0) {
-        // turn off but retain current time
-        as = ALMS_OFF;
-    } else if (ns == 1 && strcmp (state, "armed") == 0) {
-        // turn on at current time
-        as = ALMS_ARMED;
-    } else if (ns == 3 && strcmp (state, "armed") == 0 && hr >= 0 && hr < 24 && mn >= 0 && mn < 60) {
-        // turn on at given time
-        as = ALMS_ARMED;
-        hr16 = hr;
-        mn16 = mn;
-    } else {
+    char host[NV_RIGHOST_LEN];
+    int port = 4532;
+    if (strcmp (line, "off") == 0) {
+        host[0] = '\0';
+    } else if (sscanf (line, _FX("host=%25[^&]&port=%d"), host, &port) < 1 || port < 1 || port > 65535) {
         strcpy (line, garbcmd);
         return (false);
     }
 
-    // engage
-    setAlarmState (as, hr16, mn16);
+    if (!setRigctld (host, port)) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
 
     // ack
     if (clientp) {
         startPlainText (*clientp);
-        if (as == ALMS_OFF)
-            clientp->print (_FX("alarm off\n"));
-        else {
-            char buf[30];
-            snprintf (buf, sizeof(buf), "armed at %02d:%02d\n", hr16, mn16);
-            clientp->print (buf);
-        }
+        char buf[50];
+        if (host[0])
+            snprintf (buf, sizeof(buf), _FX("rigctld %s:%d\n"), host, port);
+        else
+            snprintf (buf, sizeof(buf), _FX("rigctld off\n"));
+        clientp->print (buf);
     }
 
     return (true);
 }
 
-/* remote command to set display on or off
+/* remote command to set any of the units and formatting preferences used by all displays, see units.cpp:
+ *   temp=C|F&dist=km|mi&time=UTC|DE&freq=kHz|MHz&decimals=0-3
  */
-static bool setWiFiDisplayOnOff (WiFiClient *clientp, char line[])
+static bool setWiFiUnits (WiFiClient *clientp, char line[])
 {
-    if (brOnOffOk()) {
+    // look for each keyword
+    char *T = strstr (line, _FX("temp="));
+    char *D = strstr (line, _FX("dist="));
+    char *Z = strstr (line, _FX("time="));
+    char *Q = strstr (line, _FX("freq="));
+    char *N = strstr (line, _FX("decimals="));
 
-        // parse
-        if (strcmp (line, "on") == 0)
-            brightnessOn();
-        else if (strcmp (line, "off") == 0)
-            brightnessOff();
-        else {
-            strcpy (line, _FX("Specify on or off"));
+    // require at least 1
+    if (!T && !D && !Z && !Q && !N) {
+        strcpy_P (line, PSTR("bad args"));
+        return (false);
+    }
+
+    // look for unknown keywords
+    for (char *sep = line-1, *kw = line; sep != NULL; sep = strchr (kw, '&'), kw = sep + 1) {
+        if (T != kw && D != kw && Z != kw && Q != kw && N != kw) {
+            strcpy_P (line, PSTR("unknown keyword"));
             return (false);
         }
+    }
 
-        // ack with same state
-        if (clientp) {
-            startPlainText (*clientp);
-            FWIFIPR (*clientp, F("display "));
-            clientp->println (line);
+    // check all values before changing any
+    bool temp_c = showTempC();
+    if (T) {
+        if (argIs (T+5, "C"))
+            temp_c = true;
+        else if (argIs (T+5, "F"))
+            temp_c = false;
+        else {
+            strcpy (line, garbcmd);
+            return (false);
+        }
+    }
+    bool km = show_km;
+    if (D) {
+        if (argIs (D+5, "km"))
+            km = true;
+        else if (argIs (D+5, "mi"))
+            km = false;
+        else {
+            strcpy (line, garbcmd);
+            return (false);
+        }
+    }
+    bool local = showSpotLocalTime();
+    if (Z) {
+        if (argIs (Z+5, "UTC"))
+            local = false;
+        else if (argIs (Z+5, "DE"))
+            local = true;
+        else {
+            strcpy (line, garbcmd);
+            return (false);
+        }
+    }
+    bool mhz;
+    int decimals;
+    getFreqFormat (mhz, decimals);
+    if (Q) {
+        if (argIs (Q+5, "kHz"))
+            mhz = false;
+        else if (argIs (Q+5, "MHz"))
+            mhz = true;
+        else {
+            strcpy (line, garbcmd);
+            return (false);
         }
+    }
+    if (N && (sscanf (N+9, "%d", &decimals) != 1 || decimals < 0 || decimals > 3)) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
 
-        // ok
-        return (true);
+    // ok
+    setTempC (temp_c);
+    setDistKm (km);
+    setSpotLocalTime (local);
+    setFreqFormat (mhz, decimals);
 
-    } else {
+    // ack first because initScreen takes a while
+    if (clientp) {
+        startPlainText (*clientp);
+        char buf[100];
+        snprintf (buf, sizeof(buf), _FX("temp=%c dist=%s time=%s freq=%s decimals=%d\n"), tempUnits(),
+                    distUnits(), showSpotLocalTime() ? "DE" : "UTC", freqUnits(), decimals);
+        clientp->print (buf);
+    }
 
-        strcpy (line, notsupp);
+    // redraw everything in the new units
+    initScreen();
+
+    return (true);
+}
+
+/* remote command to push a reading from an external wattmeter or to set the SWR alert level:
+ *   fwd=W&swr=S
+ *   swralert=S|off
+ */
+static bool setWiFiTxMeter (WiFiClient *clientp, char line[])
+{
+    float watts, swr;
+    if (sscanf (line, _FX("fwd=%f&swr=%f"), &watts, &swr) == 2 && watts >= 0 && (swr == 0 || swr >= 1)) {
+        noteTxMeter (watts, swr);
+    } else if (strcmp (line, "swralert=off") == 0) {
+        setSWRAlert (0);
+    } else if (sscanf (line, _FX("swralert=%f"), &swr) == 1 && swr > 1) {
+        setSWRAlert (swr);
+    } else {
+        strcpy (line, garbcmd);
         return (false);
+    }
 
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        char buf[60];
+        (void) getTxMeter (watts, swr);
+        snprintf (buf, sizeof(buf), _FX("fwd %.0f W SWR %.1f, alert at %.1f\n"), watts, swr, getSWRAlert());
+        clientp->print (buf);
     }
+
+    return (true);
 }
 
 /* convert 3-letter day-of-week abbreviation to 1..7 (Sun..Sat),
@@ -1350,6 +3011,59 @@ static bool crackDOW (const char *daystr, int &dow)
         return (false);
 }
 
+/* remote command to set the alert quiet hours
+ * from=HR:MN&to=HR:MN&day=DOW|all and/or keep=info|warning|critical|none
+ */
+static bool setWiFiQuiet (WiFiClient *clientp, char line[])
+{
+    // parse -- window and keep are each optional but not both, day defaults to all
+    int from_hr, from_mn, to_hr, to_mn, dow = -1, keep = -1;
+    char *from = strstr (line, _FX("from="));
+    char *to = strstr (line, _FX("to="));
+    char *day = strstr (line, _FX("day="));
+    char *keep_str = strstr (line, _FX("keep="));
+    if ((!from && !keep_str) || (!from != !to)
+                || (from && sscanf (from+5, _FX("%d:%d"), &from_hr, &from_mn) != 2)
+                || (to && sscanf (to+3, _FX("%d:%d"), &to_hr, &to_mn) != 2)
+                || (day && strncmp (day+4, "all", 3) != 0 && !crackDOW (day+4, dow))
+                || (keep_str && !crackAlertSeverity (keep_str+5, keep))) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    if (from) {
+        // pack times and validate
+        if (from_hr < 0 || from_mn < 0 || from_mn >= 60 || to_hr < 0 || to_mn < 0 || to_mn >= 60) {
+            strcpy (line, _FX("Invalid time"));
+            return (false);
+        }
+        uint16_t from_mins = from_hr*60 + from_mn;
+        uint16_t to_mins = to_hr*60 + to_mn;
+        if (from_mins >= MINSPERDAY || to_mins >= MINSPERDAY) {
+            strcpy (line, _FX("Invalid time"));
+            return (false);
+        }
+
+        // set one day or all
+        if (dow > 0)
+            (void) setQuietHours (dow, from_mins, to_mins);
+        else
+            for (int d = 1; d <= DAYSPERWEEK; d++)
+                (void) setQuietHours (d, from_mins, to_mins);
+    }
+
+    if (keep >= 0)
+        setQuietKeep (keep);
+
+    // ack
+    if (clientp) {
+        startPlainText (*clientp);
+        reportQuietHours (clientp);
+    }
+
+    return (true);
+}
+
 /* remote command to set display on/off/idle times
  * on=HR:MN&off=HR:MN&idle=mins&day=DOW
  */
@@ -1524,10 +3238,54 @@ static bool setWiFiNewDXGrid (WiFiClient *clientp, char line[])
     return (setWiFiNewGrid_helper (clientp, true, line));
 }
 
+/* set DE or DX from a city name, eg, Tucson or Paris, France
+ * return whether all ok
+ */
+static bool setWiFiNewCity_helper (WiFiClient *clientp, bool new_dx, char line[])
+{
+    Serial.println (line);
+
+    // find
+    LatLong ll;
+    char found[100];
+    if (!geocodeCity (line, ll, found, sizeof(found))) {
+        strcpy (line, found);
+        return (false);
+    }
+
+    // engage
+    if (new_dx)
+        newDX (ll, NULL, NULL);
+    else
+        newDE (ll, NULL);
+
+    // ack with updated info as if get
+    if (clientp)
+        return (getWiFiDEDXInfo_helper (clientp, line, new_dx));
+    else
+        return (true);
+}
+
+/* set DE from city name
+ * return whether all ok
+ */
+static bool setWiFiNewDECity (WiFiClient *clientp, char line[])
+{
+    return (setWiFiNewCity_helper (clientp, false, line));
+}
+
+/* set DX from city name
+ * return whether all ok
+ */
+static bool setWiFiNewDXCity (WiFiClient *clientp, char line[])
+{
+    return (setWiFiNewCity_helper (clientp, true, line));
+}
+
 
 
 /* set one or more view features of the map, same as menu.
- * syntax: Style=S&Grid=G&Projection=P&RSS=on|off&Night=on|off
+ * syntax: Style=S&Grid=G&Projection=P&RSS=on|off&Night=on|off&Center=C
  * all keywords optional but require at least 1.
  */
 static bool setWiFiMapView (WiFiClient *clientp, char line[])
@@ -1538,16 +3296,17 @@ static bool setWiFiMapView (WiFiClient *clientp, char line[])
     char *P = strstr (line, _FX("Projection="));
     char *R = strstr (line, _FX("RSS="));
     char *N = strstr (line, _FX("Night="));
+    char *C = strstr (line, _FX("Center="));
 
     // require at least 1
-    if (!S && !G && !P && !R && !N) {
+    if (!S && !G && !P && !R && !N && !C) {
         strcpy_P (line, PSTR("bad args"));
         return (false);
     }
 
     // look for unknown keywords
     for (char *sep = line-1, *kw = line; sep != NULL; sep = strchr (kw, '&'), kw = sep + 1) {
-        if (S != kw && G != kw && P != kw && R != kw && N != kw) {
+        if (S != kw && G != kw && P != kw && R != kw && N != kw && C != kw) {
             strcpy_P (line, PSTR("unknown keyword"));
             return (false);
         }
@@ -1648,10 +3407,23 @@ static bool setWiFiMapView (WiFiClient *clientp, char line[])
         }
     }
 
+    // check Center, engaged now because it is checked last, the map is restarted below if it moved
+    bool center_moved = false;
+    if (C) {
+        if (sscanf (C+7, sfmt, buf) != 1) {
+            strcpy (line, garbcmd);
+            return (false);
+        }
+        if (!setMapCenterModeName (buf, center_moved)) {
+            strcpy_P (line, PSTR("unknown Center"));
+            return (false);
+        }
+    }
+
     // all options look good, engage any that have changed.
     // this is rather like drawMapMenu().
 
-    bool full_redraw = false;
+    bool full_redraw = center_moved;
     if (S && my_cm != core_map) {
         if (installNewMapStyle (my_cm)) {
             full_redraw = true;
@@ -1780,7 +3552,7 @@ static bool setWiFiPane (WiFiClient *clientp, char line[])
     // build new rotset
     plot_rotset[pp] = 0;
     for (int i = 0; i < n_pc; i++)
-        plot_rotset[pp] |= (1 << pc[i]);
+        plot_rotset[pp] |= (1ULL << pc[i]);
 
     // show first and persist
     if (!setPlotChoice (pp, pc[0])) {
@@ -1809,17 +3581,83 @@ static bool setWiFiSatName (WiFiClient *clientp, char line[])
 {
     resetWatchdog();
 
-    // do it
-    if (setSatFromName (line)) {
-        if (clientp)
-            return (getWiFiSatellite (clientp, line));
-        else
-            return (true);
+    // do it
+    if (setSatFromName (line)) {
+        if (clientp)
+            return (getWiFiSatellite (clientp, line));
+        else
+            return (true);
+    }
+
+    // nope
+    strcpy (line, _FX("Unknown sat"));
+    return (false);
+}
+
+/* remote command to change the settings of one satellite, all but name are optional:
+ *   name=abc&enabled=on|off&priority=0-9&minel=0-60&mode=Any|FM|SSB|CW|Data
+ */
+static bool setWiFiSatConfig (WiFiClient *clientp, char line[])
+{
+    // start with current settings
+    char name[NV_SATNAME_LEN+1];
+    if (sscanf (line, "name=%9[^&]", name) != 1 || strlen (name) >= NV_SATNAME_LEN) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+    strncpySubChar (name, name, '_', ' ', NV_SATNAME_LEN);
+    SatConfig sc;
+    getSatConfig (name, sc);
+
+    // apply each given
+    char *E = strstr (line, "&enabled=");
+    char *P = strstr (line, "&priority=");
+    char *L = strstr (line, "&minel=");
+    char *M = strstr (line, "&mode=");
+    char mode[10];
+    int pri, el;
+    if (E) {
+        if (strncmp (E+9, "on", 2) == 0)
+            sc.enabled = true;
+        else if (strncmp (E+9, "off", 3) == 0)
+            sc.enabled = false;
+        else {
+            strcpy (line, _FX("enabled must be on or off"));
+            return (false);
+        }
+    }
+    if (P) {
+        if (sscanf (P+10, "%d", &pri) != 1 || pri < 0 || pri > SATCFG_MAXPRI) {
+            strcpy (line, _FX("priority must be 0 .. 9"));
+            return (false);
+        }
+        sc.priority = pri;
+    }
+    if (L) {
+        if (sscanf (L+7, "%d", &el) != 1 || el < 0 || el > SATCFG_MAXEL) {
+            strcpy (line, _FX("minel must be 0 .. 60"));
+            return (false);
+        }
+        sc.min_el = el;
+    }
+    if (M && (sscanf (M+6, "%9[^&]", mode) != 1 || !crackSatMode (mode, sc.mode))) {
+        strcpy (line, _FX("mode must be Any, FM, SSB, CW or Data"));
+        return (false);
+    }
+
+    if (!setSatConfig (sc)) {
+        strcpy (line, _FX("too many satellites with settings"));
+        return (false);
+    }
+    satConfigChanged (sc.name);
+
+    // ack with all
+    if (clientp) {
+        startPlainText (*clientp);
+        printSatConfigs (*clientp);
     }
 
-    // nope
-    strcpy (line, _FX("Unknown sat"));
-    return (false);
+    return (true);
 }
 
 /* set satellite from given TLE: set_sattle?name=n&t1=line1&t2=line2
@@ -1870,6 +3708,39 @@ static bool setWiFiSatTLE (WiFiClient *clientp, char line[])
     return (false);
 }
 
+/* remote command to set the contest period shown by the stopwatch:
+ *   start=YYYY-MM-DDTHH:MM&hours=N
+ *   off
+ */
+static bool setWiFiContest (WiFiClient *clientp, char line[])
+{
+    int yr, mo, dy, hr, mn, hours;
+    if (strcmp (line, "off") == 0) {
+        setContestPeriod (0, 0);
+    } else if (sscanf (line, _FX("start=%d-%d-%dT%d:%d&hours=%d"), &yr, &mo, &dy, &hr, &mn, &hours) == 6
+                        && yr >= 1970 && mo >= 1 && mo <= 12 && dy >= 1 && dy <= 31 && hr >= 0 && hr < 24
+                        && mn >= 0 && mn < 60 && hours > 0 && hours <= 24*7) {
+        tmElements_t tm;
+        tm.Year = yr - 1970;
+        tm.Month = mo;
+        tm.Day = dy;
+        tm.Hour = hr;
+        tm.Minute = mn;
+        tm.Second = 0;
+        time_t start = makeTime (tm);
+        setContestPeriod (start, start + hours*3600L);
+    } else {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    // ack
+    if (clientp)
+        return (getWiFiStopwatch (clientp, line));
+    else
+        return (true);
+}
+
 /* remote command to control stopwatch engine state
  */
 static bool setWiFiStopwatch (WiFiClient *clientp, char line[])
@@ -2162,7 +4033,7 @@ static bool doWiFiExit (WiFiClient *clientp, char *unused)
  * strings are in arrays so they are in ESP FLASH too.
  */
 #define CT_MAX_CMD      30                              // max command string length
-#define CT_MAX_HELP     60                              // max help string length
+#define CT_MAX_HELP     80                              // max help string length
 #define CT_FUNP(ctp) ((PCTF)pgm_read_dword(&ctp->funp)) // handy function pointer
 typedef bool (*PCTF)(WiFiClient *clientp, char *line);  // ptr to command table function
 typedef struct {
@@ -2171,37 +4042,105 @@ typedef struct {
     const char help[CT_MAX_HELP];                       // more info if available
 } CmdTble;
 static const CmdTble command_table[] PROGMEM = {
+#if defined(_IS_UNIX)
+    { "dashboard/",         getWiFiDashboard,      "phone friendly dashboard, open in a browser" },
+#endif // defined(_IS_UNIX)
+    { "get_awards.txt ",    getWiFiAwards,         "get DXCC, WAS and WAZ worked/confirmed per band and mode" },
+    { "get_bandrec.txt ",   getWiFiBandRec,        "get recommended bands and headings" },
     { "get_capture.bmp ",   getWiFiScreenCapture,  "get live screen shot" },
+#if defined(_IS_UNIX)
+    { "get_capture.png ",   getWiFiScreenCapturePNG, "get live screen shot as PNG" },
+    { "get_alerts?",        getWiFiAlerts,         "format=csv|json|adif&from=YYYY-MM-DD&to=YYYY-MM-DD&kind=K" },
+#endif // defined(_IS_UNIX)
     { "get_config.txt ",    getWiFiConfig,         "get current display options" },
     { "get_de.txt ",        getWiFiDEInfo,         "get DE info" },
     { "get_dx.txt ",        getWiFiDXInfo,         "get DX info" },
     { "get_dxspots.txt ",   getWiFiDXSpots,        "get DX spots" },
+#if defined(_IS_UNIX)
+    { "get_live.html?",     getWiFiLiveHTML,       "refresh=secs&shrink=1|2|4" },
+    { "get_live.png?",      getWiFiLivePNG,        "shrink=1|2|4" },
+    { "get_passplan?",      getWiFiPassPlan,       "pass=N&format=csv|json&down=MHz&up=MHz&step=secs" },
+#endif // defined(_IS_UNIX)
+    { "get_qsorate.txt ",   getWiFiQSORate,        "get QSO rates and session count" },
+    { "get_refresh.txt ",   getWiFiRefresh,        "get data source refresh intervals, ages and failures" },
+    { "get_satconfig.txt ", getWiFiSatConfig,      "get per-satellite settings" },
     { "get_satellite.txt ", getWiFiSatellite,      "get current sat info" },
     { "get_satellites.txt ",getWiFiAllSatellites,  "get list of all sats" },
     { "get_sensors.txt ",   getWiFiSensorData,     "get sensor data" },
+#if defined(_IS_UNIX)
+    { "get_share.json ",    getWiFiShareJSON,      "get conditions summary for sharing" },
+    { "get_share.png ",     getWiFiSharePNG,       "get conditions card image for sharing" },
+#endif // defined(_IS_UNIX)
     { "get_spacewx.txt ",   getWiFiSpaceWx,        "get space weather info" },
+#if defined(_IS_UNIX)
+    { "get_spacewxhist?",   getWiFiSpaceWxHistory, "kind=SSN|SFI|Kp|SolarWind|XRay|DRAP&days=N" },
+#endif // defined(_IS_UNIX)
     { "get_stopwatch.txt ", getWiFiStopwatch,      "get stopwatch state" },
     { "get_sys.txt ",       getWiFiSys,            "get system stats" },
     { "get_time.txt ",      getWiFiTime,           "get current time" },
+    { "healthz ",           getWiFiHealth,         "get liveness checks, status 503 if any fail" },
     { "set_alarm?",         setWiFiAlarm,          "state=off|armed&time=HR:MN" },
+    { "set_alert?",         setWiFiAlert,          "style=flash|pulse|steady" },
+#if defined(_IS_UNIX)
+    { "set_alertmsg?",      setWiFiAlertMsg,       "kind=watch|alarm|...&msg={call} on {freq} {band}..." },
+    { "set_alertqueue?",    setWiFiAlertQueue,     "sink=ws|script&policy=drop-oldest|drop-newest" },
+    { "set_alertsound?",    setWiFiAlertSound,     "kind=watch|alarm|...&sound=off|.-_pattern|file.wav" },
+#endif // defined(_IS_UNIX)
     { "set_antennaheading?",setWiFiAntennaHeading, "heading=degrees,width=degrees" },
+    { "set_auroraalert?",   setWiFiAuroraAlert,    "on|off" },
+    { "set_bandplan?",      setWiFiBandPlan,       "region=1|2|3&band=m" },
+    { "set_contest?",       setWiFiContest,        "start=YYYY-MM-DDTHH:MM&hours=N|off" },
     { "set_displayOnOff?",  setWiFiDisplayOnOff,   "on|off" },
     { "set_displayTimes?",  setWiFiDisplayTimes,   "on=HR:MN&off=HR:MN&day=DOW&idle=mins" },
-    { "set_mapview?",       setWiFiMapView,        "Style=S&Grid=G&Projection=P&RSS=on|off&Night=on|off" },
+    { "set_displayWake?",   setWiFiDisplayWake,    "none|info|warning|critical" },
+    { "set_doppler?",       setWiFiDoppler,        "freq=MHz&port=N&push=host:port|off" },
+    { "set_dxalertfilter?", setWiFiDXAlertFilter,  "cont=NA,SA,EU,AF,AS,OC,AN&band=40m,...&pfx=VK,...&qsl=lotw,eqsl|none" },
+    { "set_dxsort?",        setWiFiDXSort,         "time|freq|band" },
+    { "set_dxwatch?",       setWiFiDXWatch,        "call,call,...|*|none" },
+    { "set_eclipse?",       setWiFiEclipse,        "days=N|off" },
+#if defined(_IS_UNIX)
+    { "set_escalate?",      setWiFiEscalate,       "kind=alarm|swr&mins=N&chain=ws,wake,script" },
+#endif // defined(_IS_UNIX)
+    { "set_eventtimes?",    setWiFiEventTimes,     "zone=DE|UTC|local&hours=12|24" },
+    { "set_language?",      setWiFiLanguage,       "en|de|..." },
+    { "set_localk?",        setWiFiLocalK,         "url=http://host/path|off or K=n&station=name" },
+#if defined(_IS_UNIX)
+    { "set_maplayers?",     setWiFiMapLayers,      "Night|Grid|Path|Sat|Symbols|Aurora=pct&Order=bottom,...,top" },
+#endif // defined(_IS_UNIX)
+    { "set_mapview?",       setWiFiMapView,        "Style=S&Grid=G&Projection=P&RSS=on|off&Night=on|off&Center=Fixed|DE|Zero|Sun" },
     { "set_newde?",         setWiFiNewDE,          "lat=X&lng=Y" },
+    { "set_newdecity?",     setWiFiNewDECity,      "name" },
     { "set_newdegrid?",     setWiFiNewDEGrid,      "AB12" },
     { "set_newdx?",         setWiFiNewDX,          "lat=X&lng=Y" },
+    { "set_newdxcity?",     setWiFiNewDXCity,      "name" },
     { "set_newdxgrid?",     setWiFiNewDXGrid,      "AB12" },
+    { "set_offline?",       setWiFiOffline,        "on|auto" },
     { "set_pane?",          setWiFiPane,           "Pane[123]=X,Y,Z... any from:" },
+    { "set_qsorate?",       setWiFiQSORate,        "reset" },
+    { "set_quiet?",         setWiFiQuiet,          "from=HR:MN&to=HR:MN&day=DOW|all&keep=info|warning|critical|none" },
+    { "set_rigctld?",       setWiFiRigctld,        "host=H&port=P|off" },
+    { "set_satconfig?",     setWiFiSatConfig,      "name=abc&enabled=on|off&priority=0-9&minel=0-60&mode=FM|SSB|..." },
     { "set_satname?",       setWiFiSatName,        "abc|none" },
     { "set_sattle?",        setWiFiSatTLE,         "name=abc&t1=line1&t2=line2" },
+    { "set_satvisalert?",   setWiFiSatVisAlert,    "on|off" },
+#if defined(_IS_UNIX)
+    { "set_screenshots?",   setWiFiScreenshots,    "now|every=mins" },
+    { "set_share?",         setWiFiShare,          "save" },
+    { "set_spacewxhist?",   setWiFiSpaceWxHistory, "days=N" },
+#endif // defined(_IS_UNIX)
+    { "set_spotage?",       setWiFiSpotAge,        "mins=N|off" },
+    { "set_spotmerge?",     setWiFiSpotMerge,      "mins=N|off" },
     { "set_stopwatch?",     setWiFiStopwatch,      "reset|run|stop|lap|countdown=mins" },
+    { "set_testalert?",     setWiFiTestAlert,      "kind=watch|alarm|...&severity=info|warning|critical" },
     { "set_time?",          setWiFiTime,           "ISO=YYYY-MM-DDTHH:MM:SS" },
     { "set_time?",          setWiFiTime,           "Now" },
     { "set_time?",          setWiFiTime,           "unix=secs_since_1970" },
     { "set_title?",         setWiFiTitle,          "msg=hello&fg=R,G,B&bg=R,G,B|rainbow" },
     { "set_touch?",         setWiFiTouch,          "x=X&y=Y&hold=0|1" },
+    { "set_txmeter?",       setWiFiTxMeter,        "fwd=W&swr=S|swralert=S|swralert=off" },
+    { "set_units?",         setWiFiUnits,          "temp=C|F&dist=km|mi&time=UTC|DE&freq=kHz|MHz&decimals=0-3" },
     { "set_voacap?",        setWiFivoacap,         "band=80-10&power=p" },
+    { "set_websdr?",        setWiFiWebSDR,         "kiwi=URL|websdr=URL|off" },
     { "restart ",           doWiFiReboot,          "restart HamClock" },
     { "updateVersion ",     doWiFiUpdate,          "update to latest version"},
 #if defined(_IS_UNIX)
@@ -2215,6 +4154,7 @@ static const CmdTble command_table[] PROGMEM = {
 static bool roCommand (const char *cmd)
 {
     return (strncmp (cmd, "get_", 4) == 0
+                    || strncmp (cmd, "healthz", 7) == 0
                     || strncmp (cmd, "set_alarm", 9) == 0
                     || strncmp (cmd, "set_stopwatch", 13) == 0
                     || strncmp (cmd, "set_touch", 9) == 0);
@@ -2262,6 +4202,460 @@ static bool runWebserverCommand (WiFiClient *clientp, bool ro, char *command)
     return (false);
 }
 
+#if defined(_IS_UNIX)
+
+/* REST control API for automation, eg from Node-RED or curl:
+ *
+ *   curl -H "Authorization: Bearer $(cat ~/.hamclock/api_token)" http://host:8080/api/control/ack
+ *
+ * GET or POST /api/control/<action>?args, each request must carry the token found in API_TOKEN_FN.
+ * The API is disabled unless that file exists and is readable only by its owner. We have no TLS so if
+ * used beyond a trusted LAN run it behind a TLS reverse proxy such as stunnel or nginx.
+ * Once the token is set it also guards every other command that changes state, ie, all but get_ and the
+ * few read-only pages, see commandTokenOk(). Those accept either the same bearer token or, if WEB_AUTH_FN
+ * is also in use, its password, so a browser using the live page must log in with that password.
+ *
+ * N.B. the token and the WEB_AUTH_FN password are secrets kept as plain text, as is the Wi-Fi password in
+ * NVRAM on ESP. Both files are refused unless readable only by their owner. For containers and other
+ * headless installs either may instead come from API_TOKEN_ENV or WEB_AUTH_ENV, which then take precedence
+ * over the file so no secret need be written to disk at all.
+ */
+
+#define API_PREFIX      "/api/control/"         // start of each API path
+#define API_TOKEN_FN    "api_token"             // file in our_dir containing the token
+#define API_TOKEN_ENV   "HAMCLOCK_API_TOKEN"    // environment variable overriding API_TOKEN_FN
+#define API_TOKEN_MIN   16                      // shortest token we accept
+#define API_TOKEN_MAX   128                     // longest token we accept
+
+static char api_token[API_TOKEN_MAX+1];         // token, empty if API is disabled
+static bool api_token_loaded;                   // set after first attempt to load api_token
+
+/* set api_token to the given token from src, unless it is an unreasonable length.
+ */
+static void useAPIToken (const char *token, const char *src)
+{
+    size_t len = strlen (token);
+    if (len < API_TOKEN_MIN || len > API_TOKEN_MAX)
+        Serial.printf (_FX("API: %s token must be %d .. %d chars, API disabled\n"), src, API_TOKEN_MIN,
+                                                        API_TOKEN_MAX);
+    else {
+        strcpy (api_token, token);
+        Serial.printf (_FX("API: enabled from %s\n"), src);
+    }
+}
+
+/* load api_token from API_TOKEN_ENV if set, else API_TOKEN_FN, leaving it empty if missing or unsafe.
+ */
+static void loadAPIToken()
+{
+    api_token[0] = '\0';
+    api_token_loaded = true;
+
+    const char *env = getenv (API_TOKEN_ENV);
+    if (env && *env) {
+        useAPIToken (env, API_TOKEN_ENV);
+        return;
+    }
+
+    std::string fn = our_dir + API_TOKEN_FN;
+    FILE *fp = fopen (fn.c_str(), "r");
+    if (!fp)
+        return;
+
+    struct stat sbuf;
+    char buf[API_TOKEN_MAX+2];
+    if (fstat (fileno(fp), &sbuf) < 0 || (sbuf.st_mode & 077) != 0) {
+        Serial.printf (_FX("API: %s must be readable only by its owner, API disabled\n"), fn.c_str());
+    } else if (!fgets (buf, sizeof(buf), fp)) {
+        Serial.printf (_FX("API: %s is empty, API disabled\n"), fn.c_str());
+    } else {
+        buf[strcspn (buf, " \t\r\n")] = '\0';
+        useAPIToken (buf, API_TOKEN_FN);
+    }
+
+    fclose (fp);
+}
+
+/* return whether the given string matches the non-empty secret, taking the same time regardless of
+ * where they differ so the secret can not be discovered by timing.
+ */
+static bool secretOk (const char *given, const char *secret)
+{
+    size_t given_len = strlen (given);
+    size_t secret_len = strlen (secret);
+    unsigned diff = given_len ^ secret_len;
+    for (size_t i = 0; i < secret_len; i++)
+        diff |= (unsigned)(i < given_len ? given[i] : 0) ^ (unsigned)secret[i];
+    return (secret_len > 0 && diff == 0);
+}
+
+/* send an HTTP error with the given status line, WWW-Authenticate challenge if not NULL, and message
+ */
+static void sendHTTPStatus (WiFiClient client, const char *status, const char *challenge, const char *errmsg)
+{
+    resetWatchdog();
+
+    Serial.printf (_FX("Web: %s\n"), errmsg);
+
+    client.println (status);
+    sendUserAgent (client);
+    if (challenge) {
+        client.print (F("WWW-Authenticate: "));
+        client.println (challenge);
+    }
+    FWIFIPRLN (client, F("Content-Type: text/plain; charset=us-ascii"));
+    FWIFIPRLN (client, F("Connection: close\r\n"));             // include extra blank line
+    client.println (errmsg);
+}
+
+/* optional password protection for everything else the web server offers, using HTTP Basic auth.
+ * Enabled when WEB_AUTH_FN exists. Its first line is user:password, following lines may each be
+ * "public prefix" to let anyone use paths starting with prefix, eg, "public get_live". Like the API token,
+ * the file must be readable only by its owner; if not, all private paths are refused until it is fixed.
+ * If WEB_AUTH_ENV is set it holds user:password instead and the file is ignored, so there are no public paths.
+ * Basic auth passwords cross the network in the clear, so the TLS proxy advice above applies here too.
+ */
+
+#define WEB_AUTH_FN     "web_auth"              // file in our_dir with credentials and public paths
+#define WEB_AUTH_ENV    "HAMCLOCK_WEB_AUTH"     // environment variable with user:password overriding WEB_AUTH_FN
+#define WEB_AUTH_MAX    100                     // longest user:password we accept
+#define WEB_PUBLIC_N    10                      // max public prefixes
+#define WEB_PUBLIC_LEN  30                      // longest public prefix
+
+typedef enum {
+    WA_OFF,                                     // no WEB_AUTH_FN, all paths are public
+    WA_ON,                                      // require web_basic for all but web_public
+    WA_BAD,                                     // WEB_AUTH_FN is unusable, refuse all but web_public
+} WebAuthState;
+
+static WebAuthState web_auth;                   // current state
+static bool web_auth_loaded;                    // set after first attempt to load WEB_AUTH_FN
+static char web_basic[4*(WEB_AUTH_MAX+2)/3+1];  // base64 user:password, as it appears in the header
+static char web_public[WEB_PUBLIC_N][WEB_PUBLIC_LEN+1];     // paths anyone may use
+static int n_web_public;                        // n used in web_public[]
+
+/* load web_auth, web_basic and web_public from WEB_AUTH_ENV if set, else WEB_AUTH_FN.
+ */
+static void loadWebAuth()
+{
+    web_auth = WA_OFF;
+    web_basic[0] = '\0';
+    n_web_public = 0;
+    web_auth_loaded = true;
+
+    const char *env = getenv (WEB_AUTH_ENV);
+    if (env && *env) {
+        if (!strchr (env, ':') || strlen (env) > WEB_AUTH_MAX) {
+            Serial.printf (_FX("Web: %s must be user:password, refusing private paths\n"), WEB_AUTH_ENV);
+            web_auth = WA_BAD;
+        } else {
+            base64Encode ((uint8_t*)env, strlen(env), web_basic);
+            web_auth = WA_ON;
+            Serial.printf (_FX("Web: password from %s required for all paths\n"), WEB_AUTH_ENV);
+        }
+        return;
+    }
+
+    std::string fn = our_dir + WEB_AUTH_FN;
+    FILE *fp = fopen (fn.c_str(), "r");
+    if (!fp)
+        return;
+
+    struct stat sbuf;
+    char buf[WEB_AUTH_MAX+2];
+    web_auth = WA_BAD;
+    if (fstat (fileno(fp), &sbuf) < 0 || (sbuf.st_mode & 077) != 0) {
+        Serial.printf (_FX("Web: %s must be readable only by its owner, refusing private paths\n"),
+                                                        fn.c_str());
+    } else if (!fgets (buf, sizeof(buf), fp) || !strchr (buf, ':')) {
+        Serial.printf (_FX("Web: %s must start with user:password, refusing private paths\n"), fn.c_str());
+    } else {
+        buf[strcspn (buf, "\r\n")] = '\0';
+        base64Encode ((uint8_t*)buf, strlen(buf), web_basic);
+        web_auth = WA_ON;
+        char prefix[WEB_PUBLIC_LEN+1];
+        while (fgets (buf, sizeof(buf), fp)) {
+            if (sscanf (buf, "public %30s", prefix) == 1 && n_web_public < WEB_PUBLIC_N)
+                strcpy (web_public[n_web_public++], prefix);
+        }
+        Serial.printf (_FX("Web: password required except for %d public paths\n"), n_web_public);
+    }
+
+    fclose (fp);
+}
+
+/* return whether a client sending the given Authorization header value may use the given path,
+ * ie, the request line after "GET /". if not, send the appropriate refusal.
+ */
+static bool webAuthOk (WiFiClient *clientp, const char *path, const char *auth)
+{
+    if (!web_auth_loaded)
+        loadWebAuth();
+    if (web_auth == WA_OFF)
+        return (true);
+
+    for (int i = 0; i < n_web_public; i++)
+        if (strncmp (path, web_public[i], strlen(web_public[i])) == 0)
+            return (true);
+
+    if (web_auth == WA_BAD) {
+        sendHTTPStatus (*clientp, "HTTP/1.0 403 Forbidden", NULL, "web access disabled, see " WEB_AUTH_FN);
+        return (false);
+    }
+
+    const char *given = strncmp (auth, "Basic ", 6) == 0 ? auth + 6 : "";
+    if (!secretOk (given, web_basic)) {
+        sendHTTPStatus (*clientp, "HTTP/1.0 401 Unauthorized", "Basic realm=\"HamClock\"",
+                                                        "missing or wrong password");
+        return (false);
+    }
+
+    return (true);
+}
+
+/* return whether the given command, ie, the request line after "GET /", changes state
+ */
+static bool commandChangesState (const char *cmd)
+{
+    return (!(strncmp (cmd, "get_", 4) == 0
+                    || strncmp (cmd, "healthz", 7) == 0
+                    || strncmp (cmd, "dashboard", 9) == 0));
+}
+
+/* return whether a client sending the given Authorization header value may run the given command, ie,
+ * the request line after "GET /". Once API_TOKEN_FN is in use, commands that change state must carry
+ * the same bearer token as the API, or the WEB_AUTH_FN password if that is in use too.
+ * if not, send the refusal.
+ */
+static bool commandTokenOk (WiFiClient *clientp, const char *cmd, const char *auth)
+{
+    if (!api_token_loaded)
+        loadAPIToken();
+    if (api_token[0] == '\0' || !commandChangesState (cmd))
+        return (true);
+
+    if (strncmp (auth, "Bearer ", 7) == 0 && secretOk (auth + 7, api_token))
+        return (true);
+    if (web_auth == WA_ON && strncmp (auth, "Basic ", 6) == 0 && secretOk (auth + 6, web_basic))
+        return (true);
+
+    sendHTTPStatus (*clientp, "HTTP/1.0 401 Unauthorized", "Bearer", "command needs the API token");
+    return (false);
+}
+
+/* API action to acknowledge all alerts: watched DX spot, a ringing alarm clock and any escalations
+ */
+static bool apiAck (WiFiClient *clientp, char line[])
+{
+    if (line[0] != '\0') {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    ackDXClusterWatchAlert();
+    ackAlertEscalations (NULL);
+
+    AlarmState as;
+    uint16_t hr, mn;
+    getAlarmState (as, hr, mn);
+    if (as == ALMS_RINGING)
+        setAlarmState (ALMS_OFF, hr, mn);               // downgrades to armed
+
+    if (clientp) {
+        startPlainText (*clientp);
+        clientp->print (_FX("alerts acknowledged\n"));
+    }
+
+    return (true);
+}
+
+/* API action to set rig frequency
+ *   kHz=f
+ */
+static bool apiFreq (WiFiClient *clientp, char line[])
+{
+    float kHz;
+    char c;
+    if (sscanf (line, "kHz=%f%c", &kHz, &c) != 1 || kHz <= 0) {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    setRadioSpot (kHz);
+
+    if (clientp) {
+        startPlainText (*clientp);
+        char buf[40];
+        snprintf (buf, sizeof(buf), "rig set to %g kHz\n", kHz);
+        clientp->print (buf);
+    }
+
+    return (true);
+}
+
+/* API action to set or toggle map layers
+ *   Name=pct|toggle&...&Order=bottom,...,top
+ * toggle switches a layer between invisible and fully opaque.
+ */
+static bool apiLayer (WiFiClient *clientp, char line[])
+{
+    // replace each toggle with its new percentage so setWiFiMapLayers can engage them all
+    char new_line[200];
+    size_t nl = 0;
+    new_line[0] = '\0';
+    char *save;
+    for (char *kw = strtok_r (line, "&", &save); kw != NULL; kw = strtok_r (NULL, "&", &save)) {
+        if (nl >= sizeof(new_line) - 1) {
+            strcpy_P (line, PSTR("too long"));
+            return (false);
+        }
+        char *eq = strchr (kw, '=');
+        if (eq && strcmp (eq+1, "toggle") == 0) {
+            *eq = '\0';
+            int ml;
+            for (ml = 0; ml < MAPL_N; ml++)
+                if (strcasecmp (kw, getMapLayerName((MapLayer)ml)) == 0)
+                    break;
+            if (ml == MAPL_N) {
+                strcpy_P (line, PSTR("unknown layer"));
+                return (false);
+            }
+            bool on = getMapLayerOpacity ((MapLayer)ml) > 0;
+            nl += snprintf (new_line+nl, sizeof(new_line)-nl, "%s%s=%d", nl ? "&" : "", kw, on ? 0 : 100);
+        } else
+            nl += snprintf (new_line+nl, sizeof(new_line)-nl, "%s%s", nl ? "&" : "", kw);
+    }
+    if (nl >= sizeof(new_line)) {
+        strcpy_P (line, PSTR("too long"));
+        return (false);
+    }
+    strcpy (line, new_line);
+
+    return (setWiFiMapLayers (clientp, line));
+}
+
+/* API action to reload the token and web password and redraw the entire display from the current settings
+ */
+static bool apiReload (WiFiClient *clientp, char line[])
+{
+    if (line[0] != '\0') {
+        strcpy (line, garbcmd);
+        return (false);
+    }
+
+    // ack first because initScreen takes a while
+    if (clientp) {
+        startPlainText (*clientp);
+        clientp->print (_FX("reloading\n"));
+    }
+
+    loadAPIToken();
+    loadWebAuth();
+    initScreen();
+
+    return (true);
+}
+
+// API actions, in the same spirit as command_table
+typedef struct {
+    const char *action;                                 // name after API_PREFIX
+    PCTF funp;                                          // handler function
+    const char *help;                                   // args
+} APIAction;
+static const APIAction api_actions[] = {
+    { "ack",    apiAck,     "" },
+    { "freq?",  apiFreq,    "kHz=f" },
+    { "layer?", apiLayer,   "Night|Grid|Path|Sat|Symbols|Aurora=pct|toggle&Order=bottom,...,top" },
+    { "reload", apiReload,  "" },
+    { "view?",  setWiFiPane, "Pane[123]=X,Y,Z..." },
+};
+
+// the request header values we use
+typedef struct {
+    char auth[WEB_AUTH_MAX+API_TOKEN_MAX];      // Authorization
+    char ws_key[64];                            // Sec-WebSocket-Key
+} RemoteHeader;
+
+/* read the remainder of the request header, saving the values we use in rh
+ */
+static void readRemoteHeader (WiFiClient *clientp, RemoteHeader &rh)
+{
+    rh.auth[0] = '\0';
+    rh.ws_key[0] = '\0';
+
+    char hdr[sizeof(rh.auth)+50];
+    while (getTCPLine (*clientp, hdr, sizeof(hdr), NULL) && hdr[0] != '\0') {
+        if (strncasecmp (hdr, "Authorization:", 14) == 0) {
+            const char *v = hdr + 14 + strspn (hdr + 14, " ");
+            snprintf (rh.auth, sizeof(rh.auth), "%s", v);
+        } else if (strncasecmp (hdr, "Sec-WebSocket-Key:", 18) == 0) {
+            (void) sscanf (hdr+18, " %60s", rh.ws_key);
+        }
+    }
+}
+
+/* return whether the given first line of an HTTP request is for the control API
+ */
+static bool isControlAPI (const char *line)
+{
+    return (strncmp (line, "GET " API_PREFIX, 4 + sizeof(API_PREFIX) - 1) == 0
+                || strncmp (line, "POST " API_PREFIX, 5 + sizeof(API_PREFIX) - 1) == 0);
+}
+
+/* serve a control API request whose first line is in line[], ie, the method and path, and whose
+ * Authorization header value is in auth.
+ */
+static void serveControlAPI (WiFiClient *clientp, char line[], const char *auth)
+{
+    // bearer token
+    const char *token = strncmp (auth, "Bearer ", 7) == 0 ? auth + 7 : "";
+
+    // path follows method, log without the token of course
+    char *action = strchr (line, '/') + sizeof(API_PREFIX) - 1;
+    char *http = strstr (action, " HTTP");
+    if (http)
+        *http = '\0';
+    Serial.print (F("API from "));
+    Serial.print (clientp->remoteIP());
+    Serial.print (F(": "));
+    Serial.println (action);
+
+    // authenticate
+    if (!api_token_loaded)
+        loadAPIToken();
+    if (api_token[0] == '\0') {
+        sendHTTPStatus (*clientp, "HTTP/1.0 403 Forbidden", NULL, "API disabled, see " API_TOKEN_FN);
+        return;
+    }
+    if (!secretOk (token, api_token)) {
+        sendHTTPStatus (*clientp, "HTTP/1.0 401 Unauthorized", "Bearer", "missing or wrong token");
+        return;
+    }
+
+    // find action and run
+    for (unsigned i = 0; i < NARRAY(api_actions); i++) {
+        const APIAction &aa = api_actions[i];
+        size_t len = strlen (aa.action);
+        bool has_args = aa.action[len-1] == '?';
+        if (strncmp (action, aa.action, len) == 0 && (has_args || action[len] == '\0')) {
+            char *args = action + len;
+            replaceBlankEntity (args);
+            if (!(*aa.funp)(clientp, args))
+                sendHTTPError (*clientp, args);
+            return;
+        }
+    }
+
+    // not found so list actions
+    char msg[200];
+    int l = snprintf (msg, sizeof(msg), "Unknown action, choose from:\n");
+    for (unsigned i = 0; i < NARRAY(api_actions); i++)
+        l += snprintf (msg+l, sizeof(msg)-l, "  %-8s %s\n", api_actions[i].action, api_actions[i].help);
+    sendHTTPStatus (*clientp, "HTTP/1.0 404 Not Found", NULL, msg);
+}
+
+#endif // _IS_UNIX
+
 /* service remote connection.
  * if ro, only accept get commands and set_touch
  */
@@ -2270,20 +4664,43 @@ static void serveRemote(WiFiClient *clientp, bool ro)
     StackMalloc line_mem(TLE_LINEL*4);          // accommodate longest query, probably set_sattle with %20s
     char *line = (char *) line_mem.getMem();    // handy access to malloced buffer
     char *skipget = line+5;                     // handy location within line[] after "GET /"
+#if defined(_IS_UNIX)
+    RemoteHeader rh;                            // header values we use
+#endif // _IS_UNIX
 
     // first line must be the GET
     if (!getTCPLine (*clientp, line, line_mem.getSize(), NULL)) {
         sendHTTPError (*clientp, "empty web query");
         goto out;
     }
+#if defined(_IS_UNIX)
+    // we need some header values, then the control API has its own methods and authentication
+    readRemoteHeader (clientp, rh);
+    if (!ro && isControlAPI (line)) {
+        serveControlAPI (clientp, line, rh.auth);
+        goto out;
+    }
+#endif // _IS_UNIX
     if (strncmp (line, "GET /", 5)) {
         Serial.println (line);
         sendHTTPError (*clientp, "Method Not Allowed");
         goto out;
     }
+#if defined(_IS_UNIX)
+    // everything else may require a password, and the API token too if it would change anything
+    if (!webAuthOk (clientp, skipget, rh.auth) || !commandTokenOk (clientp, skipget, rh.auth))
+        goto out;
 
+    // WebSocket clients are kept open for streaming
+    if (!ro && strncmp (skipget, "ws ", 3) == 0) {
+        if (acceptWebSocket (*clientp, rh.ws_key))
+            return;
+        goto out;
+    }
+#else
     // discard remainder of header
     (void) httpSkipHeader (*clientp);
+#endif // _IS_UNIX
 
     Serial.print (F("Command from "));
         Serial.print(clientp->remoteIP());
@@ -2388,6 +4805,66 @@ TouchType readCalTouchWS (SCoord &s)
 }
 
 
+/* run the given set_ command internally as if from a web client, for simulate.cpp.
+ * return whether command was found.
+ */
+bool runInternalWebCommand (const char *cmd)
+{
+    char buf[200];
+    if (strlen (cmd) >= sizeof(buf))
+        return (false);
+    strcpy (buf, cmd);
+    return (runWebserverCommand (NULL, false, buf));
+}
+
+#if defined(_IS_UNIX)
+
+/* send the given command to the web server of the HamClock already running on this host and copy its
+ * reply to stdout, for command line options. return whether it succeeded.
+ */
+bool sendLocalWebCommand (const char *cmd)
+{
+    if (!web_auth_loaded)
+        loadWebAuth();
+    if (!api_token_loaded)
+        loadAPIToken();
+
+    WiFiClient client;
+    if (!client.connect ("localhost", svr_port)) {
+        fprintf (stderr, "No HamClock web server at localhost:%d\n", svr_port);
+        return (false);
+    }
+
+    client.print ("GET /");
+    client.print (cmd);
+    client.print (" HTTP/1.0\r\n");
+    if (web_auth == WA_ON) {
+        client.print ("Authorization: Basic ");
+        client.print (web_basic);
+        client.print ("\r\n");
+    } else if (api_token[0] != '\0') {
+        client.print ("Authorization: Bearer ");
+        client.print (api_token);
+        client.print ("\r\n");
+    }
+    client.print ("\r\n");
+
+    // check status, skip rest of header then copy body
+    char line[200];
+    bool ok = getTCPLine (client, line, sizeof(line), NULL) && strstr (line, " 200 ") != NULL;
+    if (!ok)
+        fprintf (stderr, "%s\n", line);
+    while (getTCPLine (client, line, sizeof(line), NULL) && line[0] != '\0')
+        continue;
+    while (getTCPLine (client, line, sizeof(line), NULL))
+        printf ("%s\n", line);
+
+    client.stop();
+    return (ok);
+}
+
+#endif // _IS_UNIX
+
 /* called from main loop() to run another demo command if time.
  */
 void runNextDemoCommand()
@@ -0,0 +1,483 @@
+/* stream HamClock state to remote frontends over WebSocket on the web server port at /ws.
+ *
+ * each message is one text frame holding a JSON object. All have "type" and "t", the unix time sent,
+ * the rest depends on type:
+ *
+ *   hello    version call de_lat de_lng spot_age spot_fade  once on connect
+ *   spacewx  ssn sfi kp xray swind drap protons noaa        on connect then whenever any changes
+ *            flare cme_arrival cme_speed
+ *   spot     call khz mode spotter ut lat lng grid snr      each new DX cluster spot
+ *            watched sdr
+ *   sat      name az el range rate                          every WS_SAT_SECS while a sat is chosen
+ *   alert    kind details                                   kind is watch, alarm, countdown, swr or iss
+ *
+ * angles are degrees, range km, rate m/s, ut is HHMM, sdr is a link to the receiver set with set_websdr
+ * tuned to the spot. grid is the spotted station's locator, empty if unknown, and snr its dB report.
+ * noaa is the NOAA R, S and G scale now, eg "R0 S1 G2", flare the class of the largest recent flare,
+ * cme_arrival the unix time of the soonest predicted CME arrival and cme_speed its km/s.
+ * Spots also carry path, the favored SP, LP or GL, see getSpotPath(). spot_age is the
+ * minutes for spots to fade to spot_fade brightness then expire, as on the DX Cluster pane, 0 never. Fields whose
+ * value is unknown are null. Messages from the client are read and discarded. A client that falls more
+ * than WS_LAGBYTES behind is lagging: it misses other messages but its alerts wait in alertqueue.cpp
+ * until it catches up. Only on UNIX systems because ESP has too little memory to hold clients open.
+ */
+
+#include "HamClock.h"
+
+
+#if defined(_IS_UNIX)
+
+#define MAX_WS          4                       // max clients at once
+#define WS_SAT_SECS     5                       // satellite update interval
+#define WS_SPW_SECS     30                      // space weather change check interval
+#define WS_SPW_STALE    (24*3600)               // space weather older than this is unknown
+#define WS_LAGBYTES     (16*1024)               // unsent bytes at which a client is lagging
+#define WS_GUID         "258EAFA5-E914-47DA-95CA-C5AB0DC85B11"     // RFC 6455 magic
+
+// sanity check
+#if MAX_WS > AQ_MAXTAGS
+#error alertqueue.cpp can not tag each web socket client
+#endif
+
+static WiFiClient ws_clients[MAX_WS];           // connected clients, unused if !connected()
+static int n_ws;                                // n connected, just to skip work when none
+static bool ws_lagging[MAX_WS];                 // whether each client is lagging
+static time_t next_sat, next_spw;               // when to next check sat and space weather
+static char last_spw[300];                      // last spacewx message body sent
+
+
+/* compute the SHA-1 digest of the given bytes
+ */
+static void sha1 (const uint8_t *msg, size_t len, uint8_t digest[20])
+{
+    uint32_t h[5] = {0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0};
+    uint64_t nbits = (uint64_t)len * 8;
+    size_t n_blocks = (len + 8) / 64 + 1;
+
+    for (size_t b = 0; b < n_blocks; b++) {
+
+        // next block of message, padding and length
+        uint8_t block[64];
+        for (int i = 0; i < 64; i++) {
+            size_t j = b*64 + i;
+            if (j < len)
+                block[i] = msg[j];
+            else if (j == len)
+                block[i] = 0x80;
+            else if (b == n_blocks-1 && i >= 56)
+                block[i] = (uint8_t)(nbits >> (8*(63-i)));
+            else
+                block[i] = 0;
+        }
+
+        uint32_t w[80];
+        for (int i = 0; i < 16; i++)
+            w[i] = (uint32_t)block[4*i]<<24 | (uint32_t)block[4*i+1]<<16 | (uint32_t)block[4*i+2]<<8
+                                | block[4*i+3];
+        for (int i = 16; i < 80; i++) {
+            uint32_t x = w[i-3] ^ w[i-8] ^ w[i-14] ^ w[i-16];
+            w[i] = (x << 1) | (x >> 31);
+        }
+
+        uint32_t a = h[0], b_ = h[1], c = h[2], d = h[3], e = h[4];
+        for (int i = 0; i < 80; i++) {
+            uint32_t f, k;
+            if (i < 20) {
+                f = (b_ & c) | (~b_ & d);
+                k = 0x5A827999;
+            } else if (i < 40) {
+                f = b_ ^ c ^ d;
+                k = 0x6ED9EBA1;
+            } else if (i < 60) {
+                f = (b_ & c) | (b_ & d) | (c & d);
+                k = 0x8F1BBCDC;
+            } else {
+                f = b_ ^ c ^ d;
+                k = 0xCA62C1D6;
+            }
+            uint32_t t = ((a << 5) | (a >> 27)) + f + e + k + w[i];
+            e = d;
+            d = c;
+            c = (b_ << 30) | (b_ >> 2);
+            b_ = a;
+            a = t;
+        }
+        h[0] += a; h[1] += b_; h[2] += c; h[3] += d; h[4] += e;
+    }
+
+    for (int i = 0; i < 20; i++)
+        digest[i] = (uint8_t)(h[i/4] >> (24 - 8*(i%4)));
+}
+
+/* base64 encode the given bytes into out, which must hold at least 4*((len+2)/3)+1.
+ */
+void base64Encode (const uint8_t *in, size_t len, char *out)
+{
+    static const char b64[] = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    for (size_t i = 0; i < len; i += 3) {
+        uint32_t v = (uint32_t)in[i] << 16;
+        if (i+1 < len) v |= (uint32_t)in[i+1] << 8;
+        if (i+2 < len) v |= in[i+2];
+        *out++ = b64[(v >> 18) & 63];
+        *out++ = b64[(v >> 12) & 63];
+        *out++ = i+1 < len ? b64[(v >> 6) & 63] : '=';
+        *out++ = i+2 < len ? b64[v & 63] : '=';
+    }
+    *out = '\0';
+}
+
+/* copy str into out of size out_len as a quoted JSON string, truncating if necessary
+ */
+static void jsonStr (const char *str, char *out, size_t out_len)
+{
+    size_t l = 0;
+    out[l++] = '"';
+    for (; *str && l < out_len - 8; str++) {
+        unsigned char c = *str;
+        if (c == '"' || c == '\\')
+            l += snprintf (out+l, out_len-l, "\\%c", c);
+        else if (c < ' ')
+            l += snprintf (out+l, out_len-l, "\\u%04x", c);
+        else
+            out[l++] = c;
+    }
+    out[l++] = '"';
+    out[l] = '\0';
+}
+
+/* format the given value as a JSON number, or null if it is too old to be useful or never set
+ */
+static const char *jsonSPW (const SPWxValue &v, char *buf, size_t buf_len, const char *fmt)
+{
+    if (v.age > WS_SPW_STALE)
+        return ("null");
+    snprintf (buf, buf_len, fmt, v.value);
+    return (buf);
+}
+
+/* send the given JSON object body, ie, sans braces, of the given type to client i as one text frame.
+ * close the client if it fails.
+ * return whether it was sent.
+ */
+static bool wsSendOne (int i, const char *type, const char *body)
+{
+    WiFiClient &c = ws_clients[i];
+
+    char msg[400];
+    int len = snprintf (msg, sizeof(msg), "{\"type\":\"%s\",\"t\":%ld%s%s}", type, (long)now(),
+                                                body[0] ? "," : "", body);
+    if (len >= (int)sizeof(msg)) {
+        Serial.printf (_FX("WS: %s message too long\n"), type);
+        return (false);
+    }
+
+    // unmasked final text frame, all our messages fit in 16 bit length
+    uint8_t hdr[4];
+    int hdr_len;
+    hdr[0] = 0x81;
+    if (len < 126) {
+        hdr[1] = len;
+        hdr_len = 2;
+    } else {
+        hdr[1] = 126;
+        hdr[2] = len >> 8;
+        hdr[3] = len & 0xff;
+        hdr_len = 4;
+    }
+
+    if (c.write (hdr, hdr_len) != hdr_len || c.write ((uint8_t*)msg, len) != len) {
+        Serial.printf (_FX("WS: client %d send failed, closing\n"), i);
+        c.stop();
+        n_ws--;
+        alertQDiscard (AQ_WS, i);
+        return (false);
+    }
+
+    return (true);
+}
+
+/* return whether client i is lagging, noting when it starts and stops
+ */
+static bool wsLagging (int i)
+{
+    int unsent = ws_clients[i].unsent();
+    bool lagging = unsent > WS_LAGBYTES;
+    if (lagging != ws_lagging[i]) {
+        if (lagging) {
+            Serial.printf (_FX("WS: client %d lagging with %d bytes unsent\n"), i, unsent);
+            noteAlertQLag (AQ_WS);
+        } else
+            Serial.printf (_FX("WS: client %d caught up\n"), i);
+        ws_lagging[i] = lagging;
+    }
+    return (lagging);
+}
+
+/* send the given JSON object body of the given type to all connected clients that are not lagging
+ */
+static void wsSendAll (const char *type, const char *body)
+{
+    for (int i = 0; i < MAX_WS && n_ws > 0; i++)
+        if (ws_clients[i].connected() && !wsLagging (i))
+            wsSendOne (i, type, body);
+}
+
+/* send each alert waiting for client i until none remain or it lags again
+ */
+static void wsSendQueued (int i)
+{
+    AlertQItem item;
+    while (ws_clients[i].connected() && !wsLagging (i) && alertQPop (AQ_WS, i, item)) {
+        if (wsSendOne (i, "alert", item.data))
+            noteAlertQSent (AQ_WS, &item);
+        else
+            noteAlertQDropped (AQ_WS);
+    }
+}
+
+/* send the current space weather to all if it has changed since last sent
+ */
+static void wsSendSpaceWx()
+{
+    SpaceWeather spw;
+    getSpaceWeather (spw);
+
+    char ssn_s[20], sfi_s[20], kp_s[20], swind_s[20], drap_s[20], protons_s[20], xray_s[20];
+    if (spw.xray.age > WS_SPW_STALE || spw.xray_class[0] == '\0')
+        strcpy (xray_s, "null");
+    else
+        jsonStr (spw.xray_class, xray_s, sizeof(xray_s));
+
+    // current NOAA scales
+    char noaa_s[40] = "null";
+    if (spw.noaaspw_age <= WS_SPW_STALE)
+        snprintf (noaa_s, sizeof(noaa_s), "\"%c%d %c%d %c%d\"",
+                    spw.noaaspw.cat[0], spw.noaaspw.val[0][0], spw.noaaspw.cat[1], spw.noaaspw.val[1][0],
+                    spw.noaaspw.cat[2], spw.noaaspw.val[2][0]);
+
+    // largest recent flare and soonest CME
+    char flare_s[20] = "null";
+    if (spw.flares_age <= WS_SPW_STALE && spw.flare_class[0])
+        jsonStr (spw.flare_class, flare_s, sizeof(flare_s));
+    char cme_s[20] = "null", cme_speed_s[20] = "null";
+    if (spw.cmes_age <= WS_SPW_STALE && spw.cme_arrival) {
+        snprintf (cme_s, sizeof(cme_s), "%ld", (long)spw.cme_arrival);
+        snprintf (cme_speed_s, sizeof(cme_speed_s), "%d", spw.cme_speed);
+    }
+
+    char body[sizeof(last_spw)];
+    snprintf (body, sizeof(body),
+                "\"ssn\":%s,\"sfi\":%s,\"kp\":%s,\"xray\":%s,\"swind\":%s,\"drap\":%s,\"protons\":%s,"
+                "\"noaa\":%s,\"flare\":%s,\"cme_arrival\":%s,\"cme_speed\":%s",
+                jsonSPW (spw.ssn, ssn_s, sizeof(ssn_s), "%.0f"),
+                jsonSPW (spw.flux, sfi_s, sizeof(sfi_s), "%.0f"),
+                jsonSPW (spw.kp, kp_s, sizeof(kp_s), "%.1f"),
+                xray_s,
+                jsonSPW (spw.swind, swind_s, sizeof(swind_s), "%.1f"),
+                jsonSPW (spw.drap, drap_s, sizeof(drap_s), "%.1f"),
+                jsonSPW (spw.protons, protons_s, sizeof(protons_s), "%.1f"),
+                noaa_s, flare_s, cme_s, cme_speed_s);
+
+    if (strcmp (body, last_spw) != 0) {
+        wsSendAll ("spacewx", body);
+        strcpy (last_spw, body);
+    }
+}
+
+/* send the current satellite position to all, if one is chosen
+ */
+static void wsSendSat()
+{
+    float az, el, range, rate, raz, saz, rhrs, shrs;
+    char name[NV_SATNAME_LEN];
+    if (!getSatAzElNow (name, &az, &el, &range, &rate, &raz, &saz, &rhrs, &shrs))
+        return;
+
+    char name_s[2*NV_SATNAME_LEN+3];
+    jsonStr (name, name_s, sizeof(name_s));
+    char body[150];
+    snprintf (body, sizeof(body), "\"name\":%s,\"az\":%.1f,\"el\":%.1f,\"range\":%.0f,\"rate\":%.0f",
+                                        name_s, az, el, range, rate);
+    wsSendAll ("sat", body);
+}
+
+/* called with a new web client whose request was GET /ws with the given Sec-WebSocket-Key header value
+ * to complete the WebSocket handshake. If successful the client is kept for streaming and we return
+ * true, else we send an error and return false so caller closes it.
+ */
+bool acceptWebSocket (WiFiClient &client, const char *key)
+{
+    // find a free slot
+    int slot = -1;
+    for (int i = 0; i < MAX_WS && slot < 0; i++)
+        if (!ws_clients[i].connected())
+            slot = i;
+
+    const char *err = NULL;
+    if (key[0] == '\0')
+        err = "missing Sec-WebSocket-Key";
+    else if (slot < 0)
+        err = "too many WebSocket clients";
+    if (err) {
+        Serial.printf (_FX("WS: %s\n"), err);
+        FWIFIPRLN (client, F("HTTP/1.1 400 Bad request"));
+        FWIFIPRLN (client, F("Content-Type: text/plain; charset=us-ascii"));
+        FWIFIPRLN (client, F("Connection: close\r\n"));
+        client.println (err);
+        return (false);
+    }
+
+    // accept
+    char keyguid[strlen(key) + sizeof(WS_GUID)];
+    snprintf (keyguid, sizeof(keyguid), "%s%s", key, WS_GUID);
+    uint8_t digest[20];
+    sha1 ((uint8_t*)keyguid, strlen(keyguid), digest);
+    char accept[30];
+    base64Encode (digest, sizeof(digest), accept);
+
+    FWIFIPRLN (client, F("HTTP/1.1 101 Switching Protocols"));
+    FWIFIPRLN (client, F("Upgrade: websocket"));
+    FWIFIPRLN (client, F("Connection: Upgrade"));
+    client.print (F("Sec-WebSocket-Accept: "));
+    client.println (accept);
+    client.println();
+
+    ws_clients[slot] = client;
+    n_ws++;
+    ws_lagging[slot] = false;
+    alertQDiscard (AQ_WS, slot);
+    Serial.printf (_FX("WS: client %d connected from %s\n"), slot, client.remoteIP().c_str());
+
+    // hello, then current state soon to all
+    char call_s[2*NV_CALLSIGN_LEN+3];
+    jsonStr (getCallsign(), call_s, sizeof(call_s));
+    char body[200];
+    snprintf (body, sizeof(body), "\"version\":\"%s\",\"call\":%s,\"de_lat\":%.3f,\"de_lng\":%.3f,"
+                                        "\"spot_age\":%d,\"spot_fade\":%.2f",
+                                        HC_VERSION, call_s, de_ll.lat_d, de_ll.lng_d, getSpotAge(), SPOT_FADE_MIN);
+    wsSendOne (slot, "hello", body);
+    last_spw[0] = '\0';
+    next_spw = next_sat = 0;
+
+    return (true);
+}
+
+/* called often from main loop to discard client input, notice closed clients and send periodic state
+ */
+void checkWebSockets()
+{
+    if (n_ws == 0)
+        return;
+
+    // read and discard whatever clients send, read() closes on EOF
+    for (int i = 0; i < MAX_WS; i++) {
+        WiFiClient &c = ws_clients[i];
+        if (!c.connected())
+            continue;
+        while (c.available())
+            (void) c.read();
+        if (!c.connected()) {
+            Serial.printf (_FX("WS: client %d disconnected\n"), i);
+            n_ws--;
+            alertQDiscard (AQ_WS, i);
+        } else if (alertQDepth (AQ_WS, i) > 0)
+            wsSendQueued (i);
+    }
+
+    time_t t0 = now();
+    if (t0 >= next_spw) {
+        wsSendSpaceWx();
+        next_spw = t0 + WS_SPW_SECS;
+    }
+    if (t0 >= next_sat) {
+        wsSendSat();
+        next_sat = t0 + WS_SAT_SECS;
+    }
+}
+
+/* send the given new DX cluster spot to all clients
+ */
+void sendWebSocketSpot (const DXClusterSpot &spot)
+{
+    if (n_ws == 0)
+        return;
+
+    char call_s[2*MAX_SPOTCALL_LEN+3], mode_s[2*MAX_SPOTMODE_LEN+3], spotter_s[2*MAX_SPOTCALL_LEN+3];
+    jsonStr (spot.call, call_s, sizeof(call_s));
+    jsonStr (spot.mode, mode_s, sizeof(mode_s));
+    jsonStr (spot.spotter, spotter_s, sizeof(spotter_s));
+    char snr_s[10] = "null";
+    if (spot.snr != SPOT_NO_SNR)
+        snprintf (snr_s, sizeof(snr_s), "%d", spot.snr);
+    char link[NV_WEBSDR_LEN+30], sdr_s[2*sizeof(link)+3] = "null";
+    if (getSpotSDRLink (spot, link, sizeof(link)))
+        jsonStr (link, sdr_s, sizeof(sdr_s));
+    char body[500];
+    snprintf (body, sizeof(body),
+                "\"call\":%s,\"khz\":%.1f,\"mode\":%s,\"spotter\":%s,\"ut\":\"%04d\",\"lat\":%.3f,"
+                "\"lng\":%.3f,\"grid\":\"%s\",\"snr\":%s,\"watched\":%s,\"sdr\":%s,\"path\":\"%s\"",
+                call_s, spot.freq, mode_s, spotter_s, spot.uts, spot.ll.lat_d, spot.ll.lng_d,
+                spot.grid, snr_s, spot.watched ? "true" : "false", sdr_s, getSpotPathName (getSpotPath (spot)));
+    wsSendAll ("spot", body);
+}
+
+/* send the given alert to all clients. kind is watch, alarm, countdown, swr or iss; details may be empty.
+ */
+void sendWebSocketAlert (const char *kind, const char *details)
+{
+    if (n_ws == 0)
+        return;
+
+    char kind_s[30], details_s[203];
+    jsonStr (kind, kind_s, sizeof(kind_s));
+    jsonStr (details, details_s, sizeof(details_s));
+    char body[260];
+    snprintf (body, sizeof(body), "\"kind\":%s,\"details\":%s", kind_s, details_s);
+
+    // alerts wait for lagging clients, and behind any already waiting so they stay in order
+    for (int i = 0; i < MAX_WS && n_ws > 0; i++) {
+        if (!ws_clients[i].connected())
+            continue;
+        if (wsLagging (i) || alertQDepth (AQ_WS, i) > 0)
+            (void) alertQPush (AQ_WS, i, body, strlen(body)+1);
+        else if (wsSendOne (i, "alert", body))
+            noteAlertQSent (AQ_WS, NULL);
+        else
+            noteAlertQDropped (AQ_WS);
+    }
+}
+
+/* return number of connected clients
+ */
+int nWebSockets()
+{
+    return (n_ws);
+}
+
+#else // !_IS_UNIX
+
+/* dummy versions for systems that can not hold clients open
+ */
+
+void checkWebSockets()
+{
+}
+
+void sendWebSocketSpot (const DXClusterSpot &spot)
+{
+    (void) spot;
+}
+
+void sendWebSocketAlert (const char *kind, const char *details)
+{
+    (void) kind;
+    (void) details;
+}
+
+int nWebSockets()
+{
+    return (0);
+}
+
+#endif // _IS_UNIX
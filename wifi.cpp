@@ -36,6 +36,52 @@ static const char sf_page[] = "/ham/HamClock/solar-flux/solarflux-99.txt";
 #define SWIND_COLOR     RA8875_MAGENTA          // loading message text color
 static const char swind_page[] = "/ham/HamClock/solar-wind/swind-24hr.txt";
 
+// NOAA 27 day outlook, new data posted weekly
+#define OUTLOOK_INTERVAL   (6*3600+100)            // polling interval, secs
+
+// nearest ionosonde, new data posted every several minutes
+#define IONO_INTERVAL      (15*60)                 // polling interval, secs
+
+// recent solar flares
+#define FLARES_INTERVAL    (10*60)                 // polling interval, secs
+
+// predicted CME arrivals, also keeps the countdowns fresh
+#define CME_INTERVAL       (15*60)                 // polling interval, secs
+
+// band recommendations, computed locally from the latest space weather
+#define BANDREC_INTERVAL   (10*60)                 // refresh interval, secs
+
+// EME planner, computed locally
+#define EME_INTERVAL       (10*60)                 // refresh interval, secs
+
+// NCDXF beacon schedule, each beacon transmits for this long
+#define NCDXF_INTERVAL     10                      // refresh interval, secs
+
+// band plan, computed locally but may show the radio frequency
+#define BANDPLAN_INTERVAL  30                      // refresh interval, secs
+
+// DXpeditions, announcements change daily at most
+#define DXPEDS_INTERVAL    (3600+130)              // polling interval, secs
+
+// ARISS ISS events, read from a local file the user may edit at any time
+#define ARISS_INTERVAL     (300+17)                // reread interval, secs
+
+// plugins, each sets its own fetch period
+#define PLUGINS_INTERVAL   10                      // check interval, secs
+
+// QSO rate meter, nothing to fetch but rates decay and new QSOs arrive
+#define QSORATE_INTERVAL   10                      // redraw interval, secs
+
+// award progress, nothing to fetch but new QSOs arrive
+#define AWARDS_INTERVAL    30                      // redraw interval, secs
+
+// Tx meter, bars follow the readings, all redrawn now and then in case anything overwrote the pane
+#define TXMETER_INTERVAL   1                       // bars redraw interval, secs
+#define TXMETER_ALL        30                      // full redraw interval, secs
+
+// satellite sky plot, nothing to fetch but the satellite moves
+#define SATSKY_INTERVAL    5                       // redraw interval, secs
+
 // STEREO A image and info, new data posted every few hours
 #define STEREO_A_INTERVAL  3800                    // polling interval, secs
 #define STEREO_A_COLOR     RA8875_BLUE             // loading message text color
@@ -65,13 +111,15 @@ PropMapSetting prop_map = PROP_MAP_OFF;         // whether/how showing backgroun
 #define OTHER_MAPS_INTERVAL  (60*60)            // polling interval, secs
 
 // DRAP info, new data posted every few minutes
-#define DRAPPLOT_INTERVAL    (DRAPMAP_INTERVAL+5) // polling interval, secs. N.B. avoid race with MAP
+#define DRAPPLOT_DELAY       5                  // plot this long after map, secs, to avoid race
 #define DRAPPLOT_COLOR  RA8875_RED              // loading message text color
 static const char drap_page[] = "/ham/HamClock/drap/stats.txt";
 
 // NOAA RSG space weather scales
 #define NOAASWX_INTERVAL     3700               // polling interval, secs
 static const char noaaswx_page[] = "/ham/HamClock/NOAASpaceWX/noaaswx.txt";
+static const char protons_page[] = "/ham/HamClock/NOAASpaceWX/protons.txt";
+#define SCALE_MAXAGE         (3*3600)           // max age of a raw index to set a current NOAA scale, secs
 
 // geolocation web page
 static const char locip_page[] = "/ham/HamClock/fetchIPGeoloc.pl";
@@ -79,30 +127,41 @@ static const char locip_page[] = "/ham/HamClock/fetchIPGeoloc.pl";
 // SDO images
 #define SDO_INTERVAL    3200                    // polling interval, secs
 #define SDO_COLOR       RA8875_MAGENTA          // loading message text color
-// N.B. files must match order in plot_names[]
-static const char *sdo_filename[4] = {
+#define SDO_CYCLE_INTERVAL 600                  // SDO_Cycle rotation interval, secs
+#define SDO_CACHE_TTL   (refresh_srcs[RS_SDO].secs-60) // max age of locally cached image to reuse, secs
+#define SDO_N           5                       // n SDO images
+// N.B. files must match order in plot_names[], with SDO_5 last
+static const char *sdo_filename[SDO_N] = {
     #if defined(_CLOCK_1600x960) 
         "/ham/HamClock/SDO/f_211_193_171_340.bmp",
         "/ham/HamClock/SDO/latest_340_HMIIC.bmp",
         "/ham/HamClock/SDO/latest_340_HMIB.bmp",
         "/ham/HamClock/SDO/f_193_340.bmp",
+        "/ham/HamClock/SDO/f_304_340.bmp",
     #elif defined(_CLOCK_2400x1440)
         "/ham/HamClock/SDO/f_211_193_171_510.bmp",
         "/ham/HamClock/SDO/latest_510_HMIIC.bmp",
         "/ham/HamClock/SDO/latest_510_HMIB.bmp",
         "/ham/HamClock/SDO/f_193_510.bmp",
+        "/ham/HamClock/SDO/f_304_510.bmp",
     #elif defined(_CLOCK_3200x1920)
         "/ham/HamClock/SDO/f_211_193_171_680.bmp",
         "/ham/HamClock/SDO/latest_680_HMIIC.bmp",
         "/ham/HamClock/SDO/latest_680_HMIB.bmp",
         "/ham/HamClock/SDO/f_193_680.bmp",
+        "/ham/HamClock/SDO/f_304_680.bmp",
     #else
         "/ham/HamClock/SDO/f_211_193_171_170.bmp",
         "/ham/HamClock/SDO/latest_170_HMIIC.bmp",
         "/ham/HamClock/SDO/latest_170_HMIB.bmp",
         "/ham/HamClock/SDO/f_193_170.bmp",
+        "/ham/HamClock/SDO/f_304_170.bmp",
     #endif
 };
+static const char *sdo_label[SDO_N] = {
+    "Composite", "HMI 6173", "Magnetogram", "AIA 193", "AIA 304",
+};
+static int sdo_cycle_i;                         // index of next image shown by SDO_Cycle
 
 // weather displays
 #define DEWX_INTERVAL   1700                    // polling interval, secs
@@ -112,6 +171,57 @@ static const char *sdo_filename[4] = {
 #define MOON_INTERVAL   30                      // update interval, secs
 static bool moon_reverting;                     // flag for revertPlot1();
 
+// network data sources whose refresh interval the user may change in refresh_fn, one "name secs" per line.
+// none may be set faster than its minimum to protect the servers.
+typedef enum {
+    RS_KP,
+    RS_XRAY,
+    RS_SSN,
+    RS_FLUX,
+    RS_SWIND,
+    RS_DRAP,
+    RS_NOAASWX,
+    RS_OUTLOOK,
+    RS_IONO,
+    RS_FLARES,
+    RS_CME,
+    RS_DXPEDS,
+    RS_ARISS,
+    RS_STEREO_A,
+    RS_SDO,
+    RS_DEWX,
+    RS_DXWX,
+    RS_BC,
+    RS_VOACAP,
+    RS_MAPS,
+    RS_N
+} RefreshSourceID;
+static RefreshSource refresh_srcs[RS_N] = {     // N.B. must be in RefreshSourceID order
+    { "kp",             KP_INTERVAL,            1800,   KP_INTERVAL,            0, 0 },
+    { "xray",           XRAY_INTERVAL,          300,    XRAY_INTERVAL,          0, 0 },
+    { "ssn",            SSPOT_INTERVAL,         1800,   SSPOT_INTERVAL,         0, 0 },
+    { "flux",           FLUX_INTERVAL,          1800,   FLUX_INTERVAL,          0, 0 },
+    { "swind",          SWIND_INTERVAL,         300,    SWIND_INTERVAL,         0, 0 },
+    { "drap",           DRAPMAP_INTERVAL,       180,    DRAPMAP_INTERVAL,       0, 0 },
+    { "noaaswx",        NOAASWX_INTERVAL,       1800,   NOAASWX_INTERVAL,       0, 0 },
+    { "outlook",        OUTLOOK_INTERVAL,       3600,   OUTLOOK_INTERVAL,       0, 0 },
+    { "ionosonde",      IONO_INTERVAL,          300,    IONO_INTERVAL,          0, 0 },
+    { "flares",         FLARES_INTERVAL,        300,    FLARES_INTERVAL,        0, 0 },
+    { "cme",            CME_INTERVAL,           600,    CME_INTERVAL,           0, 0 },
+    { "dxpeds",         DXPEDS_INTERVAL,        1800,   DXPEDS_INTERVAL,        0, 0 },
+    { "ariss",          ARISS_INTERVAL,         1800,   ARISS_INTERVAL,         0, 0 },
+    { "stereo_a",       STEREO_A_INTERVAL,      1800,   STEREO_A_INTERVAL,      0, 0 },
+    { "sdo",            SDO_INTERVAL,           1800,   SDO_INTERVAL,           0, 0 },
+    { "dewx",           DEWX_INTERVAL,          600,    DEWX_INTERVAL,          0, 0 },
+    { "dxwx",           DXWX_INTERVAL,          600,    DXWX_INTERVAL,          0, 0 },
+    { "bc",             BC_INTERVAL,            1200,   BC_INTERVAL,            0, 0 },
+    { "voacap",         VOACAP_INTERVAL,        1200,   VOACAP_INTERVAL,        0, 0 },
+    { "maps",           OTHER_MAPS_INTERVAL,    1800,   OTHER_MAPS_INTERVAL,    0, 0 },
+};
+#if defined(_IS_UNIX)
+static const char refresh_fn[] = "refresh.txt";  // user's intervals in our_dir
+#endif
+
 
 // list of default NTP servers unless user has set their own
 static NTPServer ntp_list[] = {                 // init times to 0 insures all get tried initially
@@ -128,6 +238,14 @@ static NTPServer ntp_list[] = {                 // init times to 0 insures all g
 // web site retry interval, secs
 #define WIFI_RETRY      15
 
+// offline mode: fetching stops when the network monitor finds a problem, after OFFLINE_FAILS consecutive
+// connection failures, or when forced, and resumes as soon as the monitor finds the backend again
+#define OFFLINE_FAILS   3                       // consecutive connect failures that mean we are offline
+#define OFFLINE_PROBE   60                      // probe interval while offline, secs
+#define OFFLINE_TAG     60                      // pane data age redraw interval while offline, secs
+#define OFFLINE_COLOR   RGB565(255,160,0)       // offline label color
+#define MAX_BACKOFF     6                       // most doublings of WIFI_RETRY after repeated failures
+
 // pane auto rotation period in seconds -- most are the same but wx is longer
 #define ROTATION_INTERVAL       30
 #define ROTATION_WX_INTERVAL    60
@@ -142,6 +260,8 @@ static time_t next_sdo_1;
 static time_t next_sdo_2;
 static time_t next_sdo_3;
 static time_t next_sdo_4;
+static time_t next_sdo_5;
+static time_t next_sdo_cycle;
 static time_t next_noaaswx;
 static time_t next_dewx;
 static time_t next_dxwx;
@@ -157,16 +277,61 @@ static time_t next_bme280_d;
 static time_t next_swind;
 static time_t next_drap;
 static time_t next_stereo_a;
+static time_t next_outlook;
+static time_t next_iono;
+static time_t next_flares;
+static time_t next_cme;
+static time_t next_bandrec;
+static time_t next_eme;
+static time_t next_ncdxf;
+static time_t next_bandplan;
+static time_t next_dxpeds;
+static time_t next_plugins;
+static time_t next_qsorate;
+static time_t next_awards;
+static time_t next_txmeter, next_txmeter_all;
+static time_t next_satsky;
+static time_t next_ariss;
 
 // persisent space weather data and refresh time for use by getSpaceWeather()
 static time_t ssn_update, xray_update, flux_update, kp_update, noaa_update, swind_update;
-static time_t drap_update, path_update;
-static float ssn_spw, xray_spw, flux_spw, kp_spw, swind_spw, drap_spw;
+static time_t drap_update, path_update, protons_update;
+static float ssn_spw, xray_spw, flux_spw, kp_spw, swind_spw, drap_spw, protons_spw;
 static float path_spw[PROP_MAP_N]; 
 static NOAASpaceWx noaa_spw;
+static ClusterSpaceWx cluster_spw;                // latest from DX cluster WWV or WCY
+
+// latest values fetched from the backend, never from the cluster, to check cluster reports against
+#define BACKEND_CHECK_AGE   (6*3600)              // don't check against backend values older than this, secs
+static float backend_sfi, backend_kp, backend_ssn;
+static time_t backend_sfi_t, backend_kp_t, backend_ssn_t;
+static char flare_spw[8];                         // largest recent flare class
+static time_t flare_peak, flares_update;          // its peak and when flares were read
+static time_t cme_arrival, cmes_update;           // soonest CME arrival and when CMEs were read
+static int cme_speed;                             // its speed, km/s
+
+// what is known so far about why the refresh in progress failed, see nextRetry()
+static FetchError fetch_err;
+static bool fetch_header_ok;                      // set when response header was read
+static long fetch_content_len;                    // Content-Length of response, -1 if none
+static long fetch_body_n;                         // body bytes read with getChar()
+static bool fetch_body_short;                     // set when body timed out or ended early
+
+// offline state
+static uint8_t offline_forced;                    // NV_OFFLINE cache: whether offline regardless
+static bool offline_init;                         // set once offline_forced is loaded from NV
+static bool offline;                              // whether we are not fetching now
+static time_t offline_since;                      // when offline began
+static time_t next_offline_probe;                 // when to next check whether we are back online
+static int n_connect_fails;                       // consecutive fetches that could not connect
+static PlotChoice offline_ch[PANE_N];             // choice each pane showed while offline
+static time_t next_offline_tag[PANE_N];           // when to next redraw each pane's data age
 
 // local funcs
 static bool updateKp(SBox &box);
+static void checkBGKp (time_t t0);
+static void checkBGARISS (time_t t0);
+static void checkBGIono (time_t t0);
 static bool updateXRay(const SBox &box);
 static bool updateSDO (const SBox &box, PlotChoice ch);
 static bool updateSTEREO_A (const SBox &box);
@@ -192,6 +357,318 @@ static time_t nextWiFiRetry()
     return (prev_try);
 }
 
+/* forget what was noted about the last fetch, call before starting a refresh.
+ */
+static void resetFetchError()
+{
+    memset (&fetch_err, 0, sizeof(fetch_err));
+    fetch_header_ok = false;
+    fetch_content_len = -1;
+    fetch_body_n = 0;
+    fetch_body_short = false;
+}
+
+/* note the given source was just refreshed successfully and return when it is next due.
+ */
+static time_t nextRefresh (RefreshSourceID id)
+{
+    RefreshSource &rs = refresh_srcs[id];
+    rs.last_ok = now();
+    rs.n_fails = 0;
+    n_connect_fails = 0;
+    resetFetchError();
+    return (rs.last_ok + rs.secs);
+}
+
+/* classify what was noted about the failed refresh of the given source into its err and log it.
+ */
+static void captureFetchError (RefreshSource &rs)
+{
+    FetchError &fe = fetch_err;
+
+    if (!fe.page[0]) {
+        fe.kind = FE_CONNECT;
+        fe.retryable = true;
+    } else if (fe.http_status >= 400) {
+        fe.kind = FE_HTTP;
+        fe.retryable = fe.http_status >= 500 || fe.http_status == 408 || fe.http_status == 429;
+    } else if (!fetch_header_ok) {
+        fe.kind = FE_HEADER;
+        fe.retryable = true;
+    } else if (fetch_body_short) {
+        // network trouble mid transfer, likely to pass
+        fe.kind = FE_SHORT;
+        fe.retryable = true;
+    } else {
+        // bad content is unlikely to be fixed soon
+        fe.kind = FE_DATA;
+        fe.retryable = false;
+    }
+    fe.t = now();
+    n_connect_fails = fe.kind == FE_CONNECT ? n_connect_fails + 1 : 0;
+
+    rs.err = fe;
+    Serial.printf (_FX("Refresh: %s failed: %s%s, HTTP %d, page %s: %s\n"), rs.name,
+                fetchErrorName (fe.kind), fe.retryable ? "" : _FX(" not retryable"), fe.http_status,
+                fe.page[0] ? fe.page : "-", fe.detail[0] ? fe.detail : "-");
+
+    resetFetchError();
+}
+
+/* note the given source just failed to refresh and return when to try again, doubling the wait with
+ * each consecutive failure but never longer than its normal interval, or the full interval if the
+ * failure is not one that retrying soon might fix.
+ */
+static time_t nextRetry (RefreshSourceID id)
+{
+    RefreshSource &rs = refresh_srcs[id];
+    captureFetchError (rs);
+    int backoff = WIFI_RETRY << (rs.n_fails < MAX_BACKOFF ? rs.n_fails : MAX_BACKOFF);
+    if (backoff > rs.secs || !rs.err.retryable)
+        backoff = rs.secs;
+    rs.n_fails++;
+
+    time_t next_try = nextWiFiRetry();
+    time_t next_backoff = now() + backoff;
+    return (next_try > next_backoff ? next_try : next_backoff);
+}
+
+/* return when the given source's last good refresh expires, 0 if never refreshed.
+ */
+static time_t refreshExpires (RefreshSourceID id)
+{
+    const RefreshSource &rs = refresh_srcs[id];
+    return (rs.last_ok ? rs.last_ok + rs.secs : 0);
+}
+
+/* return the source of data fetched for the given pane choice, RS_N if it fetches nothing.
+ */
+static RefreshSourceID paneSource (PlotChoice ch)
+{
+    switch (ch) {
+    case PLOT_CH_BC:            return (RS_BC);
+    case PLOT_CH_DEWX:          return (RS_DEWX);
+    case PLOT_CH_DXWX:          return (RS_DXWX);
+    case PLOT_CH_FLUX:          return (RS_FLUX);
+    case PLOT_CH_KP:            return (RS_KP);
+    case PLOT_CH_NOAASWX:       return (RS_NOAASWX);
+    case PLOT_CH_SSN:           return (RS_SSN);
+    case PLOT_CH_XRAY:          return (RS_XRAY);
+    case PLOT_CH_SDO_1:         // fallthru
+    case PLOT_CH_SDO_2:         // fallthru
+    case PLOT_CH_SDO_3:         // fallthru
+    case PLOT_CH_SDO_4:         // fallthru
+    case PLOT_CH_SDO_5:         // fallthru
+    case PLOT_CH_SDO_CYCLE:     return (RS_SDO);
+    case PLOT_CH_SOLWIND:       return (RS_SWIND);
+    case PLOT_CH_DRAP:          return (RS_DRAP);
+    case PLOT_CH_STEREO_A:      return (RS_STEREO_A);
+    case PLOT_CH_OUTLOOK:       return (RS_OUTLOOK);
+    case PLOT_CH_IONOSONDE:     return (RS_IONO);
+    case PLOT_CH_FLARES:        return (RS_FLARES);
+    case PLOT_CH_CME:           return (RS_CME);
+    case PLOT_CH_DXPEDS:        return (RS_DXPEDS);
+    case PLOT_CH_ARISS:         return (RS_ARISS);
+    default:                    return (RS_N);
+    }
+}
+
+/* load offline_forced from NV if not already
+ */
+static void loadOfflineMode()
+{
+    if (offline_init)
+        return;
+
+    if (!NVReadUInt8 (NV_OFFLINE, &offline_forced)) {
+        offline_forced = 0;
+        NVWriteUInt8 (NV_OFFLINE, offline_forced);
+    }
+    offline_init = true;
+}
+
+/* start offline mode: each pane keeps what it shows, labeled with its age.
+ */
+static void goOffline (const char *why)
+{
+    offline = true;
+    offline_since = now();
+    next_offline_probe = offline_since + OFFLINE_PROBE;
+    for (int i = 0; i < PANE_N; i++) {
+        offline_ch[i] = plot_ch[i];
+        next_offline_tag[i] = 0;
+    }
+    Serial.printf (_FX("Offline: %s\n"), why);
+}
+
+/* end offline mode and refresh everything
+ */
+static void goOnline()
+{
+    offline = false;
+    n_connect_fails = 0;
+    Serial.printf (_FX("Offline: back online after %ld s\n"), (long)(now() - offline_since));
+    initWiFiRetry();
+}
+
+/* enter or leave offline mode as needed, checking the network now and then while offline.
+ */
+static void checkOffline()
+{
+    loadOfflineMode();
+
+    if (offline_forced) {
+        if (!offline)
+            goOffline ("forced");
+        return;
+    }
+
+    if (!offline) {
+        // stop all fetches at once as soon as the network monitor finds a problem, or check now if
+        // fetches are failing on their own
+        NetState ns = pollNetState();
+        if (ns == NET_OK && n_connect_fails >= OFFLINE_FAILS) {
+            ns = checkNetState();
+            if (ns == NET_OK)
+                goOffline ("connections are failing");
+        }
+        if (ns != NET_OK)
+            goOffline (getNetStateName (ns));
+        return;
+    }
+
+    if (now() < next_offline_probe)
+        return;
+    if (checkNetState() == NET_OK)
+        goOnline();
+    else
+        next_offline_probe = now() + OFFLINE_PROBE;
+}
+
+/* draw how old the data shown in the given pane is, from the given source.
+ */
+static void drawPaneAge (const SBox &box, RefreshSourceID id)
+{
+    const RefreshSource &rs = refresh_srcs[id];
+    char label[40];
+    if (rs.last_ok) {
+        long age = now() - rs.last_ok;
+        if (age >= 2*3600)
+            snprintf (label, sizeof(label), xlate ("Offline, %ldh old"), age/3600);
+        else
+            snprintf (label, sizeof(label), xlate ("Offline, %ldm old"), age/60);
+    } else
+        snprintf (label, sizeof(label), "%s", xlate ("Offline, no data"));
+
+    selectFontStyle (LIGHT_FONT, FAST_FONT);
+    uint16_t lw = getTextWidth (label);
+    uint16_t x = box.x + (box.w - lw)/2;
+    uint16_t y = box.y + box.h - 11;
+    tft.fillRect (x - 2, y - 1, lw + 4, 10, RA8875_BLACK);
+    tft.setTextColor (OFFLINE_COLOR);
+    tft.setCursor (x, y);
+    tft.print (label);
+}
+
+/* while offline, handle the given pane instead of refreshing it if it shows fetched data: leave what it
+ * shows, or say there is nothing new if it was chosen while offline, and label it with the data age.
+ * return whether we handled it.
+ */
+static bool showOfflinePane (PlotPane pp, PlotChoice ch)
+{
+    RefreshSourceID id = paneSource (ch);
+    if (!offline || id == RS_N)
+        return (false);
+
+    if (ch != offline_ch[pp]) {
+        plotMessage (plot_b[pp], OFFLINE_COLOR, xlate ("Offline, waiting for network"));
+        offline_ch[pp] = ch;
+        next_offline_tag[pp] = 0;
+    }
+    if (now() >= next_offline_tag[pp]) {
+        drawPaneAge (plot_b[pp], id);
+        next_offline_tag[pp] = now() + OFFLINE_TAG;
+    }
+
+    return (true);
+}
+
+/* set whether to stay offline regardless of connectivity.
+ * turning it off lets the next fetches decide.
+ */
+void setOfflineMode (bool forced)
+{
+    loadOfflineMode();
+    offline_forced = forced;
+    NVWriteUInt8 (NV_OFFLINE, offline_forced);
+    if (!forced && offline)
+        goOnline();
+}
+
+/* return whether we are offline now, and pass back whether forced and since when.
+ */
+bool getOfflineMode (bool &forced, time_t &since)
+{
+    loadOfflineMode();
+    forced = offline_forced;
+    since = offline_since;
+    return (offline);
+}
+
+/* refresh the Kp and solar flux panes soon, as when a simulation changes what they show
+ */
+void refreshSWxPanes (void)
+{
+    next_kp = 0;
+    next_flux = 0;
+}
+
+/* return whether we are offline now, so nothing should be fetched from the network
+ */
+bool isOffline (void)
+{
+    return (offline);
+}
+
+/* set the refresh interval of each source from refresh_fn, if any, else its default.
+ */
+static void loadRefreshIntervals()
+{
+    for (int i = 0; i < RS_N; i++)
+        refresh_srcs[i].secs = refresh_srcs[i].def_secs;
+
+#if defined(_IS_UNIX)
+    std::string fn = our_dir + refresh_fn;
+    FILE *fp = fopen (fn.c_str(), "r");
+    if (!fp)
+        return;
+
+    char line[100], name[50];
+    int secs;
+    while (fgets (line, sizeof(line), fp)) {
+        if (line[0] == '#' || sscanf (line, "%49s %d", name, &secs) != 2)
+            continue;
+        int i;
+        for (i = 0; i < RS_N; i++)
+            if (strcmp (name, refresh_srcs[i].name) == 0)
+                break;
+        if (i == RS_N) {
+            Serial.printf (_FX("%s: unknown source %s\n"), refresh_fn, name);
+            continue;
+        }
+        RefreshSource &rs = refresh_srcs[i];
+        if (secs < rs.min_secs) {
+            Serial.printf (_FX("%s: %s %d s is too fast, using %d\n"), refresh_fn, name, secs, rs.min_secs);
+            secs = rs.min_secs;
+        }
+        rs.secs = secs;
+        Serial.printf (_FX("%s: %s every %d s\n"), refresh_fn, name, secs);
+    }
+
+    fclose (fp);
+#endif
+}
+
 /* return when next to rotate the given pane.
  * rotations are spaced out to avoid swamping the server or supporting service.
  */
@@ -399,6 +876,7 @@ static void initWiFi (bool verbose)
     if (WiFi.status() == WL_CONNECTED || !strcmp (mac, mac_lh)) {
         tftMsg (verbose, 0, _FX("Start web server"));
         initWebServer();
+        initMDNS();
     } else {
         tftMsg (verbose, 0, _FX("No web server"));
     }
@@ -562,11 +1040,11 @@ void checkBandConditions (const SBox &b, bool force)
 
     if (updateBandConditions(b)) {
         // worked ok so reschedule later
-        next_bc = now() + BC_INTERVAL;
+        next_bc = nextRefresh (RS_BC);
         bc_hour = hour(nowWO());
     } else {
         // retry
-        next_bc = nextWiFiRetry();
+        next_bc = nextRetry (RS_BC);
     }
 }
 
@@ -578,6 +1056,9 @@ static void checkMap(void)
     PlotPane bc_pp = findPaneChoiceNow (PLOT_CH_BC);
     bool bc_up = bc_pp != PANE_NONE;
 
+    // nothing fetched yet
+    resetFetchError();
+
     // check VOACAP first
     if (prop_map != PROP_MAP_OFF) {
 
@@ -591,7 +1072,7 @@ static void checkMap(void)
             // update prop map
             bool ok = installPropMaps (propMap2MHz (prop_map));
             if (ok) {
-                next_map = now() + VOACAP_INTERVAL;             // schedule normal refresh
+                next_map = nextRefresh (RS_VOACAP);             // schedule normal refresh
                 map_hour = hour(nowWO());                       // map is now current
                 initEarthMap();
 
@@ -599,7 +1080,7 @@ static void checkMap(void)
                 if (findPaneChoiceNow(PLOT_CH_DRAP) != PANE_NONE)
                     next_drap = now();
             } else {
-                next_map = nextWiFiRetry();                     // schedule retry
+                next_map = nextRetry (RS_VOACAP);               // schedule retry
                 map_hour = bc_hour;                             // match bc to avoid immediate retry
             }
 
@@ -619,14 +1100,16 @@ static void checkMap(void)
             if (installBackgroundMaps (false, core_map, &downloaded)) {
                 // schedule next refresh
                 if (core_map == CM_DRAP) {
-                    next_map = now() + DRAPMAP_INTERVAL;
+                    next_map = nextRefresh (RS_DRAP);
                 } else {
-                    next_map = now() + OTHER_MAPS_INTERVAL;
+                    next_map = nextRefresh (RS_MAPS);
                 }
                 if (downloaded)
                     initEarthMap();                             // avoid redraw if no change
-            } else
-                next_map = nextWiFiRetry();                     // schedule retry
+            } else {
+                // schedule retry
+                next_map = nextRetry (core_map == CM_DRAP ? RS_DRAP : RS_MAPS);
+            }
 
             Serial.printf (_FX("Next %s map check in %ld s at %ld\n"), map_styles[core_map],
                                         next_map - now(), next_map);
@@ -783,6 +1266,12 @@ static void revertPlot1 (uint32_t dt)
     case PLOT_CH_SDO_4:
         next_sdo_4 = revert_t;
         break;
+    case PLOT_CH_SDO_5:
+        next_sdo_5 = revert_t;
+        break;
+    case PLOT_CH_SDO_CYCLE:
+        next_sdo_cycle = revert_t;
+        break;
     case PLOT_CH_TEMPERATURE:
         next_bme280_t = revert_t;
         break;
@@ -807,6 +1296,51 @@ static void revertPlot1 (uint32_t dt)
     case PLOT_CH_STEREO_A:
         next_stereo_a = revert_t;
         break;
+    case PLOT_CH_OUTLOOK:
+        next_outlook = revert_t;
+        break;
+    case PLOT_CH_IONOSONDE:
+        next_iono = revert_t;
+        break;
+    case PLOT_CH_FLARES:
+        next_flares = revert_t;
+        break;
+    case PLOT_CH_CME:
+        next_cme = revert_t;
+        break;
+    case PLOT_CH_BANDREC:
+        next_bandrec = revert_t;
+        break;
+    case PLOT_CH_EME:
+        next_eme = revert_t;
+        break;
+    case PLOT_CH_NCDXF:
+        next_ncdxf = revert_t;
+        break;
+    case PLOT_CH_BANDPLAN:
+        next_bandplan = revert_t;
+        break;
+    case PLOT_CH_DXPEDS:
+        next_dxpeds = revert_t;
+        break;
+    case PLOT_CH_PLUGINS:
+        next_plugins = revert_t;
+        break;
+    case PLOT_CH_QSORATE:
+        next_qsorate = revert_t;
+        break;
+    case PLOT_CH_AWARDS:
+        next_awards = revert_t;
+        break;
+    case PLOT_CH_TXMETER:
+        next_txmeter = next_txmeter_all = revert_t;
+        break;
+    case PLOT_CH_SATSKY:
+        next_satsky = revert_t;
+        break;
+    case PLOT_CH_ARISS:
+        next_ariss = revert_t;
+        break;
     default:
         fatalError(_FX("Bug! revertPlot1() choice %d"), plot_ch[PANE_1]);
         break;
@@ -947,6 +1481,16 @@ bool setPlotChoice (PlotPane pp, PlotChoice ch)
         next_sdo_4 = 0;
         break;
 
+    case PLOT_CH_SDO_5:
+        plot_ch[pp] = ch;
+        next_sdo_5 = 0;
+        break;
+
+    case PLOT_CH_SDO_CYCLE:
+        plot_ch[pp] = ch;
+        next_sdo_cycle = 0;
+        break;
+
     case PLOT_CH_SOLWIND:
         plot_ch[pp] = ch;
         next_swind = 0;
@@ -970,6 +1514,81 @@ bool setPlotChoice (PlotPane pp, PlotChoice ch)
         next_stereo_a = 0;
         break;
 
+    case PLOT_CH_OUTLOOK:
+        plot_ch[pp] = ch;
+        next_outlook = 0;
+        break;
+
+    case PLOT_CH_IONOSONDE:
+        plot_ch[pp] = ch;
+        next_iono = 0;
+        break;
+
+    case PLOT_CH_FLARES:
+        plot_ch[pp] = ch;
+        next_flares = 0;
+        break;
+
+    case PLOT_CH_CME:
+        plot_ch[pp] = ch;
+        next_cme = 0;
+        break;
+
+    case PLOT_CH_BANDREC:
+        plot_ch[pp] = ch;
+        next_bandrec = 0;
+        break;
+
+    case PLOT_CH_EME:
+        plot_ch[pp] = ch;
+        next_eme = 0;
+        break;
+
+    case PLOT_CH_NCDXF:
+        plot_ch[pp] = ch;
+        next_ncdxf = 0;
+        break;
+
+    case PLOT_CH_BANDPLAN:
+        plot_ch[pp] = ch;
+        next_bandplan = 0;
+        break;
+
+    case PLOT_CH_DXPEDS:
+        plot_ch[pp] = ch;
+        next_dxpeds = 0;
+        break;
+
+    case PLOT_CH_PLUGINS:
+        plot_ch[pp] = ch;
+        next_plugins = 0;
+        break;
+
+    case PLOT_CH_QSORATE:
+        plot_ch[pp] = ch;
+        next_qsorate = 0;
+        break;
+
+    case PLOT_CH_AWARDS:
+        plot_ch[pp] = ch;
+        next_awards = 0;
+        break;
+
+    case PLOT_CH_TXMETER:
+        plot_ch[pp] = ch;
+        next_txmeter = next_txmeter_all = 0;
+        break;
+
+    case PLOT_CH_SATSKY:
+        plot_ch[pp] = ch;
+        next_satsky = 0;
+        break;
+
+    case PLOT_CH_ARISS:
+        plot_ch[pp] = ch;
+        next_ariss = 0;
+        break;
+
     default:
         fatalError (_FX("setPlotChoice() PlotPane %d, PlotChoice %d"), (int)pp, (int)ch);
         break;
@@ -1003,6 +1622,9 @@ void updateWiFi(void)
     // time now
     time_t t0 = now();
 
+    // decide whether to fetch at all
+    checkOffline();
+
     // update each pane
     for (int i = PANE_1; i < PANE_N; i++) {
 
@@ -1011,6 +1633,9 @@ void updateWiFi(void)
         PlotPane pp = (PlotPane)i;
         bool new_rot_ch = false;
 
+        // start each pane with a clean fetch error record
+        resetFetchError();
+
         // rotate if this pane is rotating and it's time
         if (paneIsRotating(pp) && t0 >= next_rotationT[i]) {
             setPlotChoice (pp, getNextRotationChoice(pp, plot_ch[pp]));
@@ -1018,6 +1643,13 @@ void updateWiFi(void)
             ch = plot_ch[pp];
         }
 
+        // while offline, panes of fetched data just show how old they are
+        if (showOfflinePane (pp, ch)) {
+            if (new_rot_ch)
+                showRotatingBorder (true, pp);
+            continue;
+        }
+
         switch (ch) {
 
         case PLOT_CH_BC:
@@ -1027,9 +1659,9 @@ void updateWiFi(void)
         case PLOT_CH_DEWX:
             if (t0 >= next_dewx) {
                 if (updateDEWX(box))
-                    next_dewx = now() + DEWX_INTERVAL;
+                    next_dewx = nextRefresh (RS_DEWX);
                 else
-                    next_dewx = nextWiFiRetry();
+                    next_dewx = nextRetry (RS_DEWX);
             }
             break;
 
@@ -1045,27 +1677,27 @@ void updateWiFi(void)
         case PLOT_CH_DXWX:
             if (t0 >= next_dxwx) {
                 if (updateDXWX(box))
-                    next_dxwx = now() + DXWX_INTERVAL;
+                    next_dxwx = nextRefresh (RS_DXWX);
                 else
-                    next_dxwx = nextWiFiRetry();
+                    next_dxwx = nextRetry (RS_DXWX);
             }
             break;
 
         case PLOT_CH_FLUX:
             if (t0 >= next_flux) {
                 if (updateSolarFlux(box))
-                    next_flux = now() + FLUX_INTERVAL;
+                    next_flux = nextRefresh (RS_FLUX);
                 else
-                    next_flux = nextWiFiRetry();
+                    next_flux = nextRetry (RS_FLUX);
             }
             break;
 
         case PLOT_CH_KP:
             if (t0 >= next_kp) {
                 if (updateKp(box))
-                    next_kp = now() + KP_INTERVAL;
+                    next_kp = nextRefresh (RS_KP);
                 else
-                    next_kp = nextWiFiRetry();
+                    next_kp = nextRetry (RS_KP);
             }
             break;
 
@@ -1080,27 +1712,27 @@ void updateWiFi(void)
         case PLOT_CH_NOAASWX:
             if (t0 >= next_noaaswx) {
                 if (updateNOAASWx(box))
-                    next_noaaswx = now() + NOAASWX_INTERVAL;
+                    next_noaaswx = nextRefresh (RS_NOAASWX);
                 else
-                    next_noaaswx = nextWiFiRetry();
+                    next_noaaswx = nextRetry (RS_NOAASWX);
             }
             break;
 
         case PLOT_CH_SSN:
             if (t0 >= next_ssn) {
                 if (updateSunSpots(box))
-                    next_ssn = now() + SSPOT_INTERVAL;
+                    next_ssn = nextRefresh (RS_SSN);
                 else
-                    next_ssn = nextWiFiRetry();
+                    next_ssn = nextRetry (RS_SSN);
             }
             break;
 
         case PLOT_CH_XRAY:
             if (t0 >= next_xray) {
                 if (updateXRay(box))
-                    next_xray = now() + XRAY_INTERVAL;
+                    next_xray = nextRefresh (RS_XRAY);
                 else
-                    next_xray = nextWiFiRetry();
+                    next_xray = nextRetry (RS_XRAY);
             }
             break;
 
@@ -1148,54 +1780,72 @@ void updateWiFi(void)
         case PLOT_CH_SDO_1:
             if (t0 >= next_sdo_1) {
                 if (updateSDO(box, ch))
-                    next_sdo_1 = now() + SDO_INTERVAL;
+                    next_sdo_1 = nextRefresh (RS_SDO);
                 else
-                    next_sdo_1 = nextWiFiRetry();
+                    next_sdo_1 = nextRetry (RS_SDO);
             }
             break;
 
         case PLOT_CH_SDO_2:
             if (t0 >= next_sdo_2) {
                 if (updateSDO(box, ch))
-                    next_sdo_2 = now() + SDO_INTERVAL;
+                    next_sdo_2 = nextRefresh (RS_SDO);
                 else
-                    next_sdo_2 = nextWiFiRetry();
+                    next_sdo_2 = nextRetry (RS_SDO);
             }
             break;
 
         case PLOT_CH_SDO_3:
             if (t0 >= next_sdo_3) {
                 if (updateSDO(box, ch))
-                    next_sdo_3 = now() + SDO_INTERVAL;
+                    next_sdo_3 = nextRefresh (RS_SDO);
                 else
-                    next_sdo_3 = nextWiFiRetry();
+                    next_sdo_3 = nextRetry (RS_SDO);
             }
             break;
 
         case PLOT_CH_SDO_4:
             if (t0 >= next_sdo_4) {
                 if (updateSDO(box, ch))
-                    next_sdo_4 = now() + SDO_INTERVAL;
+                    next_sdo_4 = nextRefresh (RS_SDO);
                 else
-                    next_sdo_4 = nextWiFiRetry();
+                    next_sdo_4 = nextRetry (RS_SDO);
+            }
+            break;
+
+        case PLOT_CH_SDO_5:
+            if (t0 >= next_sdo_5) {
+                if (updateSDO(box, ch))
+                    next_sdo_5 = nextRefresh (RS_SDO);
+                else
+                    next_sdo_5 = nextRetry (RS_SDO);
+            }
+            break;
+
+        case PLOT_CH_SDO_CYCLE:
+            if (t0 >= next_sdo_cycle) {
+                if (updateSDO(box, ch))
+                    next_sdo_cycle = now() + SDO_CYCLE_INTERVAL;
+                else
+                    next_sdo_cycle = nextRetry (RS_SDO);
             }
             break;
 
         case PLOT_CH_SOLWIND:
             if (t0 >= next_swind) {
                 if (updateSolarWind(box))
-                    next_swind = now() + SWIND_INTERVAL;
+                    next_swind = nextRefresh (RS_SWIND);
                 else
-                    next_swind = nextWiFiRetry();
+                    next_swind = nextRetry (RS_SWIND);
             }
             break;
 
         case PLOT_CH_DRAP:
             if (t0 >= next_drap) {
                 if (updateDRAPPlot(box))
-                    next_drap = now() + DRAPPLOT_INTERVAL;
+                    next_drap = nextRefresh (RS_DRAP) + DRAPPLOT_DELAY;
                 else
-                    next_drap = nextWiFiRetry();
+                    next_drap = nextRetry (RS_DRAP);
             }
             break;
 
@@ -1206,9 +1856,141 @@ void updateWiFi(void)
         case PLOT_CH_STEREO_A:
             if (t0 >= next_stereo_a) {
                 if (updateSTEREO_A(box))
-                    next_stereo_a = now() + STEREO_A_INTERVAL;
+                    next_stereo_a = nextRefresh (RS_STEREO_A);
+                else
+                    next_stereo_a = nextRetry (RS_STEREO_A);
+            }
+            break;
+
+        case PLOT_CH_OUTLOOK:
+            if (t0 >= next_outlook) {
+                // just redraw our copy if still good, eg, after switching panes
+                if (t0 < refreshExpires (RS_OUTLOOK) && updateOutlook (box, false))
+                    next_outlook = refreshExpires (RS_OUTLOOK);
+                else if (updateOutlook (box, true))
+                    next_outlook = nextRefresh (RS_OUTLOOK);
+                else
+                    next_outlook = nextRetry (RS_OUTLOOK);
+            }
+            break;
+
+        case PLOT_CH_IONOSONDE:
+            if (t0 >= next_iono) {
+                if (updateIonosonde(box))
+                    next_iono = nextRefresh (RS_IONO);
+                else
+                    next_iono = nextRetry (RS_IONO);
+            }
+            break;
+
+        case PLOT_CH_FLARES:
+            if (t0 >= next_flares) {
+                if (updateFlares(box))
+                    next_flares = nextRefresh (RS_FLARES);
+                else
+                    next_flares = nextRetry (RS_FLARES);
+            }
+            break;
+
+        case PLOT_CH_CME:
+            if (t0 >= next_cme) {
+                if (updateCME(box))
+                    next_cme = nextRefresh (RS_CME);
+                else
+                    next_cme = nextRetry (RS_CME);
+            }
+            break;
+
+        case PLOT_CH_BANDREC:
+            if (t0 >= next_bandrec) {
+                if (updateBandRec(box))
+                    next_bandrec = now() + BANDREC_INTERVAL;
+                else
+                    next_bandrec = nextWiFiRetry();
+            }
+            break;
+
+        case PLOT_CH_EME:
+            if (t0 >= next_eme) {
+                if (updateEME(box))
+                    next_eme = now() + EME_INTERVAL;
+                else
+                    next_eme = nextWiFiRetry();
+            }
+            break;
+
+        case PLOT_CH_NCDXF:
+            if (t0 >= next_ncdxf) {
+                // stay in step with the beacon schedule
+                if (updateBeaconPane(box))
+                    next_ncdxf = now() + NCDXF_INTERVAL - nowWO()%NCDXF_INTERVAL;
+                else
+                    next_ncdxf = nextWiFiRetry();
+            }
+            break;
+
+        case PLOT_CH_BANDPLAN:
+            if (t0 >= next_bandplan) {
+                if (updateBandPlan(box))
+                    next_bandplan = now() + BANDPLAN_INTERVAL;
+                else
+                    next_bandplan = nextWiFiRetry();
+            }
+            break;
+
+        case PLOT_CH_DXPEDS:
+            if (t0 >= next_dxpeds) {
+                if (updateDXPeds(box))
+                    next_dxpeds = nextRefresh (RS_DXPEDS);
+                else
+                    next_dxpeds = nextRetry (RS_DXPEDS);
+            }
+            break;
+
+        case PLOT_CH_PLUGINS:
+            if (t0 >= next_plugins) {
+                (void) updatePlugins(box);             // each plugin schedules its own fetch retries
+                next_plugins = now() + PLUGINS_INTERVAL;
+            }
+            break;
+
+        case PLOT_CH_QSORATE:
+            if (t0 >= next_qsorate) {
+                (void) updateQSORate(box);
+                next_qsorate = now() + QSORATE_INTERVAL;
+            }
+            break;
+
+        case PLOT_CH_AWARDS:
+            if (t0 >= next_awards) {
+                (void) updateAwards(box);
+                next_awards = now() + AWARDS_INTERVAL;
+            }
+            break;
+
+        case PLOT_CH_TXMETER:
+            if (t0 >= next_txmeter) {
+                bool all = t0 >= next_txmeter_all;
+                (void) updateTxMeter(box, all);
+                next_txmeter = now() + TXMETER_INTERVAL;
+                if (all)
+                    next_txmeter_all = now() + TXMETER_ALL;
+            }
+            break;
+
+        case PLOT_CH_SATSKY:
+            if (t0 >= next_satsky) {
+                (void) updateSatSky(box);
+                next_satsky = now() + SATSKY_INTERVAL;
+            }
+            break;
+
+        case PLOT_CH_ARISS:
+            if (t0 >= next_ariss) {
+                if (updateARISS(box))
+                    next_ariss = nextRefresh (RS_ARISS);
                 else
-                    next_stereo_a = nextWiFiRetry();
+                    next_ariss = nextRetry (RS_ARISS);
             }
             break;
 
@@ -1222,11 +2004,26 @@ void updateWiFi(void)
             showRotatingBorder (true, pp);
     }
 
+    // ISS events may be coming up whether or not the pane is showing
+    checkBGARISS (t0);
+    checkARISSAlerts();
+
+    // and Best Bands corrects its MUF from the nearest ionosonde whether or not that pane is showing
+    checkBGIono (t0);
+
+    // and aurora may reach DE whether or not Kp is showing
+    checkBGKp (t0);
+    checkAuroraAlert();
+    checkLocalK();
+    checkQSLUsers();
+    checkAwards();
+
     // check if time to update map
-    checkMap();
+    if (!offline)
+        checkMap();
 
     // freshen RSS
-    if (t0 >= next_rss) {
+    if (!offline && t0 >= next_rss) {
         if (updateRSS())
             next_rss = now() + RSS_INTERVAL;
         else
@@ -1373,7 +2170,11 @@ time_t getNTPUTC(const char **server)
  */
 bool getChar (WiFiClient &client, char *cp)
 {
-    #define GET_TO 5000 // millis()
+#if defined(_IS_UNIX)
+    uint32_t get_to = getNetReadTimeout();      // millis(), user may change
+#else
+    uint32_t get_to = 5000;                     // millis()
+#endif
 
     resetWatchdog();
 
@@ -1383,10 +2184,14 @@ bool getChar (WiFiClient &client, char *cp)
         resetWatchdog();
         if (!client.connected()) {
             // Serial.print (F("surprise getChar disconnect\n"));
+            if (fetch_header_ok && fetch_content_len >= 0 && fetch_body_n < fetch_content_len)
+                fetch_body_short = true;
             return (false);
         }
-        if (timesUp(&t0,GET_TO)) {
+        if (timesUp(&t0,get_to)) {
             Serial.print (F("surprise getChar timeout\n"));
+            if (fetch_header_ok)
+                fetch_body_short = true;
             return (false);
         }
         wdDelay(10);
@@ -1401,6 +2206,8 @@ bool getChar (WiFiClient &client, char *cp)
 
     // got one
     *cp = (char)c;
+    if (fetch_header_ok)
+        fetch_body_n++;
     return (true);
 }
 
@@ -1489,12 +2296,40 @@ void sendUserAgent (WiFiClient &client)
 /* issue an HTTP Get
  */
 void httpGET (WiFiClient &client, const char *server, const char *page)
+{
+    httpGET (client, server, page, NULL);
+}
+
+/* same but if vp is not NULL ask the server to send the page only if it no longer matches the given
+ * validators from a previous response. see httpSkipHeader() to learn whether it did.
+ */
+void httpGET (WiFiClient &client, const char *server, const char *page, const HTTPValidators *vp)
 {
     resetWatchdog();
 
+    // note for nextRetry() in case this fails
+    strncpy (fetch_err.page, page, sizeof(fetch_err.page)-1);
+    fetch_err.http_status = 0;
+    fetch_header_ok = false;
+    fetch_content_len = -1;
+    fetch_body_n = 0;
+    fetch_body_short = false;
+
     FWIFIPR (client, F("GET ")); client.print(page); FWIFIPRLN (client, F(" HTTP/1.0"));
     FWIFIPR (client, F("Host: ")); client.println (server);
     sendUserAgent (client);
+    if (vp && vp->etag[0]) {
+        FWIFIPR (client, F("If-None-Match: ")); client.println (vp->etag);
+    }
+    if (vp && vp->lastmod) {
+        // N.B. day and month names share one static buffer
+        char wday[10], date[50];
+        time_t t = vp->lastmod;
+        snprintf (wday, sizeof(wday), "%s", dayShortStr(weekday(t)));
+        snprintf (date, sizeof(date), _FX("%s, %02d %s %d %02d:%02d:%02d GMT"), wday, day(t),
+                                monthShortStr(month(t)), year(t), hour(t), minute(t), second(t));
+        FWIFIPR (client, F("If-Modified-Since: ")); client.println (date);
+    }
     FWIFIPRLN (client, F("Connection: close\r\n"));
 
     resetWatchdog();
@@ -1517,44 +2352,75 @@ static bool crackMonth (const char *name, int *monp)
 
 /* skip the given wifi client stream ahead to just after the first blank line, return whether ok.
  * this is often used so subsequent stop() on client doesn't slam door in client's face with RST.
- * Along the way, if lastmodp != NULL look for Last-Modified and set as a UNIX time, or 0 if not found.
+ * Along the way, set any Last-Modified or ETag found in v, leaving others unchanged, and set
+ * not_modified if the server answered a conditional httpGET() with 304 Not Modified.
  */
-bool httpSkipHeader (WiFiClient &client, uint32_t *lastmodp)
+bool httpSkipHeader (WiFiClient &client, HTTPValidators &v, bool &not_modified)
 {
     StackMalloc line_mem(150);
     char *line = line_mem.getMem();
+    bool first = true;
 
-    // assume no Last-Modified until found
-    if (lastmodp)
-        *lastmodp = 0;
+    not_modified = false;
 
     do {
         if (!getTCPLine (client, line, line_mem.getSize(), NULL))
             return (false);
         // Serial.println (line);
-        
+
+        // first line is status
+        int status;
+        if (first) {
+            if (sscanf (line, _FX("HTTP/%*s %d"), &status) == 1) {
+                fetch_err.http_status = status;
+                if (status == 304)
+                    not_modified = true;
+            }
+            first = false;
+        }
+
         // look for last-mod of the form: Last-Modified: Tue, 29 Sep 2020 22:55:02 GMT
-        if (lastmodp) {
-            char mstr[10];
-            int dy, mo, yr, hr, mn, sc;
-            if (sscanf (line, _FX("Last-Modified: %*[^,], %d %3s %d %d:%d:%d"), &dy, mstr, &yr, &hr, &mn, &sc)
+        char mstr[10];
+        int dy, mo, yr, hr, mn, sc;
+        if (sscanf (line, _FX("Last-Modified: %*[^,], %d %3s %d %d:%d:%d"), &dy, mstr, &yr, &hr, &mn, &sc)
                                                 == 6 && crackMonth (mstr, &mo)) {
-                tmElements_t tm;
-                tm.Year = yr - 1970;
-                tm.Month = mo;
-                tm.Day = dy;
-                tm.Hour = hr;
-                tm.Minute = mn;
-                tm.Second = sc;
-                *lastmodp = makeTime (tm);
-            }
+            tmElements_t tm;
+            tm.Year = yr - 1970;
+            tm.Month = mo;
+            tm.Day = dy;
+            tm.Hour = hr;
+            tm.Minute = mn;
+            tm.Second = sc;
+            v.lastmod = makeTime (tm);
         }
 
+        // look for an entity tag of the form: ETag: "abc-123" or W/"abc-123", useless if truncated
+        if (strncasecmp (line, _FX("ETag: "), 6) == 0 && strlen (line+6) < sizeof(v.etag))
+            strcpy (v.etag, line+6);
+
+        // note the body length, if given, so a body that ends early can be told from bad content
+        if (strncasecmp (line, _FX("Content-Length: "), 16) == 0)
+            fetch_content_len = atol (line+16);
+
     } while (line[0] != '\0');  // getTCPLine absorbs \r\n so this tests for a blank line
 
+    fetch_header_ok = true;
     return (true);
 }
 
+/* same but only pass back Last-Modified as a UNIX time if lastmodp != NULL, or 0 if not found.
+ */
+bool httpSkipHeader (WiFiClient &client, uint32_t *lastmodp)
+{
+    HTTPValidators v;
+    memset (&v, 0, sizeof(v));
+    bool not_modified;
+    bool ok = httpSkipHeader (client, v, not_modified);
+    if (lastmodp)
+        *lastmodp = v.lastmod;
+    return (ok);
+}
+
 /* same but when don't care about lastmod time
  */
 bool httpSkipHeader (WiFiClient &client)
@@ -1562,23 +2428,23 @@ bool httpSkipHeader (WiFiClient &client)
     return (httpSkipHeader (client, NULL));
 }
 
-/* retrieve and plot latest and predicted kp indices, return whether all ok
+// data are provided every 3 hours == 8/day. collect 7 days of history + 2 days of predictions
+#define NKPPD           8                       // number of values per day
+#define NHKPD           7                       // N historical days
+#define NPKPD           2                       // N predicted days
+#define NKP             ((NHKPD+NPKPD)*NKPPD)   // N total Kp values
+#define KP_NOW_I        (NHKPD*NKPPD-1)         // kp[] index of now, the last historic value
+
+/* retrieve latest and predicted kp indices into kp[NKP] and save the current value for getSpaceWeather().
+ * return whether all ok, else short reason in ynot.
  */
-static bool updateKp(SBox &box)
+static bool retrieveKp (float kp[NKP], const char *&ynot)
 {
-    // data are provided every 3 hours == 8/day. collect 7 days of history + 2 days of predictions
-    #define     NKPPD           8                       // number of values per day
-    #define     NHKPD           7                       // N historical days
-    #define     NPKPD           2                       // N predicted days
-    #define     NKP             ((NHKPD+NPKPD)*NKPPD)   // N total Kp values
-    StackMalloc kp_mem(NKP*sizeof(float));
-    float *kp = (float*)kp_mem.getMem();                // kp collection
-    uint8_t kp_i = 0;                                   // next kp index to use
     char line[100];                                     // text line
     WiFiClient kp_client;                               // wifi client connection
+    uint8_t kp_i = 0;                                   // next kp index to use
     bool ok = false;                                    // set iff all ok
 
-
     Serial.println(kp_page);
     resetWatchdog();
     if (wifiOk() && kp_client.connect(svr_host, HTTPPORT)) {
@@ -1590,51 +2456,145 @@ static bool updateKp(SBox &box)
 
         // skip response header
         if (!httpSkipHeader (kp_client)) {
-            plotMessage (box, KP_COLOR, _FX("Kp header short"));
+            ynot = _FX("Kp header short");
             goto out;
         }
 
-        // read lines into kp array and build x
-        StackMalloc kpx_mem(NKP*sizeof(float));
-        float *kpx = (float *) kpx_mem.getMem();
-        const int now_i = NHKPD*NKPPD-1;                        // last historic is now
-        for (kp_i = 0; kp_i < NKP && getTCPLine (kp_client, line, sizeof(line), NULL); kp_i++) {
+        // read lines into kp array
+        for (kp_i = 0; kp_i < NKP && getTCPLine (kp_client, line, sizeof(line), NULL); kp_i++)
             kp[kp_i] = atof(line);
-            kpx[kp_i] = (kp_i-now_i)/(float)NKPPD;
-            // Serial.printf ("%2d%c: kp[%5.3f] = %g from \"%s\"\n", kp_i, kp_i == now_i ? '*' : ' ', kpx[kp_i], kp[kp_i], line);
-        }
 
-        // plot if all
         if (kp_i == NKP) {
 
             // save current (not last!) value for getSpaceWeather()
-            kp_spw = kp[now_i];
-            kp_update = now();
-
-            // Kp value should be shown as int
-            char value_str[10];
-            snprintf (value_str, sizeof(value_str), "%d", (int)kp_spw);
-            plotXYstr (box, kpx, kp, NKP, _FX("Days"), _FX("Planetary Kp"), KP_COLOR, 0, 9, value_str);
+            kp_spw = backend_kp = kp[KP_NOW_I];
+            kp_update = backend_kp_t = now();
+            recordSWxSample (SWXH_KP, kp_spw);
+
+            // run script when the whole Kp index changes, not on first reading
+            static int prev_kp = -1;
+            if ((int)kp_spw != prev_kp) {
+                if (prev_kp >= 0) {
+                    char new_str[10], old_str[10];
+                    snprintf (new_str, sizeof(new_str), "%d", (int)kp_spw);
+                    snprintf (old_str, sizeof(old_str), "%d", prev_kp);
+                    runEventScript ("on_kp_change", new_str, old_str, NULL);
+                }
+                prev_kp = (int)kp_spw;
+            }
 
             ok = true;
 
         } else {
             Serial.printf (_FX("Kp read only %d of %d\n"), kp_i, NKP);
-            plotMessage (box, KP_COLOR, _FX("Kp data short"));
+            ynot = _FX("Kp data short");
         }
 
     } else {
-        plotMessage (box, KP_COLOR, _FX("Kp connection failed"));
+        ynot = _FX("Kp connection failed");
     }
 
     // clean up
 out:
     kp_client.stop();
     resetWatchdog();
+    return (ok);
+}
+
+/* retrieve and plot latest and predicted kp indices, return whether all ok
+ */
+static bool updateKp(SBox &box)
+{
+    StackMalloc kp_mem(NKP*sizeof(float));
+    float *kp = (float*)kp_mem.getMem();                // kp collection
+    const char *ynot;
+
+    bool ok = retrieveKp (kp, ynot);
+    if (ok) {
+
+        // a simulation replaces the current value
+        (void) getSimSpaceWeather ("kp", kp[KP_NOW_I]);
+
+        // x is days from now
+        StackMalloc kpx_mem(NKP*sizeof(float));
+        float *kpx = (float *) kpx_mem.getMem();
+        for (int i = 0; i < NKP; i++)
+            kpx[i] = (i-KP_NOW_I)/(float)NKPPD;
+
+        // Kp value should be shown as int
+        char value_str[10];
+        snprintf (value_str, sizeof(value_str), "%d", (int)kp[KP_NOW_I]);
+        // add local K to the title if we have a fresh one
+        char title[40];
+        float local_k;
+        time_t local_age;
+        const char *station;
+        if (getLocalK (local_k, local_age, &station))
+            snprintf (title, sizeof(title), xlate ("Planetary Kp, Local %d"), (int)local_k);
+        else
+            snprintf (title, sizeof(title), "%s", xlate ("Planetary Kp"));
+        plotXYstr (box, kpx, kp, NKP, xlate ("Days"), title, KP_COLOR, 0, 9, value_str);
+        plotTrend (box, (int)kp[KP_NOW_I] - (int)kp[KP_NOW_I-1], 0);
+
+    } else {
+        plotMessage (box, KP_COLOR, ynot);
+    }
+
     printFreeHeap (F("updateKp"));
     return (ok);
 }
 
+/* keep Kp fresh for the aurora alert while it is enabled even if no pane is showing Kp.
+ */
+static void checkBGKp (time_t t0)
+{
+    if (offline || !getAuroraAlert() || findPaneChoiceNow (PLOT_CH_KP) != PANE_NONE || t0 < next_kp)
+        return;
+
+    StackMalloc kp_mem(NKP*sizeof(float));
+    float *kp = (float*)kp_mem.getMem();
+    const char *ynot;
+    if (retrieveKp (kp, ynot))
+        next_kp = nextRefresh (RS_KP);
+    else {
+        Serial.printf (_FX("Kp: %s\n"), ynot);
+        next_kp = nextRetry (RS_KP);
+    }
+}
+
+/* keep the ISS events fresh for their alerts even if no pane is showing them.
+ */
+static void checkBGARISS (time_t t0)
+{
+    if (findPaneChoiceNow (PLOT_CH_ARISS) != PANE_NONE || t0 < next_ariss)
+        return;
+
+    const char *ynot;
+    if (retrieveARISS (ynot))
+        next_ariss = nextRefresh (RS_ARISS);
+    else {
+        Serial.printf (_FX("ARISS: %s\n"), ynot);
+        next_ariss = nextRetry (RS_ARISS);
+    }
+}
+
+/* keep the ionosonde correction fresh for the Best Bands pane even if no pane is showing the ionosonde.
+ */
+static void checkBGIono (time_t t0)
+{
+    if (offline || findPaneChoiceNow (PLOT_CH_BANDREC) == PANE_NONE
+                || findPaneChoiceNow (PLOT_CH_IONOSONDE) != PANE_NONE || t0 < next_iono)
+        return;
+
+    const char *ynot;
+    if (refreshIonosonde (ynot))
+        next_iono = nextRefresh (RS_IONO);
+    else {
+        Serial.printf (_FX("Ionosonde: %s\n"), ynot);
+        next_iono = nextRetry (RS_IONO);
+    }
+}
+
 /* given a GOES XRAY Flux value, return its event level designation in buf.
  */
 static char *xrayLevel (float xray, char *buf)
@@ -1714,7 +2674,7 @@ static bool updateXRay(const SBox &box)
                 x[i] = (i-NXRAY)/6.0;           // 6 entries per hour
 
             // overlay short over long
-            ok = plotXYstr (box, x, lxray, NXRAY, _FX("Hours"), _FX("GOES 16 X-Ray"), XRAY_LCOLOR,
+            ok = plotXYstr (box, x, lxray, NXRAY, xlate ("Hours"), xlate ("GOES 16 X-Ray"), XRAY_LCOLOR,
                                 -9, -2, xrayLevel(current_xray, line))
                  && plotXY (box, x, sxray, NXRAY, NULL, NULL, XRAY_SCOLOR, -9, -2, 0.0);
 
@@ -1722,6 +2682,7 @@ static bool updateXRay(const SBox &box)
                 // capture
                 xray_spw = current_xray;
                 xray_update = now();
+                recordSWxSample (SWXH_XRAY, xray_spw);
             }
 
         } else {
@@ -1781,12 +2742,16 @@ static bool updateSunSpots (const SBox &box)
         updateClocks(false);
         resetWatchdog();
         if (ssn_i == NSUNSPOT) {
-            ok = plotXY (box, x, sspot, NSUNSPOT, _FX("Days"), _FX("Sunspot Number"),
+            ok = plotXY (box, x, sspot, NSUNSPOT, xlate ("Days"), xlate ("Sunspot Number"),
                                         SSPOT_COLOR, 0, -1, sspot[NSUNSPOT-1]);
             if (ok) {
+                // show change from yesterday
+                plotTrend (box, sspot[NSUNSPOT-1] - sspot[NSUNSPOT-2], 1);
+
                 // capture
-                ssn_spw = sspot[NSUNSPOT-1];
-                ssn_update = now();
+                ssn_spw = backend_ssn = sspot[NSUNSPOT-1];
+                ssn_update = backend_ssn_t = now();
+                recordSWxSample (SWXH_SSN, ssn_spw);
             }
         }
 
@@ -1845,10 +2810,15 @@ static bool updateSolarFlux(const SBox &box)
         updateClocks(false);
         resetWatchdog();
         if (flux_i == NSFLUX) {
-            if (plotXY (box, x, flux, NSFLUX, _FX("Days"), _FX("10.7 cm Solar flux"),
+            // save the fetched value but show any from a simulation instead
+            float fetched_flux = flux[NSFLUX-10];
+            (void) getSimSpaceWeather ("sfi", flux[NSFLUX-10]);
+            if (plotXY (box, x, flux, NSFLUX, xlate ("Days"), xlate ("10.7 cm Solar flux"),
                                                 FLUX_COLOR, 0, 0, flux[NSFLUX-10])) {
-                flux_spw = flux[NSFLUX-10];
-                flux_update = now();
+                plotTrend (box, flux[NSFLUX-10] - flux[NSFLUX-13], 1);      // change from yesterday
+                flux_spw = backend_sfi = fetched_flux;
+                flux_update = backend_sfi_t = now();
+                recordSWxSample (SWXH_SFI, flux_spw);
                 ok = true;
             }
         } else
@@ -1940,9 +2910,11 @@ static bool updateSolarWind(const SBox &box)
         updateClocks(false);
         resetWatchdog();
         if (nsw >= 10) {
-            if (plotXY (box, x, y, nsw, _FX("Hours"), _FX("Solar wind"), SWIND_COLOR, 0, 0, y[nsw-1])) {
+            if (plotXY (box, x, y, nsw, xlate ("Hours"), xlate ("Solar wind"), SWIND_COLOR, 0, 0, y[nsw-1])) {
+                plotTrend (box, y[nsw-1] - y[nsw-2], 1);                    // change from previous interval
                 swind_spw = y[nsw-1];
                 swind_update = t0;
+                recordSWxSample (SWXH_SWIND, swind_spw);
                 ok = true;
             }
         } else
@@ -2048,9 +3020,10 @@ static bool updateDRAPPlot(const SBox &box)
             Serial.printf (_FX("DRAP found %d of %d, newest %g is %u s old\n"),
                         ndrap, NPLOTDRAP, max, t0 - unixs);
 
-            if (plotXY (box,x,y,ndrap,_FX("Hours"),_FX("DRAP, max MHz"),DRAPPLOT_COLOR,0,0,y[ndrap-1])) {
+            if (plotXY (box,x,y,ndrap,xlate("Hours"),xlate("DRAP, max MHz"),DRAPPLOT_COLOR,0,0,y[ndrap-1])) {
                 drap_spw = y[ndrap-1];
                 drap_update = t0;
+                recordSWxSample (SWXH_DRAP, drap_spw);
                 ok = true;
             }
         } else {
@@ -2201,19 +3174,41 @@ out:
  */
 static bool updateSDO (const SBox &box, PlotChoice ch)
 {
-    // choose file
-    const char *sdo_fn;
+    // choose image
+    int sdo_i;
     switch (ch) {
-    case PLOT_CH_SDO_1: sdo_fn = sdo_filename[0]; break;
-    case PLOT_CH_SDO_2: sdo_fn = sdo_filename[1]; break;
-    case PLOT_CH_SDO_3: sdo_fn = sdo_filename[2]; break;
-    case PLOT_CH_SDO_4: sdo_fn = sdo_filename[3]; break;
+    case PLOT_CH_SDO_1: sdo_i = 0; break;
+    case PLOT_CH_SDO_2: sdo_i = 1; break;
+    case PLOT_CH_SDO_3: sdo_i = 2; break;
+    case PLOT_CH_SDO_4: sdo_i = 3; break;
+    case PLOT_CH_SDO_5: sdo_i = 4; break;
+    case PLOT_CH_SDO_CYCLE: sdo_i = sdo_cycle_i; break;
     default:
         fatalError (_FX("Bug! updateSDO() bad choice: %d"), (int)ch);
         return (false);
     }
 
-    bool ok = drawHTTPBMP (sdo_fn, box, SDO_COLOR);
+    uint32_t lastmod;
+    bool ok = drawHTTPBMP (sdo_filename[sdo_i], box, SDO_COLOR, &lastmod, SDO_CACHE_TTL);
+
+    if (ok) {
+        // caption with image name and time along the bottom
+        selectFontStyle (LIGHT_FONT, FAST_FONT);
+        tft.setTextColor (RA8875_WHITE);
+        uint16_t y = box.y + box.h - 10;
+        tft.setCursor (box.x + 3, y);
+        tft.print (sdo_label[sdo_i]);
+        if (lastmod) {
+            char buf[10];
+            snprintf (buf, sizeof(buf), "%02d:%02dZ", hour(lastmod), minute(lastmod));
+            tft.setCursor (box.x + box.w - getTextWidth(buf) - 3, y);
+            tft.print (buf);
+        }
+
+        // advance cycle only after success so a failure retries the same image
+        if (ch == PLOT_CH_SDO_CYCLE)
+            sdo_cycle_i = (sdo_cycle_i + 1) % SDO_N;
+    }
 
     printFreeHeap(F("updateSDO"));
     return (ok);
@@ -2306,8 +3301,12 @@ bool getTCPLine (WiFiClient &client, char line[], uint16_t line_len, uint16_t *l
     uint16_t i = 0;
     while (true) {
         char c;
-        if (!getChar (client, &c))
+        if (!getChar (client, &c)) {
+            // a partial line after the header means the body was cut off
+            if (i > 0 && fetch_header_ok)
+                fetch_body_short = true;
             return (false);
+        }
         if (c == '\r')
             continue;
         if (c == '\n') {
@@ -2401,6 +3400,7 @@ static bool updateRSS ()
                     goto out;
                 if (titles[n_titles])
                     free (titles[n_titles]);
+                foldUTF8 (line);
                 titles[n_titles] = strdup (line);
                 // Serial.printf (_FX("RSS[%d] len= %d\n"), n_titles, strlen(titles[n_titles]));
             }
@@ -2472,6 +3472,90 @@ static bool updateRSS ()
     return (true);
 }
 
+/* retrieve the latest >= 10 MeV integral proton flux, pfu, for the S scale.
+ * expecting 1 line with just the value.
+ * return whether all ok.
+ */
+static bool updateProtons()
+{
+    WiFiClient protons_client;
+    char line[50];
+    bool ok = false;
+
+    Serial.println(protons_page);
+    resetWatchdog();
+    if (wifiOk() && protons_client.connect(svr_host, HTTPPORT)) {
+        resetWatchdog();
+        httpGET (protons_client, svr_host, protons_page);
+        if (httpSkipHeader (protons_client) && getTCPLine (protons_client, line, sizeof(line), NULL)) {
+            char *endp;
+            float pfu = strtod (line, &endp);
+            if (endp != line && pfu >= 0) {
+                protons_spw = pfu;
+                protons_update = now();
+                ok = true;
+            } else
+                Serial.printf (_FX("Protons: bad line: %s\n"), line);
+        } else
+            Serial.println (F("Protons: no data"));
+    }
+
+    protons_client.stop();
+    return (ok);
+}
+
+/* return NOAA G scale for the given Kp index
+ */
+static int kp2GScale (float kp)
+{
+    int g = (int)floorf(kp) - 4;
+    return (g < 0 ? 0 : (g > 5 ? 5 : g));
+}
+
+/* return NOAA S scale for the given >= 10 MeV proton flux, pfu
+ */
+static int protons2SScale (float pfu)
+{
+    int s = pfu < 10 ? 0 : (int)floorf(log10f(pfu));
+    return (s > 5 ? 5 : s);
+}
+
+/* return NOAA R scale for the given 0.1-0.8 nm X-ray flux, W/m^2
+ */
+static int xray2RScale (float xray)
+{
+    if (xray >= 2e-3F) return (5);              // X20
+    if (xray >= 1e-3F) return (4);              // X10
+    if (xray >= 1e-4F) return (3);              // X1
+    if (xray >= 5e-5F) return (2);              // M5
+    if (xray >= 1e-5F) return (1);              // M1
+    return (0);
+}
+
+/* set the current NOAA scales from any of our own raw indices that are fresh enough,
+ * else leave those from the server.
+ */
+static void setCurrentScales()
+{
+    time_t t0 = now();
+    for (int i = 0; i < N_NOAASW_C; i++) {
+        switch (noaa_spw.cat[i]) {
+        case 'R':
+            if (xray_update && t0 - xray_update < SCALE_MAXAGE)
+                noaa_spw.val[i][0] = xray2RScale (xray_spw);
+            break;
+        case 'S':
+            if (protons_update && t0 - protons_update < SCALE_MAXAGE)
+                noaa_spw.val[i][0] = protons2SScale (protons_spw);
+            break;
+        case 'G':
+            if (kp_update && t0 - kp_update < SCALE_MAXAGE)
+                noaa_spw.val[i][0] = kp2GScale (kp_spw);
+            break;
+        }
+    }
+}
+
 /* display the RSG NOAA solar environment scale values.
  */
 static bool updateNOAASWx(const SBox &box)
@@ -2520,8 +3604,10 @@ static bool updateNOAASWx(const SBox &box)
                 }
             }
 
-            // all ok, display
+            // all ok, freshen current values from our own indices then display
             noaa_update = now();
+            (void) updateProtons();
+            setCurrentScales();
             plotNOAASWx (box, noaa_spw);
             ok = true;
 
@@ -2584,6 +3670,8 @@ void initWiFiRetry()
     next_sdo_2 = 0;
     next_sdo_3 = 0;
     next_sdo_4 = 0;
+    next_sdo_5 = 0;
+    next_sdo_cycle = 0;
     next_noaaswx = 0;
     next_dewx = 0;
     next_dxwx = 0;
@@ -2598,6 +3686,24 @@ void initWiFiRetry()
     next_swind = 0;
     next_drap = 0;
     next_stereo_a = 0;
+    next_outlook = 0;
+    next_iono = 0;
+    next_flares = 0;
+    next_cme = 0;
+    next_bandrec = 0;
+    next_eme = 0;
+    next_ncdxf = 0;
+    next_bandplan = 0;
+    next_dxpeds = 0;
+    next_plugins = 0;
+    next_qsorate = 0;
+    next_awards = 0;
+    next_txmeter = next_txmeter_all = 0;
+    next_satsky = 0;
+    next_ariss = 0;
+
+    // user may have changed intervals
+    loadRefreshIntervals();
 
     // map is in memory
     // next_map = 0;
@@ -2613,6 +3719,26 @@ void newBC()
         next_bc = 0;
 }
 
+/* called to schedule an update to the ionosonde pane, or its background refresh, eg, because DE moved.
+ * if on PANE_1 it will be updated anyway when it reverts from showing DE weather.
+ */
+void newIonosonde()
+{
+    PlotPane io_pp = findPaneChoiceNow (PLOT_CH_IONOSONDE);
+    if (io_pp != PANE_1)
+        next_iono = 0;
+}
+
+/* called to schedule an update to the EME planner pane if up, eg, because DE or DX moved.
+ * if on PANE_1 it will be updated anyway when it reverts from showing DE or DX weather.
+ */
+void newEME()
+{
+    PlotPane eme_pp = findPaneChoiceNow (PLOT_CH_EME);
+    if (eme_pp != PANE_NONE && eme_pp != PANE_1)
+        next_eme = 0;
+}
+
 /* called to schedule an immediate update of the given VOACAP map, unless being turned off.
  */
 void newVOACAPMap(PropMapSetting pm)
@@ -2662,26 +3788,30 @@ void showDXWX()
 }
 
 /* return most recent space weather info and its age. values never read will be zero and ancient.
- * most are value+age but xray is a string and pathrel is an array of PROP_MAP_N.
  */
-void getSpaceWeather (SPWxValue &ssn, SPWxValue &flux, SPWxValue &kp, SPWxValue &swind, SPWxValue &drap,
-NOAASpaceWx &noaaspw, time_t &noaaspw_age, char xray[], time_t &xray_age,
-float pathrel[PROP_MAP_N], time_t &pathrel_age)
+void getSpaceWeather (SpaceWeather &spw)
 {
     // time now for ages
     time_t t0 = now();
 
     // these are easy scalars
-    ssn.value = ssn_spw;
-    ssn.age = t0 - ssn_update;
-    flux.value = flux_spw;
-    flux.age = t0 - flux_update;
-    kp.value = kp_spw;
-    kp.age = t0 - kp_update;
-    swind.value = swind_spw;
-    swind.age = t0 - swind_update;
-    drap.value = drap_spw;
-    drap.age = t0 - drap_update;
+    spw.ssn.value = ssn_spw;
+    spw.ssn.age = t0 - ssn_update;
+    spw.flux.value = flux_spw;
+    spw.flux.age = t0 - flux_update;
+    spw.kp.value = kp_spw;
+    spw.kp.age = t0 - kp_update;
+    spw.swind.value = swind_spw;
+    spw.swind.age = t0 - swind_update;
+    spw.drap.value = drap_spw;
+    spw.drap.age = t0 - drap_update;
+    spw.protons.value = protons_spw;
+    spw.protons.age = t0 - protons_update;
+
+    // xray both as flux and class
+    spw.xray.value = xray_spw;
+    spw.xray.age = t0 - xray_update;
+    (void) xrayLevel (xray_spw, spw.xray_class);  // handles 0 ok
 
     // easy struct but beware never set yet
     if (!noaa_spw.cat[0]) {
@@ -2689,17 +3819,113 @@ float pathrel[PROP_MAP_N], time_t &pathrel_age)
         noaa_spw.cat[1] = 'S';
         noaa_spw.cat[2] = 'G';
     }
-    noaaspw = noaa_spw;
-    noaaspw_age = t0 - noaa_update;
-
-    // xray is a string
-    (void) xrayLevel (xray_spw, xray);  // handles 0 ok
-    xray_age = t0 - xray_update;
+    spw.noaaspw = noaa_spw;
+    spw.noaaspw_age = t0 - noaa_update;
 
     // VOACAP path reliability is an array
     for (int i = 0; i < PROP_MAP_N; i++)
-        pathrel[i] = path_spw[i];
-    pathrel_age = t0 - path_update;
+        spw.pathrel[i] = path_spw[i];
+    spw.pathrel_age = t0 - path_update;
+
+    // flares and CMEs from their panes
+    strcpy (spw.flare_class, flare_spw);
+    spw.flare_peak = flare_peak;
+    spw.flares_age = t0 - flares_update;
+    spw.cme_arrival = cme_arrival;
+    spw.cme_speed = cme_speed;
+    spw.cmes_age = t0 - cmes_update;
+
+    // DX cluster
+    spw.cluster = cluster_spw;
+
+    // scenario values take precedence
+    applySimSpaceWeather (spw);
+}
+
+/* note the largest flare found when the flares pane last refreshed, "" if none, for getSpaceWeather()
+ */
+void noteFlares (const char *largest, time_t peak)
+{
+    strncpy (flare_spw, largest, sizeof(flare_spw)-1);
+    flare_spw[sizeof(flare_spw)-1] = '\0';
+    flare_peak = peak;
+    flares_update = now();
+}
+
+/* note the soonest CME arrival found when the CME pane last refreshed, 0 if none, for getSpaceWeather()
+ */
+void noteCMEs (time_t arrival, int speed)
+{
+    cme_arrival = arrival;
+    cme_speed = speed;
+    cmes_update = now();
+}
+
+/* use space weather announced by the DX cluster for any value the backend has not refreshed lately,
+ * such as when its pane is not shown or the backend can not be reached, and check it agrees with any recent
+ * value fetched from the backend.
+ */
+void noteClusterSpaceWeather (const ClusterSpaceWx &cspw)
+{
+    time_t t0 = now();
+
+    cluster_spw = cspw;
+    cluster_spw.t = t0;
+    cluster_spw.differs = false;
+
+    // use cluster values while the backend values are stale, but only ever check against the backend
+    if (cspw.sfi >= 0) {
+        if (t0 - flux_update > refresh_srcs[RS_FLUX].secs) {
+            flux_spw = cspw.sfi;
+            flux_update = t0;
+        }
+        if (t0 - backend_sfi_t < BACKEND_CHECK_AGE && fabsf (cspw.sfi - backend_sfi) > fmaxf (10, 0.1F*backend_sfi)) {
+            Serial.printf (_FX("DXC: %s SFI %g differs from backend %g\n"), cspw.node, cspw.sfi, backend_sfi);
+            cluster_spw.differs = true;
+        }
+    }
+    if (cspw.k >= 0) {
+        if (t0 - kp_update > refresh_srcs[RS_KP].secs) {
+            kp_spw = cspw.k;
+            kp_update = t0;
+        }
+        if (t0 - backend_kp_t < BACKEND_CHECK_AGE && fabsf (cspw.k - backend_kp) > 1) {
+            Serial.printf (_FX("DXC: %s K %g differs from backend Kp %g\n"), cspw.node, cspw.k, backend_kp);
+            cluster_spw.differs = true;
+        }
+    }
+    if (cspw.ssn >= 0) {
+        if (t0 - ssn_update > refresh_srcs[RS_SSN].secs) {
+            ssn_spw = cspw.ssn;
+            ssn_update = t0;
+        }
+        if (t0 - backend_ssn_t < BACKEND_CHECK_AGE && fabsf (cspw.ssn - backend_ssn) > fmaxf (15, 0.2F*backend_ssn)) {
+            Serial.printf (_FX("DXC: %s R %g differs from backend SSN %g\n"), cspw.node, cspw.ssn, backend_ssn);
+            cluster_spw.differs = true;
+        }
+    }
+}
+
+/* return a short name for the given kind of fetch failure
+ */
+const char *fetchErrorName (FetchErrorKind kind)
+{
+    switch (kind) {
+    case FE_NONE:       return ("none");
+    case FE_CONNECT:    return ("connect");
+    case FE_HTTP:       return ("http");
+    case FE_HEADER:     return ("header");
+    case FE_SHORT:      return ("short");
+    case FE_DATA:       return ("data");
+    }
+    return ("?");
+}
+
+/* note the reason a pane gave for failing to refresh, for nextRetry()
+ */
+void noteFetchDetail (const char *detail)
+{
+    strncpy (fetch_err.detail, detail, sizeof(fetch_err.detail)-1);
 }
 
 /* return current NTP response time list.
@@ -2710,3 +3936,12 @@ int getNTPServers (const NTPServer **listp)
     *listp = ntp_list;
     return (N_NTP);
 }
+
+/* return current data source refresh list.
+ * N.B. this is the real data, caller must not modify.
+ */
+int getRefreshSources (const RefreshSource **listp)
+{
+    *listp = refresh_srcs;
+    return (RS_N);
+}
@@ -55,6 +55,7 @@ bool getCurrentWX (const LatLong &ll, bool is_de, WXInfo *wip, char ynot[])
 
             // check for content line
             if (strncmp_P (line, PSTR("city="), 5) == 0) {
+                foldUTF8 (vstart);
                 strncpy (wip->city, vstart, sizeof(wip->city)-1);
                 n_found++;
             } else if (strncmp_P (line, PSTR("temperature_c="), 14) == 0) {